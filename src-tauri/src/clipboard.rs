@@ -0,0 +1,168 @@
+//! Verse references on the clipboard, for quick lookup: copy "Rom 12:1-2"
+//! from an email, press the lookup shortcut (or ask from the app), and the
+//! reader opens the passage.
+//!
+//! The clipboard is read only when asked, never watched. Its text is parsed
+//! with the user's book aliases (`bookAliases` in preferences) and the first
+//! reference is emitted as `clipboard://reference` for the reader to go to.
+//! Longer text is left alone: a reference is short, and a copied paragraph
+//! isn't a lookup.
+
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use tauri::{command, Emitter};
+
+use crate::content::references::{self, Aliases, ScriptureReference};
+use crate::db::{self, DbError};
+
+/// Emitted with the `ScriptureReference` found on the clipboard.
+const REFERENCE_EVENT: &str = "clipboard://reference";
+
+/// Most characters of clipboard text read for a reference.
+const MAX_TEXT: usize = 200;
+
+/// The first reference in `text`, if it's short enough to be a lookup.
+/// Quotes, brackets and closing punctuation around it are ignored.
+pub(crate) fn detect(text: &str, aliases: &Aliases) -> Option<ScriptureReference> {
+    let text = text
+        .trim()
+        .trim_start_matches(['(', '[', '"', '“', '\''])
+        .trim_end_matches([')', ']', '"', '”', '\'', '.', '!', '?']);
+    if text.is_empty() || text.chars().count() > MAX_TEXT {
+        return None;
+    }
+    references::parse_with(text, aliases).into_iter().next()
+}
+
+/// The book aliases saved in preferences.
+fn book_aliases(conn: &Connection) -> Result<HashMap<String, String>, DbError> {
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT data FROM preferences WHERE id = 'main'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(data
+        .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+        .and_then(|prefs| serde_json::from_value(prefs.get("bookAliases")?.clone()).ok())
+        .unwrap_or_default())
+}
+
+#[cfg(desktop)]
+fn clipboard_text(app: &tauri::AppHandle) -> Result<String, DbError> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+    // An empty clipboard, or one holding an image, has no text to read.
+    Ok(app.clipboard().read_text().unwrap_or_default())
+}
+
+#[cfg(not(desktop))]
+fn clipboard_text(_app: &tauri::AppHandle) -> Result<String, DbError> {
+    Err(DbError::invalid("Clipboard lookup is only on desktop"))
+}
+
+/// The reference on the clipboard, emitted as `clipboard://reference`.
+async fn lookup(app: &tauri::AppHandle) -> Result<Option<ScriptureReference>, DbError> {
+    let text = clipboard_text(app)?;
+    // Without preferences (or a database yet), the usual names still read.
+    let aliases = db::with_reader(app, book_aliases).await.unwrap_or_default();
+    let found = detect(&text, &references::aliases(&aliases));
+    if let Some(reference) = &found {
+        let _ = app.emit(REFERENCE_EVENT, reference);
+    }
+    Ok(found)
+}
+
+/// Look for a verse reference on the clipboard. One found is returned and
+/// also emitted as `clipboard://reference`, as the lookup shortcut does.
+#[command]
+pub async fn lookup_clipboard_reference(
+    app: tauri::AppHandle,
+) -> Result<Option<ScriptureReference>, DbError> {
+    lookup(&app).await
+}
+
+/// Make `shortcut` (e.g. "CmdOrCtrl+Shift+L") the system-wide lookup
+/// shortcut, in place of any before it; `None` turns it off.
+#[command]
+pub fn set_clipboard_shortcut(
+    app: tauri::AppHandle,
+    shortcut: Option<String>,
+) -> Result<(), DbError> {
+    set_shortcut(
+        &app,
+        shortcut.as_deref().map(str::trim).filter(|s| !s.is_empty()),
+    )
+}
+
+#[cfg(desktop)]
+fn set_shortcut(app: &tauri::AppHandle, shortcut: Option<&str>) -> Result<(), DbError> {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+    let shortcuts = app.global_shortcut();
+    shortcuts
+        .unregister_all()
+        .map_err(|e| DbError::invalid(format!("Couldn't clear the lookup shortcut: {e}")))?;
+    if let Some(shortcut) = shortcut {
+        shortcuts.register(shortcut).map_err(|e| {
+            DbError::invalid(format!("`{shortcut}` can't be the lookup shortcut: {e}"))
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(not(desktop))]
+fn set_shortcut(_app: &tauri::AppHandle, _shortcut: Option<&str>) -> Result<(), DbError> {
+    Err(DbError::invalid("Clipboard lookup is only on desktop"))
+}
+
+/// The lookup shortcut's handler: bring the app forward and look.
+#[cfg(desktop)]
+pub(crate) fn on_shortcut(
+    app: &tauri::AppHandle,
+    _shortcut: &tauri_plugin_global_shortcut::Shortcut,
+    event: tauri_plugin_global_shortcut::ShortcutEvent,
+) {
+    use tauri::Manager;
+    if event.state != tauri_plugin_global_shortcut::ShortcutState::Pressed {
+        return;
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = lookup(&app).await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_short_references_only() {
+        let osis = |text: &str| detect(text, &[]).map(|r| r.osis);
+        assert_eq!(osis("Rom 12:1-2").as_deref(), Some("Rom.12.1-Rom.12.2"));
+        assert_eq!(osis("  (Jn 3:16).\n").as_deref(), Some("John.3.16"));
+        assert_eq!(osis("“Ps 23”").as_deref(), Some("Ps.23"));
+        assert_eq!(osis("Juan 3:16; Rom 8").as_deref(), Some("John.3.16"));
+        assert_eq!(osis("Thanks for lunch!"), None);
+        assert_eq!(osis(""), None);
+        assert_eq!(osis(&format!("Gen 1; {}", "Gen 2; ".repeat(40))), None);
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE preferences (id TEXT PRIMARY KEY, data TEXT);
+               INSERT INTO preferences VALUES
+                 ('main', '{"bookAliases": {"Gospel of John": "John"}}');"#,
+        )
+        .unwrap();
+        let aliases = references::aliases(&book_aliases(&conn).unwrap());
+        assert_eq!(
+            detect("Gospel of John 1:1", &aliases).map(|r| r.osis),
+            Some("John.1.1".to_string())
+        );
+    }
+}
@@ -0,0 +1,559 @@
+//! Audio Bibles: recordings of a translation, one audio file per chapter.
+//!
+//! A manifest (JSON) names the recording and lists each chapter's file or
+//! URL, and optionally when each of its verses starts:
+//!
+//! ```json
+//! { "id": "web-audio", "name": "WEB Audio", "moduleId": "WEB",
+//!   "baseUrl": "https://example.org/web/",
+//!   "chapters": [{ "book": "JHN", "chapter": 1, "url": "JHN_01.mp3",
+//!                  "duration": 312.5, "verses": [0, 6.2, 11.8] }] }
+//! ```
+//!
+//! `verses` gives verse 1, 2, ... in order, or `{ "verse": 4, "start": 20.1 }`
+//! objects where verses are missing or bridged. Relative `url`s resolve
+//! against `baseUrl`, or without one against the manifest's folder.
+//! `import_audio_manifest` checks the manifest and keeps a normalized copy
+//! under `<app data>/content/audio`, which `get_chapter_audio` reads the
+//! reader's chapter from.
+//!
+//! Where the listener is in each recording is kept in the user database's
+//! `audio_positions` table, a synced data table like the observation ones,
+//! so listening picks up on the account's other devices where it stopped,
+//! alongside the synced reading position.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{command, Manager};
+
+use super::{books, import, slug, CONTENT_DIR};
+use crate::db::{self, DbError, DbErrorKind};
+
+/// Directory (in the content directory) holding one `<id>.json` per audio
+/// Bible.
+const AUDIO_DIR: &str = "audio";
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    id: Option<String>,
+    name: String,
+    #[serde(rename = "moduleId")]
+    module_id: Option<String>,
+    #[serde(rename = "baseUrl")]
+    base_url: Option<String>,
+    chapters: Vec<ManifestChapter>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestChapter {
+    book: String,
+    chapter: i64,
+    url: String,
+    duration: Option<f64>,
+    #[serde(default)]
+    verses: Vec<Timing>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Timing {
+    /// Start of the verse after the previous one.
+    Start(f64),
+    Verse {
+        verse: i64,
+        start: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerseTiming {
+    pub verse: i64,
+    /// Seconds into the chapter's audio.
+    pub start: f64,
+    /// Start of the next verse, or the end of the audio; `None` for the last
+    /// verse when the manifest gives no duration.
+    pub end: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChapterAudio {
+    /// OSIS book id.
+    pub book: String,
+    pub chapter: i64,
+    /// `http(s)` URL, or the path of a local file.
+    pub url: String,
+    /// Length in seconds, when the manifest gives it.
+    pub duration: Option<f64>,
+    /// Empty when the manifest has no verse timing.
+    pub verses: Vec<VerseTiming>,
+}
+
+/// An imported audio Bible, as stored.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AudioBible {
+    pub id: String,
+    pub name: String,
+    /// Translation whose text the recording reads, if the manifest says.
+    #[serde(rename = "moduleId")]
+    pub module_id: Option<String>,
+    pub chapters: Vec<ChapterAudio>,
+}
+
+/// An installed audio Bible, without its chapters.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AudioBibleInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "moduleId")]
+    pub module_id: Option<String>,
+    pub chapters: usize,
+    /// OSIS ids of the books it has audio for, in canonical order.
+    pub books: Vec<String>,
+    pub path: String,
+}
+
+/// Where the listener stopped in a recording (`audio_positions.data`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PlaybackPosition {
+    #[serde(rename = "audioId")]
+    pub audio_id: String,
+    pub book: String,
+    pub chapter: i64,
+    /// The verse being read at `seconds`; filled in from the verse timing
+    /// when saved without one.
+    #[serde(default)]
+    pub verse: Option<i64>,
+    /// Seconds into the chapter's audio.
+    pub seconds: f64,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: Option<String>,
+}
+
+fn audio_dir(app: &tauri::AppHandle) -> Result<PathBuf, DbError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| DbError::io(format!("Cannot determine app data dir: {e}")))?;
+    Ok(dir.join(CONTENT_DIR).join(AUDIO_DIR))
+}
+
+/// `url` as something a player can open: absolute URLs as they are,
+/// relative ones against `base` or, without one, `dir`.
+fn resolve_url(url: &str, base: Option<&reqwest::Url>, dir: &Path) -> Result<String, DbError> {
+    // A one-letter scheme is a Windows drive, not a URL.
+    if let Ok(absolute) = reqwest::Url::parse(url) {
+        if absolute.scheme().len() > 1 {
+            return Ok(absolute.to_string());
+        }
+    }
+    match base {
+        Some(base) => base
+            .join(url)
+            .map(|u| u.to_string())
+            .map_err(|e| DbError::invalid(format!("`{url}` is not a valid URL: {e}"))),
+        None => Ok(dir.join(url).display().to_string()),
+    }
+}
+
+/// Verse timing from the manifest's starts, with each verse ending where the
+/// next begins.
+fn timings(
+    verses: Vec<Timing>,
+    duration: Option<f64>,
+    label: &str,
+) -> Result<Vec<VerseTiming>, DbError> {
+    let mut timed: Vec<VerseTiming> = Vec::with_capacity(verses.len());
+    for timing in verses {
+        let (verse, start) = match timing {
+            Timing::Start(start) => (timed.last().map_or(1, |t| t.verse + 1), start),
+            Timing::Verse { verse, start } => (verse, start),
+        };
+        if let Some(last) = timed.last() {
+            if verse <= last.verse || start < last.start {
+                return Err(DbError::invalid(format!(
+                    "{label}: verse {verse} is out of order"
+                )));
+            }
+        }
+        if verse < 1 || !start.is_finite() || start < 0.0 {
+            return Err(DbError::invalid(format!(
+                "{label}: verse {verse} has no valid start"
+            )));
+        }
+        timed.push(VerseTiming {
+            verse,
+            start,
+            end: None,
+        });
+    }
+    for i in 0..timed.len() {
+        timed[i].end = timed.get(i + 1).map(|next| next.start).or(duration);
+    }
+    Ok(timed)
+}
+
+/// Check a manifest read from `dir` and normalize it for storage, as audio
+/// Bible `fallback_id` unless it names itself.
+fn read_manifest(src: &str, fallback_id: &str, dir: &Path) -> Result<AudioBible, DbError> {
+    let manifest: Manifest = serde_json::from_str(src)
+        .map_err(|e| DbError::invalid(format!("Not an audio Bible manifest: {e}")))?;
+    let id = manifest
+        .id
+        .as_deref()
+        .unwrap_or(fallback_id)
+        .trim()
+        .to_string();
+    slug(&id)?;
+    let base = match manifest.base_url.as_deref() {
+        Some(base) => {
+            let base = if base.ends_with('/') {
+                base.to_string()
+            } else {
+                format!("{base}/")
+            };
+            Some(
+                reqwest::Url::parse(&base)
+                    .map_err(|e| DbError::invalid(format!("`baseUrl` is not a URL: {e}")))?,
+            )
+        }
+        None => None,
+    };
+
+    let mut seen = HashSet::new();
+    let mut chapters = Vec::with_capacity(manifest.chapters.len());
+    for chapter in manifest.chapters {
+        let label = format!("{} {}", chapter.book, chapter.chapter);
+        let book = books::by_name(&chapter.book).ok_or_else(|| {
+            DbError::invalid(format!(
+                "`{}` is not a book BibleMarker shows",
+                chapter.book
+            ))
+        })?;
+        if chapter.chapter < 1 {
+            return Err(DbError::invalid(format!("{label} is not a chapter")));
+        }
+        if !seen.insert((book.osis, chapter.chapter)) {
+            return Err(DbError::invalid(format!("{label} is listed twice")));
+        }
+        chapters.push(ChapterAudio {
+            book: book.osis.to_string(),
+            chapter: chapter.chapter,
+            url: resolve_url(&chapter.url, base.as_ref(), dir)?,
+            duration: chapter.duration,
+            verses: timings(chapter.verses, chapter.duration, &label)?,
+        });
+    }
+    if chapters.is_empty() {
+        return Err(DbError::invalid("The manifest lists no chapters"));
+    }
+    chapters.sort_by_key(|c| (books::position(&c.book), c.chapter));
+    Ok(AudioBible {
+        id,
+        name: manifest.name,
+        module_id: manifest.module_id,
+        chapters,
+    })
+}
+
+/// The verse being read `seconds` into `audio`: the last one started by
+/// then.
+pub(crate) fn verse_at(audio: &ChapterAudio, seconds: f64) -> Option<i64> {
+    audio
+        .verses
+        .iter()
+        .take_while(|t| t.start <= seconds)
+        .last()
+        .map(|t| t.verse)
+}
+
+fn info(bible: &AudioBible, path: &Path) -> AudioBibleInfo {
+    let mut books: Vec<String> = Vec::new();
+    for chapter in &bible.chapters {
+        if books.last() != Some(&chapter.book) {
+            books.push(chapter.book.clone());
+        }
+    }
+    AudioBibleInfo {
+        id: bible.id.clone(),
+        name: bible.name.clone(),
+        module_id: bible.module_id.clone(),
+        chapters: bible.chapters.len(),
+        books,
+        path: path.display().to_string(),
+    }
+}
+
+fn load(dir: &Path, id: &str) -> Result<AudioBible, DbError> {
+    let path = dir.join(format!("{}.json", slug(id)?));
+    let src = std::fs::read_to_string(&path).map_err(|_| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("Audio Bible `{id}` is not installed"),
+        )
+    })?;
+    serde_json::from_str(&src)
+        .map_err(|e| DbError::invalid(format!("{} is damaged: {e}", path.display())))
+}
+
+/// Save `position` as the listener's place in its recording: one synced
+/// `audio_positions` row per recording, logged like `sqliteSaveToTable`.
+pub(crate) fn save_position(
+    conn: &Connection,
+    position: &PlaybackPosition,
+) -> Result<PlaybackPosition, DbError> {
+    let device = db::device_id(conn)?;
+    let now = db::now_iso(conn)?;
+    let id = &position.audio_id;
+    let created_at: String = conn
+        .query_row(
+            "SELECT created_at FROM audio_positions WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or_else(|| now.clone());
+    let saved = PlaybackPosition {
+        updated_at: Some(now.clone()),
+        ..position.clone()
+    };
+    let mut data = serde_json::to_value(&saved)
+        .map_err(|e| DbError::invalid(format!("Cannot store playback position: {e}")))?;
+    data["id"] = id.as_str().into();
+    data["createdAt"] = created_at.as_str().into();
+    let data = data.to_string();
+    conn.execute(
+        "INSERT OR REPLACE INTO audio_positions
+         (id, data, created_at, updated_at, sync_status, device_id)
+         VALUES (?1, ?2, ?3, ?4, 'pending', ?5)",
+        params![id, data, created_at, now, device],
+    )?;
+    db::record_change(
+        conn,
+        "audio_positions",
+        "upsert",
+        id,
+        Some(&data),
+        &now,
+        &device,
+    )?;
+    Ok(saved)
+}
+
+pub(crate) fn load_position(
+    conn: &Connection,
+    audio_id: &str,
+) -> Result<Option<PlaybackPosition>, DbError> {
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT data FROM audio_positions WHERE id = ?1",
+            [audio_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    data.map(|data| {
+        serde_json::from_str(&data)
+            .map_err(|e| DbError::invalid(format!("Stored playback position is not JSON: {e}")))
+    })
+    .transpose()
+}
+
+/// Import the audio Bible manifest at `path`, replacing any audio Bible of
+/// the same id (by default the file name).
+#[command]
+pub fn import_audio_manifest(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<AudioBibleInfo, DbError> {
+    let path = PathBuf::from(path);
+    let src = std::fs::read_to_string(&path)
+        .map_err(|e| DbError::io(format!("Failed to read {}: {e}", path.display())))?;
+    let file = import::file_label(&path);
+    let stem = file.split('.').next().unwrap_or(&file);
+    let bible = read_manifest(&src, stem, path.parent().unwrap_or(Path::new("")))?;
+
+    let dir = audio_dir(&app)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| DbError::io(format!("Failed to create {}: {e}", dir.display())))?;
+    let target = dir.join(format!("{}.json", slug(&bible.id)?));
+    let partial = PathBuf::from(format!("{}.partial", target.display()));
+    let json = serde_json::to_string(&bible)
+        .map_err(|e| DbError::invalid(format!("Cannot store audio Bible: {e}")))?;
+    std::fs::write(&partial, json)
+        .and_then(|()| std::fs::rename(&partial, &target))
+        .map_err(|e| DbError::io(format!("Failed to install {}: {e}", target.display())))?;
+    Ok(info(&bible, &target))
+}
+
+#[command]
+pub fn list_audio_bibles(app: tauri::AppHandle) -> Result<Vec<AudioBibleInfo>, DbError> {
+    let dir = audio_dir(&app)?;
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut bibles = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|e| e == "json") {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            match load(&dir, &stem) {
+                Ok(bible) => bibles.push(info(&bible, &path)),
+                Err(e) => println!("[audio] Skipping {}: {}", path.display(), e.message),
+            }
+        }
+    }
+    bibles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(bibles)
+}
+
+#[command]
+pub fn remove_audio_bible(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    let path = audio_dir(&app)?.join(format!("{}.json", slug(&id)?));
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path)
+        .map_err(|e| DbError::io(format!("Failed to remove {}: {e}", path.display())))?;
+    Ok(true)
+}
+
+/// The audio of `book` `chapter` in audio Bible `audio_id`, or `None` if it
+/// has none for that chapter.
+#[command]
+pub fn get_chapter_audio(
+    app: tauri::AppHandle,
+    audio_id: String,
+    book: String,
+    chapter: i64,
+) -> Result<Option<ChapterAudio>, DbError> {
+    let bible = load(&audio_dir(&app)?, &audio_id)?;
+    Ok(bible
+        .chapters
+        .into_iter()
+        .find(|c| c.book == book && c.chapter == chapter))
+}
+
+/// Remember where the listener is in `position.audio_id`.
+#[command]
+pub async fn save_playback_position(
+    app: tauri::AppHandle,
+    mut position: PlaybackPosition,
+) -> Result<PlaybackPosition, DbError> {
+    if position.verse.is_none() {
+        if let Ok(bible) = load(&audio_dir(&app)?, &position.audio_id) {
+            position.verse = bible
+                .chapters
+                .iter()
+                .find(|c| c.book == position.book && c.chapter == position.chapter)
+                .and_then(|audio| verse_at(audio, position.seconds));
+        }
+    }
+    db::with_connection(&app, move |conn| save_position(conn, &position)).await
+}
+
+/// Where the listener stopped in audio Bible `audio_id`, on any device.
+#[command]
+pub async fn get_playback_position(
+    app: tauri::AppHandle,
+    audio_id: String,
+) -> Result<Option<PlaybackPosition>, DbError> {
+    db::with_reader(&app, move |conn| load_position(conn, &audio_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"{
+        "name": "WEB Audio",
+        "moduleId": "WEB",
+        "chapters": [
+            { "book": "JHN", "chapter": 2, "url": "https://cdn.example.org/JHN_02.mp3" },
+            { "book": "John", "chapter": 1, "url": "JHN_01.mp3", "duration": 30.0,
+              "verses": [0, 6.5, { "verse": 4, "start": 12.0 }] }
+        ]
+    }"#;
+
+    #[test]
+    fn reads_a_manifest_with_verse_timing() {
+        let dir = Path::new("/audio/web");
+        let bible = read_manifest(MANIFEST, "web-audio", dir).unwrap();
+        assert_eq!(bible.id, "web-audio");
+        let john1 = &bible.chapters[0];
+        assert_eq!((john1.book.as_str(), john1.chapter), ("John", 1));
+        assert_eq!(john1.url, dir.join("JHN_01.mp3").display().to_string());
+        assert_eq!(
+            john1.verses,
+            [
+                VerseTiming {
+                    verse: 1,
+                    start: 0.0,
+                    end: Some(6.5)
+                },
+                VerseTiming {
+                    verse: 2,
+                    start: 6.5,
+                    end: Some(12.0)
+                },
+                VerseTiming {
+                    verse: 4,
+                    start: 12.0,
+                    end: Some(30.0)
+                },
+            ]
+        );
+        assert_eq!(verse_at(john1, 7.0), Some(2));
+        assert_eq!(bible.chapters[1].url, "https://cdn.example.org/JHN_02.mp3");
+        assert_eq!(verse_at(&bible.chapters[1], 7.0), None);
+
+        let based = MANIFEST.replace(
+            r#""moduleId""#,
+            r#""baseUrl": "https://example.org/web", "moduleId""#,
+        );
+        let bible = read_manifest(&based, "web-audio", dir).unwrap();
+        assert_eq!(bible.chapters[0].url, "https://example.org/web/JHN_01.mp3");
+
+        let twice = MANIFEST.replace(r#""chapter": 2"#, r#""chapter": 1"#);
+        assert!(read_manifest(&twice, "web-audio", dir).is_err());
+        let backwards = MANIFEST.replace("6.5", "-1");
+        assert!(read_manifest(&backwards, "web-audio", dir).is_err());
+    }
+
+    #[test]
+    fn saves_playback_positions_for_sync() {
+        let conn = db::migrated_test_connection();
+        let position = PlaybackPosition {
+            audio_id: "web-audio".into(),
+            book: "John".into(),
+            chapter: 1,
+            verse: Some(2),
+            seconds: 7.0,
+            updated_at: None,
+        };
+        let saved = save_position(&conn, &position).unwrap();
+        assert!(saved.updated_at.is_some());
+        save_position(
+            &conn,
+            &PlaybackPosition {
+                seconds: 13.5,
+                verse: Some(4),
+                ..position
+            },
+        )
+        .unwrap();
+
+        let loaded = load_position(&conn, "web-audio").unwrap().unwrap();
+        assert_eq!((loaded.verse, loaded.seconds), (Some(4), 13.5));
+        assert_eq!(load_position(&conn, "kjv-audio").unwrap(), None);
+        let logged: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM change_log
+                 WHERE table_name = 'audio_positions' AND row_id = 'web-audio'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(logged, 2);
+    }
+}
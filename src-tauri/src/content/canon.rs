@@ -0,0 +1,128 @@
+//! The shape of the canon: books in order, with each chapter's verse count,
+//! as a versification numbers them. Navigation, reading plans and
+//! validation read it here rather than keeping tables of their own.
+//!
+//! KJV counts are `books::KJV_VERSES`. Other schemes are the KJV verses
+//! mapped through `versification`, so the structure can never disagree with
+//! how verses are mapped between translations (Hebrew Malachi has three
+//! chapters, and a psalm's title verses count).
+
+use serde::Serialize;
+use tauri::command;
+
+use super::books;
+use super::versification::{self, Scheme};
+
+/// Books before this index are the Old Testament.
+const NEW_TESTAMENT: usize = 39;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CanonBook {
+    /// OSIS id.
+    pub id: &'static str,
+    pub name: &'static str,
+    /// `OT` or `NT`.
+    pub testament: &'static str,
+    /// Position in the canon, from 1.
+    pub order: usize,
+    pub chapters: usize,
+    /// Verses in each chapter, first chapter first.
+    pub verses: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CanonStructure {
+    /// The scheme the counts follow: `KJV` or `Leningrad` (Hebrew).
+    pub versification: &'static str,
+    pub books: Vec<CanonBook>,
+    #[serde(rename = "totalVerses")]
+    pub total_verses: i64,
+}
+
+/// Verses per chapter of book `index` as `scheme` numbers them.
+fn chapter_verses(scheme: Scheme, index: usize) -> Vec<i64> {
+    let kjv = books::KJV_VERSES[index];
+    if scheme == Scheme::Kjv {
+        return kjv.iter().map(|&n| i64::from(n)).collect();
+    }
+    let book = books::BOOKS[index].osis;
+    let mut verses: Vec<i64> = Vec::with_capacity(kjv.len());
+    for (chapter, &count) in (1..).zip(kjv) {
+        for verse in 1..=i64::from(count) {
+            let (chapter, verse) = versification::from_kjv(scheme, book, chapter, verse);
+            let at = chapter as usize - 1;
+            if verses.len() <= at {
+                verses.resize(at + 1, 0);
+            }
+            verses[at] = verses[at].max(verse);
+        }
+    }
+    verses
+}
+
+/// The canon as `scheme` numbers it.
+pub(crate) fn structure(scheme: Scheme) -> CanonStructure {
+    let books: Vec<CanonBook> = books::BOOKS
+        .iter()
+        .enumerate()
+        .map(|(index, book)| {
+            let verses = chapter_verses(scheme, index);
+            CanonBook {
+                id: book.osis,
+                name: book.name,
+                testament: if index < NEW_TESTAMENT { "OT" } else { "NT" },
+                order: index + 1,
+                chapters: verses.len(),
+                verses,
+            }
+        })
+        .collect();
+    CanonStructure {
+        versification: match scheme {
+            Scheme::Kjv => "KJV",
+            Scheme::Hebrew => "Leningrad",
+        },
+        total_verses: books.iter().flat_map(|b| &b.verses).sum(),
+        books,
+    }
+}
+
+/// Book order, chapter counts and verse counts as `versification` (a
+/// content file's, e.g. `KJV` or `Leningrad`) numbers them; unknown or
+/// missing schemes are KJV, as the reader treats them.
+#[command]
+pub fn get_canon_structure(versification: Option<String>) -> CanonStructure {
+    structure(Scheme::named(versification.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book<'a>(canon: &'a CanonStructure, id: &str) -> &'a CanonBook {
+        canon.books.iter().find(|b| b.id == id).unwrap()
+    }
+
+    #[test]
+    fn counts_books_chapters_and_verses_per_scheme() {
+        let kjv = structure(Scheme::Kjv);
+        assert_eq!((kjv.books.len(), kjv.total_verses), (66, 31102));
+        let genesis = book(&kjv, "Gen");
+        assert_eq!(
+            (genesis.order, genesis.chapters, genesis.verses[0]),
+            (1, 50, 31)
+        );
+        assert_eq!(
+            (book(&kjv, "Matt").testament, book(&kjv, "Matt").order),
+            ("NT", 40)
+        );
+
+        let hebrew = structure(Scheme::named(Some("Leningrad")));
+        assert_eq!(hebrew.versification, "Leningrad");
+        assert_eq!(book(&hebrew, "Mal").verses, [14, 17, 24]);
+        assert_eq!(book(&hebrew, "Joel").chapters, 4);
+        assert_eq!(book(&hebrew, "Ps").verses[50], 21);
+        // KJV Genesis 31:55 is Hebrew 32:1.
+        assert_eq!(book(&hebrew, "Gen").verses[30..32], [54, 33]);
+    }
+}
@@ -0,0 +1,742 @@
+//! Writing references out the way a citation style and language do.
+//!
+//! Each locale has its books' full names and standard abbreviations and
+//! the separators it writes between chapter and verse and across a range
+//! ("Joh 3,16–18" in German, "Jn 3:16-18" in Spanish). Compact styles drop
+//! the spaces inside abbreviations, and English uses its two-letter forms.
+//! A locale is matched by its language ("pt-BR" is `pt`); unknown ones are
+//! written in English. The same tables let the reference parser read book
+//! names in any of these languages.
+
+use serde::Deserialize;
+use tauri::command;
+
+use super::books::{self, BookId};
+use super::references::ScriptureReference;
+use crate::db::{DbError, DbErrorKind};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceStyle {
+    /// "1 Corinthians 13:4–7".
+    #[default]
+    Full,
+    /// "1 Cor 13:4–7".
+    Abbreviated,
+    /// "1Cor 13:4–7".
+    Compact,
+}
+
+/// Separators overriding the locale's.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct Separators {
+    /// Between chapter and verse, e.g. `:`.
+    #[serde(rename = "chapterVerse")]
+    pub chapter_verse: Option<String>,
+    /// Between the ends of a range, e.g. `–`.
+    pub range: Option<String>,
+}
+
+struct Locale {
+    language: &'static str,
+    chapter_verse: &'static str,
+    range: &'static str,
+    /// Full names in canonical order; English's are `books::BOOKS`.
+    names: Option<[&'static str; 66]>,
+    abbreviations: [&'static str; 66],
+    /// Compact forms, when they are more than the abbreviations unspaced.
+    compact: Option<[&'static str; 66]>,
+}
+
+const ENGLISH: Locale = Locale {
+    language: "en",
+    chapter_verse: ":",
+    range: "–",
+    names: None,
+    abbreviations: [
+        "Gen", "Exod", "Lev", "Num", "Deut", "Josh", "Judg", "Ruth", "1 Sam", "2 Sam", "1 Kgs",
+        "2 Kgs", "1 Chr", "2 Chr", "Ezra", "Neh", "Esth", "Job", "Ps", "Prov", "Eccl", "Song",
+        "Isa", "Jer", "Lam", "Ezek", "Dan", "Hos", "Joel", "Amos", "Obad", "Jonah", "Mic", "Nah",
+        "Hab", "Zeph", "Hag", "Zech", "Mal", "Matt", "Mark", "Luke", "John", "Acts", "Rom",
+        "1 Cor", "2 Cor", "Gal", "Eph", "Phil", "Col", "1 Thess", "2 Thess", "1 Tim", "2 Tim",
+        "Titus", "Phlm", "Heb", "Jas", "1 Pet", "2 Pet", "1 John", "2 John", "3 John", "Jude",
+        "Rev",
+    ],
+    compact: Some([
+        "Gn", "Ex", "Lv", "Nm", "Dt", "Jos", "Jgs", "Ru", "1Sm", "2Sm", "1Kgs", "2Kgs", "1Chr",
+        "2Chr", "Ezr", "Neh", "Est", "Jb", "Ps", "Prv", "Eccl", "Sg", "Is", "Jer", "Lam", "Ez",
+        "Dn", "Hos", "Jl", "Am", "Ob", "Jon", "Mi", "Na", "Hb", "Zep", "Hg", "Zec", "Mal", "Mt",
+        "Mk", "Lk", "Jn", "Acts", "Rom", "1Cor", "2Cor", "Gal", "Eph", "Phil", "Col", "1Thes",
+        "2Thes", "1Tm", "2Tm", "Ti", "Phlm", "Heb", "Jas", "1Pt", "2Pt", "1Jn", "2Jn", "3Jn",
+        "Jude", "Rv",
+    ]),
+};
+
+const LOCALES: &[Locale] = &[
+    ENGLISH,
+    Locale {
+        language: "es",
+        chapter_verse: ":",
+        range: "-",
+        names: Some([
+            "Génesis",
+            "Éxodo",
+            "Levítico",
+            "Números",
+            "Deuteronomio",
+            "Josué",
+            "Jueces",
+            "Rut",
+            "1 Samuel",
+            "2 Samuel",
+            "1 Reyes",
+            "2 Reyes",
+            "1 Crónicas",
+            "2 Crónicas",
+            "Esdras",
+            "Nehemías",
+            "Ester",
+            "Job",
+            "Salmos",
+            "Proverbios",
+            "Eclesiastés",
+            "Cantares",
+            "Isaías",
+            "Jeremías",
+            "Lamentaciones",
+            "Ezequiel",
+            "Daniel",
+            "Oseas",
+            "Joel",
+            "Amós",
+            "Abdías",
+            "Jonás",
+            "Miqueas",
+            "Nahúm",
+            "Habacuc",
+            "Sofonías",
+            "Hageo",
+            "Zacarías",
+            "Malaquías",
+            "Mateo",
+            "Marcos",
+            "Lucas",
+            "Juan",
+            "Hechos",
+            "Romanos",
+            "1 Corintios",
+            "2 Corintios",
+            "Gálatas",
+            "Efesios",
+            "Filipenses",
+            "Colosenses",
+            "1 Tesalonicenses",
+            "2 Tesalonicenses",
+            "1 Timoteo",
+            "2 Timoteo",
+            "Tito",
+            "Filemón",
+            "Hebreos",
+            "Santiago",
+            "1 Pedro",
+            "2 Pedro",
+            "1 Juan",
+            "2 Juan",
+            "3 Juan",
+            "Judas",
+            "Apocalipsis",
+        ]),
+        abbreviations: [
+            "Gn", "Éx", "Lv", "Nm", "Dt", "Jos", "Jue", "Rt", "1 S", "2 S", "1 R", "2 R", "1 Cr",
+            "2 Cr", "Esd", "Neh", "Est", "Job", "Sal", "Pr", "Ec", "Cnt", "Is", "Jer", "Lm", "Ez",
+            "Dn", "Os", "Jl", "Am", "Abd", "Jon", "Mi", "Nah", "Hab", "Sof", "Hag", "Zac", "Mal",
+            "Mt", "Mr", "Lc", "Jn", "Hch", "Ro", "1 Co", "2 Co", "Gá", "Ef", "Fil", "Col", "1 Ts",
+            "2 Ts", "1 Ti", "2 Ti", "Tit", "Flm", "He", "Stg", "1 P", "2 P", "1 Jn", "2 Jn",
+            "3 Jn", "Jud", "Ap",
+        ],
+        compact: None,
+    },
+    Locale {
+        language: "de",
+        chapter_verse: ",",
+        range: "–",
+        names: Some([
+            "1. Mose",
+            "2. Mose",
+            "3. Mose",
+            "4. Mose",
+            "5. Mose",
+            "Josua",
+            "Richter",
+            "Rut",
+            "1. Samuel",
+            "2. Samuel",
+            "1. Könige",
+            "2. Könige",
+            "1. Chronik",
+            "2. Chronik",
+            "Esra",
+            "Nehemia",
+            "Ester",
+            "Hiob",
+            "Psalmen",
+            "Sprüche",
+            "Prediger",
+            "Hoheslied",
+            "Jesaja",
+            "Jeremia",
+            "Klagelieder",
+            "Hesekiel",
+            "Daniel",
+            "Hosea",
+            "Joel",
+            "Amos",
+            "Obadja",
+            "Jona",
+            "Micha",
+            "Nahum",
+            "Habakuk",
+            "Zefanja",
+            "Haggai",
+            "Sacharja",
+            "Maleachi",
+            "Matthäus",
+            "Markus",
+            "Lukas",
+            "Johannes",
+            "Apostelgeschichte",
+            "Römer",
+            "1. Korinther",
+            "2. Korinther",
+            "Galater",
+            "Epheser",
+            "Philipper",
+            "Kolosser",
+            "1. Thessalonicher",
+            "2. Thessalonicher",
+            "1. Timotheus",
+            "2. Timotheus",
+            "Titus",
+            "Philemon",
+            "Hebräer",
+            "Jakobus",
+            "1. Petrus",
+            "2. Petrus",
+            "1. Johannes",
+            "2. Johannes",
+            "3. Johannes",
+            "Judas",
+            "Offenbarung",
+        ]),
+        abbreviations: [
+            "1Mo", "2Mo", "3Mo", "4Mo", "5Mo", "Jos", "Ri", "Rut", "1Sam", "2Sam", "1Kön", "2Kön",
+            "1Chr", "2Chr", "Esra", "Neh", "Est", "Hiob", "Ps", "Spr", "Pred", "Hld", "Jes", "Jer",
+            "Klgl", "Hes", "Dan", "Hos", "Joel", "Am", "Obd", "Jona", "Mi", "Nah", "Hab", "Zef",
+            "Hag", "Sach", "Mal", "Mt", "Mk", "Lk", "Joh", "Apg", "Röm", "1Kor", "2Kor", "Gal",
+            "Eph", "Phil", "Kol", "1Thess", "2Thess", "1Tim", "2Tim", "Tit", "Phlm", "Hebr", "Jak",
+            "1Petr", "2Petr", "1Joh", "2Joh", "3Joh", "Jud", "Offb",
+        ],
+        compact: None,
+    },
+    Locale {
+        language: "fr",
+        chapter_verse: ",",
+        range: "-",
+        names: Some([
+            "Genèse",
+            "Exode",
+            "Lévitique",
+            "Nombres",
+            "Deutéronome",
+            "Josué",
+            "Juges",
+            "Ruth",
+            "1 Samuel",
+            "2 Samuel",
+            "1 Rois",
+            "2 Rois",
+            "1 Chroniques",
+            "2 Chroniques",
+            "Esdras",
+            "Néhémie",
+            "Esther",
+            "Job",
+            "Psaumes",
+            "Proverbes",
+            "Ecclésiaste",
+            "Cantique des cantiques",
+            "Ésaïe",
+            "Jérémie",
+            "Lamentations",
+            "Ézéchiel",
+            "Daniel",
+            "Osée",
+            "Joël",
+            "Amos",
+            "Abdias",
+            "Jonas",
+            "Michée",
+            "Nahum",
+            "Habacuc",
+            "Sophonie",
+            "Aggée",
+            "Zacharie",
+            "Malachie",
+            "Matthieu",
+            "Marc",
+            "Luc",
+            "Jean",
+            "Actes",
+            "Romains",
+            "1 Corinthiens",
+            "2 Corinthiens",
+            "Galates",
+            "Éphésiens",
+            "Philippiens",
+            "Colossiens",
+            "1 Thessaloniciens",
+            "2 Thessaloniciens",
+            "1 Timothée",
+            "2 Timothée",
+            "Tite",
+            "Philémon",
+            "Hébreux",
+            "Jacques",
+            "1 Pierre",
+            "2 Pierre",
+            "1 Jean",
+            "2 Jean",
+            "3 Jean",
+            "Jude",
+            "Apocalypse",
+        ]),
+        abbreviations: [
+            "Gn", "Ex", "Lv", "Nb", "Dt", "Jos", "Jg", "Rt", "1 S", "2 S", "1 R", "2 R", "1 Ch",
+            "2 Ch", "Esd", "Né", "Est", "Jb", "Ps", "Pr", "Ec", "Ct", "És", "Jr", "Lm", "Éz", "Dn",
+            "Os", "Jl", "Am", "Ab", "Jon", "Mi", "Na", "Ha", "So", "Ag", "Za", "Ml", "Mt", "Mc",
+            "Lc", "Jn", "Ac", "Rm", "1 Co", "2 Co", "Ga", "Ép", "Ph", "Col", "1 Th", "2 Th",
+            "1 Tm", "2 Tm", "Tt", "Phm", "Hé", "Jc", "1 P", "2 P", "1 Jn", "2 Jn", "3 Jn", "Jude",
+            "Ap",
+        ],
+        compact: None,
+    },
+    Locale {
+        language: "pt",
+        chapter_verse: ":",
+        range: "-",
+        names: Some([
+            "Gênesis",
+            "Êxodo",
+            "Levítico",
+            "Números",
+            "Deuteronômio",
+            "Josué",
+            "Juízes",
+            "Rute",
+            "1 Samuel",
+            "2 Samuel",
+            "1 Reis",
+            "2 Reis",
+            "1 Crônicas",
+            "2 Crônicas",
+            "Esdras",
+            "Neemias",
+            "Ester",
+            "Jó",
+            "Salmos",
+            "Provérbios",
+            "Eclesiastes",
+            "Cânticos",
+            "Isaías",
+            "Jeremias",
+            "Lamentações",
+            "Ezequiel",
+            "Daniel",
+            "Oseias",
+            "Joel",
+            "Amós",
+            "Obadias",
+            "Jonas",
+            "Miqueias",
+            "Naum",
+            "Habacuque",
+            "Sofonias",
+            "Ageu",
+            "Zacarias",
+            "Malaquias",
+            "Mateus",
+            "Marcos",
+            "Lucas",
+            "João",
+            "Atos",
+            "Romanos",
+            "1 Coríntios",
+            "2 Coríntios",
+            "Gálatas",
+            "Efésios",
+            "Filipenses",
+            "Colossenses",
+            "1 Tessalonicenses",
+            "2 Tessalonicenses",
+            "1 Timóteo",
+            "2 Timóteo",
+            "Tito",
+            "Filemom",
+            "Hebreus",
+            "Tiago",
+            "1 Pedro",
+            "2 Pedro",
+            "1 João",
+            "2 João",
+            "3 João",
+            "Judas",
+            "Apocalipse",
+        ]),
+        abbreviations: [
+            "Gn", "Êx", "Lv", "Nm", "Dt", "Js", "Jz", "Rt", "1 Sm", "2 Sm", "1 Rs", "2 Rs", "1 Cr",
+            "2 Cr", "Ed", "Ne", "Et", "Jó", "Sl", "Pv", "Ec", "Ct", "Is", "Jr", "Lm", "Ez", "Dn",
+            "Os", "Jl", "Am", "Ob", "Jn", "Mq", "Na", "Hc", "Sf", "Ag", "Zc", "Ml", "Mt", "Mc",
+            "Lc", "Jo", "At", "Rm", "1 Co", "2 Co", "Gl", "Ef", "Fp", "Cl", "1 Ts", "2 Ts", "1 Tm",
+            "2 Tm", "Tt", "Fm", "Hb", "Tg", "1 Pe", "2 Pe", "1 Jo", "2 Jo", "3 Jo", "Jd", "Ap",
+        ],
+        compact: None,
+    },
+    Locale {
+        language: "it",
+        chapter_verse: ",",
+        range: "-",
+        names: Some([
+            "Genesi",
+            "Esodo",
+            "Levitico",
+            "Numeri",
+            "Deuteronomio",
+            "Giosuè",
+            "Giudici",
+            "Rut",
+            "1 Samuele",
+            "2 Samuele",
+            "1 Re",
+            "2 Re",
+            "1 Cronache",
+            "2 Cronache",
+            "Esdra",
+            "Neemia",
+            "Ester",
+            "Giobbe",
+            "Salmi",
+            "Proverbi",
+            "Ecclesiaste",
+            "Cantico dei Cantici",
+            "Isaia",
+            "Geremia",
+            "Lamentazioni",
+            "Ezechiele",
+            "Daniele",
+            "Osea",
+            "Gioele",
+            "Amos",
+            "Abdia",
+            "Giona",
+            "Michea",
+            "Naum",
+            "Abacuc",
+            "Sofonia",
+            "Aggeo",
+            "Zaccaria",
+            "Malachia",
+            "Matteo",
+            "Marco",
+            "Luca",
+            "Giovanni",
+            "Atti",
+            "Romani",
+            "1 Corinzi",
+            "2 Corinzi",
+            "Galati",
+            "Efesini",
+            "Filippesi",
+            "Colossesi",
+            "1 Tessalonicesi",
+            "2 Tessalonicesi",
+            "1 Timoteo",
+            "2 Timoteo",
+            "Tito",
+            "Filemone",
+            "Ebrei",
+            "Giacomo",
+            "1 Pietro",
+            "2 Pietro",
+            "1 Giovanni",
+            "2 Giovanni",
+            "3 Giovanni",
+            "Giuda",
+            "Apocalisse",
+        ]),
+        abbreviations: [
+            "Gen", "Es", "Lv", "Nm", "Dt", "Gs", "Gdc", "Rt", "1 Sam", "2 Sam", "1 Re", "2 Re",
+            "1 Cr", "2 Cr", "Esd", "Ne", "Est", "Gb", "Sal", "Pr", "Qo", "Ct", "Is", "Ger", "Lam",
+            "Ez", "Dn", "Os", "Gl", "Am", "Abd", "Gio", "Mi", "Na", "Ab", "Sof", "Ag", "Zc", "Ml",
+            "Mt", "Mc", "Lc", "Gv", "At", "Rm", "1 Cor", "2 Cor", "Gal", "Ef", "Fil", "Col",
+            "1 Ts", "2 Ts", "1 Tm", "2 Tm", "Tt", "Fm", "Eb", "Gc", "1 Pt", "2 Pt", "1 Gv", "2 Gv",
+            "3 Gv", "Gd", "Ap",
+        ],
+        compact: None,
+    },
+    Locale {
+        language: "nl",
+        chapter_verse: ":",
+        range: "-",
+        names: Some([
+            "Genesis",
+            "Exodus",
+            "Leviticus",
+            "Numeri",
+            "Deuteronomium",
+            "Jozua",
+            "Richteren",
+            "Ruth",
+            "1 Samuël",
+            "2 Samuël",
+            "1 Koningen",
+            "2 Koningen",
+            "1 Kronieken",
+            "2 Kronieken",
+            "Ezra",
+            "Nehemia",
+            "Ester",
+            "Job",
+            "Psalmen",
+            "Spreuken",
+            "Prediker",
+            "Hooglied",
+            "Jesaja",
+            "Jeremia",
+            "Klaagliederen",
+            "Ezechiël",
+            "Daniël",
+            "Hosea",
+            "Joël",
+            "Amos",
+            "Obadja",
+            "Jona",
+            "Micha",
+            "Nahum",
+            "Habakuk",
+            "Sefanja",
+            "Haggai",
+            "Zacharia",
+            "Maleachi",
+            "Matteüs",
+            "Marcus",
+            "Lucas",
+            "Johannes",
+            "Handelingen",
+            "Romeinen",
+            "1 Korintiërs",
+            "2 Korintiërs",
+            "Galaten",
+            "Efeziërs",
+            "Filippenzen",
+            "Kolossenzen",
+            "1 Tessalonicenzen",
+            "2 Tessalonicenzen",
+            "1 Timoteüs",
+            "2 Timoteüs",
+            "Titus",
+            "Filemon",
+            "Hebreeën",
+            "Jakobus",
+            "1 Petrus",
+            "2 Petrus",
+            "1 Johannes",
+            "2 Johannes",
+            "3 Johannes",
+            "Judas",
+            "Openbaring",
+        ]),
+        abbreviations: [
+            "Gen", "Ex", "Lev", "Num", "Deut", "Joz", "Re", "Ruth", "1 Sam", "2 Sam", "1 Kon",
+            "2 Kon", "1 Kron", "2 Kron", "Ezra", "Neh", "Est", "Job", "Ps", "Spr", "Pred", "Hgl",
+            "Jes", "Jer", "Klaagl", "Ezech", "Dan", "Hos", "Joël", "Amos", "Ob", "Jona", "Mi",
+            "Nah", "Hab", "Sef", "Hag", "Zach", "Mal", "Mat", "Mar", "Luc", "Joh", "Hand", "Rom",
+            "1 Kor", "2 Kor", "Gal", "Ef", "Fil", "Kol", "1 Tess", "2 Tess", "1 Tim", "2 Tim",
+            "Tit", "Filem", "Heb", "Jak", "1 Petr", "2 Petr", "1 Joh", "2 Joh", "3 Joh", "Jud",
+            "Openb",
+        ],
+        compact: None,
+    },
+];
+
+/// The locale for `tag` ("de", "pt-BR"), else English.
+fn locale(tag: Option<&str>) -> &'static Locale {
+    let language = tag
+        .unwrap_or("en")
+        .split(['-', '_'])
+        .next()
+        .unwrap_or("en")
+        .to_lowercase();
+    LOCALES
+        .iter()
+        .find(|l| l.language == language)
+        .unwrap_or(&LOCALES[0])
+}
+
+/// The book `name` names in a language other than English, by full name
+/// or standard abbreviation ("Juan", "1. Mose", "Apg"), or by the start of
+/// full names that all name one book ("Apoc").
+pub(crate) fn book_named(name: &str) -> Option<&'static BookId> {
+    let wanted = books::fold(name);
+    if !wanted.chars().any(char::is_alphabetic) {
+        return None;
+    }
+    let others = || LOCALES.iter().filter(|l| l.language != "en");
+    let exact = others().find_map(|locale| {
+        let names = locale.names.iter().flatten();
+        names
+            .chain(&locale.abbreviations)
+            .position(|n| books::fold(n) == wanted)
+            .map(|i| i % 66)
+    });
+    if let Some(index) = exact {
+        return Some(&books::BOOKS[index]);
+    }
+    if wanted.trim_start_matches(char::is_numeric).chars().count() < 3 {
+        return None;
+    }
+    let mut starting = others()
+        .flat_map(|locale| locale.names.iter().flatten().enumerate())
+        .filter(|(_, n)| books::fold(n).starts_with(&wanted))
+        .map(|(i, _)| i);
+    let first = starting.next()?;
+    starting.all(|i| i == first).then(|| &books::BOOKS[first])
+}
+
+/// Every full name and abbreviation of every language, with the index of
+/// the book it names; English names first.
+pub(crate) fn book_names() -> impl Iterator<Item = (usize, &'static str)> {
+    LOCALES.iter().flat_map(|locale| {
+        let names: [&'static str; 66] = locale
+            .names
+            .unwrap_or_else(|| std::array::from_fn(|i| books::BOOKS[i].name));
+        names
+            .into_iter()
+            .enumerate()
+            .chain(locale.abbreviations.into_iter().enumerate())
+    })
+}
+
+/// How `book` is written in `style`.
+fn book_name(book: &BookId, index: usize, style: ReferenceStyle, locale: &Locale) -> String {
+    match style {
+        ReferenceStyle::Full => locale
+            .names
+            .map_or(book.name, |names| names[index])
+            .to_string(),
+        ReferenceStyle::Abbreviated => locale.abbreviations[index].to_string(),
+        ReferenceStyle::Compact => match locale.compact {
+            Some(compact) => compact[index].to_string(),
+            None => locale.abbreviations[index].replace([' ', '.'], ""),
+        },
+    }
+}
+
+/// `reference` written in `style` for `locale`, optionally with other
+/// `separators`. A whole book is its name alone.
+pub(crate) fn format(
+    reference: &ScriptureReference,
+    style: ReferenceStyle,
+    locale_tag: Option<&str>,
+    separators: &Separators,
+) -> Option<String> {
+    let index = books::position(&reference.book)? - 1;
+    let book = &books::BOOKS[index];
+    let locale = locale(locale_tag);
+    let chapter_verse = separators
+        .chapter_verse
+        .as_deref()
+        .unwrap_or(locale.chapter_verse);
+    let range = separators.range.as_deref().unwrap_or(locale.range);
+    let name = book_name(book, index, style, locale);
+
+    let (chapter, verse) = (reference.chapter, reference.verse);
+    let (end_chapter, end_verse) = (reference.end_chapter, reference.end_verse);
+    let chapters = books::KJV_VERSES[index].len() as i64;
+    if chapter == 1 && verse.is_none() && end_chapter == chapters && end_verse.is_none() {
+        return Some(name);
+    }
+    let at = |chapter: i64, verse: Option<i64>| match verse {
+        Some(verse) => format!("{chapter}{chapter_verse}{verse}"),
+        None => chapter.to_string(),
+    };
+    let start = at(chapter, verse);
+    if (chapter, verse) == (end_chapter, end_verse) {
+        return Some(format!("{name} {start}"));
+    }
+    let end = match end_verse {
+        Some(end_verse) if chapter == end_chapter && verse.is_some() => end_verse.to_string(),
+        _ => at(end_chapter, end_verse),
+    };
+    Some(format!("{name} {start}{range}{end}"))
+}
+
+/// `reference` written as `style` (full names by default) cites it in
+/// `locale`, with the locale's separators unless `separators` says otherwise,
+/// for exported notes and copied verses.
+#[command]
+pub fn format_reference(
+    reference: ScriptureReference,
+    style: Option<ReferenceStyle>,
+    locale: Option<String>,
+    separators: Option<Separators>,
+) -> Result<String, DbError> {
+    format(
+        &reference,
+        style.unwrap_or_default(),
+        locale.as_deref(),
+        &separators.unwrap_or_default(),
+    )
+    .ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("`{}` is not a book of the Bible", reference.book),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::references::parse;
+
+    fn cite(text: &str, style: ReferenceStyle, locale: &str) -> String {
+        format(&parse(text)[0], style, Some(locale), &Separators::default()).unwrap()
+    }
+
+    #[test]
+    fn cites_in_each_style_and_language() {
+        use ReferenceStyle::*;
+        assert_eq!(cite("1 Cor 13:4-7", Full, "en"), "1 Corinthians 13:4–7");
+        assert_eq!(cite("1 Cor 13:4-7", Abbreviated, "en-GB"), "1 Cor 13:4–7");
+        assert_eq!(cite("1 Cor 13:4-7", Compact, "en"), "1Cor 13:4–7");
+        assert_eq!(cite("Jn 3:16-18", Abbreviated, "de"), "Joh 3,16–18");
+        assert_eq!(cite("Gen 1:1-2:3", Full, "de"), "1. Mose 1,1–2,3");
+        assert_eq!(cite("1 Sam 3", Compact, "es"), "1S 3");
+        assert_eq!(cite("Jn 3:16", Full, "pt-BR"), "João 3:16");
+        assert_eq!(cite("Ps 23", Full, "fr"), "Psaumes 23");
+        assert_eq!(cite("Rom", Abbreviated, "xx"), "Rom");
+
+        let separators = Separators {
+            chapter_verse: Some(".".into()),
+            range: Some("-".into()),
+        };
+        let verses = &parse("Matt 5:3-12")[0];
+        assert_eq!(
+            format(verses, Abbreviated, None, &separators).unwrap(),
+            "Matt 5.3-12"
+        );
+        for locale in LOCALES {
+            assert!(locale.abbreviations.iter().all(|a| !a.is_empty()));
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! e-Sword Bible modules (`.bblx`).
+//!
+//! A `.bblx` is a SQLite database: one `Bible (Book, Chapter, Verse,
+//! Scripture)` row per verse, with books numbered 1-66 in canonical order
+//! (apocryphal books, numbered after them, are skipped), and a one-row
+//! `Details` table naming the module. `Scripture` is an RTF fragment; its
+//! italics and bold are kept as verse styles, superscript Strong's numbers
+//! tag the word before them, and colours are ignored since modules don't agree
+//! on which entry of the colour table marks the words of Christ. MySword
+//! modules share the layout (see `theword`).
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::command;
+
+use super::books;
+use super::import::{self, Book, ImportReport, Styled, Verse, Warnings, Word};
+use crate::db::{DbError, DbErrorKind};
+
+/// Formatting in effect inside an RTF group.
+#[derive(Debug, Default, Clone, Copy)]
+struct Group {
+    italic: bool,
+    bold: bool,
+    small_caps: bool,
+    superscript: bool,
+    /// A destination (`\*`, `\fonttbl`, ...) whose text isn't shown.
+    hidden: bool,
+    /// `\ucN`: characters after a `\uN` that stand in for it.
+    fallback: usize,
+}
+
+impl Group {
+    fn chars(&self) -> Vec<&'static str> {
+        let mut chars = Vec::new();
+        match (self.bold, self.italic) {
+            (true, true) => chars.push("bdit"),
+            (true, false) => chars.push("bd"),
+            (false, true) => chars.push("it"),
+            (false, false) => {}
+        }
+        if self.small_caps {
+            chars.push("sc");
+        }
+        chars
+    }
+}
+
+/// Windows-1252, which `\'hh` escapes are in, where it differs from Latin-1.
+fn cp1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+        '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}',
+        'ž', 'Ÿ',
+    ];
+    match byte {
+        0x80..=0x9f => HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+/// Destinations whose contents are metadata rather than text.
+const HIDDEN: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "fldinst",
+    "header",
+    "footer",
+];
+
+/// The plain text of an RTF fragment, its styled runs and the words its
+/// superscript Strong's numbers tag. Bare numbers take `prefix`.
+pub(super) fn read_rtf(src: &str, prefix: Option<char>) -> (String, Styled, Vec<Word>) {
+    let mut text = String::new();
+    let mut styled = Styled::default();
+    let mut words = Vec::new();
+    // Superscript text since the last shown text.
+    let mut sup = String::new();
+    let mut group = Group {
+        fallback: 1,
+        ..Default::default()
+    };
+    let mut outer: Vec<Group> = Vec::new();
+    // Fallback characters still to skip after a `\uN`.
+    let mut skip = 0;
+    let mut emit = |group: &Group, s: &str, text: &mut String| {
+        if group.hidden {
+            return;
+        }
+        if group.superscript {
+            sup.push_str(s);
+            return;
+        }
+        if !sup.is_empty() {
+            let strongs = import::strongs_numbers(&std::mem::take(&mut sup), prefix);
+            import::tag_last_word(&mut words, text, strongs, None);
+        }
+        let start = text.len();
+        import::push_text(text, s);
+        styled.push("p", &group.chars(), &text[start..]);
+    };
+
+    let mut chars = src.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => outer.push(group),
+            '}' => group = outer.pop().unwrap_or(group),
+            // Line breaks in the source are only there for width.
+            '\r' | '\n' => {}
+            '\\' => match chars.peek().copied() {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    let mut word = String::new();
+                    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                        word.push(c);
+                        chars.next();
+                    }
+                    let mut digits = String::new();
+                    if chars.peek() == Some(&'-') {
+                        digits.push('-');
+                        chars.next();
+                    }
+                    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                        digits.push(c);
+                        chars.next();
+                    }
+                    // One space ends the control word and isn't text.
+                    if chars.peek() == Some(&' ') {
+                        chars.next();
+                    }
+                    let param: Option<i32> = digits.parse().ok();
+                    let on = param != Some(0);
+                    match word.as_str() {
+                        "par" | "line" | "tab" | "sect" => emit(&group, " ", &mut text),
+                        "i" => group.italic = on,
+                        "b" => group.bold = on,
+                        "scaps" => group.small_caps = on,
+                        "super" => group.superscript = true,
+                        "nosupersub" => group.superscript = false,
+                        "plain" => {
+                            group = Group {
+                                hidden: group.hidden,
+                                fallback: group.fallback,
+                                ..Default::default()
+                            }
+                        }
+                        "uc" => group.fallback = param.unwrap_or(1).max(0) as usize,
+                        "u" => {
+                            // Negative values are the upper half of UTF-16.
+                            let unit = param.unwrap_or(0).rem_euclid(0x10000) as u32;
+                            let c = char::from_u32(unit).unwrap_or('\u{fffd}');
+                            emit(&group, c.encode_utf8(&mut [0; 4]), &mut text);
+                            skip = group.fallback;
+                            continue;
+                        }
+                        "emdash" => emit(&group, "—", &mut text),
+                        "endash" => emit(&group, "–", &mut text),
+                        "lquote" => emit(&group, "‘", &mut text),
+                        "rquote" => emit(&group, "’", &mut text),
+                        "ldblquote" => emit(&group, "“", &mut text),
+                        "rdblquote" => emit(&group, "”", &mut text),
+                        word if HIDDEN.contains(&word) => group.hidden = true,
+                        _ => {}
+                    }
+                }
+                Some('\'') => {
+                    chars.next();
+                    let hex: String = chars.by_ref().take(2).collect();
+                    if skip > 0 {
+                        skip -= 1;
+                        continue;
+                    }
+                    if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                        emit(&group, cp1252(byte).encode_utf8(&mut [0; 4]), &mut text);
+                    }
+                }
+                Some('*') => {
+                    chars.next();
+                    group.hidden = true;
+                }
+                Some('~') => {
+                    chars.next();
+                    emit(&group, "\u{a0}", &mut text);
+                }
+                Some('_') => {
+                    chars.next();
+                    emit(&group, "-", &mut text);
+                }
+                Some(c @ ('\\' | '{' | '}')) => {
+                    chars.next();
+                    emit(&group, c.encode_utf8(&mut [0; 4]), &mut text);
+                }
+                // `\-` (optional hyphen) and unknown symbols.
+                Some(_) => {
+                    chars.next();
+                }
+                None => {}
+            },
+            c => {
+                if skip > 0 {
+                    skip -= 1;
+                    continue;
+                }
+                emit(&group, c.encode_utf8(&mut [0; 4]), &mut text);
+            }
+        }
+        skip = 0;
+    }
+    // Trailing superscripts, flushed by an empty shown text.
+    emit(&Group::default(), "", &mut text);
+    text.truncate(text.trim_end().len());
+    styled.trim_end();
+    (text, styled, words)
+}
+
+/// What a module's `Details` row says about it, with lowercased keys.
+#[derive(Debug, Default)]
+pub(super) struct Details(HashMap<String, String>);
+
+impl Details {
+    pub(super) fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|v| v.trim()).filter(|v| !v.is_empty())
+    }
+}
+
+fn value_text(value: ValueRef<'_>) -> Option<String> {
+    match value {
+        ValueRef::Text(text) => Some(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Integer(n) => Some(n.to_string()),
+        ValueRef::Real(n) => Some(n.to_string()),
+        ValueRef::Null | ValueRef::Blob(_) => None,
+    }
+}
+
+pub(super) fn open(path: &Path) -> Result<Connection, DbError> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| DbError::io(format!("Failed to open {}: {e}", path.display())))
+}
+
+/// A SQLite Bible format laid out like e-Sword's: MySword modules have the
+/// same tables, with theWord markup in `Scripture` instead of RTF.
+pub(super) struct Format {
+    pub source_format: &'static str,
+    /// Name of the format in messages.
+    pub label: &'static str,
+    /// Add the verse at chapter:verse with this `Scripture` to a book.
+    pub add: fn(&mut Book, i64, i64, &str),
+    /// Plain text of the markup in `Details.Comments`.
+    pub plain: fn(&str) -> String,
+}
+
+pub(super) const ESWORD: Format = Format {
+    source_format: "esword",
+    label: "e-Sword",
+    add: add_verse,
+    plain: |src| read_rtf(src, None).0,
+};
+
+fn add_verse(book: &mut Book, chapter: i64, verse: i64, scripture: &str) {
+    let prefix = import::strongs_prefix(&book.id);
+    let (text, styled, words) = read_rtf(scripture, Some(prefix));
+    book.verses.push(Verse {
+        chapter,
+        verse,
+        through: verse,
+        text,
+        html: styled.html(),
+        words,
+        alignments: Vec::new(),
+        red_letter: styled.spans("wj"),
+        paragraphs: Vec::new(),
+        line: None,
+    });
+}
+
+pub(super) fn read_details(conn: &Connection) -> Details {
+    let mut details = Details::default();
+    // Older modules have no Details table; the file name stands in.
+    let Ok(mut stmt) = conn.prepare("SELECT * FROM Details LIMIT 1") else {
+        return details;
+    };
+    let columns: Vec<String> = stmt
+        .column_names()
+        .iter()
+        .map(|c| c.to_lowercase())
+        .collect();
+    let _ = stmt.query_row([], |row| {
+        for (i, column) in columns.iter().enumerate() {
+            if let Some(value) = value_text(row.get_ref(i)?) {
+                details.0.insert(column.clone(), value);
+            }
+        }
+        Ok(())
+    });
+    details
+}
+
+fn read_bible(path: &Path, format: &Format, warnings: &mut Warnings) -> Result<Vec<Book>, DbError> {
+    let conn = open(path)?;
+    let file = import::file_label(path);
+    let not_a_bible = |e: rusqlite::Error| {
+        DbError::invalid(format!("{file} is not a {} Bible: {e}", format.label))
+    };
+    let mut stmt = conn
+        .prepare("SELECT Book, Chapter, Verse, Scripture FROM Bible ORDER BY Book, Chapter, Verse")
+        .map_err(not_a_bible)?;
+    let mut rows = stmt.query([]).map_err(not_a_bible)?;
+    let mut books: Vec<Book> = Vec::new();
+    let mut skipped: Vec<i64> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let (number, chapter, verse): (i64, i64, i64) = (row.get(0)?, row.get(1)?, row.get(2)?);
+        let Some(id) = usize::try_from(number)
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| books::BOOKS.get(i))
+        else {
+            if !skipped.contains(&number) {
+                skipped.push(number);
+                warnings.push(
+                    &file,
+                    None,
+                    format!("Book {number} is not one of the 66 canonical books; skipped"),
+                );
+            }
+            continue;
+        };
+        if chapter < 1 || verse < 1 {
+            warnings.push(
+                &file,
+                None,
+                format!("{} {chapter}:{verse} is not a verse; skipped", id.osis),
+            );
+            continue;
+        }
+        let scripture = value_text(row.get_ref(3)?).unwrap_or_default();
+        if books.last().map(|b| b.id.as_str()) != Some(id.osis) {
+            books.push(Book {
+                id: id.osis.to_string(),
+                file: file.clone(),
+                ..Default::default()
+            });
+        }
+        let book = books.last_mut().expect("pushed above");
+        (format.add)(book, chapter, verse, &scripture);
+    }
+    Ok(books)
+}
+
+/// Import the module at `path` in `format` and mount it. The translation id
+/// defaults to the module's abbreviation and its name to its title (or
+/// description).
+pub(super) fn import_module(
+    app: &tauri::AppHandle,
+    path: &Path,
+    module_id: Option<String>,
+    name: Option<String>,
+    format: &Format,
+) -> Result<ImportReport, DbError> {
+    let details = read_details(&open(path)?);
+    let stem = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.split('.').next())
+        .unwrap_or_default()
+        .to_string();
+    let module_id = module_id
+        .or_else(|| details.get("abbreviation").map(str::to_string))
+        .unwrap_or(stem);
+    let name = name
+        .or_else(|| {
+            details
+                .get("title")
+                .or_else(|| details.get("description"))
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| module_id.clone());
+
+    let mut warnings = Warnings::default();
+    let books = read_bible(path, format, &mut warnings)?;
+    import::progress(app, &module_id, &import::file_label(path), 1, 1);
+
+    let comments = details.get("comments").map(format.plain);
+    let mut info: Vec<(&str, &str)> = ["abbreviation", "description", "version", "language"]
+        .into_iter()
+        .filter_map(|key| details.get(key).map(|value| (key, value)))
+        .collect();
+    if let Some(comments) = comments.as_deref().filter(|c| !c.is_empty()) {
+        info.push(("comments", comments));
+    }
+    if details.get("righttoleft") == Some("1") {
+        info.push(("direction", "rtl"));
+    }
+    import::install(
+        app,
+        &module_id,
+        &name,
+        format.source_format,
+        &info,
+        books,
+        warnings,
+    )
+}
+
+/// Import an e-Sword `.bblx` Bible and mount it; see `import_module` for the
+/// id and name it gets.
+#[command]
+pub async fn import_esword(
+    app: tauri::AppHandle,
+    path: String,
+    module_id: Option<String>,
+    name: Option<String>,
+) -> Result<ImportReport, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        import_module(&app, Path::new(&path), module_id, name, &ESWORD)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn reduces_rtf_to_text_and_styles() {
+        let (text, styled, words) = read_rtf(
+            r"{\rtf1 In the beginning{\cf11\super H7225} God {\i created}{\super 1254}\par the heaven caf\'e9 \u8212? \'93x\'94 \b bold\b0 .}",
+            Some('H'),
+        );
+        assert_eq!(
+            text,
+            "In the beginning God created the heaven caf\u{e9} \u{2014} \u{201c}x\u{201d} bold."
+        );
+        assert_eq!(
+            styled.html().unwrap(),
+            "In the beginning God <span class=\"char-it\">created</span> the heaven caf\u{e9} \
+             \u{2014} \u{201c}x\u{201d} <span class=\"char-bd\">bold</span>."
+        );
+        let tagged: Vec<(usize, &str, &str)> = words
+            .iter()
+            .map(|w| (w.position, w.text.as_str(), w.strongs[0].as_str()))
+            .collect();
+        assert_eq!(
+            tagged,
+            [(7, "beginning", "H7225"), (21, "created", "H1254")]
+        );
+
+        let (text, styled, _) = read_rtf(
+            r"{\fonttbl{\f0 Arial;}}{\*\bkmkstart x}Plain\{ text\}",
+            None,
+        );
+        assert_eq!(text, "Plain{ text}");
+        assert!(styled.html().is_none());
+    }
+
+    #[test]
+    fn reads_bible_rows_and_skips_other_books() {
+        let dir = ScratchDir::new("esword");
+        let path = dir.join("kjv.bblx");
+        let _ = std::fs::remove_file(&path);
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            r"CREATE TABLE Bible (Book INT, Chapter INT, Verse INT, Scripture TEXT);
+              CREATE TABLE Details (Title NVARCHAR(255), Abbreviation NVARCHAR(50),
+                                    Description TEXT, RightToLeft BOOL);
+              INSERT INTO Details VALUES ('King James Version', 'KJV', 'KJV with Strongs', 0);
+              INSERT INTO Bible VALUES (1, 1, 2, 'And the earth was {\i without form}');
+              INSERT INTO Bible VALUES (1, 1, 1, 'In the beginning');
+              INSERT INTO Bible VALUES (43, 11, 35, 'Jesus wept.');
+              INSERT INTO Bible VALUES (67, 1, 1, 'Tobit');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let details = read_details(&open(&path).unwrap());
+        assert_eq!(details.get("title"), Some("King James Version"));
+        assert_eq!(details.get("abbreviation"), Some("KJV"));
+
+        let mut warnings = Warnings::default();
+        let books = read_bible(&path, &ESWORD, &mut warnings).unwrap();
+        assert_eq!(warnings.list.len(), 1, "{:?}", warnings.list);
+        let ids: Vec<&str> = books.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, ["Gen", "John"]);
+        let genesis: Vec<(i64, &str)> = books[0]
+            .verses
+            .iter()
+            .map(|v| (v.verse, v.text.as_str()))
+            .collect();
+        assert_eq!(
+            genesis,
+            [
+                (1, "In the beginning"),
+                (2, "And the earth was without form")
+            ]
+        );
+        assert_eq!(
+            books[0].verses[1].html.as_deref(),
+            Some("And the earth was <span class=\"char-it\">without form</span>")
+        );
+        assert!(read_bible(&dir.join("missing.bblx"), &ESWORD, &mut warnings).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Footnotes of mounted translations: read with their chapter, and
+//! searched through each content file's `footnote_index`.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::command;
+
+use super::{has_table, list_mounted_content, MountedContent};
+use crate::db::search::{match_expression, split_highlights, DEFAULT_LIMIT};
+use crate::db::{self, DbError};
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ChapterFootnote {
+    pub caller: String,
+    /// Character offset in the verse text the note is anchored after.
+    pub position: usize,
+    pub text: String,
+    /// `footnote`, `translation`, `variant` or `study`.
+    pub kind: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FootnoteHit {
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    pub book: String,
+    pub chapter: i64,
+    pub verse: i64,
+    /// The whole footnote.
+    pub text: String,
+    /// Excerpt around the best match.
+    pub snippet: String,
+    /// `[start, end)` of each matched term within `snippet`, in UTF-16 code
+    /// units.
+    pub highlights: Vec<(usize, usize)>,
+    /// bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+/// Footnotes of one chapter from an attached content schema, keyed by verse
+/// number. Files from before format 2 have none, and ones from before
+/// format 8 don't tell their kinds apart.
+pub(crate) fn chapter_footnotes(
+    conn: &Connection,
+    schema: &str,
+    book: &str,
+    chapter: i64,
+) -> Result<BTreeMap<i64, Vec<ChapterFootnote>>, DbError> {
+    let mut notes: BTreeMap<i64, Vec<ChapterFootnote>> = BTreeMap::new();
+    if !has_table(conn, schema, "footnotes")? {
+        return Ok(notes);
+    }
+    let has_kind: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('footnotes', ?1) WHERE name = 'kind'",
+        [schema],
+        |row| row.get(0),
+    )?;
+    let kind = if has_kind { "kind" } else { "'footnote'" };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT verse, caller, position, text, {kind} FROM \"{schema}\".footnotes
+         WHERE book = ?1 AND chapter = ?2 ORDER BY verse, seq"
+    ))?;
+    let rows = stmt.query_map(params![book, chapter], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            ChapterFootnote {
+                caller: row.get(1)?,
+                position: row.get::<_, i64>(2)? as usize,
+                text: row.get(3)?,
+                kind: row.get(4)?,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (verse, note) = row?;
+        notes.entry(verse).or_default().push(note);
+    }
+    Ok(notes)
+}
+
+/// Search the footnotes of `mounted` translations, best matches first.
+pub(crate) fn search(
+    conn: &Connection,
+    query: &str,
+    mounted: &[MountedContent],
+    limit: u32,
+) -> Result<Vec<FootnoteHit>, DbError> {
+    let Some(expression) = match_expression(query) else {
+        return Ok(Vec::new());
+    };
+    let mut hits = Vec::new();
+    for content in mounted {
+        let schema = &content.schema;
+        // Files from before format 8 aren't indexed.
+        if !has_table(conn, schema, "footnote_index")? {
+            continue;
+        }
+        let mut stmt = conn.prepare(&format!(
+            "SELECT book, chapter, verse, text,
+                    snippet(footnote_index, 0, char(2), char(3), '…', 24), rank
+             FROM \"{schema}\".footnote_index
+             WHERE footnote_index MATCH ?1
+             ORDER BY rank
+             LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![expression, limit], |row| {
+            let (snippet, highlights) = split_highlights(&row.get::<_, String>(4)?);
+            Ok(FootnoteHit {
+                module_id: content.module_id.clone(),
+                book: row.get(0)?,
+                chapter: row.get(1)?,
+                verse: row.get(2)?,
+                text: row.get(3)?,
+                snippet,
+                highlights,
+                rank: row.get(5)?,
+            })
+        })?;
+        for hit in rows {
+            hits.push(hit?);
+        }
+    }
+    hits.sort_by(|a, b| a.rank.total_cmp(&b.rank));
+    hits.truncate(limit as usize);
+    Ok(hits)
+}
+
+/// Search the footnotes of every mounted translation, or only of
+/// `module_id`.
+#[command]
+pub async fn search_footnotes(
+    app: tauri::AppHandle,
+    query: String,
+    module_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<FootnoteHit>, DbError> {
+    let mounted: Vec<MountedContent> = list_mounted_content()
+        .into_iter()
+        .filter(|m| module_id.as_ref().is_none_or(|id| *id == m.module_id))
+        .collect();
+    db::with_reader(&app, move |conn| {
+        search(conn, &query, &mounted, limit.unwrap_or(DEFAULT_LIMIT))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::import::{self, Book, Footnote, Verse, Warnings};
+    use crate::test_util::ScratchDir;
+
+    fn note(verse: i64, position: usize, text: &str, kind: &str) -> Footnote {
+        Footnote {
+            chapter: 1,
+            verse,
+            caller: "a".into(),
+            position,
+            text: text.into(),
+            kind: kind.into(),
+        }
+    }
+
+    #[test]
+    fn reads_and_searches_footnotes() {
+        let dir = ScratchDir::new("footnotes");
+        let path = dir.join("web.db");
+        let verse = |verse, text: &str| Verse {
+            chapter: 1,
+            verse,
+            through: verse,
+            text: text.into(),
+            ..Default::default()
+        };
+        let book = Book {
+            id: "John".into(),
+            verses: vec![
+                verse(1, "In the beginning was the Word,"),
+                verse(18, "No one has seen God at any time."),
+            ],
+            footnotes: vec![
+                note(1, 30, "Or, Logos", "translation"),
+                note(
+                    18,
+                    32,
+                    "Some manuscripts read “the only begotten Son”",
+                    "variant",
+                ),
+            ],
+            ..Default::default()
+        };
+        import::write(
+            &path,
+            "web",
+            "WEB",
+            "usfm",
+            &[],
+            vec![book],
+            Warnings::default(),
+        )
+        .unwrap();
+        let conn = Connection::open(&path).unwrap();
+
+        let notes = chapter_footnotes(&conn, "main", "John", 1).unwrap();
+        assert_eq!(notes[&1][0].text, "Or, Logos");
+        assert_eq!(
+            (notes[&18][0].position, notes[&18][0].kind.as_str()),
+            (32, "variant")
+        );
+
+        let web = MountedContent {
+            module_id: "web".into(),
+            schema: "main".into(),
+            path: path.display().to_string(),
+            verses: 2,
+        };
+        let hits = search(&conn, "manuscripts", std::slice::from_ref(&web), 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].module_id.as_str(), hits[0].verse), ("web", 18));
+        let (start, end) = hits[0].highlights[0];
+        assert_eq!(&hits[0].snippet[start..end], "manuscripts");
+        assert!(search(&conn, "grace", &[web], 10).unwrap().is_empty());
+        drop(conn);
+    }
+}
@@ -0,0 +1,865 @@
+//! Shared tail of the translation importers.
+//!
+//! A format parser turns its files into `Book`s and collects warnings about
+//! anything it had to guess at or drop; `write` checks the books and builds
+//! the content file from them, and `finish` swaps it in for an already
+//! mounted translation. Importers report progress per file with
+//! `content://import-progress`.
+
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+
+use super::{books, content_path, create, mount, mounted, record_checksums, set_info};
+use crate::db::{self, DbError, DbErrorKind};
+
+/// Emitted with an `ImportProgress` as each source file is read.
+pub(crate) const PROGRESS_EVENT: &str = "content://import-progress";
+
+/// Warnings kept in a report; the rest are only counted.
+const MAX_WARNINGS: usize = 200;
+
+#[derive(Debug, Default)]
+pub(crate) struct Verse {
+    pub chapter: i64,
+    pub verse: i64,
+    /// Last verse of a bridge like `\v 4-6`; `verse` otherwise.
+    pub through: i64,
+    pub text: String,
+    /// `text` with its paragraph and character styles (see `Styled`), for
+    /// formats that carry them.
+    pub html: Option<String>,
+    /// Words the source tags with Strong's numbers or morphology, in order.
+    pub words: Vec<Word>,
+    /// Original-language words the source aligns with words of `text`, in
+    /// order.
+    pub alignments: Vec<Alignment>,
+    /// Words of Christ, as (start, end) character offsets into `text`.
+    pub red_letter: Vec<(usize, usize)>,
+    /// Paragraphs and poetry lines beginning in the verse, as (character
+    /// offset into `text`, USFM paragraph style: `p`, `m`, `q1`, `q2`, `b`,
+    /// ...), which other formats map onto.
+    pub paragraphs: Vec<(usize, String)>,
+    /// Source line, for warnings.
+    pub line: Option<usize>,
+}
+
+/// A word of a verse tagged with Strong's numbers, morphology or both.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Word {
+    /// Character offset of the word in the verse text.
+    pub position: usize,
+    pub text: String,
+    /// Normalized numbers (`H7225`, `G3056`); a word can carry several.
+    pub strongs: Vec<String>,
+    /// English gloss, for sources that give one.
+    pub gloss: Option<String>,
+    /// Morphology code without its scheme (`V-PAI-3S`, `HVqp3ms`; see
+    /// `morphology`).
+    pub morph: Option<String>,
+}
+
+/// An original-language word aligned with a span of verse text, as
+/// aligned USFM gives them (`\zaln-s |x-content="λόγος" ...\*`).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Alignment {
+    /// Character offset of the aligned span in the verse text.
+    pub position: usize,
+    pub text: String,
+    /// The word in Hebrew, Aramaic or Greek.
+    pub original: String,
+    pub lemma: Option<String>,
+    pub strongs: Vec<String>,
+    pub morph: Option<String>,
+    /// Which occurrence of `original` in the source verse it is (from 1),
+    /// telling repeated words apart.
+    pub occurrence: i64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Heading {
+    pub chapter: i64,
+    /// The verse the heading comes before.
+    pub verse: i64,
+    /// USFM paragraph style (`s1`, `ms`, `d`, ...), which other formats map onto.
+    pub style: String,
+    pub text: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct Footnote {
+    pub chapter: i64,
+    pub verse: i64,
+    pub caller: String,
+    /// Character offset in the verse text the note is anchored after.
+    pub position: usize,
+    pub text: String,
+    /// `footnote`, `translation` (translator's note), `variant` (textual
+    /// apparatus) or `study`; see `note_kind`.
+    pub kind: String,
+}
+
+/// Paragraph styles read as plain prose: verses in them get no wrapper.
+const PROSE_STYLES: &[&str] = &["p", "m", "nb"];
+
+/// Character styles kept in formatted verses; text in others is kept
+/// unstyled.
+const CHAR_STYLES: &[&str] = &[
+    "wj", "nd", "add", "it", "bd", "bdit", "em", "sc", "sup", "qs", "tl", "pn", "k", "sig", "sls",
+    "dc", "ord", "bk", "qt",
+];
+
+#[derive(Debug, PartialEq)]
+struct Run {
+    para: String,
+    chars: Vec<String>,
+    text: String,
+}
+
+/// A verse's text in runs of the same styles, rendered as the app's verse
+/// `html`: each paragraph the verse spans (other than plain prose) is a
+/// `<span class="para-q1">`, with character styles inside it as
+/// `<span class="char-wj">`. Styles are USFM marker names, which USX and the
+/// other formats map onto.
+#[derive(Debug, Default)]
+pub(crate) struct Styled {
+    runs: Vec<Run>,
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Styled {
+    /// Add `text` in paragraph style `para` inside character styles `chars`
+    /// (outermost first), keeping only the styles the app shows.
+    pub(crate) fn push(&mut self, para: &str, chars: &[&str], text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let para = if PROSE_STYLES.contains(&para) {
+            ""
+        } else {
+            para
+        };
+        let chars: Vec<String> = chars
+            .iter()
+            .filter(|c| CHAR_STYLES.contains(c))
+            .map(|c| c.to_string())
+            .collect();
+        match self.runs.last_mut() {
+            Some(run) if run.para == para && run.chars == chars => run.text.push_str(text),
+            _ => self.runs.push(Run {
+                para: para.to_string(),
+                chars,
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    /// Drop trailing whitespace, like the verse text's.
+    pub(crate) fn trim_end(&mut self) {
+        while let Some(run) = self.runs.last_mut() {
+            run.text.truncate(run.text.trim_end().len());
+            if !run.text.is_empty() {
+                break;
+            }
+            self.runs.pop();
+        }
+    }
+
+    /// Character ranges of the text in character style `style`, e.g. `wj`
+    /// for the words of Christ.
+    pub(crate) fn spans(&self, style: &str) -> Vec<(usize, usize)> {
+        let mut text = String::new();
+        let mut spans = Vec::new();
+        for run in &self.runs {
+            let start = text.len();
+            text.push_str(&run.text);
+            if run.chars.iter().any(|c| c == style) {
+                red_letter(&mut spans, &text, start);
+            }
+        }
+        spans
+    }
+
+    /// The verse as HTML, or `None` if it has no styles to show.
+    pub(crate) fn html(&self) -> Option<String> {
+        if self
+            .runs
+            .iter()
+            .all(|run| run.para.is_empty() && run.chars.is_empty())
+        {
+            return None;
+        }
+        let mut html = String::new();
+        let mut para: Option<&str> = None;
+        for run in &self.runs {
+            if para != Some(run.para.as_str()) {
+                if para.is_some_and(|p| !p.is_empty()) {
+                    html.push_str("</span>");
+                }
+                if !run.para.is_empty() {
+                    html.push_str(&format!("<span class=\"para-{}\">", run.para));
+                }
+                para = Some(&run.para);
+            }
+            for style in &run.chars {
+                html.push_str(&format!("<span class=\"char-{style}\">"));
+            }
+            html.push_str(&escape_html(&run.text));
+            html.push_str(&"</span>".repeat(run.chars.len()));
+        }
+        if para.is_some_and(|p| !p.is_empty()) {
+            html.push_str("</span>");
+        }
+        Some(html)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Book {
+    /// OSIS id (see `books`).
+    pub id: String,
+    /// The translation's own name for the book, when the source has one.
+    pub name: Option<String>,
+    pub verses: Vec<Verse>,
+    pub headings: Vec<Heading>,
+    pub footnotes: Vec<Footnote>,
+    /// Source file, for warnings.
+    pub file: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ImportWarning {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Warnings {
+    pub list: Vec<ImportWarning>,
+    pub dropped: usize,
+}
+
+impl Warnings {
+    pub(crate) fn push(&mut self, file: &str, line: Option<usize>, message: impl Into<String>) {
+        if self.list.len() == MAX_WARNINGS {
+            self.dropped += 1;
+            return;
+        }
+        self.list.push(ImportWarning {
+            file: file.to_string(),
+            line,
+            message: message.into(),
+        });
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    pub path: String,
+    /// OSIS ids of the imported books, in canonical order.
+    pub books: Vec<String>,
+    pub verses: usize,
+    pub headings: usize,
+    pub footnotes: usize,
+    pub warnings: Vec<ImportWarning>,
+    /// Warnings past the ones listed.
+    #[serde(rename = "moreWarnings")]
+    pub more_warnings: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    pub file: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+pub(crate) fn progress(
+    app: &tauri::AppHandle,
+    module_id: &str,
+    file: &str,
+    done: usize,
+    total: usize,
+) {
+    let _ = app.emit(
+        PROGRESS_EVENT,
+        ImportProgress {
+            module_id: module_id.to_string(),
+            file: file.to_string(),
+            done,
+            total,
+        },
+    );
+}
+
+/// `path` as shown in warnings: just the file name.
+pub(crate) fn file_label(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// The text of `path`, or `None` (with a warning) if it can't be read.
+/// Bytes that aren't UTF-8 are replaced, with a warning, rather than failing
+/// the whole import.
+pub(crate) fn read_text(path: &Path, warnings: &mut Warnings) -> Option<String> {
+    let file = file_label(path);
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warnings.push(&file, None, format!("Could not read the file: {e}"));
+            return None;
+        }
+    };
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => {
+            warnings.push(
+                &file,
+                None,
+                "Not UTF-8; unreadable characters were replaced",
+            );
+            String::from_utf8_lossy(e.as_bytes()).into_owned()
+        }
+    };
+    Some(match text.strip_prefix('\u{feff}') {
+        Some(rest) => rest.to_string(),
+        None => text,
+    })
+}
+
+/// Append `text`, collapsing runs of whitespace (other than no-break
+/// spaces) to one space.
+pub(crate) fn push_text(buf: &mut String, text: &str) {
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !buf.is_empty() && !buf.ends_with(' ') {
+                buf.push(' ');
+            }
+        } else {
+            buf.push(c);
+        }
+    }
+}
+
+/// The kind of note an OSIS-style `type` (`translation`, `variant`,
+/// `x-studynote`, ...) or USFM marker (`f`, `fe`, `ef`) names.
+pub(crate) fn note_kind(name: Option<&str>) -> String {
+    match name.unwrap_or_default() {
+        "translation" => "translation",
+        "variant" | "x-variant" | "alternative" => "variant",
+        "study" | "x-studynote" | "explanation" | "ef" => "study",
+        _ => "footnote",
+    }
+    .to_string()
+}
+
+/// `H` or `G`: the lexicon bare Strong's numbers in `book` refer to.
+pub(crate) fn strongs_prefix(book: &str) -> char {
+    match books::position(book) {
+        Some(position) if position > 39 => 'G',
+        _ => 'H',
+    }
+}
+
+/// The Strong's numbers in `raw`, normalized to `H7225` / `G3056`: sources
+/// write them `strong:H07225`, `H7225`, `WH7225` or (in a testament that
+/// implies the lexicon) `7225`, separated by spaces, commas or `|`. Bare
+/// numbers take `prefix`, or are skipped without one.
+pub(crate) fn strongs_numbers(raw: &str, prefix: Option<char>) -> Vec<String> {
+    let mut numbers: Vec<String> = Vec::new();
+    for part in raw.split(|c: char| c.is_whitespace() || c == ',' || c == '|') {
+        let part = part.rsplit(':').next().unwrap_or_default();
+        let part = part.strip_prefix(['W', 'w']).unwrap_or(part);
+        let (letter, digits) = match part.chars().next() {
+            Some(c @ ('H' | 'G' | 'h' | 'g')) => (Some(c.to_ascii_uppercase()), &part[1..]),
+            _ => (prefix, part),
+        };
+        let Some(letter) = letter else {
+            continue;
+        };
+        let number = digits.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+        let suffix = &digits[number.len()..];
+        let Ok(value) = number.parse::<u32>() else {
+            continue;
+        };
+        if value == 0 || suffix.len() > 1 {
+            continue;
+        }
+        let normalized = format!("{letter}{value}{suffix}");
+        if !numbers.contains(&normalized) {
+            numbers.push(normalized);
+        }
+    }
+    numbers
+}
+
+/// A raw morphology attribute reduced to its code: `robinson:N-NSM` to
+/// `N-NSM`. Sources that give several (one per lemma) keep the first.
+pub(crate) fn morph_code(raw: &str) -> Option<String> {
+    raw.split_whitespace()
+        .map(super::morphology::strip_scheme)
+        .find(|code| !code.is_empty())
+        .map(str::to_string)
+}
+
+/// Tag the text of `verse` from byte `start` on (a word that was just
+/// pushed) with `strongs` and `morph`.
+pub(crate) fn tag_word(
+    words: &mut Vec<Word>,
+    verse: &str,
+    start: usize,
+    strongs: Vec<String>,
+    gloss: Option<String>,
+    morph: Option<String>,
+) {
+    let Some(tail) = verse.get(start..) else {
+        return;
+    };
+    let text = tail.trim();
+    if text.is_empty() || (strongs.is_empty() && morph.is_none()) {
+        return;
+    }
+    let lead = tail.len() - tail.trim_start().len();
+    words.push(Word {
+        position: verse[..start + lead].chars().count(),
+        text: text.to_string(),
+        strongs,
+        gloss: gloss.filter(|g| !g.trim().is_empty()),
+        morph,
+    });
+}
+
+/// Align the text of `verse` from byte `start` on (what was pushed since
+/// the alignment opened) with an original-language word, inserted at `at`
+/// so nested alignments keep the order they opened in.
+pub(crate) fn align(
+    alignments: &mut Vec<Alignment>,
+    at: usize,
+    verse: &str,
+    start: usize,
+    mut alignment: Alignment,
+) {
+    let Some(tail) = verse.get(start..) else {
+        return;
+    };
+    let text = tail.trim();
+    if text.is_empty() || alignment.original.trim().is_empty() {
+        return;
+    }
+    let lead = tail.len() - tail.trim_start().len();
+    alignment.position = verse[..start + lead].chars().count();
+    alignment.text = text.to_string();
+    alignments.insert(at.min(alignments.len()), alignment);
+}
+
+/// Mark the text of `verse` from byte `start` on (what was pushed since the
+/// words of Christ began) as red letter, joining the span before it when
+/// only whitespace lies between.
+pub(crate) fn red_letter(spans: &mut Vec<(usize, usize)>, verse: &str, start: usize) {
+    let Some(tail) = verse.get(start..) else {
+        return;
+    };
+    let text = tail.trim();
+    if text.is_empty() {
+        return;
+    }
+    let lead = tail.len() - tail.trim_start().len();
+    let from = verse[..start + lead].chars().count();
+    let to = from + text.chars().count();
+    match spans.last_mut() {
+        Some(last)
+            if verse
+                .chars()
+                .skip(last.1)
+                .take(from.saturating_sub(last.1))
+                .all(char::is_whitespace) =>
+        {
+            last.1 = last.1.max(to);
+        }
+        _ => spans.push((from, to)),
+    }
+}
+
+/// Tag the last word of `verse` with `strongs` and `morph`, for formats
+/// whose tags follow the word they belong to. Tags in a row add to the
+/// same word.
+pub(crate) fn tag_last_word(
+    words: &mut Vec<Word>,
+    verse: &str,
+    strongs: Vec<String>,
+    morph: Option<String>,
+) {
+    if strongs.is_empty() && morph.is_none() {
+        return;
+    }
+    let verse = verse.trim_end();
+    let start = verse.rfind(char::is_whitespace).map_or(0, |at| {
+        at + verse[at..].chars().next().map_or(1, char::len_utf8)
+    });
+    let position = verse[..start].chars().count();
+    match words.last_mut() {
+        Some(word) if word.position == position => {
+            for number in strongs {
+                if !word.strongs.contains(&number) {
+                    word.strongs.push(number);
+                }
+            }
+            if word.morph.is_none() {
+                word.morph = morph;
+            }
+        }
+        _ if start < verse.len() => words.push(Word {
+            position,
+            text: verse[start..].to_string(),
+            strongs,
+            gloss: None,
+            morph,
+        }),
+        _ => {}
+    }
+}
+
+/// Drop duplicate verses and warn about gaps, out-of-order and empty ones.
+pub(crate) fn check(book: &mut Book, warnings: &mut Warnings) {
+    let mut seen = HashSet::new();
+    let mut last: Option<(i64, i64)> = None;
+    book.verses.retain(|v| {
+        if !seen.insert((v.chapter, v.verse)) {
+            warnings.push(
+                &book.file,
+                v.line,
+                format!(
+                    "{} {}:{} appears twice; kept the first",
+                    book.id, v.chapter, v.verse
+                ),
+            );
+            return false;
+        }
+        match last {
+            Some((chapter, through)) if chapter == v.chapter && v.verse != through + 1 => {
+                warnings.push(
+                    &book.file,
+                    v.line,
+                    format!(
+                        "{} {}:{} follows verse {through}",
+                        book.id, v.chapter, v.verse
+                    ),
+                );
+            }
+            Some((chapter, _)) if chapter > v.chapter => warnings.push(
+                &book.file,
+                v.line,
+                format!(
+                    "{} chapter {} follows chapter {chapter}",
+                    book.id, v.chapter
+                ),
+            ),
+            _ => {}
+        }
+        if v.text.is_empty() {
+            warnings.push(
+                &book.file,
+                v.line,
+                format!("{} {}:{} has no text", book.id, v.chapter, v.verse),
+            );
+        }
+        last = Some((v.chapter, v.through.max(v.verse)));
+        true
+    });
+}
+
+/// Check `books` and write them to a new content file at `path`, replacing
+/// any file already there only once the new one is complete. `info` is
+/// stored in `content_info` alongside the source format.
+pub(crate) fn write(
+    path: &Path,
+    module_id: &str,
+    name: &str,
+    source_format: &str,
+    info: &[(&str, &str)],
+    mut books: Vec<Book>,
+    mut warnings: Warnings,
+) -> Result<ImportReport, DbError> {
+    let mut ids = HashSet::new();
+    books.retain(|book| {
+        let first = ids.insert(book.id.clone());
+        if !first {
+            warnings.push(
+                &book.file,
+                None,
+                format!("{} was already imported from another file", book.id),
+            );
+        }
+        first
+    });
+    books.sort_by_key(|book| books::position(&book.id));
+    for book in &mut books {
+        check(book, &mut warnings);
+    }
+    let verses: usize = books.iter().map(|b| b.verses.len()).sum();
+    if verses == 0 {
+        return Err(DbError::invalid("No verses found to import"));
+    }
+
+    let partial = PathBuf::from(format!("{}.partial", path.display()));
+    let written = (|| {
+        let mut conn = create(&partial, module_id, name)?;
+        set_info(&conn, "source_format", source_format)?;
+        for (key, value) in info {
+            set_info(&conn, key, value)?;
+        }
+        let tx = conn.transaction()?;
+        {
+            let mut book_row =
+                tx.prepare("INSERT INTO books (book, position, name) VALUES (?1, ?2, ?3)")?;
+            let mut verse_row = tx.prepare(
+                "INSERT INTO verses (book, chapter, verse, text) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            let mut html_row = tx.prepare(
+                "INSERT INTO verse_html (book, chapter, verse, html) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            let mut heading_row = tx.prepare(
+                "INSERT INTO headings (book, chapter, verse, seq, style, text)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            let mut footnote_row = tx.prepare(
+                "INSERT INTO footnotes (book, chapter, verse, seq, caller, position, text, kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let mut footnote_index_row = tx.prepare(
+                "INSERT INTO footnote_index (text, book, chapter, verse, seq)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let mut alignment_row = tx.prepare(
+                "INSERT INTO alignments
+                 (book, chapter, verse, seq, position, text, original, lemma, strongs, morph,
+                  occurrence)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            )?;
+            let mut red_letter_row = tx.prepare(
+                "INSERT INTO red_letter (book, chapter, verse, seq, position, length)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            let mut paragraph_row = tx.prepare(
+                "INSERT INTO paragraphs (book, chapter, verse, seq, position, style)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            let mut word_row = tx.prepare(
+                "INSERT INTO words (book, chapter, verse, seq, position, text, strongs, gloss, morph)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for book in &books {
+                let position = books::position(&book.id).unwrap_or_default();
+                let name = book
+                    .name
+                    .clone()
+                    .or_else(|| {
+                        position
+                            .checked_sub(1)
+                            .map(|i| books::BOOKS[i].name.to_string())
+                    })
+                    .unwrap_or_else(|| book.id.clone());
+                book_row.execute(params![book.id, position as i64, name])?;
+                for v in &book.verses {
+                    verse_row.execute(params![book.id, v.chapter, v.verse, v.text])?;
+                    if let Some(html) = &v.html {
+                        html_row.execute(params![book.id, v.chapter, v.verse, html])?;
+                    }
+                    for (seq, w) in v.words.iter().enumerate() {
+                        word_row.execute(params![
+                            book.id,
+                            v.chapter,
+                            v.verse,
+                            seq as i64,
+                            w.position as i64,
+                            w.text,
+                            w.strongs.join(" "),
+                            w.gloss,
+                            w.morph
+                        ])?;
+                    }
+                    for (seq, (start, end)) in v.red_letter.iter().enumerate() {
+                        red_letter_row.execute(params![
+                            book.id,
+                            v.chapter,
+                            v.verse,
+                            seq as i64,
+                            *start as i64,
+                            (end - start) as i64
+                        ])?;
+                    }
+                    for (seq, (position, style)) in v.paragraphs.iter().enumerate() {
+                        paragraph_row.execute(params![
+                            book.id,
+                            v.chapter,
+                            v.verse,
+                            seq as i64,
+                            *position as i64,
+                            style
+                        ])?;
+                    }
+                    for (seq, a) in v.alignments.iter().enumerate() {
+                        alignment_row.execute(params![
+                            book.id,
+                            v.chapter,
+                            v.verse,
+                            seq as i64,
+                            a.position as i64,
+                            a.text,
+                            a.original,
+                            a.lemma,
+                            a.strongs.join(" "),
+                            a.morph,
+                            a.occurrence
+                        ])?;
+                    }
+                }
+                for (seq, h) in book.headings.iter().enumerate() {
+                    heading_row.execute(params![
+                        book.id, h.chapter, h.verse, seq as i64, h.style, h.text
+                    ])?;
+                }
+                for (seq, f) in book.footnotes.iter().enumerate() {
+                    footnote_row.execute(params![
+                        book.id,
+                        f.chapter,
+                        f.verse,
+                        seq as i64,
+                        f.caller,
+                        f.position as i64,
+                        f.text,
+                        f.kind
+                    ])?;
+                    footnote_index_row
+                        .execute(params![f.text, book.id, f.chapter, f.verse, seq as i64])?;
+                }
+            }
+        }
+        record_checksums(&tx, None)?;
+        tx.commit()?;
+        Ok::<_, DbError>(())
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, path)
+        .map_err(|e| DbError::io(format!("Failed to install {}: {e}", path.display())))?;
+
+    Ok(ImportReport {
+        module_id: module_id.to_string(),
+        path: path.display().to_string(),
+        books: books.iter().map(|b| b.id.clone()).collect(),
+        verses,
+        headings: books.iter().map(|b| b.headings.len()).sum(),
+        footnotes: books.iter().map(|b| b.footnotes.len()).sum(),
+        warnings: warnings.list,
+        more_warnings: warnings.dropped,
+    })
+}
+
+/// Read `paths` with `parse` and import what it finds as translation
+/// `module_id`, reporting progress per file, with `info` stored in its
+/// `content_info`. Runs off the async runtime.
+pub(crate) async fn import_files(
+    app: tauri::AppHandle,
+    module_id: String,
+    name: String,
+    source_format: &'static str,
+    info: &'static [(&'static str, &'static str)],
+    paths: Vec<PathBuf>,
+    parse: fn(&str, &str, &mut Warnings) -> Vec<Book>,
+) -> Result<ImportReport, DbError> {
+    let target = content_path(&app, &module_id)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut warnings = Warnings::default();
+        let mut books = Vec::new();
+        for (i, path) in paths.iter().enumerate() {
+            let file = file_label(path);
+            if let Some(src) = read_text(path, &mut warnings) {
+                books.extend(parse(&src, &file, &mut warnings));
+            }
+            progress(&app, &module_id, &file, i + 1, paths.len());
+        }
+        let report = write(
+            &target,
+            &module_id,
+            &name,
+            source_format,
+            info,
+            books,
+            warnings,
+        )?;
+        finish(&app, &report)?;
+        Ok(report)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+/// Write `books` as translation `module_id` and mount it, for the formats
+/// imported from a single module file that names itself.
+pub(crate) fn install(
+    app: &tauri::AppHandle,
+    module_id: &str,
+    name: &str,
+    source_format: &str,
+    info: &[(&str, &str)],
+    books: Vec<Book>,
+    warnings: Warnings,
+) -> Result<ImportReport, DbError> {
+    let target = content_path(app, module_id)?;
+    let report = write(
+        &target,
+        module_id,
+        name,
+        source_format,
+        info,
+        books,
+        warnings,
+    )?;
+    finish(app, &report)?;
+    if mounted(module_id).is_none() {
+        mount(module_id, &target)?;
+        reanchor(app, module_id);
+    }
+    Ok(report)
+}
+
+/// Point an already mounted translation at its freshly imported file.
+pub(crate) fn finish(app: &tauri::AppHandle, report: &ImportReport) -> Result<(), DbError> {
+    if mounted(&report.module_id).is_some() {
+        mount(&report.module_id, Path::new(&report.path))?;
+        // Readers still attached to the old file pick up the new one.
+        db::connections::manager(&db::db_path(app)?).release_readers();
+        reanchor(app, &report.module_id);
+    }
+    Ok(())
+}
+
+/// Bring the translation's marks onto its new text. A failure (writes held
+/// by sync, say) doesn't fail the import; `reanchor_annotations` can be run
+/// again later.
+fn reanchor(app: &tauri::AppHandle, module_id: &str) {
+    match db::anchors::reanchor_now(app, module_id) {
+        Ok(report) => println!(
+            "[import] Re-anchored {module_id}: {} repaired, {} orphaned",
+            report.repaired,
+            report.orphaned.len()
+        ),
+        Err(e) => println!("[import] Could not re-anchor {module_id}: {e}"),
+    }
+}
@@ -0,0 +1,301 @@
+//! Interlinear view of aligned translations.
+//!
+//! Aligned sources (see `usfm`) link original-language words to spans of a
+//! translation's verse text, stored in the content file's `alignments`
+//! table. A verse is read as rows the reader stacks side by side: each row
+//! is the original words over the translation words they became. Words
+//! aligned together (`ὁ λόγος` over `the Word`) share a row, a word aligned
+//! with words apart from each other keeps them in one row, and translation
+//! words nothing is aligned with get rows of their own, so the rows read
+//! through the whole verse in order.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::command;
+
+use super::morphology::{self, Parsing};
+use super::mounted;
+use super::words::{word_spans, VerseRef};
+use crate::db::{self, DbError, DbErrorKind};
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OriginalWord {
+    /// The word in Hebrew, Aramaic or Greek.
+    pub text: String,
+    pub lemma: Option<String>,
+    pub strongs: Vec<String>,
+    pub parsing: Option<Parsing>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct InterlinearRow {
+    /// Empty for translation words nothing is aligned with.
+    pub originals: Vec<OriginalWord>,
+    /// Indexes of the translation words (counted as for `get_word_data`),
+    /// in order.
+    pub words: Vec<usize>,
+    /// Those words as the reader shows them.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Interlinear {
+    /// The verse's translation text.
+    pub text: String,
+    pub rows: Vec<InterlinearRow>,
+}
+
+/// Original words aligned with the same translation words.
+struct Group {
+    words: Vec<usize>,
+    /// (original, occurrence) of each of `originals`.
+    keys: Vec<(String, i64)>,
+    originals: Vec<OriginalWord>,
+}
+
+/// A verse of the content attached as `schema` as interlinear rows.
+pub(crate) fn interlinear(
+    conn: &Connection,
+    schema: &str,
+    verse: &VerseRef,
+) -> Result<Interlinear, DbError> {
+    // Files from before format 6 have no `alignments`, and unaligned
+    // translations none in it.
+    let aligned: bool = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) > 0 FROM \"{schema}\".sqlite_master
+             WHERE type = 'table' AND name = 'alignments'"
+        ),
+        [],
+        |row| row.get(0),
+    )?;
+    let aligned = aligned
+        && conn.query_row(
+            &format!("SELECT EXISTS (SELECT 1 FROM \"{schema}\".alignments)"),
+            [],
+            |row| row.get(0),
+        )?;
+    if !aligned {
+        return Err(DbError::new(
+            DbErrorKind::NotFound,
+            "This translation is not aligned with the original languages",
+        ));
+    }
+
+    let text: String = conn
+        .query_row(
+            &format!(
+                "SELECT text FROM \"{schema}\".verses
+                 WHERE book = ?1 AND chapter = ?2 AND verse = ?3"
+            ),
+            params![verse.book, verse.chapter, verse.verse],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            DbError::new(
+                DbErrorKind::NotFound,
+                format!("{} {}:{} not found", verse.book, verse.chapter, verse.verse),
+            )
+        })?;
+    let spans = word_spans(&text);
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT position, text, original, lemma, strongs, morph, occurrence
+         FROM \"{schema}\".alignments
+         WHERE book = ?1 AND chapter = ?2 AND verse = ?3 ORDER BY seq"
+    ))?;
+    let rows = stmt.query_map(params![verse.book, verse.chapter, verse.verse], |row| {
+        Ok((
+            row.get::<_, i64>(0)? as usize,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, Option<String>>(5)?,
+            row.get::<_, i64>(6)?,
+        ))
+    })?;
+
+    let mut groups: Vec<Group> = Vec::new();
+    for row in rows {
+        let (position, aligned, original, lemma, strongs, morph, occurrence) = row?;
+        let end = position + aligned.chars().count();
+        let words: Vec<usize> = spans
+            .iter()
+            .enumerate()
+            .filter(|(_, (start, stop, _))| *start < end && *stop > position)
+            .map(|(i, _)| i)
+            .collect();
+        if words.is_empty() {
+            continue;
+        }
+        let key = (original.clone(), occurrence);
+        if groups
+            .iter()
+            .any(|g| g.words == words && g.keys.contains(&key))
+        {
+            continue;
+        }
+        let word = OriginalWord {
+            text: original,
+            lemma,
+            strongs: strongs.split_whitespace().map(str::to_string).collect(),
+            parsing: morph.as_deref().map(morphology::decode),
+        };
+        if let Some(group) = groups.iter_mut().find(|g| g.words == words) {
+            group.keys.push(key);
+            group.originals.push(word);
+        } else if let Some(group) = groups.iter_mut().find(|g| g.keys.contains(&key)) {
+            // The same word aligned with words apart from each other.
+            group.words.extend(words);
+            group.words.sort_unstable();
+            group.words.dedup();
+        } else {
+            groups.push(Group {
+                words,
+                keys: vec![key],
+                originals: vec![word],
+            });
+        }
+    }
+
+    let mut rows: Vec<InterlinearRow> = groups
+        .into_iter()
+        .map(|group| InterlinearRow {
+            originals: group.originals,
+            words: group.words,
+            text: String::new(),
+        })
+        .collect();
+    let mut unaligned: Vec<usize> = Vec::new();
+    for i in 0..spans.len() {
+        let covered = rows.iter().any(|row| row.words.contains(&i));
+        if !covered && unaligned.last().is_some_and(|&last| last + 1 == i) {
+            unaligned.push(i);
+            continue;
+        }
+        if !unaligned.is_empty() {
+            rows.push(InterlinearRow {
+                originals: Vec::new(),
+                words: std::mem::take(&mut unaligned),
+                text: String::new(),
+            });
+        }
+        if !covered {
+            unaligned.push(i);
+        }
+    }
+    if !unaligned.is_empty() {
+        rows.push(InterlinearRow {
+            originals: Vec::new(),
+            words: unaligned,
+            text: String::new(),
+        });
+    }
+    rows.sort_by_key(|row| row.words[0]);
+    for row in &mut rows {
+        let mut text = String::new();
+        for (n, &i) in row.words.iter().enumerate() {
+            if n > 0 {
+                text.push_str(if row.words[n - 1] + 1 == i {
+                    " "
+                } else {
+                    " … "
+                });
+            }
+            text.push_str(spans[i].2);
+        }
+        row.text = text;
+    }
+    Ok(Interlinear { text, rows })
+}
+
+/// `verse` of mounted translation `module_id` as interlinear rows.
+#[command]
+pub async fn get_interlinear(
+    app: tauri::AppHandle,
+    module_id: String,
+    verse: VerseRef,
+) -> Result<Interlinear, DbError> {
+    let mounted = mounted(&module_id).ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("Translation `{module_id}` is not mounted"),
+        )
+    })?;
+    db::with_reader(&app, move |conn| interlinear(conn, &mounted.schema, &verse)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::import::{self, Warnings};
+    use crate::content::usfm;
+    use crate::test_util::ScratchDir;
+
+    const JOHN: &str = r#"\id JHN unfoldingWord Literal Text
+\c 1
+\p
+\v 1 \zaln-s |x-strong="G17220" x-lemma="ἐν" x-morph="Gr,P,,,,,,,," x-occurrence="1" x-occurrences="1" x-content="Ἐν"\*\w In|x-occurrence="1" x-occurrences="1"\w*\zaln-e\* \w the|x-occurrence="1" x-occurrences="2"\w* \zaln-s |x-strong="G07460" x-lemma="ἀρχή" x-morph="Gr,N,,,,,DFS," x-occurrence="1" x-occurrences="1" x-content="ἀρχῇ"\*\w beginning|x-occurrence="1" x-occurrences="1"\w*\zaln-e\* \zaln-s |x-strong="G15100" x-lemma="εἰμί" x-morph="Gr,V,IIA3,,S," x-occurrence="1" x-occurrences="1" x-content="ἦν"\*\w was|x-occurrence="1" x-occurrences="1"\w*\zaln-e\* \zaln-s |x-strong="G35880" x-lemma="ὁ" x-content="ὁ"\*\zaln-s |x-strong="G30560" x-lemma="λόγος" x-morph="robinson:N-NSM" x-content="λόγος"\*\w the|x-occurrence="2" x-occurrences="2"\w* \w Word|x-occurrence="1" x-occurrences="1"\w*\zaln-e\*\zaln-e\*.
+"#;
+
+    fn john() -> VerseRef {
+        VerseRef {
+            book: "John".into(),
+            chapter: 1,
+            verse: 1,
+        }
+    }
+
+    #[test]
+    fn stacks_aligned_usfm_as_rows() {
+        let mut warnings = Warnings::default();
+        let books = usfm::parse(JOHN, "43JHN.usfm", &mut warnings);
+        let verse = &books[0].verses[0];
+        assert_eq!(verse.text, "In the beginning was the Word.");
+        assert_eq!(verse.alignments.len(), 5);
+        assert_eq!(verse.words[0].strongs, ["G1722"]);
+
+        let dir = ScratchDir::new("interlinear");
+        let path = dir.join("ult.db");
+        import::write(&path, "ult", "ULT", "usfm", &[], books, warnings).unwrap();
+        let conn = Connection::open(&path).unwrap();
+
+        let read = interlinear(&conn, "main", &john()).unwrap();
+        let rows: Vec<(Vec<&str>, &str)> = read
+            .rows
+            .iter()
+            .map(|row| {
+                let originals = row.originals.iter().map(|o| o.text.as_str()).collect();
+                (originals, row.text.as_str())
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (vec!["Ἐν"], "In"),
+                (vec![], "the"),
+                (vec!["ἀρχῇ"], "beginning"),
+                (vec!["ἦν"], "was"),
+                (vec!["ὁ", "λόγος"], "the Word."),
+            ]
+        );
+        let logos = &read.rows[4].originals[1];
+        assert_eq!(logos.lemma.as_deref(), Some("λόγος"));
+        assert_eq!(
+            logos
+                .parsing
+                .as_ref()
+                .and_then(|p| p.description.as_deref()),
+            Some("Noun: nominative singular masculine")
+        );
+
+        conn.execute_batch("DELETE FROM alignments").unwrap();
+        assert_eq!(
+            interlinear(&conn, "main", &john()).unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+        drop(conn);
+    }
+}
@@ -0,0 +1,167 @@
+//! Layout of mounted translations' chapters: the headings before verses
+//! and the paragraphs and poetry lines verses begin, so the reader can set
+//! prose as paragraphs and psalms as indented lines.
+//!
+//! Styles are USFM paragraph markers, which the other formats map onto.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::has_table;
+use crate::db::DbError;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ChapterHeading {
+    /// `s1`, `ms`, `d` (a psalm's title), ...
+    pub style: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ParagraphBreak {
+    /// Character offset in the verse text the paragraph begins at.
+    pub position: usize,
+    /// `p`, `m`, `q1`, `q2`, `b` (a blank line between stanzas), ...
+    pub style: String,
+    /// Whether it is a line of poetry.
+    pub poetry: bool,
+    /// Indentation level: 1 for `q1` or `pi1`, 0 for a flush paragraph.
+    pub indent: u32,
+}
+
+/// Paragraph styles indented by their level (`q2` twice as far as `q1`).
+const INDENTED: &[&str] = &["q", "qm", "pi", "li", "lim", "ph", "mi"];
+
+/// Paragraph styles of poetry.
+const POETRY: &[&str] = &["q", "qm", "qr", "qc", "qd", "b"];
+
+impl ParagraphBreak {
+    fn new(position: usize, style: String) -> Self {
+        let base = style.trim_end_matches(|c: char| c.is_ascii_digit());
+        let indent = if INDENTED.contains(&base) {
+            style[base.len()..].parse().unwrap_or(1)
+        } else {
+            0
+        };
+        Self {
+            position,
+            poetry: POETRY.contains(&base),
+            indent,
+            style,
+        }
+    }
+}
+
+/// Headings of one chapter from an attached content schema, keyed by the
+/// verse they come before. Files from before format 2 have none.
+pub(crate) fn chapter_headings(
+    conn: &Connection,
+    schema: &str,
+    book: &str,
+    chapter: i64,
+) -> Result<BTreeMap<i64, Vec<ChapterHeading>>, DbError> {
+    let mut headings: BTreeMap<i64, Vec<ChapterHeading>> = BTreeMap::new();
+    if !has_table(conn, schema, "headings")? {
+        return Ok(headings);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT verse, style, text FROM \"{schema}\".headings
+         WHERE book = ?1 AND chapter = ?2 ORDER BY verse, seq"
+    ))?;
+    let rows = stmt.query_map(params![book, chapter], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            ChapterHeading {
+                style: row.get(1)?,
+                text: row.get(2)?,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (verse, heading) = row?;
+        headings.entry(verse).or_default().push(heading);
+    }
+    Ok(headings)
+}
+
+/// Paragraph breaks of one chapter from an attached content schema, keyed
+/// by verse number. Files from before format 9 have none.
+pub(crate) fn chapter_paragraphs(
+    conn: &Connection,
+    schema: &str,
+    book: &str,
+    chapter: i64,
+) -> Result<BTreeMap<i64, Vec<ParagraphBreak>>, DbError> {
+    let mut paragraphs: BTreeMap<i64, Vec<ParagraphBreak>> = BTreeMap::new();
+    if !has_table(conn, schema, "paragraphs")? {
+        return Ok(paragraphs);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT verse, position, style FROM \"{schema}\".paragraphs
+         WHERE book = ?1 AND chapter = ?2 ORDER BY verse, seq"
+    ))?;
+    let rows = stmt.query_map(params![book, chapter], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)? as usize,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (verse, position, style) = row?;
+        paragraphs
+            .entry(verse)
+            .or_default()
+            .push(ParagraphBreak::new(position, style));
+    }
+    Ok(paragraphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::import::{self, Warnings};
+    use crate::content::usfm;
+    use crate::test_util::ScratchDir;
+
+    const PSALM: &str = r#"\id PSA
+\c 23
+\d A Psalm of David.
+\q1
+\v 1 Yahweh is my shepherd:
+\q2 I shall lack nothing.
+\b
+\q1
+\v 2 He makes me lie down in green pastures.
+"#;
+
+    #[test]
+    fn reads_headings_and_poetry_lines() {
+        let mut warnings = Warnings::default();
+        let books = usfm::parse(PSALM, "19PSA.usfm", &mut warnings);
+        let dir = ScratchDir::new("layout");
+        let path = dir.join("web.db");
+        import::write(&path, "web", "WEB", "usfm", &[], books, warnings).unwrap();
+        let conn = Connection::open(&path).unwrap();
+
+        let headings = chapter_headings(&conn, "main", "Ps", 23).unwrap();
+        assert_eq!(
+            headings[&1],
+            [ChapterHeading {
+                style: "d".into(),
+                text: "A Psalm of David.".into()
+            }]
+        );
+        let paragraphs = chapter_paragraphs(&conn, "main", "Ps", 23).unwrap();
+        let lines = |verse: i64| -> Vec<(usize, &str, bool, u32)> {
+            paragraphs[&verse]
+                .iter()
+                .map(|p| (p.position, p.style.as_str(), p.poetry, p.indent))
+                .collect()
+        };
+        assert_eq!(lines(1), [(0, "q1", true, 1), (23, "q2", true, 2)]);
+        assert_eq!(lines(2), [(0, "b", true, 0), (0, "q1", true, 1)]);
+        drop(conn);
+    }
+}
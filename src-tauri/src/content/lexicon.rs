@@ -0,0 +1,532 @@
+//! Strong's lexicons, for word study.
+//!
+//! A lexicon is a read-only SQLite file under `<app data>/content/lexicons`,
+//! kept apart from the user database like a translation's content file,
+//! with one entry per Strong's number: lemma, transliteration,
+//! pronunciation, derivation, definition and KJV usage, as far as the source
+//! has them. Strong's own Hebrew and Greek dictionaries import from the Open
+//! Scriptures JSON editions (`strongs-greek-dictionary.js`, ...); other
+//! lexicons keyed by Strong's number (Thayer, BDB, ...) from e-Sword
+//! `.lexi`/`.lexx` modules or a SWORD library. `lookup_lexicon` asks every
+//! installed lexicon, so one tap shows Strong's next to Thayer.
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{command, Manager};
+
+use super::{esword, import, slug, sword, CONTENT_DIR};
+use crate::db::{DbError, DbErrorKind};
+
+/// Directory (in the content directory) holding one `<id>.db` per lexicon.
+const LEXICON_DIR: &str = "lexicons";
+
+/// Layout version of lexicon files (`lexicon_info.format`); newer ones are
+/// skipped.
+const LEXICON_FORMAT: u32 = 1;
+
+const LEXICON_SCHEMA: &str = "
+    CREATE TABLE lexicon_info (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE entries (
+        strongs TEXT PRIMARY KEY,
+        lemma TEXT,
+        transliteration TEXT,
+        pronunciation TEXT,
+        derivation TEXT,
+        definition TEXT NOT NULL,
+        usage TEXT
+    ) WITHOUT ROWID;";
+
+/// One Strong's number's entry in a lexicon.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Entry {
+    /// Normalized Strong's number (`H7225`, `G3056`).
+    pub strongs: String,
+    /// The word in Hebrew or Greek.
+    pub lemma: Option<String>,
+    pub transliteration: Option<String>,
+    pub pronunciation: Option<String>,
+    /// Where the word comes from (`from G3004`, `a primitive root`).
+    pub derivation: Option<String>,
+    pub definition: String,
+    /// How the KJV translates it.
+    pub usage: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LexiconEntry {
+    pub lexicon: String,
+    #[serde(rename = "lexiconName")]
+    pub lexicon_name: String,
+    #[serde(flatten)]
+    pub entry: Entry,
+}
+
+/// An installed lexicon.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Lexicon {
+    pub id: String,
+    pub name: String,
+    /// `hebrew` or `greek`; `None` for lexicons of both.
+    pub language: Option<String>,
+    pub entries: i64,
+    #[serde(rename = "sourceFormat")]
+    pub source_format: String,
+    pub path: String,
+}
+
+fn lexicon_dir(app: &tauri::AppHandle) -> Result<PathBuf, DbError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| DbError::io(format!("Cannot determine app data dir: {e}")))?;
+    Ok(dir.join(CONTENT_DIR).join(LEXICON_DIR))
+}
+
+/// `Some(text)` unless `text` is blank.
+fn some(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Write `entries` as lexicon `id` at `path`, replacing any file already
+/// there once the new one is complete. Entries for a number already seen are
+/// dropped.
+fn write(
+    path: &Path,
+    id: &str,
+    name: &str,
+    source_format: &str,
+    entries: Vec<Entry>,
+) -> Result<Lexicon, DbError> {
+    let mut seen = HashSet::new();
+    let entries: Vec<Entry> = entries
+        .into_iter()
+        .filter(|e| !e.definition.is_empty() && seen.insert(e.strongs.clone()))
+        .collect();
+    if entries.is_empty() {
+        return Err(DbError::invalid("No Strong's-numbered entries found"));
+    }
+    let language = if entries.iter().all(|e| e.strongs.starts_with('H')) {
+        Some("hebrew")
+    } else if entries.iter().all(|e| e.strongs.starts_with('G')) {
+        Some("greek")
+    } else {
+        None
+    };
+
+    let partial = PathBuf::from(format!("{}.partial", path.display()));
+    let written = (|| {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| DbError::io(format!("Failed to create {}: {e}", dir.display())))?;
+        }
+        let _ = std::fs::remove_file(&partial);
+        let mut conn = Connection::open(&partial)?;
+        conn.execute_batch(LEXICON_SCHEMA)?;
+        let tx = conn.transaction()?;
+        {
+            let mut info = tx.prepare("INSERT INTO lexicon_info (key, value) VALUES (?1, ?2)")?;
+            let format = LEXICON_FORMAT.to_string();
+            for (key, value) in [
+                ("format", format.as_str()),
+                ("lexicon_id", id),
+                ("name", name),
+                ("source_format", source_format),
+            ]
+            .into_iter()
+            .chain(language.map(|l| ("language", l)))
+            {
+                info.execute(params![key, value])?;
+            }
+            let mut row = tx.prepare(
+                "INSERT INTO entries (strongs, lemma, transliteration, pronunciation,
+                                      derivation, definition, usage)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for e in &entries {
+                row.execute(params![
+                    e.strongs,
+                    e.lemma,
+                    e.transliteration,
+                    e.pronunciation,
+                    e.derivation,
+                    e.definition,
+                    e.usage
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok::<_, DbError>(())
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, path)
+        .map_err(|e| DbError::io(format!("Failed to install {}: {e}", path.display())))?;
+    println!("[lexicon] Installed {id} with {} entries", entries.len());
+    Ok(Lexicon {
+        id: id.to_string(),
+        name: name.to_string(),
+        language: language.map(str::to_string),
+        entries: entries.len() as i64,
+        source_format: source_format.to_string(),
+        path: path.display().to_string(),
+    })
+}
+
+fn open(path: &Path) -> Result<Connection, DbError> {
+    Ok(Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )?)
+}
+
+/// What the lexicon file at `path` holds, if it is one this app can read.
+fn installed(conn: &Connection, path: &Path) -> Result<Lexicon, DbError> {
+    let not_lexicon = || DbError::invalid(format!("{} is not a lexicon", path.display()));
+    let info: BTreeMap<String, String> = conn
+        .prepare("SELECT key, value FROM lexicon_info")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<_>>()
+        })
+        .map_err(|_| not_lexicon())?;
+    let format: u32 = info
+        .get("format")
+        .and_then(|f| f.parse().ok())
+        .ok_or_else(not_lexicon)?;
+    if format > LEXICON_FORMAT {
+        return Err(DbError::new(
+            DbErrorKind::SchemaTooNew,
+            format!("{} needs a newer version of BibleMarker", path.display()),
+        ));
+    }
+    let id = info.get("lexicon_id").cloned().ok_or_else(not_lexicon)?;
+    Ok(Lexicon {
+        name: info.get("name").cloned().unwrap_or_else(|| id.clone()),
+        language: info.get("language").cloned(),
+        entries: conn.query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))?,
+        source_format: info.get("source_format").cloned().unwrap_or_default(),
+        path: path.display().to_string(),
+        id,
+    })
+}
+
+/// The lexicons in `dir`, by name, each with its open connection.
+fn open_all(dir: &Path) -> Vec<(Lexicon, Connection)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut lexicons: Vec<(Lexicon, Connection)> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|path| {
+            let found = open(&path).and_then(|conn| Ok((installed(&conn, &path)?, conn)));
+            match found {
+                Ok(found) => Some(found),
+                Err(e) => {
+                    println!("[lexicon] Skipping {}: {e}", path.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    lexicons.sort_by_key(|(l, _)| l.name.to_lowercase());
+    lexicons
+}
+
+/// The entries for `number` in every lexicon in `dir`. A number with a
+/// letter suffix (`H1254a`) falls back on the plain number in lexicons that
+/// don't split it.
+fn lookup(dir: &Path, number: &str) -> Result<Vec<LexiconEntry>, DbError> {
+    let plain = number.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let mut found = Vec::new();
+    for (lexicon, conn) in open_all(dir) {
+        let mut stmt = conn.prepare(
+            "SELECT strongs, lemma, transliteration, pronunciation, derivation, definition, usage
+             FROM entries WHERE strongs = ?1",
+        )?;
+        let mut read = |number: &str| {
+            stmt.query_row([number], |row| {
+                Ok(Entry {
+                    strongs: row.get(0)?,
+                    lemma: row.get(1)?,
+                    transliteration: row.get(2)?,
+                    pronunciation: row.get(3)?,
+                    derivation: row.get(4)?,
+                    definition: row.get(5)?,
+                    usage: row.get(6)?,
+                })
+            })
+            .optional()
+        };
+        let mut entry = read(number)?;
+        if entry.is_none() && plain != number {
+            entry = read(plain)?;
+        }
+        if let Some(entry) = entry {
+            found.push(LexiconEntry {
+                lexicon: lexicon.id,
+                lexicon_name: lexicon.name,
+                entry,
+            });
+        }
+    }
+    Ok(found)
+}
+
+/// The entries of an Open Scriptures Strong's dictionary: a JSON object
+/// from number to entry, optionally wrapped in JavaScript
+/// (`var strongsGreekDictionary = {...}; module.exports = ...`).
+fn parse_open_scriptures(src: &str) -> Result<Vec<Entry>, DbError> {
+    let start = src.find("= {").map(|at| at + 2).or_else(|| src.find('{'));
+    let end = src.rfind('}');
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(DbError::invalid(
+            "Not a Strong's dictionary: no JSON object",
+        ));
+    };
+    let object: BTreeMap<String, serde_json::Value> = serde_json::from_str(&src[start..=end])
+        .map_err(|e| DbError::invalid(format!("Not a Strong's dictionary: {e}")))?;
+    let mut entries = Vec::new();
+    for (key, value) in object {
+        let Some(strongs) = import::strongs_numbers(&key, None).into_iter().next() else {
+            continue;
+        };
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| value.get(name).and_then(|v| v.as_str()))
+                .and_then(some)
+        };
+        let definition = field(&["strongs_def"]).unwrap_or_default();
+        entries.push(Entry {
+            strongs,
+            lemma: field(&["lemma"]),
+            transliteration: field(&["xlit", "translit"]),
+            pronunciation: field(&["pron"]),
+            derivation: field(&["derivation"]),
+            definition,
+            usage: field(&["kjv_def"]),
+        });
+    }
+    Ok(entries)
+}
+
+/// The entries of an e-Sword lexicon: `Lexicon (Topic, Definition)` rows,
+/// keyed by prefixed Strong's number, with RTF definitions.
+fn read_esword(path: &Path) -> Result<(Vec<Entry>, Option<String>), DbError> {
+    let conn = esword::open(path)?;
+    let title = esword::read_details(&conn).get("title").map(str::to_string);
+    let mut stmt = conn
+        .prepare("SELECT Topic, Definition FROM Lexicon")
+        .map_err(|_| DbError::invalid(format!("{} is not an e-Sword lexicon", path.display())))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, Option<String>>(1)?,
+        ))
+    })?;
+    let mut entries = Vec::new();
+    for row in rows {
+        let (Some(topic), Some(definition)) = row? else {
+            continue;
+        };
+        if let Some(strongs) = import::strongs_numbers(&topic, None).into_iter().next() {
+            entries.push(Entry {
+                strongs,
+                definition: esword::read_rtf(&definition, None).0,
+                ..Default::default()
+            });
+        }
+    }
+    Ok((entries, title))
+}
+
+fn install(
+    app: &tauri::AppHandle,
+    id: &str,
+    name: &str,
+    source_format: &str,
+    entries: Vec<Entry>,
+) -> Result<Lexicon, DbError> {
+    let path = lexicon_dir(app)?.join(format!("{}.db", slug(id)?));
+    write(&path, id, name, source_format, entries)
+}
+
+#[command]
+pub fn list_lexicons(app: tauri::AppHandle) -> Result<Vec<Lexicon>, DbError> {
+    let dir = lexicon_dir(&app)?;
+    Ok(open_all(&dir).into_iter().map(|(l, _)| l).collect())
+}
+
+/// Import an Open Scriptures Strong's dictionary (`.js` or `.json`) or an
+/// e-Sword lexicon (`.lexi`, `.lexx`) as lexicon `lexicon_id` (by default
+/// the file name), replacing any lexicon of that id.
+#[command]
+pub async fn import_lexicon(
+    app: tauri::AppHandle,
+    path: String,
+    lexicon_id: Option<String>,
+    name: Option<String>,
+) -> Result<Lexicon, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = PathBuf::from(path);
+        let file = import::file_label(&path);
+        let stem = file.split('.').next().unwrap_or(&file).to_string();
+        let ext = file
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let (entries, title, source_format) = match ext.as_str() {
+            "js" | "json" => {
+                let src = std::fs::read_to_string(&path)
+                    .map_err(|e| DbError::io(format!("Failed to read {file}: {e}")))?;
+                (parse_open_scriptures(&src)?, None, "openscriptures")
+            }
+            "lexi" | "lexx" => {
+                let (entries, title) = read_esword(&path)?;
+                (entries, title, "esword")
+            }
+            _ => {
+                return Err(DbError::invalid(format!(
+                    "{file} is not a lexicon BibleMarker reads (.js, .json, .lexi, .lexx)"
+                )))
+            }
+        };
+        let id = lexicon_id.unwrap_or(stem);
+        let name = name.or(title).unwrap_or_else(|| id.clone());
+        install(&app, &id, &name, source_format, entries)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+/// Import SWORD lexicon `module` (`StrongsGreek`, `Thayer`, ...) from
+/// `library` as a lexicon named after it.
+#[command]
+pub async fn import_sword_lexicon(
+    app: tauri::AppHandle,
+    library: String,
+    module: String,
+) -> Result<Lexicon, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let lexicon = sword::read_lexicon(Path::new(&library), &module)?;
+        if lexicon.skipped > 0 {
+            println!(
+                "[lexicon] {module}: skipped {} entries without a Strong's number",
+                lexicon.skipped
+            );
+        }
+        let entries = lexicon
+            .entries
+            .into_iter()
+            .map(|(strongs, definition)| Entry {
+                strongs,
+                definition,
+                ..Default::default()
+            })
+            .collect();
+        install(&app, &module, &lexicon.description, "sword", entries)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+/// Delete lexicon `id`; false if it wasn't installed.
+#[command]
+pub fn remove_lexicon(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    let path = lexicon_dir(&app)?.join(format!("{}.db", slug(&id)?));
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path)
+        .map_err(|e| DbError::io(format!("Failed to remove {}: {e}", path.display())))?;
+    Ok(true)
+}
+
+/// The entries for Strong's number `strongs_number` (`H7225`, `G3056`,
+/// `strong:H07225`) in every installed lexicon, by lexicon name.
+#[command]
+pub fn lookup_lexicon(
+    app: tauri::AppHandle,
+    strongs_number: String,
+) -> Result<Vec<LexiconEntry>, DbError> {
+    let number = import::strongs_numbers(&strongs_number, None)
+        .into_iter()
+        .next()
+        .ok_or_else(|| DbError::invalid(format!("`{strongs_number}` is not a Strong's number")))?;
+    let dir = lexicon_dir(&app)?;
+    if open_all(&dir).is_empty() {
+        return Err(DbError::new(
+            DbErrorKind::NotFound,
+            "No lexicons are installed",
+        ));
+    }
+    lookup(&dir, &number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    const GREEK: &str = r#"/* Strong's Greek dictionary, public domain */
+var strongsGreekDictionary = {"G3056":{"translit":"lógos","lemma":"λόγος","kjv_def":"account, word","derivation":"from G3004;","strongs_def":"something said (including the thought)"},
+"G3004":{"translit":"légō","lemma":"λέγω","strongs_def":" to \"lay\" forth, i.e. relate in words"},
+"x":{"strongs_def":"not a number"}}; module.exports = strongsGreekDictionary;"#;
+
+    #[test]
+    fn reads_open_scriptures_dictionaries() {
+        let entries = parse_open_scriptures(GREEK).unwrap();
+        assert_eq!(entries.len(), 2);
+        let logos = entries.iter().find(|e| e.strongs == "G3056").unwrap();
+        assert_eq!(logos.lemma.as_deref(), Some("λόγος"));
+        assert_eq!(logos.transliteration.as_deref(), Some("lógos"));
+        assert_eq!(logos.derivation.as_deref(), Some("from G3004;"));
+        assert_eq!(logos.usage.as_deref(), Some("account, word"));
+        assert!(parse_open_scriptures("not json").is_err());
+    }
+
+    #[test]
+    fn looks_numbers_up_in_every_lexicon() {
+        let dir = ScratchDir::new("lexicon");
+        let strongs = write(
+            &dir.join("strongs_greek.db"),
+            "strongs-greek",
+            "Strong's Greek",
+            "openscriptures",
+            parse_open_scriptures(GREEK).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            (strongs.entries, strongs.language.as_deref()),
+            (2, Some("greek"))
+        );
+        write(
+            &dir.join("thayer.db"),
+            "thayer",
+            "Thayer",
+            "sword",
+            vec![Entry {
+                strongs: "G3056".into(),
+                definition: "a word, uttered by a living voice".into(),
+                ..Default::default()
+            }],
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.db"), b"not a database").unwrap();
+
+        let found = lookup(&dir, "G3056").unwrap();
+        let names: Vec<&str> = found.iter().map(|e| e.lexicon_name.as_str()).collect();
+        assert_eq!(names, ["Strong's Greek", "Thayer"]);
+        assert_eq!(found[0].entry.lemma.as_deref(), Some("λόγος"));
+        assert_eq!(lookup(&dir, "G3004a").unwrap()[0].entry.strongs, "G3004");
+        assert!(lookup(&dir, "H1").unwrap().is_empty());
+        assert!(write(&dir.join("empty.db"), "empty", "Empty", "sword", Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,709 @@
+//! Translation content databases.
+//!
+//! Bible text lives in one read-only SQLite file per translation under
+//! `<app data>/content`, never in `biblemarker.db`, so sync only ever moves
+//! user data. Mounting a content file ATTACHes it to the native read
+//! connections as `content_<module>`, so queries can join verse text with the
+//! user's annotations; unmounting detaches it again.
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{command, Manager};
+
+use crate::db::{self, DbError, DbErrorKind};
+use crate::download::to_hex;
+use footnotes::ChapterFootnote;
+use layout::{ChapterHeading, ParagraphBreak};
+
+// Canonical book list and the ids other formats use
+pub(crate) mod books;
+
+// What every importer shares: validation, writing the content file, progress
+pub(crate) mod import;
+
+// USFM importer
+pub mod usfm;
+
+// USX and Digital Bible Library bundle importer
+pub mod usx;
+
+// SWORD library scanning and module import
+pub mod sword;
+
+// Zefania XML and OpenSong importers
+pub mod zefania;
+
+// e-Sword .bblx importer
+pub mod esword;
+
+// theWord and MySword importers
+pub mod theword;
+
+// SBLGNT and WLC original-language text importers
+pub mod original;
+
+// Plain text and CSV importer, with a preview of the mapping
+pub mod plaintext;
+
+// Strong's-tagged words, looked up from the reader
+pub mod words;
+
+// Footnotes in chapters and footnote search
+pub mod footnotes;
+
+// Headings, paragraphs and poetry lines in chapters
+pub mod layout;
+
+// Morphology codes read as parsing
+pub mod morphology;
+
+// Original-language words aligned with translation words
+pub mod interlinear;
+
+// Strong's lexicons: import and lookup
+pub mod lexicon;
+
+// Audio Bibles: manifest import, chapter audio and playback positions
+pub mod audio;
+
+// Verse numbers mapped between versifications
+pub(crate) mod versification;
+
+// Book order, chapter and verse counts per versification
+pub mod canon;
+
+// Verse references parsed from what people type
+pub mod references;
+
+// References written out in a citation style and language
+pub mod citation;
+
+// Union, intersection and containment of references
+pub mod ranges;
+
+// Named passages (pericopes): import, lookup and pericope-scoped queries
+pub mod pericopes;
+
+// One chapter in several translations, verse by verse
+pub mod parallel;
+
+// Minimal XML reader for the XML-based formats (and entity decoding for
+// fetched HTML)
+pub(crate) mod xml;
+
+/// Directory (in app data) holding one `<module>.db` per installed translation.
+pub(crate) const CONTENT_DIR: &str = "content";
+
+/// Layout version of content files (`content_info.format`); newer ones are
+/// refused. Format 2 added `books`, `headings` and `footnotes`, format 3
+/// `verse_html`, format 4 `words`, format 5 `words.morph`, format 6
+/// `alignments`, format 7 `red_letter`, format 8 `footnotes.kind` and
+/// `footnote_index`, format 9 `paragraphs` and format 10 `book_checksums`;
+/// format 1 files only have `verses`, which is all reading needs.
+pub(crate) const CONTENT_FORMAT: u32 = 10;
+
+/// Tables every content file has. `verses.text` is plain verse text, the
+/// same as a `chapter_cache` verse. A heading belongs before `verse`; a
+/// footnote is anchored `position` characters into its verse's text, and
+/// `footnote_index` is the full-text index of footnote text.
+/// `verse_html` holds the formatted text (see `import::Styled`) of the
+/// verses whose source styles them. `words` holds the Strong's numbers
+/// (space separated) and morphology codes of tagged words, each `position`
+/// characters into its verse's text, and `alignments` the original-language
+/// words aligned with spans of it. `red_letter` spans are the words of
+/// Christ, `length` characters from `position`, and `paragraphs` the
+/// paragraphs and poetry lines (by USFM style) beginning `position`
+/// characters into a verse. `book_checksums` records each book's verse
+/// count and `checksum` as imported or downloaded.
+const CONTENT_SCHEMA: &str = "
+    CREATE TABLE content_info (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE verses (
+        book TEXT NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        PRIMARY KEY (book, chapter, verse)
+    ) WITHOUT ROWID;
+    CREATE TABLE verse_html (
+        book TEXT NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        html TEXT NOT NULL,
+        PRIMARY KEY (book, chapter, verse)
+    ) WITHOUT ROWID;
+    CREATE TABLE books (
+        book TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL
+    ) WITHOUT ROWID;
+    CREATE TABLE headings (
+        book TEXT NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        seq INTEGER NOT NULL,
+        style TEXT NOT NULL,
+        text TEXT NOT NULL,
+        PRIMARY KEY (book, chapter, verse, seq)
+    ) WITHOUT ROWID;
+    CREATE TABLE footnotes (
+        book TEXT NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        seq INTEGER NOT NULL,
+        caller TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        kind TEXT NOT NULL DEFAULT 'footnote',
+        PRIMARY KEY (book, chapter, verse, seq)
+    ) WITHOUT ROWID;
+    CREATE VIRTUAL TABLE footnote_index USING fts5(
+        text,
+        book UNINDEXED,
+        chapter UNINDEXED,
+        verse UNINDEXED,
+        seq UNINDEXED,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+    CREATE TABLE words (
+        book TEXT NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        seq INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        strongs TEXT NOT NULL,
+        gloss TEXT,
+        morph TEXT,
+        PRIMARY KEY (book, chapter, verse, seq)
+    ) WITHOUT ROWID;
+    CREATE TABLE red_letter (
+        book TEXT NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        seq INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        length INTEGER NOT NULL,
+        PRIMARY KEY (book, chapter, verse, seq)
+    ) WITHOUT ROWID;
+    CREATE TABLE paragraphs (
+        book TEXT NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        seq INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        style TEXT NOT NULL,
+        PRIMARY KEY (book, chapter, verse, seq)
+    ) WITHOUT ROWID;
+    CREATE TABLE alignments (
+        book TEXT NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        seq INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        original TEXT NOT NULL,
+        lemma TEXT,
+        strongs TEXT NOT NULL,
+        morph TEXT,
+        occurrence INTEGER NOT NULL,
+        PRIMARY KEY (book, chapter, verse, seq)
+    ) WITHOUT ROWID;
+    CREATE TABLE book_checksums (
+        book TEXT PRIMARY KEY,
+        verses INTEGER NOT NULL,
+        sha256 TEXT NOT NULL
+    ) WITHOUT ROWID;";
+
+/// SQLite allows 10 attached databases per connection by default; leave room
+/// for the ones merges and bundles attach.
+const MAX_MOUNTS: usize = 8;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct MountedContent {
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    /// Schema name the file is attached under (`content_<module>`).
+    pub schema: String,
+    pub path: String,
+    pub verses: i64,
+}
+
+/// One chapter of a mounted translation, keyed by verse number.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ContentChapter {
+    pub verses: BTreeMap<i64, String>,
+    /// Words of Christ as (start, end) character offsets into each verse's
+    /// text; verses without any are left out.
+    #[serde(rename = "redLetter")]
+    pub red_letter: BTreeMap<i64, Vec<(usize, usize)>>,
+    /// Footnotes of each verse, in order; verses without any are left out.
+    pub footnotes: BTreeMap<i64, Vec<ChapterFootnote>>,
+    /// Headings before each verse, in order.
+    pub headings: BTreeMap<i64, Vec<ChapterHeading>>,
+    /// Paragraphs and poetry lines beginning in each verse, in order.
+    pub paragraphs: BTreeMap<i64, Vec<ParagraphBreak>>,
+}
+
+struct Mounts {
+    /// Bumped on every mount/unmount so connections know to re-sync.
+    generation: u64,
+    list: Vec<MountedContent>,
+}
+
+static MOUNTS: Mutex<Mounts> = Mutex::new(Mounts {
+    generation: 0,
+    list: Vec::new(),
+});
+
+fn mounts() -> MutexGuard<'static, Mounts> {
+    MOUNTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// `module_id` reduced to `[a-z0-9_]`, for schema and file names.
+pub(crate) fn slug(module_id: &str) -> Result<String, DbError> {
+    let slug: String = module_id
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if slug.trim_matches('_').is_empty() {
+        return Err(DbError::invalid(format!(
+            "Invalid translation id `{module_id}`"
+        )));
+    }
+    Ok(slug)
+}
+
+fn schema_name(module_id: &str) -> Result<String, DbError> {
+    Ok(format!("content_{}", slug(module_id)?))
+}
+
+/// Where the content file for `module_id` lives by default.
+pub(crate) fn content_path(app: &tauri::AppHandle, module_id: &str) -> Result<PathBuf, DbError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| DbError::io(format!("Cannot determine app data dir: {e}")))?;
+    Ok(dir
+        .join(CONTENT_DIR)
+        .join(format!("{}.db", slug(module_id)?)))
+}
+
+/// A `file:` URI opening `path` read-only.
+fn read_only_uri(path: &Path) -> String {
+    let path = path
+        .display()
+        .to_string()
+        .replace('%', "%25")
+        .replace('?', "%3f")
+        .replace('#', "%23");
+    format!("file:{path}?mode=ro")
+}
+
+/// Create an empty content file at `path` for importers to fill.
+pub(crate) fn create(path: &Path, module_id: &str, name: &str) -> Result<Connection, DbError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| DbError::io(format!("Failed to create {}: {e}", dir.display())))?;
+    }
+    let _ = std::fs::remove_file(path);
+    let conn = Connection::open(path)?;
+    conn.execute_batch(CONTENT_SCHEMA)?;
+    for (key, value) in [
+        ("format", CONTENT_FORMAT.to_string().as_str()),
+        ("module_id", module_id),
+        ("name", name),
+    ] {
+        set_info(&conn, key, value)?;
+    }
+    Ok(conn)
+}
+
+pub(crate) fn set_info(conn: &Connection, key: &str, value: &str) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO content_info (key, value) VALUES (?1, ?2)",
+        [key, value],
+    )?;
+    Ok(())
+}
+
+/// Check that `path` is a content file for `module_id` this app can read,
+/// returning its verse count.
+pub(crate) fn inspect(path: &Path, module_id: &str) -> Result<i64, DbError> {
+    if !path.exists() {
+        return Err(DbError::new(
+            DbErrorKind::NotFound,
+            format!("Content file not found: {}", path.display()),
+        ));
+    }
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let info = |key: &str| -> Result<Option<String>, DbError> {
+        conn.query_row(
+            "SELECT value FROM content_info WHERE key = ?1",
+            [key],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|_| DbError::invalid(format!("{} is not a content file", path.display())))
+    };
+    let format: u32 = info("format")?
+        .and_then(|f| f.parse().ok())
+        .ok_or_else(|| DbError::invalid(format!("{} is not a content file", path.display())))?;
+    if format > CONTENT_FORMAT {
+        return Err(DbError::new(
+            DbErrorKind::SchemaTooNew,
+            format!("{} needs a newer version of BibleMarker", path.display()),
+        ));
+    }
+    let found = info("module_id")?.unwrap_or_default();
+    if found != module_id {
+        return Err(DbError::invalid(format!(
+            "{} holds `{found}`, not `{module_id}`",
+            path.display()
+        )));
+    }
+    Ok(conn.query_row("SELECT COUNT(*) FROM verses", [], |row| row.get(0))?)
+}
+
+/// Every `content_info` row of the content file at `path`.
+pub(crate) fn read_info(path: &Path) -> Result<BTreeMap<String, String>, DbError> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let info = conn
+        .prepare("SELECT key, value FROM content_info")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<_>>()
+        })
+        .map_err(|_| DbError::invalid(format!("{} is not a content file", path.display())))?;
+    Ok(info)
+}
+
+/// Register `path` as the content of `module_id`. Connections attach it the
+/// next time they're used. Mounting an id again replaces its file.
+pub(crate) fn mount(module_id: &str, path: &Path) -> Result<MountedContent, DbError> {
+    let schema = schema_name(module_id)?;
+    let verses = inspect(path, module_id)?;
+    let mounted = MountedContent {
+        module_id: module_id.to_string(),
+        schema,
+        path: path.display().to_string(),
+        verses,
+    };
+    let mut mounts = mounts();
+    let others = mounts
+        .list
+        .iter()
+        .filter(|m| m.schema != mounted.schema)
+        .count();
+    if others >= MAX_MOUNTS {
+        return Err(DbError::invalid(format!(
+            "At most {MAX_MOUNTS} translations can be mounted at once"
+        )));
+    }
+    mounts.list.retain(|m| m.schema != mounted.schema);
+    mounts.list.push(mounted.clone());
+    mounts.generation += 1;
+    Ok(mounted)
+}
+
+/// Forget `module_id`'s content file; false if it wasn't mounted.
+pub(crate) fn unmount(module_id: &str) -> Result<bool, DbError> {
+    let schema = schema_name(module_id)?;
+    let mut mounts = mounts();
+    let before = mounts.list.len();
+    mounts.list.retain(|m| m.schema != schema);
+    if mounts.list.len() == before {
+        return Ok(false);
+    }
+    mounts.generation += 1;
+    Ok(true)
+}
+
+pub(crate) fn mounted(module_id: &str) -> Option<MountedContent> {
+    let schema = schema_name(module_id).ok()?;
+    mounts().list.iter().find(|m| m.schema == schema).cloned()
+}
+
+/// Bring `conn`'s attachments in line with the mount table. `seen` is the
+/// generation `conn` last synced to (`None` for a new connection), so this
+/// is a no-op until something is mounted or unmounted.
+pub(crate) fn attach_mounted(conn: &Connection, seen: &mut Option<u64>) -> Result<(), DbError> {
+    let (generation, wanted) = {
+        let mounts = mounts();
+        if *seen == Some(mounts.generation) {
+            return Ok(());
+        }
+        (mounts.generation, mounts.list.clone())
+    };
+    let attached: Vec<(String, String)> = conn
+        .prepare(
+            "SELECT name, file FROM pragma_database_list WHERE name LIKE 'content\\_%' ESCAPE '\\'",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (schema, file) in &attached {
+        let keep = wanted
+            .iter()
+            .any(|m| &m.schema == schema && Path::new(&m.path) == Path::new(file));
+        if !keep {
+            conn.execute("DETACH DATABASE ?1", [schema])?;
+        }
+    }
+    for m in &wanted {
+        let present = attached
+            .iter()
+            .any(|(schema, file)| schema == &m.schema && Path::new(file) == Path::new(&m.path));
+        if !present {
+            conn.execute(
+                "ATTACH DATABASE ?1 AS ?2",
+                params![read_only_uri(Path::new(&m.path)), m.schema],
+            )
+            .map_err(|e| DbError::from(e).context(format!("Failed to attach {}", m.path)))?;
+        }
+    }
+    *seen = Some(generation);
+    Ok(())
+}
+
+/// Whether the content attached as `schema` has `table`; older formats lack
+/// the later tables.
+pub(crate) fn has_table(conn: &Connection, schema: &str, table: &str) -> Result<bool, DbError> {
+    Ok(conn.query_row(
+        &format!(
+            "SELECT COUNT(*) > 0 FROM \"{schema}\".sqlite_master
+             WHERE type IN ('table', 'view') AND name = ?1"
+        ),
+        [table],
+        |row| row.get(0),
+    )?)
+}
+
+/// Verse count and hex SHA-256 of every book of the content attached as
+/// `schema`, over each verse's `chapter:verse` and text in order.
+pub(crate) fn checksums(
+    conn: &Connection,
+    schema: &str,
+) -> Result<BTreeMap<String, (i64, String)>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT book, chapter, verse, text FROM \"{schema}\".verses ORDER BY book, chapter, verse"
+    ))?;
+    let mut rows = stmt.query([])?;
+    let mut hashers: BTreeMap<String, (i64, Sha256)> = BTreeMap::new();
+    while let Some(row) = rows.next()? {
+        let book: String = row.get(0)?;
+        let (chapter, verse, text): (i64, i64, String) = (row.get(1)?, row.get(2)?, row.get(3)?);
+        let (count, hasher) = hashers.entry(book).or_insert_with(|| (0, Sha256::new()));
+        *count += 1;
+        hasher.update(format!("{chapter}:{verse}\t{text}\n").as_bytes());
+    }
+    Ok(hashers
+        .into_iter()
+        .map(|(book, (count, hasher))| (book, (count, to_hex(&hasher.finalize()))))
+        .collect())
+}
+
+/// Record the checksums of `books` (every book when `None`) of the content
+/// file open as `conn`, adding `book_checksums` to older formats.
+pub(crate) fn record_checksums(conn: &Connection, books: Option<&[String]>) -> Result<(), DbError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS book_checksums (
+             book TEXT PRIMARY KEY,
+             verses INTEGER NOT NULL,
+             sha256 TEXT NOT NULL
+         ) WITHOUT ROWID;",
+    )?;
+    if books.is_none() {
+        conn.execute("DELETE FROM book_checksums", [])?;
+    }
+    for (book, (verses, sha256)) in checksums(conn, "main")? {
+        if books.is_none_or(|books| books.contains(&book)) {
+            conn.execute(
+                "INSERT OR REPLACE INTO book_checksums (book, verses, sha256) VALUES (?1, ?2, ?3)",
+                params![book, verses, sha256],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Verse text of one chapter from an attached content schema, keyed by verse
+/// number as in `chapter_cache.verses`.
+pub(crate) fn chapter(
+    conn: &Connection,
+    schema: &str,
+    book: &str,
+    chapter: i64,
+) -> Result<BTreeMap<i64, String>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT verse, text FROM \"{schema}\".verses WHERE book = ?1 AND chapter = ?2"
+    ))?;
+    let verses = stmt
+        .query_map(params![book, chapter], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(verses)
+}
+
+/// Red-letter spans of one chapter from an attached content schema. Files
+/// from before format 7 have none.
+pub(crate) fn red_letter(
+    conn: &Connection,
+    schema: &str,
+    book: &str,
+    chapter: i64,
+) -> Result<BTreeMap<i64, Vec<(usize, usize)>>, DbError> {
+    let mut spans: BTreeMap<i64, Vec<(usize, usize)>> = BTreeMap::new();
+    if !has_table(conn, schema, "red_letter")? {
+        return Ok(spans);
+    }
+    let mut stmt = conn.prepare(&format!(
+        "SELECT verse, position, length FROM \"{schema}\".red_letter
+         WHERE book = ?1 AND chapter = ?2 ORDER BY verse, seq"
+    ))?;
+    let rows = stmt.query_map(params![book, chapter], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (verse, position, length) = row?;
+        let start = position as usize;
+        spans
+            .entry(verse)
+            .or_default()
+            .push((start, start + length as usize));
+    }
+    Ok(spans)
+}
+
+/// Attach a translation's content file (by default
+/// `<app data>/content/<module>.db`) to the native connections.
+#[command]
+pub fn mount_content(
+    app: tauri::AppHandle,
+    module_id: String,
+    path: Option<String>,
+) -> Result<MountedContent, DbError> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => content_path(&app, &module_id)?,
+    };
+    mount(&module_id, &path)
+}
+
+/// Detach a translation's content file. Idle connections are closed so the
+/// file is released right away.
+#[command]
+pub fn unmount_content(app: tauri::AppHandle, module_id: String) -> Result<bool, DbError> {
+    let removed = unmount(&module_id)?;
+    if removed {
+        db::connections::manager(&db::db_path(&app)?).release_readers();
+    }
+    Ok(removed)
+}
+
+#[command]
+pub fn list_mounted_content() -> Vec<MountedContent> {
+    mounts().list.clone()
+}
+
+/// One chapter of a mounted translation, with its red-letter spans,
+/// footnotes and layout.
+#[command]
+pub async fn get_content_chapter(
+    app: tauri::AppHandle,
+    module_id: String,
+    book: String,
+    chapter: i64,
+) -> Result<ContentChapter, DbError> {
+    let mounted = mounted(&module_id).ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("Translation `{module_id}` is not mounted"),
+        )
+    })?;
+    db::with_reader(&app, move |conn| {
+        Ok(ContentChapter {
+            verses: self::chapter(conn, &mounted.schema, &book, chapter)?,
+            red_letter: red_letter(conn, &mounted.schema, &book, chapter)?,
+            footnotes: footnotes::chapter_footnotes(conn, &mounted.schema, &book, chapter)?,
+            headings: layout::chapter_headings(conn, &mounted.schema, &book, chapter)?,
+            paragraphs: layout::chapter_paragraphs(conn, &mounted.schema, &book, chapter)?,
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    fn content_file(dir: &Path, module_id: &str, text: &str) -> PathBuf {
+        let path = dir.join(CONTENT_DIR).join(format!("{module_id}.db"));
+        let conn = create(&path, module_id, module_id).unwrap();
+        conn.execute("INSERT INTO verses VALUES ('John', 1, 1, ?1)", [text])
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn slugs_are_safe_schema_names() {
+        assert_eq!(slug("NASB-2020").unwrap(), "nasb_2020");
+        assert!(slug(" -- ").is_err());
+    }
+
+    #[test]
+    fn mounted_content_attaches_to_read_connections() {
+        let dir = ScratchDir::new("content-mount");
+        let kjv = content_file(&dir, "test-kjv", "In the beginning was the Word");
+        let m = db::connections::manager(&dir.join(db::DB_FILE));
+
+        let mounted = mount("test-kjv", &kjv).unwrap();
+        assert_eq!(
+            (mounted.schema.as_str(), mounted.verses),
+            ("content_test_kjv", 1)
+        );
+        let verses = m
+            .read(|conn| chapter(conn, "content_test_kjv", "John", 1))
+            .unwrap();
+        assert_eq!(verses[&1], "In the beginning was the Word");
+        // Attached read-only: the content can't be edited through the app.
+        let err = m
+            .read(|conn| Ok(conn.execute("DELETE FROM content_test_kjv.verses", [])?))
+            .unwrap_err();
+        assert_eq!(err.kind, DbErrorKind::Sqlite);
+
+        assert!(unmount("test-kjv").unwrap());
+        assert!(!unmount("test-kjv").unwrap());
+        assert!(m
+            .read(|conn| chapter(conn, "content_test_kjv", "John", 1))
+            .is_err());
+        db::connections::close_connections();
+    }
+
+    #[test]
+    fn mount_refuses_files_for_another_translation() {
+        let dir = ScratchDir::new("content-mismatch");
+        let esv = content_file(&dir, "test-esv", "x");
+        assert_eq!(
+            mount("test-other", &esv).unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+        assert_eq!(
+            mount("test-esv", &dir.join("missing.db")).unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+    }
+}
@@ -0,0 +1,542 @@
+//! Reading morphology codes as parsing.
+//!
+//! Tagged original-language texts give each word a morphology code. Greek
+//! texts use Robinson's codes (RMAC): `V-PAI-3S` is a verb, present active
+//! indicative, third person singular. Hebrew and Aramaic texts use the Open
+//! Scriptures Hebrew Morphology (OSHM): `HVqp3ms` is a Hebrew verb, qal
+//! perfect, third person masculine singular, and `/` separates the
+//! prefixes and suffixes written onto a word (`HC/Ncmpa`). Codes in other
+//! schemes (Strong's `TH8804` tense numbers, ...) are kept but not read.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Parsing {
+    pub code: String,
+    /// `greek`, `hebrew` or `aramaic`; `None` for a code not read.
+    pub language: Option<String>,
+    /// The code in words, e.g. `Verb: present active indicative, third
+    /// person singular`.
+    pub description: Option<String>,
+}
+
+/// `code` with its scheme prefix (`robinson:`, `oshm:`) dropped.
+pub(crate) fn strip_scheme(code: &str) -> &str {
+    code.trim().rsplit(':').next().unwrap_or_default()
+}
+
+/// Read `code` in whichever scheme it is written in.
+pub(crate) fn decode(code: &str) -> Parsing {
+    let bare = strip_scheme(code);
+    let (language, description) = match robinson(bare) {
+        Some(description) => (Some("greek"), Some(description)),
+        None => match oshm(bare) {
+            Some((language, description)) => (Some(language), Some(description)),
+            None => (None, None),
+        },
+    };
+    Parsing {
+        code: bare.to_string(),
+        language: language.map(str::to_string),
+        description,
+    }
+}
+
+fn lookup<'a>(table: &[(char, &'a str)], c: char) -> Option<&'a str> {
+    table.iter().find(|(k, _)| *k == c).map(|(_, v)| *v)
+}
+
+fn join(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .filter(|p| !p.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const PERSONS: &[(char, &str)] = &[
+    ('1', "first person"),
+    ('2', "second person"),
+    ('3', "third person"),
+];
+
+// --- Robinson (Greek) ---
+
+/// Codes without inflection.
+const INDECLINABLE: &[(&str, &str)] = &[
+    ("ADV", "Adverb"),
+    ("ADV-I", "Interrogative adverb"),
+    ("ADV-N", "Negative adverb"),
+    ("ADV-C", "Adverb, comparative"),
+    ("ADV-S", "Adverb, superlative"),
+    ("CONJ", "Conjunction"),
+    ("CONJ-N", "Negative conjunction"),
+    ("COND", "Conditional particle"),
+    ("COND-K", "Conditional particle, with crasis"),
+    ("PRT", "Particle"),
+    ("PRT-I", "Interrogative particle"),
+    ("PRT-N", "Negative particle"),
+    ("PREP", "Preposition"),
+    ("INJ", "Interjection"),
+    ("ARAM", "Aramaic word"),
+    ("HEB", "Hebrew word"),
+    ("N-PRI", "Proper noun, indeclinable"),
+    ("N-LI", "Letter, indeclinable"),
+    ("N-OI", "Noun, indeclinable"),
+    ("A-NUI", "Numeral, indeclinable"),
+];
+
+const DECLINED: &[(char, &str)] = &[
+    ('N', "Noun"),
+    ('A', "Adjective"),
+    ('T', "Definite article"),
+    ('P', "Personal pronoun"),
+    ('R', "Relative pronoun"),
+    ('C', "Reciprocal pronoun"),
+    ('D', "Demonstrative pronoun"),
+    ('K', "Correlative pronoun"),
+    ('I', "Interrogative pronoun"),
+    ('X', "Indefinite pronoun"),
+    ('Q', "Correlative or interrogative pronoun"),
+    ('F', "Reflexive pronoun"),
+    ('S', "Possessive pronoun"),
+];
+
+const CASES: &[(char, &str)] = &[
+    ('N', "nominative"),
+    ('G', "genitive"),
+    ('D', "dative"),
+    ('A', "accusative"),
+    ('V', "vocative"),
+];
+
+const NUMBERS: &[(char, &str)] = &[('S', "singular"), ('P', "plural")];
+
+const GENDERS: &[(char, &str)] = &[('M', "masculine"), ('F', "feminine"), ('N', "neuter")];
+
+const TENSES: &[(char, &str)] = &[
+    ('P', "present"),
+    ('I', "imperfect"),
+    ('F', "future"),
+    ('A', "aorist"),
+    ('R', "perfect"),
+    ('L', "pluperfect"),
+    ('X', "no tense stated"),
+];
+
+const VOICES: &[(char, &str)] = &[
+    ('A', "active"),
+    ('M', "middle"),
+    ('P', "passive"),
+    ('E', "middle or passive"),
+    ('D', "middle deponent"),
+    ('O', "passive deponent"),
+    ('N', "middle or passive deponent"),
+    ('Q', "impersonal active"),
+    ('X', "no voice stated"),
+];
+
+const MOODS: &[(char, &str)] = &[
+    ('I', "indicative"),
+    ('S', "subjunctive"),
+    ('O', "optative"),
+    ('M', "imperative"),
+    ('N', "infinitive"),
+    ('P', "participle"),
+    ('R', "imperative participle"),
+];
+
+/// Trailing `-X` qualifiers.
+const QUALIFIERS: &[(&str, &str)] = &[
+    ("C", "comparative"),
+    ("S", "superlative"),
+    ("I", "interrogative"),
+    ("N", "negative"),
+    ("K", "with crasis"),
+    ("ATT", "Attic form"),
+    ("ABB", "abbreviated"),
+    ("M", "middle significance"),
+    ("AP", "apocopated form"),
+];
+
+/// Case, number and gender (`NSM`), with a person before them for
+/// pronouns (`1GS`).
+fn declension(fields: &str) -> Option<String> {
+    let mut chars = fields.chars().peekable();
+    let mut parts = Vec::new();
+    if let Some(person) = chars.peek().and_then(|&c| lookup(PERSONS, c)) {
+        parts.push(person);
+        chars.next();
+    }
+    parts.push(lookup(CASES, chars.next()?)?);
+    parts.push(lookup(NUMBERS, chars.next()?)?);
+    if let Some(c) = chars.next() {
+        parts.push(lookup(GENDERS, c)?);
+    }
+    chars.next().is_none().then(|| join(&parts))
+}
+
+fn robinson(code: &str) -> Option<String> {
+    if let Some((_, name)) = INDECLINABLE.iter().find(|(c, _)| *c == code) {
+        return Some(name.to_string());
+    }
+    let mut fields = code.split('-');
+    let head = fields.next()?;
+    let mut rest: Vec<&str> = fields.collect();
+    let mut qualifiers = Vec::new();
+    while let Some(q) = rest
+        .last()
+        .and_then(|last| QUALIFIERS.iter().find(|(c, _)| c == last))
+    {
+        // The inflection itself can look like one (`N-NSN` vs `-N`).
+        if rest.len() < 2 {
+            break;
+        }
+        qualifiers.insert(0, q.1);
+        rest.pop();
+    }
+    let described = if head == "V" {
+        let (form, person) = match rest.as_slice() {
+            [form] => (*form, None),
+            [form, person] => (*form, Some(*person)),
+            _ => return None,
+        };
+        let (second, form) = match form.strip_prefix('2') {
+            Some(form) => (true, form),
+            None => (false, form),
+        };
+        let mut chars = form.chars();
+        let tense = lookup(TENSES, chars.next()?)?;
+        let voice = lookup(VOICES, chars.next()?)?;
+        let mood = lookup(MOODS, chars.next()?)?;
+        if chars.next().is_some() {
+            return None;
+        }
+        let mut text = format!(
+            "Verb: {}",
+            join(&[if second { "second" } else { "" }, tense, voice, mood])
+        );
+        if let Some(person) = person {
+            let mut chars = person.chars();
+            let first = chars.next()?;
+            let inflection = match lookup(PERSONS, first) {
+                Some(p) => {
+                    let number = lookup(NUMBERS, chars.next()?)?;
+                    if chars.next().is_some() {
+                        return None;
+                    }
+                    join(&[p, number])
+                }
+                None => declension(person)?,
+            };
+            text.push_str(", ");
+            text.push_str(&inflection);
+        }
+        text
+    } else {
+        let mut chars = head.chars();
+        let name = lookup(DECLINED, chars.next()?)?;
+        if chars.next().is_some() {
+            return None;
+        }
+        let [fields] = rest.as_slice() else {
+            return None;
+        };
+        // Possessive pronouns name the possessor first: `S-1SNSM`.
+        if head == "S" {
+            let mut chars = fields.chars();
+            let person = lookup(PERSONS, chars.next()?)?;
+            let possessor = lookup(NUMBERS, chars.next()?)?;
+            format!(
+                "{name}: {person} {possessor} possessor, {}",
+                declension(chars.as_str())?
+            )
+        } else {
+            format!("{name}: {}", declension(fields)?)
+        }
+    };
+    if qualifiers.is_empty() {
+        Some(described)
+    } else {
+        Some(format!("{described}, {}", qualifiers.join(", ")))
+    }
+}
+
+// --- OSHM (Hebrew and Aramaic) ---
+
+const HEBREW_STEMS: &[(char, &str)] = &[
+    ('q', "qal"),
+    ('N', "niphal"),
+    ('p', "piel"),
+    ('P', "pual"),
+    ('h', "hiphil"),
+    ('H', "hophal"),
+    ('t', "hithpael"),
+    ('o', "polel"),
+    ('O', "polal"),
+    ('r', "hithpolel"),
+    ('m', "poel"),
+    ('M', "poal"),
+    ('k', "palel"),
+    ('K', "pulal"),
+    ('Q', "qal passive"),
+    ('l', "pilpel"),
+    ('L', "polpal"),
+    ('f', "hithpalpel"),
+    ('D', "nithpael"),
+    ('j', "pealal"),
+    ('i', "pilel"),
+    ('u', "hothpaal"),
+    ('c', "tiphil"),
+    ('v', "hishtaphel"),
+    ('w', "nithpalel"),
+    ('y', "nithpoel"),
+    ('z', "hithpoel"),
+];
+
+const ARAMAIC_STEMS: &[(char, &str)] = &[
+    ('q', "peal"),
+    ('Q', "peil"),
+    ('u', "hithpeel"),
+    ('p', "pael"),
+    ('P', "ithpaal"),
+    ('M', "hithpaal"),
+    ('a', "aphel"),
+    ('h', "haphel"),
+    ('s', "saphel"),
+    ('e', "shaphel"),
+    ('H', "hophal"),
+    ('i', "ithpeel"),
+    ('t', "hishtaphel"),
+    ('v', "ishtaphel"),
+    ('w', "hithaphel"),
+    ('o', "polel"),
+    ('z', "ithpoel"),
+    ('r', "hithpolel"),
+    ('f', "hithpalpel"),
+    ('b', "hephal"),
+    ('c', "tiphel"),
+    ('m', "poel"),
+    ('l', "palpel"),
+    ('L', "ithpalpel"),
+    ('O', "ithpolel"),
+    ('G', "ittaphal"),
+];
+
+const CONJUGATIONS: &[(char, &str)] = &[
+    ('p', "perfect"),
+    ('q', "sequential perfect"),
+    ('i', "imperfect"),
+    ('w', "sequential imperfect"),
+    ('h', "cohortative"),
+    ('j', "jussive"),
+    ('v', "imperative"),
+    ('r', "active participle"),
+    ('s', "passive participle"),
+    ('a', "infinitive absolute"),
+    ('c', "infinitive construct"),
+];
+
+const HEBREW_GENDERS: &[(char, &str)] = &[
+    ('m', "masculine"),
+    ('f', "feminine"),
+    ('b', "masculine or feminine"),
+    ('c', "common"),
+];
+
+const HEBREW_NUMBERS: &[(char, &str)] = &[('s', "singular"), ('p', "plural"), ('d', "dual")];
+
+const STATES: &[(char, &str)] = &[('a', "absolute"), ('c', "construct"), ('d', "determined")];
+
+/// Person, gender, number and state, each optional but in that order, as
+/// the rest of a segment spells them (`3ms`, `mpa`).
+fn hebrew_inflection(fields: &str) -> Option<String> {
+    let tables: [&[(char, &str)]; 4] = [PERSONS, HEBREW_GENDERS, HEBREW_NUMBERS, STATES];
+    let mut parts = Vec::new();
+    let mut next = 0;
+    for c in fields.chars() {
+        // `x` marks a field the word leaves unstated.
+        if c == 'x' {
+            next += 1;
+            continue;
+        }
+        let at = (next..tables.len()).find(|&i| lookup(tables[i], c).is_some())?;
+        parts.push(lookup(tables[at], c)?);
+        next = at + 1;
+    }
+    Some(join(&parts))
+}
+
+/// One `/`-separated segment of an OSHM code.
+fn oshm_segment(segment: &str, aramaic: bool) -> Option<String> {
+    let mut chars = segment.chars();
+    let pos = chars.next()?;
+    let rest = chars.as_str();
+    let typed = |name: &str, types: &[(char, &str)]| -> Option<String> {
+        let mut chars = rest.chars();
+        let Some(c) = chars.next() else {
+            return Some(name.to_string());
+        };
+        let kind = lookup(types, c)?;
+        let inflection = hebrew_inflection(chars.as_str())?;
+        Some(if inflection.is_empty() {
+            format!("{name}: {kind}")
+        } else {
+            format!("{name}: {kind}, {inflection}")
+        })
+    };
+    match pos {
+        'V' => {
+            let mut chars = rest.chars();
+            let stems = if aramaic { ARAMAIC_STEMS } else { HEBREW_STEMS };
+            let stem = lookup(stems, chars.next()?)?;
+            let conjugation = lookup(CONJUGATIONS, chars.next()?)?;
+            let inflection = hebrew_inflection(chars.as_str())?;
+            Some(if inflection.is_empty() {
+                format!("Verb: {stem} {conjugation}")
+            } else {
+                format!("Verb: {stem} {conjugation}, {inflection}")
+            })
+        }
+        'N' => typed(
+            "Noun",
+            &[('c', "common"), ('g', "gentilic"), ('p', "proper name")],
+        ),
+        'A' => typed(
+            "Adjective",
+            &[
+                ('a', "adjective"),
+                ('c', "cardinal number"),
+                ('g', "gentilic"),
+                ('o', "ordinal number"),
+            ],
+        ),
+        'P' => typed(
+            "Pronoun",
+            &[
+                ('d', "demonstrative"),
+                ('f', "indefinite"),
+                ('i', "interrogative"),
+                ('p', "personal"),
+                ('r', "relative"),
+            ],
+        ),
+        'S' => typed(
+            "Suffix",
+            &[
+                ('d', "directional he"),
+                ('h', "paragogic he"),
+                ('n', "paragogic nun"),
+                ('p', "pronominal"),
+            ],
+        ),
+        'T' => typed(
+            "Particle",
+            &[
+                ('a', "affirmation"),
+                ('d', "definite article"),
+                ('e', "exhortation"),
+                ('i', "interrogative"),
+                ('j', "interjection"),
+                ('m', "demonstrative"),
+                ('n', "negative"),
+                ('o', "direct object marker"),
+                ('r', "relative"),
+            ],
+        ),
+        'R' if rest == "d" => Some("Preposition, with definite article".to_string()),
+        'R' if rest.is_empty() => Some("Preposition".to_string()),
+        'C' if rest.is_empty() => Some("Conjunction".to_string()),
+        'D' if rest.is_empty() => Some("Adverb".to_string()),
+        _ => None,
+    }
+}
+
+fn oshm(code: &str) -> Option<(&'static str, String)> {
+    let (language, aramaic) = match code.chars().next()? {
+        'H' => ("hebrew", false),
+        'A' => ("aramaic", true),
+        _ => return None,
+    };
+    let segments = code[1..]
+        .split('/')
+        .map(|segment| oshm_segment(segment, aramaic))
+        .collect::<Option<Vec<_>>>()?;
+    Some((language, segments.join(" + ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn described(code: &str) -> Option<String> {
+        decode(code).description
+    }
+
+    #[test]
+    fn reads_robinson_codes() {
+        assert_eq!(
+            described("robinson:V-PAI-3S").as_deref(),
+            Some("Verb: present active indicative, third person singular")
+        );
+        assert_eq!(
+            described("V-2AAP-NSM").as_deref(),
+            Some("Verb: second aorist active participle, nominative singular masculine")
+        );
+        assert_eq!(
+            described("V-PAN").as_deref(),
+            Some("Verb: present active infinitive")
+        );
+        assert_eq!(
+            described("N-NSM").as_deref(),
+            Some("Noun: nominative singular masculine")
+        );
+        assert_eq!(
+            described("P-1GS").as_deref(),
+            Some("Personal pronoun: first person genitive singular")
+        );
+        assert_eq!(
+            described("A-NSM-C").as_deref(),
+            Some("Adjective: nominative singular masculine, comparative")
+        );
+        assert_eq!(
+            described("S-1SNSM").as_deref(),
+            Some("Possessive pronoun: first person singular possessor, nominative singular masculine")
+        );
+        assert_eq!(described("CONJ").as_deref(), Some("Conjunction"));
+        assert_eq!(decode("N-NSM").language.as_deref(), Some("greek"));
+    }
+
+    #[test]
+    fn reads_oshm_codes() {
+        assert_eq!(
+            described("oshm:HVqp3ms").as_deref(),
+            Some("Verb: qal perfect, third person masculine singular")
+        );
+        let parsing = decode("HC/Ncmpa");
+        assert_eq!(parsing.language.as_deref(), Some("hebrew"));
+        assert_eq!(
+            parsing.description.as_deref(),
+            Some("Conjunction + Noun: common, masculine plural absolute")
+        );
+        assert_eq!(
+            described("HR/Td/Ncfsa").as_deref(),
+            Some("Preposition + Particle: definite article + Noun: common, feminine singular absolute")
+        );
+        assert_eq!(
+            described("AVhp3ms").as_deref(),
+            Some("Verb: haphel perfect, third person masculine singular")
+        );
+    }
+
+    #[test]
+    fn keeps_codes_it_cannot_read() {
+        let parsing = decode("TH8804");
+        assert_eq!(
+            (parsing.code.as_str(), parsing.language, parsing.description),
+            ("TH8804", None, None)
+        );
+        assert_eq!(described("V-ZZZ-3S"), None);
+    }
+}
@@ -0,0 +1,664 @@
+//! Original-language texts: the SBL Greek New Testament and the
+//! Westminster Leningrad Codex.
+//!
+//! The SBLGNT is read from MorphGNT, which gives it one word per line with
+//! its part of speech and parsing: `010101 N- ----NSF- Βίβλος Βίβλος βίβλος
+//! βίβλος` is Matthew 1:1's first word (books are numbered from Matthew).
+//! Parsing is kept as the Robinson code (`N-NSF`) the reader decodes.
+//!
+//! The WLC is read from the Open Scriptures Hebrew Bible's OSIS files, one
+//! book each: `<w lemma="b/7225" morph="HR/Ncfsa">בְּ/רֵאשִׁ֖ית</w>` is a word,
+//! `/` marking where its prefixes and suffixes join. Words joined by a
+//! maqqef (`<seg type="x-maqqef">`) are kept together, the sof pasuq ends
+//! the verse and the open and closed section marks (pe, samekh) begin a
+//! paragraph at the next verse. Hebrew is kept in the order the source
+//! writes it rather than normalized: NFC reorders the vowel points and the
+//! dagesh in ways common fonts render wrongly. Cantillation (U+0591 to
+//! U+05AF) can be dropped, keeping the vowel points.
+
+use std::path::PathBuf;
+use tauri::command;
+
+use super::books;
+use super::import::{self, Book, Footnote, ImportReport, Verse, Warnings};
+use super::xml::{self, Event};
+use crate::db::DbError;
+
+// --- SBLGNT (MorphGNT) ---
+
+/// Text-critical signs SBLGNT prints where its apparatus has a note; the
+/// apparatus isn't imported, so they are dropped.
+const APPARATUS_SIGNS: &[char] = &['⸀', '⸁', '⸂', '⸃', '⸄', '⸅'];
+
+/// The Robinson code for MorphGNT part of speech `pos` and parsing
+/// `parsing` (person, tense, voice, mood, case, number, gender, degree,
+/// `-` where not given), or `None` for ones it has no code for.
+fn robinson_code(pos: &str, parsing: &str) -> Option<String> {
+    let fields: Vec<char> = parsing.chars().collect();
+    let [person, tense, voice, mood, case, number, gender, degree] = fields[..] else {
+        return None;
+    };
+    let given = |c: char| (c != '-').then_some(c);
+    let declension = || -> Option<String> {
+        let mut code = String::new();
+        code.push(given(case)?);
+        code.push(given(number)?);
+        code.extend(given(gender));
+        Some(code)
+    };
+    let declined = |head: &str| -> Option<String> {
+        let mut code = format!("{head}-{}", declension()?);
+        if let Some(degree) = given(degree) {
+            code.push('-');
+            code.push(degree);
+        }
+        Some(code)
+    };
+    match pos {
+        "N-" => declined("N"),
+        "A-" => declined("A"),
+        "RA" => declined("T"),
+        "RD" => declined("D"),
+        "RI" => declined("I"),
+        "RR" => declined("R"),
+        "RP" => match given(person) {
+            // First and second person pronouns aren't marked for gender.
+            Some(person) => Some(format!("P-{person}{}{}", given(case)?, given(number)?)),
+            None => declined("P"),
+        },
+        "V-" => {
+            let tense = match given(tense)? {
+                'X' => 'R',
+                'Y' => 'L',
+                t => t,
+            };
+            let mood = match given(mood)? {
+                'D' => 'M',
+                m => m,
+            };
+            let form = format!("V-{tense}{}{mood}", given(voice)?);
+            match mood {
+                'N' => Some(form),
+                'P' => Some(format!("{form}-{}", declension()?)),
+                _ => Some(format!("{form}-{}{}", given(person)?, given(number)?)),
+            }
+        }
+        "C-" => Some("CONJ".into()),
+        "D-" => Some("ADV".into()),
+        "I-" => Some("INJ".into()),
+        "P-" => Some("PREP".into()),
+        "X-" => Some("PRT".into()),
+        _ => None,
+    }
+}
+
+/// The books in a MorphGNT file (one book or the whole New Testament).
+pub(crate) fn parse_morphgnt(src: &str, file: &str, warnings: &mut Warnings) -> Vec<Book> {
+    let mut books: Vec<Book> = Vec::new();
+    let mut skipped = 0;
+    for (n, line) in src.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() {
+            continue;
+        }
+        let parsed = match fields[..] {
+            [bcv, pos, parsing, text, word, ..]
+                if bcv.len() == 6 && bcv.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                bcv[0..2]
+                    .parse::<usize>()
+                    .ok()
+                    .zip(bcv[2..4].parse::<i64>().ok())
+                    .zip(bcv[4..6].parse::<i64>().ok())
+                    .map(|((b, c), v)| (b, c, v, pos, parsing, text, word))
+            }
+            _ => None,
+        };
+        let Some((number, chapter, verse, pos, parsing, text, word)) = parsed else {
+            skipped += 1;
+            if skipped == 1 {
+                warnings.push(file, Some(n + 1), "Not a MorphGNT line; skipped");
+            }
+            continue;
+        };
+        let Some(id) = number
+            .checked_sub(1)
+            .and_then(|i| books::BOOKS.get(39 + i))
+            .map(|b| b.osis)
+        else {
+            warnings.push(
+                file,
+                Some(n + 1),
+                format!("Book {number} is not a New Testament book; skipped"),
+            );
+            continue;
+        };
+        if books.last().map(|b| b.id.as_str()) != Some(id) {
+            books.push(Book {
+                id: id.to_string(),
+                file: file.to_string(),
+                ..Default::default()
+            });
+        }
+        let book = books.last_mut().expect("just pushed");
+        let last = book.verses.last();
+        if last.map(|v| (v.chapter, v.verse)) != Some((chapter, verse)) {
+            book.verses.push(Verse {
+                chapter,
+                verse,
+                through: verse,
+                line: Some(n + 1),
+                ..Default::default()
+            });
+        }
+        let verse = book.verses.last_mut().expect("just pushed");
+        let text: String = text
+            .chars()
+            .filter(|c| !APPARATUS_SIGNS.contains(c))
+            .collect();
+        if !verse.text.is_empty() {
+            verse.text.push(' ');
+        }
+        let start = verse.text.len();
+        verse.text.push_str(&text);
+        // Tag the word without the punctuation around it.
+        let word: String = word
+            .chars()
+            .filter(|c| !APPARATUS_SIGNS.contains(c))
+            .collect();
+        if let Some(at) = text.find(&word).filter(|_| !word.is_empty()) {
+            let end = start + at + word.len();
+            import::tag_word(
+                &mut verse.words,
+                &verse.text[..end],
+                start + at,
+                Vec::new(),
+                None,
+                robinson_code(pos, parsing),
+            );
+        }
+    }
+    if skipped > 1 {
+        warnings.push(
+            file,
+            None,
+            format!("{skipped} lines weren't MorphGNT lines and were skipped"),
+        );
+    }
+    books
+}
+
+// --- WLC (Open Scriptures Hebrew Bible OSIS) ---
+
+const MAQQEF: char = '\u{05be}';
+
+/// `text` without its cantillation marks (U+0591 to U+05AF); vowel points,
+/// meteg and punctuation are kept.
+fn strip_cantillation(text: &str) -> String {
+    text.chars()
+        .filter(|c| !('\u{0591}'..='\u{05af}').contains(c))
+        .collect()
+}
+
+/// The Strong's numbers in a morphhb `lemma`: `b/7225` is the prefix `b`
+/// and H7225, `1254 a` the first of the homographs numbered 1254.
+fn lemma_strongs(lemma: &str) -> Vec<String> {
+    let numbers: Vec<&str> = lemma
+        .split('/')
+        .filter_map(|part| part.split_whitespace().next())
+        .filter(|part| part.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+    import::strongs_numbers(&numbers.join(" "), Some('H'))
+}
+
+struct WlcParser<'w> {
+    file: String,
+    warnings: &'w mut Warnings,
+    accents: bool,
+    books: Vec<Book>,
+    book: Option<Book>,
+    verse: Option<usize>,
+    /// Open `<div>`s, and whether each is the book's.
+    divs: Vec<bool>,
+    /// The next word follows a maqqef, without a space.
+    joined: bool,
+    /// Start in the verse text, Strong's numbers and morphology of the open
+    /// `<w>`.
+    word: Option<(usize, Vec<String>, Option<String>)>,
+    /// Type of the open `<seg>`.
+    seg: Option<String>,
+    /// Text, anchor offset and kind of the open note, with whether its
+    /// reading is the qere.
+    note: Option<(String, usize, String, bool)>,
+    /// Open `<catchWord>`s, whose text is dropped.
+    catch_word: usize,
+    /// A section mark closed the last verse: the next begins a paragraph.
+    paragraph: bool,
+    line: usize,
+}
+
+impl<'w> WlcParser<'w> {
+    fn warn(&mut self, message: impl Into<String>) {
+        let line = Some(self.line);
+        self.warnings.push(&self.file, line, message);
+    }
+
+    fn end_book(&mut self) {
+        if let Some(book) = self.book.take() {
+            self.books.push(book);
+        }
+        self.verse = None;
+        self.paragraph = false;
+    }
+
+    fn start_book(&mut self, attrs: &[(&str, String)]) {
+        self.end_book();
+        let id = xml::attr(attrs, "osisID").unwrap_or_default().trim();
+        match books::by_name(id) {
+            Some(book) => {
+                self.book = Some(Book {
+                    id: book.osis.to_string(),
+                    file: self.file.clone(),
+                    ..Default::default()
+                })
+            }
+            None => self.warn(format!("Book `{id}` is not one BibleMarker shows; skipped")),
+        }
+    }
+
+    fn start_verse(&mut self, attrs: &[(&str, String)]) {
+        self.verse = None;
+        self.joined = false;
+        let id = xml::attr(attrs, "osisID").unwrap_or_default();
+        let mut parts = id.rsplit('.');
+        let numbers = parts
+            .next()
+            .and_then(|v| v.parse::<i64>().ok())
+            .zip(parts.next().and_then(|c| c.parse::<i64>().ok()))
+            .filter(|&(v, c)| v > 0 && c > 0);
+        let Some((verse, chapter)) = numbers else {
+            self.warn(format!("`{id}` is not a verse reference; dropped"));
+            return;
+        };
+        let paragraph = std::mem::take(&mut self.paragraph);
+        let line = Some(self.line);
+        let Some(book) = &mut self.book else {
+            return;
+        };
+        book.verses.push(Verse {
+            chapter,
+            verse,
+            through: verse,
+            paragraphs: if paragraph {
+                vec![(0, "p".to_string())]
+            } else {
+                Vec::new()
+            },
+            line,
+            ..Default::default()
+        });
+        self.verse = Some(book.verses.len() - 1);
+    }
+
+    /// The verse text being written, unless inside a note.
+    fn verse_text(&mut self) -> Option<&mut String> {
+        match (self.verse, &mut self.book) {
+            (Some(i), Some(book)) if self.note.is_none() => Some(&mut book.verses[i].text),
+            _ => None,
+        }
+    }
+
+    fn start(&mut self, name: &str, attrs: &[(&str, String)], empty: bool) {
+        match name {
+            "div" => {
+                let book = xml::attr(attrs, "type") == Some("book");
+                if book {
+                    self.start_book(attrs);
+                }
+                self.divs.push(book);
+            }
+            "verse" if self.book.is_some() => self.start_verse(attrs),
+            "w" if !empty => {
+                let strongs = lemma_strongs(xml::attr(attrs, "lemma").unwrap_or_default());
+                let morph = xml::attr(attrs, "morph").and_then(import::morph_code);
+                let joined = std::mem::take(&mut self.joined);
+                if let Some((note, ..)) = &mut self.note {
+                    if !note.is_empty() {
+                        note.push(' ');
+                    }
+                } else if let Some(text) = self.verse_text() {
+                    if !text.is_empty() && !joined {
+                        text.push(' ');
+                    }
+                    let start = text.len();
+                    self.word = Some((start, strongs, morph));
+                }
+            }
+            "seg" if !empty => self.seg = xml::attr(attrs, "type").map(str::to_string),
+            "note" if !empty && self.note.is_none() => {
+                if let (Some(i), Some(book)) = (self.verse, &self.book) {
+                    let anchor = book.verses[i].text.chars().count();
+                    let kind = import::note_kind(xml::attr(attrs, "type"));
+                    self.note = Some((String::new(), anchor, kind, false));
+                }
+            }
+            "rdg" => {
+                if let Some((.., qere)) = &mut self.note {
+                    *qere |= xml::attr(attrs, "type") == Some("x-qere");
+                }
+            }
+            "catchWord" if !empty => self.catch_word += 1,
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "div" => {
+                let book = self.divs.pop() == Some(true);
+                if book {
+                    self.end_book();
+                }
+            }
+            "verse" => self.verse = None,
+            "w" => {
+                if let (Some((start, strongs, morph)), Some(i), Some(book)) =
+                    (self.word.take(), self.verse, &mut self.book)
+                {
+                    let verse = &mut book.verses[i];
+                    import::tag_word(&mut verse.words, &verse.text, start, strongs, None, morph);
+                }
+            }
+            "seg" => self.seg = None,
+            "note" => self.close_note(),
+            "catchWord" => self.catch_word = self.catch_word.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    fn close_note(&mut self) {
+        let Some((text, position, kind, qere)) = self.note.take() else {
+            return;
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        let text = if qere {
+            format!("Qere: {text}")
+        } else {
+            text.to_string()
+        };
+        if let (Some(i), Some(book)) = (self.verse, &mut self.book) {
+            let (chapter, verse) = (book.verses[i].chapter, book.verses[i].verse);
+            book.footnotes.push(Footnote {
+                chapter,
+                verse,
+                caller: "+".into(),
+                position,
+                text,
+                kind,
+            });
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.catch_word > 0 || self.book.is_none() {
+            return;
+        }
+        let mut text = text.replace('/', "");
+        if !self.accents {
+            text = strip_cantillation(&text);
+        }
+        if let Some((note, ..)) = &mut self.note {
+            import::push_text(note, &text);
+            return;
+        }
+        let seg = self.seg.clone();
+        if matches!(seg.as_deref(), Some("x-pe" | "x-samekh")) {
+            self.paragraph = true;
+            return;
+        }
+        let in_word = self.word.is_some();
+        let Some(verse) = self.verse_text() else {
+            return;
+        };
+        match seg.as_deref() {
+            Some("x-maqqef") => {
+                verse.truncate(verse.trim_end().len());
+                verse.push(MAQQEF);
+                self.joined = true;
+            }
+            // The sof pasuq is written onto the verse's last word.
+            Some("x-sof-pasuq") => {
+                verse.truncate(verse.trim_end().len());
+                verse.push_str(text.trim());
+            }
+            _ if in_word => verse.push_str(text.trim()),
+            _ => {
+                let text = text.trim();
+                if !text.is_empty() {
+                    if !verse.is_empty() {
+                        verse.push(' ');
+                    }
+                    verse.push_str(text);
+                }
+            }
+        }
+    }
+}
+
+fn parse_wlc_with(accents: bool, src: &str, file: &str, warnings: &mut Warnings) -> Vec<Book> {
+    let events = match xml::parse(src) {
+        Ok(events) => events,
+        Err((line, message)) => {
+            warnings.push(file, Some(line), format!("Not valid XML: {message}"));
+            return Vec::new();
+        }
+    };
+    let mut parser = WlcParser {
+        file: file.to_string(),
+        warnings,
+        accents,
+        books: Vec::new(),
+        book: None,
+        verse: None,
+        divs: Vec::new(),
+        joined: false,
+        word: None,
+        seg: None,
+        note: None,
+        catch_word: 0,
+        paragraph: false,
+        line: 1,
+    };
+    let mut saw_root = false;
+    for event in &events {
+        match event {
+            Event::Start {
+                name,
+                attrs,
+                empty,
+                line,
+            } => {
+                parser.line = *line;
+                saw_root |= *name == "osis";
+                parser.start(name, attrs, *empty);
+                if *empty {
+                    parser.end(name);
+                }
+            }
+            Event::End { name, line } => {
+                parser.line = *line;
+                parser.end(name);
+            }
+            Event::Text(text) => parser.text(text),
+        }
+    }
+    parser.end_book();
+    if !saw_root {
+        parser
+            .warnings
+            .push(file, None, "No `<osis>` element; wrong format?");
+    }
+    parser.books
+}
+
+/// The books in Open Scriptures Hebrew Bible OSIS files, with cantillation.
+pub(crate) fn parse_wlc(src: &str, file: &str, warnings: &mut Warnings) -> Vec<Book> {
+    parse_wlc_with(true, src, file, warnings)
+}
+
+/// The books in Open Scriptures Hebrew Bible OSIS files, pointed but
+/// without cantillation.
+pub(crate) fn parse_wlc_pointed(src: &str, file: &str, warnings: &mut Warnings) -> Vec<Book> {
+    parse_wlc_with(false, src, file, warnings)
+}
+
+/// Import MorphGNT files as the SBL Greek New Testament (`SBLGNT` unless
+/// `module_id` says otherwise), replacing its content file.
+#[command]
+pub async fn import_sblgnt(
+    app: tauri::AppHandle,
+    module_id: Option<String>,
+    name: Option<String>,
+    paths: Vec<String>,
+) -> Result<ImportReport, DbError> {
+    let module_id = module_id.unwrap_or_else(|| "SBLGNT".into());
+    let name = name.unwrap_or_else(|| "SBL Greek New Testament".into());
+    let paths = paths.into_iter().map(PathBuf::from).collect();
+    let info = &[("language", "grc"), ("versification", "KJV")];
+    import::import_files(
+        app,
+        module_id,
+        name,
+        "morphgnt",
+        info,
+        paths,
+        parse_morphgnt,
+    )
+    .await
+}
+
+/// Import Open Scriptures Hebrew Bible OSIS files as the Westminster
+/// Leningrad Codex (`WLC` unless `module_id` says otherwise), replacing its
+/// content file. Cantillation is kept unless `accents` is false.
+#[command]
+pub async fn import_wlc(
+    app: tauri::AppHandle,
+    module_id: Option<String>,
+    name: Option<String>,
+    paths: Vec<String>,
+    accents: Option<bool>,
+) -> Result<ImportReport, DbError> {
+    let module_id = module_id.unwrap_or_else(|| "WLC".into());
+    let name = name.unwrap_or_else(|| "Westminster Leningrad Codex".into());
+    let paths = paths.into_iter().map(PathBuf::from).collect();
+    let parse = if accents.unwrap_or(true) {
+        parse_wlc
+    } else {
+        parse_wlc_pointed
+    };
+    let info = &[("language", "hbo"), ("versification", "Leningrad")];
+    import::import_files(app, module_id, name, "osis", info, paths, parse).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_morphgnt_words_as_robinson_codes() {
+        let src = "\
+010101 N- ----NSF- Βίβλος Βίβλος βίβλος βίβλος
+010101 N- ----GSF- γενέσεως γενέσεως γενέσεως γένεσις
+010101 N- ----GSM- ⸀Ἰησοῦ Ἰησοῦ Ἰησοῦ Ἰησοῦς
+010101 N- ----GSM- Χριστοῦ, Χριστοῦ Χριστοῦ Χριστός
+010102 V- 3AAI-S-- ἐγέννησεν ἐγέννησεν ἐγέννησεν γεννάω
+040101 P- -------- Ἐν Ἐν ἐν ἐν
+";
+        let mut warnings = Warnings::default();
+        let books = parse_morphgnt(src, "61-Mt-morphgnt.txt", &mut warnings);
+        let ids: Vec<&str> = books.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, ["Matt", "John"]);
+        let verse = &books[0].verses[0];
+        assert_eq!(verse.text, "Βίβλος γενέσεως Ἰησοῦ Χριστοῦ,");
+        let words: Vec<(usize, &str, Option<&str>)> = verse
+            .words
+            .iter()
+            .map(|w| (w.position, w.text.as_str(), w.morph.as_deref()))
+            .collect();
+        assert_eq!(words[3], (22, "Χριστοῦ", Some("N-GSM")));
+        assert_eq!(
+            books[0].verses[1].words[0].morph.as_deref(),
+            Some("V-AAI-3S")
+        );
+        assert_eq!(
+            robinson_code("V-", "-PAPNSM-").as_deref(),
+            Some("V-PAP-NSM")
+        );
+        assert_eq!(robinson_code("V-", "-XAN----").as_deref(), Some("V-RAN"));
+        assert_eq!(robinson_code("RP", "1---DS--").as_deref(), Some("P-1DS"));
+        assert_eq!(robinson_code("A-", "----NSMC").as_deref(), Some("A-NSM-C"));
+        assert!(warnings.list.is_empty(), "{:?}", warnings.list);
+    }
+
+    const GENESIS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<osis xmlns="http://www.bibletechnologies.net/2003/OSIS/namespace">
+<osisText osisIDWork="WLC" xml:lang="he">
+<header><work osisWork="WLC"><title>Genesis</title></work></header>
+<div type="book" osisID="Gen">
+<chapter osisID="Gen.1">
+<verse osisID="Gen.1.1">
+<w lemma="b/7225" morph="HR/Ncfsa" id="01xeN">בְּ/רֵאשִׁ֖ית</w>
+<w lemma="1254 a" morph="HVqp3ms" id="01Nvk">בָּרָ֣א</w>
+<w lemma="430" morph="HNcmpa" id="01TyA">אֱלֹהִ֑ים</w>
+<seg type="x-sof-pasuq">׃</seg>
+</verse>
+<verse osisID="Gen.1.2">
+<w lemma="c/776" morph="HC/Td/Ncbsa">וְ/הָ/אָ֗רֶץ</w>
+<w lemma="5921 a" morph="HR">עַל</w><seg type="x-maqqef">־</seg><w lemma="6440" morph="HNcbpc">פְּנֵ֣י</w>
+<w lemma="8415" morph="HNcbsa">תְה֑וֹם</w>
+<note type="variant"><catchWord>תְה֑וֹם</catchWord><rdg type="x-qere"><w morph="HNcbsa">תְּהוֹם</w></rdg></note>
+<seg type="x-sof-pasuq">׃</seg> <seg type="x-pe">פ</seg>
+</verse>
+<verse osisID="Gen.1.3">
+<w lemma="c/559" morph="HC/Vqw3ms">וַ/יֹּ֥אמֶר</w>
+<seg type="x-sof-pasuq">׃</seg>
+</verse>
+</chapter>
+</div>
+</osisText>
+</osis>"#;
+
+    #[test]
+    fn reads_wlc_words_maqqef_and_sections() {
+        let mut warnings = Warnings::default();
+        let books = parse_wlc(GENESIS, "Gen.xml", &mut warnings);
+        assert!(warnings.list.is_empty(), "{:?}", warnings.list);
+        let genesis = &books[0];
+        assert_eq!(genesis.id, "Gen");
+        assert_eq!(genesis.verses[0].text, "בְּרֵאשִׁ֖ית בָּרָ֣א אֱלֹהִ֑ים׃");
+        let first = &genesis.verses[0].words[0];
+        assert_eq!(
+            (first.text.as_str(), first.strongs.as_slice()),
+            ("בְּרֵאשִׁ֖ית", ["H7225".to_string()].as_slice())
+        );
+        assert_eq!(first.morph.as_deref(), Some("HR/Ncfsa"));
+        assert_eq!(genesis.verses[0].words[1].strongs, ["H1254"]);
+
+        let second = &genesis.verses[1];
+        assert_eq!(second.text, "וְהָאָ֗רֶץ עַל־פְּנֵ֣י תְה֑וֹם׃");
+        assert_eq!(second.words[2].text, "פְּנֵ֣י");
+        let note = &genesis.footnotes[0];
+        assert_eq!(
+            (note.text.as_str(), note.kind.as_str(), note.verse),
+            ("Qere: תְּהוֹם", "variant", 2)
+        );
+        assert!(second.paragraphs.is_empty());
+        assert_eq!(genesis.verses[2].paragraphs, [(0, "p".to_string())]);
+
+        let pointed = parse_wlc_pointed(GENESIS, "Gen.xml", &mut warnings);
+        assert_eq!(pointed[0].verses[0].text, "בְּרֵאשִׁית בָּרָא אֱלֹהִים׃");
+    }
+}
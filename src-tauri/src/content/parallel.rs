@@ -0,0 +1,200 @@
+//! Parallel reading: one chapter in several translations, verse by verse.
+//!
+//! Rows follow the first translation's verses. The others' verses are
+//! mapped onto its numbering (see `versification`), so a Hebrew Bible's
+//! Malachi 3:19-24 lines up with an English Malachi 4, and a verse that
+//! only one numbering has gets a row of its own.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::command;
+
+use super::versification::{self, Scheme};
+use super::{mounted, MountedContent};
+use crate::db::{self, DbError, DbErrorKind};
+
+/// Most translations shown side by side.
+const MAX_TRANSLATIONS: usize = 4;
+
+/// A verse of one translation, numbered as that translation numbers it.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ParallelVerse {
+    pub chapter: i64,
+    pub verse: i64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ParallelRow {
+    /// Verse in the first translation's numbering; 0 for a psalm title only
+    /// another translation numbers.
+    pub verse: i64,
+    /// Each translation's verses for the row, in `translations` order;
+    /// empty where a translation has none.
+    pub cells: Vec<Vec<ParallelVerse>>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ParallelChapter {
+    pub book: String,
+    pub chapter: i64,
+    pub translations: Vec<String>,
+    pub rows: Vec<ParallelRow>,
+}
+
+/// Verses of chapters `first` to `last` of `book`, in order.
+fn verses(
+    conn: &Connection,
+    schema: &str,
+    book: &str,
+    first: i64,
+    last: i64,
+) -> Result<Vec<ParallelVerse>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT chapter, verse, text FROM \"{schema}\".verses
+         WHERE book = ?1 AND chapter BETWEEN ?2 AND ?3 ORDER BY chapter, verse"
+    ))?;
+    let verses = stmt
+        .query_map(params![book, first, last], |row| {
+            Ok(ParallelVerse {
+                chapter: row.get(0)?,
+                verse: row.get(1)?,
+                text: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(verses)
+}
+
+/// `chapter` of `book` across `translations`, aligned on the first one's
+/// verse numbers.
+pub(crate) fn parallel_chapter(
+    conn: &Connection,
+    book: &str,
+    chapter: i64,
+    translations: &[MountedContent],
+) -> Result<ParallelChapter, DbError> {
+    let mut rows: BTreeMap<i64, Vec<Vec<ParallelVerse>>> = BTreeMap::new();
+    let mut primary = Scheme::Kjv;
+    for (column, content) in translations.iter().enumerate() {
+        let scheme = versification::declared(conn, &content.schema)?;
+        if column == 0 {
+            primary = scheme;
+        }
+        // Chapter breaks move by at most a chapter between numberings.
+        let (first, last) = if scheme == primary {
+            (chapter, chapter)
+        } else {
+            (chapter - 1, chapter + 1)
+        };
+        for verse in verses(conn, &content.schema, book, first, last)? {
+            let (c, v) = versification::map(scheme, primary, book, verse.chapter, verse.verse);
+            if c != chapter {
+                continue;
+            }
+            rows.entry(v)
+                .or_insert_with(|| vec![Vec::new(); translations.len()])[column]
+                .push(verse);
+        }
+    }
+    Ok(ParallelChapter {
+        book: book.to_string(),
+        chapter,
+        translations: translations.iter().map(|t| t.module_id.clone()).collect(),
+        rows: rows
+            .into_iter()
+            .map(|(verse, cells)| ParallelRow { verse, cells })
+            .collect(),
+    })
+}
+
+/// One chapter in up to four mounted translations, verse-aligned on the
+/// first, for the parallel view.
+#[command]
+pub async fn get_parallel_chapter(
+    app: tauri::AppHandle,
+    book: String,
+    chapter: i64,
+    translation_ids: Vec<String>,
+) -> Result<ParallelChapter, DbError> {
+    if translation_ids.is_empty() || translation_ids.len() > MAX_TRANSLATIONS {
+        return Err(DbError::invalid(format!(
+            "Choose between 1 and {MAX_TRANSLATIONS} translations"
+        )));
+    }
+    let translations = translation_ids
+        .iter()
+        .map(|id| {
+            mounted(id).ok_or_else(|| {
+                DbError::new(
+                    DbErrorKind::NotFound,
+                    format!("Translation `{id}` is not mounted"),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    db::with_reader(&app, move |conn| {
+        parallel_chapter(conn, &book, chapter, &translations)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_hebrew_numbering_on_the_first_translation() {
+        let conn = Connection::open_in_memory().unwrap();
+        for (schema, versification, verses) in [
+            (
+                "content_kjv",
+                "KJV",
+                &[
+                    (4, 5, "Behold, I will send you Elijah"),
+                    (4, 6, "And he shall turn"),
+                ][..],
+            ),
+            (
+                "content_wlc",
+                "Leningrad",
+                &[(3, 23, "הִנֵּה אָנֹכִי שֹׁלֵחַ"), (3, 24, "וְהֵשִׁיב")][..],
+            ),
+        ] {
+            conn.execute(&format!("ATTACH ':memory:' AS {schema}"), [])
+                .unwrap();
+            conn.execute_batch(&format!(
+                "CREATE TABLE {schema}.content_info (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+                 CREATE TABLE {schema}.verses (book TEXT, chapter INTEGER, verse INTEGER, text TEXT);
+                 INSERT INTO {schema}.content_info VALUES ('versification', '{versification}');"
+            ))
+            .unwrap();
+            for (chapter, verse, text) in verses {
+                conn.execute(
+                    &format!("INSERT INTO {schema}.verses VALUES ('Mal', ?1, ?2, ?3)"),
+                    params![chapter, verse, text],
+                )
+                .unwrap();
+            }
+        }
+        let content = |id: &str| MountedContent {
+            module_id: id.to_string(),
+            schema: format!("content_{id}"),
+            path: String::new(),
+            verses: 2,
+        };
+
+        let chapter = parallel_chapter(&conn, "Mal", 4, &[content("kjv"), content("wlc")]).unwrap();
+        assert_eq!(chapter.translations, ["kjv", "wlc"]);
+        let row = &chapter.rows[0];
+        assert_eq!(row.verse, 5);
+        assert_eq!(row.cells[0][0].text, "Behold, I will send you Elijah");
+        assert_eq!((row.cells[1][0].chapter, row.cells[1][0].verse), (3, 23));
+
+        let hebrew = parallel_chapter(&conn, "Mal", 3, &[content("wlc"), content("kjv")]).unwrap();
+        let verses: Vec<i64> = hebrew.rows.iter().map(|r| r.verse).collect();
+        assert_eq!(verses, [23, 24]);
+        assert_eq!(hebrew.rows[1].cells[1][0].text, "And he shall turn");
+    }
+}
@@ -0,0 +1,786 @@
+//! Pericopes: passages by name ("The Parable of the Prodigal Son", Luke
+//! 15:11–32), so the reader can navigate and mark by passage unit.
+//!
+//! A pericope set is a read-only SQLite file under
+//! `<app data>/content/pericopes`, like a lexicon. Sets import from a
+//! tab-separated list (one `reference<TAB>title` a line, either way round)
+//! or JSON (`[{"title": ..., "reference": ...}]`, optionally as
+//! `{"name": ..., "pericopes": [...]}`), or are built from a mounted
+//! translation's section headings: each `s`/`s1` heading begins a pericope
+//! that runs to the verse before the next one. Ranges are stored verse to
+//! verse under the KJV numbering, which the reader navigates by.
+
+use rusqlite::{params, Connection, OpenFlags};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{command, Manager};
+
+use super::books;
+use super::references::{self, ScriptureReference};
+use super::{content_path, import, mounted, slug, CONTENT_DIR};
+use crate::db::{self, DbError, DbErrorKind};
+
+/// Directory (in the content directory) holding one `<id>.db` per set.
+const PERICOPE_DIR: &str = "pericopes";
+
+/// Layout version of pericope files (`pericope_info.format`); newer ones are
+/// skipped.
+const PERICOPE_FORMAT: u32 = 1;
+
+/// `position` is the book's place in the canon, for ordering.
+const PERICOPE_SCHEMA: &str = "
+    CREATE TABLE pericope_info (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE pericopes (
+        id INTEGER PRIMARY KEY,
+        book TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        chapter INTEGER NOT NULL,
+        verse INTEGER NOT NULL,
+        end_chapter INTEGER NOT NULL,
+        end_verse INTEGER NOT NULL,
+        title TEXT NOT NULL
+    );
+    CREATE INDEX pericopes_by_location ON pericopes (book, chapter, end_chapter);";
+
+/// Section heading styles that begin a pericope.
+const SECTION_STYLES: &[&str] = &["s", "s1"];
+
+/// Most titles `search_pericopes` returns.
+const SEARCH_LIMIT: usize = 50;
+
+/// A pericope's title and passage, as read from a source.
+type Titled = (String, ScriptureReference);
+
+/// A section heading: book, chapter, the verse it comes before, and text.
+type Heading = (String, i64, i64, String);
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Pericope {
+    /// Its number in the set, in canonical order from 1.
+    pub id: i64,
+    pub title: String,
+    /// The passage, verse to verse.
+    pub reference: ScriptureReference,
+}
+
+/// An installed pericope set.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PericopeSet {
+    pub id: String,
+    pub name: String,
+    pub pericopes: i64,
+    /// `tsv`, `json`, or `headings` for one built from a translation.
+    #[serde(rename = "sourceFormat")]
+    pub source_format: String,
+    pub path: String,
+}
+
+fn pericope_dir(app: &tauri::AppHandle) -> Result<PathBuf, DbError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| DbError::io(format!("Cannot determine app data dir: {e}")))?;
+    Ok(dir.join(CONTENT_DIR).join(PERICOPE_DIR))
+}
+
+/// Verses in `chapter` of book `index` under the KJV numbering.
+fn last_verse(index: usize, chapter: i64) -> Option<i64> {
+    let chapters = books::KJV_VERSES[index];
+    let count = chapters.get(usize::try_from(chapter).ok()?.checked_sub(1)?)?;
+    Some(i64::from(*count))
+}
+
+/// The passage `text` names, verse to verse: from the start of its first
+/// reference to the end of its last, which must be in the same book.
+fn passage(text: &str) -> Option<ScriptureReference> {
+    let found = references::parse(text);
+    let (first, last) = (found.first()?, found.last()?);
+    if first.book != last.book {
+        return None;
+    }
+    let index = books::position(&first.book)? - 1;
+    let end_verse = match last.end_verse {
+        Some(verse) => verse,
+        None => last_verse(index, last.end_chapter)?,
+    };
+    references::span(
+        &books::BOOKS[index],
+        (first.chapter, Some(first.verse.unwrap_or(1))),
+        (last.end_chapter, Some(end_verse)),
+    )
+}
+
+/// The pericopes of a tab-separated list, with the lines that didn't read.
+fn parse_tsv(src: &str) -> (Vec<Titled>, usize) {
+    let mut pericopes = Vec::new();
+    let mut skipped = 0;
+    for line in src.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let found = line.split_once('\t').and_then(|(a, b)| {
+            let (a, b) = (a.trim(), b.trim());
+            match passage(a) {
+                Some(reference) => Some((b, reference)),
+                None => passage(b).map(|reference| (a, reference)),
+            }
+        });
+        match found {
+            Some((title, reference)) if !title.is_empty() => {
+                pericopes.push((title.to_string(), reference))
+            }
+            _ => skipped += 1,
+        }
+    }
+    (pericopes, skipped)
+}
+
+/// The name and pericopes of a JSON set, with the entries that didn't read.
+fn parse_json(src: &str) -> Result<(Option<String>, Vec<Titled>, usize), DbError> {
+    let value: serde_json::Value = serde_json::from_str(src)
+        .map_err(|e| DbError::invalid(format!("Not a pericope list: {e}")))?;
+    let name = value
+        .get("name")
+        .and_then(|n| n.as_str())
+        .map(str::to_string);
+    let entries = value
+        .as_array()
+        .or_else(|| value.get("pericopes").and_then(|p| p.as_array()))
+        .ok_or_else(|| DbError::invalid("Not a pericope list: no array of pericopes"))?;
+    let field = |entry: &serde_json::Value, names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| entry.get(name).and_then(|v| v.as_str()))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let mut pericopes = Vec::new();
+    let mut skipped = 0;
+    for entry in entries {
+        let title = field(entry, &["title", "heading", "name"]);
+        let reference =
+            field(entry, &["reference", "ref", "passage", "osis"]).and_then(|text| passage(&text));
+        match (title, reference) {
+            (Some(title), Some(reference)) => pericopes.push((title, reference)),
+            _ => skipped += 1,
+        }
+    }
+    Ok((name, pericopes, skipped))
+}
+
+/// Pericopes from section headings (`book`, `chapter`, `verse` it comes
+/// before, `title`): each runs to the verse before the book's next heading,
+/// or to the end of the book. Of several headings before one verse the
+/// first is kept.
+fn from_headings(headings: Vec<Heading>) -> Vec<Titled> {
+    let mut starts: Vec<(usize, i64, i64, String)> = headings
+        .into_iter()
+        .filter_map(|(book, chapter, verse, title)| {
+            let index = books::position(&book)? - 1;
+            last_verse(index, chapter)?;
+            Some((index, chapter, verse.max(1), title.trim().to_string()))
+        })
+        .filter(|(.., title)| !title.is_empty())
+        .collect();
+    starts.sort_by_key(|&(index, chapter, verse, _)| (index, chapter, verse));
+    starts.dedup_by_key(|&mut (index, chapter, verse, _)| (index, chapter, verse));
+
+    let mut pericopes = Vec::with_capacity(starts.len());
+    for (i, (index, chapter, verse, title)) in starts.iter().enumerate() {
+        let end = match starts.get(i + 1) {
+            Some(&(next, next_chapter, 1, _)) if next == *index => {
+                last_verse(*index, next_chapter - 1).map(|v| (next_chapter - 1, v))
+            }
+            Some(&(next, next_chapter, next_verse, _)) if next == *index => {
+                Some((next_chapter, next_verse - 1))
+            }
+            _ => {
+                let chapters = books::KJV_VERSES[*index].len() as i64;
+                last_verse(*index, chapters).map(|v| (chapters, v))
+            }
+        };
+        let reference = end.and_then(|(end_chapter, end_verse)| {
+            references::span(
+                &books::BOOKS[*index],
+                (*chapter, Some(*verse)),
+                (end_chapter, Some(end_verse)),
+            )
+        });
+        if let Some(reference) = reference {
+            pericopes.push((title.clone(), reference));
+        }
+    }
+    pericopes
+}
+
+/// The section headings of the content file at `path`.
+fn read_headings(path: &Path) -> Result<Vec<Heading>, DbError> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    if !super::has_table(&conn, "main", "headings")? {
+        return Ok(Vec::new());
+    }
+    let styles = SECTION_STYLES
+        .iter()
+        .map(|s| format!("'{s}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT book, chapter, verse, text FROM headings
+         WHERE style IN ({styles}) ORDER BY book, chapter, verse, seq"
+    ))?;
+    let headings = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(headings)
+}
+
+/// Write `pericopes` as set `id` at `path`, in canonical order, replacing
+/// any file already there once the new one is complete.
+fn write(
+    path: &Path,
+    id: &str,
+    name: &str,
+    source_format: &str,
+    mut pericopes: Vec<Titled>,
+) -> Result<PericopeSet, DbError> {
+    if pericopes.is_empty() {
+        return Err(DbError::invalid("No pericopes found"));
+    }
+    let key = |r: &ScriptureReference| {
+        let position = books::position(&r.book).unwrap_or_default();
+        (position, r.chapter, r.verse, r.end_chapter, r.end_verse)
+    };
+    pericopes.sort_by_key(|(_, r)| key(r));
+
+    let partial = PathBuf::from(format!("{}.partial", path.display()));
+    let written = (|| {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| DbError::io(format!("Failed to create {}: {e}", dir.display())))?;
+        }
+        let _ = std::fs::remove_file(&partial);
+        let mut conn = Connection::open(&partial)?;
+        conn.execute_batch(PERICOPE_SCHEMA)?;
+        let tx = conn.transaction()?;
+        {
+            let mut info = tx.prepare("INSERT INTO pericope_info (key, value) VALUES (?1, ?2)")?;
+            let format = PERICOPE_FORMAT.to_string();
+            for (key, value) in [
+                ("format", format.as_str()),
+                ("set_id", id),
+                ("name", name),
+                ("source_format", source_format),
+            ] {
+                info.execute(params![key, value])?;
+            }
+            let mut row = tx.prepare(
+                "INSERT INTO pericopes
+                 (id, book, position, chapter, verse, end_chapter, end_verse, title)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for (n, (title, r)) in (1_i64..).zip(&pericopes) {
+                row.execute(params![
+                    n,
+                    r.book,
+                    books::position(&r.book).unwrap_or_default() as i64,
+                    r.chapter,
+                    r.verse,
+                    r.end_chapter,
+                    r.end_verse,
+                    title
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok::<_, DbError>(())
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, path)
+        .map_err(|e| DbError::io(format!("Failed to install {}: {e}", path.display())))?;
+    println!(
+        "[pericopes] Installed {id} with {} pericopes",
+        pericopes.len()
+    );
+    Ok(PericopeSet {
+        id: id.to_string(),
+        name: name.to_string(),
+        pericopes: pericopes.len() as i64,
+        source_format: source_format.to_string(),
+        path: path.display().to_string(),
+    })
+}
+
+/// What the pericope file at `path` holds, if it is one this app can read.
+fn installed(conn: &Connection, path: &Path) -> Result<PericopeSet, DbError> {
+    let not_set = || DbError::invalid(format!("{} is not a pericope set", path.display()));
+    let info: BTreeMap<String, String> = conn
+        .prepare("SELECT key, value FROM pericope_info")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<_>>()
+        })
+        .map_err(|_| not_set())?;
+    let format: u32 = info
+        .get("format")
+        .and_then(|f| f.parse().ok())
+        .ok_or_else(not_set)?;
+    if format > PERICOPE_FORMAT {
+        return Err(DbError::new(
+            DbErrorKind::SchemaTooNew,
+            format!("{} needs a newer version of BibleMarker", path.display()),
+        ));
+    }
+    let id = info.get("set_id").cloned().ok_or_else(not_set)?;
+    Ok(PericopeSet {
+        name: info.get("name").cloned().unwrap_or_else(|| id.clone()),
+        pericopes: conn.query_row("SELECT COUNT(*) FROM pericopes", [], |row| row.get(0))?,
+        source_format: info.get("source_format").cloned().unwrap_or_default(),
+        path: path.display().to_string(),
+        id,
+    })
+}
+
+/// The pericope sets in `dir`, by name, each with its open connection.
+fn open_all(dir: &Path) -> Vec<(PericopeSet, Connection)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sets: Vec<(PericopeSet, Connection)> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|path| {
+            let found = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(DbError::from)
+                .and_then(|conn| Ok((installed(&conn, &path)?, conn)));
+            match found {
+                Ok(found) => Some(found),
+                Err(e) => {
+                    println!("[pericopes] Skipping {}: {e}", path.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    sets.sort_by_key(|(s, _)| s.name.to_lowercase());
+    sets
+}
+
+/// Set `id` in `dir`, or the first by name when `None`; `Ok(None)` only when
+/// no set is installed.
+fn choose(dir: &Path, id: Option<&str>) -> Result<Option<Connection>, DbError> {
+    let mut sets = open_all(dir).into_iter();
+    match id {
+        None => Ok(sets.next().map(|(_, conn)| conn)),
+        Some(id) => sets
+            .find(|(s, _)| s.id == id)
+            .map(|(_, conn)| Some(conn))
+            .ok_or_else(|| {
+                DbError::new(
+                    DbErrorKind::NotFound,
+                    format!("No pericope set `{id}` is installed"),
+                )
+            }),
+    }
+}
+
+/// The pericopes matching `filter` (over `pericopes` columns), in order.
+fn select(
+    conn: &Connection,
+    filter: &str,
+    args: impl rusqlite::Params,
+) -> Result<Vec<Pericope>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, title, book, chapter, verse, end_chapter, end_verse
+         FROM pericopes WHERE {filter} ORDER BY id"
+    ))?;
+    let rows = stmt.query_map(args, |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            (row.get::<_, i64>(3)?, row.get::<_, i64>(4)?),
+            (row.get::<_, i64>(5)?, row.get::<_, i64>(6)?),
+        ))
+    })?;
+    let mut pericopes = Vec::new();
+    for row in rows {
+        let (id, title, book, (chapter, verse), (end_chapter, end_verse)) = row?;
+        let reference = books::position(&book).and_then(|position| {
+            references::span(
+                &books::BOOKS[position - 1],
+                (chapter, Some(verse)),
+                (end_chapter, Some(end_verse)),
+            )
+        });
+        if let Some(reference) = reference {
+            pericopes.push(Pericope {
+                id,
+                title,
+                reference,
+            });
+        }
+    }
+    Ok(pericopes)
+}
+
+/// Pericopes overlapping `book` `chapter`.
+fn in_chapter(conn: &Connection, book: &str, chapter: i64) -> Result<Vec<Pericope>, DbError> {
+    select(
+        conn,
+        "book = ?1 AND chapter <= ?2 AND end_chapter >= ?2",
+        params![book, chapter],
+    )
+}
+
+/// The pericope `book` `chapter`:`verse` is in; of nested ones, the one
+/// starting last.
+fn at(
+    conn: &Connection,
+    book: &str,
+    chapter: i64,
+    verse: i64,
+) -> Result<Option<Pericope>, DbError> {
+    Ok(select(
+        conn,
+        "book = ?1 AND (chapter, verse) <= (?2, ?3) AND (end_chapter, end_verse) >= (?2, ?3)",
+        params![book, chapter, verse],
+    )?
+    .pop())
+}
+
+/// Pericopes whose title has every word of `query`, in canonical order.
+fn search(conn: &Connection, query: &str) -> Result<Vec<Pericope>, DbError> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|w| format!("%{}%", w.replace(['%', '_'], "")))
+        .collect();
+    if words.is_empty() {
+        return Ok(Vec::new());
+    }
+    let filter = (1..=words.len())
+        .map(|n| format!("title LIKE ?{n}"))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let mut found = select(conn, &filter, rusqlite::params_from_iter(words.iter()))?;
+    found.truncate(SEARCH_LIMIT);
+    Ok(found)
+}
+
+fn by_id(conn: &Connection, id: i64) -> Result<Pericope, DbError> {
+    select(conn, "id = ?1", [id])?.pop().ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("No pericope {id} in the set"),
+        )
+    })
+}
+
+/// Whether annotation `data` begins within `pericope`: symbols by `ref`,
+/// text marks by `startRef`.
+fn begins_in(data: &serde_json::Value, pericope: &Pericope) -> bool {
+    let key = if data["type"] == "symbol" {
+        "ref"
+    } else {
+        "startRef"
+    };
+    let location = &data[key];
+    let (Some(chapter), Some(verse)) = (location["chapter"].as_i64(), location["verse"].as_i64())
+    else {
+        return false;
+    };
+    let r = &pericope.reference;
+    let start = (r.chapter, r.verse.unwrap_or(1));
+    let end = (r.end_chapter, r.end_verse.unwrap_or(i64::MAX));
+    (start..=end).contains(&(chapter, verse))
+}
+
+fn install(
+    app: &tauri::AppHandle,
+    id: &str,
+    name: &str,
+    source_format: &str,
+    pericopes: Vec<Titled>,
+) -> Result<PericopeSet, DbError> {
+    let path = pericope_dir(app)?.join(format!("{}.db", slug(id)?));
+    write(&path, id, name, source_format, pericopes)
+}
+
+#[command]
+pub fn list_pericope_sets(app: tauri::AppHandle) -> Result<Vec<PericopeSet>, DbError> {
+    let dir = pericope_dir(&app)?;
+    Ok(open_all(&dir).into_iter().map(|(s, _)| s).collect())
+}
+
+/// Import a tab-separated (`.tsv`, `.txt`) or JSON (`.json`) pericope list
+/// as set `set_id` (by default the file name), replacing any set of that id.
+#[command]
+pub async fn import_pericopes(
+    app: tauri::AppHandle,
+    path: String,
+    set_id: Option<String>,
+    name: Option<String>,
+) -> Result<PericopeSet, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = PathBuf::from(path);
+        let file = import::file_label(&path);
+        let stem = file.split('.').next().unwrap_or(&file).to_string();
+        let ext = file
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let src = std::fs::read_to_string(&path)
+            .map_err(|e| DbError::io(format!("Failed to read {file}: {e}")))?;
+        let (title, pericopes, skipped, source_format) = match ext.as_str() {
+            "json" => {
+                let (title, pericopes, skipped) = parse_json(&src)?;
+                (title, pericopes, skipped, "json")
+            }
+            "tsv" | "txt" => {
+                let (pericopes, skipped) = parse_tsv(&src);
+                (None, pericopes, skipped, "tsv")
+            }
+            _ => {
+                return Err(DbError::invalid(format!(
+                    "{file} is not a pericope list BibleMarker reads (.tsv, .txt, .json)"
+                )))
+            }
+        };
+        if skipped > 0 {
+            println!("[pericopes] {file}: skipped {skipped} entries without a title and passage");
+        }
+        let id = set_id.unwrap_or(stem);
+        let name = name.or(title).unwrap_or_else(|| id.clone());
+        install(&app, &id, &name, source_format, pericopes)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+/// Build set `<module>-headings` from the section headings of translation
+/// `module_id` (mounted, or installed in the content directory).
+#[command]
+pub async fn import_pericopes_from_translation(
+    app: tauri::AppHandle,
+    module_id: String,
+) -> Result<PericopeSet, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = match mounted(&module_id) {
+            Some(m) => PathBuf::from(m.path),
+            None => content_path(&app, &module_id)?,
+        };
+        if !path.exists() {
+            return Err(DbError::new(
+                DbErrorKind::NotFound,
+                format!("Translation `{module_id}` is not installed"),
+            ));
+        }
+        let pericopes = from_headings(read_headings(&path)?);
+        if pericopes.is_empty() {
+            return Err(DbError::invalid(format!(
+                "`{module_id}` has no section headings"
+            )));
+        }
+        let id = format!("{module_id}-headings");
+        let name = format!("{module_id} headings");
+        install(&app, &id, &name, "headings", pericopes)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+/// Delete pericope set `id`; false if it wasn't installed.
+#[command]
+pub fn remove_pericope_set(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    let path = pericope_dir(&app)?.join(format!("{}.db", slug(&id)?));
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path)
+        .map_err(|e| DbError::io(format!("Failed to remove {}: {e}", path.display())))?;
+    Ok(true)
+}
+
+/// Pericopes overlapping `book` `chapter`, in order, from set `set_id` (by
+/// default the first installed); none when no set is installed.
+#[command]
+pub fn get_pericopes(
+    app: tauri::AppHandle,
+    book: String,
+    chapter: i64,
+    set_id: Option<String>,
+) -> Result<Vec<Pericope>, DbError> {
+    match choose(&pericope_dir(&app)?, set_id.as_deref())? {
+        Some(conn) => in_chapter(&conn, &book, chapter),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// The pericope `book` `chapter`:`verse` belongs to, if any.
+#[command]
+pub fn get_pericope_at(
+    app: tauri::AppHandle,
+    book: String,
+    chapter: i64,
+    verse: i64,
+    set_id: Option<String>,
+) -> Result<Option<Pericope>, DbError> {
+    match choose(&pericope_dir(&app)?, set_id.as_deref())? {
+        Some(conn) => at(&conn, &book, chapter, verse),
+        None => Ok(None),
+    }
+}
+
+/// Pericopes titled with every word of `query` ("prodigal son"), at most
+/// 50, in canonical order.
+#[command]
+pub fn search_pericopes(
+    app: tauri::AppHandle,
+    query: String,
+    set_id: Option<String>,
+) -> Result<Vec<Pericope>, DbError> {
+    match choose(&pericope_dir(&app)?, set_id.as_deref())? {
+        Some(conn) => search(&conn, &query),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Annotations of `module_id` beginning within pericope `pericope_id`, as
+/// the JSON the TS layer stored.
+#[command]
+pub async fn get_pericope_annotations(
+    app: tauri::AppHandle,
+    module_id: String,
+    pericope_id: i64,
+    set_id: Option<String>,
+) -> Result<Vec<serde_json::Value>, DbError> {
+    let conn = choose(&pericope_dir(&app)?, set_id.as_deref())?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, "No pericope sets are installed"))?;
+    let pericope = by_id(&conn, pericope_id)?;
+    drop(conn);
+    db::with_reader(&app, move |conn| {
+        let r = &pericope.reference;
+        let mut found = Vec::new();
+        for chapter in r.chapter..=r.end_chapter {
+            found.extend(
+                db::annotations::chapter_annotations(conn, &module_id, &r.book, chapter)?
+                    .into_iter()
+                    // A text mark is in every chapter it spans; take it once.
+                    .filter(|data| {
+                        begins_in(data, &pericope)
+                            && data["startRef"]["chapter"]
+                                .as_i64()
+                                .is_none_or(|start| start == chapter)
+                    }),
+            );
+        }
+        Ok(found)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    const TSV: &str = "# Luke\n\
+        Luke 15:1-10\tThe Lost Sheep and the Lost Coin\n\
+        The Parable of the Prodigal Son\tLk 15:11-32\n\
+        Luke 16\tThe Shrewd Manager\n\
+        no passage here\n";
+
+    fn titles(pericopes: &[Pericope]) -> Vec<&str> {
+        pericopes.iter().map(|p| p.title.as_str()).collect()
+    }
+
+    #[test]
+    fn reads_lists_json_and_headings() {
+        let (pericopes, skipped) = parse_tsv(TSV);
+        assert_eq!((pericopes.len(), skipped), (3, 1));
+        assert_eq!(pericopes[1].0, "The Parable of the Prodigal Son");
+        assert_eq!(pericopes[1].1.osis, "Luke.15.11-Luke.15.32");
+        assert_eq!(pericopes[2].1.osis, "Luke.16.1-Luke.16.31");
+
+        let (name, pericopes, skipped) = parse_json(
+            r#"{"name": "Gospels", "pericopes": [
+                {"title": "The Sermon on the Mount", "reference": "Matt 5-7"},
+                {"title": "Missing"}, {"heading": "Jesus Walks on Water", "ref": "John 6:16-21"}]}"#,
+        )
+        .unwrap();
+        assert_eq!((name.as_deref(), skipped), (Some("Gospels"), 1));
+        assert_eq!(pericopes[0].1.osis, "Matt.5.1-Matt.7.29");
+        assert!(parse_json("[1, 2").is_err());
+
+        let heading =
+            |chapter, verse, title: &str| ("Ruth".to_string(), chapter, verse, title.to_string());
+        let pericopes = from_headings(vec![
+            heading(2, 1, "Ruth Meets Boaz"),
+            heading(1, 1, "Naomi Widowed"),
+            heading(1, 6, "Ruth's Loyalty"),
+            heading(1, 6, "Duplicate"),
+        ]);
+        let osis: Vec<&str> = pericopes.iter().map(|(_, r)| r.osis.as_str()).collect();
+        assert_eq!(
+            osis,
+            [
+                "Ruth.1.1-Ruth.1.5",
+                "Ruth.1.6-Ruth.1.22",
+                "Ruth.2.1-Ruth.4.22"
+            ]
+        );
+    }
+
+    #[test]
+    fn looks_pericopes_up_by_location_and_title() {
+        let dir = ScratchDir::new("pericopes");
+        let set = write(
+            &dir.join("luke.db"),
+            "luke",
+            "Luke",
+            "tsv",
+            parse_tsv(TSV).0,
+        )
+        .unwrap();
+        assert_eq!(set.pericopes, 3);
+        std::fs::write(dir.join("broken.db"), b"not a database").unwrap();
+
+        let conn = choose(&dir, None).unwrap().unwrap();
+        assert_eq!(
+            titles(&in_chapter(&conn, "Luke", 15).unwrap()),
+            [
+                "The Lost Sheep and the Lost Coin",
+                "The Parable of the Prodigal Son"
+            ]
+        );
+        let prodigal = at(&conn, "Luke", 15, 20).unwrap().unwrap();
+        assert_eq!((prodigal.id, prodigal.reference.verse), (2, Some(11)));
+        assert_eq!(at(&conn, "Luke", 14, 1).unwrap(), None);
+        assert_eq!(
+            titles(&search(&conn, "prodigal SON").unwrap()),
+            ["The Parable of the Prodigal Son"]
+        );
+        assert!(search(&conn, "  ").unwrap().is_empty());
+        assert_eq!(by_id(&conn, 9).unwrap_err().kind, DbErrorKind::NotFound);
+        assert_eq!(
+            choose(&dir, Some("esv")).unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+
+        let mark = |kind: &str, key: &str, chapter: i64, verse: i64| serde_json::json!({"type": kind, key: {"book": "Luke", "chapter": chapter, "verse": verse}});
+        assert!(begins_in(&mark("highlight", "startRef", 15, 32), &prodigal));
+        assert!(begins_in(&mark("symbol", "ref", 15, 11), &prodigal));
+        assert!(!begins_in(&mark("symbol", "startRef", 15, 11), &prodigal));
+        assert!(!begins_in(
+            &mark("underline", "startRef", 15, 10),
+            &prodigal
+        ));
+    }
+}
@@ -0,0 +1,633 @@
+//! Plain text and CSV import, for translations no other format covers.
+//!
+//! A file is either one verse per line led by its reference (`Gen 1:1 In
+//! the beginning`, `1 John 3:16<TAB>For God`, `Gen.1.1 ...`), or delimited
+//! columns (comma with quoted fields, tab, `|` or `;`) holding the book,
+//! chapter, verse and text, or a reference and text. Books are named in
+//! English, by OSIS or USFM id, or numbered 1 to 66. `preview_text_import`
+//! guesses the layout, reads the file with it and reports the verses
+//! missing against the KJV numbering, so the mapping can be corrected
+//! before `import_text` writes anything.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::command;
+
+use super::books::{self, BookId};
+use super::import::{self, Book, ImportReport, ImportWarning, Verse, Warnings};
+use crate::db::{DbError, DbErrorKind};
+
+/// Lines looked at to guess a file's layout.
+const SAMPLE_LINES: usize = 20;
+/// Verses shown in a preview.
+const SAMPLE_VERSES: usize = 5;
+/// Gaps listed per book; the rest are only counted.
+const MAX_GAPS: usize = 50;
+
+/// Where a file keeps each verse's reference and text.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TextLayout {
+    /// Column separator; `None` for lines led by a reference, where the
+    /// column fields are ignored.
+    pub delimiter: Option<char>,
+    /// The first line names the columns.
+    #[serde(rename = "hasHeader", default)]
+    pub has_header: bool,
+    /// Column (from 0) of a whole reference (`Gen 1:1`); otherwise `book`,
+    /// `chapter` and `verse` give one each.
+    pub reference: Option<usize>,
+    pub book: Option<usize>,
+    pub chapter: Option<usize>,
+    pub verse: Option<usize>,
+    /// Continues to the end of the line when it's the last column, so
+    /// unquoted commas in the text survive.
+    pub text: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PreviewVerse {
+    pub book: String,
+    pub chapter: i64,
+    pub verse: i64,
+    pub text: String,
+}
+
+/// How much of a book the file has, against the KJV numbering.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BookCoverage {
+    pub book: String,
+    pub verses: usize,
+    pub expected: usize,
+    /// The first gaps, as `3:5`, `3:5-9` or `4` (a whole chapter).
+    pub missing: Vec<String>,
+    #[serde(rename = "missingVerses")]
+    pub missing_verses: usize,
+    /// Verses the KJV numbering doesn't have (`3:27`), for a translation
+    /// numbered differently.
+    pub extra: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TextImportPreview {
+    /// The layout read with: the one given, or the guess.
+    pub layout: TextLayout,
+    /// The header's column names, or the first line's fields.
+    pub columns: Vec<String>,
+    pub sample: Vec<PreviewVerse>,
+    pub verses: usize,
+    pub books: Vec<BookCoverage>,
+    /// OSIS ids of the books the file has nothing of.
+    #[serde(rename = "missingBooks")]
+    pub missing_books: Vec<String>,
+    pub warnings: Vec<ImportWarning>,
+    #[serde(rename = "moreWarnings")]
+    pub more_warnings: usize,
+}
+
+/// The fields of `line`, split at `delimiter`. Fields may be quoted
+/// (`"a, b"`), with `""` for a quote inside one.
+fn split_record(line: &str, delimiter: char) -> Vec<String> {
+    split_with_starts(line, delimiter).0
+}
+
+/// `split_record`, with the byte offset each field starts at.
+fn split_with_starts(line: &str, delimiter: char) -> (Vec<String>, Vec<usize>) {
+    let mut fields = Vec::new();
+    let mut starts = vec![0];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.char_indices().peekable();
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek().map(|&(_, c)| c) == Some('"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            c if c == delimiter && !quoted => {
+                fields.push(std::mem::take(&mut field));
+                starts.push(at + c.len_utf8());
+            }
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    let fields = fields.into_iter().map(|f| f.trim().to_string()).collect();
+    (fields, starts)
+}
+
+/// The book `name` names, or numbers from 1 to 66.
+fn book_named(name: &str) -> Option<&'static BookId> {
+    let name = name.trim();
+    match name.parse::<usize>() {
+        Ok(n) => n.checked_sub(1).and_then(|i| books::BOOKS.get(i)),
+        Err(_) => books::by_name(name),
+    }
+}
+
+/// `3:16`, `3.16` or `3:16-18` as chapter, verse and last verse.
+fn chapter_verse(text: &str) -> Option<(i64, i64, i64)> {
+    let (chapter, verses) = text.split_once([':', '.'])?;
+    let (verse, through) = match verses.split_once('-') {
+        Some((verse, through)) => (verse, Some(through)),
+        None => (verses, None),
+    };
+    let chapter: i64 = chapter.parse().ok().filter(|&c| c > 0)?;
+    let verse: i64 = verse.parse().ok().filter(|&v| v > 0)?;
+    let through = match through {
+        Some(t) => t.parse().ok().filter(|&t| t >= verse)?,
+        None => verse,
+    };
+    Some((chapter, verse, through))
+}
+
+/// A verse's place: book name (as written), chapter, verse and last verse.
+type Reference<'a> = (&'a str, i64, i64, i64);
+
+/// The reference `text` begins with (`1 John 3:16`, `Gen.1.1`) and the
+/// text after it.
+fn leading_reference(text: &str) -> Option<(Reference<'_>, &str)> {
+    let text = text.trim_start();
+    let first_end = text.find(char::is_whitespace).unwrap_or(text.len());
+    // OSIS style: `Gen.1.1`.
+    let first = &text[..first_end];
+    if let Some((book, cv)) = first.split_once('.') {
+        if !book.is_empty() && !book.starts_with(|c: char| c.is_ascii_digit()) {
+            if let Some((c, v, t)) = chapter_verse(cv) {
+                return Some(((book, c, v, t), &text[first_end..]));
+            }
+        }
+    }
+    let mut start = 0;
+    while start < text.len() {
+        let rest = &text[start..];
+        let skip = rest.len() - rest.trim_start().len();
+        let token_start = start + skip;
+        if token_start >= text.len() {
+            break;
+        }
+        let token_end = text[token_start..]
+            .find(char::is_whitespace)
+            .map_or(text.len(), |i| token_start + i);
+        if token_start > 0 {
+            if let Some((c, v, t)) = chapter_verse(&text[token_start..token_end]) {
+                let book = text[..token_start].trim_end();
+                return Some(((book, c, v, t), &text[token_end..]));
+            }
+        }
+        start = token_end;
+        // A book name is at most three words (`Song of Solomon`).
+        if text[..start].split_whitespace().count() > 3 {
+            break;
+        }
+    }
+    None
+}
+
+/// The reference and text of one line read with `layout`; `None` if the
+/// line doesn't have them.
+fn read_line(line: &str, layout: &TextLayout) -> Option<((String, i64, i64, i64), String)> {
+    let Some(delimiter) = layout.delimiter else {
+        let ((book, chapter, verse, through), text) = leading_reference(line)?;
+        return Some((
+            (book.to_string(), chapter, verse, through),
+            text.trim().to_string(),
+        ));
+    };
+    let (fields, starts) = split_with_starts(line, delimiter);
+    let field = |i: Option<usize>| i.and_then(|i| fields.get(i)).map(String::as_str);
+    let last = [layout.reference, layout.book, layout.chapter, layout.verse]
+        .iter()
+        .flatten()
+        .all(|&i| i < layout.text);
+    let text = match starts.get(layout.text) {
+        // Unquoted delimiters in the text split it into more fields.
+        Some(&start) if last && fields.len() > layout.text + 1 => line[start..].trim().to_string(),
+        _ => field(Some(layout.text))?.to_string(),
+    };
+    let reference = match layout.reference {
+        Some(_) => {
+            let ((book, chapter, verse, through), _) = leading_reference(field(layout.reference)?)?;
+            (book.to_string(), chapter, verse, through)
+        }
+        None => {
+            let chapter: i64 = field(layout.chapter)?.parse().ok().filter(|&c| c > 0)?;
+            let (verse, through) = match field(layout.verse)?.split_once('-') {
+                Some((v, t)) => (v.trim().parse().ok()?, t.trim().parse().ok()?),
+                None => {
+                    let v: i64 = field(layout.verse)?.parse().ok()?;
+                    (v, v)
+                }
+            };
+            if verse < 1 || through < verse {
+                return None;
+            }
+            (field(layout.book)?.to_string(), chapter, verse, through)
+        }
+    };
+    Some((reference, text))
+}
+
+/// The books in `src`, read with `layout`.
+pub(crate) fn parse(
+    src: &str,
+    file: &str,
+    layout: &TextLayout,
+    warnings: &mut Warnings,
+) -> Vec<Book> {
+    let mut found: Vec<Book> = Vec::new();
+    let mut unknown = HashSet::new();
+    let mut unread = 0;
+    let lines = src.lines().enumerate().skip(usize::from(layout.has_header));
+    for (n, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(((name, chapter, verse, through), text)) = read_line(line, layout) else {
+            unread += 1;
+            if unread <= 10 {
+                warnings.push(file, Some(n + 1), "No reference found; line skipped");
+            }
+            continue;
+        };
+        let Some(id) = book_named(&name).map(|b| b.osis) else {
+            if unknown.insert(name.clone()) {
+                warnings.push(
+                    file,
+                    Some(n + 1),
+                    format!("Book `{name}` not recognized; its verses were skipped"),
+                );
+            }
+            continue;
+        };
+        let book = match found.iter().position(|b| b.id == id) {
+            Some(i) => &mut found[i],
+            None => {
+                found.push(Book {
+                    id: id.to_string(),
+                    file: file.to_string(),
+                    ..Default::default()
+                });
+                found.last_mut().expect("just pushed")
+            }
+        };
+        let mut verse = Verse {
+            chapter,
+            verse,
+            through,
+            line: Some(n + 1),
+            ..Default::default()
+        };
+        import::push_text(&mut verse.text, &text);
+        book.verses.push(verse);
+    }
+    if unread > 10 {
+        warnings.push(
+            file,
+            None,
+            format!("{unread} lines had no reference and were skipped"),
+        );
+    }
+    found
+}
+
+/// A layout for `src`, from its first lines: lines led by references if
+/// most are, else the delimiter most lines split at, with columns named by
+/// a header or told apart by what's in them.
+pub(crate) fn guess_layout(src: &str) -> TextLayout {
+    let sample: Vec<&str> = src
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(SAMPLE_LINES)
+        .collect();
+    let mostly = |ok: usize| !sample.is_empty() && ok * 5 >= sample.len() * 4;
+    let led = TextLayout {
+        delimiter: None,
+        has_header: false,
+        reference: None,
+        book: None,
+        chapter: None,
+        verse: None,
+        text: 0,
+    };
+    let referenced = sample
+        .iter()
+        .filter(|l| leading_reference(l).is_some_and(|((b, ..), _)| book_named(b).is_some()))
+        .count();
+    if mostly(referenced) {
+        return led;
+    }
+    let Some(delimiter) = ['\t', '|', ';', ','].into_iter().find(|&d| {
+        mostly(
+            sample
+                .iter()
+                .filter(|l| split_record(l, d).len() >= 2)
+                .count(),
+        )
+    }) else {
+        return led;
+    };
+    let rows: Vec<Vec<String>> = sample.iter().map(|l| split_record(l, delimiter)).collect();
+    let mut layout = TextLayout {
+        delimiter: Some(delimiter),
+        ..led
+    };
+    // Columns by content, from a line past any header.
+    let data = rows.get(1).or(rows.first()).cloned().unwrap_or_default();
+    let is_number = |f: &str| {
+        f.split('-')
+            .next()
+            .is_some_and(|n| n.parse::<i64>().is_ok())
+    };
+    if let Some(i) = data.iter().position(|f| {
+        leading_reference(f)
+            .is_some_and(|((b, ..), rest)| rest.trim().is_empty() && book_named(b).is_some())
+    }) {
+        layout.reference = Some(i);
+    } else if let Some(i) = data.iter().position(|f| book_named(f).is_some()) {
+        layout.book = Some(i);
+        let mut numbers = (i + 1..data.len()).filter(|&j| is_number(&data[j]));
+        layout.chapter = numbers.next();
+        layout.verse = numbers.next();
+    }
+    // The text follows the reference; failing that, it's the longest field.
+    let used = [layout.reference, layout.book, layout.chapter, layout.verse];
+    let after = used.iter().flatten().max().map_or(0, |&i| i + 1);
+    let unused = || (0..data.len()).filter(|i| !used.contains(&Some(*i)));
+    layout.text = unused()
+        .find(|&i| i >= after)
+        .or_else(|| unused().max_by_key(|&i| data[i].chars().count()))
+        .unwrap_or(data.len().saturating_sub(1));
+    // A header names its columns rather than holding a verse.
+    if let Some(first) = rows.first() {
+        layout.has_header =
+            read_line(sample[0], &layout).is_none_or(|((b, ..), _)| book_named(&b).is_none());
+        if layout.has_header {
+            for (i, name) in first.iter().enumerate() {
+                let name = name.to_lowercase();
+                let slot = match name.as_str() {
+                    "reference" | "ref" => &mut layout.reference,
+                    "book" | "bookname" | "book_name" | "book number" => &mut layout.book,
+                    "chapter" | "chap" | "c" => &mut layout.chapter,
+                    "verse" | "vers" | "v" => &mut layout.verse,
+                    "text" | "verse text" | "scripture" | "content" => {
+                        layout.text = i;
+                        continue;
+                    }
+                    _ => continue,
+                };
+                *slot = Some(i);
+            }
+        }
+    }
+    layout
+}
+
+/// The gaps in `have` (sorted verse numbers of chapter `chapter`) against
+/// verses 1 to `expected`, pushed onto `missing`; returns how many verses.
+fn gaps(chapter: usize, expected: usize, have: &[i64], missing: &mut Vec<String>) -> usize {
+    if have.is_empty() {
+        missing.push(chapter.to_string());
+        return expected;
+    }
+    let mut count = 0;
+    let mut verse = 1;
+    while verse <= expected as i64 {
+        if have.binary_search(&verse).is_ok() {
+            verse += 1;
+            continue;
+        }
+        let first = verse;
+        while verse <= expected as i64 && have.binary_search(&verse).is_err() {
+            verse += 1;
+        }
+        let last = verse - 1;
+        count += (last - first + 1) as usize;
+        missing.push(if first == last {
+            format!("{chapter}:{first}")
+        } else {
+            format!("{chapter}:{first}-{last}")
+        });
+    }
+    count
+}
+
+/// How much of each book in `found` there is, against the KJV numbering.
+fn coverage(found: &[Book]) -> Vec<BookCoverage> {
+    let mut coverage = Vec::new();
+    for book in found {
+        let Some(position) = books::position(&book.id) else {
+            continue;
+        };
+        let expected_chapters = books::KJV_VERSES[position - 1];
+        let mut missing = Vec::new();
+        let mut missing_verses = 0;
+        let mut extra = Vec::new();
+        let mut verses = 0;
+        for (i, &expected) in expected_chapters.iter().enumerate() {
+            let chapter = i as i64 + 1;
+            let mut have: Vec<i64> = book
+                .verses
+                .iter()
+                .filter(|v| v.chapter == chapter)
+                .flat_map(|v| v.verse..=v.through)
+                .collect();
+            have.sort_unstable();
+            have.dedup();
+            verses += have.len();
+            missing_verses += gaps(i + 1, expected as usize, &have, &mut missing);
+            extra.extend(
+                have.iter()
+                    .filter(|&&v| v > expected as i64)
+                    .map(|v| format!("{chapter}:{v}")),
+            );
+        }
+        for verse in &book.verses {
+            if verse.chapter > expected_chapters.len() as i64 {
+                verses += 1;
+                extra.push(format!("{}:{}", verse.chapter, verse.verse));
+            }
+        }
+        missing.truncate(MAX_GAPS);
+        extra.truncate(MAX_GAPS);
+        coverage.push(BookCoverage {
+            book: book.id.clone(),
+            verses,
+            expected: expected_chapters.iter().map(|&n| n as usize).sum(),
+            missing,
+            missing_verses,
+            extra,
+        });
+    }
+    coverage
+}
+
+/// Read `src` with `layout` (or a guess) and report what an import would
+/// hold, without writing anything.
+pub(crate) fn preview(src: &str, file: &str, layout: Option<TextLayout>) -> TextImportPreview {
+    let layout = layout.unwrap_or_else(|| guess_layout(src));
+    let mut warnings = Warnings::default();
+    let mut found = parse(src, file, &layout, &mut warnings);
+    found.sort_by_key(|book| books::position(&book.id));
+    for book in &mut found {
+        import::check(book, &mut warnings);
+    }
+    let first = src
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or_default();
+    let columns = match layout.delimiter {
+        Some(delimiter) => split_record(first, delimiter),
+        None => vec![first.trim().to_string()],
+    };
+    let sample = found
+        .iter()
+        .flat_map(|book| {
+            book.verses.iter().map(|v| PreviewVerse {
+                book: book.id.clone(),
+                chapter: v.chapter,
+                verse: v.verse,
+                text: v.text.clone(),
+            })
+        })
+        .take(SAMPLE_VERSES)
+        .collect();
+    let missing_books = books::BOOKS
+        .iter()
+        .filter(|b| !found.iter().any(|f| f.id == b.osis))
+        .map(|b| b.osis.to_string())
+        .collect();
+    TextImportPreview {
+        layout,
+        columns,
+        sample,
+        verses: found.iter().map(|b| b.verses.len()).sum(),
+        books: coverage(&found),
+        missing_books,
+        warnings: warnings.list,
+        more_warnings: warnings.dropped,
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, DbError> {
+    let mut warnings = Warnings::default();
+    import::read_text(path, &mut warnings).ok_or_else(|| {
+        let reason = warnings.list.pop().map(|w| w.message).unwrap_or_default();
+        DbError::io(format!("{}: {reason}", path.display()))
+    })
+}
+
+/// Guess how the text or CSV file at `path` is laid out (unless `layout`
+/// says), read it so, and report its coverage and problems, for checking
+/// the mapping before importing.
+#[command]
+pub async fn preview_text_import(
+    path: String,
+    layout: Option<TextLayout>,
+) -> Result<TextImportPreview, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&path);
+        let src = read_file(path)?;
+        Ok(preview(&src, &import::file_label(path), layout))
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Preview task failed: {e}")))?
+}
+
+/// Import the text or CSV file at `path`, read with `layout`, as
+/// translation `module_id`, and mount it.
+#[command]
+pub async fn import_text(
+    app: tauri::AppHandle,
+    module_id: String,
+    name: Option<String>,
+    path: String,
+    layout: TextLayout,
+) -> Result<ImportReport, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&path);
+        let name = name.unwrap_or_else(|| module_id.clone());
+        let file = import::file_label(path);
+        let mut warnings = Warnings::default();
+        let src = import::read_text(path, &mut warnings)
+            .ok_or_else(|| DbError::io(format!("Could not read {}", path.display())))?;
+        let books = parse(&src, &file, &layout, &mut warnings);
+        import::progress(&app, &module_id, &file, 1, 1);
+        import::install(&app, &module_id, &name, "text", &[], books, warnings)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_reference_led_lines_and_reports_gaps() {
+        let src = "Gen 1:1 In the beginning God created the heaven and the earth.\n\
+                   Gen 1:2\tAnd the earth was without form, and void.\n\
+                   Genesis 1:4 And God saw the light.\n\
+                   1 John 3:16 Hereby perceive we the love of God.\n\
+                   Jn 3:16 For God so loved the world.\n";
+        let layout = guess_layout(src);
+        assert_eq!(layout.delimiter, None);
+        let preview = preview(src, "mine.txt", None);
+        assert_eq!(preview.verses, 4);
+        assert_eq!(
+            preview.sample[1].text,
+            "And the earth was without form, and void."
+        );
+        let genesis = &preview.books[0];
+        assert_eq!(genesis.book, "Gen");
+        assert_eq!(&genesis.missing[..3], ["1:3", "1:5-31", "2"]);
+        assert_eq!(genesis.expected, 1533);
+        assert_eq!(genesis.missing_verses, 1530);
+        assert_eq!(preview.books[1].book, "1John");
+        assert!(preview.missing_books.contains(&"Exod".to_string()));
+        let messages: Vec<&str> = preview
+            .warnings
+            .iter()
+            .map(|w| w.message.as_str())
+            .collect();
+        assert!(messages.contains(&"Book `Jn` not recognized; its verses were skipped"));
+    }
+
+    #[test]
+    fn guesses_csv_columns_from_a_header() {
+        let src = "Book,Chapter,Verse,Text\n\
+                   1,1,1,\"In the beginning, God created\"\n\
+                   Gen,1,2,And the earth was without form, and void\n\
+                   43,3,16,For God so loved the world\n";
+        let layout = guess_layout(src);
+        assert_eq!(
+            layout,
+            TextLayout {
+                delimiter: Some(','),
+                has_header: true,
+                reference: None,
+                book: Some(0),
+                chapter: Some(1),
+                verse: Some(2),
+                text: 3,
+            }
+        );
+        let mut warnings = Warnings::default();
+        let books = parse(src, "bible.csv", &layout, &mut warnings);
+        assert!(warnings.list.is_empty(), "{:?}", warnings.list);
+        assert_eq!(books[0].verses[0].text, "In the beginning, God created");
+        assert_eq!(
+            books[0].verses[1].text,
+            "And the earth was without form, and void"
+        );
+        assert_eq!(
+            (books[1].id.as_str(), books[1].verses[0].verse),
+            ("John", 16)
+        );
+    }
+}
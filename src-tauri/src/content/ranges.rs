@@ -0,0 +1,245 @@
+//! Set operations on references: union, intersection, containment and
+//! splitting by chapter, for pericopes and reading plans.
+//!
+//! A reference is read as the verses it covers under the KJV numbering (a
+//! chapter is all its verses, a book all its chapters), and results are
+//! written back in the shortest form: verses 1 to the last make a chapter,
+//! every chapter a book.
+
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use super::books;
+use super::references::{self, ScriptureReference};
+use crate::db::DbError;
+
+/// A verse, as book index, chapter and verse.
+type Verse = (usize, i64, i64);
+
+/// The verses from `start` to `end`, which are in the same book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: Verse,
+    end: Verse,
+}
+
+/// Verses in `chapter` of book `index`, if it has that chapter.
+fn verses_in(index: usize, chapter: i64) -> Option<i64> {
+    let chapters = books::KJV_VERSES[index];
+    let count = chapters.get(usize::try_from(chapter).ok()?.checked_sub(1)?)?;
+    Some(i64::from(*count))
+}
+
+/// The verse after `verse`, across chapter breaks.
+fn following((index, chapter, verse): Verse) -> Option<Verse> {
+    if verse < verses_in(index, chapter)? {
+        Some((index, chapter, verse + 1))
+    } else {
+        verses_in(index, chapter + 1).map(|_| (index, chapter + 1, 1))
+    }
+}
+
+fn invalid(reference: &ScriptureReference) -> DbError {
+    DbError::invalid(format!(
+        "`{} {}` is not a reference this Bible has",
+        reference.book, reference.chapter
+    ))
+}
+
+fn to_span(reference: &ScriptureReference) -> Result<Span, DbError> {
+    let index = books::position(&reference.book).ok_or_else(|| invalid(reference))? - 1;
+    let last = verses_in(index, reference.end_chapter).ok_or_else(|| invalid(reference))?;
+    verses_in(index, reference.chapter).ok_or_else(|| invalid(reference))?;
+    let start = (index, reference.chapter, reference.verse.unwrap_or(1));
+    let end = (
+        index,
+        reference.end_chapter,
+        reference.end_verse.unwrap_or(last),
+    );
+    if end < start || end.2 > last {
+        return Err(invalid(reference));
+    }
+    Ok(Span { start, end })
+}
+
+fn to_reference(span: Span) -> ScriptureReference {
+    let (index, chapter, verse) = span.start;
+    let (_, end_chapter, end_verse) = span.end;
+    let book = &books::BOOKS[index];
+    let whole_chapters = verse == 1 && Some(end_verse) == verses_in(index, end_chapter);
+    let (verse, end_verse) = if whole_chapters {
+        (None, None)
+    } else {
+        (Some(verse), Some(end_verse))
+    };
+    references::span(book, (chapter, verse), (end_chapter, end_verse))
+        .expect("spans are built from checked references")
+}
+
+/// `spans` sorted, with overlapping and adjoining ones merged.
+fn merged(mut spans: Vec<Span>) -> Vec<Span> {
+    spans.sort_by_key(|s| (s.start, s.end));
+    let mut out: Vec<Span> = Vec::with_capacity(spans.len());
+    for s in spans {
+        match out.last_mut() {
+            Some(last) if s.start <= following(last.end).unwrap_or(last.end) => {
+                last.end = last.end.max(s.end);
+            }
+            _ => out.push(s),
+        }
+    }
+    out
+}
+
+fn spans(references: &[ScriptureReference]) -> Result<Vec<Span>, DbError> {
+    Ok(merged(
+        references.iter().map(to_span).collect::<Result<_, _>>()?,
+    ))
+}
+
+/// The verses of any of `references`, in canonical order.
+pub(crate) fn union(references: &[ScriptureReference]) -> Result<Vec<ScriptureReference>, DbError> {
+    Ok(spans(references)?.into_iter().map(to_reference).collect())
+}
+
+/// The verses both `a` and `b` cover.
+pub(crate) fn intersection(
+    a: &[ScriptureReference],
+    b: &[ScriptureReference],
+) -> Result<Vec<ScriptureReference>, DbError> {
+    let (a, b) = (spans(a)?, spans(b)?);
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        let start = a[i].start.max(b[j].start);
+        let end = a[i].end.min(b[j].end);
+        // Spans never cross books, so one that starts and ends in order
+        // lies within a single book.
+        if start <= end && start.0 == end.0 {
+            out.push(to_reference(Span { start, end }));
+        }
+        if a[i].end < b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Whether every verse of `reference` is in one of `references`.
+pub(crate) fn contains(
+    references: &[ScriptureReference],
+    reference: &ScriptureReference,
+) -> Result<bool, DbError> {
+    let wanted = to_span(reference)?;
+    Ok(spans(references)?
+        .iter()
+        .any(|s| s.start <= wanted.start && wanted.end <= s.end))
+}
+
+/// `reference` as one reference per chapter it touches.
+pub(crate) fn split_by_chapter(
+    reference: &ScriptureReference,
+) -> Result<Vec<ScriptureReference>, DbError> {
+    let Span { start, end } = to_span(reference)?;
+    let index = start.0;
+    Ok((start.1..=end.1)
+        .map(|chapter| {
+            let first = if chapter == start.1 { start.2 } else { 1 };
+            let last = if chapter == end.1 {
+                end.2
+            } else {
+                verses_in(index, chapter).unwrap_or(end.2)
+            };
+            to_reference(Span {
+                start: (index, chapter, first),
+                end: (index, chapter, last),
+            })
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RangeOperation {
+    /// `references` merged.
+    Union,
+    /// What `references` and `other` share.
+    Intersection,
+    /// Whether `references` cover the first of `other`.
+    Contains,
+    /// Each of `references` split into chapters.
+    SplitByChapter,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum RangeResult {
+    References(Vec<ScriptureReference>),
+    Contains(bool),
+}
+
+/// Run `operation` on parsed references (see `parse_references`), e.g.
+/// to find the markings within a pericope or the chapters left in a plan.
+#[command]
+pub fn reference_ranges(
+    operation: RangeOperation,
+    references: Vec<ScriptureReference>,
+    other: Option<Vec<ScriptureReference>>,
+) -> Result<RangeResult, DbError> {
+    let other = other.unwrap_or_default();
+    Ok(match operation {
+        RangeOperation::Union => RangeResult::References(union(&references)?),
+        RangeOperation::Intersection => RangeResult::References(intersection(&references, &other)?),
+        RangeOperation::Contains => {
+            let reference = other
+                .first()
+                .ok_or_else(|| DbError::invalid("Nothing to look for"))?;
+            RangeResult::Contains(contains(&references, reference)?)
+        }
+        RangeOperation::SplitByChapter => {
+            let mut split = Vec::new();
+            for reference in &references {
+                split.extend(split_by_chapter(reference)?);
+            }
+            RangeResult::References(split)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::references::parse;
+
+    fn labels(references: Vec<ScriptureReference>) -> Vec<String> {
+        references.into_iter().map(|r| r.label).collect()
+    }
+
+    #[test]
+    fn combines_references_as_verse_sets() {
+        let union_of = |text: &str| labels(union(&parse(text)).unwrap());
+        assert_eq!(union_of("Rom 8:1-20; Rom 8:15-39"), ["Romans 8"]);
+        assert_eq!(union_of("Gen 2; Gen 1"), ["Genesis 1–2"]);
+        assert_eq!(union_of("Jn 3:16, 18"), ["John 3:16", "John 3:18"]);
+        assert_eq!(union_of("Jn 3:36; Jn 4:1-3"), ["John 3:36–4:3"]);
+        assert_eq!(union_of("Jude 1-25"), ["Jude"]);
+
+        let both = intersection(&parse("Matt 5-7"), &parse("Matt 6:9-13; Matt 7:28-8:4")).unwrap();
+        assert_eq!(labels(both), ["Matthew 6:9–13", "Matthew 7:28–29"]);
+        assert!(intersection(&parse("Gen 1"), &parse("Exod 1"))
+            .unwrap()
+            .is_empty());
+
+        let sermon = parse("Matt 5-7");
+        assert!(contains(&sermon, &parse("Matt 6:9-13")[0]).unwrap());
+        assert!(!contains(&sermon, &parse("Matt 7:28-8:1")[0]).unwrap());
+
+        let split = split_by_chapter(&parse("Gen 1:26-3:5")[0]).unwrap();
+        assert_eq!(
+            labels(split),
+            ["Genesis 1:26–31", "Genesis 2", "Genesis 3:1–5"]
+        );
+    }
+}
@@ -0,0 +1,637 @@
+//! Verse references as people type them: "Jn 3:16-18; Rom 8; 1 Pet 1:3".
+//!
+//! A list is split on `;`, after which a reference may name a new book or
+//! reuse the last one, and on `,`, which continues the reference before it:
+//! "Jn 3:16, 18" is John 3:16 and 3:18, "Rom 8, 9" chapters 8 and 9. Books
+//! go by their names, ids and the usual abbreviations (see
+//! `books::by_abbreviation`), or by their names and abbreviations in the
+//! languages `citation` writes ("Juan 3:16", "1. Mose 1"), or by aliases the
+//! user added; a dot separates chapter and verse as well as a
+//! colon, so OSIS "Gen.1.1" reads too. A one-chapter book takes a lone
+//! number as a verse ("Jude 3"). Chapters and verses are checked against the
+//! KJV versification, as the reader's navigation is, and parts that don't
+//! parse are left out, or, when checking, reported with the books a
+//! misspelled name might have meant ("Galations 5": Galatians 5).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::command;
+
+use super::books::{self, BookId};
+use super::citation::{self, ReferenceStyle, Separators};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScriptureReference {
+    /// OSIS book id.
+    pub book: String,
+    pub chapter: i64,
+    /// `None` for whole chapters (or, with `chapter` 1, a whole book).
+    pub verse: Option<i64>,
+    #[serde(rename = "endChapter")]
+    pub end_chapter: i64,
+    #[serde(rename = "endVerse")]
+    pub end_verse: Option<i64>,
+    /// The reference in OSIS form, e.g. `John.3.16-John.3.18`.
+    #[serde(default)]
+    pub osis: String,
+    /// The reference written out, e.g. `John 3:16–18` (see `citation`).
+    #[serde(default)]
+    pub label: String,
+}
+
+/// Most books suggested for a name no book goes by.
+const SUGGESTIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceErrorKind {
+    /// No book goes by the name; `suggestions` are the closest that do.
+    UnknownBook,
+    /// Nothing to read, or chapters and verses the book doesn't have.
+    Invalid,
+}
+
+/// Serializes to `{ kind, message, text, suggestions }`: `DbError`'s shape,
+/// with the part of the text that didn't read and what it might have meant.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ReferenceError {
+    pub kind: ReferenceErrorKind,
+    pub message: String,
+    /// The part that didn't read, e.g. `Galations 5`.
+    pub text: String,
+    /// Best first.
+    pub suggestions: Vec<BookSuggestion>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BookSuggestion {
+    /// OSIS book id.
+    pub book: String,
+    /// The name it is close to, e.g. `Galatians`.
+    pub name: String,
+    /// The part with that name instead, e.g. `Galatians 5`.
+    pub reference: String,
+    /// Letters to add, drop, change or swap to get from one to the other.
+    pub distance: usize,
+}
+
+/// What a reference without a book, or continuing after a comma, refers to.
+struct Context {
+    book: &'static BookId,
+    chapter: i64,
+    /// The last reference ended on a verse, so a lone number is a verse.
+    verse: bool,
+}
+
+/// Whether `part` is only numbers and separators, with perhaps a verse
+/// part letter ("16b").
+fn is_numbers(part: &str) -> bool {
+    let part = part.trim_end_matches(['a', 'b', 'c']);
+    part.starts_with(|c: char| c.is_ascii_digit())
+        && part
+            .chars()
+            .all(|c| c.is_ascii_digit() || ":.-–— ".contains(c))
+}
+
+/// `part` split into the book it names, if any, and the numbers after it.
+fn split_book(part: &str) -> (Option<&str>, &str) {
+    if is_numbers(part) {
+        return (None, part);
+    }
+    let mut letters = false;
+    for (i, c) in part.char_indices() {
+        if c.is_alphabetic() {
+            letters = true;
+        } else if c.is_ascii_digit() && letters {
+            return (Some(&part[..i]), &part[i..]);
+        }
+    }
+    (Some(part), "")
+}
+
+/// A chapter or verse number, without a verse part letter.
+fn number(s: &str) -> Option<i64> {
+    s.trim()
+        .trim_end_matches(['a', 'b', 'c'])
+        .parse()
+        .ok()
+        .filter(|&n| n > 0)
+}
+
+/// `c:v` (or `c.v`) as chapter and verse, or a lone number.
+fn point(s: &str) -> Option<(i64, Option<i64>)> {
+    match s.split_once([':', '.']) {
+        Some((chapter, verse)) => Some((number(chapter)?, Some(number(verse)?))),
+        None => Some((number(s)?, None)),
+    }
+}
+
+fn osis(book: &str, chapter: i64, verse: Option<i64>) -> String {
+    match verse {
+        Some(verse) => format!("{book}.{chapter}.{verse}"),
+        None => format!("{book}.{chapter}"),
+    }
+}
+
+/// `book` from `start` to `end`, once checked against the KJV numbering.
+pub(super) fn span(
+    book: &'static BookId,
+    (chapter, verse): (i64, Option<i64>),
+    (end_chapter, end_verse): (i64, Option<i64>),
+) -> Option<ScriptureReference> {
+    let chapters = books::KJV_VERSES[books::position(book.osis)? - 1];
+    let fits = |chapter: i64, verse: Option<i64>| {
+        let verses = chapters.get(usize::try_from(chapter).ok()?.checked_sub(1)?)?;
+        Some(verse.is_none_or(|v| v <= i64::from(*verses)))
+    };
+    if !fits(chapter, verse)? || !fits(end_chapter, end_verse)? {
+        return None;
+    }
+    if (end_chapter, end_verse.unwrap_or(i64::MAX)) < (chapter, verse.unwrap_or(0)) {
+        return None;
+    }
+    let whole_book = chapter == 1 && end_chapter == chapters.len() as i64 && verse.is_none();
+    let osis = if whole_book && end_verse.is_none() {
+        book.osis.to_string()
+    } else if (chapter, verse) == (end_chapter, end_verse) {
+        osis(book.osis, chapter, verse)
+    } else {
+        format!(
+            "{}-{}",
+            osis(book.osis, chapter, verse),
+            osis(book.osis, end_chapter, end_verse)
+        )
+    };
+    let mut reference = ScriptureReference {
+        book: book.osis.to_string(),
+        chapter,
+        verse,
+        end_chapter,
+        end_verse,
+        osis,
+        label: String::new(),
+    };
+    reference.label = citation::format(
+        &reference,
+        ReferenceStyle::Full,
+        None,
+        &Separators::default(),
+    )?;
+    Some(reference)
+}
+
+/// User aliases, by folded name (see `books::fold`).
+pub(crate) type Aliases = [(String, &'static BookId)];
+
+/// `names` (alias to any name of the book it stands for) as `Aliases`;
+/// ones naming no book are left out.
+pub(crate) fn aliases(names: &HashMap<String, String>) -> Vec<(String, &'static BookId)> {
+    names
+        .iter()
+        .filter_map(|(alias, name)| Some((books::fold(alias), book(name.trim(), &[])?)))
+        .collect()
+}
+
+/// The book `name` names: one of `aliases`, an English name or
+/// abbreviation, or a name in another language.
+fn book(name: &str, aliases: &Aliases) -> Option<&'static BookId> {
+    let folded = books::fold(name);
+    aliases
+        .iter()
+        .find(|(alias, _)| *alias == folded)
+        .map(|(_, book)| *book)
+        .or_else(|| books::by_abbreviation(name))
+        .or_else(|| citation::book_named(name))
+}
+
+/// Edits (insertions, deletions, substitutions and swaps of neighbours)
+/// between `a` and `b`, so "pslam" is one from "psalm".
+fn distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d: Vec<Vec<usize>> = (0..=a.len())
+        .map(|i| (0..=b.len()).map(|j| if i == 0 { j } else { i }).collect())
+        .collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let change = d[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut best = change.min(d[i - 1][j] + 1).min(d[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(d[i - 2][j - 2] + 1);
+            }
+            d[i][j] = best;
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// The books `name` (followed by `rest`) most likely misspells: names and
+/// abbreviations in any language within a third of its letters, closest
+/// first, then ones starting with the same letter (a misspelling rarely
+/// starts wrong), then in canonical order.
+fn suggestions(name: &str, rest: &str) -> Vec<BookSuggestion> {
+    let wanted = books::fold(name);
+    let letters = wanted.trim_start_matches(char::is_numeric).chars().count();
+    if letters < 3 {
+        return Vec::new();
+    }
+    let most = letters.div_ceil(3);
+    // Closest name of each book: (distance, starts differently), book
+    // index, name.
+    let mut closest: Vec<((usize, bool), usize, &str)> = Vec::new();
+    for (index, candidate) in citation::book_names() {
+        let folded = books::fold(candidate);
+        let d = distance(&wanted, &folded);
+        if d > most {
+            continue;
+        }
+        let rank = (d, wanted.chars().next() != folded.chars().next());
+        match closest.iter_mut().find(|(_, i, _)| *i == index) {
+            Some(found) if found.0 <= rank => {}
+            Some(found) => *found = (rank, index, candidate),
+            None => closest.push((rank, index, candidate)),
+        }
+    }
+    closest.sort_by_key(|&(rank, index, _)| (rank, index));
+    closest
+        .into_iter()
+        .take(SUGGESTIONS)
+        .map(|((distance, _), index, name)| BookSuggestion {
+            book: books::BOOKS[index].osis.to_string(),
+            name: name.to_string(),
+            reference: format!("{name} {rest}").trim_end().to_string(),
+            distance,
+        })
+        .collect()
+}
+
+/// Why `part` doesn't read as a reference.
+fn unreadable(part: &str, aliases: &Aliases) -> ReferenceError {
+    let (name, rest) = split_book(part);
+    match name.map(str::trim) {
+        Some(name) if book(name, aliases).is_none() => {
+            let suggestions = suggestions(name, rest.trim());
+            let message = match suggestions.first() {
+                Some(best) => format!(
+                    "No book is called `{name}`; did you mean {}?",
+                    best.reference
+                ),
+                None => format!("No book is called `{name}`"),
+            };
+            ReferenceError {
+                kind: ReferenceErrorKind::UnknownBook,
+                message,
+                text: part.to_string(),
+                suggestions,
+            }
+        }
+        _ => ReferenceError {
+            kind: ReferenceErrorKind::Invalid,
+            message: format!("`{part}` is not a reference this Bible has"),
+            text: part.to_string(),
+            suggestions: Vec::new(),
+        },
+    }
+}
+
+/// One reference of a list; `continues` when it follows a comma.
+fn reference(
+    part: &str,
+    context: Option<&Context>,
+    continues: bool,
+    aliases: &Aliases,
+) -> Option<(ScriptureReference, Context)> {
+    let (name, rest) = split_book(part);
+    let book = match name {
+        Some(name) => self::book(name.trim(), aliases)?,
+        None => context?.book,
+    };
+    let chapters = books::KJV_VERSES[books::position(book.osis)? - 1].len() as i64;
+    let rest = rest.replace(['–', '—'], "-");
+    let rest = rest.trim();
+    if rest.is_empty() {
+        // A book alone is the whole book.
+        let whole = span(book, (1, None), (chapters, None))?;
+        let context = Context {
+            book,
+            chapter: chapters,
+            verse: false,
+        };
+        return Some((whole, context));
+    }
+
+    let (start, end) = match rest.split_once('-') {
+        Some((start, end)) => (start, Some(end)),
+        None => (rest, None),
+    };
+    let (chapter, verse) = match point(start)? {
+        (n, None) if chapters == 1 => (1, Some(n)),
+        (n, None) if continues && name.is_none() && context.is_some_and(|c| c.verse) => {
+            (context?.chapter, Some(n))
+        }
+        point => point,
+    };
+    let end = match end {
+        Some(end) => {
+            // The end may name the book again, as OSIS ranges do.
+            let end = match split_book(end.trim()) {
+                (Some(name), rest) if self::book(name.trim(), aliases)?.osis == book.osis => rest,
+                (Some(_), _) => return None,
+                (None, end) => end,
+            };
+            match point(end)? {
+                (n, None) if verse.is_some() => (chapter, Some(n)),
+                point => point,
+            }
+        }
+        None => (chapter, verse),
+    };
+    // "Rom 8-9:3" starts at the first verse of chapter 8.
+    let verse = verse.or(end.1.map(|_| 1));
+    let found = span(book, (chapter, verse), end)?;
+    let context = Context {
+        book,
+        chapter: found.end_chapter,
+        verse: found.end_verse.is_some(),
+    };
+    Some((found, context))
+}
+
+/// Every reference in `text`, in order.
+pub(crate) fn parse(text: &str) -> Vec<ScriptureReference> {
+    parse_with(text, &[])
+}
+
+/// Every reference in `text`, reading `aliases` as the books they name.
+pub(crate) fn parse_with(text: &str, aliases: &Aliases) -> Vec<ScriptureReference> {
+    read(text, aliases, false).unwrap_or_default()
+}
+
+/// Every reference in `text`, as `parse_with` reads them, unless a part
+/// doesn't read or there's nothing to read.
+pub(crate) fn check_with(
+    text: &str,
+    aliases: &Aliases,
+) -> Result<Vec<ScriptureReference>, ReferenceError> {
+    let references = read(text, aliases, true)?;
+    if references.is_empty() {
+        return Err(ReferenceError {
+            kind: ReferenceErrorKind::Invalid,
+            message: "No reference to read".to_string(),
+            text: text.trim().to_string(),
+            suggestions: Vec::new(),
+        });
+    }
+    Ok(references)
+}
+
+/// The references in `text`; when `strict`, the first part that doesn't
+/// read is an error rather than left out.
+fn read(
+    text: &str,
+    aliases: &Aliases,
+    strict: bool,
+) -> Result<Vec<ScriptureReference>, ReferenceError> {
+    let mut references = Vec::new();
+    let mut context: Option<Context> = None;
+    for group in text.split([';', '\n']) {
+        for (i, part) in group.split(',').enumerate() {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            match reference(part, context.as_ref(), i > 0, aliases) {
+                Some((found, next)) => {
+                    references.push(found);
+                    context = Some(next);
+                }
+                None if strict => return Err(unreadable(part, aliases)),
+                None => {}
+            }
+        }
+    }
+    Ok(references)
+}
+
+/// How much of `s` is chapter and verse numbers as running text writes
+/// them ("3:16-18, 21"): numbers joined by `:` or `.`, ranges and lists,
+/// ending on a number or verse part letter.
+fn numbers_len(s: &str) -> usize {
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let next_is = |j: usize, f: fn(char) -> bool| chars.get(j).is_some_and(|&(_, c)| f(c));
+    let skip_spaces = |mut j: usize| {
+        while next_is(j, |c| c == ' ') {
+            j += 1;
+        }
+        j
+    };
+    let (mut end, mut i, mut after_digit) = (0, 0, false);
+    while let Some(&(at, c)) = chars.get(i) {
+        if c.is_ascii_digit() {
+            (end, after_digit) = (at + 1, true);
+            i += 1;
+            continue;
+        }
+        if !after_digit {
+            break;
+        }
+        match c {
+            'a' | 'b' | 'c' if !next_is(i + 1, char::is_alphanumeric) => {
+                end = at + 1;
+                break;
+            }
+            ':' | '.' if next_is(i + 1, |c| c.is_ascii_digit()) => i += 1,
+            '-' | '–' | '—' | ',' if next_is(skip_spaces(i + 1), |c| c.is_ascii_digit()) => {
+                i = skip_spaces(i + 1);
+            }
+            // "3:16 - 18"
+            ' ' if next_is(skip_spaces(i), |c| "-–—".contains(c)) => i = skip_spaces(i),
+            _ => break,
+        }
+        after_digit = c == ' ' || c.is_ascii_digit();
+    }
+    end
+}
+
+/// The references in running text ("compare with Heb 11:6 and 1 Cor 13"),
+/// in order. Books are capitalized, as people write them, and one named in
+/// fewer than three letters needs a verse ("Ps 23:1", not "Ps 23"), so
+/// "I Am 3 days in" isn't Amos 3.
+pub(crate) fn find(text: &str) -> Vec<ScriptureReference> {
+    let mut found = Vec::new();
+    // Words before `unused` belong to a reference already found.
+    let (mut unused, mut skip) = (0, 0);
+    for (at, c) in text.char_indices() {
+        if at < skip || !c.is_ascii_digit() || !text[..at].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let numbers = &text[at..at + numbers_len(&text[at..])];
+        skip = at + numbers.len();
+        let words: Vec<&str> = text[unused..at].split_whitespace().collect();
+        for k in (1..=words.len().min(4)).rev() {
+            let (first, others) = words[words.len() - k..].split_first().unwrap_or((&"", &[]));
+            let first = first.trim_start_matches(|c: char| !c.is_alphanumeric());
+            let plain = |w: &&str| w.chars().all(|c| c.is_alphanumeric() || c == '.');
+            let name = std::iter::once(first)
+                .chain(others.iter().copied())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !others.iter().all(plain)
+                || !name
+                    .chars()
+                    .find(|c| c.is_alphabetic())
+                    .is_some_and(char::is_uppercase)
+            {
+                continue;
+            }
+            let letters = books::fold(&name)
+                .trim_start_matches(char::is_numeric)
+                .chars()
+                .count();
+            if letters < 3 && !numbers.contains([':', '.']) {
+                continue;
+            }
+            let references = parse(&format!("{name} {numbers}"));
+            if !references.is_empty() {
+                found.extend(references);
+                unused = skip;
+                break;
+            }
+        }
+    }
+    found
+}
+
+/// The verse references in `text` ("Jn 3:16-18; Rom 8; 1 Pet 1:3"),
+/// normalized, for search, note links and quick navigation. `aliases` maps
+/// names the user added ("Evangelio de Juan") to the book each stands for,
+/// by any name the parser knows.
+#[command]
+pub fn parse_references(
+    text: String,
+    aliases: Option<HashMap<String, String>>,
+) -> Vec<ScriptureReference> {
+    parse_with(&text, &self::aliases(&aliases.unwrap_or_default()))
+}
+
+/// The references in `text` as `parse_references` reads them, or why a
+/// part doesn't read: a book no book is called, with the ones it might
+/// misspell ("Did you mean Galatians 5?"), or numbers the book doesn't have.
+#[command]
+pub fn check_references(
+    text: String,
+    aliases: Option<HashMap<String, String>>,
+) -> Result<Vec<ScriptureReference>, ReferenceError> {
+    check_with(&text, &self::aliases(&aliases.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osis(text: &str) -> Vec<String> {
+        parse(text).into_iter().map(|r| r.osis).collect()
+    }
+
+    #[test]
+    fn parses_lists_ranges_and_abbreviations() {
+        let found = parse("Jn 3:16-18; Rom 8; 1 Pet 1:3");
+        let labels: Vec<&str> = found.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, ["John 3:16–18", "Romans 8", "1 Peter 1:3"]);
+        assert_eq!(
+            (found[0].chapter, found[0].verse, found[0].end_verse),
+            (3, Some(16), Some(18))
+        );
+        assert_eq!(found[0].osis, "John.3.16-John.3.18");
+        assert_eq!((found[1].verse, found[1].end_chapter), (None, 8));
+
+        assert_eq!(osis("Jn 3:16, 18; 4"), ["John.3.16", "John.3.18", "John.4"]);
+        assert_eq!(osis("Rom 8, 9"), ["Rom.8", "Rom.9"]);
+        assert_eq!(osis("Gen 1:1–2:3"), ["Gen.1.1-Gen.2.3"]);
+        assert_eq!(osis("Gen.1.1-Gen.1.3"), ["Gen.1.1-Gen.1.3"]);
+        assert_eq!(
+            osis("II Cor 5:17; Jude 3; Ps 119:105a"),
+            ["2Cor.5.17", "Jude.1.3", "Ps.119.105"]
+        );
+        assert_eq!(
+            osis("First John 1:9, Song of Solomon 2"),
+            ["1John.1.9", "Song.2"]
+        );
+        assert_eq!(parse("Phlm")[0].label, "Philemon");
+        assert_eq!(parse("Matt 5-7")[0].label, "Matthew 5–7");
+
+        // Not books, past the end of a chapter, or backwards.
+        assert!(parse("love one another").is_empty());
+        assert!(parse("Ju 3").is_empty());
+        assert!(parse("John 3:37").is_empty());
+        assert!(parse("John 3:18-16").is_empty());
+        assert!(parse("Gen 50 - Exod 2").is_empty());
+    }
+
+    #[test]
+    fn finds_references_in_running_text() {
+        let osis = |text: &str| find(text).into_iter().map(|r| r.osis).collect::<Vec<_>>();
+        assert_eq!(
+            osis(
+                "Faith pleases God (compare with Heb 11:6). See also 1 Cor 13 and Rom 8:28–30, 32."
+            ),
+            ["Heb.11.6", "1Cor.13", "Rom.8.28-Rom.8.30", "Rom.8.32"]
+        );
+        assert_eq!(
+            osis("Song of Solomon 2:4 - 6 and First John 1:9b."),
+            ["Song.2.4-Song.2.6", "1John.1.9"]
+        );
+        assert_eq!(
+            osis("In Genesis 1 God speaks; Ps 23:1"),
+            ["Gen.1", "Ps.23.1"]
+        );
+        assert!(osis("I Am 3 days in, read 2 chapters of john 3 by 5:30.").is_empty());
+        assert!(osis("Ps 23 and Room 101").is_empty());
+    }
+
+    #[test]
+    fn reads_other_languages_and_aliases() {
+        assert_eq!(osis("Juan 3:16"), ["John.3.16"]);
+        assert_eq!(osis("1. Mose 1"), ["Gen.1"]);
+        assert_eq!(osis("Génesis 1; Genesis 2"), ["Gen.1", "Gen.2"]);
+        assert_eq!(osis("Apocalipsis 21:4"), ["Rev.21.4"]);
+        assert_eq!(osis("Johannes 3:16; Apg 2:38"), ["John.3.16", "Acts.2.38"]);
+        assert_eq!(osis("Évangile 1"), Vec::<String>::new());
+
+        let aliases = [(books::fold("Evangelio de Juan"), &books::BOOKS[42])];
+        assert_eq!(
+            parse_with("Evangelio de Juan 1:1", &aliases)[0].osis,
+            "John.1.1"
+        );
+    }
+
+    #[test]
+    fn suggests_books_for_misspelled_names() {
+        let unknown = |text: &str| check_with(text, &[]).unwrap_err();
+        let galatians = unknown("Galations 5");
+        assert_eq!(galatians.kind, ReferenceErrorKind::UnknownBook);
+        assert_eq!(
+            (
+                galatians.suggestions[0].reference.as_str(),
+                galatians.suggestions[0].distance
+            ),
+            ("Galatians 5", 1)
+        );
+        assert_eq!(
+            galatians.message,
+            "No book is called `Galations`; did you mean Galatians 5?"
+        );
+        assert_eq!(unknown("Pslam 23").suggestions[0].book, "Ps");
+        assert_eq!(
+            unknown("Rom 8; 1 Corinthans 13:4-7").suggestions[0].reference,
+            "1 Corinthians 13:4-7"
+        );
+        assert_eq!(unknown("Revelations 21").suggestions[0].name, "Revelation");
+
+        assert!(unknown("Xyzzy 3").suggestions.is_empty());
+        assert!(unknown("Ju 3").suggestions.is_empty());
+        assert_eq!(unknown("John 3:37").kind, ReferenceErrorKind::Invalid);
+        assert_eq!(unknown("  ").kind, ReferenceErrorKind::Invalid);
+        assert_eq!(check_with("Jn 3:16; Ps 23", &[]).unwrap().len(), 2);
+        assert_eq!(distance("pslam", "psalm"), 1);
+    }
+}
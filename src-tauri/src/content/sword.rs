@@ -0,0 +1,1154 @@
+//! SWORD (CrossWire) Bible modules.
+//!
+//! A SWORD library is a directory with `mods.d/*.conf` describing each
+//! module and the module data under `modules/`. Bible text comes in two
+//! layouts: RawText (`ot`/`nt` with a `.vss` index) and zText, whose verses
+//! are zlib-compressed in blocks (`.bzz`, with `.bzs` block and `.bzv` verse
+//! indexes); the `4` variants widen the size fields. Either way verse `n` of
+//! a testament is entry `n` of its index, counting the module, testament,
+//! book and chapter heading entries the versification puts before it. Only
+//! KJV versification (and KJVA, whose extra books follow Malachi) is laid
+//! out here; modules in other versifications are listed but can't be
+//! imported yet, like locked (enciphered) ones.
+//!
+//! OSIS, ThML and GBF verse markup is reduced to plain text, keeping section
+//! titles as headings, paragraphs (`<p>`, `<milestone type="x-p"/>`,
+//! `<CM>`) and poetry lines (`<l level="2">`) as layout, notes other than
+//! cross references as footnotes and Strong's numbers (`<w lemma="strong:H7225">`, `<sync type="Strongs">`,
+//! `<WH7225>`) and morphology (`<w morph="robinson:N-NSM">`, `<WTN-NSM>`) as
+//! tagged words, and the words of Christ (`<q who="Jesus">`, `<FR>`) as red
+//! letter. Book and chapter introductions are skipped.
+//!
+//! Lexicons keyed by Strong's number are read too, for `lexicon`.
+
+use flate2::read::ZlibDecoder;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{command, Manager};
+
+use super::books;
+use super::content_path;
+use super::import::{self, Book, Footnote, Heading, ImportReport, Verse, Warnings, Word};
+use super::xml;
+use crate::db::{DbError, DbErrorKind};
+
+const CONF_DIR: &str = "mods.d";
+
+/// Versifications whose layout of the 66 books is KJV's.
+const KJV_LAYOUTS: &[&str] = &["KJV", "KJVA"];
+
+/// Libraries scanned this session, for `list_sword_modules`.
+static LIBRARIES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+fn libraries() -> MutexGuard<'static, Vec<PathBuf>> {
+    LIBRARIES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Driver {
+    RawText { wide: bool },
+    ZText { wide: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Markup {
+    Osis,
+    Thml,
+    Gbf,
+    Plain,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SwordModule {
+    /// Module name, e.g. `KJV`; also the translation id it imports as.
+    pub name: String,
+    pub description: String,
+    pub language: Option<String>,
+    pub version: Option<String>,
+    pub versification: String,
+    /// The library directory the module is in.
+    pub library: String,
+    /// Why the module can't be imported, if it can't.
+    pub problem: Option<String>,
+}
+
+/// One module section of a `.conf` file.
+struct Conf {
+    name: String,
+    values: HashMap<String, String>,
+}
+
+impl Conf {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.values
+            .get(key)
+            .map(String::as_str)
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// Read a `.conf` file: `[Name]` then `Key=Value` lines, where a trailing
+/// `\` continues the value on the next line. Repeated keys keep the first.
+fn parse_conf(src: &str) -> Option<Conf> {
+    let mut conf: Option<Conf> = None;
+    let mut lines = src.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if conf.is_some() {
+                break;
+            }
+            conf = Some(Conf {
+                name: name.trim().to_string(),
+                values: HashMap::new(),
+            });
+            continue;
+        }
+        let Some(conf) = &mut conf else {
+            continue;
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let mut value = value.trim().to_string();
+        while value.ends_with('\\') {
+            value.pop();
+            match lines.next() {
+                Some(more) => value.push_str(more.trim()),
+                None => break,
+            }
+        }
+        conf.values.entry(key.trim().to_string()).or_insert(value);
+    }
+    conf
+}
+
+fn driver(conf: &Conf) -> Option<Driver> {
+    match conf.get("ModDrv")?.to_ascii_lowercase().as_str() {
+        "rawtext" => Some(Driver::RawText { wide: false }),
+        "rawtext4" => Some(Driver::RawText { wide: true }),
+        "ztext" => Some(Driver::ZText { wide: false }),
+        "ztext4" => Some(Driver::ZText { wide: true }),
+        _ => None,
+    }
+}
+
+fn markup(conf: &Conf) -> Markup {
+    match conf
+        .get("SourceType")
+        .unwrap_or("Plain")
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "osis" => Markup::Osis,
+        // Lexicons' TEI reads as tags around text, like OSIS.
+        "tei" => Markup::Osis,
+        "thml" => Markup::Thml,
+        "gbf" => Markup::Gbf,
+        _ => Markup::Plain,
+    }
+}
+
+fn data_dir(library: &Path, conf: &Conf) -> PathBuf {
+    let path = conf.get("DataPath").unwrap_or_default();
+    library.join(path.trim_start_matches("./"))
+}
+
+/// Why `conf` can't be imported from `library`, if it can't.
+fn problem(library: &Path, conf: &Conf) -> Option<String> {
+    let versification = conf.get("Versification").unwrap_or("KJV");
+    if !KJV_LAYOUTS.contains(&versification) {
+        return Some(format!("{versification} versification is not supported"));
+    }
+    // Locked modules have a `CipherKey`, empty until the user enters one.
+    if conf.values.contains_key("CipherKey") {
+        return Some("Locked modules are not supported".into());
+    }
+    if matches!(driver(conf), Some(Driver::ZText { .. })) {
+        let compression = conf.get("CompressType").unwrap_or("ZIP");
+        if !compression.eq_ignore_ascii_case("ZIP") {
+            return Some(format!("{compression} compression is not supported"));
+        }
+    }
+    if !data_dir(library, conf).is_dir() {
+        return Some("The module's data is missing".into());
+    }
+    None
+}
+
+/// The Bible modules in `library`, by name. Commentaries, dictionaries and
+/// other module types are left out.
+fn scan(library: &Path) -> Result<Vec<(SwordModule, Conf)>, DbError> {
+    let dir = library.join(CONF_DIR);
+    let entries = std::fs::read_dir(&dir).map_err(|_| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!(
+                "{} is not a SWORD library: no {CONF_DIR}",
+                library.display()
+            ),
+        )
+    })?;
+    let mut modules = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("conf"))
+        {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(&path) else {
+            continue;
+        };
+        let Some(conf) = parse_conf(&String::from_utf8_lossy(&bytes)) else {
+            continue;
+        };
+        if driver(&conf).is_none() {
+            continue;
+        }
+        let module = SwordModule {
+            name: conf.name.clone(),
+            description: conf.get("Description").unwrap_or(&conf.name).to_string(),
+            language: conf.get("Lang").map(str::to_string),
+            version: conf.get("Version").map(str::to_string),
+            versification: conf.get("Versification").unwrap_or("KJV").to_string(),
+            library: library.display().to_string(),
+            problem: problem(library, &conf),
+        };
+        modules.push((module, conf));
+    }
+    modules.sort_by_key(|(m, _)| m.name.to_lowercase());
+    Ok(modules)
+}
+
+/// One testament's files.
+struct Testament {
+    driver: Driver,
+    index: Vec<u8>,
+    data: Vec<u8>,
+    /// zText block index.
+    blocks: Vec<u8>,
+    /// The last block decompressed.
+    block: Option<(u32, Vec<u8>)>,
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+impl Testament {
+    /// The files for testament `name` (`ot` or `nt`), or `None` if the
+    /// module doesn't have it.
+    fn open(dir: &Path, name: &str, driver: Driver) -> Result<Option<Self>, DbError> {
+        let (index, data, blocks) = match driver {
+            Driver::RawText { .. } => (format!("{name}.vss"), name.to_string(), None),
+            Driver::ZText { .. } => (
+                format!("{name}.bzv"),
+                format!("{name}.bzz"),
+                Some(format!("{name}.bzs")),
+            ),
+        };
+        if !dir.join(&index).exists() {
+            return Ok(None);
+        }
+        let read = |file: &str| {
+            std::fs::read(dir.join(file))
+                .map_err(|e| DbError::io(format!("Failed to read {file}: {e}")))
+        };
+        Ok(Some(Self {
+            driver,
+            index: read(&index)?,
+            data: read(&data)?,
+            blocks: match blocks {
+                Some(file) => read(&file)?,
+                None => Vec::new(),
+            },
+            block: None,
+        }))
+    }
+
+    /// The bytes of index entry `n`; `None` if it's empty or past the end.
+    fn entry(&mut self, n: usize) -> Result<Option<&[u8]>, String> {
+        match self.driver {
+            Driver::RawText { wide } => {
+                let width = if wide { 8 } else { 6 };
+                let at = n * width;
+                let Some(offset) = u32_at(&self.index, at) else {
+                    return Ok(None);
+                };
+                let size = if wide {
+                    u32_at(&self.index, at + 4)
+                } else {
+                    u16_at(&self.index, at + 4).map(u32::from)
+                };
+                let (start, size) = (offset as usize, size.unwrap_or(0) as usize);
+                Ok(self.data.get(start..start + size).filter(|b| !b.is_empty()))
+            }
+            Driver::ZText { wide } => {
+                let width = if wide { 12 } else { 10 };
+                let at = n * width;
+                let (Some(block), Some(start)) =
+                    (u32_at(&self.index, at), u32_at(&self.index, at + 4))
+                else {
+                    return Ok(None);
+                };
+                let size = if wide {
+                    u32_at(&self.index, at + 8)
+                } else {
+                    u16_at(&self.index, at + 8).map(u32::from)
+                };
+                let size = size.unwrap_or(0) as usize;
+                if size == 0 {
+                    return Ok(None);
+                }
+                if self.block.as_ref().map(|(b, _)| *b) != Some(block) {
+                    let at = block as usize * 12;
+                    let (Some(offset), Some(compressed)) =
+                        (u32_at(&self.blocks, at), u32_at(&self.blocks, at + 4))
+                    else {
+                        return Err(format!("Block {block} is missing from the index"));
+                    };
+                    let (offset, compressed) = (offset as usize, compressed as usize);
+                    let raw = self
+                        .data
+                        .get(offset..offset + compressed)
+                        .ok_or_else(|| format!("Block {block} is past the end of the data"))?;
+                    let mut bytes = Vec::new();
+                    ZlibDecoder::new(raw)
+                        .read_to_end(&mut bytes)
+                        .map_err(|e| format!("Block {block} is corrupt: {e}"))?;
+                    self.block = Some((block, bytes));
+                }
+                let bytes = &self.block.as_ref().expect("decompressed above").1;
+                let start = start as usize;
+                Ok(bytes.get(start..start + size))
+            }
+        }
+    }
+}
+
+/// What one verse entry held, once its markup is read.
+#[derive(Debug, Default, PartialEq)]
+struct Entry {
+    text: String,
+    /// (style, text) of titles in the entry.
+    headings: Vec<(String, String)>,
+    /// (caller, position, text, kind) of its notes.
+    notes: Vec<(String, usize, String, String)>,
+    words: Vec<Word>,
+    red_letter: Vec<(usize, usize)>,
+    /// (position, style) of the paragraphs and poetry lines it begins.
+    paragraphs: Vec<(usize, String)>,
+}
+
+/// Value of `key` in the attributes part of a tag.
+pub(super) fn tag_attr<'a>(tag: &'a str, key: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(at) = rest.find(key) {
+        let before_ok = at == 0 || rest.as_bytes()[at - 1].is_ascii_whitespace();
+        let after = rest[at + key.len()..].trim_start();
+        if let (true, Some(value)) = (before_ok, after.strip_prefix('=')) {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                return value[1..].split(quote).next();
+            }
+        }
+        rest = &rest[at + key.len()..];
+    }
+    None
+}
+
+/// Read the markup of one verse. SWORD entries are fragments of a larger
+/// document (milestones, tags opened in one verse and closed in another),
+/// so tags are read one at a time rather than as XML. Bare Strong's
+/// numbers take `prefix`.
+fn read_entry(raw: &str, markup: Markup, prefix: char) -> Entry {
+    enum Target {
+        Text,
+        Heading(String),
+        /// Caller and `import::note_kind`.
+        Note(String, String),
+        Drop,
+    }
+    let mut entry = Entry::default();
+    let mut target = Target::Text;
+    let mut buf = String::new();
+    // Start in the text and attributes of the open `<w>`.
+    let mut word: Option<(usize, &str)> = None;
+    // Start in the text of the words of Christ, which of the open `<q>`s
+    // are his, and the `sID`s of his milestoned ones.
+    let mut red: Option<usize> = None;
+    let mut quotes: Vec<bool> = Vec::new();
+    let mut milestones: Vec<&str> = Vec::new();
+    let mut rest = raw;
+    let push = |target: &Target, buf: &mut String, entry: &mut Entry, text: &str| {
+        let text = if markup == Markup::Plain {
+            text.into()
+        } else {
+            xml::decode(text)
+        };
+        match target {
+            Target::Text => import::push_text(&mut entry.text, &text),
+            Target::Heading(_) | Target::Note(..) => import::push_text(buf, &text),
+            Target::Drop => {}
+        }
+    };
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<').filter(|_| markup != Markup::Plain) else {
+            push(&target, &mut buf, &mut entry, rest);
+            break;
+        };
+        push(&target, &mut buf, &mut entry, &rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        rest = &rest[open + close + 1..];
+        let closing = tag.starts_with('/');
+        let empty = tag.ends_with('/');
+        let body = tag.trim_start_matches('/').trim_end_matches('/');
+        let name = body.split_whitespace().next().unwrap_or_default();
+        // What the tag starts or ends, in this markup.
+        let (note, title, space) = match markup {
+            Markup::Gbf => (
+                match name {
+                    "RF" => Some(true),
+                    "Rf" => Some(false),
+                    _ => None,
+                },
+                match name {
+                    "TS" => Some(true),
+                    "Ts" => Some(false),
+                    _ => None,
+                },
+                matches!(name, "CM" | "CL"),
+            ),
+            _ => {
+                let start = !closing && !empty;
+                (
+                    (name == "note" && !empty).then_some(start),
+                    (name == "title" && !empty).then_some(start),
+                    matches!(name, "lb" | "l" | "lg" | "p" | "br" | "div"),
+                )
+            }
+        };
+        // The paragraph or poetry line (as a USFM style) the tag begins.
+        let begins = !closing && tag_attr(body, "eID").is_none();
+        let paragraph = match (markup, name) {
+            (Markup::Gbf, "CM") => Some("p".to_string()),
+            (Markup::Gbf, _) => None,
+            (_, "p") if begins => Some("p".into()),
+            (_, "div") if begins && tag_attr(body, "type") == Some("paragraph") => Some("p".into()),
+            (_, "milestone") if tag_attr(body, "type") == Some("x-p") => Some("p".into()),
+            (_, "l") if begins => Some(format!("q{}", tag_attr(body, "level").unwrap_or("1"))),
+            _ => None,
+        };
+        if space || paragraph.is_some() {
+            push(&target, &mut buf, &mut entry, " ");
+        }
+        if let (Some(style), Target::Text) = (paragraph, &target) {
+            entry.paragraphs.push((entry.text.chars().count(), style));
+        }
+        if matches!(target, Target::Text) {
+            match (markup, name) {
+                (Markup::Gbf, tag) if tag.starts_with("WH") || tag.starts_with("WG") => {
+                    let strongs = import::strongs_numbers(tag, Some(prefix));
+                    import::tag_last_word(&mut entry.words, &entry.text, strongs, None);
+                }
+                (Markup::Gbf, tag) if tag.starts_with("WT") => {
+                    let code = import::morph_code(&tag[2..]);
+                    import::tag_last_word(&mut entry.words, &entry.text, Vec::new(), code);
+                }
+                (Markup::Gbf, "FR") => red = red.or(Some(entry.text.len())),
+                (Markup::Gbf, "Fr") => {
+                    if let Some(start) = red.take() {
+                        import::red_letter(&mut entry.red_letter, &entry.text, start);
+                    }
+                }
+                (Markup::Gbf, _) => {}
+                // `<q who="Jesus">`, or as milestones `<q who="Jesus"
+                // sID=".."/>` ... `<q eID=".."/>`.
+                (_, "q") => {
+                    let jesus = tag_attr(body, "who") == Some("Jesus");
+                    let ends = match tag_attr(body, "eID") {
+                        _ if closing => quotes.pop().unwrap_or(false),
+                        Some(id) => match milestones.iter().position(|m| *m == id) {
+                            Some(at) => {
+                                milestones.remove(at);
+                                true
+                            }
+                            None => false,
+                        },
+                        None if empty => {
+                            if jesus {
+                                milestones.extend(tag_attr(body, "sID"));
+                            }
+                            false
+                        }
+                        None => {
+                            quotes.push(jesus);
+                            false
+                        }
+                    };
+                    if jesus && !closing && tag_attr(body, "eID").is_none() {
+                        red = red.or(Some(entry.text.len()));
+                    } else if ends && !quotes.contains(&true) && milestones.is_empty() {
+                        if let Some(start) = red.take() {
+                            import::red_letter(&mut entry.red_letter, &entry.text, start);
+                        }
+                    }
+                }
+                (_, "w") if closing => {
+                    if let Some((start, attrs)) = word.take() {
+                        let strongs = import::strongs_numbers(
+                            tag_attr(attrs, "lemma").unwrap_or_default(),
+                            Some(prefix),
+                        );
+                        let gloss = tag_attr(attrs, "gloss").map(str::to_string);
+                        let morph = tag_attr(attrs, "morph").and_then(import::morph_code);
+                        import::tag_word(
+                            &mut entry.words,
+                            &entry.text,
+                            start,
+                            strongs,
+                            gloss,
+                            morph,
+                        );
+                    }
+                }
+                (_, "w") if !empty => word = Some((entry.text.len(), body)),
+                (_, "sync") if tag_attr(body, "type") == Some("Strongs") => {
+                    let strongs = import::strongs_numbers(
+                        tag_attr(body, "value").unwrap_or_default(),
+                        Some(prefix),
+                    );
+                    import::tag_last_word(&mut entry.words, &entry.text, strongs, None);
+                }
+                _ => {}
+            }
+        }
+        match (note, title, &target) {
+            (Some(true), _, Target::Text) => {
+                target = if tag_attr(body, "type") == Some("crossReference") {
+                    Target::Drop
+                } else {
+                    let caller = tag_attr(body, "n").unwrap_or("+").to_string();
+                    Target::Note(caller, import::note_kind(tag_attr(body, "type")))
+                };
+            }
+            (Some(false), _, Target::Note(caller, kind)) => {
+                let text = std::mem::take(&mut buf).trim().to_string();
+                if !text.is_empty() {
+                    let position = entry.text.trim_end().chars().count();
+                    entry
+                        .notes
+                        .push((caller.clone(), position, text, kind.clone()));
+                }
+                target = Target::Text;
+            }
+            (Some(false), _, Target::Drop) => target = Target::Text,
+            (_, Some(true), Target::Text) => {
+                let psalm = tag_attr(body, "canonical") == Some("true")
+                    || tag_attr(body, "type") == Some("psalm");
+                target = Target::Heading(if psalm { "d" } else { "s1" }.into());
+            }
+            (_, Some(false), Target::Heading(style)) => {
+                let text = std::mem::take(&mut buf).trim().to_string();
+                if !text.is_empty() {
+                    entry.headings.push((style.clone(), text));
+                }
+                target = Target::Text;
+            }
+            _ => {}
+        }
+    }
+    // Words of Christ running on into the next verse end with this one.
+    if let Some(start) = red {
+        import::red_letter(&mut entry.red_letter, &entry.text, start);
+    }
+    entry.text.truncate(entry.text.trim_end().len());
+    entry
+}
+
+/// Split off the paragraphs `entry` begins after its last text, which
+/// belong to the start of the next verse (`<CM>` ends a verse's entry).
+fn trailing_paragraphs(entry: &mut Entry) -> Vec<String> {
+    let length = entry.text.chars().count();
+    let at = entry
+        .paragraphs
+        .iter()
+        .position(|(position, _)| length == 0 || *position >= length)
+        .unwrap_or(entry.paragraphs.len());
+    entry
+        .paragraphs
+        .split_off(at)
+        .into_iter()
+        .map(|(_, style)| style)
+        .collect()
+}
+
+/// Read every verse of the module `conf` describes.
+fn read_module(library: &Path, conf: &Conf, warnings: &mut Warnings) -> Result<Vec<Book>, DbError> {
+    if let Some(problem) = problem(library, conf) {
+        return Err(DbError::invalid(format!("{}: {problem}", conf.name)));
+    }
+    let driver = driver(conf)
+        .ok_or_else(|| DbError::invalid(format!("{} is not a Bible module", conf.name)))?;
+    let markup = markup(conf);
+    let latin1 = !conf
+        .get("Encoding")
+        .is_some_and(|e| e.eq_ignore_ascii_case("UTF-8"));
+    let dir = data_dir(library, conf);
+    let file = conf.name.clone();
+
+    let mut books = Vec::new();
+    for (testament, range) in [("ot", 0..39), ("nt", 39..66)] {
+        let Some(mut files) = Testament::open(&dir, testament, driver)? else {
+            continue;
+        };
+        // Entry 0 is the module heading, 1 the testament's.
+        let mut n = 1;
+        for i in range {
+            n += 1;
+            let mut book = Book {
+                id: books::BOOKS[i].osis.to_string(),
+                file: file.clone(),
+                ..Default::default()
+            };
+            // Paragraphs begun after a verse's text, for the next verse.
+            let mut carried: Vec<String> = Vec::new();
+            for (c, &count) in books::KJV_VERSES[i].iter().enumerate() {
+                n += 1;
+                let chapter = c as i64 + 1;
+                for verse in 1..=count as i64 {
+                    n += 1;
+                    let raw = match files.entry(n) {
+                        Ok(Some(raw)) => raw,
+                        Ok(None) => continue,
+                        Err(message) => {
+                            warnings.push(&file, None, format!("{} {chapter}: {message}", book.id));
+                            continue;
+                        }
+                    };
+                    let raw = if latin1 {
+                        raw.iter().map(|&b| b as char).collect()
+                    } else {
+                        String::from_utf8_lossy(raw).into_owned()
+                    };
+                    let mut entry = read_entry(&raw, markup, import::strongs_prefix(&book.id));
+                    let trailing = trailing_paragraphs(&mut entry);
+                    for (style, text) in entry.headings {
+                        book.headings.push(Heading {
+                            chapter,
+                            verse,
+                            style,
+                            text,
+                        });
+                    }
+                    for (caller, position, text, kind) in entry.notes {
+                        book.footnotes.push(Footnote {
+                            chapter,
+                            verse,
+                            caller,
+                            position,
+                            text,
+                            kind,
+                        });
+                    }
+                    if !entry.text.is_empty() {
+                        let paragraphs = std::mem::take(&mut carried)
+                            .into_iter()
+                            .map(|style| (0, style))
+                            .chain(entry.paragraphs)
+                            .collect();
+                        book.verses.push(Verse {
+                            chapter,
+                            verse,
+                            through: verse,
+                            text: entry.text,
+                            words: entry.words,
+                            red_letter: entry.red_letter,
+                            paragraphs,
+                            ..Default::default()
+                        });
+                    }
+                    carried.extend(trailing);
+                }
+            }
+            if !book.verses.is_empty() {
+                books.push(book);
+            }
+        }
+    }
+    Ok(books)
+}
+
+/// A SWORD lexicon keyed by Strong's number (`StrongsGreek`, `Thayer`, ...).
+pub(super) struct SwordLexicon {
+    pub description: String,
+    /// (Strong's number, plain text) of each entry, in index order.
+    pub entries: Vec<(String, String)>,
+    /// Entries whose key isn't a Strong's number, or that only link to
+    /// another entry.
+    pub skipped: usize,
+}
+
+/// Read lexicon `module` of `library`. Only uncompressed lexicons
+/// (`RawLD`, `RawLD4`) are read; their `.idx` holds the offset and size of
+/// each entry in `.dat`, which starts with the entry's key on a line of
+/// its own. Bare numbers take the testament the conf's `Feature` names.
+pub(super) fn read_lexicon(library: &Path, module: &str) -> Result<SwordLexicon, DbError> {
+    let dir = library.join(CONF_DIR);
+    let conf = std::fs::read_dir(&dir)
+        .map_err(|_| {
+            DbError::new(
+                DbErrorKind::NotFound,
+                format!(
+                    "{} is not a SWORD library: no {CONF_DIR}",
+                    library.display()
+                ),
+            )
+        })?
+        .flatten()
+        .filter_map(|entry| std::fs::read(entry.path()).ok())
+        .filter_map(|bytes| parse_conf(&String::from_utf8_lossy(&bytes)))
+        .find(|conf| conf.name.eq_ignore_ascii_case(module))
+        .ok_or_else(|| {
+            DbError::new(
+                DbErrorKind::NotFound,
+                format!("No module `{module}` in {}", library.display()),
+            )
+        })?;
+    let wide = match conf
+        .get("ModDrv")
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "rawld" => false,
+        "rawld4" => true,
+        "zld" => {
+            return Err(DbError::invalid(format!(
+                "{}: compressed lexicons are not supported",
+                conf.name
+            )))
+        }
+        _ => return Err(DbError::invalid(format!("{} is not a lexicon", conf.name))),
+    };
+    if conf.values.contains_key("CipherKey") {
+        return Err(DbError::invalid(format!(
+            "{}: Locked modules are not supported",
+            conf.name
+        )));
+    }
+    let prefix = match conf.get("Feature") {
+        Some("GreekDef") => Some('G'),
+        Some("HebrewDef") => Some('H'),
+        _ => None,
+    };
+    let markup = markup(&conf);
+    let latin1 = !conf
+        .get("Encoding")
+        .is_some_and(|e| e.eq_ignore_ascii_case("UTF-8"));
+    let base = data_dir(library, &conf).display().to_string();
+    let read = |ext: &str| {
+        std::fs::read(format!("{base}.{ext}"))
+            .map_err(|e| DbError::io(format!("Failed to read {}.{ext}: {e}", conf.name)))
+    };
+    let (index, data) = (read("idx")?, read("dat")?);
+
+    let width = if wide { 8 } else { 6 };
+    let mut lexicon = SwordLexicon {
+        description: conf.get("Description").unwrap_or(&conf.name).to_string(),
+        entries: Vec::new(),
+        skipped: 0,
+    };
+    for at in (0..index.len() / width).map(|n| n * width) {
+        let offset = u32_at(&index, at).unwrap_or(0) as usize;
+        let size = if wide {
+            u32_at(&index, at + 4)
+        } else {
+            u16_at(&index, at + 4).map(u32::from)
+        };
+        let Some(raw) = data.get(offset..offset + size.unwrap_or(0) as usize) else {
+            lexicon.skipped += 1;
+            continue;
+        };
+        let raw: String = if latin1 {
+            raw.iter().map(|&b| b as char).collect()
+        } else {
+            String::from_utf8_lossy(raw).into_owned()
+        };
+        let (key, body) = raw.split_once('\n').unwrap_or((&raw, ""));
+        let number = import::strongs_numbers(key.trim(), prefix)
+            .into_iter()
+            .next();
+        match number {
+            Some(number) if !body.trim_start().starts_with("@LINK") => {
+                let text = read_entry(body, markup, prefix.unwrap_or('H')).text;
+                if text.is_empty() {
+                    lexicon.skipped += 1;
+                } else {
+                    lexicon.entries.push((number, text));
+                }
+            }
+            _ => lexicon.skipped += 1,
+        }
+    }
+    Ok(lexicon)
+}
+
+/// The usual SWORD library of this user (`~/.sword`, or `%APPDATA%\Sword`
+/// on Windows).
+fn default_library(app: &tauri::AppHandle) -> Result<PathBuf, DbError> {
+    let path = if cfg!(windows) {
+        app.path().data_dir().map(|dir| dir.join("Sword"))
+    } else {
+        app.path().home_dir().map(|dir| dir.join(".sword"))
+    };
+    path.map_err(|e| DbError::io(format!("Cannot determine the SWORD library: {e}")))
+}
+
+/// Scan a SWORD library (by default the user's own) and remember it for
+/// `list_sword_modules`. Returns its Bible modules.
+#[command]
+pub fn scan_sword_library(
+    app: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<Vec<SwordModule>, DbError> {
+    let library = match path {
+        Some(path) => PathBuf::from(path),
+        None => default_library(&app)?,
+    };
+    let modules = scan(&library)?.into_iter().map(|(m, _)| m).collect();
+    let mut libraries = libraries();
+    if !libraries.contains(&library) {
+        libraries.push(library);
+    }
+    Ok(modules)
+}
+
+/// The Bible modules of every library scanned this session. Libraries that
+/// have since gone away are skipped.
+#[command]
+pub fn list_sword_modules() -> Vec<SwordModule> {
+    let scanned = libraries().clone();
+    scanned
+        .iter()
+        .filter_map(|library| scan(library).ok())
+        .flatten()
+        .map(|(m, _)| m)
+        .collect()
+}
+
+/// Import SWORD modules from `library` as translations named after them,
+/// replacing their content files. `content://import-progress` reports each
+/// module as it's read.
+#[command]
+pub async fn import_sword_modules(
+    app: tauri::AppHandle,
+    library: String,
+    modules: Vec<String>,
+) -> Result<Vec<ImportReport>, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let library = PathBuf::from(library);
+        let mut found = scan(&library)?;
+        let mut reports = Vec::new();
+        for (i, name) in modules.iter().enumerate() {
+            let at = found
+                .iter()
+                .position(|(m, _)| m.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    DbError::new(
+                        DbErrorKind::NotFound,
+                        format!("No module `{name}` in {}", library.display()),
+                    )
+                })?;
+            let (module, conf) = found.swap_remove(at);
+            let mut warnings = Warnings::default();
+            let books = read_module(&library, &conf, &mut warnings)?;
+            import::progress(&app, &module.name, &module.name, i + 1, modules.len());
+            let target = content_path(&app, &module.name)?;
+            let mut info = vec![("versification", module.versification.as_str())];
+            if let Some(language) = &module.language {
+                info.push(("language", language));
+            }
+            if let Some(version) = &module.version {
+                info.push(("version", version));
+            }
+            if let Some(license) = conf.get("DistributionLicense") {
+                info.push(("license", license));
+            }
+            if let Some(copyright) = conf.get("ShortCopyright").or(conf.get("Copyright")) {
+                info.push(("copyright", copyright));
+            }
+            let report = import::write(
+                &target,
+                &module.name,
+                &module.description,
+                "sword",
+                &info,
+                books,
+                warnings,
+            )?;
+            import::finish(&app, &report)?;
+            reports.push(report);
+        }
+        Ok(reports)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    /// Index entry of `book` `chapter`:`verse` (KJV layout) in its testament.
+    fn entry_number(book: usize, chapter: usize, verse: usize) -> usize {
+        let first = if book < 39 { 0 } else { 39 };
+        let mut n = 1;
+        for b in first..book {
+            n += 1 + books::KJV_VERSES[b].len();
+            n += books::KJV_VERSES[b]
+                .iter()
+                .map(|&v| v as usize)
+                .sum::<usize>();
+        }
+        n += 1;
+        for c in 0..chapter - 1 {
+            n += 1 + books::KJV_VERSES[book][c] as usize;
+        }
+        n + 1 + verse
+    }
+
+    #[test]
+    fn genesis_starts_after_the_headings() {
+        assert_eq!(entry_number(0, 1, 1), 4);
+        assert_eq!(entry_number(39, 1, 1), 4, "Matthew starts the NT file");
+    }
+
+    #[test]
+    fn reads_the_words_of_christ() {
+        let osis = read_entry(
+            r#"Jesus said, <q who="Jesus" marker="">Follow <q who="Peter">me?</q> me.</q> And they did."#,
+            Markup::Osis,
+            'G',
+        );
+        assert_eq!(osis.text, "Jesus said, Follow me? me. And they did.");
+        assert_eq!(osis.red_letter, [(12, 26)]);
+        let milestones = read_entry(
+            r#"He said, <q who="Jesus" sID="q1"/>Come, <q sID="q2" who="Peter"/>see<q eID="q2"/>.<q eID="q1"/> So"#,
+            Markup::Osis,
+            'G',
+        );
+        assert_eq!(milestones.red_letter, [(9, 19)]);
+        let gbf = read_entry("And he said, <FR>It is finished.<Fr>", Markup::Gbf, 'G');
+        assert_eq!(gbf.red_letter, [(13, 28)]);
+    }
+
+    #[test]
+    fn reads_paragraphs_and_poetry_lines() {
+        let mut osis = read_entry(
+            r#"<milestone marker="¶" type="x-p"/>The LORD is my shepherd;<lg><l level="1">I shall not want.</l><l level="2">He maketh me</l></lg><p eID="p1"/>"#,
+            Markup::Osis,
+            'H',
+        );
+        assert_eq!(
+            osis.text,
+            "The LORD is my shepherd; I shall not want. He maketh me"
+        );
+        assert_eq!(
+            osis.paragraphs,
+            [(0, "p".into()), (25, "q1".into()), (43, "q2".into())]
+        );
+        assert!(trailing_paragraphs(&mut osis).is_empty());
+        let mut gbf = read_entry("And it was so.<CM>", Markup::Gbf, 'H');
+        assert_eq!(trailing_paragraphs(&mut gbf), ["p"]);
+        assert!(gbf.paragraphs.is_empty());
+    }
+
+    fn library(name: &str) -> ScratchDir {
+        let dir = ScratchDir::new(&format!("sword-{name}"));
+        std::fs::create_dir_all(dir.join(CONF_DIR)).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reads_a_ztext_module() {
+        let lib = library("ztext");
+        std::fs::write(
+            lib.join(CONF_DIR).join("test.conf"),
+            "[TestKJV]\nDataPath=./modules/texts/ztext/testkjv/\nModDrv=zText\n\
+             SourceType=OSIS\nEncoding=UTF-8\nDescription=Test \\\n King James\n\
+             CompressType=ZIP\nLang=en\n",
+        )
+        .unwrap();
+        std::fs::write(
+            lib.join(CONF_DIR).join("dict.conf"),
+            "[Dict]\nModDrv=RawLD\n",
+        )
+        .unwrap();
+        let dir = lib.join("modules/texts/ztext/testkjv");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // John 1:1-2 in one compressed block of the NT.
+        let verses = [
+            r#"<title type="section" subType="x-preverse">The Word</title>In the beginning was <w lemma="strong:G3056" morph="robinson:N-NSM">the Word</w>,<note type="study" n="a">Or, <hi type="italic">Logos</hi></note> and the Word was with God."#,
+            r#"The same was in the beginning with God.<note type="crossReference"><reference>Gen 1:1</reference></note>"#,
+        ];
+        let block: String = verses.concat();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(block.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut bzs = Vec::new();
+        for n in [0u32, compressed.len() as u32, block.len() as u32] {
+            bzs.extend(n.to_le_bytes());
+        }
+        let first = entry_number(42, 1, 1);
+        let mut bzv = vec![0u8; (first + 2) * 10];
+        let mut start = 0u32;
+        for (i, verse) in verses.iter().enumerate() {
+            let at = (first + i) * 10;
+            bzv[at..at + 4].copy_from_slice(&0u32.to_le_bytes());
+            bzv[at + 4..at + 8].copy_from_slice(&start.to_le_bytes());
+            bzv[at + 8..at + 10].copy_from_slice(&(verse.len() as u16).to_le_bytes());
+            start += verse.len() as u32;
+        }
+        std::fs::write(dir.join("nt.bzs"), bzs).unwrap();
+        std::fs::write(dir.join("nt.bzv"), bzv).unwrap();
+        std::fs::write(dir.join("nt.bzz"), compressed).unwrap();
+
+        let modules = scan(&lib).unwrap();
+        assert_eq!(modules.len(), 1, "dictionaries are left out");
+        let (module, conf) = &modules[0];
+        assert_eq!(
+            (module.name.as_str(), module.description.as_str()),
+            ("TestKJV", "Test King James")
+        );
+        assert_eq!(module.problem, None);
+
+        let mut warnings = Warnings::default();
+        let books = read_module(&lib, conf, &mut warnings).unwrap();
+        assert!(warnings.list.is_empty(), "{:?}", warnings.list);
+        assert_eq!(books.len(), 1);
+        let john = &books[0];
+        assert_eq!(john.id, "John");
+        assert_eq!(
+            john.verses[0].text,
+            "In the beginning was the Word, and the Word was with God."
+        );
+        assert_eq!(
+            john.verses[1].text,
+            "The same was in the beginning with God."
+        );
+        assert_eq!(john.headings[0].text, "The Word");
+        assert_eq!(john.footnotes.len(), 1);
+        let note = &john.footnotes[0];
+        assert_eq!(
+            (note.caller.as_str(), note.text.as_str(), note.kind.as_str()),
+            ("a", "Or, Logos", "study")
+        );
+        assert_eq!(
+            &john.verses[0].text[..note.position],
+            "In the beginning was the Word,"
+        );
+        let word = &john.verses[0].words[0];
+        assert_eq!(
+            (word.position, word.text.as_str(), word.strongs.as_slice()),
+            (21, "the Word", ["G3056".to_string()].as_slice())
+        );
+        assert_eq!(word.morph.as_deref(), Some("N-NSM"));
+    }
+
+    #[test]
+    fn reads_a_strongs_keyed_lexicon() {
+        let lib = library("lexicon");
+        std::fs::write(
+            lib.join(CONF_DIR).join("greek.conf"),
+            "[StrongsGreek]\nDataPath=./modules/lexdict/rawld4/strongsgreek/strongsgreek\n\
+             ModDrv=RawLD4\nSourceType=TEI\nEncoding=UTF-8\nFeature=GreekDef\n\
+             Description=Strong's Greek Bible Dictionary\n",
+        )
+        .unwrap();
+        let dir = lib.join("modules/lexdict/rawld4/strongsgreek");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut idx = Vec::new();
+        let mut dat = Vec::new();
+        for entry in [
+            "03056\n<entryFree n=\"03056\"><orth>λόγος</orth> something said</entryFree>",
+            "03057\n@LINK 03056",
+            "PREFACE\nAbout this dictionary",
+        ] {
+            idx.extend((dat.len() as u32).to_le_bytes());
+            idx.extend((entry.len() as u32).to_le_bytes());
+            dat.extend(entry.as_bytes());
+        }
+        std::fs::write(dir.join("strongsgreek.idx"), idx).unwrap();
+        std::fs::write(dir.join("strongsgreek.dat"), dat).unwrap();
+
+        let lexicon = read_lexicon(&lib, "strongsgreek").unwrap();
+        assert_eq!(lexicon.description, "Strong's Greek Bible Dictionary");
+        assert_eq!(
+            lexicon.entries,
+            [("G3056".to_string(), "λόγος something said".to_string())]
+        );
+        assert_eq!(lexicon.skipped, 2);
+        assert_eq!(
+            read_lexicon(&lib, "missing").err().map(|e| e.kind),
+            Some(DbErrorKind::NotFound)
+        );
+    }
+
+    #[test]
+    fn reads_raw_text_and_flags_what_it_cannot_import() {
+        let lib = library("raw");
+        std::fs::write(
+            lib.join(CONF_DIR).join("raw.conf"),
+            "[Raw]\nDataPath=./modules/texts/rawtext/raw/\nModDrv=RawText\nSourceType=GBF\n",
+        )
+        .unwrap();
+        std::fs::write(
+            lib.join(CONF_DIR).join("nrsv.conf"),
+            "[Other]\nDataPath=./modules/texts/rawtext/raw/\nModDrv=RawText\nVersification=NRSV\n",
+        )
+        .unwrap();
+        let dir = lib.join("modules/texts/rawtext/raw");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Latin-1, since the conf names no encoding.
+        let verse =
+            b"In the beginning God created<WH1254><RF>Heb. bara<Rf> the heaven \xe2nd the earth.";
+        let n = entry_number(0, 1, 1);
+        let mut vss = vec![0u8; (n + 1) * 6];
+        vss[n * 6 + 4..n * 6 + 6].copy_from_slice(&(verse.len() as u16).to_le_bytes());
+        std::fs::write(dir.join("ot.vss"), vss).unwrap();
+        std::fs::write(dir.join("ot"), verse).unwrap();
+
+        let modules = scan(&lib).unwrap();
+        assert_eq!(
+            modules[0].0.problem.as_deref(),
+            Some("NRSV versification is not supported")
+        );
+        let (_, conf) = &modules[1];
+        let books = read_module(&lib, conf, &mut Warnings::default()).unwrap();
+        assert_eq!(
+            books[0].verses[0].text,
+            "In the beginning God created the heaven \u{e2}nd the earth."
+        );
+        assert_eq!(books[0].footnotes[0].text, "Heb. bara");
+        assert_eq!(books[0].verses[0].words[0].strongs, ["H1254"]);
+    }
+}
@@ -0,0 +1,382 @@
+//! theWord (`.ont`, `.ot`, `.nt`) and MySword (`.bbl.mybible`) Bible modules.
+//!
+//! A theWord Bible is UTF-8 text with one line per verse in KJV order: all
+//! 31,102 verses in an `.ont`, the Old or New Testament alone in an `.ot` or
+//! `.nt`. Empty lines are verses the module lacks. Lines after the verses
+//! are `key=value` properties describing the module. MySword modules are
+//! SQLite files laid out like e-Sword's (see `esword`) with theWord markup in
+//! their verses.
+//!
+//! theWord markup is GBF-like: `<FI>...<Fi>` marks added words, `<FR>...<Fr>`
+//! the words of Christ, `<TS>...<Ts>` a title before the verse and
+//! `<RF>...<Rf>` a footnote. Strong's tags (`<WH7225>`) and morphology tags
+//! (`<WTN-NSF>`) tag the word before them; cross references and formatting
+//! other than styles are dropped. The few HTML tags modules use (`<i>`,
+//! `<b>`, `<sup>`, `<br/>`) are read too.
+
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::command;
+
+use super::books;
+use super::esword::{self, Format};
+use super::import::{self, Book, Footnote, Heading, ImportReport, Styled, Verse, Warnings, Word};
+use super::xml;
+use crate::db::{DbError, DbErrorKind};
+
+/// MySword's tables are e-Sword's; only the verse markup differs.
+const MYSWORD: Format = Format {
+    source_format: "mysword",
+    label: "MySword",
+    add: add_verse,
+    plain: |src| read_verse(src).text,
+};
+
+/// One verse's markup, read.
+#[derive(Debug, Default)]
+struct Read {
+    text: String,
+    styled: Styled,
+    headings: Vec<String>,
+    /// (caller, position, text) of its footnotes.
+    notes: Vec<(String, usize, String)>,
+    words: Vec<Word>,
+}
+
+/// The (GBF tag opening it, closing tag, character style) of each style.
+const STYLES: &[(&str, &str, &str)] = &[
+    ("FI", "Fi", "add"),
+    ("FR", "Fr", "wj"),
+    ("FO", "Fo", "qt"),
+    ("FB", "Fb", "bd"),
+    ("i", "/i", "it"),
+    ("b", "/b", "bd"),
+    ("sup", "/sup", "sup"),
+];
+
+fn read_verse(raw: &str) -> Read {
+    enum Target {
+        Text,
+        Heading,
+        Note(String),
+    }
+    let mut read = Read::default();
+    let mut target = Target::Text;
+    let mut chars: Vec<&str> = Vec::new();
+    let mut buf = String::new();
+    let mut rest = raw;
+    let push = |target: &Target, chars: &[&str], buf: &mut String, read: &mut Read, text: &str| {
+        let text = xml::decode(text);
+        match target {
+            Target::Text => {
+                let start = read.text.len();
+                import::push_text(&mut read.text, &text);
+                read.styled.push("p", chars, &read.text[start..]);
+            }
+            Target::Heading | Target::Note(_) => import::push_text(buf, &text),
+        }
+    };
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            push(&target, &chars, &mut buf, &mut read, rest);
+            break;
+        };
+        push(&target, &chars, &mut buf, &mut read, &rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            break;
+        };
+        let tag = rest[open + 1..open + close].trim_end_matches('/').trim();
+        rest = &rest[open + close + 1..];
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '=')
+            .next()
+            .unwrap_or_default();
+
+        if let Some(&(_, _, style)) = STYLES.iter().find(|(start, _, _)| *start == name) {
+            chars.push(style);
+            continue;
+        }
+        if let Some(&(_, _, style)) = STYLES.iter().find(|(_, end, _)| *end == name) {
+            if let Some(at) = chars.iter().rposition(|c| *c == style) {
+                chars.remove(at);
+            }
+            continue;
+        }
+        match (name, &target) {
+            ("CM" | "CL" | "br" | "p" | "/p", _) => {
+                push(&target, &chars, &mut buf, &mut read, " ");
+            }
+            (strongs, Target::Text) if strongs.starts_with("WH") || strongs.starts_with("WG") => {
+                let numbers = import::strongs_numbers(strongs, None);
+                import::tag_last_word(&mut read.words, &read.text, numbers, None);
+            }
+            (morph, Target::Text) if morph.starts_with("WT") => {
+                let code = import::morph_code(&morph[2..]);
+                import::tag_last_word(&mut read.words, &read.text, Vec::new(), code);
+            }
+            ("TS", Target::Text) => target = Target::Heading,
+            ("Ts", Target::Heading) => {
+                let text = std::mem::take(&mut buf).trim().to_string();
+                if !text.is_empty() {
+                    read.headings.push(text);
+                }
+                target = Target::Text;
+            }
+            ("RF", Target::Text) => {
+                let caller = tag
+                    .split_whitespace()
+                    .find_map(|part| part.strip_prefix("q="))
+                    .map(|q| q.trim_matches(|c| c == '"' || c == '\''))
+                    .filter(|q| !q.is_empty())
+                    .unwrap_or("+");
+                target = Target::Note(caller.to_string());
+            }
+            ("Rf", Target::Note(caller)) => {
+                let text = std::mem::take(&mut buf).trim().to_string();
+                if !text.is_empty() {
+                    let position = read.text.trim_end().chars().count();
+                    read.notes.push((caller.clone(), position, text));
+                }
+                target = Target::Text;
+            }
+            _ => {}
+        }
+    }
+    read.text.truncate(read.text.trim_end().len());
+    read.styled.trim_end();
+    read
+}
+
+fn add_verse(book: &mut Book, chapter: i64, verse: i64, raw: &str) {
+    let read = read_verse(raw);
+    for text in read.headings {
+        book.headings.push(Heading {
+            chapter,
+            verse,
+            style: "s1".to_string(),
+            text,
+        });
+    }
+    for (caller, position, text) in read.notes {
+        book.footnotes.push(Footnote {
+            chapter,
+            verse,
+            caller,
+            position,
+            text,
+            kind: import::note_kind(None),
+        });
+    }
+    book.verses.push(Verse {
+        chapter,
+        verse,
+        through: verse,
+        text: read.text,
+        html: read.styled.html(),
+        words: read.words,
+        alignments: Vec::new(),
+        red_letter: read.styled.spans("wj"),
+        paragraphs: Vec::new(),
+        line: None,
+    });
+}
+
+/// The books a theWord file covers, from its extension.
+fn testament(file: &str) -> Option<std::ops::Range<usize>> {
+    let ext = file.rsplit('.').next()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "ont" => Some(0..66),
+        "ot" => Some(0..39),
+        "nt" => Some(39..66),
+        _ => None,
+    }
+}
+
+/// The books of a theWord file and the properties after its verses.
+fn parse(src: &str, file: &str, warnings: &mut Warnings) -> (Vec<Book>, HashMap<String, String>) {
+    let Some(range) = testament(file) else {
+        warnings.push(file, None, "Not a theWord Bible (.ont, .ot or .nt)");
+        return (Vec::new(), HashMap::new());
+    };
+    let mut lines = src.lines().enumerate();
+    let mut books = Vec::new();
+    'books: for index in range {
+        let id = &books::BOOKS[index];
+        let mut book = Book {
+            id: id.osis.to_string(),
+            file: file.to_string(),
+            ..Default::default()
+        };
+        for (chapter, &count) in books::KJV_VERSES[index].iter().enumerate() {
+            for verse in 1..=count as i64 {
+                let Some((at, line)) = lines.next() else {
+                    warnings.push(
+                        file,
+                        None,
+                        format!("The file ends at {} {}:{verse}", id.osis, chapter + 1),
+                    );
+                    if !book.verses.is_empty() {
+                        books.push(book);
+                    }
+                    break 'books;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                add_verse(&mut book, chapter as i64 + 1, verse, line);
+                if let Some(v) = book.verses.last_mut() {
+                    v.line = Some(at + 1);
+                }
+            }
+        }
+        if !book.verses.is_empty() {
+            books.push(book);
+        }
+    }
+    let properties = lines
+        .filter_map(|(_, line)| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_lowercase(), value.trim().to_string()))
+        .filter(|(key, value)| !key.is_empty() && !value.is_empty() && !key.starts_with('#'))
+        .collect();
+    (books, properties)
+}
+
+/// Import a theWord Bible and mount it. The translation id defaults to the
+/// module's short title and its name to its description.
+#[command]
+pub async fn import_theword(
+    app: tauri::AppHandle,
+    path: String,
+    module_id: Option<String>,
+    name: Option<String>,
+) -> Result<ImportReport, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&path);
+        let file = import::file_label(path);
+        if testament(&file).is_none() {
+            return Err(DbError::invalid(format!(
+                "{file} is not a theWord Bible (.ont, .ot or .nt)"
+            )));
+        }
+        let mut warnings = Warnings::default();
+        let src = import::read_text(path, &mut warnings)
+            .ok_or_else(|| DbError::io(format!("Could not read {file}")))?;
+        let (books, properties) = parse(&src, &file, &mut warnings);
+        let property = |key: &str| properties.get(key).map(String::as_str);
+        let module_id = module_id
+            .or_else(|| property("short.title").map(str::to_string))
+            .unwrap_or_else(|| file.split('.').next().unwrap_or_default().to_string());
+        import::progress(&app, &module_id, &file, 1, 1);
+        let name = name
+            .or_else(|| property("description").map(str::to_string))
+            .unwrap_or_else(|| module_id.clone());
+        let version = match (property("version.major"), property("version.minor")) {
+            (Some(major), Some(minor)) => Some(format!("{major}.{minor}")),
+            (major, _) => major.map(str::to_string),
+        };
+        let mut info: Vec<(&str, &str)> = Vec::new();
+        if let Some(language) = property("lang") {
+            info.push(("language", language));
+        }
+        if let Some(version) = &version {
+            info.push(("version", version));
+        }
+        if let Some(about) = property("about") {
+            info.push(("comments", about));
+        }
+        if property("r2l") == Some("1") {
+            info.push(("direction", "rtl"));
+        }
+        import::install(&app, &module_id, &name, "theword", &info, books, warnings)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+/// Import a MySword `.bbl.mybible` Bible and mount it. The translation id
+/// defaults to the module's abbreviation and its name to its title.
+#[command]
+pub async fn import_mysword(
+    app: tauri::AppHandle,
+    path: String,
+    module_id: Option<String>,
+    name: Option<String>,
+) -> Result<ImportReport, DbError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        esword::import_module(&app, Path::new(&path), module_id, name, &MYSWORD)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Import task failed: {e}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_theword_markup() {
+        let read = read_verse(
+            "<TS>The Word Became Flesh<Ts>In the beginning<WG1722> was<WG2258><WTV-IAI-3S> <WG2258> the Word,<RF q=a>Or, Logos<Rf> \
+             and <FI>the Word<Fi> was <FR>with God &amp; <i>God</i><Fr>.<CM>",
+        );
+        assert_eq!(read.headings, ["The Word Became Flesh"]);
+        assert_eq!(
+            read.text,
+            "In the beginning was the Word, and the Word was with God & God."
+        );
+        assert_eq!(read.notes, [("a".to_string(), 30, "Or, Logos".to_string())]);
+        let tagged: Vec<(usize, &str, Vec<String>)> = read
+            .words
+            .iter()
+            .map(|w| (w.position, w.text.as_str(), w.strongs.clone()))
+            .collect();
+        assert_eq!(
+            tagged,
+            [
+                (7, "beginning", vec!["G1722".to_string()]),
+                (17, "was", vec!["G2258".to_string()])
+            ]
+        );
+        assert_eq!(read.words[1].morph.as_deref(), Some("V-IAI-3S"));
+        assert_eq!(
+            read.styled.html().unwrap(),
+            "In the beginning was the Word, and <span class=\"char-add\">the Word</span> was \
+             <span class=\"char-wj\">with God &amp; </span><span class=\"char-wj\"><span \
+             class=\"char-it\">God</span></span>."
+        );
+    }
+
+    #[test]
+    fn lays_out_theword_lines_in_kjv_order() {
+        let mut src = String::new();
+        // Matthew 1 has 25 verses; verse 2 is missing.
+        for verse in 1..=25 {
+            if verse != 2 {
+                src.push_str(&format!("Matthew 1:{verse}"));
+            }
+            src.push('\n');
+        }
+        src.push_str("Matthew 2:1\n");
+        let mut warnings = Warnings::default();
+        let (books, properties) = parse(&src, "web.nt", &mut warnings);
+        assert_eq!(warnings.list.len(), 1, "{:?}", warnings.list);
+        assert!(warnings.list[0].message.contains("Matt 2:2"));
+        assert!(properties.is_empty());
+        let matthew = &books[0];
+        assert_eq!(matthew.id, "Matt");
+        assert_eq!((matthew.verses[1].chapter, matthew.verses[1].verse), (1, 3));
+        assert_eq!(matthew.verses[1].line, Some(3));
+        assert_eq!(matthew.verses.last().unwrap().text, "Matthew 2:1");
+
+        // A full file ends in its properties.
+        let mut src = "v\n".repeat(31_102);
+        src.push_str("short.title=WEB\ndescription=World English Bible\nlang=en\n");
+        let (books, properties) = parse(&src, "web.ont", &mut warnings);
+        assert_eq!(books.len(), 66);
+        assert_eq!(
+            properties.get("short.title").map(String::as_str),
+            Some("WEB")
+        );
+        assert_eq!(properties.get("lang").map(String::as_str), Some("en"));
+    }
+}
@@ -0,0 +1,1012 @@
+//! USFM import.
+//!
+//! USFM is the backslash markup most Bible translations are kept in, one
+//! book per file (`\id GEN`). The parser keeps what BibleMarker shows —
+//! verse text, section headings, where paragraphs and poetry lines begin,
+//! footnotes, the Strong's numbers and
+//! morphology of `\w grace|strong="G5485" x-morph="..."\w*` and the
+//! original-language words aligned USFM links to its text
+//! (`\zaln-s |x-strong="G3056" x-content="λόγος"\*\w Word\w*\zaln-e\*`) —
+//! and drops introductions, cross references, figures and other word-level
+//! attributes. Markers it doesn't know are read as plain text, with a
+//! warning.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tauri::command;
+
+use super::books;
+use super::import::{self, Alignment, Book, Footnote, Heading, ImportReport, Verse, Warnings};
+use super::sword::tag_attr;
+use crate::db::DbError;
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    /// `\name`, or `\name*` when `closing`. Nested character markers
+    /// (`\+nd`) come without the `+`.
+    Marker {
+        name: &'a str,
+        closing: bool,
+        line: usize,
+    },
+    Text(&'a str),
+}
+
+fn tokenize(src: &str) -> Vec<Token<'_>> {
+    let bytes = src.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            let start = i + 1;
+            let mut end = start;
+            while end < bytes.len()
+                && (bytes[end].is_ascii_alphanumeric() || b"+-".contains(&bytes[end]))
+            {
+                end += 1;
+            }
+            let name = src[start..end].trim_start_matches('+');
+            let closing = bytes.get(end) == Some(&b'*');
+            i = if closing { end + 1 } else { end };
+            tokens.push(Token::Marker {
+                name,
+                closing,
+                line,
+            });
+            // One space (or line break) ends an opening marker.
+            if !closing && i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                if bytes[i] == b'\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+        } else {
+            let start = i;
+            while i < bytes.len() && bytes[i] != b'\\' {
+                if bytes[i] == b'\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            tokens.push(Token::Text(&src[start..i]));
+        }
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Kind {
+    Id,
+    Chapter,
+    Verse,
+    /// Book name, by preference: `\h` 0, `\toc2` 1, `\toc1` 2.
+    Name(usize),
+    Heading,
+    /// A paragraph whose text is dropped (introductions, titles, remarks).
+    Ignore,
+    /// A paragraph of verse text (`\p`, `\q1`, `\m`, ...).
+    Body,
+    Footnote,
+    CrossRef,
+    /// Part of a note: `true` for text kept in the footnote.
+    NotePart(bool),
+    /// Inline style whose text is kept (`\nd`, `\wj`, `\w`, ...).
+    Char,
+    /// Inline span whose text is dropped (`\fig`, `\va`, ...).
+    Skip,
+    Milestone,
+    Unknown,
+}
+
+const HEADINGS: &[&str] = &["s", "ms", "d", "sp", "qa"];
+const IGNORED: &[&str] = &[
+    "rem", "ide", "sts", "usfm", "toc", "toca", "mt", "mte", "imt", "imte", "is", "ip", "ipi",
+    "ipq", "ipr", "im", "imi", "imq", "iq", "ib", "ili", "iot", "io", "iex", "ie", "mr", "sr", "r",
+    "cl", "cd", "cp", "sd", "lit", "periph", "restore",
+];
+const BODY: &[&str] = &[
+    "p", "m", "po", "pr", "cls", "pmo", "pm", "pmc", "pmr", "pi", "mi", "nb", "pc", "ph", "li",
+    "lh", "lf", "lim", "q", "qr", "qc", "qm", "qd", "b", "pb", "tr", "th", "thr", "tc", "tcr",
+];
+/// Body markers that don't begin a paragraph of their own: `\nb` carries
+/// the last one on, and table rows and cells are read as prose.
+const NO_BREAK: &[&str] = &["nb", "tr", "th", "thr", "tc", "tcr"];
+const NOTE_TEXT: &[&str] = &["ft", "fq", "fqa", "fk", "fl", "fw", "fp", "fv", "fdc", "fm"];
+const NOTE_DROPPED: &[&str] = &[
+    "fr", "xo", "xt", "xk", "xq", "xta", "xop", "xot", "xnt", "xdc",
+];
+const CHARS: &[&str] = &[
+    "add", "bk", "dc", "k", "nd", "ord", "pn", "png", "addpn", "qt", "sig", "sls", "tl", "wj",
+    "em", "bd", "it", "bdit", "no", "sc", "sup", "w", "wg", "wh", "wa", "rb", "qs", "qac", "lik",
+    "liv", "jmp", "litl", "ndx", "pro", "ior", "iqt",
+];
+const SKIPPED: &[&str] = &["fig", "ca", "va", "vp", "rq", "cat"];
+
+/// `\id` codes of peripheral books (front matter, glossary, ...), skipped
+/// without a warning.
+pub(super) const PERIPHERALS: &[&str] = &[
+    "FRT", "BAK", "OTH", "INT", "CNC", "GLO", "TDX", "NDX", "TOA", "XXA", "XXB", "XXC", "XXD",
+    "XXE", "XXF", "XXG",
+];
+
+pub(super) fn kind(name: &str) -> Kind {
+    if name.ends_with("-s") || name.ends_with("-e") || name == "ts" {
+        return Kind::Milestone;
+    }
+    match name {
+        "id" => return Kind::Id,
+        "c" => return Kind::Chapter,
+        "v" => return Kind::Verse,
+        "h" => return Kind::Name(0),
+        "toc2" => return Kind::Name(1),
+        "toc1" => return Kind::Name(2),
+        "f" | "fe" | "ef" => return Kind::Footnote,
+        "x" | "ex" => return Kind::CrossRef,
+        _ => {}
+    }
+    let base = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if CHARS.contains(&base) {
+        Kind::Char
+    } else if SKIPPED.contains(&base) {
+        Kind::Skip
+    } else if NOTE_TEXT.contains(&base) {
+        Kind::NotePart(true)
+    } else if NOTE_DROPPED.contains(&base) {
+        Kind::NotePart(false)
+    } else if HEADINGS.contains(&base) {
+        Kind::Heading
+    } else if BODY.contains(&base) {
+        Kind::Body
+    } else if IGNORED.contains(&base) {
+        Kind::Ignore
+    } else {
+        Kind::Unknown
+    }
+}
+
+/// Append `text` like `import::push_text`. `~` is USFM's no-break space
+/// and `//` an optional line break.
+fn push_text(buf: &mut String, text: &str) {
+    import::push_text(buf, &text.replace("//", " ").replace('~', "\u{a0}"));
+}
+
+/// unfoldingWord's Greek texts give Strong's numbers an extra digit
+/// (`G17220` for G1722); drop it.
+fn padded_strongs(raw: &str) -> String {
+    raw.split_whitespace()
+        .map(|part| match part.strip_prefix('G') {
+            Some(digits) if digits.len() == 5 && digits.bytes().all(|b| b.is_ascii_digit()) => {
+                format!("G{}", &digits[..4])
+            }
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Para {
+    Body,
+    Ignore,
+    Id,
+    Chapter,
+    Heading,
+    Name(usize),
+}
+
+struct Note {
+    caller: Option<String>,
+    /// See `import::note_kind`.
+    kind: String,
+    text: String,
+    keep_text: bool,
+    /// Where it goes: (verse index, offset), or `None` to drop it.
+    anchor: Option<(usize, usize)>,
+    line: usize,
+}
+
+/// An open `\w`.
+struct WordSpan {
+    /// Byte offset of the word in the open verse's text.
+    start: usize,
+    /// `chars` inside it, to match its closing marker.
+    depth: usize,
+    /// What follows the `|`.
+    attrs: String,
+}
+
+/// An open `\zaln-s`.
+struct AlignSpan {
+    /// Byte offset of the aligned text in the open verse's text.
+    start: usize,
+    /// Alignments of the verse before it.
+    seq: usize,
+    attrs: String,
+}
+
+struct Parser<'w> {
+    file: String,
+    warnings: &'w mut Warnings,
+    books: Vec<Book>,
+    /// Index of the book being read in `books`; `None` before `\id` and in
+    /// skipped books.
+    book: Option<usize>,
+    names: [Option<String>; 3],
+    chapter: Option<i64>,
+    /// Index of the open verse in the book.
+    verse: Option<usize>,
+    para: Para,
+    style: String,
+    buf: String,
+    line: usize,
+    pending: Vec<(String, String)>,
+    /// Paragraph styles begun since the last verse text; they are placed
+    /// where text or a verse next comes.
+    breaks: Vec<String>,
+    verse_number: bool,
+    note: Option<Note>,
+    in_xref: bool,
+    skip: usize,
+    chars: usize,
+    attrs: bool,
+    word: Option<WordSpan>,
+    /// Attributes of the `\zaln-s` being read, until its `\*`.
+    aligning: Option<String>,
+    /// Open alignments, innermost last; `None` for ones outside verse text.
+    alignments: Vec<Option<AlignSpan>>,
+    /// Inside `\wj`, and where in the open verse's text it (or the part in
+    /// this verse) began.
+    in_wj: bool,
+    red: Option<usize>,
+    milestone: bool,
+    stray_text: bool,
+    unknown: HashSet<String>,
+}
+
+impl<'w> Parser<'w> {
+    fn new(file: &str, warnings: &'w mut Warnings) -> Self {
+        Self {
+            file: file.to_string(),
+            warnings,
+            books: Vec::new(),
+            book: None,
+            names: [None, None, None],
+            chapter: None,
+            verse: None,
+            para: Para::Ignore,
+            style: String::new(),
+            buf: String::new(),
+            line: 1,
+            pending: Vec::new(),
+            breaks: Vec::new(),
+            verse_number: false,
+            note: None,
+            in_xref: false,
+            skip: 0,
+            chars: 0,
+            attrs: false,
+            word: None,
+            aligning: None,
+            alignments: Vec::new(),
+            in_wj: false,
+            red: None,
+            milestone: false,
+            stray_text: false,
+            unknown: HashSet::new(),
+        }
+    }
+
+    fn warn(&mut self, message: impl Into<String>) {
+        let line = Some(self.line);
+        self.warnings.push(&self.file, line, message);
+    }
+
+    fn current(&mut self) -> Option<&mut Book> {
+        self.book.map(|i| &mut self.books[i])
+    }
+
+    /// Finish the paragraph being collected, if it's one that collects text.
+    fn end_para(&mut self) {
+        let text = std::mem::take(&mut self.buf).trim().to_string();
+        match self.para {
+            Para::Id => self.start_book(&text),
+            Para::Chapter => {
+                let number = text.split_whitespace().next().unwrap_or_default();
+                match number.parse::<i64>() {
+                    Ok(n) if n > 0 => {
+                        self.chapter = Some(n);
+                        self.verse = None;
+                        self.stray_text = false;
+                    }
+                    _ => self.warn(format!("`\\c {number}` is not a chapter number")),
+                }
+            }
+            Para::Heading if !text.is_empty() => {
+                let style = std::mem::take(&mut self.style);
+                self.pending.push((style, text));
+            }
+            Para::Name(rank) if !text.is_empty() && self.names[rank].is_none() => {
+                self.names[rank] = Some(text);
+            }
+            _ => {}
+        }
+        // Character styles end with their paragraph.
+        self.end_red();
+        self.in_wj = false;
+        self.para = Para::Ignore;
+        self.chars = 0;
+        self.attrs = false;
+        self.word = None;
+    }
+
+    fn end_book(&mut self) {
+        self.close_note();
+        if let Some(i) = self.book.take() {
+            let names = std::mem::take(&mut self.names);
+            self.books[i].name = names.into_iter().flatten().next();
+            if !self.pending.is_empty() {
+                self.warn("Dropped headings after the last verse");
+            }
+        }
+        self.pending.clear();
+        self.breaks.clear();
+        self.chapter = None;
+        self.verse = None;
+    }
+
+    fn start_book(&mut self, id_line: &str) {
+        self.end_book();
+        let code = id_line.split_whitespace().next().unwrap_or_default();
+        match books::by_usfm(code) {
+            Some(book) => {
+                self.books.push(Book {
+                    id: book.osis.to_string(),
+                    file: self.file.clone(),
+                    ..Default::default()
+                });
+                self.book = Some(self.books.len() - 1);
+            }
+            None if PERIPHERALS.contains(&code.to_ascii_uppercase().as_str()) => {}
+            None => self.warn(format!(
+                "`\\id {code}` is not a book BibleMarker shows; skipped"
+            )),
+        }
+    }
+
+    fn start_verse(&mut self, number: &str) {
+        self.verse = None;
+        let Some(chapter) = self.chapter else {
+            self.warn(format!("Verse {number} comes before any chapter; dropped"));
+            return;
+        };
+        let digits: String = number.chars().take_while(char::is_ascii_digit).collect();
+        let Ok(verse) = digits.parse::<i64>() else {
+            self.warn(format!("`\\v {number}` is not a verse number"));
+            return;
+        };
+        let rest = &number[digits.len()..];
+        let through = match rest.strip_prefix('-').map(str::parse::<i64>) {
+            Some(Ok(last)) if last > verse => last,
+            _ if rest.is_empty() => verse,
+            _ => {
+                self.warn(format!("Verse number `{number}` read as {verse}"));
+                verse
+            }
+        };
+        let line = Some(self.line);
+        let pending = std::mem::take(&mut self.pending);
+        let breaks = std::mem::take(&mut self.breaks);
+        let Some(book) = self.current() else {
+            return;
+        };
+        for (style, text) in pending {
+            book.headings.push(Heading {
+                chapter,
+                verse,
+                style,
+                text,
+            });
+        }
+        book.verses.push(Verse {
+            chapter,
+            verse,
+            through,
+            line,
+            paragraphs: breaks.into_iter().map(|style| (0, style)).collect(),
+            ..Default::default()
+        });
+        self.verse = Some(book.verses.len() - 1);
+        if self.in_wj {
+            self.red = Some(0);
+        }
+        self.para = Para::Body;
+    }
+
+    fn open_note(&mut self, marker: &str) {
+        self.close_note();
+        let anchor = match (self.para, self.verse) {
+            (Para::Body, Some(i)) => self.current().map(|book| {
+                let offset = book.verses[i].text.trim_end().chars().count();
+                (i, offset)
+            }),
+            _ => None,
+        };
+        self.note = Some(Note {
+            caller: None,
+            kind: import::note_kind(Some(marker)),
+            text: String::new(),
+            keep_text: true,
+            anchor,
+            line: self.line,
+        });
+    }
+
+    fn close_note(&mut self) {
+        let Some(note) = self.note.take() else {
+            return;
+        };
+        let text = note.text.trim().to_string();
+        match note.anchor {
+            Some((i, position)) if !text.is_empty() => {
+                let Some(book) = self.current() else {
+                    return;
+                };
+                let (chapter, verse) = (book.verses[i].chapter, book.verses[i].verse);
+                book.footnotes.push(Footnote {
+                    chapter,
+                    verse,
+                    caller: note.caller.unwrap_or_else(|| "+".into()),
+                    position,
+                    text,
+                    kind: note.kind,
+                });
+            }
+            None if !text.is_empty() => self.warnings.push(
+                &self.file,
+                Some(note.line),
+                "Dropped a footnote outside verse text",
+            ),
+            _ => {}
+        }
+    }
+
+    fn open_word(&mut self) {
+        let in_verse = self.para == Para::Body && self.note.is_none();
+        let start = match self.verse {
+            Some(i) if in_verse => self.current().map(|book| book.verses[i].text.len()),
+            _ => None,
+        };
+        self.word = start.map(|start| WordSpan {
+            start,
+            depth: self.chars,
+            attrs: String::new(),
+        });
+    }
+
+    /// Tag the `\w` just closed with the Strong's numbers and morphology in
+    /// its attributes.
+    fn close_word(&mut self) {
+        let (Some(word), Some(i)) = (self.word.take(), self.verse) else {
+            return;
+        };
+        let Some(book) = self.current() else {
+            return;
+        };
+        // `\w word|x-morph="..." strong="H1234"\w*`
+        let strongs = import::strongs_numbers(
+            tag_attr(&word.attrs, "strong").unwrap_or_default(),
+            Some(import::strongs_prefix(&book.id)),
+        );
+        let gloss = tag_attr(&word.attrs, "gloss")
+            .or_else(|| tag_attr(&word.attrs, "x-gloss"))
+            .map(str::to_string);
+        let morph = tag_attr(&word.attrs, "x-morph").and_then(import::morph_code);
+        let verse = &mut book.verses[i];
+        import::tag_word(
+            &mut verse.words,
+            &verse.text,
+            word.start,
+            strongs,
+            gloss,
+            morph,
+        );
+    }
+
+    fn open_red(&mut self) {
+        self.in_wj = true;
+        let in_verse = self.para == Para::Body && self.note.is_none();
+        self.red = match self.verse {
+            Some(i) if in_verse => self.current().map(|book| book.verses[i].text.len()),
+            _ => None,
+        };
+    }
+
+    /// Mark the open verse's text since `\wj` (or the verse start) red.
+    fn end_red(&mut self) {
+        let (Some(start), Some(i)) = (self.red.take(), self.verse) else {
+            return;
+        };
+        if let Some(book) = self.current() {
+            let verse = &mut book.verses[i];
+            import::red_letter(&mut verse.red_letter, &verse.text, start);
+        }
+    }
+
+    fn open_alignment(&mut self, attrs: &str) {
+        let in_verse = self.para == Para::Body && self.note.is_none();
+        let open = match self.verse {
+            Some(i) if in_verse => self.current().map(|book| {
+                let verse = &book.verses[i];
+                (verse.text.len(), verse.alignments.len())
+            }),
+            _ => None,
+        };
+        let attrs = attrs.trim_start_matches(|c: char| c == '|' || c.is_whitespace());
+        self.alignments.push(open.map(|(start, seq)| AlignSpan {
+            start,
+            seq,
+            attrs: attrs.to_string(),
+        }));
+    }
+
+    /// Align the text since the `\zaln-s` just closed with the word in its
+    /// attributes, and tag it with the word's Strong's numbers.
+    fn close_alignment(&mut self) {
+        let (Some(Some(span)), Some(i)) = (self.alignments.pop(), self.verse) else {
+            return;
+        };
+        let Some(book) = self.current() else {
+            return;
+        };
+        let attr = |key| tag_attr(&span.attrs, key);
+        let strongs = import::strongs_numbers(
+            &padded_strongs(attr("x-strong").unwrap_or_default()),
+            Some(import::strongs_prefix(&book.id)),
+        );
+        let morph = attr("x-morph").and_then(import::morph_code);
+        let verse = &mut book.verses[i];
+        import::tag_word(
+            &mut verse.words,
+            &verse.text,
+            span.start,
+            strongs.clone(),
+            None,
+            morph.clone(),
+        );
+        import::align(
+            &mut verse.alignments,
+            span.seq,
+            &verse.text,
+            span.start,
+            Alignment {
+                position: 0,
+                text: String::new(),
+                original: attr("x-content").unwrap_or_default().to_string(),
+                lemma: attr("x-lemma").map(str::to_string),
+                strongs,
+                morph,
+                occurrence: attr("x-occurrence")
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(1),
+            },
+        );
+    }
+
+    fn marker(&mut self, name: &str, closing: bool, line: usize) {
+        self.line = line;
+        self.milestone = false;
+        if let Some(attrs) = self.aligning.take() {
+            self.open_alignment(&attrs);
+        }
+        match name {
+            // `\zaln-s\*` with no attributes.
+            "zaln-s" if closing => return self.open_alignment(""),
+            "zaln-s" => {
+                self.aligning = Some(String::new());
+                self.milestone = true;
+                return;
+            }
+            "zaln-e" => return self.close_alignment(),
+            _ => {}
+        }
+        let kind = kind(name);
+        // The `\id` line ends at the next marker; it decides whether there
+        // is a book to read at all.
+        if self.para == Para::Id {
+            self.end_para();
+        }
+        if self.book.is_none() && kind != Kind::Id {
+            return;
+        }
+        if closing {
+            match kind {
+                Kind::Footnote => self.close_note(),
+                Kind::CrossRef => self.in_xref = false,
+                Kind::Skip => self.skip = self.skip.saturating_sub(1),
+                Kind::Char => {
+                    if self.word.as_ref().is_some_and(|w| w.depth == self.chars) {
+                        self.close_word();
+                    }
+                    if name == "wj" && self.note.is_none() {
+                        self.end_red();
+                        self.in_wj = false;
+                    }
+                    self.chars = self.chars.saturating_sub(1);
+                    self.attrs = false;
+                }
+                _ => {}
+            }
+            return;
+        }
+        if self.note.is_some() || self.in_xref {
+            match kind {
+                Kind::NotePart(keep) => {
+                    if let Some(note) = &mut self.note {
+                        note.keep_text = keep;
+                    }
+                    return;
+                }
+                Kind::Char | Kind::Skip | Kind::Milestone | Kind::Unknown => {}
+                _ => {
+                    if self.note.is_some() {
+                        self.warn("Footnote not closed with `\\f*`");
+                        self.close_note();
+                    }
+                    self.in_xref = false;
+                }
+            }
+        }
+        match kind {
+            Kind::Id => {
+                self.end_para();
+                self.para = Para::Id;
+            }
+            Kind::Chapter => {
+                self.end_para();
+                self.para = Para::Chapter;
+            }
+            Kind::Verse => {
+                if self.para != Para::Body {
+                    self.end_para();
+                }
+                self.chars = 0;
+                self.attrs = false;
+                self.word = None;
+                self.alignments.clear();
+                self.end_red();
+                self.verse_number = true;
+            }
+            Kind::Name(rank) => {
+                self.end_para();
+                self.para = Para::Name(rank);
+            }
+            Kind::Heading => {
+                self.end_para();
+                self.para = Para::Heading;
+                self.style = name.to_string();
+            }
+            Kind::Ignore => self.end_para(),
+            Kind::Body => {
+                self.end_para();
+                self.para = Para::Body;
+                let base = name.trim_end_matches(|c: char| c.is_ascii_digit());
+                if !NO_BREAK.contains(&base) {
+                    self.breaks.push(name.to_string());
+                }
+                self.append(" ");
+            }
+            Kind::Footnote => self.open_note(name),
+            Kind::CrossRef => self.in_xref = true,
+            Kind::Char => {
+                self.chars += 1;
+                if name == "w" {
+                    self.open_word();
+                }
+                if name == "wj" && self.note.is_none() {
+                    self.open_red();
+                }
+            }
+            Kind::Skip => self.skip += 1,
+            Kind::Milestone => self.milestone = true,
+            Kind::NotePart(_) => {}
+            Kind::Unknown => {
+                if self.unknown.insert(name.to_string()) {
+                    self.warn(format!("Unknown marker `\\{name}`; its text was kept"));
+                }
+            }
+        }
+    }
+
+    /// Add `text` to the open verse.
+    fn append(&mut self, text: &str) {
+        match self.verse {
+            Some(i) => {
+                let breaks = if text.trim().is_empty() {
+                    Vec::new()
+                } else {
+                    std::mem::take(&mut self.breaks)
+                };
+                if let Some(book) = self.current() {
+                    let verse = &mut book.verses[i];
+                    let position = verse.text.chars().count();
+                    verse
+                        .paragraphs
+                        .extend(breaks.into_iter().map(|style| (position, style)));
+                    push_text(&mut verse.text, text);
+                }
+            }
+            None if !text.trim().is_empty() && !self.stray_text => {
+                self.stray_text = true;
+                self.warn("Dropped text outside any verse");
+            }
+            None => {}
+        }
+    }
+
+    fn text(&mut self, mut text: &str) {
+        if let Some(attrs) = &mut self.aligning {
+            attrs.push_str(text);
+        }
+        if self.milestone || self.skip > 0 || self.in_xref {
+            return;
+        }
+        if self.book.is_none() && self.para != Para::Id {
+            return;
+        }
+        if self.chars > 0 {
+            if self.attrs {
+                if let Some(word) = &mut self.word {
+                    word.attrs.push_str(text);
+                }
+                return;
+            }
+            if let Some((word, attrs)) = text.split_once('|') {
+                self.attrs = true;
+                if let Some(span) = &mut self.word {
+                    span.attrs.push_str(attrs);
+                }
+                text = word;
+            }
+        }
+        if self.verse_number {
+            self.verse_number = false;
+            let trimmed = text.trim_start();
+            let (number, rest) = trimmed
+                .split_once(char::is_whitespace)
+                .unwrap_or((trimmed, ""));
+            self.start_verse(number);
+            text = rest;
+        }
+        if let Some(note) = &mut self.note {
+            if note.caller.is_none() {
+                let trimmed = text.trim_start();
+                let (caller, rest) = trimmed
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((trimmed, ""));
+                note.caller = Some(caller.to_string());
+                text = rest;
+            }
+            if note.keep_text {
+                push_text(&mut note.text, text);
+            }
+            return;
+        }
+        match self.para {
+            Para::Body => self.append(text),
+            Para::Ignore => {}
+            _ => push_text(&mut self.buf, text),
+        }
+    }
+
+    fn finish(mut self) -> Vec<Book> {
+        self.end_para();
+        self.end_book();
+        for book in &mut self.books {
+            for verse in &mut book.verses {
+                verse.text.truncate(verse.text.trim_end().len());
+            }
+        }
+        self.books
+    }
+}
+
+/// The books in one USFM file (usually one; concatenated files can hold
+/// several `\id`s).
+pub(crate) fn parse(src: &str, file: &str, warnings: &mut Warnings) -> Vec<Book> {
+    let mut parser = Parser::new(file, warnings);
+    let mut saw_id = false;
+    for token in tokenize(src) {
+        match token {
+            Token::Marker {
+                name,
+                closing,
+                line,
+            } => {
+                saw_id |= name == "id";
+                parser.marker(name, closing, line);
+            }
+            Token::Text(text) => parser.text(text),
+        }
+    }
+    if !saw_id {
+        parser
+            .warnings
+            .push(file, None, "No `\\id` line; not a USFM book");
+    }
+    parser.finish()
+}
+
+/// Import USFM files as translation `module_id`, replacing its content file.
+/// `content://import-progress` reports each file as it's read.
+#[command]
+pub async fn import_usfm(
+    app: tauri::AppHandle,
+    module_id: String,
+    name: Option<String>,
+    paths: Vec<String>,
+) -> Result<ImportReport, DbError> {
+    let name = name.unwrap_or_else(|| module_id.clone());
+    let paths = paths.into_iter().map(PathBuf::from).collect();
+    import::import_files(app, module_id, name, "usfm", &[], paths, parse).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+    use rusqlite::Connection;
+
+    const JOHN: &str = r#"\id JHN World English Bible
+\usfm 3.0
+\h John
+\toc1 The Good News According to John
+\mt1 The Good News According to John
+\ip An \bk introduction\bk* to drop.
+\c 1
+\s1 The Word Became Flesh
+\p
+\v 1 In the beginning was the Word,\f + \fr 1:1 \ft Or, \fq Logos\f* and the Word was with God,
+\q1 and the \w Word|strong="G3056"\w* was God.
+\v 2 The same was in the beginning with God.\x - \xo 1:2 \xt Gen 1:1\x*
+\c 2
+\p
+\v 1 On the third day, \wj “Come.”\wj*
+\v 3 there was a wedding \zaln-s |x-occurrence="1"\*in Cana\zaln-e\*.
+\v 3 Again.
+"#;
+
+    fn parse_john() -> (Vec<Book>, Warnings) {
+        let mut warnings = Warnings::default();
+        let books = parse(JOHN, "43JHN.usfm", &mut warnings);
+        (books, warnings)
+    }
+
+    #[test]
+    fn reads_verses_headings_and_footnotes() {
+        let (books, _) = parse_john();
+        assert_eq!(books.len(), 1);
+        let john = &books[0];
+        assert_eq!(
+            (john.id.as_str(), john.name.as_deref()),
+            ("John", Some("John"))
+        );
+
+        let text: Vec<&str> = john.verses.iter().map(|v| v.text.as_str()).collect();
+        assert_eq!(
+            text[0],
+            "In the beginning was the Word, and the Word was with God, and the Word was God."
+        );
+        assert_eq!(text[1], "The same was in the beginning with God.");
+        assert_eq!(text[3], "there was a wedding in Cana.");
+        assert_eq!(john.verses[2].red_letter, [(18, 25)]);
+        let styles: Vec<&str> = john.verses[0]
+            .paragraphs
+            .iter()
+            .map(|(_, style)| style.as_str())
+            .collect();
+        assert_eq!(styles, ["p", "q1"]);
+        let line = john.verses[0].paragraphs[1].0;
+        assert_eq!(&text[0][line..], "and the Word was God.");
+        assert_eq!(john.verses[2].paragraphs, [(0, "p".to_string())]);
+
+        assert_eq!(john.headings.len(), 1);
+        let heading = &john.headings[0];
+        assert_eq!(
+            (
+                heading.chapter,
+                heading.verse,
+                heading.style.as_str(),
+                heading.text.as_str()
+            ),
+            (1, 1, "s1", "The Word Became Flesh")
+        );
+
+        assert_eq!(john.footnotes.len(), 1);
+        let note = &john.footnotes[0];
+        assert_eq!(
+            (note.caller.as_str(), note.text.as_str(), note.kind.as_str()),
+            ("+", "Or, Logos", "footnote")
+        );
+        assert_eq!(&text[0][..note.position], "In the beginning was the Word,");
+
+        let word = &john.verses[0].words[0];
+        assert_eq!(
+            (word.text.as_str(), word.strongs.as_slice()),
+            ("Word", ["G3056".to_string()].as_slice())
+        );
+        assert_eq!(&text[0][word.position..], "Word was God.");
+    }
+
+    #[test]
+    fn writes_a_content_file_and_reports_problems() {
+        let (books, warnings) = parse_john();
+        let dir = ScratchDir::new("usfm");
+        let path = dir.join("web.db");
+        let report = import::write(
+            &path,
+            "web",
+            "World English Bible",
+            "usfm",
+            &[],
+            books,
+            warnings,
+        )
+        .unwrap();
+
+        assert_eq!(report.books, ["John"]);
+        assert_eq!(
+            (report.verses, report.headings, report.footnotes),
+            (4, 1, 1)
+        );
+        let messages: Vec<&str> = report.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert!(
+            messages.contains(&"John 2:3 follows verse 1"),
+            "{messages:?}"
+        );
+        assert!(
+            messages.contains(&"John 2:3 appears twice; kept the first"),
+            "{messages:?}"
+        );
+
+        let conn = Connection::open(&path).unwrap();
+        let text: String = conn
+            .query_row(
+                "SELECT text FROM verses WHERE book = 'John' AND chapter = 1 AND verse = 2",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(text, "The same was in the beginning with God.");
+        let name: String = conn
+            .query_row("SELECT name FROM books WHERE book = 'John'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(name, "John");
+        let red = crate::content::red_letter(&conn, "main", "John", 2).unwrap();
+        assert_eq!(red[&1], [(18, 25)]);
+        drop(conn);
+    }
+
+    #[test]
+    fn skips_unknown_books_and_warns_about_stray_markup() {
+        let mut warnings = Warnings::default();
+        let books = parse(
+            "\\id FRT\n\\p Preface\n\\id XYZ\n\\c 1\n\\p\n\\v 1 Lost.\n\\id JUD\n\\c 1\n\\p\n\\v 1 Jude, \\zz odd\\zz* text.",
+            "mixed.usfm",
+            &mut warnings,
+        );
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].verses[0].text, "Jude, odd text.");
+        let messages: Vec<&str> = warnings.list.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "`\\id XYZ` is not a book BibleMarker shows; skipped",
+                "Unknown marker `\\zz`; its text was kept",
+            ]
+        );
+        assert_eq!(warnings.list[1].line, Some(10));
+    }
+}
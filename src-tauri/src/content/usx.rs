@@ -0,0 +1,736 @@
+//! USX import, including whole Digital Bible Library bundles.
+//!
+//! USX is the XML form of USFM that the DBL distributes: the same style
+//! names, carried on `<para>`, `<char>` and `<note>` elements, with
+//! `<chapter>` and `<verse>` milestones. Styles are classified exactly as
+//! the USFM importer reads the matching markers. Verses keep their text and,
+//! in `html`, the paragraph and character styles the app shows (poetry
+//! indents, words of Jesus, `nd` small caps, ...; see `import::Styled`)
+//! and the Strong's numbers and `x-morph` codes of
+//! `<char style="w" strong="G3056">` words.
+//!
+//! A DBL bundle is a directory with `metadata.xml` and one USX file per
+//! book. Bundles come zipped; `import_dbl_bundle` takes the unzipped
+//! directory.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+use super::books;
+use super::import::{self, Book, Footnote, Heading, ImportReport, Styled, Verse, Warnings};
+use super::usfm::{kind, Kind, PERIPHERALS};
+use super::xml::{self, Event};
+use crate::db::DbError;
+
+const METADATA_FILE: &str = "metadata.xml";
+
+/// What an open element does to the text inside it.
+#[derive(Debug, Clone, PartialEq)]
+enum Frame {
+    /// A paragraph of verse text, by style.
+    Body(String),
+    /// A heading paragraph, by style.
+    Heading(String),
+    /// A book name paragraph, by preference (see `Kind::Name`).
+    Name(usize),
+    /// A character style; `None` for ones whose text is kept unstyled.
+    Char(Option<String>),
+    Note,
+    /// Text inside is dropped.
+    Drop,
+    /// No effect on the text (`<usx>`, `<ref>`, table cells, milestones).
+    Plain,
+}
+
+struct Note {
+    caller: String,
+    /// See `import::note_kind`.
+    kind: String,
+    text: String,
+    /// Where it goes: (verse index, offset), or `None` to drop it.
+    anchor: Option<(usize, usize)>,
+    line: usize,
+}
+
+/// An open `<char style="w">` with Strong's numbers.
+struct WordSpan {
+    /// Stack depth of its frame.
+    depth: usize,
+    /// Byte offset of the word in the open verse's text.
+    start: usize,
+    strongs: Vec<String>,
+    gloss: Option<String>,
+    morph: Option<String>,
+}
+
+struct Parser<'w> {
+    file: String,
+    warnings: &'w mut Warnings,
+    books: Vec<Book>,
+    book: Option<Book>,
+    /// `import::Styled` runs of each verse in `book`.
+    styled: Vec<Styled>,
+    names: [Option<String>; 3],
+    chapter: Option<i64>,
+    verse: Option<usize>,
+    stack: Vec<Frame>,
+    /// Text of the heading or name paragraph being read.
+    buf: String,
+    pending: Vec<(String, String)>,
+    note: Option<Note>,
+    word: Option<WordSpan>,
+    line: usize,
+    stray_text: bool,
+    unknown: HashSet<String>,
+}
+
+impl<'w> Parser<'w> {
+    fn new(file: &str, warnings: &'w mut Warnings) -> Self {
+        Self {
+            file: file.to_string(),
+            warnings,
+            books: Vec::new(),
+            book: None,
+            styled: Vec::new(),
+            names: [None, None, None],
+            chapter: None,
+            verse: None,
+            stack: Vec::new(),
+            buf: String::new(),
+            pending: Vec::new(),
+            note: None,
+            word: None,
+            line: 1,
+            stray_text: false,
+            unknown: HashSet::new(),
+        }
+    }
+
+    fn warn(&mut self, message: impl Into<String>) {
+        let line = Some(self.line);
+        self.warnings.push(&self.file, line, message);
+    }
+
+    fn warn_unknown(&mut self, what: &str, style: &str) {
+        if self.unknown.insert(format!("{what} {style}")) {
+            self.warn(format!("Unknown {what} style `{style}`; its text was kept"));
+        }
+    }
+
+    fn end_book(&mut self) {
+        let Some(mut book) = self.book.take() else {
+            return;
+        };
+        for (verse, styled) in book.verses.iter_mut().zip(&mut self.styled) {
+            verse.text.truncate(verse.text.trim_end().len());
+            styled.trim_end();
+            verse.html = styled.html();
+            verse.red_letter = styled.spans("wj");
+        }
+        self.styled.clear();
+        let names = std::mem::take(&mut self.names);
+        book.name = names.into_iter().flatten().next();
+        if !self.pending.is_empty() {
+            self.warn("Dropped headings after the last verse");
+            self.pending.clear();
+        }
+        self.books.push(book);
+        self.chapter = None;
+        self.verse = None;
+    }
+
+    fn start_book(&mut self, code: &str) {
+        self.end_book();
+        match books::by_usfm(code) {
+            Some(book) => {
+                self.book = Some(Book {
+                    id: book.osis.to_string(),
+                    file: self.file.clone(),
+                    ..Default::default()
+                });
+            }
+            None if PERIPHERALS.contains(&code.to_ascii_uppercase().as_str()) => {}
+            None => self.warn(format!(
+                "Book `{code}` is not one BibleMarker shows; skipped"
+            )),
+        }
+    }
+
+    fn start_chapter(&mut self, number: &str) {
+        match number.trim().parse::<i64>() {
+            Ok(n) if n > 0 => {
+                self.chapter = Some(n);
+                self.verse = None;
+                self.stray_text = false;
+            }
+            _ => self.warn(format!("`{number}` is not a chapter number")),
+        }
+    }
+
+    fn start_verse(&mut self, number: &str) {
+        self.verse = None;
+        let Some(chapter) = self.chapter else {
+            self.warn(format!("Verse {number} comes before any chapter; dropped"));
+            return;
+        };
+        let number = number.trim();
+        let digits: String = number.chars().take_while(char::is_ascii_digit).collect();
+        let Ok(verse) = digits.parse::<i64>() else {
+            self.warn(format!("`{number}` is not a verse number"));
+            return;
+        };
+        let rest = &number[digits.len()..];
+        let through = match rest.strip_prefix('-').map(str::parse::<i64>) {
+            Some(Ok(last)) if last > verse => last,
+            _ if rest.is_empty() => verse,
+            _ => {
+                self.warn(format!("Verse number `{number}` read as {verse}"));
+                verse
+            }
+        };
+        let line = Some(self.line);
+        let pending = std::mem::take(&mut self.pending);
+        let Some(book) = &mut self.book else {
+            return;
+        };
+        for (style, text) in pending {
+            book.headings.push(Heading {
+                chapter,
+                verse,
+                style,
+                text,
+            });
+        }
+        book.verses.push(Verse {
+            chapter,
+            verse,
+            through,
+            line,
+            ..Default::default()
+        });
+        self.styled.push(Styled::default());
+        self.verse = Some(book.verses.len() - 1);
+    }
+
+    /// The innermost paragraph, if text in it is kept.
+    fn para(&self) -> Option<&Frame> {
+        if self.stack.contains(&Frame::Drop) {
+            return None;
+        }
+        self.stack
+            .iter()
+            .rev()
+            .find(|f| matches!(f, Frame::Body(_) | Frame::Heading(_) | Frame::Name(_)))
+    }
+
+    fn open_note(&mut self, caller: &str, style: &str) {
+        let anchor = match (self.para(), self.verse, &self.book) {
+            (Some(Frame::Body(_)), Some(i), Some(book)) => {
+                Some((i, book.verses[i].text.trim_end().chars().count()))
+            }
+            _ => None,
+        };
+        self.note = Some(Note {
+            caller: caller.to_string(),
+            kind: import::note_kind(Some(style)),
+            text: String::new(),
+            anchor,
+            line: self.line,
+        });
+    }
+
+    fn close_note(&mut self) {
+        let Some(note) = self.note.take() else {
+            return;
+        };
+        let text = note.text.trim().to_string();
+        match (note.anchor, &mut self.book) {
+            (_, _) if text.is_empty() => {}
+            (Some((i, position)), Some(book)) => {
+                let (chapter, verse) = (book.verses[i].chapter, book.verses[i].verse);
+                book.footnotes.push(Footnote {
+                    chapter,
+                    verse,
+                    caller: note.caller,
+                    position,
+                    text,
+                    kind: note.kind,
+                });
+            }
+            _ => self.warnings.push(
+                &self.file,
+                Some(note.line),
+                "Dropped a footnote outside verse text",
+            ),
+        }
+    }
+
+    /// The frame for `<para style>`.
+    fn para_frame(&mut self, style: &str) -> Frame {
+        match kind(style) {
+            Kind::Body => Frame::Body(style.to_string()),
+            Kind::Heading => Frame::Heading(style.to_string()),
+            Kind::Name(rank) => Frame::Name(rank),
+            Kind::Ignore => Frame::Drop,
+            _ => {
+                self.warn_unknown("paragraph", style);
+                Frame::Body(style.to_string())
+            }
+        }
+    }
+
+    /// The frame for `<char style>`.
+    fn char_frame(&mut self, style: &str) -> Frame {
+        match kind(style) {
+            Kind::Char => Frame::Char(Some(style.to_string())),
+            Kind::NotePart(true) => Frame::Char(None),
+            Kind::Skip | Kind::NotePart(false) => Frame::Drop,
+            _ => {
+                self.warn_unknown("character", style);
+                Frame::Char(None)
+            }
+        }
+    }
+
+    fn start(&mut self, name: &str, attrs: &[(&str, String)], empty: bool) {
+        let attr = |key: &str| xml::attr(attrs, key).unwrap_or_default();
+        if name == "book" {
+            self.start_book(attr("code"));
+        }
+        let frame = if name == "usx" {
+            Frame::Plain
+        } else if self.book.is_none() {
+            Frame::Drop
+        } else {
+            match name {
+                "book" | "figure" | "sidebar" | "periph" => Frame::Drop,
+                "chapter" => {
+                    if !attr("number").is_empty() {
+                        self.start_chapter(attr("number"));
+                    }
+                    Frame::Plain
+                }
+                "verse" => {
+                    if !attr("number").is_empty() {
+                        self.start_verse(attr("number"));
+                    } else if !attr("eid").is_empty() {
+                        self.verse = None;
+                    }
+                    Frame::Plain
+                }
+                "para" => self.para_frame(attr("style")),
+                // Table rows read like paragraphs.
+                "row" => Frame::Body(attr("style").to_string()),
+                "char" => self.char_frame(attr("style")),
+                "note" if self.note.is_none() && kind(attr("style")) == Kind::Footnote => {
+                    self.open_note(attr("caller"), attr("style"));
+                    Frame::Note
+                }
+                "note" => Frame::Drop,
+                _ => Frame::Plain,
+            }
+        };
+        if !empty {
+            self.stack.push(frame);
+            if name == "char" && attr("style") == "w" {
+                self.open_word(attrs);
+            }
+        }
+    }
+
+    fn open_word(&mut self, attrs: &[(&str, String)]) {
+        if self.note.is_some() || !matches!(self.para(), Some(Frame::Body(_))) {
+            return;
+        }
+        let (Some(i), Some(book)) = (self.verse, &self.book) else {
+            return;
+        };
+        let strongs = import::strongs_numbers(
+            xml::attr(attrs, "strong").unwrap_or_default(),
+            Some(import::strongs_prefix(&book.id)),
+        );
+        let morph = xml::attr(attrs, "x-morph").and_then(import::morph_code);
+        let start = book.verses[i].text.len();
+        if !strongs.is_empty() || morph.is_some() {
+            self.word = Some(WordSpan {
+                depth: self.stack.len(),
+                start,
+                strongs,
+                gloss: xml::attr(attrs, "gloss").map(str::to_string),
+                morph,
+            });
+        }
+    }
+
+    fn end(&mut self) {
+        if self
+            .word
+            .as_ref()
+            .is_some_and(|w| w.depth == self.stack.len())
+        {
+            if let (Some(word), Some(i), Some(book)) =
+                (self.word.take(), self.verse, &mut self.book)
+            {
+                let verse = &mut book.verses[i];
+                import::tag_word(
+                    &mut verse.words,
+                    &verse.text,
+                    word.start,
+                    word.strongs,
+                    word.gloss,
+                    word.morph,
+                );
+            }
+        }
+        match self.stack.pop() {
+            Some(Frame::Note) => self.close_note(),
+            Some(Frame::Heading(style)) => {
+                let text = std::mem::take(&mut self.buf).trim().to_string();
+                if !text.is_empty() {
+                    self.pending.push((style, text));
+                }
+            }
+            Some(Frame::Name(rank)) => {
+                let text = std::mem::take(&mut self.buf).trim().to_string();
+                if !text.is_empty() && self.names[rank].is_none() {
+                    self.names[rank] = Some(text);
+                }
+            }
+            // Paragraphs run into each other within a verse.
+            Some(Frame::Body(style)) => self.append(&style, &[], " "),
+            _ => {}
+        }
+    }
+
+    /// Add `text` to the open verse, styled with paragraph `para` and
+    /// character styles `chars`.
+    fn append(&mut self, para: &str, chars: &[&str], text: &str) {
+        let Some(i) = self.verse else {
+            if !text.trim().is_empty() && !self.stray_text && self.book.is_some() {
+                self.stray_text = true;
+                self.warn("Dropped text outside any verse");
+            }
+            return;
+        };
+        let Some(book) = &mut self.book else {
+            return;
+        };
+        let verse = &mut book.verses[i].text;
+        let before = verse.len();
+        import::push_text(verse, text);
+        self.styled[i].push(para, chars, &verse[before..]);
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.book.is_none() || self.stack.contains(&Frame::Drop) {
+            return;
+        }
+        if let Some(note) = &mut self.note {
+            import::push_text(&mut note.text, text);
+            return;
+        }
+        match self.para().cloned() {
+            Some(Frame::Body(style)) => {
+                let chars: Vec<String> = self
+                    .stack
+                    .iter()
+                    .filter_map(|f| match f {
+                        Frame::Char(Some(style)) => Some(style.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                let chars: Vec<&str> = chars.iter().map(String::as_str).collect();
+                self.append(&style, &chars, text);
+            }
+            Some(_) => import::push_text(&mut self.buf, text),
+            None => {}
+        }
+    }
+}
+
+/// The books in one USX file (one, unless it isn't USX at all).
+pub(crate) fn parse(src: &str, file: &str, warnings: &mut Warnings) -> Vec<Book> {
+    let events = match xml::parse(src) {
+        Ok(events) => events,
+        Err((line, message)) => {
+            warnings.push(file, Some(line), format!("Not valid XML: {message}"));
+            return Vec::new();
+        }
+    };
+    let mut parser = Parser::new(file, warnings);
+    let mut saw_usx = false;
+    for event in &events {
+        match event {
+            Event::Start {
+                name,
+                attrs,
+                empty,
+                line,
+            } => {
+                parser.line = *line;
+                saw_usx |= *name == "usx";
+                parser.start(name, attrs, *empty);
+            }
+            Event::End { line, .. } => {
+                parser.line = *line;
+                parser.end();
+            }
+            Event::Text(text) => parser.text(text),
+        }
+    }
+    if !saw_usx {
+        parser
+            .warnings
+            .push(file, None, "No `<usx>` element; not USX");
+    }
+    parser.end_book();
+    parser.books
+}
+
+/// Import USX files as translation `module_id`, replacing its content file.
+/// `content://import-progress` reports each file as it's read.
+#[command]
+pub async fn import_usx(
+    app: tauri::AppHandle,
+    module_id: String,
+    name: Option<String>,
+    paths: Vec<String>,
+) -> Result<ImportReport, DbError> {
+    let name = name.unwrap_or_else(|| module_id.clone());
+    let paths = paths.into_iter().map(PathBuf::from).collect();
+    import::import_files(app, module_id, name, "usx", &[], paths, parse).await
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Bundle {
+    pub name: Option<String>,
+    pub abbreviation: Option<String>,
+    /// The USX files of the default publication, in canon order.
+    pub files: Vec<PathBuf>,
+}
+
+/// `*.usx` files directly in `dir`, sorted by name.
+fn usx_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("usx"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Read the metadata of the unzipped DBL bundle at `dir`.
+pub(crate) fn read_bundle(dir: &Path) -> Result<Bundle, DbError> {
+    let metadata = dir.join(METADATA_FILE);
+    let src = std::fs::read_to_string(&metadata).map_err(|_| {
+        DbError::invalid(format!(
+            "{} is not a DBL bundle: no {METADATA_FILE}",
+            dir.display()
+        ))
+    })?;
+    let root = xml::tree(src.trim_start_matches('\u{feff}')).map_err(|(line, message)| {
+        DbError::invalid(format!("{METADATA_FILE} line {line}: {message}"))
+    })?;
+    if root.name != "DBLMetadata" {
+        return Err(DbError::invalid(format!(
+            "{METADATA_FILE} is not DBL metadata"
+        )));
+    }
+    if let Some(kind) = root
+        .attr("type")
+        .or_else(|| root.text_at("type/medium"))
+        .filter(|kind| *kind != "text")
+    {
+        return Err(DbError::invalid(format!(
+            "The bundle holds {kind}, not Bible text"
+        )));
+    }
+
+    let text = |paths: &[&str]| {
+        paths
+            .iter()
+            .find_map(|path| root.text_at(path))
+            .map(str::to_string)
+    };
+    let mut bundle = Bundle {
+        name: text(&["identification/nameLocal", "identification/name"]),
+        abbreviation: text(&[
+            "identification/abbreviationLocal",
+            "identification/abbreviation",
+        ]),
+        files: Vec::new(),
+    };
+
+    // DBL 2: the default publication lists its files.
+    let publication = root.child("publications").and_then(|list| {
+        list.children("publication")
+            .find(|p| p.attr("default") == Some("true"))
+            .or_else(|| list.child("publication"))
+    });
+    if let Some(structure) = publication.and_then(|p| p.child("structure")) {
+        bundle.files = structure
+            .children("content")
+            .filter_map(|c| c.attr("src"))
+            .filter(|src| src.to_ascii_lowercase().ends_with(".usx"))
+            .map(|src| dir.join(src))
+            .collect();
+    }
+    // DBL 1 bundles (and ones without a structure) keep the books in a
+    // `USX_<n>` directory, at the top or under `release/`.
+    if bundle.files.is_empty() {
+        for parent in [dir.join("release"), dir.to_path_buf()] {
+            let mut dirs: Vec<PathBuf> = std::fs::read_dir(&parent)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| {
+                    path.is_dir()
+                        && path
+                            .file_name()
+                            .is_some_and(|n| n.to_string_lossy().starts_with("USX_"))
+                })
+                .collect();
+            dirs.sort();
+            if let Some(books) = dirs.first() {
+                bundle.files = usx_files(books);
+                break;
+            }
+        }
+    }
+    if bundle.files.is_empty() {
+        return Err(DbError::invalid("The bundle has no USX books"));
+    }
+    Ok(bundle)
+}
+
+/// Import an unzipped DBL text bundle. The translation id defaults to the
+/// bundle's abbreviation and its name to the bundle's (local) name.
+#[command]
+pub async fn import_dbl_bundle(
+    app: tauri::AppHandle,
+    path: String,
+    module_id: Option<String>,
+    name: Option<String>,
+) -> Result<ImportReport, DbError> {
+    let bundle = read_bundle(Path::new(&path))?;
+    let module_id = module_id
+        .or(bundle.abbreviation)
+        .ok_or_else(|| DbError::invalid("The bundle has no abbreviation; choose an id"))?;
+    let name = name.or(bundle.name).unwrap_or_else(|| module_id.clone());
+    import::import_files(app, module_id, name, "usx", &[], bundle.files, parse).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    const JOHN: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<usx version="3.0">
+  <book code="JHN" style="id">World English Bible</book>
+  <para style="h">John</para>
+  <para style="mt1">The Good News According to John</para>
+  <chapter number="1" style="c" sid="JHN 1" />
+  <para style="s1">The Word Became Flesh</para>
+  <para style="p">
+    <verse number="1" style="v" sid="JHN 1:1" />In the beginning was the Word,<note caller="+" style="f"><char style="fr" closed="false">1:1 </char><char style="ft" closed="false">Or, Logos</char></note> and the Word was with God,</para>
+  <para style="q1">and the <char style="w" strong="G3056">Word</char> was God.<verse eid="JHN 1:1" /></para>
+  <para style="p">
+    <verse number="2" style="v" sid="JHN 1:2" />Jesus said, <char style="wj">“I am &amp; was.”</char><note caller="-" style="x"><char style="xt">Gen 1:1</char></note><verse eid="JHN 1:2" /></para>
+  <chapter eid="JHN 1" />
+</usx>"#;
+
+    #[test]
+    fn maps_usx_styles_onto_verses() {
+        let mut warnings = Warnings::default();
+        let books = parse(JOHN, "JHN.usx", &mut warnings);
+        assert!(warnings.list.is_empty(), "{:?}", warnings.list);
+        let john = &books[0];
+        assert_eq!(
+            (john.id.as_str(), john.name.as_deref()),
+            ("John", Some("John"))
+        );
+        assert_eq!(
+            john.verses[0].text,
+            "In the beginning was the Word, and the Word was with God, and the Word was God."
+        );
+        assert_eq!(
+            john.verses[0].html.as_deref(),
+            Some(
+                "In the beginning was the Word, and the Word was with God, \
+                 <span class=\"para-q1\">and the Word was God.</span>"
+            )
+        );
+        assert_eq!(john.verses[1].text, "Jesus said, “I am & was.”");
+        assert_eq!(
+            john.verses[1].html.as_deref(),
+            Some("Jesus said, <span class=\"char-wj\">“I am &amp; was.”</span>")
+        );
+        assert_eq!(john.verses[1].red_letter, [(12, 25)]);
+
+        let word = &john.verses[0].words[0];
+        assert_eq!(
+            (word.position, word.text.as_str(), word.strongs.as_slice()),
+            (66, "Word", ["G3056".to_string()].as_slice())
+        );
+
+        assert_eq!(john.headings[0].style, "s1");
+        let note = &john.footnotes[0];
+        assert_eq!(
+            (note.caller.as_str(), note.text.as_str()),
+            ("+", "Or, Logos")
+        );
+        assert_eq!(
+            &john.verses[0].text[..note.position],
+            "In the beginning was the Word,"
+        );
+    }
+
+    #[test]
+    fn reads_a_dbl_bundle() {
+        let dir = ScratchDir::new("dbl");
+        std::fs::create_dir_all(dir.join("release/USX_1")).unwrap();
+        std::fs::write(
+            dir.join(METADATA_FILE),
+            r#"<DBLMetadata id="x" version="2.1">
+  <identification><name>World English Bible</name><abbreviation>WEB</abbreviation></identification>
+  <type><medium>text</medium></type>
+  <publications>
+    <publication default="true" id="p1">
+      <structure><content role="JHN" src="release/USX_1/JHN.usx" /></structure>
+    </publication>
+  </publications>
+</DBLMetadata>"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("release/USX_1/JHN.usx"), JOHN).unwrap();
+
+        let bundle = read_bundle(&dir).unwrap();
+        assert_eq!(bundle.name.as_deref(), Some("World English Bible"));
+        assert_eq!(bundle.abbreviation.as_deref(), Some("WEB"));
+        assert_eq!(bundle.files, [dir.join("release/USX_1/JHN.usx")]);
+
+        std::fs::write(
+            dir.join(METADATA_FILE),
+            "<DBLMetadata><type><medium>audio</medium></type></DBLMetadata>",
+        )
+        .unwrap();
+        assert!(read_bundle(&dir).is_err());
+    }
+
+    #[test]
+    fn reports_broken_files() {
+        let mut warnings = Warnings::default();
+        assert!(parse("<usx><book code=\"JHN\">", "bad.usx", &mut warnings).is_empty());
+        assert_eq!(
+            warnings.list[0].message,
+            "Not valid XML: `<book>` is never closed"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Mapping verse numbers between versifications.
+//!
+//! Most translations number verses as the KJV does. Hebrew Bibles (the WLC,
+//! SWORD's `Leningrad` and `MT` modules) differ in two ways: some chapters
+//! begin a few verses earlier or later (KJV Malachi 4 is Hebrew Malachi
+//! 3:19-24), and a psalm's title is its first verse or two, so the verses
+//! after it are numbered one or two higher. References are mapped through
+//! the KJV numbering; versifications not listed here are read as KJV.
+
+use rusqlite::{Connection, OptionalExtension};
+
+use super::mounted;
+use crate::db::DbError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Scheme {
+    Kjv,
+    Hebrew,
+}
+
+impl Scheme {
+    /// The scheme a content file's `versification` names.
+    pub(crate) fn named(name: Option<&str>) -> Scheme {
+        match name.map(str::trim) {
+            Some(name) if ["leningrad", "mt", "wlc"].contains(&name.to_lowercase().as_str()) => {
+                Scheme::Hebrew
+            }
+            _ => Scheme::Kjv,
+        }
+    }
+}
+
+/// The versification a mounted content file declares.
+pub(crate) fn declared(conn: &Connection, schema: &str) -> Result<Scheme, DbError> {
+    let name: Option<String> = conn
+        .query_row(
+            &format!("SELECT value FROM \"{schema}\".content_info WHERE key = 'versification'"),
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(Scheme::named(name.as_deref()))
+}
+
+/// The versification of translation `module_id`: its file's, when mounted,
+/// and otherwise KJV, as the reader numbers verses it fetches.
+pub(crate) fn of_module(conn: &Connection, module_id: &str) -> Result<Scheme, DbError> {
+    match mounted(module_id) {
+        Some(content) => declared(conn, &content.schema),
+        None => Ok(Scheme::Kjv),
+    }
+}
+
+/// Ranges the Hebrew numbering places differently: KJV `chapter`:`first` to
+/// `last` of `book` are Hebrew `to_chapter`:`to_first` onward.
+struct Shift {
+    book: &'static str,
+    chapter: i64,
+    first: i64,
+    last: i64,
+    to_chapter: i64,
+    to_first: i64,
+}
+
+const fn shift(
+    book: &'static str,
+    (chapter, first, last): (i64, i64, i64),
+    (to_chapter, to_first): (i64, i64),
+) -> Shift {
+    Shift {
+        book,
+        chapter,
+        first,
+        last,
+        to_chapter,
+        to_first,
+    }
+}
+
+const HEBREW_SHIFTS: &[Shift] = &[
+    shift("Gen", (31, 55, 55), (32, 1)),
+    shift("Gen", (32, 1, 32), (32, 2)),
+    shift("Exod", (8, 1, 4), (7, 26)),
+    shift("Exod", (8, 5, 32), (8, 1)),
+    shift("Exod", (22, 1, 1), (21, 37)),
+    shift("Exod", (22, 2, 31), (22, 1)),
+    shift("Lev", (6, 1, 7), (5, 20)),
+    shift("Lev", (6, 8, 30), (6, 1)),
+    shift("Num", (16, 36, 50), (17, 1)),
+    shift("Num", (17, 1, 13), (17, 16)),
+    shift("Num", (29, 40, 40), (30, 1)),
+    shift("Num", (30, 1, 16), (30, 2)),
+    shift("Deut", (12, 32, 32), (13, 1)),
+    shift("Deut", (13, 1, 18), (13, 2)),
+    shift("Deut", (22, 30, 30), (23, 1)),
+    shift("Deut", (23, 1, 25), (23, 2)),
+    shift("Deut", (29, 1, 1), (28, 69)),
+    shift("Deut", (29, 2, 29), (29, 1)),
+    shift("1Sam", (23, 29, 29), (24, 1)),
+    shift("1Sam", (24, 1, 22), (24, 2)),
+    shift("2Sam", (18, 33, 33), (19, 1)),
+    shift("2Sam", (19, 1, 43), (19, 2)),
+    shift("1Kgs", (4, 21, 34), (5, 1)),
+    shift("1Kgs", (5, 1, 18), (5, 15)),
+    shift("1Kgs", (22, 44, 53), (22, 45)),
+    shift("2Kgs", (11, 21, 21), (12, 1)),
+    shift("2Kgs", (12, 1, 21), (12, 2)),
+    shift("1Chr", (6, 1, 15), (5, 27)),
+    shift("1Chr", (6, 16, 81), (6, 1)),
+    shift("1Chr", (12, 5, 40), (12, 6)),
+    shift("2Chr", (2, 1, 1), (1, 18)),
+    shift("2Chr", (2, 2, 18), (2, 1)),
+    shift("2Chr", (14, 1, 1), (13, 23)),
+    shift("2Chr", (14, 2, 15), (14, 1)),
+    shift("Neh", (4, 1, 6), (3, 33)),
+    shift("Neh", (4, 7, 23), (4, 1)),
+    shift("Neh", (9, 38, 38), (10, 1)),
+    shift("Neh", (10, 1, 39), (10, 2)),
+    shift("Job", (40, 1, 5), (39, 31)),
+    shift("Job", (40, 6, 24), (40, 1)),
+    shift("Job", (41, 1, 8), (40, 25)),
+    shift("Job", (41, 9, 34), (41, 1)),
+    shift("Eccl", (5, 1, 1), (4, 17)),
+    shift("Eccl", (5, 2, 20), (5, 1)),
+    shift("Song", (6, 13, 13), (7, 1)),
+    shift("Song", (7, 1, 13), (7, 2)),
+    shift("Isa", (9, 1, 1), (8, 23)),
+    shift("Isa", (9, 2, 21), (9, 1)),
+    shift("Isa", (64, 1, 1), (63, 19)),
+    shift("Isa", (64, 2, 12), (64, 1)),
+    shift("Jer", (9, 1, 1), (8, 23)),
+    shift("Jer", (9, 2, 26), (9, 1)),
+    shift("Ezek", (20, 45, 49), (21, 1)),
+    shift("Ezek", (21, 1, 32), (21, 6)),
+    shift("Dan", (4, 1, 3), (3, 31)),
+    shift("Dan", (4, 4, 37), (4, 1)),
+    shift("Dan", (5, 31, 31), (6, 1)),
+    shift("Dan", (6, 1, 28), (6, 2)),
+    shift("Hos", (1, 10, 11), (2, 1)),
+    shift("Hos", (2, 1, 23), (2, 3)),
+    shift("Hos", (11, 12, 12), (12, 1)),
+    shift("Hos", (12, 1, 14), (12, 2)),
+    shift("Hos", (13, 16, 16), (14, 1)),
+    shift("Hos", (14, 1, 9), (14, 2)),
+    shift("Joel", (2, 28, 32), (3, 1)),
+    shift("Joel", (3, 1, 21), (4, 1)),
+    shift("Jonah", (1, 17, 17), (2, 1)),
+    shift("Jonah", (2, 1, 10), (2, 2)),
+    shift("Mic", (5, 1, 1), (4, 14)),
+    shift("Mic", (5, 2, 15), (5, 1)),
+    shift("Nah", (1, 15, 15), (2, 1)),
+    shift("Nah", (2, 1, 13), (2, 2)),
+    shift("Zech", (1, 18, 21), (2, 1)),
+    shift("Zech", (2, 1, 13), (2, 5)),
+    shift("Mal", (4, 1, 6), (3, 19)),
+];
+
+/// Psalms whose Hebrew numbering counts the title as verse 1 (or, for
+/// the last four, verses 1 and 2).
+const TITLED_PSALMS: &[i64] = &[
+    3, 4, 5, 6, 7, 8, 9, 12, 13, 18, 19, 20, 21, 22, 30, 31, 34, 36, 38, 39, 40, 41, 42, 44, 45,
+    46, 47, 48, 49, 53, 55, 56, 57, 58, 59, 61, 62, 63, 64, 65, 67, 68, 69, 70, 75, 76, 77, 80, 81,
+    83, 84, 85, 88, 89, 92, 102, 108, 140, 142,
+];
+const DOUBLE_TITLED_PSALMS: &[i64] = &[51, 52, 54, 60];
+
+fn title_verses(psalm: i64) -> i64 {
+    if DOUBLE_TITLED_PSALMS.contains(&psalm) {
+        2
+    } else if TITLED_PSALMS.contains(&psalm) {
+        1
+    } else {
+        0
+    }
+}
+
+/// `chapter`:`verse` of `book` in `scheme`, numbered as the KJV does.
+/// A Hebrew psalm title has no KJV verse and maps to verse 0.
+pub(crate) fn to_kjv(scheme: Scheme, book: &str, chapter: i64, verse: i64) -> (i64, i64) {
+    if scheme == Scheme::Kjv {
+        return (chapter, verse);
+    }
+    if book == "Ps" {
+        let titles = title_verses(chapter);
+        return (chapter, if verse <= titles { 0 } else { verse - titles });
+    }
+    HEBREW_SHIFTS
+        .iter()
+        .filter(|s| s.book == book && s.to_chapter == chapter)
+        .find(|s| (s.to_first..=s.to_first + s.last - s.first).contains(&verse))
+        .map(|s| (s.chapter, s.first + verse - s.to_first))
+        .unwrap_or((chapter, verse))
+}
+
+/// KJV `chapter`:`verse` of `book` as `scheme` numbers it.
+pub(crate) fn from_kjv(scheme: Scheme, book: &str, chapter: i64, verse: i64) -> (i64, i64) {
+    if scheme == Scheme::Kjv {
+        return (chapter, verse);
+    }
+    if book == "Ps" {
+        return (chapter, verse + title_verses(chapter));
+    }
+    HEBREW_SHIFTS
+        .iter()
+        .filter(|s| s.book == book && s.chapter == chapter)
+        .find(|s| (s.first..=s.last).contains(&verse))
+        .map(|s| (s.to_chapter, s.to_first + verse - s.first))
+        .unwrap_or((chapter, verse))
+}
+
+/// `chapter`:`verse` of `book` numbered in `from`, as `to` numbers it.
+pub(crate) fn map(from: Scheme, to: Scheme, book: &str, chapter: i64, verse: i64) -> (i64, i64) {
+    if from == to {
+        return (chapter, verse);
+    }
+    let (chapter, verse) = to_kjv(from, book, chapter, verse);
+    if verse == 0 {
+        return (chapter, 0);
+    }
+    from_kjv(to, book, chapter, verse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_hebrew_chapter_breaks_and_psalm_titles() {
+        let hebrew = Scheme::named(Some("Leningrad"));
+        assert_eq!(hebrew, Scheme::Hebrew);
+        assert_eq!(Scheme::named(None), Scheme::Kjv);
+        assert_eq!(from_kjv(hebrew, "Mal", 4, 5), (3, 23));
+        assert_eq!(to_kjv(hebrew, "Mal", 3, 23), (4, 5));
+        assert_eq!(from_kjv(hebrew, "Joel", 3, 1), (4, 1));
+        assert_eq!(to_kjv(hebrew, "Joel", 3, 1), (2, 28));
+        assert_eq!(to_kjv(hebrew, "Gen", 31, 54), (31, 54));
+        assert_eq!(from_kjv(hebrew, "Ps", 51, 1), (51, 3));
+        assert_eq!(to_kjv(hebrew, "Ps", 51, 2), (51, 0));
+        assert_eq!(map(hebrew, Scheme::Kjv, "Ps", 23, 1), (23, 1));
+        // Every shifted range maps back to where it came from.
+        for s in HEBREW_SHIFTS {
+            for verse in s.first..=s.last {
+                let (c, v) = from_kjv(hebrew, s.book, s.chapter, verse);
+                assert_eq!(
+                    to_kjv(hebrew, s.book, c, v),
+                    (s.chapter, verse),
+                    "{}",
+                    s.book
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,358 @@
+//! Strong's numbers and morphology of the words in tagged translations, for
+//! looking a word up from the reader.
+//!
+//! Importers record the words a source tags (see `import::Word`) in the
+//! content file's `words` table, by character offset into the verse text.
+//! The reader counts a verse's words as runs of non-whitespace, so a tapped
+//! word is matched with the tagged words overlapping it: `LORD's` and
+//! `beginning,` find the tags on `LORD` and `beginning`, and a tag spanning
+//! `the Word` answers for both words.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use super::morphology::{self, Parsing};
+use super::mounted;
+use crate::db::{self, DbError, DbErrorKind};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VerseRef {
+    /// OSIS book id.
+    pub book: String,
+    pub chapter: i64,
+    pub verse: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WordData {
+    /// The word as the reader shows it, punctuation and all.
+    pub word: String,
+    /// Strong's numbers (`H7225`, `G3056`) of the word; empty when the
+    /// source left it untagged.
+    pub strongs: Vec<String>,
+    pub gloss: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Morphology {
+    pub word: String,
+    /// One per morphology code on the word; empty when the source gave it
+    /// none.
+    pub parsings: Vec<Parsing>,
+}
+
+/// The words of `text` counted as runs of non-whitespace: each one's start
+/// and end character offsets and the word itself.
+pub(super) fn word_spans(text: &str) -> Vec<(usize, usize, &str)> {
+    let mut offset = 0;
+    let mut words = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    for (byte, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((offset, byte)),
+            (true, Some((chars, from))) => {
+                words.push((chars, offset, &text[from..byte]));
+                start = None;
+            }
+            _ => {}
+        }
+        offset += 1;
+    }
+    if let Some((chars, from)) = start {
+        words.push((chars, offset, &text[from..]));
+    }
+    words
+}
+
+/// Word `index` of `text` as `word_spans` counts them.
+fn nth_word(text: &str, index: usize) -> Option<(usize, usize, &str)> {
+    word_spans(text).get(index).copied()
+}
+
+/// A row of `words`.
+struct Tagged {
+    strongs: String,
+    gloss: Option<String>,
+    morph: Option<String>,
+}
+
+/// Word `word_index` of a verse in the content attached as `schema`, and
+/// the tagged words overlapping it.
+fn tagged_under(
+    conn: &Connection,
+    schema: &str,
+    verse: &VerseRef,
+    word_index: usize,
+) -> Result<(String, Vec<Tagged>), DbError> {
+    // Files from before format 4 have no `words`, and untagged
+    // translations none in it.
+    let tagged: bool = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) > 0 FROM \"{schema}\".sqlite_master
+             WHERE type = 'table' AND name = 'words'"
+        ),
+        [],
+        |row| row.get(0),
+    )?;
+    let tagged = tagged
+        && conn.query_row(
+            &format!("SELECT EXISTS (SELECT 1 FROM \"{schema}\".words)"),
+            [],
+            |row| row.get(0),
+        )?;
+    if !tagged {
+        return Err(DbError::new(
+            DbErrorKind::NotFound,
+            "This translation has no tagged words",
+        ));
+    }
+
+    let text: String = conn
+        .query_row(
+            &format!(
+                "SELECT text FROM \"{schema}\".verses
+                 WHERE book = ?1 AND chapter = ?2 AND verse = ?3"
+            ),
+            params![verse.book, verse.chapter, verse.verse],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            DbError::new(
+                DbErrorKind::NotFound,
+                format!("{} {}:{} not found", verse.book, verse.chapter, verse.verse),
+            )
+        })?;
+    let (start, end, word) = nth_word(&text, word_index).ok_or_else(|| {
+        DbError::invalid(format!(
+            "{} {}:{} has no word {word_index}",
+            verse.book, verse.chapter, verse.verse
+        ))
+    })?;
+
+    // Format 4 files have no `morph`.
+    let has_morph: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('words', ?1) WHERE name = 'morph'",
+        [schema],
+        |row| row.get(0),
+    )?;
+    let morph = if has_morph { "morph" } else { "NULL" };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT position, text, strongs, gloss, {morph} FROM \"{schema}\".words
+         WHERE book = ?1 AND chapter = ?2 AND verse = ?3 ORDER BY seq"
+    ))?;
+    let rows = stmt.query_map(params![verse.book, verse.chapter, verse.verse], |row| {
+        Ok((
+            row.get::<_, i64>(0)? as usize,
+            row.get::<_, String>(1)?,
+            Tagged {
+                strongs: row.get(2)?,
+                gloss: row.get(3)?,
+                morph: row.get(4)?,
+            },
+        ))
+    })?;
+    let mut under = Vec::new();
+    for row in rows {
+        let (position, tagged, row) = row?;
+        if position < end && position + tagged.chars().count() > start {
+            under.push(row);
+        }
+    }
+    Ok((word.to_string(), under))
+}
+
+/// The Strong's numbers of word `word_index` of a verse in the content
+/// attached as `schema`.
+pub(crate) fn word_data(
+    conn: &Connection,
+    schema: &str,
+    verse: &VerseRef,
+    word_index: usize,
+) -> Result<WordData, DbError> {
+    let (word, under) = tagged_under(conn, schema, verse, word_index)?;
+    let mut data = WordData {
+        word,
+        strongs: Vec::new(),
+        gloss: None,
+    };
+    for tagged in under {
+        for number in tagged.strongs.split_whitespace() {
+            if !data.strongs.iter().any(|n| n == number) {
+                data.strongs.push(number.to_string());
+            }
+        }
+        data.gloss = data.gloss.or(tagged.gloss);
+    }
+    Ok(data)
+}
+
+/// The parsing of word `word_index` of a verse in the content attached as
+/// `schema`.
+pub(crate) fn morphology(
+    conn: &Connection,
+    schema: &str,
+    verse: &VerseRef,
+    word_index: usize,
+) -> Result<Morphology, DbError> {
+    let (word, under) = tagged_under(conn, schema, verse, word_index)?;
+    let mut parsings: Vec<Parsing> = Vec::new();
+    for code in under.into_iter().filter_map(|t| t.morph) {
+        if !parsings.iter().any(|p| p.code == code) {
+            parsings.push(morphology::decode(&code));
+        }
+    }
+    Ok(Morphology { word, parsings })
+}
+
+/// Strong's numbers and gloss of word `word_index` (0-based, counting runs
+/// of non-whitespace) of `verse` in mounted translation `module_id`.
+#[command]
+pub async fn get_word_data(
+    app: tauri::AppHandle,
+    module_id: String,
+    verse: VerseRef,
+    word_index: usize,
+) -> Result<WordData, DbError> {
+    let mounted = mounted(&module_id).ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("Translation `{module_id}` is not mounted"),
+        )
+    })?;
+    db::with_reader(&app, move |conn| {
+        word_data(conn, &mounted.schema, &verse, word_index)
+    })
+    .await
+}
+
+/// Parsing of word `word_index` (counted as for `get_word_data`) of
+/// `verse` in mounted translation `module_id`.
+#[command]
+pub async fn get_morphology(
+    app: tauri::AppHandle,
+    module_id: String,
+    verse: VerseRef,
+    word_index: usize,
+) -> Result<Morphology, DbError> {
+    let mounted = mounted(&module_id).ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("Translation `{module_id}` is not mounted"),
+        )
+    })?;
+    db::with_reader(&app, move |conn| {
+        morphology(conn, &mounted.schema, &verse, word_index)
+    })
+    .await
+}
+
+/// Read a morphology code (`V-PAI-3S`, `HVqp3ms`) as parsing.
+#[command]
+pub fn decode_morphology(code: String) -> Parsing {
+    morphology::decode(&code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::create;
+    use crate::test_util::ScratchDir;
+
+    fn john(verse: i64) -> VerseRef {
+        VerseRef {
+            book: "John".into(),
+            chapter: 1,
+            verse,
+        }
+    }
+
+    #[test]
+    fn finds_the_tags_under_a_tapped_word() {
+        let dir = ScratchDir::new("words");
+        let conn = create(&dir.join("kjv.db"), "kjv", "KJV").unwrap();
+        conn.execute_batch(
+            "INSERT INTO verses VALUES ('John', 1, 1, 'In the beginning was the Word,');
+             INSERT INTO verses VALUES ('John', 1, 2, 'The same was in the beginning.');
+             INSERT INTO words VALUES ('John', 1, 1, 0, 7, 'beginning', 'G746', 'beginning', 'N-DSF');
+             INSERT INTO words VALUES ('John', 1, 1, 1, 21, 'the Word', 'G3588 G3056', NULL, NULL);",
+        )
+        .unwrap();
+
+        let data = word_data(&conn, "main", &john(1), 2).unwrap();
+        assert_eq!(
+            data,
+            WordData {
+                word: "beginning".into(),
+                strongs: vec!["G746".into()],
+                gloss: Some("beginning".into()),
+            }
+        );
+        let word = word_data(&conn, "main", &john(1), 5).unwrap();
+        assert_eq!((word.word.as_str(), word.gloss), ("Word,", None));
+        assert_eq!(word.strongs, ["G3588", "G3056"]);
+        assert!(word_data(&conn, "main", &john(1), 1)
+            .unwrap()
+            .strongs
+            .is_empty());
+        assert!(word_data(&conn, "main", &john(2), 0)
+            .unwrap()
+            .strongs
+            .is_empty());
+
+        assert_eq!(
+            word_data(&conn, "main", &john(1), 6).unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+        assert_eq!(
+            word_data(&conn, "main", &john(3), 0).unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+        conn.execute_batch("DROP TABLE words").unwrap();
+        assert_eq!(
+            word_data(&conn, "main", &john(1), 2).unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+        drop(conn);
+    }
+
+    #[test]
+    fn parses_the_tapped_word() {
+        let dir = ScratchDir::new("morph");
+        let conn = create(&dir.join("na28.db"), "na28", "NA28").unwrap();
+        conn.execute_batch(
+            "INSERT INTO verses VALUES ('John', 1, 1, 'In the beginning was the Word');
+             INSERT INTO words VALUES ('John', 1, 1, 0, 17, 'was', 'G2258', NULL, 'V-IAI-3S');
+             INSERT INTO words VALUES ('John', 1, 1, 1, 21, 'the', 'G3588', NULL, 'T-NSM');
+             INSERT INTO words VALUES ('John', 1, 1, 2, 25, 'Word', 'G3056', NULL, NULL);",
+        )
+        .unwrap();
+
+        let was = morphology(&conn, "main", &john(1), 3).unwrap();
+        assert_eq!(was.word, "was");
+        assert_eq!(
+            was.parsings[0].description.as_deref(),
+            Some("Verb: imperfect active indicative, third person singular")
+        );
+        assert!(morphology(&conn, "main", &john(1), 5)
+            .unwrap()
+            .parsings
+            .is_empty());
+
+        // Format 4 files tag words without morphology.
+        conn.execute_batch(
+            "DROP TABLE words;
+             CREATE TABLE words (book, chapter, verse, seq, position, text, strongs, gloss);
+             INSERT INTO words VALUES ('John', 1, 1, 0, 17, 'was', 'G2258', NULL);",
+        )
+        .unwrap();
+        let was = morphology(&conn, "main", &john(1), 3).unwrap();
+        assert!(was.parsings.is_empty());
+        assert_eq!(
+            word_data(&conn, "main", &john(1), 3).unwrap().strongs,
+            ["G2258"]
+        );
+        drop(conn);
+    }
+}
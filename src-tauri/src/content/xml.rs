@@ -0,0 +1,364 @@
+//! Just enough XML for the importers' formats (USX, DBL metadata, Zefania,
+//! OpenSong): elements, attributes, text and the predefined and numeric
+//! entities. Comments, processing instructions and the doctype are skipped;
+//! DTDs and namespaces are not interpreted.
+
+use std::borrow::Cow;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Event<'a> {
+    Start {
+        name: &'a str,
+        attrs: Vec<(&'a str, String)>,
+        /// `<name/>`: no `End` follows.
+        empty: bool,
+        line: usize,
+    },
+    End {
+        name: &'a str,
+        line: usize,
+    },
+    Text(Cow<'a, str>),
+}
+
+/// Value of attribute `name` in a `Start`'s `attrs`.
+pub(crate) fn attr<'x>(attrs: &'x [(&str, String)], name: &str) -> Option<&'x str> {
+    attrs
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Replace entity and character references in `text`. Unknown entities are
+/// kept as written.
+pub(crate) fn decode(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.bytes().take(12).position(|b| b == b';') else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+fn is_name_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b":_-.".contains(&b) || b >= 0x80
+}
+
+/// The events of `src`, or the line and reason it stops being XML. Tags
+/// must nest; text outside the root element is returned like any other.
+pub(crate) fn parse(src: &str) -> Result<Vec<Event<'_>>, (usize, String)> {
+    let bytes = src.as_bytes();
+    let mut events = Vec::new();
+    let mut open: Vec<&str> = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    let lines = |from: usize, to: usize| bytes[from..to].iter().filter(|&&b| b == b'\n').count();
+    while i < bytes.len() {
+        if bytes[i] != b'<' {
+            let start = i;
+            while i < bytes.len() && bytes[i] != b'<' {
+                i += 1;
+            }
+            line += lines(start, i);
+            events.push(Event::Text(decode(&src[start..i])));
+            continue;
+        }
+        let rest = &src[i..];
+        // Markup that isn't an element.
+        let skip_to = |end: &str, what: &str| {
+            rest.find(end)
+                .map(|at| at + end.len())
+                .ok_or((line, format!("Unterminated {what}")))
+        };
+        if rest.starts_with("<!--") {
+            let len = skip_to("-->", "comment")?;
+            line += lines(i, i + len);
+            i += len;
+            continue;
+        }
+        if let Some(body) = rest.strip_prefix("<![CDATA[") {
+            let len = body
+                .find("]]>")
+                .ok_or((line, "Unterminated CDATA section".to_string()))?;
+            events.push(Event::Text(Cow::Borrowed(&body[..len])));
+            let total = "<![CDATA[".len() + len + 3;
+            line += lines(i, i + total);
+            i += total;
+            continue;
+        }
+        if rest.starts_with("<?") {
+            let len = skip_to("?>", "processing instruction")?;
+            line += lines(i, i + len);
+            i += len;
+            continue;
+        }
+        if rest.starts_with("<!") {
+            // A doctype, possibly with an internal subset in brackets.
+            let end = match (rest.find('['), rest.find('>')) {
+                (Some(open), Some(close)) if open < close => rest
+                    .find("]>")
+                    .map(|at| at + 2)
+                    .ok_or((line, "Unterminated doctype".to_string()))?,
+                (_, Some(close)) => close + 1,
+                _ => return Err((line, "Unterminated doctype".to_string())),
+            };
+            line += lines(i, i + end);
+            i += end;
+            continue;
+        }
+
+        let tag_line = line;
+        let closing = bytes.get(i + 1) == Some(&b'/');
+        let name_start = i + if closing { 2 } else { 1 };
+        let mut j = name_start;
+        while j < bytes.len() && is_name_byte(bytes[j]) {
+            j += 1;
+        }
+        let name = &src[name_start..j];
+        if name.is_empty() {
+            return Err((line, "Stray `<` in text".to_string()));
+        }
+        let mut attrs = Vec::new();
+        let mut empty = false;
+        loop {
+            while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                if bytes[j] == b'\n' {
+                    line += 1;
+                }
+                j += 1;
+            }
+            match bytes.get(j) {
+                None => return Err((tag_line, format!("Unterminated tag `<{name}`"))),
+                Some(b'>') => {
+                    j += 1;
+                    break;
+                }
+                Some(b'/') if !closing && bytes.get(j + 1) == Some(&b'>') => {
+                    empty = true;
+                    j += 2;
+                    break;
+                }
+                Some(_) if closing => {
+                    return Err((line, format!("Unexpected text in `</{name}>`")));
+                }
+                Some(_) => {}
+            }
+            let key_start = j;
+            while j < bytes.len() && is_name_byte(bytes[j]) {
+                j += 1;
+            }
+            let key = &src[key_start..j];
+            while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                j += 1;
+            }
+            if key.is_empty() || bytes.get(j) != Some(&b'=') {
+                return Err((line, format!("Malformed attribute in `<{name}>`")));
+            }
+            j += 1;
+            while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                j += 1;
+            }
+            let quote = match bytes.get(j) {
+                Some(&q) if q == b'"' || q == b'\'' => q,
+                _ => return Err((line, format!("Unquoted attribute `{key}` in `<{name}>`"))),
+            };
+            let value_start = j + 1;
+            let Some(len) = bytes[value_start..].iter().position(|&b| b == quote) else {
+                return Err((
+                    line,
+                    format!("Unterminated attribute `{key}` in `<{name}>`"),
+                ));
+            };
+            let value = &src[value_start..value_start + len];
+            line += lines(value_start, value_start + len);
+            attrs.push((key, decode(value).into_owned()));
+            j = value_start + len + 1;
+        }
+        i = j;
+
+        if closing {
+            match open.pop() {
+                Some(expected) if expected == name => {}
+                Some(expected) => {
+                    return Err((tag_line, format!("`</{name}>` closes `<{expected}>`")))
+                }
+                None => return Err((tag_line, format!("`</{name}>` was never opened"))),
+            }
+            events.push(Event::End {
+                name,
+                line: tag_line,
+            });
+        } else {
+            if !empty {
+                open.push(name);
+            }
+            events.push(Event::Start {
+                name,
+                attrs,
+                empty,
+                line: tag_line,
+            });
+        }
+    }
+    if let Some(name) = open.pop() {
+        return Err((line, format!("`<{name}>` is never closed")));
+    }
+    Ok(events)
+}
+
+/// An element with its children, for small documents read as a whole.
+#[derive(Debug, Default)]
+pub(crate) struct Node {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+    /// Text directly inside the element, concatenated.
+    pub text: String,
+}
+
+impl Node {
+    pub(crate) fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    pub(crate) fn children<'n>(&'n self, name: &'n str) -> impl Iterator<Item = &'n Node> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Trimmed text of the child at `path` (`identification/name`).
+    pub(crate) fn text_at(&self, path: &str) -> Option<&str> {
+        let mut node = self;
+        for name in path.split('/') {
+            node = node.child(name)?;
+        }
+        Some(node.text.trim()).filter(|t| !t.is_empty())
+    }
+}
+
+/// The root element of `src`.
+pub(crate) fn tree(src: &str) -> Result<Node, (usize, String)> {
+    let mut stack: Vec<Node> = Vec::new();
+    let mut root = None;
+    for event in parse(src)? {
+        match event {
+            Event::Start {
+                name, attrs, empty, ..
+            } => {
+                let node = Node {
+                    name: name.to_string(),
+                    attrs: attrs
+                        .into_iter()
+                        .map(|(key, value)| (key.to_string(), value))
+                        .collect(),
+                    ..Default::default()
+                };
+                if empty {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(node),
+                        None => root = root.or(Some(node)),
+                    }
+                } else {
+                    stack.push(node);
+                }
+            }
+            Event::End { .. } => {
+                let node = stack.pop().expect("parse checks nesting");
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => root = root.or(Some(node)),
+                }
+            }
+            Event::Text(text) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(&text);
+                }
+            }
+        }
+    }
+    root.ok_or((1, "No root element".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_elements_attributes_and_entities() {
+        let src = "<?xml version=\"1.0\"?>\n<!DOCTYPE usx>\n<!-- c -->\n<usx version='3.0'>\n  <para style=\"p\">A &amp; B&#x2019;s &lt;x&gt;<verse number=\"1\"\n style=\"v\"/><![CDATA[<raw>]]></para>\n</usx>";
+        let events = parse(src).unwrap();
+        let starts: Vec<(&str, usize)> = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::Start { name, line, .. } => Some((*name, *line)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(starts, [("usx", 4), ("para", 5), ("verse", 5)]);
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::Text(t) => Some(t.as_ref()),
+                _ => None,
+            })
+            .collect();
+        assert!(text.contains("A & B\u{2019}s <x><raw>"), "{text:?}");
+
+        let root = tree(src).unwrap();
+        let para = root.child("para").unwrap();
+        assert_eq!(para.attr("style"), Some("p"));
+        assert_eq!(para.child("verse").unwrap().attr("number"), Some("1"));
+    }
+
+    #[test]
+    fn reports_where_it_stops_being_xml() {
+        assert_eq!(
+            parse("<a>\n<b></a>").unwrap_err(),
+            (2, "`</a>` closes `<b>`".to_string())
+        );
+        assert_eq!(parse("<a>\n\ntext").unwrap_err().0, 3);
+        assert!(parse("<a x=1/>").is_err());
+    }
+}
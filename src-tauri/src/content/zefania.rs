@@ -0,0 +1,524 @@
+//! Zefania XML and OpenSong import.
+//!
+//! Both formats are one XML file per Bible holding books, chapters and
+//! verses, and between them cover most of the free translations shared in
+//! other languages. Zefania marks them `<BIBLEBOOK bnumber="1">`,
+//! `<CHAPTER cnumber="1">` and `<VERS vnumber="1">`, with `<CAPTION>`
+//! headings, `<NOTE>`s and `<gr str="3056" rmac="N-NSM">` Strong's numbers
+//! and morphology; OpenSong marks them `<b n="Genesis">`, `<c n="1">` and
+//! `<v n="1">`, naming books rather than numbering them.
+//! Tag names are read in any case, since files in the wild differ.
+
+use std::path::PathBuf;
+use tauri::command;
+
+use super::books;
+use super::import::{self, Book, Footnote, Heading, ImportReport, Verse, Warnings};
+use super::xml::{self, Event};
+use crate::db::DbError;
+
+/// Element and attribute names of one format, lowercase.
+struct Dialect {
+    root: &'static str,
+    book: &'static str,
+    chapter: &'static str,
+    verse: &'static str,
+    /// Attribute numbering books in canon order (Zefania), if any.
+    book_number: Option<&'static str>,
+    book_name: &'static str,
+    chapter_number: &'static str,
+    verse_number: &'static str,
+    heading: Option<&'static str>,
+    note: Option<&'static str>,
+    /// Element tagging a word with Strong's numbers (in its `str`), if any.
+    word: Option<&'static str>,
+    /// Elements whose text is dropped.
+    dropped: &'static [&'static str],
+}
+
+const ZEFANIA: Dialect = Dialect {
+    root: "xmlbible",
+    book: "biblebook",
+    chapter: "chapter",
+    verse: "vers",
+    book_number: Some("bnumber"),
+    book_name: "bname",
+    chapter_number: "cnumber",
+    verse_number: "vnumber",
+    heading: Some("caption"),
+    note: Some("note"),
+    word: Some("gr"),
+    dropped: &["information", "prolog", "remark", "xref", "media"],
+};
+
+const OPENSONG: Dialect = Dialect {
+    root: "bible",
+    book: "b",
+    chapter: "c",
+    verse: "v",
+    book_number: None,
+    book_name: "n",
+    chapter_number: "n",
+    verse_number: "n",
+    heading: None,
+    note: None,
+    word: None,
+    dropped: &[],
+};
+
+/// `number` as a positive integer, or one past `last` when it's missing.
+fn number_or_next(number: Option<&str>, last: Option<i64>) -> Option<i64> {
+    match number.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(n) => {
+            let digits: String = n.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok().filter(|&n: &i64| n > 0)
+        }
+        None => Some(last.unwrap_or(0) + 1),
+    }
+}
+
+struct Parser<'w> {
+    dialect: &'static Dialect,
+    file: String,
+    warnings: &'w mut Warnings,
+    books: Vec<Book>,
+    book: Option<Book>,
+    /// `<b>`/`<BIBLEBOOK>` elements seen, for books matched by place.
+    seen_books: usize,
+    matched_by_place: bool,
+    chapter: Option<i64>,
+    verse: Option<usize>,
+    /// Open elements whose text is dropped.
+    dropping: usize,
+    heading: Option<String>,
+    pending: Vec<String>,
+    /// Text, anchor offset and `import::note_kind` of the open note.
+    note: Option<(String, Option<usize>, String)>,
+    /// Start in the verse text, Strong's numbers and morphology of the open
+    /// `<gr>`.
+    word: Option<(usize, String, Option<String>)>,
+    line: usize,
+}
+
+impl<'w> Parser<'w> {
+    fn warn(&mut self, message: impl Into<String>) {
+        let line = Some(self.line);
+        self.warnings.push(&self.file, line, message);
+    }
+
+    fn end_book(&mut self) {
+        if let Some(book) = self.book.take() {
+            if !self.pending.is_empty() {
+                self.warn("Dropped headings after the last verse");
+            }
+            self.books.push(book);
+        }
+        self.pending.clear();
+        self.chapter = None;
+        self.verse = None;
+    }
+
+    fn start_book(&mut self, attrs: &[(&str, String)]) {
+        self.end_book();
+        self.seen_books += 1;
+        let name = xml::attr(attrs, self.dialect.book_name)
+            .map(str::trim)
+            .filter(|n| !n.is_empty());
+        let found = match self.dialect.book_number {
+            Some(key) => {
+                let number = xml::attr(attrs, key).unwrap_or_default().trim();
+                let found = number
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|i| books::BOOKS.get(i));
+                if found.is_none() {
+                    self.warn(format!(
+                        "Book {number} is not one BibleMarker shows; skipped"
+                    ));
+                }
+                found
+            }
+            None => match name.and_then(books::by_name) {
+                Some(book) => Some(book),
+                // Translated book names: fall back on the canon order.
+                None => {
+                    let by_place = books::BOOKS.get(self.seen_books - 1);
+                    if by_place.is_some() && !self.matched_by_place {
+                        self.matched_by_place = true;
+                        self.warn(format!(
+                            "Book name `{}` not recognized; books without a known name \
+                             were matched by their place in the file",
+                            name.unwrap_or_default()
+                        ));
+                    } else if by_place.is_none() {
+                        self.warn(format!(
+                            "Book `{}` is not one BibleMarker shows; skipped",
+                            name.unwrap_or_default()
+                        ));
+                    }
+                    by_place
+                }
+            },
+        };
+        self.book = found.map(|book| Book {
+            id: book.osis.to_string(),
+            name: name.map(str::to_string),
+            file: self.file.clone(),
+            ..Default::default()
+        });
+    }
+
+    fn start_chapter(&mut self, attrs: &[(&str, String)]) {
+        let number = xml::attr(attrs, self.dialect.chapter_number);
+        match number_or_next(number, self.chapter) {
+            Some(n) => self.chapter = Some(n),
+            None => self.warn(format!(
+                "`{}` is not a chapter number",
+                number.unwrap_or_default()
+            )),
+        }
+        self.verse = None;
+    }
+
+    fn start_verse(&mut self, attrs: &[(&str, String)]) {
+        self.verse = None;
+        let Some(chapter) = self.chapter else {
+            self.warn("Verse outside any chapter; dropped");
+            return;
+        };
+        let last = self
+            .book
+            .as_ref()
+            .and_then(|book| book.verses.last())
+            .filter(|v| v.chapter == chapter)
+            .map(|v| v.through);
+        let number = xml::attr(attrs, self.dialect.verse_number);
+        let Some(verse) = number_or_next(number, last) else {
+            self.warn(format!(
+                "`{}` is not a verse number",
+                number.unwrap_or_default()
+            ));
+            return;
+        };
+        let through = number
+            .and_then(|n| n.split_once('-'))
+            .and_then(|(_, last)| last.trim().parse().ok())
+            .filter(|&last| last > verse)
+            .unwrap_or(verse);
+        let line = Some(self.line);
+        let pending = std::mem::take(&mut self.pending);
+        let Some(book) = &mut self.book else {
+            return;
+        };
+        for text in pending {
+            book.headings.push(Heading {
+                chapter,
+                verse,
+                style: "s1".into(),
+                text,
+            });
+        }
+        book.verses.push(Verse {
+            chapter,
+            verse,
+            through,
+            line,
+            ..Default::default()
+        });
+        self.verse = Some(book.verses.len() - 1);
+    }
+
+    fn end_verse(&mut self) {
+        if let (Some(i), Some(book)) = (self.verse, &mut self.book) {
+            let text = &mut book.verses[i].text;
+            text.truncate(text.trim_end().len());
+        }
+        self.verse = None;
+    }
+
+    fn open_note(&mut self, attrs: &[(&str, String)]) {
+        let anchor = match (self.verse, &self.book) {
+            (Some(i), Some(book)) => Some(book.verses[i].text.trim_end().chars().count()),
+            _ => None,
+        };
+        let kind = import::note_kind(xml::attr(attrs, "type"));
+        self.note = Some((String::new(), anchor, kind));
+    }
+
+    fn close_note(&mut self) {
+        let Some((text, anchor, kind)) = self.note.take() else {
+            return;
+        };
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        match (anchor, self.verse, &mut self.book) {
+            (Some(position), Some(i), Some(book)) => {
+                let (chapter, verse) = (book.verses[i].chapter, book.verses[i].verse);
+                book.footnotes.push(Footnote {
+                    chapter,
+                    verse,
+                    caller: "+".into(),
+                    position,
+                    text,
+                    kind,
+                });
+            }
+            _ => self.warn("Dropped a note outside verse text"),
+        }
+    }
+
+    fn start(&mut self, name: &str, attrs: &[(&str, String)], empty: bool) {
+        let d = self.dialect;
+        if name == d.book {
+            self.start_book(attrs);
+        } else if self.book.is_some() && self.dropping == 0 {
+            if name == d.chapter {
+                self.start_chapter(attrs);
+            } else if name == d.verse {
+                self.start_verse(attrs);
+            } else if Some(name) == d.heading && !empty {
+                self.heading = Some(String::new());
+            } else if Some(name) == d.note && !empty && self.note.is_none() {
+                self.open_note(attrs);
+            } else if Some(name) == d.word && !empty && self.note.is_none() {
+                if let (Some(i), Some(book)) = (self.verse, &self.book) {
+                    let strongs = xml::attr(attrs, "str").unwrap_or_default().to_string();
+                    let morph = xml::attr(attrs, "rmac").and_then(import::morph_code);
+                    self.word = Some((book.verses[i].text.len(), strongs, morph));
+                }
+            } else if name == "br" {
+                self.text(" ");
+            }
+        }
+        if d.dropped.contains(&name) && !empty {
+            self.dropping += 1;
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        let d = self.dialect;
+        if d.dropped.contains(&name) {
+            self.dropping = self.dropping.saturating_sub(1);
+            return;
+        }
+        if self.dropping > 0 {
+            return;
+        }
+        if name == d.book {
+            self.end_book();
+        } else if name == d.verse {
+            self.end_verse();
+        } else if Some(name) == d.heading {
+            if let Some(text) = self.heading.take() {
+                let text = text.trim().to_string();
+                if !text.is_empty() {
+                    self.pending.push(text);
+                }
+            }
+        } else if Some(name) == d.note {
+            self.close_note();
+        } else if Some(name) == d.word {
+            if let (Some((start, strongs, morph)), Some(i), Some(book)) =
+                (self.word.take(), self.verse, &mut self.book)
+            {
+                let strongs =
+                    import::strongs_numbers(&strongs, Some(import::strongs_prefix(&book.id)));
+                let verse = &mut book.verses[i];
+                import::tag_word(&mut verse.words, &verse.text, start, strongs, None, morph);
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.dropping > 0 || self.book.is_none() {
+            return;
+        }
+        if let Some((note, _, _)) = &mut self.note {
+            import::push_text(note, text);
+        } else if let Some(heading) = &mut self.heading {
+            import::push_text(heading, text);
+        } else if let (Some(i), Some(book)) = (self.verse, &mut self.book) {
+            import::push_text(&mut book.verses[i].text, text);
+        }
+    }
+}
+
+fn parse_with(
+    dialect: &'static Dialect,
+    src: &str,
+    file: &str,
+    warnings: &mut Warnings,
+) -> Vec<Book> {
+    let events = match xml::parse(src) {
+        Ok(events) => events,
+        Err((line, message)) => {
+            warnings.push(file, Some(line), format!("Not valid XML: {message}"));
+            return Vec::new();
+        }
+    };
+    let mut parser = Parser {
+        dialect,
+        file: file.to_string(),
+        warnings,
+        books: Vec::new(),
+        book: None,
+        seen_books: 0,
+        matched_by_place: false,
+        chapter: None,
+        verse: None,
+        dropping: 0,
+        heading: None,
+        pending: Vec::new(),
+        note: None,
+        word: None,
+        line: 1,
+    };
+    let mut saw_root = false;
+    for event in &events {
+        match event {
+            Event::Start {
+                name,
+                attrs,
+                empty,
+                line,
+            } => {
+                parser.line = *line;
+                let name = name.to_ascii_lowercase();
+                saw_root |= name == dialect.root;
+                parser.start(&name, attrs, *empty);
+                if *empty {
+                    parser.end(&name);
+                }
+            }
+            Event::End { name, line } => {
+                parser.line = *line;
+                parser.end(&name.to_ascii_lowercase());
+            }
+            Event::Text(text) => parser.text(text),
+        }
+    }
+    parser.end_book();
+    if !saw_root {
+        parser.warnings.push(
+            file,
+            None,
+            format!("No `<{}>` element; wrong format?", dialect.root),
+        );
+    }
+    parser.books
+}
+
+/// The books in a Zefania XML Bible.
+pub(crate) fn parse_zefania(src: &str, file: &str, warnings: &mut Warnings) -> Vec<Book> {
+    parse_with(&ZEFANIA, src, file, warnings)
+}
+
+/// The books in an OpenSong Bible.
+pub(crate) fn parse_opensong(src: &str, file: &str, warnings: &mut Warnings) -> Vec<Book> {
+    parse_with(&OPENSONG, src, file, warnings)
+}
+
+/// Import Zefania XML files as translation `module_id`, replacing its
+/// content file.
+#[command]
+pub async fn import_zefania(
+    app: tauri::AppHandle,
+    module_id: String,
+    name: Option<String>,
+    paths: Vec<String>,
+) -> Result<ImportReport, DbError> {
+    let name = name.unwrap_or_else(|| module_id.clone());
+    let paths = paths.into_iter().map(PathBuf::from).collect();
+    import::import_files(app, module_id, name, "zefania", &[], paths, parse_zefania).await
+}
+
+/// Import OpenSong Bible files as translation `module_id`, replacing its
+/// content file.
+#[command]
+pub async fn import_opensong(
+    app: tauri::AppHandle,
+    module_id: String,
+    name: Option<String>,
+    paths: Vec<String>,
+) -> Result<ImportReport, DbError> {
+    let name = name.unwrap_or_else(|| module_id.clone());
+    let paths = paths.into_iter().map(PathBuf::from).collect();
+    import::import_files(app, module_id, name, "opensong", &[], paths, parse_opensong).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_zefania_books_headings_and_notes() {
+        let src = r#"<?xml version="1.0" encoding="utf-8"?>
+<XMLBIBLE biblename="Luther 1912" type="x-bible">
+  <INFORMATION><title>Luther 1912</title></INFORMATION>
+  <BIBLEBOOK bnumber="43" bname="Johannes">
+    <CHAPTER cnumber="1">
+      <CAPTION>Das Wort ward Fleisch</CAPTION>
+      <VERS vnumber="1">Im Anfang war das <gr str="3056">Wort</gr>,<NOTE type="x-studynote">Gr. Logos</NOTE> und das Wort war bei Gott.</VERS>
+      <VERS vnumber="2">Dasselbe war im Anfang bei Gott.</VERS>
+    </CHAPTER>
+  </BIBLEBOOK>
+  <BIBLEBOOK bnumber="70" bname="Tobias"><CHAPTER cnumber="1"><VERS vnumber="1">x</VERS></CHAPTER></BIBLEBOOK>
+</XMLBIBLE>"#;
+        let mut warnings = Warnings::default();
+        let books = parse_zefania(src, "luther.xml", &mut warnings);
+        assert_eq!(books.len(), 1);
+        let john = &books[0];
+        assert_eq!(
+            (john.id.as_str(), john.name.as_deref()),
+            ("John", Some("Johannes"))
+        );
+        assert_eq!(
+            john.verses[0].text,
+            "Im Anfang war das Wort, und das Wort war bei Gott."
+        );
+        assert_eq!(john.verses[1].verse, 2);
+        assert_eq!(
+            (john.headings[0].verse, john.headings[0].text.as_str()),
+            (1, "Das Wort ward Fleisch")
+        );
+        let note = &john.footnotes[0];
+        assert_eq!(note.text, "Gr. Logos");
+        assert_eq!(
+            &john.verses[0].text[..note.position],
+            "Im Anfang war das Wort,"
+        );
+        let word = &john.verses[0].words[0];
+        assert_eq!(
+            (word.position, word.text.as_str(), word.strongs.as_slice()),
+            (18, "Wort", ["G3056".to_string()].as_slice())
+        );
+        let messages: Vec<&str> = warnings.list.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(messages, ["Book 70 is not one BibleMarker shows; skipped"]);
+    }
+
+    #[test]
+    fn reads_opensong_books_by_name_or_place() {
+        let src = r#"<bible>
+  <b n="Genesis"><c n="1"><v n="1">In the beginning God created the heaven and the earth.</v><v n="2">And the earth was without form.</v></c></b>
+  <b n="Exodus"><c n="1"><v n="1">Now these are the names.</v></c></b>
+  <b n="Levitique"><c n="1"><v n="1">L'Éternel appela Moïse.</v></c></b>
+</bible>"#;
+        let mut warnings = Warnings::default();
+        let books = parse_opensong(src, "bible.xmm", &mut warnings);
+        let ids: Vec<&str> = books.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, ["Gen", "Exod", "Lev"]);
+        assert_eq!(books[0].verses[1].text, "And the earth was without form.");
+        assert_eq!(books[2].name.as_deref(), Some("Levitique"));
+        assert_eq!(warnings.list.len(), 1, "{:?}", warnings.list);
+
+        let mut warnings = Warnings::default();
+        assert!(parse_opensong("<XMLBIBLE/>", "x.xml", &mut warnings).is_empty());
+        assert_eq!(
+            warnings.list[0].message,
+            "No `<bible>` element; wrong format?"
+        );
+    }
+}
@@ -0,0 +1,760 @@
+//! Text anchors: how a marking finds its words again when the translation
+//! under it changes, because it was re-imported with corrected text or
+//! switched for another file of the same id.
+//!
+//! Word indexes and character offsets only say where a mark was. An anchor
+//! also keeps what it covered: the marked words, a few words either side,
+//! and a fingerprint of the verse. `reanchor` reads each mark's verse from
+//! the mounted content again: marks on an unchanged verse are left alone,
+//! marks whose words moved (within the verse, or into the verse before or
+//! after) are moved with them, and marks whose words are gone are flagged
+//! `orphanedAt` for the user to look over rather than drawn on other words.
+//!
+//! Offsets here are UTF-16 code units, as the reader's JS strings count them.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use super::annotations::{self, Annotation, VerseRef, WordSpan};
+use super::{connections, now_iso, undo, with_connection, with_reader, DbError, DbErrorKind};
+use crate::content::{self, mounted};
+use crate::download::to_hex;
+
+/// Words kept either side of the marked ones.
+const CONTEXT: usize = 3;
+
+/// Marked words found again without any context word agreeing are taken
+/// only when there are at least this many of them.
+const UNIQUE_RUN: usize = 3;
+
+/// What a mark covered when it was last known to be right.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TextAnchor {
+    /// The marked words, as the verse wrote them.
+    pub exact: String,
+    /// Up to `CONTEXT` words before and after them in the verse.
+    #[serde(default)]
+    pub before: String,
+    #[serde(default)]
+    pub after: String,
+    /// Fingerprint of the verse text; empty when the text isn't known.
+    #[serde(default)]
+    pub verse: String,
+    /// When its words were last found missing; cleared once they're found.
+    #[serde(
+        rename = "orphanedAt",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub orphaned_at: Option<String>,
+}
+
+/// A word of a verse, split as the reader splits them (runs of
+/// non-whitespace), with its UTF-16 span.
+struct Word<'a> {
+    text: &'a str,
+    /// Lowercase letters and digits, what words are compared by; the word
+    /// itself when it has none.
+    key: String,
+    start: i64,
+    end: i64,
+}
+
+fn key(word: &str) -> String {
+    let key: String = word
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if key.is_empty() {
+        word.to_string()
+    } else {
+        key
+    }
+}
+
+fn words(text: &str) -> Vec<Word<'_>> {
+    let mut words = Vec::new();
+    let mut units = 0i64;
+    let mut start: Option<(usize, i64)> = None;
+    for (at, c) in text.char_indices() {
+        if c.is_whitespace() {
+            if let Some((from, from_units)) = start.take() {
+                words.push(Word {
+                    text: &text[from..at],
+                    key: key(&text[from..at]),
+                    start: from_units,
+                    end: units,
+                });
+            }
+        } else if start.is_none() {
+            start = Some((at, units));
+        }
+        units += c.len_utf16() as i64;
+    }
+    if let Some((from, from_units)) = start {
+        words.push(Word {
+            text: &text[from..],
+            key: key(&text[from..]),
+            start: from_units,
+            end: units,
+        });
+    }
+    words
+}
+
+fn keys(text: &str) -> Vec<String> {
+    words(text).into_iter().map(|w| w.key).collect()
+}
+
+fn fingerprint(text: &str) -> String {
+    to_hex(&Sha256::digest(text.as_bytes())[..8])
+}
+
+fn joined(words: &[Word]) -> String {
+    words.iter().map(|w| w.text).collect::<Vec<_>>().join(" ")
+}
+
+/// The anchor of words `first` to `last` of `text`.
+fn anchor_at(text: &str, words: &[Word], first: usize, last: usize) -> TextAnchor {
+    TextAnchor {
+        exact: joined(&words[first..=last]),
+        before: joined(&words[first.saturating_sub(CONTEXT)..first]),
+        after: joined(&words[last + 1..(last + 1 + CONTEXT).min(words.len())]),
+        verse: fingerprint(text),
+        orphaned_at: None,
+    }
+}
+
+/// The verse a mark's words are all in, and the fields that place them.
+/// None for marks on whole verses or across verses.
+struct Target<'a> {
+    verse: &'a mut VerseRef,
+    end: Option<&'a mut VerseRef>,
+    words: &'a mut WordSpan,
+    word_index: Option<&'a mut Option<i64>>,
+}
+
+fn same_verse(a: &VerseRef, b: &VerseRef) -> bool {
+    a.chapter == b.chapter && a.verse == b.verse
+}
+
+fn target(annotation: &mut Annotation) -> Option<Target<'_>> {
+    let target = match annotation {
+        Annotation::Highlight(a) | Annotation::TextColor(a) => Target {
+            verse: &mut a.span.start_ref,
+            end: Some(&mut a.span.end_ref),
+            words: &mut a.words,
+            word_index: None,
+        },
+        Annotation::Underline(a) => Target {
+            verse: &mut a.span.start_ref,
+            end: Some(&mut a.span.end_ref),
+            words: &mut a.words,
+            word_index: None,
+        },
+        Annotation::Symbol(a) => Target {
+            verse: &mut a.verse_ref,
+            end: a.end_ref.as_mut(),
+            words: &mut a.words,
+            word_index: Some(&mut a.word_index),
+        },
+    };
+    if target
+        .end
+        .as_deref()
+        .is_some_and(|end| !same_verse(end, target.verse))
+    {
+        return None;
+    }
+    let placed = target.words.start_word_index.is_some()
+        || target.words.start_offset.is_some()
+        || target.word_index.as_deref().is_some_and(Option::is_some);
+    placed.then_some(target)
+}
+
+impl Target<'_> {
+    /// The first and last word it covers now in `words`.
+    fn located(&self, words: &[Word]) -> Option<(usize, usize)> {
+        let span = &*self.words;
+        if let (Some(first), Some(last)) = (span.start_word_index, span.end_word_index) {
+            let (first, last) = (usize::try_from(first).ok()?, usize::try_from(last).ok()?);
+            return (first <= last && last < words.len()).then_some((first, last));
+        }
+        if let (Some(start), Some(end)) = (span.start_offset, span.end_offset) {
+            let first = words.iter().position(|w| w.end > start)?;
+            let last = words.iter().rposition(|w| w.start < end)?;
+            return (first <= last).then_some((first, last));
+        }
+        let index = usize::try_from((*self.word_index.as_deref()?)?).ok()?;
+        (index < words.len()).then_some((index, index))
+    }
+
+    /// Put it on words `first` to `last` of `text`, in verse `verse`.
+    fn place(&mut self, verse: i64, text: &str, words: &[Word], first: usize, last: usize) {
+        self.verse.verse = verse;
+        if let Some(end) = self.end.as_deref_mut() {
+            end.chapter = self.verse.chapter;
+            end.verse = verse;
+        }
+        let span = &mut *self.words;
+        if span.start_word_index.is_some() {
+            span.start_word_index = Some(first as i64);
+            span.end_word_index = Some(last as i64);
+        }
+        if span.start_offset.is_some() {
+            span.start_offset = Some(words[first].start);
+            span.end_offset = Some(words[last].end);
+        }
+        if let Some(Some(index)) = self.word_index.as_deref_mut() {
+            *index = first as i64;
+        }
+        span.anchor = Some(anchor_at(text, words, first, last));
+    }
+}
+
+/// How many context words agree with words `first` to `last`, counting out
+/// from the mark on each side until one doesn't.
+fn agreement(
+    words: &[Word],
+    first: usize,
+    last: usize,
+    before: &[String],
+    after: &[String],
+) -> usize {
+    let left = before
+        .iter()
+        .rev()
+        .zip(words[..first].iter().rev())
+        .take_while(|(k, w)| **k == w.key)
+        .count();
+    let right = after
+        .iter()
+        .zip(&words[last + 1..])
+        .take_while(|(k, w)| **k == w.key)
+        .count();
+    left + right
+}
+
+/// Where the words `exact` best fit in `words`: the run with the most
+/// context agreeing, then the one nearest `near`. With context to go by, a
+/// short run none of it agrees with is taken as some other occurrence.
+fn find(
+    words: &[Word],
+    exact: &[String],
+    before: &[String],
+    after: &[String],
+    near: usize,
+) -> Option<(usize, usize, usize)> {
+    let n = exact.len();
+    if n == 0 || n > words.len() {
+        return None;
+    }
+    let has_context = !before.is_empty() || !after.is_empty();
+    (0..=words.len() - n)
+        .filter(|&i| words[i..i + n].iter().zip(exact).all(|(w, k)| w.key == *k))
+        .map(|i| (i, agreement(words, i, i + n - 1, before, after)))
+        .filter(|&(_, agree)| !has_context || agree > 0 || n >= UNIQUE_RUN)
+        .max_by_key(|&(i, agree)| (agree, std::cmp::Reverse(i.abs_diff(near))))
+        .map(|(i, agree)| (i, i + n - 1, agree))
+}
+
+/// What the pass did with one mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// Its verse is as it was anchored.
+    Intact,
+    /// It was given an anchor, or its anchor was brought up to date, where
+    /// it stands.
+    Anchored,
+    /// Its words had moved, and it moved with them.
+    Repaired,
+    /// Its words are gone.
+    Orphaned,
+    /// It marks whole verses, spans verses, or can't be checked.
+    Skipped,
+}
+
+/// Check `annotation` against `verses`, the text of its chapter keyed by
+/// verse, moving or flagging it as needed. `now` stamps a new orphan.
+pub(crate) fn reanchor(
+    annotation: &mut Annotation,
+    verses: &BTreeMap<i64, String>,
+    now: &str,
+) -> Outcome {
+    let Some(mut target) = target(annotation) else {
+        return Outcome::Skipped;
+    };
+    let anchor = target.words.anchor.clone();
+    let verse = target.verse.verse;
+    let text = verses.get(&verse);
+    if let (Some(text), Some(anchor)) = (text, &anchor) {
+        if anchor.verse == fingerprint(text) && anchor.orphaned_at.is_none() {
+            return Outcome::Intact;
+        }
+    }
+    // What it should cover: the anchor's words, or for a mark from before
+    // anchors, the text selected when it was made.
+    let exact = anchor
+        .as_ref()
+        .map(|a| a.exact.clone())
+        .or_else(|| target.words.selected_text.clone());
+    let current = text.map(|t| (t, words(t)));
+    if let Some((text, words)) = &current {
+        if let Some((first, last)) = target.located(words) {
+            let fits = exact.as_deref().is_none_or(|exact| {
+                words[first..=last]
+                    .iter()
+                    .map(|w| w.key.clone())
+                    .eq(keys(exact))
+            });
+            if fits {
+                let before = target.words.clone();
+                target.place(verse, text, words, first, last);
+                let moved = WordSpan {
+                    anchor: None,
+                    ..target.words.clone()
+                } != WordSpan {
+                    anchor: None,
+                    ..before
+                };
+                return if moved || anchor.is_some_and(|a| a.orphaned_at.is_some()) {
+                    Outcome::Repaired
+                } else {
+                    Outcome::Anchored
+                };
+            }
+        }
+    }
+    let Some(exact) = exact.map(|e| keys(&e)) else {
+        return Outcome::Skipped;
+    };
+    let (before, after) = anchor
+        .as_ref()
+        .map(|a| (keys(&a.before), keys(&a.after)))
+        .unwrap_or_default();
+    let near = target
+        .words
+        .start_word_index
+        .and_then(|i| usize::try_from(i).ok())
+        .unwrap_or(0);
+    // The verse itself first; then its neighbours, in case the verses were
+    // divided differently, when there is enough to tell it's the same text.
+    let may_move = !before.is_empty() || !after.is_empty() || exact.len() >= UNIQUE_RUN;
+    let candidates = if may_move {
+        vec![verse, verse - 1, verse + 1]
+    } else {
+        vec![verse]
+    };
+    let mut found = None;
+    for candidate in candidates {
+        let Some(text) = verses.get(&candidate) else {
+            continue;
+        };
+        let words = words(text);
+        let near = if candidate == verse {
+            near
+        } else if candidate < verse {
+            words.len()
+        } else {
+            0
+        };
+        if let Some((first, last, agree)) = find(&words, &exact, &before, &after, near) {
+            if candidate == verse || found.is_none_or(|(_, _, _, best)| agree > best) {
+                found = Some((candidate, first, last, agree));
+            }
+        }
+        if candidate == verse && found.is_some() {
+            break;
+        }
+    }
+    if let Some((verse, first, last, _)) = found {
+        let text = &verses[&verse];
+        let words = words(text);
+        target.place(verse, text, &words, first, last);
+        if target.words.selected_text.is_some() {
+            target.words.selected_text = Some(joined(&words[first..=last]));
+        }
+        return Outcome::Repaired;
+    }
+    if anchor.as_ref().is_some_and(|a| a.orphaned_at.is_some()) {
+        return Outcome::Orphaned;
+    }
+    let anchor = target.words.anchor.get_or_insert_with(|| TextAnchor {
+        exact: target.words.selected_text.clone().unwrap_or_default(),
+        ..Default::default()
+    });
+    anchor.orphaned_at = Some(now.to_string());
+    Outcome::Orphaned
+}
+
+/// Place `annotation`, another translation's mark carried into this one and
+/// numbered as it numbers verses, on the same words in `verses` (this
+/// translation's text of its chapter, when mounted), or else on its whole
+/// verses: word positions only mean anything in the wording they were made
+/// in. Returns whether it kept its words.
+pub(crate) fn carry(annotation: &mut Annotation, verses: Option<&BTreeMap<i64, String>>) -> bool {
+    let known = target(annotation).is_some_and(|t| {
+        t.words
+            .anchor
+            .as_ref()
+            .is_some_and(|a| !a.exact.trim().is_empty())
+            || t.words
+                .selected_text
+                .as_deref()
+                .is_some_and(|s| !s.trim().is_empty())
+    });
+    let placed = known
+        && verses.is_some_and(|verses| {
+            matches!(
+                reanchor(annotation, verses, ""),
+                Outcome::Intact | Outcome::Anchored | Outcome::Repaired
+            )
+        });
+    if !placed {
+        match annotation {
+            Annotation::Highlight(a) | Annotation::TextColor(a) => a.words = WordSpan::default(),
+            Annotation::Underline(a) => a.words = WordSpan::default(),
+            Annotation::Symbol(a) => {
+                a.words = WordSpan::default();
+                a.word_index = None;
+            }
+        }
+    }
+    placed
+}
+
+/// Give a new or edited `annotation` the anchor of the words it covers in
+/// `schema`, when it covers words within one verse there.
+pub(crate) fn anchor(
+    conn: &Connection,
+    schema: &str,
+    annotation: &mut Annotation,
+) -> Result<(), DbError> {
+    let Some(target) = target(annotation) else {
+        return Ok(());
+    };
+    let (book, chapter, verse) = (
+        target.verse.book.clone(),
+        target.verse.chapter,
+        target.verse.verse,
+    );
+    let verses = content::chapter(conn, schema, &book, chapter)?;
+    if let Some(text) = verses.get(&verse) {
+        let words = words(text);
+        if let Some((first, last)) = target.located(&words) {
+            target.words.anchor = Some(anchor_at(text, &words, first, last));
+        }
+    }
+    Ok(())
+}
+
+/// `annotation` with its anchor, when its translation is mounted. Anchoring
+/// is a help, not a condition: a failure leaves it as it was.
+pub(crate) async fn anchored(app: &tauri::AppHandle, mut annotation: Annotation) -> Annotation {
+    let Some(content) = mounted(&annotation.common().module_id) else {
+        return annotation;
+    };
+    let module_id = annotation.common().module_id.clone();
+    let fallback = annotation.clone();
+    match with_reader(app, move |conn| {
+        anchor(conn, &content.schema, &mut annotation)?;
+        Ok(annotation)
+    })
+    .await
+    {
+        Ok(annotation) => annotation,
+        Err(e) => {
+            println!("[anchors] Could not anchor a mark in {module_id}: {e}");
+            fallback
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct ReanchorReport {
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    pub checked: usize,
+    pub intact: usize,
+    pub anchored: usize,
+    pub repaired: usize,
+    /// Ids of the marks whose words are gone.
+    pub orphaned: Vec<String>,
+    pub skipped: usize,
+}
+
+/// A mark the pass changed, with the `updated_at` it was read with.
+pub(crate) type Checked = (Annotation, String);
+
+/// Check every mark of `module_id` against the text in `schema`, returning
+/// the report and the marks to save.
+pub(crate) fn check_all(
+    conn: &Connection,
+    module_id: &str,
+    schema: &str,
+) -> Result<(ReanchorReport, Vec<Checked>), DbError> {
+    let now = now_iso(conn)?;
+    let rows: Vec<(String, String)> = conn
+        .prepare("SELECT data, updated_at FROM annotations WHERE module_id = ?1 ORDER BY id")?
+        .query_map([module_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut report = ReanchorReport {
+        module_id: module_id.to_string(),
+        ..Default::default()
+    };
+    let mut changed = Vec::new();
+    let mut chapters: BTreeMap<(String, i64), BTreeMap<i64, String>> = BTreeMap::new();
+    for (data, updated_at) in rows {
+        let Ok(mut annotation) = serde_json::from_str::<Annotation>(&data) else {
+            report.skipped += 1;
+            continue;
+        };
+        report.checked += 1;
+        let (book, chapter) = match &annotation {
+            Annotation::Highlight(a) | Annotation::TextColor(a) => {
+                (a.span.start_ref.book.clone(), a.span.start_ref.chapter)
+            }
+            Annotation::Underline(a) => (a.span.start_ref.book.clone(), a.span.start_ref.chapter),
+            Annotation::Symbol(a) => (a.verse_ref.book.clone(), a.verse_ref.chapter),
+        };
+        let key = (book, chapter);
+        if !chapters.contains_key(&key) {
+            let verses = content::chapter(conn, schema, &key.0, key.1)?;
+            chapters.insert(key.clone(), verses);
+        }
+        let before = annotation.clone();
+        let outcome = reanchor(&mut annotation, &chapters[&key], &now);
+        match outcome {
+            Outcome::Intact => report.intact += 1,
+            Outcome::Anchored => report.anchored += 1,
+            Outcome::Repaired => report.repaired += 1,
+            Outcome::Orphaned => report.orphaned.push(annotation.common().id.clone()),
+            Outcome::Skipped => report.skipped += 1,
+        }
+        if annotation != before {
+            changed.push((annotation, updated_at));
+        }
+    }
+    Ok((report, changed))
+}
+
+/// Save the marks the pass changed, as one undo step, skipping any edited or
+/// deleted since they were read. Returns how many were saved.
+pub(crate) fn save(conn: &mut Connection, changed: Vec<Checked>) -> Result<usize, DbError> {
+    let since = undo::last_seq(conn)?;
+    let tx = conn.transaction()?;
+    let mut saved = 0;
+    for (annotation, read_at) in changed {
+        let updated_at: Option<String> = tx
+            .query_row(
+                "SELECT updated_at FROM annotations WHERE id = ?1",
+                [&annotation.common().id],
+                |row| row.get(0),
+            )
+            .ok();
+        if updated_at.as_deref() == Some(read_at.as_str()) {
+            annotations::update(&tx, annotation, None)?;
+            saved += 1;
+        }
+    }
+    tx.commit()?;
+    undo::group_since(conn, since)?;
+    Ok(saved)
+}
+
+fn mounted_schema(module_id: &str) -> Result<String, DbError> {
+    mounted(module_id).map(|m| m.schema).ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("Translation `{module_id}` is not mounted"),
+        )
+    })
+}
+
+/// Re-anchor `module_id`'s marks straight away, for an import that has just
+/// replaced its text. Runs on the calling thread.
+pub(crate) fn reanchor_now(
+    app: &tauri::AppHandle,
+    module_id: &str,
+) -> Result<ReanchorReport, DbError> {
+    let schema = mounted_schema(module_id)?;
+    let manager = connections::manager(&super::db_path(app)?);
+    let module = module_id.to_string();
+    let (report, changed) = manager.read(move |conn| check_all(conn, &module, &schema))?;
+    if !changed.is_empty() {
+        manager.write(move |conn| save(conn, changed))?;
+    }
+    Ok(report)
+}
+
+/// Check every mark of a mounted translation against its current text:
+/// anchor the ones without an anchor, move the ones whose words moved, and
+/// flag the ones whose words are gone.
+#[tauri::command]
+pub async fn reanchor_annotations(
+    app: tauri::AppHandle,
+    module_id: String,
+) -> Result<ReanchorReport, DbError> {
+    let schema = mounted_schema(&module_id)?;
+    let (report, changed) =
+        with_reader(&app, move |conn| check_all(conn, &module_id, &schema)).await?;
+    if !changed.is_empty() {
+        with_connection(&app, move |conn| save(conn, changed)).await?;
+    }
+    Ok(report)
+}
+
+/// Marks flagged by `reanchor_annotations` as having lost their words, of
+/// one translation or all.
+#[tauri::command]
+pub async fn get_orphaned_annotations(
+    app: tauri::AppHandle,
+    module_id: Option<String>,
+) -> Result<Vec<Annotation>, DbError> {
+    with_reader(&app, move |conn| {
+        let rows: Vec<String> = conn
+            .prepare(
+                "SELECT data FROM annotations
+                 WHERE json_extract(data, '$.anchor.orphanedAt') IS NOT NULL
+                   AND (?1 IS NULL OR module_id = ?1)
+                 ORDER BY json_extract(data, '$.anchor.orphanedAt') DESC, id",
+            )?
+            .query_map(params![module_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(rows
+            .iter()
+            .filter_map(|data| serde_json::from_str(data).ok())
+            .collect())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+    use serde_json::json;
+
+    fn highlight(id: &str, verse: i64, first: i64, last: i64, text: &str) -> Annotation {
+        Annotation::from_value(&json!({
+            "type": "highlight", "id": id, "moduleId": "kjv", "color": "yellow",
+            "startRef": { "book": "Gen", "chapter": 1, "verse": verse },
+            "endRef": { "book": "Gen", "chapter": 1, "verse": verse },
+            "startWordIndex": first, "endWordIndex": last, "selectedText": text,
+        }))
+        .unwrap()
+    }
+
+    fn span(annotation: &Annotation) -> (i64, &WordSpan) {
+        match annotation {
+            Annotation::Highlight(a) => (a.span.start_ref.verse, &a.words),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn repairs_or_flags_marks_when_the_text_changes() {
+        let mut verses = BTreeMap::from([
+            (
+                1,
+                "In the beginning God created the heaven and the earth.".to_string(),
+            ),
+            (2, "And the earth was without form, and void.".to_string()),
+        ]);
+        let mut god = highlight("god", 1, 3, 3, "God");
+        let mut earth = highlight("earth", 2, 2, 2, "earth");
+        let mut void = highlight("void", 2, 7, 7, "void.");
+        for mark in [&mut god, &mut earth, &mut void] {
+            assert_eq!(reanchor(mark, &verses, "t0"), Outcome::Anchored);
+            assert_eq!(reanchor(mark, &verses, "t0"), Outcome::Intact);
+        }
+        assert_eq!(
+            span(&god).1.anchor.as_ref().unwrap().before,
+            "In the beginning"
+        );
+
+        // Re-imported with an added word and a verse divided differently.
+        verses.insert(
+            1,
+            "In the very beginning God created the heaven.".to_string(),
+        );
+        verses.insert(2, "And the earth, it was without form.".to_string());
+        assert_eq!(reanchor(&mut god, &verses, "t1"), Outcome::Repaired);
+        assert_eq!(span(&god).1.start_word_index, Some(4));
+        assert_eq!(span(&god).1.selected_text.as_deref(), Some("God"));
+        // "earth," still reads as the same word.
+        assert_eq!(reanchor(&mut earth, &verses, "t1"), Outcome::Anchored);
+        assert_eq!(reanchor(&mut void, &verses, "t1"), Outcome::Orphaned);
+        assert_eq!(
+            span(&void)
+                .1
+                .anchor
+                .as_ref()
+                .unwrap()
+                .orphaned_at
+                .as_deref(),
+            Some("t1")
+        );
+        assert_eq!(span(&void).1.start_word_index, Some(7));
+
+        verses.insert(3, "And void it was, and darkness.".to_string());
+        assert_eq!(reanchor(&mut void, &verses, "t2"), Outcome::Repaired);
+        assert_eq!(span(&void).0, 3);
+        assert_eq!(span(&void).1.start_word_index, Some(1));
+        assert_eq!(span(&void).1.anchor.as_ref().unwrap().orphaned_at, None);
+
+        // Offsets count UTF-16 units, as JS strings do.
+        let mut offsets = Annotation::from_value(&json!({
+            "type": "underline", "id": "u", "moduleId": "kjv", "color": "red",
+            "startRef": { "book": "Gen", "chapter": 1, "verse": 4 },
+            "endRef": { "book": "Gen", "chapter": 1, "verse": 4 },
+            "startOffset": 6, "endOffset": 10, "selectedText": "good",
+        }))
+        .unwrap();
+        verses.insert(4, "𝔊od 𝔰aw good things.".to_string());
+        assert_eq!(reanchor(&mut offsets, &verses, "t3"), Outcome::Repaired);
+        let Annotation::Underline(u) = &offsets else {
+            unreachable!()
+        };
+        assert_eq!(
+            (u.words.start_offset, u.words.end_offset),
+            (Some(10), Some(14))
+        );
+    }
+
+    #[test]
+    fn saves_checked_marks_unless_edited_since() {
+        let mut conn = migrated_test_connection();
+        conn.execute_batch(
+            "ATTACH ':memory:' AS content_kjv;
+             CREATE TABLE content_kjv.verses (book TEXT, chapter INTEGER, verse INTEGER, text TEXT);
+             INSERT INTO content_kjv.verses VALUES ('Gen', 1, 1, 'In the beginning God created.');",
+        )
+        .unwrap();
+        annotations::create(&conn, highlight("a", 1, 2, 2, "beginning")).unwrap();
+        annotations::create(&conn, highlight("b", 1, 0, 0, "Formerly")).unwrap();
+        let (report, changed) = check_all(&conn, "kjv", "content_kjv").unwrap();
+        assert_eq!((report.checked, report.anchored), (2, 1));
+        assert_eq!(report.orphaned, ["b"]);
+
+        conn.execute(
+            "UPDATE annotations SET updated_at = 'later' WHERE id = 'b'",
+            [],
+        )
+        .unwrap();
+        assert_eq!(save(&mut conn, changed).unwrap(), 1);
+        let (report, changed) = check_all(&conn, "kjv", "content_kjv").unwrap();
+        assert_eq!((report.intact, changed.len()), (1, 1));
+        let logged: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM change_log WHERE row_id = 'a'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(logged, 2);
+    }
+}
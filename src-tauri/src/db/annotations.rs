@@ -0,0 +1,1051 @@
+//! Annotations: the typed model of highlights, underlines and symbols, the
+//! commands that create, update and delete them, and the hot paths (loading
+//! a chapter's marks, writing many at once).
+//!
+//! Every write, from the UI or from sync, goes through `Annotation::check`
+//! and is stored as the model serializes it, in the `data` JSON the TS
+//! `Annotation` type reads. Fields the model doesn't know (from a newer
+//! app) are kept as they came.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::anchors::{self, TextAnchor};
+use super::propagation;
+use super::{
+    device_id, now_iso, record_change, undo, with_connection, with_reader, with_sync_connection,
+    DbError, DbErrorKind,
+};
+use crate::content::books;
+
+/// A verse, as `VerseRef` in src/types/bible.ts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct VerseRef {
+    /// OSIS book id.
+    pub book: String,
+    pub chapter: i64,
+    pub verse: i64,
+}
+
+/// What every annotation has.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Common {
+    pub id: String,
+    /// The translation it marks.
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    /// ISO times; set by the commands that write them.
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    /// The `MarkingPreset` it was made with.
+    #[serde(rename = "presetId", default, skip_serializing_if = "Option::is_none")]
+    pub preset_id: Option<String>,
+    /// The `HighlightStyle` a text mark is drawn with. Its own type and
+    /// color stay as the look for apps without styles, and for a style
+    /// since deleted.
+    #[serde(rename = "styleId", default, skip_serializing_if = "Option::is_none")]
+    pub style_id: Option<String>,
+    /// The `MarkingTemplate` that made it, which can remove it again.
+    #[serde(
+        rename = "templateId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub template_id: Option<String>,
+    /// Shown in its own translation only, for a mark on wording the others
+    /// don't share. Unset, key-word marks are (marking a key word gives each
+    /// translation a copy on its own words) and other marks aren't.
+    #[serde(
+        rename = "translationLocked",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub translation_locked: Option<bool>,
+    /// Set on another translation's mark as `get_chapter_annotations`
+    /// carries it into the one being read (see `propagation`). Never stored:
+    /// it is numbered and placed for that translation, not its own.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub carried: bool,
+}
+
+impl Common {
+    /// Whether it stays out of other translations (see `translation_locked`).
+    pub(crate) fn locked_to_translation(&self) -> bool {
+        self.translation_locked.unwrap_or(self.preset_id.is_some())
+    }
+}
+
+/// The verses a highlight or underline covers, from `start_ref` to
+/// `end_ref`, which may be in a later chapter; its `WordSpan` narrows them
+/// down to words and characters.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TextSpan {
+    #[serde(rename = "startRef")]
+    pub start_ref: VerseRef,
+    #[serde(rename = "endRef")]
+    pub end_ref: VerseRef,
+}
+
+/// Which words of its verses a mark covers, and the anchor that finds them
+/// again if the text changes (see `anchors`). Starts count in the first
+/// verse and ends in the last, so a span across verses runs from its start
+/// to the end of the first verse, through every verse between, and on to
+/// its end in the last. Flattened beside the other fields, never inside
+/// another flattened struct, so the `extra` catch-all sees its fields as
+/// taken.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WordSpan {
+    #[serde(
+        rename = "startWordIndex",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_word_index: Option<i64>,
+    #[serde(
+        rename = "endWordIndex",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub end_word_index: Option<i64>,
+    #[serde(
+        rename = "selectedText",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub selected_text: Option<String>,
+    /// Character offsets into the start and end verses.
+    #[serde(
+        rename = "startOffset",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_offset: Option<i64>,
+    #[serde(rename = "endOffset", default, skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<TextAnchor>,
+}
+
+/// A highlight, or (as `Annotation::TextColor`) text drawn in a color.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Highlight {
+    #[serde(flatten)]
+    pub common: Common,
+    #[serde(flatten)]
+    pub span: TextSpan,
+    #[serde(flatten)]
+    pub words: WordSpan,
+    /// A `HighlightColor`.
+    pub color: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnderlineStyle {
+    Solid,
+    Dashed,
+    Dotted,
+    Double,
+    Wavy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Underline {
+    #[serde(flatten)]
+    pub common: Common,
+    #[serde(flatten)]
+    pub span: TextSpan,
+    #[serde(flatten)]
+    pub words: WordSpan,
+    pub color: String,
+    #[serde(
+        rename = "underlineStyle",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub underline_style: Option<UnderlineStyle>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolPosition {
+    Before,
+    After,
+    Center,
+}
+
+/// Where a centered symbol sits.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolPlacement {
+    Above,
+    Overlay,
+}
+
+/// A symbol at a verse or word, or centered on a selection.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SymbolMark {
+    #[serde(flatten)]
+    pub common: Common,
+    #[serde(rename = "ref")]
+    pub verse_ref: VerseRef,
+    /// A `SymbolKey`.
+    pub symbol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(rename = "wordIndex", default, skip_serializing_if = "Option::is_none")]
+    pub word_index: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<SymbolPosition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<SymbolPlacement>,
+    /// Last verse of a selection the symbol is centered on.
+    #[serde(rename = "endRef", default, skip_serializing_if = "Option::is_none")]
+    pub end_ref: Option<VerseRef>,
+    /// The selection a centered symbol covers.
+    #[serde(flatten)]
+    pub words: WordSpan,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An annotation as the TS `Annotation` union stores it, tagged by `type`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum Annotation {
+    #[serde(rename = "highlight")]
+    Highlight(Highlight),
+    #[serde(rename = "textColor")]
+    TextColor(Highlight),
+    #[serde(rename = "underline")]
+    Underline(Underline),
+    #[serde(rename = "symbol")]
+    Symbol(SymbolMark),
+}
+
+/// The time and device of a change sync brings in, written as it came.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteWrite {
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+    #[serde(rename = "deviceId")]
+    pub device_id: String,
+}
+
+fn check_ref(id: &str, field: &str, r: &VerseRef) -> Result<(), DbError> {
+    if books::position(&r.book).is_none() || r.chapter < 1 || r.verse < 1 {
+        return Err(DbError::invalid(format!(
+            "annotation {id}: `{field}` is not a verse ({} {}:{})",
+            r.book, r.chapter, r.verse
+        )));
+    }
+    Ok(())
+}
+
+/// That `end` is a verse of `start`'s book, not before it.
+fn check_range(id: &str, start: &VerseRef, end: &VerseRef) -> Result<(), DbError> {
+    check_ref(id, "endRef", end)?;
+    if end.book != start.book || (end.chapter, end.verse) < (start.chapter, start.verse) {
+        return Err(DbError::invalid(format!(
+            "annotation {id}: `endRef` must follow its start in the same book"
+        )));
+    }
+    Ok(())
+}
+
+/// That `words` is a span of its verses: no negative positions, and within
+/// one verse an end not before its start. A text mark (`paired`) gives
+/// both ends of a word or character range or neither; a symbol may sit on
+/// one word.
+fn check_words(id: &str, words: &WordSpan, one_verse: bool, paired: bool) -> Result<(), DbError> {
+    let ranges = [
+        ("WordIndex", words.start_word_index, words.end_word_index),
+        ("Offset", words.start_offset, words.end_offset),
+    ];
+    for (field, start, end) in ranges {
+        if start.is_some_and(|at| at < 0) || end.is_some_and(|at| at < 0) {
+            return Err(DbError::invalid(format!(
+                "annotation {id}: `start{field}` and `end{field}` can't be negative"
+            )));
+        }
+        if paired && start.is_some() != end.is_some() {
+            return Err(DbError::invalid(format!(
+                "annotation {id}: `start{field}` and `end{field}` go together"
+            )));
+        }
+        if let (true, Some(start), Some(end)) = (one_verse, start, end) {
+            if end < start {
+                return Err(DbError::invalid(format!(
+                    "annotation {id}: `end{field}` is before `start{field}` in one verse"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Annotation {
+    /// `value` as an annotation, which must be one.
+    pub(crate) fn from_value(value: &Value) -> Result<Self, DbError> {
+        Self::deserialize(value).map_err(|e| DbError::invalid(format!("Not an annotation: {e}")))
+    }
+
+    pub fn common(&self) -> &Common {
+        match self {
+            Self::Highlight(a) | Self::TextColor(a) => &a.common,
+            Self::Underline(a) => &a.common,
+            Self::Symbol(a) => &a.common,
+        }
+    }
+
+    pub(crate) fn common_mut(&mut self) -> &mut Common {
+        match self {
+            Self::Highlight(a) | Self::TextColor(a) => &mut a.common,
+            Self::Underline(a) => &mut a.common,
+            Self::Symbol(a) => &mut a.common,
+        }
+    }
+
+    /// The `type` it is stored with.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Highlight(_) => "highlight",
+            Self::TextColor(_) => "textColor",
+            Self::Underline(_) => "underline",
+            Self::Symbol(_) => "symbol",
+        }
+    }
+
+    /// Whether it is one the app can show: ids set, verses that exist in a
+    /// book of the canon, ranges forwards within a book, word and character
+    /// spans that fit them, and a color or symbol to draw.
+    pub(crate) fn check(&self) -> Result<(), DbError> {
+        let common = self.common();
+        let id = common.id.as_str();
+        if id.trim().is_empty() {
+            return Err(DbError::invalid("annotation is missing `id`"));
+        }
+        if common.module_id.trim().is_empty() {
+            return Err(DbError::invalid(format!(
+                "annotation {id}: `moduleId` is empty"
+            )));
+        }
+        if common.carried {
+            return Err(DbError::invalid(format!(
+                "annotation {id} is {}'s, carried into another translation; edit it there",
+                common.module_id
+            )));
+        }
+        let (span, words, color) = match self {
+            Self::Highlight(a) | Self::TextColor(a) => (&a.span, &a.words, a.color.as_str()),
+            Self::Underline(a) => (&a.span, &a.words, a.color.as_str()),
+            Self::Symbol(a) => {
+                check_ref(id, "ref", &a.verse_ref)?;
+                if let Some(end) = &a.end_ref {
+                    check_range(id, &a.verse_ref, end)?;
+                }
+                let one_verse = a.end_ref.as_ref().is_none_or(|end| *end == a.verse_ref);
+                check_words(id, &a.words, one_verse, false)?;
+                if a.symbol.trim().is_empty() {
+                    return Err(DbError::invalid(format!(
+                        "annotation {id}: `symbol` is empty"
+                    )));
+                }
+                return Ok(());
+            }
+        };
+        check_ref(id, "startRef", &span.start_ref)?;
+        check_range(id, &span.start_ref, &span.end_ref)?;
+        check_words(id, words, span.start_ref == span.end_ref, true)?;
+        if color.trim().is_empty() {
+            return Err(DbError::invalid(format!(
+                "annotation {id}: `color` is empty"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Store `annotation` as its row, returning the `data` written.
+fn write_row(
+    conn: &Connection,
+    annotation: &Annotation,
+    updated_at: &str,
+    sync_status: &str,
+    device: &str,
+) -> Result<String, DbError> {
+    let common = annotation.common();
+    let data = serde_json::to_string(annotation)
+        .map_err(|e| DbError::invalid(format!("annotation {}: {e}", common.id)))?;
+    conn.prepare_cached(
+        "INSERT OR REPLACE INTO annotations
+         (id, module_id, type, data, preset_id, created_at, updated_at, sync_status, device_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )?
+    .execute(params![
+        common.id,
+        common.module_id,
+        annotation.kind(),
+        data,
+        common.preset_id,
+        common.created_at,
+        updated_at,
+        sync_status,
+        device
+    ])?;
+    Ok(data)
+}
+
+/// Save new `annotation`, stamped now and logged for sync.
+pub(crate) fn create(conn: &Connection, mut annotation: Annotation) -> Result<Annotation, DbError> {
+    annotation.check()?;
+    let id = annotation.common().id.clone();
+    let exists = conn
+        .query_row("SELECT 1 FROM annotations WHERE id = ?1", [&id], |_| Ok(()))
+        .optional()?
+        .is_some();
+    if exists {
+        return Err(DbError::invalid(format!("annotation {id} already exists")));
+    }
+    let (now, device) = (now_iso(conn)?, device_id(conn)?);
+    let common = annotation.common_mut();
+    common.created_at = now.clone();
+    common.updated_at = now.clone();
+    let data = write_row(conn, &annotation, &now, "pending", &device)?;
+    record_change(
+        conn,
+        "annotations",
+        "upsert",
+        &id,
+        Some(&data),
+        &now,
+        &device,
+    )?;
+    Ok(annotation)
+}
+
+/// Save `annotation` over the stored one of its id. A local edit keeps when
+/// it was created, is stamped now and logged; a change from sync
+/// (`remote`) is written as it came, whether or not the row is here yet,
+/// and isn't logged again.
+pub(crate) fn update(
+    conn: &Connection,
+    mut annotation: Annotation,
+    remote: Option<&RemoteWrite>,
+) -> Result<Annotation, DbError> {
+    annotation.check()?;
+    if let Some(remote) = remote {
+        write_row(
+            conn,
+            &annotation,
+            &remote.updated_at,
+            "synced",
+            &remote.device_id,
+        )?;
+        return Ok(annotation);
+    }
+    let id = annotation.common().id.clone();
+    let created_at: String = conn
+        .query_row(
+            "SELECT created_at FROM annotations WHERE id = ?1",
+            [&id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("No annotation {id}")))?;
+    let (now, device) = (now_iso(conn)?, device_id(conn)?);
+    let common = annotation.common_mut();
+    common.created_at = created_at;
+    common.updated_at = now.clone();
+    let data = write_row(conn, &annotation, &now, "pending", &device)?;
+    record_change(
+        conn,
+        "annotations",
+        "upsert",
+        &id,
+        Some(&data),
+        &now,
+        &device,
+    )?;
+    Ok(annotation)
+}
+
+/// Annotations of `module_id` located in `book` `chapter`, as the JSON the TS
+/// layer stored. Symbols key off `ref`; text marks are in every chapter from
+/// their `startRef` to their `endRef`, so a span across chapters shows in
+/// each. Filtered in SQL instead of parsing every annotation of the
+/// translation in JS.
+pub(crate) fn chapter_annotations(
+    conn: &Connection,
+    module_id: &str,
+    book: &str,
+    chapter: i64,
+) -> Result<Vec<Value>, DbError> {
+    chapters_annotations(conn, module_id, book, chapter, chapter)
+}
+
+/// Annotations of `module_id` in any of chapters `first` to `last` of
+/// `book`, as `chapter_annotations` finds them in one.
+pub(crate) fn chapters_annotations(
+    conn: &Connection,
+    module_id: &str,
+    book: &str,
+    first: i64,
+    last: i64,
+) -> Result<Vec<Value>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT data FROM annotations
+         WHERE module_id = ?1
+           AND (CASE type WHEN 'symbol' THEN json_extract(data, '$.ref.book')
+                          ELSE json_extract(data, '$.startRef.book') END) = ?2
+           AND (CASE type WHEN 'symbol' THEN json_extract(data, '$.ref.chapter')
+                          ELSE json_extract(data, '$.startRef.chapter') END) <= ?4
+           AND (CASE type WHEN 'symbol' THEN json_extract(data, '$.ref.chapter')
+                          ELSE coalesce(json_extract(data, '$.endRef.chapter'),
+                                        json_extract(data, '$.startRef.chapter')) END) >= ?3",
+    )?;
+    let rows = stmt
+        .query_map(params![module_id, book, first, last], |row| {
+            row.get::<_, String>(0)
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    rows.iter()
+        .map(|data| {
+            serde_json::from_str(data)
+                .map_err(|e| DbError::invalid(format!("Stored annotation is not JSON: {e}")))
+        })
+        .collect()
+}
+
+fn required_str<'a>(annotation: &'a Value, field: &str) -> Result<&'a str, DbError> {
+    annotation
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| DbError::invalid(format!("annotation is missing `{field}`")))
+}
+
+/// Insert or replace one annotation as a local write (row written `pending`
+/// with a fresh `updated_at`, change logged), keeping its `createdAt`.
+/// Returns its id.
+fn upsert_one<'a>(
+    conn: &Connection,
+    annotation: &'a Value,
+    now: &str,
+    device: &str,
+) -> Result<&'a str, DbError> {
+    let id = required_str(annotation, "id")?;
+    required_str(annotation, "moduleId")?;
+    required_str(annotation, "type")?;
+    let mut typed = Annotation::from_value(annotation)?;
+    typed.check()?;
+    let common = typed.common_mut();
+    if common.created_at.is_empty() {
+        common.created_at = now.to_string();
+    }
+    let data = write_row(conn, &typed, now, "pending", device)?;
+    record_change(conn, "annotations", "upsert", id, Some(&data), now, device)?;
+    Ok(id)
+}
+
+/// Delete one annotation and log it; false if there was no such row.
+pub(crate) fn delete_one(
+    conn: &Connection,
+    id: &str,
+    now: &str,
+    device: &str,
+) -> Result<bool, DbError> {
+    let deleted = conn
+        .prepare_cached("DELETE FROM annotations WHERE id = ?1")?
+        .execute([id])?;
+    if deleted > 0 {
+        record_change(conn, "annotations", "delete", id, None, now, device)?;
+    }
+    Ok(deleted > 0)
+}
+
+/// Save every annotation in one transaction, as `upsert_one` saves one.
+/// All-or-nothing: an invalid annotation rolls the whole batch back.
+pub(crate) fn bulk_insert(conn: &mut Connection, annotations: &[Value]) -> Result<usize, DbError> {
+    let device = device_id(conn)?;
+    let tx = conn.transaction()?;
+    let now = now_iso(&tx)?;
+    for annotation in annotations {
+        upsert_one(&tx, annotation, &now, &device)?;
+    }
+    tx.commit()?;
+    Ok(annotations.len())
+}
+
+/// One step of a `bulk_apply_markings` batch.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum MarkingOp {
+    Upsert { annotation: Value },
+    Delete { id: String },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkingBatch {
+    pub operations: Vec<MarkingOp>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkingStatus {
+    Saved,
+    Deleted,
+    /// Delete of an id that wasn't there; nothing to do.
+    NotFound,
+    /// Rejected or failed; `error` says why and nothing of it was written.
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarkingResult {
+    /// Position in `operations`.
+    pub index: usize,
+    pub id: Option<String>,
+    pub status: MarkingStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkApplyResult {
+    pub saved: usize,
+    pub deleted: usize,
+    pub failed: usize,
+    pub results: Vec<MarkingResult>,
+}
+
+/// Apply a batch of upserts and deletes in one transaction. Each operation
+/// runs in its own savepoint, so a bad item is reported and skipped without
+/// undoing the rest; only a failure to commit loses the batch.
+pub(crate) fn bulk_apply(
+    conn: &mut Connection,
+    operations: &[MarkingOp],
+) -> Result<BulkApplyResult, DbError> {
+    let device = device_id(conn)?;
+    let mut tx = conn.transaction()?;
+    let now = now_iso(&tx)?;
+    let mut result = BulkApplyResult {
+        saved: 0,
+        deleted: 0,
+        failed: 0,
+        results: Vec::with_capacity(operations.len()),
+    };
+    for (index, operation) in operations.iter().enumerate() {
+        let sp = tx.savepoint()?;
+        let (id, outcome) = match operation {
+            MarkingOp::Upsert { annotation } => (
+                annotation.get("id").and_then(Value::as_str),
+                upsert_one(&sp, annotation, &now, &device).map(|_| MarkingStatus::Saved),
+            ),
+            MarkingOp::Delete { id } => (
+                Some(id.as_str()),
+                delete_one(&sp, id, &now, &device).map(|deleted| {
+                    if deleted {
+                        MarkingStatus::Deleted
+                    } else {
+                        MarkingStatus::NotFound
+                    }
+                }),
+            ),
+        };
+        let (status, error) = match outcome {
+            Ok(status) => {
+                sp.commit()?;
+                (status, None)
+            }
+            // Dropping the savepoint rolls back whatever the item wrote.
+            Err(e) => (MarkingStatus::Failed, Some(e.message)),
+        };
+        match status {
+            MarkingStatus::Saved => result.saved += 1,
+            MarkingStatus::Deleted => result.deleted += 1,
+            MarkingStatus::Failed => result.failed += 1,
+            MarkingStatus::NotFound => {}
+        }
+        result.results.push(MarkingResult {
+            index,
+            id: id.map(str::to_string),
+            status,
+            error,
+        });
+    }
+    tx.commit()?;
+    Ok(result)
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkInsertResult {
+    pub saved: usize,
+}
+
+/// `rows` as the annotations the model reads, leaving out (and logging) any
+/// stored row that isn't one.
+pub(crate) fn readable(rows: &[Value]) -> Vec<Annotation> {
+    rows.iter()
+        .filter_map(|data| match Annotation::from_value(data) {
+            Ok(annotation) => Some(annotation),
+            Err(e) => {
+                println!("[annotations] Skipping {}: {e}", data["id"]);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Chapter annotations for the reader, filtered natively: the translation's
+/// own, then those of other translations carried into it (`carried` set).
+/// Stored rows that aren't annotations the model reads are left out.
+#[tauri::command]
+pub async fn get_chapter_annotations(
+    app: tauri::AppHandle,
+    module_id: String,
+    book: String,
+    chapter: i64,
+) -> Result<Vec<Annotation>, DbError> {
+    with_reader(&app, move |conn| {
+        let mut annotations = readable(&chapter_annotations(conn, &module_id, &book, chapter)?);
+        annotations.extend(propagation::carried(conn, &module_id, &book, chapter)?);
+        Ok(annotations)
+    })
+    .await
+}
+
+/// Save a new annotation, validated, with `createdAt` and `updatedAt` set
+/// to now. Returns it as saved.
+#[tauri::command]
+pub async fn create_annotation(
+    app: tauri::AppHandle,
+    annotation: Annotation,
+) -> Result<Annotation, DbError> {
+    let annotation = anchors::anchored(&app, annotation).await;
+    with_connection(&app, move |conn| create(conn, annotation)).await
+}
+
+/// Save an edited annotation over the stored one, or, with `remote`, a
+/// change sync brought in. Returns it as saved.
+#[tauri::command]
+pub async fn update_annotation(
+    app: tauri::AppHandle,
+    annotation: Annotation,
+    remote: Option<RemoteWrite>,
+) -> Result<Annotation, DbError> {
+    // A local edit may have moved the mark; sync's copy comes anchored, and
+    // is written while sync holds writes paused.
+    match remote {
+        None => {
+            let annotation = anchors::anchored(&app, annotation).await;
+            with_connection(&app, move |conn| update(conn, annotation, None)).await
+        }
+        Some(remote) => {
+            with_sync_connection(&app, move |conn| update(conn, annotation, Some(&remote))).await
+        }
+    }
+}
+
+/// Delete an annotation and log it for sync; false if there was none.
+#[tauri::command]
+pub async fn delete_annotation(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| {
+        let (now, device) = (now_iso(conn)?, device_id(conn)?);
+        delete_one(conn, &id, &now, &device)
+    })
+    .await
+}
+
+/// Insert or replace many annotations (e.g. marking every occurrence of a
+/// keyword) in a single transaction.
+#[tauri::command]
+pub async fn db_bulk_insert_markings(
+    app: tauri::AppHandle,
+    annotations: Vec<Value>,
+) -> Result<BulkInsertResult, DbError> {
+    with_connection(&app, move |conn| {
+        let since = undo::last_seq(conn)?;
+        let saved = bulk_insert(conn, &annotations)?;
+        undo::group_since(conn, since)?;
+        Ok(BulkInsertResult { saved })
+    })
+    .await
+}
+
+/// Apply a mixed batch of marking upserts and deletes (e.g. marking or
+/// clearing every occurrence of a keyword in a book) in one transaction,
+/// reporting the outcome of each operation.
+#[tauri::command]
+pub async fn bulk_apply_markings(
+    app: tauri::AppHandle,
+    payload: MarkingBatch,
+) -> Result<BulkApplyResult, DbError> {
+    with_connection(&app, move |conn| {
+        let since = undo::last_seq(conn)?;
+        let result = bulk_apply(conn, &payload.operations)?;
+        undo::group_since(conn, since)?;
+        Ok(result)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+    use serde_json::json;
+
+    fn highlight(id: &str, book: &str, chapter: i64) -> Value {
+        json!({
+            "id": id, "moduleId": "kjv", "type": "highlight",
+            "startRef": { "book": book, "chapter": chapter, "verse": 1 },
+            "endRef": { "book": book, "chapter": chapter, "verse": 1 },
+            "color": "yellow", "presetId": "p1",
+            "createdAt": "2025-01-01T00:00:00.000Z", "updatedAt": "2025-01-01T00:00:00.000Z"
+        })
+    }
+
+    fn symbol(id: &str, book: &str, chapter: i64) -> Value {
+        json!({
+            "id": id, "moduleId": "kjv", "type": "symbol",
+            "ref": { "book": book, "chapter": chapter, "verse": 3 },
+            "symbol": "cross",
+            "createdAt": "2025-01-01T00:00:00.000Z", "updatedAt": "2025-01-01T00:00:00.000Z"
+        })
+    }
+
+    #[test]
+    fn bulk_insert_saves_rows_and_logs_changes() {
+        let mut conn = test_connection();
+        conn.execute(
+            "INSERT INTO sync_tombstones VALUES ('annotations', 'a1', '2020-01-01', 'dev-x')",
+            [],
+        )
+        .unwrap();
+        let saved = bulk_insert(
+            &mut conn,
+            &[highlight("a1", "John", 3), symbol("s1", "John", 3)],
+        )
+        .unwrap();
+        assert_eq!(saved, 2);
+
+        let (count, pending): (i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), SUM(sync_status = 'pending' AND device_id = 'dev-local')
+                 FROM annotations",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((count, pending), (2, 2));
+        let logged: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM change_log WHERE op = 'upsert' AND flushed = 0",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(logged, 2);
+        let tombstones: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sync_tombstones", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(
+            tombstones, 0,
+            "re-saving a deleted row clears its tombstone"
+        );
+    }
+
+    #[test]
+    fn bulk_insert_is_all_or_nothing() {
+        let mut conn = test_connection();
+        let err = bulk_insert(
+            &mut conn,
+            &[highlight("a1", "John", 3), json!({ "id": "bad" })],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, crate::db::DbErrorKind::Invalid);
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM annotations", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn bulk_apply_reports_each_operation_and_keeps_the_good_ones() {
+        let mut conn = test_connection();
+        bulk_insert(&mut conn, &[highlight("old", "John", 3)]).unwrap();
+        let batch: MarkingBatch = serde_json::from_value(json!({
+            "operations": [
+                { "op": "upsert", "annotation": highlight("a1", "John", 3) },
+                { "op": "upsert", "annotation": { "id": "bad" } },
+                { "op": "delete", "id": "old" },
+                { "op": "delete", "id": "never-existed" }
+            ]
+        }))
+        .unwrap();
+
+        let result = bulk_apply(&mut conn, &batch.operations).unwrap();
+        assert_eq!((result.saved, result.deleted, result.failed), (1, 1, 1));
+        let statuses: Vec<_> = result.results.iter().map(|r| &r.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                &MarkingStatus::Saved,
+                &MarkingStatus::Failed,
+                &MarkingStatus::Deleted,
+                &MarkingStatus::NotFound
+            ]
+        );
+        assert_eq!(result.results[1].id.as_deref(), Some("bad"));
+        assert!(result.results[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("moduleId"));
+
+        let ids: Vec<String> = conn
+            .prepare("SELECT id FROM annotations")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(ids, ["a1"]);
+        let tombstone: String = conn
+            .query_row(
+                "SELECT row_id FROM sync_tombstones WHERE table_name = 'annotations'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(tombstone, "old");
+    }
+
+    #[test]
+    fn chapter_query_matches_symbols_by_ref_and_marks_by_their_span() {
+        let mut conn = test_connection();
+        let mut across = highlight("across", "John", 2);
+        across["endRef"] = json!({ "book": "John", "chapter": 3, "verse": 2 });
+        bulk_insert(
+            &mut conn,
+            &[
+                highlight("in-h", "John", 3),
+                symbol("in-s", "John", 3),
+                highlight("other-chapter", "John", 4),
+                symbol("other-book", "Mark", 3),
+                across,
+            ],
+        )
+        .unwrap();
+        let ids = |chapter| {
+            let mut ids: Vec<String> = chapter_annotations(&conn, "kjv", "John", chapter)
+                .unwrap()
+                .iter()
+                .map(|a| a["id"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(3), ["across", "in-h", "in-s"]);
+        assert_eq!(ids(2), ["across"]);
+        assert!(chapter_annotations(&conn, "esv", "John", 3)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn typed_annotations_are_checked_and_keep_unknown_fields() {
+        let conn = test_connection();
+        let mut value = symbol("s1", "John", 3);
+        value["position"] = json!("center");
+        value["futureField"] = json!({ "kept": true });
+        value["startWordIndex"] = json!(2);
+        let created = create(&conn, Annotation::from_value(&value).unwrap()).unwrap();
+        let Annotation::Symbol(mark) = &created else {
+            panic!("not a symbol: {created:?}");
+        };
+        assert_eq!(mark.position, Some(SymbolPosition::Center));
+        assert_eq!(mark.words.start_word_index, Some(2));
+        assert!(!mark.extra.contains_key("startWordIndex"));
+        assert_ne!(created.common().created_at, "2025-01-01T00:00:00.000Z");
+        let stored = &chapter_annotations(&conn, "kjv", "John", 3).unwrap()[0];
+        assert_eq!(stored["futureField"], json!({ "kept": true }));
+        assert_eq!(stored["type"], "symbol");
+        assert_eq!(
+            create(&conn, created.clone()).unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+
+        let mut edited = value.clone();
+        edited["symbol"] = json!("dove");
+        let updated = update(&conn, Annotation::from_value(&edited).unwrap(), None).unwrap();
+        assert_eq!(updated.common().created_at, created.common().created_at);
+        let missing = Annotation::from_value(&highlight("h9", "John", 3)).unwrap();
+        assert_eq!(
+            update(&conn, missing.clone(), None).unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+        let remote = RemoteWrite {
+            updated_at: "2030-01-01T00:00:00.000Z".into(),
+            device_id: "dev-x".into(),
+        };
+        update(&conn, missing, Some(&remote)).unwrap();
+        let (status, device): (String, String) = conn
+            .query_row(
+                "SELECT sync_status, device_id FROM annotations WHERE id = 'h9'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((status.as_str(), device.as_str()), ("synced", "dev-x"));
+
+        let invalid = |edit: fn(&mut Value)| {
+            let mut value = highlight("bad", "John", 3);
+            edit(&mut value);
+            Annotation::from_value(&value).and_then(|a| a.check())
+        };
+        assert!(invalid(|_| {}).is_ok());
+        assert!(invalid(|v| v["startRef"]["book"] = json!("Hezekiah")).is_err());
+        assert!(invalid(|v| v["endRef"]["chapter"] = json!(2)).is_err());
+        assert!(invalid(|v| v["color"] = json!("")).is_err());
+        assert!(invalid(|v| {
+            v["startWordIndex"] = json!(4);
+            v["endWordIndex"] = json!(2);
+        })
+        .is_err());
+        assert!(invalid(|v| v["startOffset"] = json!(3)).is_err());
+        assert!(invalid(|v| {
+            v["startOffset"] = json!(-1);
+            v["endOffset"] = json!(5);
+        })
+        .is_err());
+
+        // From word 7 of verse 1 to word 1 of verse 4, stored as it came.
+        let mut spanning = highlight("span", "John", 3);
+        spanning["endRef"]["verse"] = json!(4);
+        for (field, at) in [
+            ("startWordIndex", 7),
+            ("endWordIndex", 1),
+            ("startOffset", 30),
+            ("endOffset", 6),
+        ] {
+            spanning[field] = json!(at);
+        }
+        let mark = Annotation::from_value(&spanning).unwrap();
+        mark.check().unwrap();
+        assert_eq!(serde_json::to_value(&mark).unwrap(), spanning);
+        assert!(invalid(|v| v["type"] = json!("sticker")).is_err());
+        assert!(invalid(|v| v["underlineStyle"] = json!("zigzag")).is_ok());
+        assert!(invalid(|v| {
+            v["type"] = json!("underline");
+            v["underlineStyle"] = json!("zigzag");
+        })
+        .is_err());
+    }
+}
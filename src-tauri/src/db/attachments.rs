@@ -0,0 +1,363 @@
+//! Files attached to notes: images and PDFs, kept once each under
+//! `<app data>/Documents/attachments` as `<sha256>.<ext>`, the folder the
+//! sync container carries to the account's other devices.
+//!
+//! A note links a file by its id (`![](attachment:<sha256>.png)`), so the
+//! notes that use it sync as text and the same file added twice, or on two
+//! devices, is stored once. Nothing records which notes use a file; a file
+//! no note, trashed note or undo step links is an orphan, and
+//! `collect_attachment_garbage` deletes orphans once they are a day old, so
+//! a file added for a note that isn't saved yet survives until it is.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::Manager;
+
+use super::{demo, with_reader, DbError, DbErrorKind};
+use crate::download::hash_file;
+use crate::sync::merge::table_exists;
+
+/// The attachment store, under the sync container's documents folder.
+const ATTACHMENTS_DIR: &str = "Documents/attachments";
+
+/// How notes link an attachment.
+const LINK_PREFIX: &str = "attachment:";
+
+/// Largest file that can be attached.
+pub(crate) const MAX_ATTACHMENT_BYTES: u64 = 50 * 1024 * 1024;
+
+/// How long an orphan is kept before garbage collection deletes it.
+const ORPHAN_GRACE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Extensions that can be attached, and the MIME type each is served as.
+const KINDS: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("heic", "image/heic"),
+    ("pdf", "application/pdf"),
+];
+
+/// A stored attachment.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Attachment {
+    /// `<sha256>.<ext>`.
+    pub id: String,
+    pub mime: &'static str,
+    pub size: u64,
+    pub path: String,
+    /// What a note writes to link it: `attachment:<id>`.
+    pub link: String,
+}
+
+/// The store's size, and what garbage collection would free.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct AttachmentUsage {
+    pub files: usize,
+    pub bytes: u64,
+    /// Files no note links.
+    pub orphans: usize,
+    #[serde(rename = "orphanBytes")]
+    pub orphan_bytes: u64,
+}
+
+/// What a garbage collection deleted.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct GarbageReport {
+    pub removed: Vec<String>,
+    #[serde(rename = "freedBytes")]
+    pub freed_bytes: u64,
+}
+
+fn attachments_dir(app: &tauri::AppHandle) -> Result<PathBuf, DbError> {
+    let dir = match demo::dir() {
+        Some(dir) => dir.to_path_buf(),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| DbError::io(format!("Cannot determine app data dir: {e}")))?,
+    };
+    Ok(dir.join(ATTACHMENTS_DIR))
+}
+
+/// The MIME type of an attachment file name, if it can be attached.
+fn mime(name: &str) -> Option<&'static str> {
+    let (_, ext) = name.rsplit_once('.')?;
+    let ext = ext.to_ascii_lowercase();
+    let ext = if ext == "jpeg" {
+        "jpg".to_string()
+    } else {
+        ext
+    };
+    KINDS.iter().find(|(e, _)| *e == ext).map(|(_, m)| *m)
+}
+
+/// Whether `id` is an attachment id, so it can't name a file outside the
+/// store.
+fn is_id(id: &str) -> bool {
+    id.split_once('.').is_some_and(|(hash, ext)| {
+        hash.len() == 64
+            && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+            && KINDS.iter().any(|(e, _)| *e == ext)
+    })
+}
+
+fn attachment(dir: &Path, id: &str) -> Result<Attachment, DbError> {
+    let not_found = || DbError::new(DbErrorKind::NotFound, format!("No attachment {id}"));
+    if !is_id(id) {
+        return Err(not_found());
+    }
+    let path = dir.join(id);
+    let size = std::fs::metadata(&path).map_err(|_| not_found())?.len();
+    Ok(Attachment {
+        id: id.to_string(),
+        mime: mime(id).ok_or_else(not_found)?,
+        size,
+        path: path.display().to_string(),
+        link: format!("{LINK_PREFIX}{id}"),
+    })
+}
+
+/// Copy `source` into the store under `dir`, unless the same file is there.
+fn store(dir: &Path, source: &Path) -> Result<Attachment, DbError> {
+    let name = source.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let mime = mime(name).ok_or_else(|| {
+        DbError::invalid(format!(
+            "Only images and PDFs can be attached, not {}",
+            source.display()
+        ))
+    })?;
+    let size = std::fs::metadata(source)
+        .map_err(|e| DbError::io(format!("Cannot read {}: {e}", source.display())))?
+        .len();
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(DbError::invalid(format!(
+            "{name} is over the {} MB attachment limit",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+    let hash = hash_file(source)
+        .map_err(|e| DbError::io(format!("Cannot read {}: {e}", source.display())))?;
+    let ext = KINDS
+        .iter()
+        .find(|(_, m)| *m == mime)
+        .map_or("", |(e, _)| e);
+    let id = format!("{hash}.{ext}");
+    let dest = dir.join(&id);
+    if !dest.exists() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| DbError::io(format!("Cannot create {}: {e}", dir.display())))?;
+        // Copied beside it and renamed, so a half-copied file never has
+        // the name the sync container would carry.
+        let partial = dir.join(format!(".{id}.partial"));
+        std::fs::copy(source, &partial)
+            .and_then(|_| std::fs::rename(&partial, &dest))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&partial);
+                DbError::io(format!("Cannot store {name}: {e}"))
+            })?;
+    }
+    attachment(dir, &id)
+}
+
+/// Attachment ids linked in `text`.
+fn links_in(text: &str, ids: &mut HashSet<String>) {
+    for (at, _) in text.match_indices(LINK_PREFIX) {
+        let rest = &text[at + LINK_PREFIX.len()..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))
+            .unwrap_or(rest.len());
+        let id = rest[..end].trim_end_matches('.');
+        if is_id(id) {
+            ids.insert(id.to_string());
+        }
+    }
+}
+
+/// Every attachment a note links, counting trashed notes and the notes
+/// undo, redo or a revert would bring back, which are restored with their
+/// links.
+fn linked(conn: &Connection) -> Result<HashSet<String>, DbError> {
+    let sources = [
+        ("notes", "SELECT content FROM notes"),
+        ("trash", "SELECT row FROM trash WHERE table_name = 'notes'"),
+        (
+            "undo_journal",
+            "SELECT COALESCE(before, '') || COALESCE(after, '') FROM undo_journal
+             WHERE table_name = 'notes'",
+        ),
+        (
+            "edit_history",
+            "SELECT row FROM edit_history WHERE table_name = 'notes' AND row IS NOT NULL",
+        ),
+        ("margin_notes", "SELECT data FROM margin_notes"),
+    ];
+    let mut ids = HashSet::new();
+    for (table, sql) in sources {
+        if !table_exists(conn, "main", table)? {
+            continue;
+        }
+        let mut stmt = conn.prepare(sql)?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            links_in(&row.get::<_, String>(0)?, &mut ids);
+        }
+    }
+    Ok(ids)
+}
+
+/// The files in the store: id, size and when last written.
+fn files(dir: &Path) -> Result<Vec<(String, u64, SystemTime)>, DbError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(DbError::io(format!("Cannot read {}: {e}", dir.display()))),
+    };
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let Some(id) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if is_id(&id) && meta.is_file() {
+            files.push((id, meta.len(), meta.modified().unwrap_or(SystemTime::now())));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn usage(conn: &Connection, dir: &Path) -> Result<AttachmentUsage, DbError> {
+    let linked = linked(conn)?;
+    let mut usage = AttachmentUsage::default();
+    for (id, size, _) in files(dir)? {
+        usage.files += 1;
+        usage.bytes += size;
+        if !linked.contains(&id) {
+            usage.orphans += 1;
+            usage.orphan_bytes += size;
+        }
+    }
+    Ok(usage)
+}
+
+/// Delete the orphans under `dir` last written before `cutoff`.
+fn collect(conn: &Connection, dir: &Path, cutoff: SystemTime) -> Result<GarbageReport, DbError> {
+    let linked = linked(conn)?;
+    let mut report = GarbageReport::default();
+    for (id, size, modified) in files(dir)? {
+        if linked.contains(&id) || modified >= cutoff {
+            continue;
+        }
+        match std::fs::remove_file(dir.join(&id)) {
+            Ok(()) => {
+                report.freed_bytes += size;
+                report.removed.push(id);
+            }
+            Err(e) => println!("[attachments] Cannot remove {id}: {e}"),
+        }
+    }
+    Ok(report)
+}
+
+/// Add the image or PDF at `path` to the store, returning the link a note
+/// writes to show it.
+#[tauri::command]
+pub async fn add_attachment(app: tauri::AppHandle, path: String) -> Result<Attachment, DbError> {
+    let dir = attachments_dir(&app)?;
+    tauri::async_runtime::spawn_blocking(move || store(&dir, Path::new(&path)))
+        .await
+        .map_err(|e| DbError::io(format!("Attachment task failed: {e}")))?
+}
+
+/// The file of attachment `id`, for showing or opening it. `NotFound` until
+/// the sync container has brought a file added on another device.
+#[tauri::command]
+pub async fn get_attachment_path(app: tauri::AppHandle, id: String) -> Result<String, DbError> {
+    let dir = attachments_dir(&app)?;
+    Ok(attachment(&dir, &id)?.path)
+}
+
+#[tauri::command]
+pub async fn get_attachment_usage(app: tauri::AppHandle) -> Result<AttachmentUsage, DbError> {
+    let dir = attachments_dir(&app)?;
+    with_reader(&app, move |conn| usage(conn, &dir)).await
+}
+
+/// Delete attachments no note has linked for a day.
+#[tauri::command]
+pub async fn collect_attachment_garbage(app: tauri::AppHandle) -> Result<GarbageReport, DbError> {
+    let dir = attachments_dir(&app)?;
+    let cutoff = SystemTime::now() - ORPHAN_GRACE;
+    with_reader(&app, move |conn| collect(conn, &dir, cutoff)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn stores_files_once_and_collects_the_ones_no_note_links() {
+        let scratch = ScratchDir::new("attachments");
+        let store_dir = scratch.join("attachments");
+        let (map, scan, notes) = (
+            scratch.join("Map.JPEG"),
+            scratch.join("scan.pdf"),
+            scratch.join("notes.txt"),
+        );
+        std::fs::write(&map, b"a map of Canaan").unwrap();
+        std::fs::write(&scan, b"%PDF-1.4 sermon").unwrap();
+        std::fs::write(&notes, b"plain").unwrap();
+
+        let first = store(&store_dir, &map).unwrap();
+        assert!(first.id.ends_with(".jpg") && first.mime == "image/jpeg");
+        std::fs::copy(&map, scratch.join("copy.jpg")).unwrap();
+        assert_eq!(store(&store_dir, &scratch.join("copy.jpg")).unwrap(), first);
+        let pdf = store(&store_dir, &scan).unwrap();
+        assert_eq!(
+            store(&store_dir, &notes).unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+        assert_eq!(
+            attachment(&store_dir, "../biblemarker.db")
+                .unwrap_err()
+                .kind,
+            DbErrorKind::NotFound
+        );
+
+        let conn = migrated_test_connection();
+        conn.execute(
+            "INSERT INTO notes VALUES ('n1', 'kjv', '{}', NULL, ?1,
+                '2025-01-01', '2025-01-01', 'synced', 'dev-local')",
+            [format!("The land ![map]({}).", first.link)],
+        )
+        .unwrap();
+        let u = usage(&conn, &store_dir).unwrap();
+        assert_eq!((u.files, u.orphans, u.orphan_bytes), (2, 1, pdf.size));
+
+        // Just added: within the grace period.
+        let past = SystemTime::now() - ORPHAN_GRACE;
+        assert!(collect(&conn, &store_dir, past).unwrap().removed.is_empty());
+        let later = SystemTime::now() + Duration::from_secs(1);
+        let report = collect(&conn, &store_dir, later).unwrap();
+        assert_eq!(report.removed, std::slice::from_ref(&pdf.id));
+        assert_eq!(report.freed_bytes, pdf.size);
+
+        // A deleted note keeps its file while it can be restored.
+        conn.execute("DELETE FROM notes WHERE id = 'n1'", [])
+            .unwrap();
+        assert!(collect(&conn, &store_dir, later)
+            .unwrap()
+            .removed
+            .is_empty());
+        assert!(attachment(&store_dir, &first.id).is_ok());
+    }
+}
@@ -0,0 +1,521 @@
+//! Links between notes, for a Zettelkasten-style study: a note's Markdown
+//! can link another by its title (`[[Covenant]]`, `[[Covenant|the
+//! covenant]]`) and a passage by its reference (`[[Heb 11:6]]`). Title
+//! links are indexed by migration 22, passage links with the references the
+//! note's text makes (`note_references`), both from the same queue.
+//!
+//! A note's title is its first line when that is a heading (`# Covenant`);
+//! a note without one can still be linked by its id. Links and mentions in
+//! code (fenced blocks and `inline` spans) don't count.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::ops::Range;
+
+use super::note_references::catch_up;
+use super::{with_reader, DbError, DbErrorKind};
+use crate::content::references::{self, ScriptureReference};
+
+/// Characters of text kept on each side of an unlinked mention.
+const CONTEXT: usize = 40;
+
+/// A note linking to, or citing the passage of, another (`get_backlinks`).
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Backlink {
+    #[serde(rename = "noteId")]
+    pub note_id: String,
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    #[serde(rename = "noteRef")]
+    pub note_ref: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub content: String,
+    /// Its wiki links naming the note, as written.
+    pub links: Vec<String>,
+    /// Its references touching the passage the note is on, e.g. `Hebrews 11:6`.
+    pub passages: Vec<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
+/// A note that names a term without linking it (`get_unlinked_mentions`).
+#[derive(Debug, Serialize, PartialEq)]
+pub struct UnlinkedMention {
+    #[serde(rename = "noteId")]
+    pub note_id: String,
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    #[serde(rename = "noteRef")]
+    pub note_ref: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Each mention with the text around it, e.g. `…the new covenant in my…`.
+    pub mentions: Vec<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
+/// A `[[…]]` link in a note.
+#[derive(Debug, PartialEq)]
+pub(crate) struct WikiLink<'a> {
+    /// What it links to: before any `|` label or `#` heading, trimmed.
+    pub target: &'a str,
+    /// The whole link as written, without the brackets.
+    pub label: &'a str,
+}
+
+/// How a link target or title is compared: case and spacing folded.
+pub(crate) fn key(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Byte ranges of `content` that are code, where nothing links or mentions.
+fn code(content: &str) -> Vec<Range<usize>> {
+    let mut code = Vec::new();
+    let mut fence: Option<usize> = None;
+    let mut at = 0;
+    for line in content.split_inclusive('\n') {
+        let end = at + line.len();
+        if line.trim_start().starts_with("```") {
+            match fence.take() {
+                Some(start) => code.push(start..end),
+                None => fence = Some(at),
+            }
+        } else if fence.is_none() {
+            let mut open: Option<usize> = None;
+            for (i, _) in line.char_indices().filter(|&(_, c)| c == '`') {
+                match open.take() {
+                    Some(start) => code.push(at + start..at + i + 1),
+                    None => open = Some(i),
+                }
+            }
+        }
+        at = end;
+    }
+    if let Some(start) = fence {
+        code.push(start..content.len());
+    }
+    code
+}
+
+fn overlaps(spans: &[Range<usize>], range: &Range<usize>) -> bool {
+    spans
+        .iter()
+        .any(|s| s.start < range.end && range.start < s.end)
+}
+
+/// The wiki links in `content` with where each is, outside code.
+fn links_at(content: &str) -> Vec<(Range<usize>, WikiLink<'_>)> {
+    let code = code(content);
+    let mut links = Vec::new();
+    let mut from = 0;
+    while let Some(open) = content[from..].find("[[").map(|i| from + i) {
+        let inner = open + 2;
+        let Some(close) = content[inner..].find("]]").map(|i| inner + i) else {
+            break;
+        };
+        let label = &content[inner..close];
+        if label.contains('\n') || label.contains("[[") {
+            from = inner;
+            continue;
+        }
+        let span = open..close + 2;
+        let target = label.split(['|', '#']).next().unwrap_or_default().trim();
+        if !target.is_empty() && !overlaps(&code, &span) {
+            links.push((span, WikiLink { target, label }));
+        }
+        from = close + 2;
+    }
+    links
+}
+
+pub(crate) fn links(content: &str) -> Vec<WikiLink<'_>> {
+    links_at(content)
+        .into_iter()
+        .map(|(_, link)| link)
+        .collect()
+}
+
+/// The passage a link names, if it names one: it reads as a reference and
+/// has a number, so `[[John]]` stays a link to a note titled John.
+fn passage(target: &str) -> Vec<ScriptureReference> {
+    if !target.chars().any(|c| c.is_ascii_digit()) {
+        return Vec::new();
+    }
+    references::parse(target)
+}
+
+/// A note's title: its first line, when that is a Markdown heading.
+pub(crate) fn title(content: &str) -> Option<String> {
+    let line = content.lines().find(|l| !l.trim().is_empty())?.trim();
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    if !(1..=6).contains(&hashes) || !line[hashes..].starts_with(char::is_whitespace) {
+        return None;
+    }
+    let title = line[hashes..].trim().trim_end_matches('#').trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Re-index `note_id`'s title links from `content` (`None` once the note is
+/// gone), returning the passages it links, for `note_references`.
+pub(crate) fn index(
+    conn: &Connection,
+    note_id: &str,
+    content: Option<&str>,
+) -> Result<Vec<ScriptureReference>, DbError> {
+    conn.execute("DELETE FROM note_links WHERE note_id = ?1", [note_id])?;
+    let mut passages = Vec::new();
+    for link in links(content.unwrap_or_default()) {
+        let cited = passage(link.target);
+        if cited.is_empty() {
+            conn.execute(
+                "INSERT OR IGNORE INTO note_links (note_id, target, label) VALUES (?1, ?2, ?3)",
+                params![note_id, key(link.target), link.label],
+            )?;
+        }
+        passages.extend(cited);
+    }
+    Ok(passages)
+}
+
+/// A chapter and verse.
+type Verse = (i64, i64);
+
+/// Where a note is attached: its book, first verse and last.
+fn passage_of(
+    note_ref: &serde_json::Value,
+    range: &serde_json::Value,
+) -> Option<(String, Verse, Verse)> {
+    let at =
+        |v: &serde_json::Value| Some((v["chapter"].as_i64()?, v["verse"].as_i64().unwrap_or(1)));
+    let start = if range.is_object() {
+        &range["start"]
+    } else {
+        note_ref
+    };
+    let end = if range.is_object() {
+        &range["end"]
+    } else {
+        note_ref
+    };
+    Some((start["book"].as_str()?.to_string(), at(start)?, at(end)?))
+}
+
+/// Notes that link to `note_id` by its title or id, or cite the passage it
+/// is on, most recently edited first.
+pub(crate) fn backlinks(conn: &Connection, note_id: &str) -> Result<Vec<Backlink>, DbError> {
+    let (content, note_ref, range): (String, String, Option<String>) = conn
+        .query_row(
+            "SELECT content, ref, range FROM notes WHERE id = ?1",
+            [note_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("No note {note_id}")))?;
+    let mut targets = vec![key(note_id)];
+    targets.extend(title(&content).map(|t| key(&t)));
+    let parse = |s: &str| serde_json::from_str(s).unwrap_or_default();
+    let attached = passage_of(
+        &parse(&note_ref),
+        &range.as_deref().map(parse).unwrap_or_default(),
+    );
+
+    let mut found: Vec<Backlink> = Vec::new();
+    let mut add =
+        |conn: &Connection, id: String, label: String, is_link: bool| -> Result<(), DbError> {
+            let link = match found.iter_mut().find(|b| b.note_id == id) {
+                Some(link) => link,
+                None => {
+                    let Some(link) = conn
+                        .query_row(
+                            "SELECT module_id, ref, content, updated_at FROM notes WHERE id = ?1",
+                            [&id],
+                            |row| {
+                                let note_ref: String = row.get(1)?;
+                                let content: String = row.get(2)?;
+                                Ok(Backlink {
+                                    note_id: id.clone(),
+                                    module_id: row.get(0)?,
+                                    note_ref: serde_json::from_str(&note_ref).unwrap_or_default(),
+                                    title: title(&content),
+                                    content,
+                                    links: Vec::new(),
+                                    passages: Vec::new(),
+                                    updated_at: row.get(3)?,
+                                })
+                            },
+                        )
+                        .optional()?
+                    else {
+                        return Ok(());
+                    };
+                    found.push(link);
+                    found.last_mut().expect("just pushed")
+                }
+            };
+            let labels = if is_link {
+                &mut link.links
+            } else {
+                &mut link.passages
+            };
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+            Ok(())
+        };
+
+    let mut stmt = conn.prepare(
+        "SELECT note_id, label FROM note_links WHERE target = ?1 AND note_id <> ?2 ORDER BY note_id",
+    )?;
+    for target in &targets {
+        let rows: Vec<(String, String)> = stmt
+            .query_map(params![target, note_id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        for (id, label) in rows {
+            add(conn, id, label, true)?;
+        }
+    }
+    if let Some((book, start, end)) = attached {
+        let rows: Vec<(String, String)> = conn
+            .prepare(
+                "SELECT note_id, label FROM note_references
+                 WHERE book = ?1 AND note_id <> ?2
+                   AND (chapter, verse) <= (?5, ?6) AND (end_chapter, end_verse) >= (?3, ?4)
+                 ORDER BY note_id, chapter, verse",
+            )?
+            .query_map(
+                params![book, note_id, start.0, start.1, end.0, end.1],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?
+            .collect::<rusqlite::Result<_>>()?;
+        for (id, label) in rows {
+            add(conn, id, label, false)?;
+        }
+    }
+    found.sort_by(|a, b| {
+        b.updated_at
+            .cmp(&a.updated_at)
+            .then(a.note_id.cmp(&b.note_id))
+    });
+    Ok(found)
+}
+
+/// Where `term_lower` (lowercased) starts `text[at..]`, ignoring case: the
+/// end of the match.
+fn match_at(text: &str, at: usize, term_lower: &[char]) -> Option<usize> {
+    let mut want = term_lower.iter();
+    let mut pending = want.next();
+    for (i, c) in text[at..].char_indices() {
+        for lower in c.to_lowercase() {
+            if pending != Some(&lower) {
+                return None;
+            }
+            pending = want.next();
+        }
+        if pending.is_none() {
+            return Some(at + i + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// `text` around `range`, on one line, cut to `CONTEXT` characters a side.
+fn snippet(text: &str, range: &Range<usize>) -> String {
+    let before: Vec<char> = text[..range.start]
+        .chars()
+        .rev()
+        .take(CONTEXT + 1)
+        .collect();
+    let after: Vec<char> = text[range.end..].chars().take(CONTEXT + 1).collect();
+    let mut snippet = String::new();
+    if before.len() > CONTEXT {
+        snippet.push('…');
+    }
+    snippet.extend(before.iter().take(CONTEXT).rev());
+    snippet.push_str(&text[range.clone()]);
+    snippet.extend(after.iter().take(CONTEXT));
+    if after.len() > CONTEXT {
+        snippet.push('…');
+    }
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The places `content` says `term` as whole words, outside links and code.
+fn mentions(content: &str, term: &str) -> Vec<Range<usize>> {
+    let term_lower: Vec<char> = term.chars().flat_map(char::to_lowercase).collect();
+    let mut skip = code(content);
+    skip.extend(links_at(content).into_iter().map(|(span, _)| span));
+    let word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let mut found: Vec<Range<usize>> = Vec::new();
+    for (at, _) in content.char_indices() {
+        if found.last().is_some_and(|f| at < f.end) || word(content[..at].chars().next_back()) {
+            continue;
+        }
+        let Some(end) = match_at(content, at, &term_lower) else {
+            continue;
+        };
+        if !word(content[end..].chars().next()) && !overlaps(&skip, &(at..end)) {
+            found.push(at..end);
+        }
+    }
+    found
+}
+
+/// Notes that say `term` without linking it, most recently edited first,
+/// leaving out the note titled `term` itself.
+pub(crate) fn unlinked_mentions(
+    conn: &Connection,
+    term: &str,
+) -> Result<Vec<UnlinkedMention>, DbError> {
+    let term = term.split_whitespace().collect::<Vec<_>>().join(" ");
+    if term.is_empty() {
+        return Err(DbError::invalid("Nothing to look for"));
+    }
+    let mut stmt = conn.prepare(
+        "SELECT id, module_id, ref, content, updated_at FROM notes
+         WHERE instr(lower(content), lower(?1)) > 0
+         ORDER BY updated_at DESC, id",
+    )?;
+    let rows: Vec<(String, String, String, String, String)> = stmt
+        .query_map([&term], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut found = Vec::new();
+    for (note_id, module_id, note_ref, content, updated_at) in rows {
+        let title = title(&content);
+        if title.as_deref().map(key) == Some(key(&term)) {
+            continue;
+        }
+        let mentions: Vec<String> = mentions(&content, &term)
+            .iter()
+            .map(|range| snippet(&content, range))
+            .collect();
+        if mentions.is_empty() {
+            continue;
+        }
+        found.push(UnlinkedMention {
+            note_id,
+            module_id,
+            note_ref: serde_json::from_str(&note_ref).unwrap_or_default(),
+            title,
+            mentions,
+            updated_at,
+        });
+    }
+    Ok(found)
+}
+
+/// Notes linking to `note_id` (`[[its title]]` or `[[its id]]`) or citing
+/// the passage it is on.
+#[tauri::command]
+pub async fn get_backlinks(
+    app: tauri::AppHandle,
+    note_id: String,
+) -> Result<Vec<Backlink>, DbError> {
+    catch_up(&app).await?;
+    with_reader(&app, move |conn| backlinks(conn, &note_id)).await
+}
+
+/// Notes that mention `term` (a note's title, say) in plain text, as
+/// candidates for a link.
+#[tauri::command]
+pub async fn get_unlinked_mentions(
+    app: tauri::AppHandle,
+    term: String,
+) -> Result<Vec<UnlinkedMention>, DbError> {
+    with_reader(&app, move |conn| unlinked_mentions(conn, &term)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+    use crate::db::note_references::refresh;
+
+    fn note(conn: &Connection, id: &str, content: &str) {
+        conn.execute(
+            "INSERT OR REPLACE INTO notes (id, module_id, ref, content, created_at, updated_at)
+             VALUES (?1, 'kjv', '{\"book\":\"Heb\",\"chapter\":11,\"verse\":6}', ?2,
+                     '2025-01-01', ?1)",
+            params![id, content],
+        )
+        .unwrap();
+    }
+
+    fn ids<T>(found: &[T], id: impl Fn(&T) -> &str) -> Vec<&str> {
+        found.iter().map(id).collect()
+    }
+
+    #[test]
+    fn reads_links_titles_and_mentions() {
+        let text = "# Covenant #\nSee [[ New  Covenant|the new one]], `[[code]]` and [[Heb 11:6]].";
+        assert_eq!(title(text).as_deref(), Some("Covenant"));
+        assert_eq!(title("#hashtag first"), None);
+        let found = links(text);
+        assert_eq!(ids(&found, |l| l.target), ["New  Covenant", "Heb 11:6"]);
+        assert_eq!(key(found[0].target), "new covenant");
+        assert!(passage("John").is_empty() && !passage("Heb 11:6").is_empty());
+
+        let text = "The covenant, covenants and [[Covenant]]; God's COVENANT.";
+        let spans = mentions(text, "Covenant");
+        assert_eq!(
+            spans.iter().map(|r| &text[r.clone()]).collect::<Vec<_>>(),
+            ["covenant", "COVENANT"]
+        );
+        assert_eq!(snippet("a\nb covenant c", &(4..12)), "a b covenant c");
+    }
+
+    #[test]
+    fn finds_backlinks_and_unlinked_mentions() {
+        let mut conn = migrated_test_connection();
+        note(&conn, "n1", "# Covenant\nGod binds himself by oath.");
+        note(
+            &conn,
+            "n2",
+            "Abraham believed: see [[covenant]] and [[n1|the oath]].",
+        );
+        note(
+            &conn,
+            "n3",
+            "Faith pleases God, [[Heb 11:6]]. The covenant is sure.",
+        );
+        note(&conn, "n4", "Nothing here about it.");
+        refresh(&mut conn).unwrap();
+
+        let found = backlinks(&conn, "n1").unwrap();
+        assert_eq!(ids(&found, |b| &b.note_id), ["n3", "n2"]);
+        assert_eq!(found[1].links, ["n1|the oath", "covenant"]);
+        assert_eq!(found[0].passages, ["Hebrews 11:6"]);
+        assert_eq!(
+            backlinks(&conn, "gone").unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+
+        let mentioned = unlinked_mentions(&conn, " covenant ").unwrap();
+        assert_eq!(ids(&mentioned, |m| &m.note_id), ["n3"]);
+        assert_eq!(
+            mentioned[0].mentions,
+            ["Faith pleases God, [[Heb 11:6]]. The covenant is sure."]
+        );
+
+        note(&conn, "n2", "No links any more.");
+        refresh(&mut conn).unwrap();
+        assert_eq!(
+            ids(&backlinks(&conn, "n1").unwrap(), |b| &b.note_id),
+            ["n3"]
+        );
+    }
+}
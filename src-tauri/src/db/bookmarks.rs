@@ -0,0 +1,424 @@
+//! Bookmarks: passages kept for coming back to ("where I'm studying"),
+//! optionally filed in folders, in the synced `bookmarks` and
+//! `bookmark_folders` tables of migration 23. Unlike highlights they mark
+//! no words and carry no color.
+//!
+//! Both are generic data rows like `places`, carried by sync, merges and
+//! dumps with no code of their own. A bookmark whose folder is gone (deleted
+//! on another device before a sync) shows at the top level.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::annotations::VerseRef;
+use super::data_rows::{self, DataRow};
+use super::{with_connection, with_reader, DbError, DbErrorKind};
+use crate::content::books;
+
+/// Longest label or folder name accepted, in characters.
+const MAX_LABEL: usize = 200;
+
+/// A bookmark, as stored in `bookmarks.data`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bookmark {
+    pub id: String,
+    #[serde(rename = "ref")]
+    pub verse_ref: VerseRef,
+    /// The last verse, for a bookmark on a passage; same book, not before
+    /// `ref`.
+    #[serde(rename = "endRef", default, skip_serializing_if = "Option::is_none")]
+    pub end_ref: Option<VerseRef>,
+    /// The translation it was made in, to open it in again.
+    #[serde(rename = "moduleId", default, skip_serializing_if = "Option::is_none")]
+    pub module_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Its `BookmarkFolder`; none at the top level.
+    #[serde(rename = "folderId", default, skip_serializing_if = "Option::is_none")]
+    pub folder_id: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BookmarkFolder {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DataRow for Bookmark {
+    const TABLE: &'static str = "bookmarks";
+    const NOUN: &'static str = "bookmark";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+impl DataRow for BookmarkFolder {
+    const TABLE: &'static str = "bookmark_folders";
+    const NOUN: &'static str = "bookmark folder";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FolderEntry {
+    #[serde(flatten)]
+    pub folder: BookmarkFolder,
+    pub bookmarks: i64,
+}
+
+/// Checked as annotations' are: translations number verses differently, so
+/// only the book is known for sure.
+fn check_ref(field: &str, r: &VerseRef) -> Result<(), DbError> {
+    if books::position(&r.book).is_none() || r.chapter < 1 || r.verse < 1 {
+        return Err(DbError::invalid(format!(
+            "Bookmark `{field}` is not a verse ({} {}:{})",
+            r.book, r.chapter, r.verse
+        )));
+    }
+    Ok(())
+}
+
+/// Trimmed, and none if empty. Fails `Invalid` past `MAX_LABEL`.
+fn tidy(text: Option<String>, what: &str) -> Result<Option<String>, DbError> {
+    let text = text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if text.as_ref().is_some_and(|t| t.chars().count() > MAX_LABEL) {
+        return Err(DbError::invalid(format!(
+            "A bookmark {what} can be at most {MAX_LABEL} characters"
+        )));
+    }
+    Ok(text)
+}
+
+/// Tidy `bookmark` and check it points at verses and a folder that exist.
+fn check(conn: &Connection, bookmark: &mut Bookmark) -> Result<(), DbError> {
+    if bookmark.id.trim().is_empty() {
+        return Err(DbError::invalid("Bookmark has no id"));
+    }
+    check_ref("ref", &bookmark.verse_ref)?;
+    if bookmark.end_ref.as_ref() == Some(&bookmark.verse_ref) {
+        bookmark.end_ref = None;
+    }
+    if let Some(end) = &bookmark.end_ref {
+        check_ref("endRef", end)?;
+        let start = &bookmark.verse_ref;
+        if end.book != start.book || (end.chapter, end.verse) < (start.chapter, start.verse) {
+            return Err(DbError::invalid(
+                "A bookmark's `endRef` must follow its `ref` in the same book",
+            ));
+        }
+    }
+    bookmark.label = tidy(bookmark.label.take(), "label")?;
+    bookmark.module_id = bookmark.module_id.take().filter(|m| !m.trim().is_empty());
+    bookmark.folder_id = bookmark.folder_id.take().filter(|f| !f.trim().is_empty());
+    if let Some(folder) = &bookmark.folder_id {
+        if data_rows::created_at(conn, "bookmark_folders", folder)?.is_none() {
+            return Err(DbError::new(
+                DbErrorKind::NotFound,
+                format!("No bookmark folder {folder}"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Bookmarks in `folder`, or every one without it, newest first.
+pub(crate) fn bookmarks(conn: &Connection, folder: Option<&str>) -> Result<Vec<Bookmark>, DbError> {
+    let folders: Vec<String> = data_rows::all::<BookmarkFolder>(conn)?
+        .into_iter()
+        .map(|f| f.id)
+        .collect();
+    let mut bookmarks: Vec<Bookmark> = data_rows::all::<Bookmark>(conn)?
+        .into_iter()
+        .filter(|b| folder.is_none() || b.folder_id.as_deref() == folder)
+        .collect();
+    for bookmark in &mut bookmarks {
+        if bookmark
+            .folder_id
+            .as_ref()
+            .is_some_and(|f| !folders.contains(f))
+        {
+            bookmark.folder_id = None;
+        }
+    }
+    bookmarks.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(bookmarks)
+}
+
+/// Folders by name, with how many bookmarks each holds.
+pub(crate) fn folders(conn: &Connection) -> Result<Vec<FolderEntry>, DbError> {
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    for bookmark in data_rows::all::<Bookmark>(conn)? {
+        if let Some(folder) = bookmark.folder_id {
+            *counts.entry(folder).or_insert(0) += 1;
+        }
+    }
+    let mut folders: Vec<FolderEntry> = data_rows::all::<BookmarkFolder>(conn)?
+        .into_iter()
+        .map(|folder| FolderEntry {
+            bookmarks: counts.get(&folder.id).copied().unwrap_or(0),
+            folder,
+        })
+        .collect();
+    folders.sort_by_cached_key(|f| (f.folder.name.to_lowercase(), f.folder.id.clone()));
+    Ok(folders)
+}
+
+/// Add a bookmark. Fails `Invalid` if its id is taken or it isn't on verses
+/// that exist, `NotFound` if its folder doesn't.
+pub(crate) fn create(conn: &Connection, mut bookmark: Bookmark) -> Result<Bookmark, DbError> {
+    check(conn, &mut bookmark)?;
+    data_rows::insert(conn, bookmark)
+}
+
+/// Save an edited bookmark (moved, relabelled or refiled) over the stored
+/// one of its id.
+pub(crate) fn update(conn: &Connection, mut bookmark: Bookmark) -> Result<Bookmark, DbError> {
+    check(conn, &mut bookmark)?;
+    data_rows::replace(conn, bookmark)
+}
+
+/// Tidy `folder` and check its name is free among `others`.
+fn check_folder(folder: &mut BookmarkFolder, others: &[FolderEntry]) -> Result<(), DbError> {
+    if folder.id.trim().is_empty() {
+        return Err(DbError::invalid("Bookmark folder has no id"));
+    }
+    folder.name = tidy(Some(std::mem::take(&mut folder.name)), "folder name")?
+        .ok_or_else(|| DbError::invalid("Give the folder a name"))?;
+    if others
+        .iter()
+        .any(|o| o.folder.id != folder.id && o.folder.name.eq_ignore_ascii_case(&folder.name))
+    {
+        return Err(DbError::invalid(format!(
+            "There is already a folder named {}",
+            folder.name
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn create_folder(
+    conn: &Connection,
+    mut folder: BookmarkFolder,
+) -> Result<BookmarkFolder, DbError> {
+    check_folder(&mut folder, &folders(conn)?)?;
+    data_rows::insert(conn, folder)
+}
+
+/// Rename a folder.
+pub(crate) fn update_folder(
+    conn: &Connection,
+    mut folder: BookmarkFolder,
+) -> Result<BookmarkFolder, DbError> {
+    check_folder(&mut folder, &folders(conn)?)?;
+    data_rows::replace(conn, folder)
+}
+
+/// Delete a folder, moving its bookmarks to the top level, or deleting them
+/// too with `with_bookmarks`. False if there was no such folder.
+pub(crate) fn delete_folder(
+    conn: &mut Connection,
+    id: &str,
+    with_bookmarks: bool,
+) -> Result<bool, DbError> {
+    let tx = conn.transaction()?;
+    for mut bookmark in bookmarks(&tx, Some(id))? {
+        if with_bookmarks {
+            data_rows::delete(&tx, "bookmarks", &bookmark.id)?;
+        } else {
+            bookmark.folder_id = None;
+            data_rows::save(&tx, &mut bookmark)?;
+        }
+    }
+    let deleted = data_rows::delete(&tx, "bookmark_folders", id)?;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Bookmarks newest first: those in `folder_id`, or all with none.
+#[tauri::command]
+pub async fn get_bookmarks(
+    app: tauri::AppHandle,
+    folder_id: Option<String>,
+) -> Result<Vec<Bookmark>, DbError> {
+    with_reader(&app, move |conn| bookmarks(conn, folder_id.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn create_bookmark(
+    app: tauri::AppHandle,
+    bookmark: Bookmark,
+) -> Result<Bookmark, DbError> {
+    with_connection(&app, move |conn| create(conn, bookmark)).await
+}
+
+#[tauri::command]
+pub async fn update_bookmark(
+    app: tauri::AppHandle,
+    bookmark: Bookmark,
+) -> Result<Bookmark, DbError> {
+    with_connection(&app, move |conn| update(conn, bookmark)).await
+}
+
+#[tauri::command]
+pub async fn delete_bookmark(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| data_rows::delete(conn, "bookmarks", &id)).await
+}
+
+/// Bookmark folders by name, with how many bookmarks each holds.
+#[tauri::command]
+pub async fn get_bookmark_folders(app: tauri::AppHandle) -> Result<Vec<FolderEntry>, DbError> {
+    with_reader(&app, folders).await
+}
+
+#[tauri::command]
+pub async fn create_bookmark_folder(
+    app: tauri::AppHandle,
+    folder: BookmarkFolder,
+) -> Result<BookmarkFolder, DbError> {
+    with_connection(&app, move |conn| create_folder(conn, folder)).await
+}
+
+#[tauri::command]
+pub async fn update_bookmark_folder(
+    app: tauri::AppHandle,
+    folder: BookmarkFolder,
+) -> Result<BookmarkFolder, DbError> {
+    with_connection(&app, move |conn| update_folder(conn, folder)).await
+}
+
+/// Delete a folder; its bookmarks move to the top level unless
+/// `with_bookmarks`.
+#[tauri::command]
+pub async fn delete_bookmark_folder(
+    app: tauri::AppHandle,
+    id: String,
+    with_bookmarks: Option<bool>,
+) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| {
+        delete_folder(conn, &id, with_bookmarks.unwrap_or(false))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+
+    fn verse(book: &str, chapter: i64, verse: i64) -> VerseRef {
+        VerseRef {
+            book: book.into(),
+            chapter,
+            verse,
+        }
+    }
+
+    fn bookmark(id: &str, at: VerseRef, folder: Option<&str>) -> Bookmark {
+        Bookmark {
+            id: id.into(),
+            verse_ref: at,
+            end_ref: None,
+            module_id: Some("kjv".into()),
+            label: None,
+            folder_id: folder.map(str::to_string),
+            created_at: String::new(),
+            updated_at: String::new(),
+            extra: Map::new(),
+        }
+    }
+
+    fn folder(id: &str, name: &str) -> BookmarkFolder {
+        BookmarkFolder {
+            id: id.into(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn files_bookmarks_in_folders() {
+        let mut conn = migrated_test_connection();
+        create_folder(&conn, folder("f1", " Studying now ")).unwrap();
+        assert_eq!(
+            create_folder(&conn, folder("f2", "studying NOW"))
+                .unwrap_err()
+                .kind,
+            DbErrorKind::Invalid
+        );
+
+        let mut passage = bookmark("b1", verse("Rom", 8, 28), Some("f1"));
+        passage.end_ref = Some(verse("Rom", 8, 39));
+        passage.label = Some("  ".into());
+        let saved = create(&conn, passage).unwrap();
+        assert_eq!(saved.label, None);
+        create(&conn, bookmark("b2", verse("John", 3, 16), None)).unwrap();
+        for bad in [
+            verse("John", 3, 0),
+            verse("John", 0, 1),
+            verse("Nope", 1, 1),
+        ] {
+            assert_eq!(
+                create(&conn, bookmark("bad", bad, None)).unwrap_err().kind,
+                DbErrorKind::Invalid
+            );
+        }
+        assert_eq!(
+            create(&conn, bookmark("b3", verse("Gen", 1, 1), Some("gone")))
+                .unwrap_err()
+                .kind,
+            DbErrorKind::NotFound
+        );
+
+        let entries = folders(&conn).unwrap();
+        assert_eq!(
+            (entries[0].folder.name.as_str(), entries[0].bookmarks),
+            ("Studying now", 1)
+        );
+        let filed = bookmarks(&conn, Some("f1")).unwrap();
+        assert_eq!(filed.len(), 1);
+        assert_eq!(filed[0].end_ref, Some(verse("Rom", 8, 39)));
+
+        let mut moved = filed[0].clone();
+        moved.label = Some("All things".into());
+        let created = moved.created_at.clone();
+        assert_eq!(update(&conn, moved).unwrap().created_at, created);
+
+        assert!(delete_folder(&mut conn, "f1", false).unwrap());
+        let all = bookmarks(&conn, None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(all.iter().all(|b| b.folder_id.is_none()));
+        assert_eq!(
+            all.iter().find(|b| b.id == "b1").unwrap().label.as_deref(),
+            Some("All things")
+        );
+    }
+}
@@ -0,0 +1,438 @@
+//! Bulk edits: one change made to every mark or note a filter picks, such
+//! as recoloring every mark tagged "Covenant", moving every note from one
+//! tag to another, or deleting the marks made in a chapter today.
+//!
+//! An edit runs in one transaction and is one undo step: annotation and
+//! note writes are journaled by migration 17's triggers and tag links by
+//! migration 30's, and `undo::group_since` stamps them together. A dry run
+//! does the same work and rolls it back, so its summary is exactly what the
+//! edit would do.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use super::annotations::{self, Annotation, VerseRef};
+use super::tags::{self, ItemType, Tags};
+use super::{device_id, now_iso, undo, with_connection, DbError, DbErrorKind};
+
+/// Which items an edit applies to; every field given must match.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BulkFilter {
+    /// One translation's marks and notes.
+    #[serde(rename = "moduleId", default)]
+    pub module_id: Option<String>,
+    /// OSIS book.
+    #[serde(default)]
+    pub book: Option<String>,
+    /// A chapter of `book`; a mark or note spanning it matches.
+    #[serde(default)]
+    pub chapter: Option<i64>,
+    /// Tagged with this tag (id or path) or one under it.
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Created at or after this instant (RFC 3339). "Today" is the caller's
+    /// local midnight, which only the caller knows.
+    #[serde(rename = "createdSince", default)]
+    pub created_since: Option<String>,
+    /// Marks of this color; no note has one.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Only marks, or only notes; a recolor or delete is of marks anyway.
+    #[serde(rename = "itemType", default)]
+    pub item_type: Option<ItemType>,
+}
+
+impl BulkFilter {
+    fn is_empty(&self) -> bool {
+        self.module_id.is_none()
+            && self.book.is_none()
+            && self.tag.is_none()
+            && self.created_since.is_none()
+            && self.color.is_none()
+    }
+}
+
+/// The change to make.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BulkEdit {
+    /// Give marks `color`. Their highlight style is dropped, since it would
+    /// draw them as before; the flat look is what is left.
+    Recolor { color: String },
+    /// Move items from tag `from` to tag `to` (made if new).
+    Retag { from: String, to: String },
+    /// Delete marks; they go to the trash.
+    Delete,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkEditRequest {
+    #[serde(flatten)]
+    pub edit: BulkEdit,
+    #[serde(default)]
+    pub filter: BulkFilter,
+    /// Report what would change and change nothing.
+    #[serde(rename = "dryRun", default)]
+    pub dry_run: bool,
+}
+
+/// What an edit did, or with `dryRun` would do.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct BulkEditSummary {
+    /// Items the filter picked.
+    pub matched: usize,
+    /// Items changed; a mark already of the color isn't.
+    pub changed: usize,
+    /// The changed items' ids.
+    pub ids: Vec<String>,
+    /// The undo step it made, for `undo_last` to take back; none for a dry
+    /// run or an edit that changed nothing.
+    #[serde(rename = "undoAt", skip_serializing_if = "Option::is_none")]
+    pub undo_at: Option<String>,
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+}
+
+/// A mark or note, as far as a filter looks at it.
+struct Item {
+    item_type: ItemType,
+    id: String,
+    module_id: String,
+    /// Where it begins and ends.
+    span: Option<(VerseRef, VerseRef)>,
+    created_at: String,
+    color: Option<String>,
+    /// The stored JSON, for marks.
+    data: Option<Value>,
+}
+
+impl Item {
+    fn spans(&self, book: &str, chapter: Option<i64>) -> bool {
+        self.span.as_ref().is_some_and(|(start, end)| {
+            start.book == book && chapter.is_none_or(|c| (start.chapter..=end.chapter).contains(&c))
+        })
+    }
+}
+
+fn mark(data: Value) -> Option<Item> {
+    let annotation = Annotation::from_value(&data).ok()?;
+    let (span, color) = match &annotation {
+        Annotation::Highlight(a) | Annotation::TextColor(a) => (
+            (a.span.start_ref.clone(), a.span.end_ref.clone()),
+            Some(a.color.clone()),
+        ),
+        Annotation::Underline(a) => (
+            (a.span.start_ref.clone(), a.span.end_ref.clone()),
+            Some(a.color.clone()),
+        ),
+        Annotation::Symbol(a) => (
+            (
+                a.verse_ref.clone(),
+                a.end_ref.clone().unwrap_or_else(|| a.verse_ref.clone()),
+            ),
+            a.color.clone(),
+        ),
+    };
+    let common = annotation.common();
+    Some(Item {
+        item_type: ItemType::Annotation,
+        id: common.id.clone(),
+        module_id: common.module_id.clone(),
+        span: Some(span),
+        created_at: common.created_at.clone(),
+        color,
+        data: Some(data),
+    })
+}
+
+/// Every mark and, unless `marks_only`, every note; rows that aren't one
+/// are passed over.
+fn items(conn: &Connection, marks_only: bool) -> Result<Vec<Item>, DbError> {
+    let mut items = Vec::new();
+    let mut stmt = conn.prepare("SELECT data FROM annotations ORDER BY id")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let data: String = row.get(0)?;
+        if let Some(item) = serde_json::from_str(&data).ok().and_then(mark) {
+            items.push(item);
+        }
+    }
+    if marks_only {
+        return Ok(items);
+    }
+    let mut stmt =
+        conn.prepare("SELECT id, module_id, ref, range, created_at FROM notes ORDER BY id")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let verse: Option<VerseRef> = serde_json::from_str(&row.get::<_, String>(2)?).ok();
+        let range: Option<Value> = row
+            .get::<_, Option<String>>(3)?
+            .and_then(|r| serde_json::from_str(&r).ok());
+        let end = range
+            .and_then(|r| serde_json::from_value::<VerseRef>(r["end"].clone()).ok())
+            .or_else(|| verse.clone());
+        items.push(Item {
+            item_type: ItemType::Note,
+            id: row.get(0)?,
+            module_id: row.get(1)?,
+            span: verse.zip(end),
+            created_at: row.get(4)?,
+            color: None,
+            data: None,
+        });
+    }
+    Ok(items)
+}
+
+/// The items tagged `tag` or a tag under it.
+fn tagged(conn: &Connection, tag: &str) -> Result<HashSet<(ItemType, String)>, DbError> {
+    let tags = Tags::load(conn)?;
+    let found = tags
+        .find(tag)
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("No tag `{tag}`")))?;
+    let subtree: HashSet<String> = tags.subtree(&found.id).into_iter().collect();
+    let mut stmt = conn.prepare("SELECT data FROM tag_links")?;
+    let links = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(links
+        .iter()
+        .filter_map(|d| serde_json::from_str::<tags::TagLink>(d).ok())
+        .filter(|l| subtree.contains(&l.tag_id))
+        .map(|l| (l.item_type, l.item_id))
+        .collect())
+}
+
+/// The items `filter` picks, marks first.
+fn matching(
+    conn: &Connection,
+    filter: &BulkFilter,
+    marks_only: bool,
+) -> Result<Vec<Item>, DbError> {
+    if filter.chapter.is_some() && filter.book.is_none() {
+        return Err(DbError::invalid("A chapter filter needs its book"));
+    }
+    let tagged = filter.tag.as_deref().map(|t| tagged(conn, t)).transpose()?;
+    Ok(items(conn, marks_only)?
+        .into_iter()
+        .filter(|i| filter.item_type.is_none_or(|t| t == i.item_type))
+        .filter(|i| filter.module_id.as_ref().is_none_or(|m| *m == i.module_id))
+        .filter(|i| {
+            filter
+                .book
+                .as_deref()
+                .is_none_or(|b| i.spans(b, filter.chapter))
+        })
+        .filter(|i| {
+            filter
+                .created_since
+                .as_ref()
+                .is_none_or(|since| i.created_at >= *since)
+        })
+        .filter(|i| filter.color.is_none() || i.color == filter.color)
+        .filter(|i| {
+            tagged
+                .as_ref()
+                .is_none_or(|t| t.contains(&(i.item_type, i.id.clone())))
+        })
+        .collect())
+}
+
+/// Make edit `request` in one transaction, committed unless it is a dry run.
+pub(crate) fn apply(
+    conn: &mut Connection,
+    request: &BulkEditRequest,
+) -> Result<BulkEditSummary, DbError> {
+    let marks_only = !matches!(request.edit, BulkEdit::Retag { .. });
+    if marks_only && request.filter.is_empty() {
+        return Err(DbError::invalid(
+            "Recoloring or deleting marks in bulk needs a filter",
+        ));
+    }
+    let device = device_id(conn)?;
+    let tx = conn.transaction()?;
+    let since = undo::last_seq(&tx)?;
+    let now = now_iso(&tx)?;
+    let items = matching(&tx, &request.filter, marks_only)?;
+    let mut summary = BulkEditSummary {
+        matched: items.len(),
+        dry_run: request.dry_run,
+        ..Default::default()
+    };
+    match &request.edit {
+        BulkEdit::Recolor { color } => {
+            let color = color.trim();
+            if color.is_empty() {
+                return Err(DbError::invalid("A recolor needs a color"));
+            }
+            for item in items {
+                let Some(mut data) = item.data else { continue };
+                let styled = data.get("styleId").is_some();
+                if item.color.as_deref() == Some(color) && !styled {
+                    continue;
+                }
+                data["color"] = color.into();
+                if let Some(fields) = data.as_object_mut() {
+                    fields.remove("styleId");
+                }
+                annotations::update(&tx, Annotation::from_value(&data)?, None)?;
+                summary.ids.push(item.id);
+            }
+        }
+        BulkEdit::Retag { from, to } => {
+            let picked: HashSet<(ItemType, String)> =
+                items.into_iter().map(|i| (i.item_type, i.id)).collect();
+            let moved = tags::relink(&tx, from, to, |item_type, id| {
+                picked.contains(&(item_type, id.to_string()))
+            })?;
+            summary.ids = moved.into_iter().map(|(_, id)| id).collect();
+        }
+        BulkEdit::Delete => {
+            for item in items {
+                if annotations::delete_one(&tx, &item.id, &now, &device)? {
+                    summary.ids.push(item.id);
+                }
+            }
+        }
+    }
+    summary.changed = summary.ids.len();
+    if request.dry_run {
+        // Dropping the transaction rolls every write back.
+        return Ok(summary);
+    }
+    undo::group_since(&tx, since)?;
+    summary.undo_at = tx
+        .query_row(
+            "SELECT at FROM undo_journal WHERE seq > ?1 ORDER BY seq LIMIT 1",
+            [since],
+            |row| row.get(0),
+        )
+        .optional()?;
+    tx.commit()?;
+    Ok(summary)
+}
+
+/// Recolor, retag or delete every mark or note a filter picks, as one undo
+/// step.
+#[tauri::command]
+pub async fn bulk_edit_annotations(
+    app: tauri::AppHandle,
+    request: BulkEditRequest,
+) -> Result<BulkEditSummary, DbError> {
+    with_connection(&app, move |conn| apply(conn, &request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+    use serde_json::json;
+
+    fn request(edit: Value) -> BulkEditRequest {
+        serde_json::from_value(edit).unwrap()
+    }
+
+    #[test]
+    fn edits_what_the_filter_picks_as_one_undo_step() {
+        let mut conn = migrated_test_connection();
+        let text = |id: &str, chapter: i64, color: &str, created: &str| {
+            json!({
+                "id": id, "moduleId": "kjv", "type": "highlight", "color": color,
+                "createdAt": created, "styleId": "legacy-yellow",
+                "startRef": { "book": "Gen", "chapter": chapter, "verse": 6 },
+                "endRef": { "book": "Gen", "chapter": chapter, "verse": 6 },
+            })
+        };
+        annotations::bulk_insert(
+            &mut conn,
+            &[
+                text("h1", 15, "yellow", "2025-01-01T00:00:00Z"),
+                text("h2", 17, "yellow", "2025-01-01T00:00:00Z"),
+                text("h3", 15, "blue", "2025-06-01T09:00:00Z"),
+            ],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO notes VALUES ('n1', 'kjv', '{\"book\":\"Gen\",\"chapter\":15,\"verse\":6}',
+                NULL, 'Counted righteous', '2025-01-01', '2025-01-01', 'synced', 'dev-local')",
+            [],
+        )
+        .unwrap();
+        tags::attach(&mut conn, "Covenant", ItemType::Annotation, "h1").unwrap();
+        tags::attach(&mut conn, "Covenant/Abraham", ItemType::Annotation, "h2").unwrap();
+        tags::attach(&mut conn, "Covenant", ItemType::Note, "n1").unwrap();
+        // Statements in one test run can share a millisecond; keep steps apart.
+        let settle = |conn: &Connection, day: &str| {
+            conn.execute("UPDATE undo_journal SET at = ?1 WHERE at > ?1", [day])
+                .unwrap();
+        };
+        settle(&conn, "2025-01-01");
+
+        let recolor =
+            request(json!({ "op": "recolor", "color": "red", "filter": { "tag": "Covenant" } }));
+        let dry = apply(
+            &mut conn,
+            &BulkEditRequest {
+                dry_run: true,
+                ..recolor.clone()
+            },
+        )
+        .unwrap();
+        assert_eq!((dry.matched, dry.changed, dry.undo_at), (2, 2, None));
+        let done = apply(&mut conn, &recolor).unwrap();
+        settle(&conn, "2025-01-02");
+        assert_eq!(done.ids, ["h1", "h2"]);
+        assert!(done.undo_at.is_some());
+        let stored = |conn: &Connection, id: &str| -> Option<Value> {
+            conn.query_row("SELECT data FROM annotations WHERE id = ?1", [id], |r| {
+                r.get::<_, String>(0)
+            })
+            .optional()
+            .unwrap()
+            .map(|d| serde_json::from_str(&d).unwrap())
+        };
+        let h2 = stored(&conn, "h2").unwrap();
+        assert_eq!(
+            (h2["color"].as_str(), h2.get("styleId")),
+            (Some("red"), None)
+        );
+
+        let retag = request(json!({
+            "op": "retag", "from": "Covenant", "to": "Themes/Faith",
+            "filter": { "itemType": "note" },
+        }));
+        assert_eq!(apply(&mut conn, &retag).unwrap().ids, ["n1"]);
+        settle(&conn, "2025-01-03");
+        let note_tags = tags::item_tags(&conn, ItemType::Note, "n1").unwrap();
+        assert_eq!(note_tags[0].path, "Themes/Faith");
+
+        let today = request(json!({
+            "op": "delete",
+            "filter": { "book": "Gen", "chapter": 15, "createdSince": "2025-06-01T00:00:00Z" },
+        }));
+        assert_eq!(apply(&mut conn, &today).unwrap().ids, ["h3"]);
+        assert!(apply(&mut conn, &request(json!({ "op": "delete" }))).is_err());
+
+        // Each edit is one step: undo the delete, then the retag.
+        undo::replay(&mut conn, false).unwrap();
+        assert!(stored(&conn, "h3").is_some());
+        let step = undo::replay(&mut conn, false).unwrap().unwrap();
+        assert!(step.entries.iter().all(|e| e.table == "tag_links"));
+        assert_eq!(
+            tags::item_tags(&conn, ItemType::Note, "n1").unwrap()[0].path,
+            "Covenant"
+        );
+        undo::replay(&mut conn, false).unwrap();
+        assert_eq!(stored(&conn, "h1").unwrap()["color"], "yellow");
+
+        // A tag link another device removed doesn't touch the redo steps.
+        let link: String = conn
+            .query_row("SELECT id FROM tag_links LIMIT 1", [], |r| r.get(0))
+            .unwrap();
+        assert!(undo::delete_synced(&mut conn, "tag_links", &link).unwrap());
+        let journal = undo::stack(&conn).unwrap();
+        assert_eq!(journal.redo.len(), 3);
+    }
+}
@@ -0,0 +1,712 @@
+//! Verse collections: named, ordered lists of passages ("Promises", "Memory
+//! Verses 2025"), each entry with an optional note, in the synced
+//! `verse_collections` table of migration 24.
+//!
+//! A collection is one generic data row with its entries inline, the way
+//! `observation_lists` keeps its items, so sync, merges and dumps carry it
+//! with no code of their own and a reorder is a single write. Entries are
+//! in the order the user put them; they aren't kept in canonical order.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::annotations::VerseRef;
+use super::data_rows::{self, DataRow};
+use super::{now_iso, with_connection, with_reader, DbError, DbErrorKind};
+use crate::content::citation::{self, ReferenceStyle, Separators};
+use crate::content::references::ScriptureReference;
+use crate::content::{self, books};
+
+/// Longest collection name accepted, in characters.
+const MAX_NAME: usize = 200;
+
+/// One passage of a collection.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CollectionEntry {
+    pub id: String,
+    #[serde(rename = "ref")]
+    pub verse_ref: VerseRef,
+    /// The last verse of a passage; same book, not before `ref`.
+    #[serde(rename = "endRef", default, skip_serializing_if = "Option::is_none")]
+    pub end_ref: Option<VerseRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(rename = "addedAt", default)]
+    pub added_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A collection, as stored in `verse_collections.data`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct VerseCollection {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub entries: Vec<CollectionEntry>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DataRow for VerseCollection {
+    const TABLE: &'static str = "verse_collections";
+    const NOUN: &'static str = "collection";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+/// A passage to add to a collection.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct NewEntry {
+    #[serde(rename = "ref")]
+    pub verse_ref: VerseRef,
+    #[serde(rename = "endRef", default)]
+    pub end_ref: Option<VerseRef>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// The verses selected in the reader (`TextSelection` in the TS store).
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct VerseSelection {
+    pub book: String,
+    pub chapter: i64,
+    #[serde(rename = "startVerse")]
+    pub start_verse: i64,
+    #[serde(rename = "endVerse")]
+    pub end_verse: i64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct EntriesAdded {
+    pub collection: VerseCollection,
+    pub added: usize,
+    /// Passages left out because the collection already had them.
+    #[serde(rename = "alreadyThere")]
+    pub already_there: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// A heading and a bulleted list, notes beneath their passages.
+    #[default]
+    Markdown,
+    /// Reference, text and note, a blank line between entries.
+    Text,
+    /// `reference,text,note` rows under a header.
+    Csv,
+}
+
+fn check_ref(field: &str, r: &VerseRef) -> Result<(), DbError> {
+    if books::position(&r.book).is_none() || r.chapter < 1 || r.verse < 1 {
+        return Err(DbError::invalid(format!(
+            "Collection entry `{field}` is not a verse ({} {}:{})",
+            r.book, r.chapter, r.verse
+        )));
+    }
+    Ok(())
+}
+
+/// Check `entry`'s verses, dropping an `end_ref` that is just `ref`.
+fn check_entry(entry: &mut NewEntry) -> Result<(), DbError> {
+    check_ref("ref", &entry.verse_ref)?;
+    if entry.end_ref.as_ref() == Some(&entry.verse_ref) {
+        entry.end_ref = None;
+    }
+    if let Some(end) = &entry.end_ref {
+        check_ref("endRef", end)?;
+        let start = &entry.verse_ref;
+        if end.book != start.book || (end.chapter, end.verse) < (start.chapter, start.verse) {
+            return Err(DbError::invalid(
+                "A collection entry's `endRef` must follow its `ref` in the same book",
+            ));
+        }
+    }
+    entry.note = tidy(entry.note.take());
+    Ok(())
+}
+
+fn tidy(text: Option<String>) -> Option<String> {
+    text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Tidy `name` and check no other collection than `id` has it.
+fn check_name(conn: &Connection, id: &str, name: &str) -> Result<String, DbError> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME {
+        return Err(DbError::invalid(format!(
+            "A collection's name must be 1 to {MAX_NAME} characters"
+        )));
+    }
+    if collections(conn)?
+        .iter()
+        .any(|c| c.id != id && c.name.eq_ignore_ascii_case(&name))
+    {
+        return Err(DbError::invalid(format!(
+            "There is already a collection named {name}"
+        )));
+    }
+    Ok(name)
+}
+
+/// Every collection, by name. Rows that don't parse are skipped.
+pub(crate) fn collections(conn: &Connection) -> Result<Vec<VerseCollection>, DbError> {
+    let mut collections = data_rows::all::<VerseCollection>(conn)?;
+    collections.sort_by_cached_key(|c| (c.name.to_lowercase(), c.id.clone()));
+    Ok(collections)
+}
+
+pub(crate) fn collection(conn: &Connection, id: &str) -> Result<VerseCollection, DbError> {
+    data_rows::get(conn, id)
+}
+
+fn new_id(conn: &Connection, prefix: &str) -> Result<String, DbError> {
+    Ok(conn.query_row(
+        "SELECT ?1 || '-' || lower(hex(randomblob(8)))",
+        [prefix],
+        |row| row.get(0),
+    )?)
+}
+
+pub(crate) fn create(
+    conn: &Connection,
+    name: &str,
+    description: Option<String>,
+) -> Result<VerseCollection, DbError> {
+    let mut collection = VerseCollection {
+        id: new_id(conn, "vc")?,
+        description: tidy(description),
+        ..Default::default()
+    };
+    collection.name = check_name(conn, &collection.id, name)?;
+    data_rows::save(conn, &mut collection)?;
+    Ok(collection)
+}
+
+/// Rename a collection or change its description.
+pub(crate) fn edit(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    description: Option<String>,
+) -> Result<VerseCollection, DbError> {
+    let mut collection = collection(conn, id)?;
+    collection.name = check_name(conn, id, name)?;
+    collection.description = tidy(description);
+    data_rows::save(conn, &mut collection)?;
+    Ok(collection)
+}
+
+pub(crate) fn delete(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    data_rows::delete(conn, "verse_collections", id)
+}
+
+/// Add `entries` at `position` (the end without it), leaving out passages
+/// the collection already has.
+pub(crate) fn add(
+    conn: &Connection,
+    id: &str,
+    entries: Vec<NewEntry>,
+    position: Option<usize>,
+) -> Result<EntriesAdded, DbError> {
+    let mut collection = collection(conn, id)?;
+    let mut have: HashSet<(VerseRef, Option<VerseRef>)> = collection
+        .entries
+        .iter()
+        .map(|e| (e.verse_ref.clone(), e.end_ref.clone()))
+        .collect();
+    let now = now_iso(conn)?;
+    let mut new = Vec::new();
+    let mut already_there = 0;
+    for mut entry in entries {
+        check_entry(&mut entry)?;
+        if !have.insert((entry.verse_ref.clone(), entry.end_ref.clone())) {
+            already_there += 1;
+            continue;
+        }
+        new.push(CollectionEntry {
+            id: new_id(conn, "vce")?,
+            verse_ref: entry.verse_ref,
+            end_ref: entry.end_ref,
+            note: entry.note,
+            added_at: now.clone(),
+            extra: Map::new(),
+        });
+    }
+    let added = new.len();
+    if added > 0 {
+        let at = position.unwrap_or(usize::MAX).min(collection.entries.len());
+        collection.entries.splice(at..at, new);
+        data_rows::save(conn, &mut collection)?;
+    }
+    Ok(EntriesAdded {
+        collection,
+        added,
+        already_there,
+    })
+}
+
+/// Add each verse of the reader's selection as its own entry.
+pub(crate) fn add_selection(
+    conn: &Connection,
+    id: &str,
+    selection: &VerseSelection,
+) -> Result<EntriesAdded, DbError> {
+    let (first, last) = (
+        selection.start_verse.min(selection.end_verse),
+        selection.start_verse.max(selection.end_verse),
+    );
+    let entries = (first..=last)
+        .map(|verse| NewEntry {
+            verse_ref: VerseRef {
+                book: selection.book.clone(),
+                chapter: selection.chapter,
+                verse,
+            },
+            end_ref: None,
+            note: None,
+        })
+        .collect();
+    add(conn, id, entries, None)
+}
+
+fn entry_mut<'a>(
+    collection: &'a mut VerseCollection,
+    entry_id: &str,
+) -> Result<&'a mut CollectionEntry, DbError> {
+    let id = collection.id.clone();
+    collection
+        .entries
+        .iter_mut()
+        .find(|e| e.id == entry_id)
+        .ok_or_else(|| {
+            DbError::new(
+                DbErrorKind::NotFound,
+                format!("Collection {id} has no entry {entry_id}"),
+            )
+        })
+}
+
+/// Set or clear an entry's note.
+pub(crate) fn set_note(
+    conn: &Connection,
+    id: &str,
+    entry_id: &str,
+    note: Option<String>,
+) -> Result<VerseCollection, DbError> {
+    let mut collection = collection(conn, id)?;
+    entry_mut(&mut collection, entry_id)?.note = tidy(note);
+    data_rows::save(conn, &mut collection)?;
+    Ok(collection)
+}
+
+pub(crate) fn remove_entries(
+    conn: &Connection,
+    id: &str,
+    entry_ids: &[String],
+) -> Result<VerseCollection, DbError> {
+    let mut collection = collection(conn, id)?;
+    let before = collection.entries.len();
+    collection.entries.retain(|e| !entry_ids.contains(&e.id));
+    if collection.entries.len() != before {
+        data_rows::save(conn, &mut collection)?;
+    }
+    Ok(collection)
+}
+
+/// Put the entries in the order of `entry_ids`, which must name each of
+/// them once.
+pub(crate) fn reorder(
+    conn: &Connection,
+    id: &str,
+    entry_ids: &[String],
+) -> Result<VerseCollection, DbError> {
+    let mut collection = collection(conn, id)?;
+    let mut by_id: HashMap<String, CollectionEntry> = collection
+        .entries
+        .drain(..)
+        .map(|e| (e.id.clone(), e))
+        .collect();
+    let count = by_id.len();
+    let ordered: Vec<CollectionEntry> = entry_ids
+        .iter()
+        .filter_map(|entry_id| by_id.remove(entry_id))
+        .collect();
+    if ordered.len() != count || entry_ids.len() != count {
+        return Err(DbError::invalid(format!(
+            "A new order must name each of the collection's {count} entries once"
+        )));
+    }
+    collection.entries = ordered;
+    data_rows::save(conn, &mut collection)?;
+    Ok(collection)
+}
+
+/// `entry`'s passage written out, e.g. `Romans 8:28–30`.
+fn label(entry: &CollectionEntry) -> String {
+    let end = entry.end_ref.as_ref().unwrap_or(&entry.verse_ref);
+    let reference = ScriptureReference {
+        book: entry.verse_ref.book.clone(),
+        chapter: entry.verse_ref.chapter,
+        verse: Some(entry.verse_ref.verse),
+        end_chapter: end.chapter,
+        end_verse: Some(end.verse),
+        osis: String::new(),
+        label: String::new(),
+    };
+    citation::format(
+        &reference,
+        ReferenceStyle::Full,
+        None,
+        &Separators::default(),
+    )
+    .unwrap_or_else(|| {
+        format!(
+            "{} {}:{}",
+            entry.verse_ref.book, entry.verse_ref.chapter, entry.verse_ref.verse
+        )
+    })
+}
+
+/// The text of `entry`'s verses in the content attached as `schema`, joined
+/// by spaces; empty where the translation lacks them.
+fn text(
+    conn: &Connection,
+    schema: &str,
+    entry: &CollectionEntry,
+    chapters: &mut HashMap<(String, i64), BTreeMap<i64, String>>,
+) -> Result<String, DbError> {
+    let start = &entry.verse_ref;
+    let end = entry.end_ref.as_ref().unwrap_or(start);
+    let mut verses = Vec::new();
+    for chapter in start.chapter..=end.chapter {
+        let key = (start.book.clone(), chapter);
+        if !chapters.contains_key(&key) {
+            let text = content::chapter(conn, schema, &start.book, chapter)?;
+            chapters.insert(key.clone(), text);
+        }
+        for (&verse, text) in &chapters[&key] {
+            if (chapter, verse) >= (start.chapter, start.verse)
+                && (chapter, verse) <= (end.chapter, end.verse)
+            {
+                verses.push(text.trim().to_string());
+            }
+        }
+    }
+    Ok(verses.join(" "))
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(['"', ',', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// `collection` written out in `format`, with each passage's text from the
+/// content attached as `schema` if given.
+pub(crate) fn export(
+    conn: &Connection,
+    collection: &VerseCollection,
+    format: ExportFormat,
+    schema: Option<&str>,
+) -> Result<String, DbError> {
+    let mut chapters = HashMap::new();
+    let mut rows = Vec::new();
+    for entry in &collection.entries {
+        let text = match schema {
+            Some(schema) => text(conn, schema, entry, &mut chapters)?,
+            None => String::new(),
+        };
+        rows.push((label(entry), text, entry.note.clone().unwrap_or_default()));
+    }
+    let mut out = String::new();
+    match format {
+        ExportFormat::Markdown => {
+            out.push_str(&format!("# {}\n\n", collection.name));
+            if let Some(description) = &collection.description {
+                out.push_str(&format!("{description}\n\n"));
+            }
+            for (label, text, note) in &rows {
+                out.push_str(&format!("- **{label}**"));
+                if !text.is_empty() {
+                    out.push_str(&format!(" {text}"));
+                }
+                out.push('\n');
+                for line in note.lines() {
+                    out.push_str(&format!("  {line}\n"));
+                }
+            }
+        }
+        ExportFormat::Text => {
+            out.push_str(&format!("{}\n", collection.name));
+            if let Some(description) = &collection.description {
+                out.push_str(&format!("{description}\n"));
+            }
+            for (label, text, note) in &rows {
+                out.push('\n');
+                for line in [label, text, note] {
+                    if !line.is_empty() {
+                        out.push_str(&format!("{line}\n"));
+                    }
+                }
+            }
+        }
+        ExportFormat::Csv => {
+            out.push_str("reference,text,note\n");
+            for (label, text, note) in &rows {
+                out.push_str(&format!(
+                    "{},{},{}\n",
+                    csv_field(label),
+                    csv_field(text),
+                    csv_field(note)
+                ));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Every collection by name, with its entries.
+#[tauri::command]
+pub async fn get_verse_collections(app: tauri::AppHandle) -> Result<Vec<VerseCollection>, DbError> {
+    with_reader(&app, collections).await
+}
+
+#[tauri::command]
+pub async fn get_verse_collection(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<VerseCollection, DbError> {
+    with_reader(&app, move |conn| collection(conn, &id)).await
+}
+
+/// Make an empty collection. Fails `Invalid` if the name is taken.
+#[tauri::command]
+pub async fn create_verse_collection(
+    app: tauri::AppHandle,
+    name: String,
+    description: Option<String>,
+) -> Result<VerseCollection, DbError> {
+    with_connection(&app, move |conn| create(conn, &name, description)).await
+}
+
+/// Rename a collection or change its description.
+#[tauri::command]
+pub async fn update_verse_collection(
+    app: tauri::AppHandle,
+    id: String,
+    name: String,
+    description: Option<String>,
+) -> Result<VerseCollection, DbError> {
+    with_connection(&app, move |conn| edit(conn, &id, &name, description)).await
+}
+
+#[tauri::command]
+pub async fn delete_verse_collection(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| delete(conn, &id)).await
+}
+
+/// Add passages at `position` (the end by default), skipping ones already
+/// there.
+#[tauri::command]
+pub async fn add_to_verse_collection(
+    app: tauri::AppHandle,
+    list_id: String,
+    entries: Vec<NewEntry>,
+    position: Option<usize>,
+) -> Result<EntriesAdded, DbError> {
+    with_connection(&app, move |conn| add(conn, &list_id, entries, position)).await
+}
+
+/// Add every verse of the reader's selection to a collection, one entry
+/// each, skipping verses already there.
+#[tauri::command]
+pub async fn add_current_selection_to_list(
+    app: tauri::AppHandle,
+    list_id: String,
+    selection: VerseSelection,
+) -> Result<EntriesAdded, DbError> {
+    with_connection(&app, move |conn| add_selection(conn, &list_id, &selection)).await
+}
+
+/// Set or clear the note of one entry.
+#[tauri::command]
+pub async fn update_verse_collection_entry(
+    app: tauri::AppHandle,
+    list_id: String,
+    entry_id: String,
+    note: Option<String>,
+) -> Result<VerseCollection, DbError> {
+    with_connection(&app, move |conn| set_note(conn, &list_id, &entry_id, note)).await
+}
+
+#[tauri::command]
+pub async fn remove_from_verse_collection(
+    app: tauri::AppHandle,
+    list_id: String,
+    entry_ids: Vec<String>,
+) -> Result<VerseCollection, DbError> {
+    with_connection(&app, move |conn| remove_entries(conn, &list_id, &entry_ids)).await
+}
+
+/// Put a collection's entries in the order of `entry_ids`.
+#[tauri::command]
+pub async fn reorder_verse_collection(
+    app: tauri::AppHandle,
+    list_id: String,
+    entry_ids: Vec<String>,
+) -> Result<VerseCollection, DbError> {
+    with_connection(&app, move |conn| reorder(conn, &list_id, &entry_ids)).await
+}
+
+/// A collection as Markdown, plain text or CSV, with its passages' text
+/// from `module_id` when that translation is mounted.
+#[tauri::command]
+pub async fn export_verse_collection(
+    app: tauri::AppHandle,
+    list_id: String,
+    format: Option<ExportFormat>,
+    module_id: Option<String>,
+) -> Result<String, DbError> {
+    let schema = module_id
+        .as_deref()
+        .and_then(content::mounted)
+        .map(|m| m.schema);
+    with_reader(&app, move |conn| {
+        let collection = collection(conn, &list_id)?;
+        export(
+            conn,
+            &collection,
+            format.unwrap_or_default(),
+            schema.as_deref(),
+        )
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+
+    fn entry(book: &str, chapter: i64, verse: i64, end: Option<i64>) -> NewEntry {
+        let at = |verse| VerseRef {
+            book: book.into(),
+            chapter,
+            verse,
+        };
+        NewEntry {
+            verse_ref: at(verse),
+            end_ref: end.map(at),
+            note: None,
+        }
+    }
+
+    fn order(collection: &VerseCollection) -> Vec<String> {
+        collection.entries.iter().map(label).collect()
+    }
+
+    #[test]
+    fn keeps_ordered_collections_and_exports_them() {
+        let conn = migrated_test_connection();
+        conn.execute_batch(
+            "ATTACH ':memory:' AS content_kjv;
+             CREATE TABLE content_kjv.verses (book TEXT, chapter INTEGER, verse INTEGER, text TEXT);
+             INSERT INTO content_kjv.verses VALUES
+               ('Rom', 8, 28, 'And we know that all things work together for good'),
+               ('Rom', 8, 29, 'For whom he did foreknow,'),
+               ('Phil', 4, 13, 'I can do all things through Christ');",
+        )
+        .unwrap();
+        let promises = create(&conn, " Promises ", None).unwrap();
+        assert_eq!(promises.name, "Promises");
+        assert_eq!(
+            create(&conn, "promises", None).unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+
+        let added = add(
+            &conn,
+            &promises.id,
+            vec![
+                entry("Phil", 4, 13, Some(13)),
+                entry("Rom", 8, 28, Some(29)),
+            ],
+            None,
+        )
+        .unwrap();
+        assert_eq!((added.added, added.already_there), (2, 0));
+        assert_eq!(added.collection.entries[0].end_ref, None);
+        let selection = VerseSelection {
+            book: "Rom".into(),
+            chapter: 8,
+            start_verse: 29,
+            end_verse: 28,
+        };
+        let added = add_selection(&conn, &promises.id, &selection).unwrap();
+        assert_eq!((added.added, added.already_there), (2, 0));
+        let again = add_selection(&conn, &promises.id, &selection).unwrap();
+        assert_eq!((again.added, again.already_there), (0, 2));
+        assert_eq!(
+            order(&again.collection),
+            [
+                "Philippians 4:13",
+                "Romans 8:28–29",
+                "Romans 8:28",
+                "Romans 8:29"
+            ]
+        );
+
+        let ids: Vec<String> = again
+            .collection
+            .entries
+            .iter()
+            .map(|e| e.id.clone())
+            .collect();
+        let shorter = remove_entries(&conn, &promises.id, &ids[2..]).unwrap();
+        assert_eq!(
+            reorder(&conn, &promises.id, &ids[..1]).unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+        let reordered = reorder(&conn, &promises.id, &[ids[1].clone(), ids[0].clone()]).unwrap();
+        assert_eq!(order(&reordered), ["Romans 8:28–29", "Philippians 4:13"]);
+        assert_eq!(shorter.entries.len(), 2);
+        let noted = set_note(
+            &conn,
+            &promises.id,
+            &ids[0],
+            Some("Strength, not\nescape".into()),
+        )
+        .unwrap();
+
+        let markdown = export(&conn, &noted, ExportFormat::Markdown, Some("content_kjv")).unwrap();
+        assert_eq!(
+            markdown,
+            "# Promises\n\n\
+             - **Romans 8:28–29** And we know that all things work together for good For whom he did foreknow,\n\
+             - **Philippians 4:13** I can do all things through Christ\n  Strength, not\n  escape\n"
+        );
+        let csv = export(&conn, &noted, ExportFormat::Csv, None).unwrap();
+        assert_eq!(
+            csv,
+            "reference,text,note\nRomans 8:28–29,,\nPhilippians 4:13,,\"Strength, not\nescape\"\n"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! One writer, many readers.
+//!
+//! Every native write goes through a single connection owned by a dedicated
+//! thread, fed from a queue, so Rust-side writers (commands, sync merges, the
+//! idle maintenance job) line up behind each other instead of racing for the
+//! lock and surfacing `SQLITE_BUSY`. Reads check out one of a small pool of
+//! `query_only` connections and, thanks to WAL, never wait on the writer;
+//! they also carry the mounted translation content (see `content`).
+//! The busy timeout still matters for the tauri-plugin-sql connection the TS
+//! layer writes through, which this queue can't see.
+
+use rusqlite::Connection;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::command;
+
+use super::write_lock::{WriteLock, WRITE_LOCK};
+use super::{open, DbError, DbErrorKind};
+
+/// Default wait on another connection's lock before a statement fails `Busy`.
+pub(crate) const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Idle reader connections kept open between reads.
+const MAX_IDLE_READERS: usize = 4;
+
+static BUSY_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_BUSY_TIMEOUT.as_millis() as u64);
+
+/// The manager for the database currently in use, created on first access.
+static MANAGER: Mutex<Option<Arc<ConnectionManager>>> = Mutex::new(None);
+
+pub(crate) fn busy_timeout() -> Duration {
+    Duration::from_millis(BUSY_TIMEOUT_MS.load(Ordering::Relaxed))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+type Job = Box<dyn FnOnce(&mut Connection) + Send>;
+
+struct Writer {
+    jobs: mpsc::Sender<Job>,
+    thread: JoinHandle<()>,
+}
+
+pub(crate) struct ConnectionManager {
+    path: PathBuf,
+    writer: Mutex<Option<Writer>>,
+    /// Idle readers, with the content-mount generation each last synced to.
+    readers: Mutex<Vec<(Connection, Option<u64>)>>,
+    /// What pauses writes; `WRITE_LOCK` but in tests.
+    write_lock: &'static WriteLock,
+}
+
+fn stopped() -> DbError {
+    DbError::new(DbErrorKind::Sqlite, "Database writer stopped")
+}
+
+impl ConnectionManager {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            writer: Mutex::new(None),
+            readers: Mutex::new(Vec::new()),
+            write_lock: &WRITE_LOCK,
+        }
+    }
+
+    /// The writer's queue, starting the thread on first use.
+    fn queue(&self) -> Result<mpsc::Sender<Job>, DbError> {
+        let mut writer = lock(&self.writer);
+        if let Some(w) = writer.as_ref() {
+            return Ok(w.jobs.clone());
+        }
+        // Open here rather than on the thread so a bad path fails this call.
+        let mut conn = open(&self.path)?;
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let thread = std::thread::Builder::new()
+            .name("db-writer".into())
+            .spawn(move || {
+                for job in queue {
+                    let _ = conn.busy_timeout(busy_timeout());
+                    // A panicking job fails only its own caller (its reply
+                    // channel drops); the queue keeps going.
+                    let _ = std::panic::catch_unwind(AssertUnwindSafe(|| job(&mut conn)));
+                }
+            })
+            .map_err(|e| DbError::io(format!("Failed to start database writer: {e}")))?;
+        *writer = Some(Writer {
+            jobs: jobs.clone(),
+            thread,
+        });
+        Ok(jobs)
+    }
+
+    /// Run `f` on the writer connection after every write queued before it,
+    /// blocking until it finishes. Fails `DatabaseLocked` while writes are
+    /// paused (see `write_lock`).
+    pub(crate) fn write<T, F>(&self, f: F) -> Result<T, DbError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, DbError> + Send + 'static,
+    {
+        self.write_lock.check()?;
+        self.write_synced(f)
+    }
+
+    /// `write`, for what sync brings in, which goes through while writes are
+    /// paused: the engine pauses them so its own applies aren't interleaved
+    /// with, and those applies must not fail on its lock.
+    pub(crate) fn write_synced<T, F>(&self, f: F) -> Result<T, DbError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, DbError> + Send + 'static,
+    {
+        let (reply, result) = mpsc::channel();
+        let job: Job = Box::new(move |conn| {
+            let _ = reply.send(f(conn));
+        });
+        self.queue()?.send(job).map_err(|_| stopped())?;
+        result.recv().map_err(|_| stopped())?
+    }
+
+    /// Run `f` on a read-only connection from the pool.
+    pub(crate) fn read<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, DbError>,
+    ) -> Result<T, DbError> {
+        let (conn, mut mounts) = match lock(&self.readers).pop() {
+            Some(reader) => reader,
+            None => {
+                let conn = open(&self.path)?;
+                conn.pragma_update(None, "query_only", true)?;
+                (conn, None)
+            }
+        };
+        conn.busy_timeout(busy_timeout())?;
+        crate::content::attach_mounted(&conn, &mut mounts)?;
+        let result = f(&conn);
+        let mut idle = lock(&self.readers);
+        if idle.len() < MAX_IDLE_READERS {
+            idle.push((conn, mounts));
+        }
+        result
+    }
+
+    /// Close the idle readers, e.g. so an unmounted content file is released.
+    pub(crate) fn release_readers(&self) {
+        lock(&self.readers).clear();
+    }
+
+    /// Finish the queued writes and close every connection.
+    fn close(&self) {
+        if let Some(Writer { jobs, thread }) = lock(&self.writer).take() {
+            drop(jobs);
+            let _ = thread.join();
+        }
+        lock(&self.readers).clear();
+    }
+}
+
+/// The connection manager for the database at `path`.
+pub(crate) fn manager(path: &Path) -> Arc<ConnectionManager> {
+    let mut current = lock(&MANAGER);
+    match current.as_ref() {
+        Some(m) if m.path == path => m.clone(),
+        _ => {
+            if let Some(old) = current.take() {
+                old.close();
+            }
+            let m = Arc::new(ConnectionManager::new(path));
+            *current = Some(m.clone());
+            m
+        }
+    }
+}
+
+/// Close the native connections, e.g. before the database file is moved or
+/// replaced. The next access reopens them.
+pub(crate) fn close_connections() {
+    if let Some(m) = lock(&MANAGER).take() {
+        m.close();
+    }
+}
+
+/// Change how long native connections wait on a lock before failing `Busy`.
+/// Applies to the next statement on every connection, including open ones.
+#[command]
+pub fn set_database_busy_timeout(timeout_ms: u64) {
+    BUSY_TIMEOUT_MS.store(timeout_ms, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    fn scratch(name: &str) -> (ScratchDir, ConnectionManager) {
+        let dir = ScratchDir::new(&format!("conn-{name}"));
+        let path = dir.join(super::super::DB_FILE);
+        let m = ConnectionManager::new(&path);
+        m.write(|conn| Ok(conn.execute_batch("CREATE TABLE t (n INTEGER)")?))
+            .unwrap();
+        (dir, m)
+    }
+
+    #[test]
+    fn concurrent_writes_queue_instead_of_failing_busy() {
+        let (_dir, m) = scratch("queue");
+        let m = Arc::new(m);
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let m = m.clone();
+                std::thread::spawn(move || {
+                    for j in 0..25 {
+                        m.write(move |conn| {
+                            let tx = conn.transaction()?;
+                            tx.execute("INSERT INTO t VALUES (?1)", [i * 100 + j])?;
+                            tx.commit()?;
+                            Ok(())
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let count: i64 = m
+            .read(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM t", [], |r| r.get(0))?))
+            .unwrap();
+        assert_eq!(count, 200);
+        m.close();
+    }
+
+    #[test]
+    fn readers_cannot_write() {
+        let (_dir, m) = scratch("readonly");
+        let err = m
+            .read(|conn| Ok(conn.execute("INSERT INTO t VALUES (1)", [])?))
+            .unwrap_err();
+        assert_eq!(err.kind, DbErrorKind::Sqlite);
+        m.close();
+    }
+
+    #[test]
+    fn sync_writes_go_through_a_pause_that_stops_the_rest() {
+        static PAUSED: WriteLock = WriteLock::new();
+        let (_dir, mut m) = scratch("paused");
+        m.write_lock = &PAUSED;
+        PAUSED.set(true, Some("applying snapshot".into()));
+        let insert = |conn: &mut Connection| Ok(conn.execute("INSERT INTO t VALUES (1)", [])?);
+        assert_eq!(
+            m.write(insert).unwrap_err().kind,
+            DbErrorKind::DatabaseLocked
+        );
+        assert_eq!(m.write_synced(insert).unwrap(), 1);
+        PAUSED.set(false, None);
+        assert_eq!(m.write(insert).unwrap(), 1);
+        m.close();
+    }
+
+    #[test]
+    fn writer_survives_a_panicking_job() {
+        let (_dir, m) = scratch("restart");
+        assert!(m.write::<(), _>(|_| panic!("boom")).is_err());
+        m.write(|conn| Ok(conn.execute("INSERT INTO t VALUES (1)", [])?))
+            .unwrap();
+        m.close();
+    }
+}
@@ -0,0 +1,225 @@
+//! Rows of the generic data tables: an `id`, the item as JSON in `data`, its
+//! timestamps and the sync columns, as `sqliteSaveToTable` writes them in
+//! sqlite-db.ts. The native tables of migrations 20 to 29 read and write
+//! their items through here, keyed by table like the TS helpers, so each
+//! table's module keeps only its item type and its validation. Every write
+//! is logged for sync with `record_change`.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{device_id, now_iso, record_change, DbError, DbErrorKind};
+
+/// An item stored as the `data` of a row of `TABLE`.
+///
+/// Items written by a newer version may carry fields this one doesn't know.
+/// Each implementor keeps them in a `#[serde(flatten)] extra` map, so a save
+/// from here writes them back as they came instead of dropping them for the
+/// devices that do know them.
+pub(crate) trait DataRow: Serialize + DeserializeOwned {
+    const TABLE: &'static str;
+    /// What one is called in messages, e.g. `margin note`.
+    const NOUN: &'static str;
+
+    fn id(&self) -> &str;
+
+    /// Its `createdAt` and `updatedAt`, which `save` stamps.
+    fn timestamps(&mut self) -> (&mut String, &mut String);
+}
+
+/// `data` of row `id` as a `T`, or None (logged) if it doesn't parse.
+pub(crate) fn parse<T: DataRow>(id: &str, data: &str) -> Option<T> {
+    match serde_json::from_str(data) {
+        Ok(item) => Some(item),
+        Err(e) => {
+            println!("[{}] Skipping {id}: {e}", T::TABLE);
+            None
+        }
+    }
+}
+
+/// Every stored `T`, in no particular order. Rows that don't parse are
+/// skipped.
+pub(crate) fn all<T: DataRow>(conn: &Connection) -> Result<Vec<T>, DbError> {
+    let mut stmt = conn.prepare(&format!("SELECT id, data FROM {}", T::TABLE))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, data)| parse(&id, &data))
+        .collect())
+}
+
+fn data(conn: &Connection, table: &str, id: &str) -> Result<Option<String>, DbError> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT data FROM {table} WHERE id = ?1"),
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// The `T` of `id`, None if there is none or it doesn't parse.
+pub(crate) fn find<T: DataRow>(conn: &Connection, id: &str) -> Result<Option<T>, DbError> {
+    Ok(data(conn, T::TABLE, id)?.and_then(|data| parse(id, &data)))
+}
+
+/// The `T` of `id`. Fails `NotFound` if there is none, `Invalid` if it
+/// doesn't parse.
+pub(crate) fn get<T: DataRow>(conn: &Connection, id: &str) -> Result<T, DbError> {
+    let data = data(conn, T::TABLE, id)?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("No {} {id}", T::NOUN)))?;
+    serde_json::from_str(&data)
+        .map_err(|e| DbError::invalid(format!("Stored {} {id} can't be read: {e}", T::NOUN)))
+}
+
+/// When row `id` of `table` was created, None if there is no such row.
+pub(crate) fn created_at(
+    conn: &Connection,
+    table: &str,
+    id: &str,
+) -> Result<Option<String>, DbError> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT created_at FROM {table} WHERE id = ?1"),
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// Store `item` as of now and log it for sync. It keeps its `createdAt`, or
+/// is created now without one.
+pub(crate) fn save<T: DataRow>(conn: &Connection, item: &mut T) -> Result<(), DbError> {
+    let (now, device) = (now_iso(conn)?, device_id(conn)?);
+    let (created_at, updated_at) = item.timestamps();
+    if created_at.is_empty() {
+        *created_at = now.clone();
+    }
+    let created_at = created_at.clone();
+    *updated_at = now.clone();
+    let data = serde_json::to_string(&item)
+        .map_err(|e| DbError::invalid(format!("Cannot store {}: {e}", T::NOUN)))?;
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO {}
+             (id, data, created_at, updated_at, sync_status, device_id)
+             VALUES (?1, ?2, ?3, ?4, 'pending', ?5)",
+            T::TABLE
+        ),
+        params![item.id(), data, created_at, now, device],
+    )?;
+    record_change(
+        conn,
+        T::TABLE,
+        "upsert",
+        item.id(),
+        Some(&data),
+        &now,
+        &device,
+    )
+}
+
+/// Store a new `item`, created now. Fails `Invalid` if its id is taken.
+pub(crate) fn insert<T: DataRow>(conn: &Connection, mut item: T) -> Result<T, DbError> {
+    if created_at(conn, T::TABLE, item.id())?.is_some() {
+        return Err(DbError::invalid(format!(
+            "{} {} already exists",
+            T::NOUN,
+            item.id()
+        )));
+    }
+    item.timestamps().0.clear();
+    save(conn, &mut item)?;
+    Ok(item)
+}
+
+/// Save `item` over the stored one of its id, keeping when that was
+/// created. Fails `NotFound` if there is none.
+pub(crate) fn replace<T: DataRow>(conn: &Connection, mut item: T) -> Result<T, DbError> {
+    let created = created_at(conn, T::TABLE, item.id())?.ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("No {} {}", T::NOUN, item.id()),
+        )
+    })?;
+    *item.timestamps().0 = created;
+    save(conn, &mut item)?;
+    Ok(item)
+}
+
+/// Delete row `id` of `table` and log it for sync; false if there was none.
+pub(crate) fn delete(conn: &Connection, table: &str, id: &str) -> Result<bool, DbError> {
+    let deleted = conn.execute(&format!("DELETE FROM {table} WHERE id = ?1"), [id])?;
+    if deleted > 0 {
+        let (now, device) = (now_iso(conn)?, device_id(conn)?);
+        record_change(conn, table, "delete", id, None, &now, &device)?;
+    }
+    Ok(deleted > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::margin_notes::MarginNote;
+    use crate::db::migrated_test_connection;
+
+    fn note(id: &str) -> MarginNote {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "book": "Rom", "chapter": 8, "content": "No condemnation",
+            "createdAt": "2020-01-01T00:00:00.000Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn stores_rows_by_table_and_logs_each_write() {
+        let conn = migrated_test_connection();
+        let created = insert(&conn, note("m1")).unwrap();
+        assert_ne!(created.created_at, "2020-01-01T00:00:00.000Z");
+        assert_eq!(
+            insert(&conn, note("m1")).unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+
+        let edited = replace(&conn, note("m1")).unwrap();
+        assert_eq!(edited.created_at, created.created_at);
+        assert_eq!(
+            replace(&conn, note("m2")).unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+        assert_eq!(get::<MarginNote>(&conn, "m1").unwrap(), edited);
+        assert_eq!(
+            get::<MarginNote>(&conn, "m2").unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+
+        conn.execute(
+            "INSERT INTO margin_notes (id, data, created_at, updated_at) VALUES ('bad', '{', 'x', 'x')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(all::<MarginNote>(&conn).unwrap(), [edited]);
+        assert!(find::<MarginNote>(&conn, "bad").unwrap().is_none());
+        assert_eq!(
+            get::<MarginNote>(&conn, "bad").unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+
+        assert!(delete(&conn, "margin_notes", "m1").unwrap());
+        assert!(!delete(&conn, "margin_notes", "m1").unwrap());
+        let logged: Vec<String> = conn
+            .prepare("SELECT op FROM change_log WHERE table_name = 'margin_notes' ORDER BY seq")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(logged, ["upsert", "upsert", "delete"]);
+    }
+}
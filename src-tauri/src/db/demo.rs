@@ -0,0 +1,209 @@
+//! Demo mode: a throwaway database seeded with sample annotations.
+//!
+//! Started with `--demo` (or `BIBLEMARKER_DEMO=1`), the app keeps its
+//! database in a fresh directory under the system temp dir instead of app
+//! data, so reviewers, testers and the E2E suite can exercise every command
+//! without touching the user's data. tauri-plugin-sql can only open files,
+//! so the database is a file nobody keeps rather than `:memory:`: the
+//! directory is removed when the app exits. The TS layer asks
+//! `get_database_mode` which file to open and, once the schema exists,
+//! calls `seed_demo_database`.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::{annotations, device_id, now_iso, trash, with_connection, DbError};
+use crate::sync::merge::table_exists;
+
+pub(crate) const DEMO_FLAG: &str = "--demo";
+pub(crate) const DEMO_ENV: &str = "BIBLEMARKER_DEMO";
+
+/// Translation the samples are on; `loadSampleData` caches its chapters.
+const SAMPLE_MODULE: &str = "WEB";
+
+static DEMO_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseMode {
+    pub demo: bool,
+    /// The database file native commands use.
+    pub path: String,
+}
+
+fn requested(mut args: impl Iterator<Item = String>, env: Option<&str>) -> bool {
+    args.any(|arg| arg == DEMO_FLAG) || env.is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Decide once, at startup, whether this run is a demo. A demo that can't
+/// get its scratch directory refuses to start rather than fall back to the
+/// real database.
+pub(crate) fn init() {
+    DEMO_DIR.get_or_init(|| {
+        let env = std::env::var(DEMO_ENV).ok();
+        if !requested(std::env::args().skip(1), env.as_deref()) {
+            return None;
+        }
+        let dir = std::env::temp_dir().join(format!("biblemarker-demo-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("demo mode needs a scratch directory");
+        println!("[demo] Using a throwaway database in {}", dir.display());
+        Some(dir)
+    });
+}
+
+/// The scratch directory while in demo mode.
+pub(crate) fn dir() -> Option<&'static Path> {
+    DEMO_DIR.get().and_then(Option::as_deref)
+}
+
+/// Drop the demo database when the app exits.
+pub(crate) fn cleanup() {
+    let Some(dir) = dir() else {
+        return;
+    };
+    super::connections::close_connections();
+    if let Err(e) = std::fs::remove_dir_all(dir) {
+        println!("[demo] Could not remove {}: {e}", dir.display());
+    }
+}
+
+fn sample_annotations(now: &str) -> Vec<Value> {
+    let verse = |book: &str, chapter: i64, verse: i64| json!({ "book": book, "chapter": chapter, "verse": verse });
+    let highlight = |id: &str, book: &str, chapter: i64, from: i64, to: i64, color: &str| {
+        json!({
+            "id": id, "moduleId": SAMPLE_MODULE, "type": "highlight",
+            "startRef": verse(book, chapter, from), "endRef": verse(book, chapter, to),
+            "color": color, "createdAt": now, "updatedAt": now
+        })
+    };
+    let symbol = |id: &str, book: &str, chapter: i64, at: i64, symbol: &str, word: &str| {
+        json!({
+            "id": id, "moduleId": SAMPLE_MODULE, "type": "symbol",
+            "ref": verse(book, chapter, at), "position": "center", "placement": "above",
+            "selectedText": word, "symbol": symbol, "createdAt": now, "updatedAt": now
+        })
+    };
+    vec![
+        highlight("demo-john-1-1", "John", 1, 1, 1, "yellow"),
+        highlight("demo-john-1-14", "John", 1, 14, 14, "blue"),
+        symbol("demo-john-1-29", "John", 1, 29, "cross", "Jesus"),
+        highlight("demo-rom-6-4", "Rom", 6, 4, 4, "green"),
+        highlight("demo-rom-6-23", "Rom", 6, 23, 23, "purple"),
+        symbol("demo-gen-1-1", "Gen", 1, 1, "triangle", "God"),
+    ]
+}
+
+fn sample_notes(now: &str) -> Vec<Value> {
+    let note = |id: &str, book: &str, chapter: i64, verse: i64, content: &str| {
+        json!({
+            "id": id, "module_id": SAMPLE_MODULE,
+            "ref": json!({ "book": book, "chapter": chapter, "verse": verse }).to_string(),
+            "content": content, "created_at": now
+        })
+    };
+    vec![
+        note(
+            "demo-note-john-1-1",
+            "John",
+            1,
+            1,
+            "Compare **Genesis 1:1** — \"In the beginning\".",
+        ),
+        note(
+            "demo-note-rom-6-23",
+            "Rom",
+            6,
+            23,
+            "Wages are earned; a gift is not.",
+        ),
+    ]
+}
+
+/// Seed an empty database with the samples. Leaves a database that already
+/// has annotations or notes alone and returns 0. The seed is not undoable.
+pub(crate) fn seed(conn: &mut Connection) -> Result<usize, DbError> {
+    let existing: i64 = conn.query_row(
+        "SELECT (SELECT COUNT(*) FROM annotations) + (SELECT COUNT(*) FROM notes)",
+        [],
+        |row| row.get(0),
+    )?;
+    if existing > 0 {
+        return Ok(0);
+    }
+    let now = now_iso(conn)?;
+    let mut saved = annotations::bulk_insert(conn, &sample_annotations(&now))?;
+
+    let device = device_id(conn)?;
+    let tx = conn.transaction()?;
+    for note in sample_notes(&now) {
+        trash::write_row(&tx, "notes", note, &now, &device)?;
+        saved += 1;
+    }
+    if table_exists(&tx, "main", "undo_journal")? {
+        tx.execute("DELETE FROM undo_journal", [])?;
+    }
+    tx.commit()?;
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn get_database_mode(app: tauri::AppHandle) -> Result<DatabaseMode, DbError> {
+    Ok(DatabaseMode {
+        demo: dir().is_some(),
+        path: super::db_path(&app)?.to_string_lossy().into_owned(),
+    })
+}
+
+/// Seed the demo database. Returns how many sample rows were written.
+#[tauri::command]
+pub async fn seed_demo_database(app: tauri::AppHandle) -> Result<usize, DbError> {
+    if dir().is_none() {
+        return Err(DbError::invalid("Sample data is only seeded in demo mode"));
+    }
+    with_connection(&app, seed).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrated_test_connection, undo};
+
+    #[test]
+    fn demo_is_asked_for_by_flag_or_env() {
+        let args = |list: &[&str]| {
+            list.iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        assert!(requested(args(&["--demo"]), None));
+        assert!(requested(args(&[]), Some("1")));
+        assert!(!requested(args(&["--demos"]), None));
+        assert!(!requested(args(&[]), Some("0")));
+        assert!(!requested(args(&[]), Some("")));
+    }
+
+    #[test]
+    fn seeding_fills_an_empty_database_once() {
+        let mut conn = migrated_test_connection();
+        let saved = seed(&mut conn).unwrap();
+        assert_eq!(saved, 8);
+
+        let (annotations, notes): (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM annotations), (SELECT COUNT(*) FROM notes)",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((annotations, notes), (6, 2));
+        assert!(undo::stack(&conn).unwrap().undo.is_empty());
+        let john = annotations::chapter_annotations(&conn, SAMPLE_MODULE, "John", 1).unwrap();
+        assert_eq!(john.len(), 3);
+
+        assert_eq!(seed(&mut conn).unwrap(), 0, "already seeded");
+    }
+}
@@ -0,0 +1,480 @@
+//! Plain-text SQL dumps of the user data.
+//!
+//! A dump holds every synced table as `DELETE` + one `INSERT` per row, in id
+//! order and one row per line, so two dumps diff cleanly and the file can be
+//! read or edited without SQLite. Caches, sync bookkeeping and the schema
+//! itself are left out: a dump is loaded into a database the app has already
+//! created, and only ever replaces the tables it names. An import is logged
+//! for sync as an edit made then, so it reaches the account's other devices.
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Batch, Connection};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::{
+    device_id, migrations, now_iso, record_change, with_connection, with_reader, DbError,
+    DbErrorKind,
+};
+use crate::sync::merge::{self, SYNCED_TABLES};
+
+const DUMP_TITLE: &str = "-- BibleMarker SQL dump";
+
+/// Bumped when the dump layout changes; newer dumps are refused.
+const DUMP_FORMAT: u32 = 1;
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct DumpTable {
+    pub table: String,
+    pub rows: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SqlDumpReport {
+    pub path: String,
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u32,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub tables: Vec<DumpTable>,
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// `value` as an SQL literal. Line breaks in text are spelled out with
+/// `char()` to keep one row per line.
+fn literal(out: &mut String, value: ValueRef<'_>) {
+    match value {
+        ValueRef::Null => out.push_str("NULL"),
+        ValueRef::Integer(n) => {
+            let _ = write!(out, "{n}");
+        }
+        ValueRef::Real(f) => {
+            let _ = write!(out, "{f:?}");
+        }
+        ValueRef::Text(bytes) => {
+            let text = String::from_utf8_lossy(bytes).replace('\'', "''");
+            out.push('\'');
+            for c in text.chars() {
+                match c {
+                    '\n' => out.push_str("'||char(10)||'"),
+                    '\r' => out.push_str("'||char(13)||'"),
+                    c => out.push(c),
+                }
+            }
+            out.push('\'');
+        }
+        ValueRef::Blob(bytes) => {
+            out.push_str("X'");
+            for b in bytes {
+                let _ = write!(out, "{b:02X}");
+            }
+            out.push('\'');
+        }
+    }
+}
+
+/// Write the dump of `conn`'s user data to `out`.
+pub(crate) fn write_dump(
+    conn: &Connection,
+    out: &mut impl Write,
+) -> Result<(u32, String, Vec<DumpTable>), DbError> {
+    let version = migrations::current_version(conn)?;
+    let created_at = now_iso(conn)?;
+    let io = |e: std::io::Error| DbError::io(format!("Failed to write SQL dump: {e}"));
+    writeln!(out, "{DUMP_TITLE}").map_err(io)?;
+    writeln!(out, "-- format: {DUMP_FORMAT}").map_err(io)?;
+    writeln!(out, "-- schema: {version}").map_err(io)?;
+    writeln!(out, "-- created: {created_at}").map_err(io)?;
+    writeln!(out, "-- device: {}", device_id(conn)?).map_err(io)?;
+    writeln!(out, "BEGIN TRANSACTION;").map_err(io)?;
+
+    let mut tables = Vec::new();
+    for &table in SYNCED_TABLES {
+        if !merge::table_exists(conn, "main", table)? {
+            continue;
+        }
+        let columns = merge::columns(conn, "main", table)?;
+        let order = if columns.iter().any(|c| c == "id") {
+            "id"
+        } else {
+            "rowid"
+        };
+        let list = columns
+            .iter()
+            .map(|c| quote_ident(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let insert = format!("INSERT INTO {} ({list}) VALUES (", quote_ident(table));
+        writeln!(out, "\nDELETE FROM {};", quote_ident(table)).map_err(io)?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {list} FROM {} ORDER BY {order}",
+            quote_ident(table)
+        ))?;
+        let mut rows = stmt.query([])?;
+        let mut count = 0;
+        let mut line = String::new();
+        while let Some(row) = rows.next()? {
+            line.clear();
+            line.push_str(&insert);
+            for i in 0..columns.len() {
+                if i > 0 {
+                    line.push_str(", ");
+                }
+                literal(&mut line, row.get_ref(i)?);
+            }
+            line.push_str(");");
+            writeln!(out, "{line}").map_err(io)?;
+            count += 1;
+        }
+        tables.push(DumpTable {
+            table: table.to_string(),
+            rows: count,
+        });
+    }
+    writeln!(out, "\nCOMMIT;").map_err(io)?;
+    out.flush().map_err(io)?;
+    Ok((version, created_at, tables))
+}
+
+/// Dump to `path`, via a temporary file so a failed export never leaves a
+/// truncated dump behind.
+pub(crate) fn export_file(conn: &Connection, path: &Path) -> Result<SqlDumpReport, DbError> {
+    let tmp = path.with_extension("sql.partial");
+    let file = std::fs::File::create(&tmp)
+        .map_err(|e| DbError::io(format!("Failed to create {}: {e}", tmp.display())))?;
+    let written = write_dump(conn, &mut BufWriter::new(file));
+    let (schema_version, created_at, tables) = match written {
+        Ok(done) => done,
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+    };
+    std::fs::rename(&tmp, path)
+        .map_err(|e| DbError::io(format!("Failed to write {}: {e}", path.display())))?;
+    Ok(SqlDumpReport {
+        path: path.display().to_string(),
+        schema_version,
+        created_at,
+        tables,
+    })
+}
+
+/// The `-- key: value` header lines of a dump.
+pub(super) fn header<'a>(sql: &'a str, key: &str) -> Option<&'a str> {
+    sql.lines()
+        .take_while(|line| line.starts_with("--"))
+        .find_map(|line| {
+            line.strip_prefix("-- ")?
+                .strip_prefix(key)?
+                .strip_prefix(": ")
+        })
+}
+
+fn skip_comments(mut sql: &str) -> &str {
+    loop {
+        sql = sql.trim_start();
+        if let Some(rest) = sql.strip_prefix("--") {
+            sql = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(rest) = sql.strip_prefix("/*") {
+            sql = rest.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            return sql;
+        }
+    }
+}
+
+enum DumpStatement {
+    /// The dump's own BEGIN/COMMIT; the import runs in its own transaction.
+    Transaction,
+    /// A DELETE or INSERT on one of the synced tables.
+    Write(String),
+}
+
+/// Accept only what a dump is made of, so importing one can't run arbitrary
+/// SQL (ATTACH, DROP, PRAGMA, writes to other tables).
+fn classify(sql: &str) -> Result<DumpStatement, DbError> {
+    let sql = skip_comments(sql);
+    let words: Vec<String> = sql
+        .split_whitespace()
+        .take(5)
+        .map(|w| w.trim_end_matches(';').to_ascii_uppercase())
+        .collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let target = match words.as_slice() {
+        ["BEGIN" | "COMMIT" | "END", ..] => return Ok(DumpStatement::Transaction),
+        ["DELETE", "FROM", ..] => 2,
+        ["INSERT", "INTO", ..] => 2,
+        ["INSERT", "OR", "REPLACE" | "IGNORE", "INTO", ..] => 4,
+        _ => usize::MAX,
+    };
+    let table = sql
+        .split_whitespace()
+        .nth(target)
+        .map(|t| t.split(['(', ';']).next().unwrap_or(t).trim_matches('"'));
+    match table {
+        Some(table) if SYNCED_TABLES.contains(&table) => Ok(DumpStatement::Write(table.into())),
+        _ => {
+            let shown: String = sql.chars().take(60).collect();
+            Err(DbError::invalid(format!(
+                "Unsupported statement in SQL dump: {shown}"
+            )))
+        }
+    }
+}
+
+fn ids(conn: &Connection, table: &str) -> Result<HashSet<String>, DbError> {
+    let mut stmt = conn.prepare(&format!("SELECT id FROM {}", quote_ident(table)))?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(ids)
+}
+
+/// Log `table` as replaced for sync: every row it now holds as an upsert and
+/// every one of `before` it lost as a delete, all as edits made `now`, so the
+/// other devices take the imported data over what they hold.
+fn log_replaced(
+    conn: &Connection,
+    table: &str,
+    before: &HashSet<String>,
+    now: &str,
+    device: &str,
+) -> Result<(), DbError> {
+    let name = quote_ident(table);
+    conn.execute(&format!("UPDATE {name} SET updated_at = ?1"), [now])?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, {} FROM {name}",
+        merge::entity_json_sql(table)
+    ))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut after = HashSet::with_capacity(rows.len());
+    for (id, data) in rows {
+        record_change(conn, table, "upsert", &id, data.as_deref(), now, device)?;
+        after.insert(id);
+    }
+    for id in before.difference(&after) {
+        record_change(conn, table, "delete", id, None, now, device)?;
+    }
+    Ok(())
+}
+
+/// Replace the user data with the dump in `sql`, all or nothing.
+pub(crate) fn import(conn: &mut Connection, sql: &str) -> Result<Vec<DumpTable>, DbError> {
+    if !sql.starts_with(DUMP_TITLE) {
+        return Err(DbError::invalid("Not a BibleMarker SQL dump"));
+    }
+    let format: u32 = header(sql, "format")
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or_else(|| DbError::invalid("SQL dump has no format header"))?;
+    let schema: u32 = header(sql, "schema")
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or_else(|| DbError::invalid("SQL dump has no schema header"))?;
+    let current = migrations::current_version(conn)?;
+    if format > DUMP_FORMAT || schema > current {
+        return Err(DbError::new(
+            DbErrorKind::SchemaTooNew,
+            format!("SQL dump is from a newer version of BibleMarker (schema {schema}, this database is {current})"),
+        ));
+    }
+
+    let tx = conn.transaction()?;
+    // The replaced rows are neither edits to undo nor deletes to trash.
+    tx.execute("INSERT INTO undo_replay (active) VALUES (1)", [])?;
+    // Each table the dump writes, with the ids it held before.
+    let mut touched: Vec<(String, HashSet<String>)> = Vec::new();
+    let mut batch = Batch::new(&tx, sql);
+    while let Some(mut stmt) = batch.next()? {
+        let text = stmt.expanded_sql().unwrap_or_default();
+        if let DumpStatement::Write(table) = classify(&text)? {
+            if !touched.iter().any(|(t, _)| *t == table) {
+                let before = ids(&tx, &table)?;
+                touched.push((table, before));
+            }
+            stmt.raw_execute()?;
+        }
+    }
+    tx.execute("DELETE FROM undo_replay", [])?;
+    tx.execute("DELETE FROM undo_journal", [])?;
+
+    let (now, device) = (now_iso(&tx)?, device_id(&tx)?);
+    let mut tables = Vec::with_capacity(touched.len());
+    for (table, before) in touched {
+        log_replaced(&tx, &table, &before, &now, &device)?;
+        let rows: i64 = tx.query_row(
+            &format!("SELECT COUNT(*) FROM {}", quote_ident(&table)),
+            [],
+            |r| r.get(0),
+        )?;
+        tables.push(DumpTable {
+            table,
+            rows: rows as usize,
+        });
+    }
+    tx.commit()?;
+    Ok(tables)
+}
+
+/// `import`, reported the way the commands return it.
+pub(super) fn import_report(
+    conn: &mut Connection,
+    path: String,
+    sql: &str,
+) -> Result<SqlDumpReport, DbError> {
+    let tables = import(conn, sql)?;
+    Ok(SqlDumpReport {
+        path,
+        schema_version: header(sql, "schema")
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or_default(),
+        created_at: header(sql, "created").unwrap_or_default().to_string(),
+        tables,
+    })
+}
+
+/// Write the user data to `path` as a plain-text SQL dump.
+#[tauri::command]
+pub async fn export_sql_dump(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<SqlDumpReport, DbError> {
+    let out = PathBuf::from(path);
+    with_reader(&app, move |conn| export_file(conn, &out)).await
+}
+
+/// Replace the user data with the SQL dump at `path`. Tables the dump
+/// doesn't mention are left alone.
+#[tauri::command]
+pub async fn import_sql_dump(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<SqlDumpReport, DbError> {
+    let sql = std::fs::read_to_string(&path)
+        .map_err(|e| DbError::io(format!("Failed to read {path}: {e}")))?;
+    with_connection(&app, move |conn| import_report(conn, path, &sql)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+
+    fn dump(conn: &Connection) -> String {
+        let mut out = Vec::new();
+        write_dump(conn, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn round_trips_awkward_values() {
+        let mut conn = migrated_test_connection();
+        conn.execute(
+            "INSERT INTO notes VALUES ('n1', 'kjv', '{\"book\":\"John\"}', NULL,
+                'It''s\nmulti-line; -- not a comment', '2025-01-01', '2025-01-02',
+                'pending', 'dev-local')",
+            [],
+        )
+        .unwrap();
+        let text = dump(&conn);
+        assert!(text.starts_with(DUMP_TITLE));
+        let insert = text.lines().find(|l| l.contains("'n1'")).unwrap();
+        assert!(insert.ends_with(");"), "one row per line: {insert}");
+
+        conn.execute("UPDATE notes SET content = 'changed'", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO notes VALUES ('n2', 'kjv', '{}', NULL, 'extra', '2025-01-01',
+                '2025-01-02', 'pending', 'dev-local')",
+            [],
+        )
+        .unwrap();
+        let tables = import(&mut conn, &text).unwrap();
+        assert!(tables.contains(&DumpTable {
+            table: "notes".into(),
+            rows: 1
+        }));
+        let content: String = conn
+            .query_row("SELECT content FROM notes WHERE id = 'n1'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(content, "It's\nmulti-line; -- not a comment");
+        let side_effects: i64 = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM trash) + (SELECT COUNT(*) FROM undo_journal)",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(side_effects, 0);
+        // The same rows, stamped as edited now so sync carries them.
+        let stamped: String = conn
+            .query_row("SELECT updated_at FROM notes WHERE id = 'n1'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_ne!(stamped, "2025-01-02");
+        assert_eq!(
+            dump(&conn).lines().skip(5).collect::<Vec<_>>(),
+            text.replace("'2025-01-02'", &format!("'{stamped}'"))
+                .lines()
+                .skip(5)
+                .collect::<Vec<_>>()
+        );
+        let logged: Vec<(String, String)> = conn
+            .prepare(
+                "SELECT op, row_id FROM change_log WHERE table_name = 'notes'
+                 AND updated_at = ?1 ORDER BY seq",
+            )
+            .unwrap()
+            .query_map([&stamped], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            logged,
+            [
+                ("upsert".to_string(), "n1".to_string()),
+                ("delete".to_string(), "n2".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn refuses_foreign_statements_and_newer_dumps() {
+        let mut conn = migrated_test_connection();
+        let text = dump(&conn);
+        for evil in [
+            "DROP TABLE notes;",
+            "ATTACH 'x.db' AS x;",
+            "INSERT INTO sync_config VALUES ('device_id', 'stolen');",
+        ] {
+            let err = import(
+                &mut conn,
+                &text.replace("COMMIT;", &format!("{evil}\nCOMMIT;")),
+            )
+            .unwrap_err();
+            assert_eq!(err.kind, DbErrorKind::Invalid, "{evil}");
+        }
+        let newer = text.replacen("-- schema: ", "-- schema: 9", 1);
+        assert_eq!(
+            import(&mut conn, &newer).unwrap_err().kind,
+            DbErrorKind::SchemaTooNew
+        );
+        assert_eq!(
+            import(&mut conn, "SELECT 1;").unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+    }
+}
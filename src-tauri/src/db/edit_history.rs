@@ -0,0 +1,317 @@
+//! Edit history of each note and annotation: the versions it has had,
+//! whose device made each, when, and what changed from the one before.
+//!
+//! Migration 31 appends a version from a trigger on every write, so local
+//! saves, native batches, undo and rows written by sync are all recorded,
+//! and a row's history explains what sync did to it: a version from
+//! another device replaced the one before because it was edited later, or
+//! at the same moment by a device that sorts after (the merge's newest-wins
+//! rule). Each row keeps its last 50 versions; versions older than
+//! `RETENTION_DAYS` are purged, but for a live row's latest.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use super::{device_id, now_iso, trash, with_connection, DbError, DbErrorKind};
+
+/// Days a version is kept once it has been replaced.
+pub(crate) const RETENTION_DAYS: i64 = 180;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryOp {
+    Create,
+    Update,
+    Delete,
+}
+
+/// Why a version from another device replaced the one before it.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Resolution {
+    /// It was edited after the version it replaced.
+    Newer,
+    /// Both were edited at the same moment; the higher device id wins.
+    DeviceTiebreak,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    /// `null` where the field was absent.
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HistoryVersion {
+    pub version: i64,
+    pub op: HistoryOp,
+    /// When this device recorded it.
+    pub at: String,
+    /// When it was edited, on whichever device; what sync compares.
+    #[serde(rename = "updatedAt", skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(rename = "deviceId", skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    /// Made on this device rather than arriving by sync.
+    pub local: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+    /// The note or annotation as the TS layer shapes it; none for a delete.
+    pub item: Option<Value>,
+    /// Fields that differ from the version before, `updatedAt` aside.
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ItemHistory {
+    /// `annotations` or `notes`.
+    pub table: String,
+    pub id: String,
+    /// Most recent first.
+    pub versions: Vec<HistoryVersion>,
+}
+
+/// The fields of `after` that differ from `before`.
+fn changes(before: Option<&Value>, after: Option<&Value>) -> Vec<FieldChange> {
+    let fields = |v: Option<&Value>| {
+        v.and_then(Value::as_object)
+            .map(|o| o.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    let keys: BTreeSet<String> = fields(before).into_iter().chain(fields(after)).collect();
+    keys.into_iter()
+        .filter(|k| k != "updatedAt")
+        .filter_map(|field| {
+            let get = |v: Option<&Value>| v.and_then(|v| v.get(&field)).cloned();
+            let (b, a) = (get(before), get(after));
+            (b != a).then(|| FieldChange {
+                before: b.unwrap_or(Value::Null),
+                after: a.unwrap_or(Value::Null),
+                field,
+            })
+        })
+        .collect()
+}
+
+/// The table whose row `id` has history, the latest written if both do.
+fn table_of(conn: &Connection, id: &str) -> Result<Option<String>, DbError> {
+    Ok(conn
+        .query_row(
+            "SELECT table_name FROM edit_history WHERE row_id = ?1 ORDER BY id DESC LIMIT 1",
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+/// The history of note or annotation `id`.
+pub(crate) fn history(conn: &Connection, id: &str) -> Result<ItemHistory, DbError> {
+    let table = table_of(conn, id)?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("No history for {id}")))?;
+    let local = device_id(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT version, row, updated_at, device_id, sync_status, at FROM edit_history
+         WHERE table_name = ?1 AND row_id = ?2 ORDER BY version",
+    )?;
+    let rows = stmt
+        .query_map(params![table, id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut versions: Vec<HistoryVersion> = Vec::with_capacity(rows.len());
+    for (version, row, updated_at, device, sync_status, at) in rows {
+        let item = row
+            .and_then(|r| serde_json::from_str::<Value>(&r).ok())
+            .map(|r| trash::item_object(&table, &r));
+        let previous = versions.last();
+        let op = match (previous.and_then(|p| p.item.as_ref()), &item) {
+            (_, None) => HistoryOp::Delete,
+            (None, Some(_)) => HistoryOp::Create,
+            (Some(_), Some(_)) => HistoryOp::Update,
+        };
+        let is_local = item.is_none()
+            || sync_status.as_deref() == Some("pending")
+            || device.as_deref() == Some(local.as_str());
+        let resolution = match previous {
+            Some(p) if !is_local && op == HistoryOp::Update => match (&updated_at, &p.updated_at) {
+                (Some(new), Some(old)) if new == old => Some(Resolution::DeviceTiebreak),
+                _ => Some(Resolution::Newer),
+            },
+            _ => None,
+        };
+        versions.push(HistoryVersion {
+            changes: changes(previous.and_then(|p| p.item.as_ref()), item.as_ref()),
+            version,
+            op,
+            at,
+            updated_at,
+            device_id: device,
+            local: is_local,
+            resolution,
+            item,
+        });
+    }
+    versions.reverse();
+    Ok(ItemHistory {
+        table,
+        id: id.to_string(),
+        versions,
+    })
+}
+
+/// Write `id` back as it was at `version`, as a new local edit (and so a
+/// new version). Returns the item as restored.
+pub(crate) fn revert(conn: &mut Connection, id: &str, version: i64) -> Result<Value, DbError> {
+    let device = device_id(conn)?;
+    let tx = conn.transaction()?;
+    let table = table_of(&tx, id)?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("No history for {id}")))?;
+    let row: Option<String> = tx
+        .query_row(
+            "SELECT row FROM edit_history WHERE table_name = ?1 AND row_id = ?2 AND version = ?3",
+            params![table, id, version],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            DbError::new(
+                DbErrorKind::NotFound,
+                format!("{id} has no version {version}"),
+            )
+        })?;
+    let row = row.ok_or_else(|| {
+        DbError::invalid(format!(
+            "Version {version} of {id} is its deletion; revert to one before it"
+        ))
+    })?;
+    let row: Value = serde_json::from_str(&row)
+        .map_err(|e| DbError::invalid(format!("Version {version} of {id} is unreadable: {e}")))?;
+    let now = now_iso(&tx)?;
+    let item = trash::write_row(&tx, &table, row, &now, &device)?;
+    // It's back, so a trashed copy is no longer the one to restore.
+    tx.execute(
+        "DELETE FROM trash WHERE table_name = ?1 AND row_id = ?2",
+        params![table, id],
+    )?;
+    tx.commit()?;
+    Ok(item)
+}
+
+/// Drop versions recorded more than `RETENTION_DAYS` ago, but for a live
+/// row's latest, so its history still begins somewhere.
+pub(crate) fn purge_expired(conn: &Connection) -> Result<usize, DbError> {
+    let cutoff = format!("-{RETENTION_DAYS} days");
+    // A deletion is never kept, so a deleted row's history goes with it.
+    Ok(conn.execute(
+        "DELETE FROM edit_history
+         WHERE at < strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?1)
+           AND (row IS NULL OR version < (
+               SELECT MAX(h.version) FROM edit_history h
+               WHERE h.table_name = edit_history.table_name
+                 AND h.row_id = edit_history.row_id))",
+        [&cutoff],
+    )?)
+}
+
+/// Every version of a note or annotation, most recent first.
+#[tauri::command]
+pub async fn get_annotation_history(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<ItemHistory, DbError> {
+    with_connection(&app, move |conn| {
+        purge_expired(conn)?;
+        history(conn, &id)
+    })
+    .await
+}
+
+/// Put a note or annotation back as it was at `version`.
+#[tauri::command]
+pub async fn revert_annotation(
+    app: tauri::AppHandle,
+    id: String,
+    version: i64,
+) -> Result<Value, DbError> {
+    with_connection(&app, move |conn| revert(conn, &id, version)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::annotations::{self, Annotation, RemoteWrite};
+    use crate::db::migrated_test_connection;
+    use serde_json::json;
+
+    #[test]
+    fn records_each_version_with_its_source_and_reverts_to_one() {
+        let mut conn = migrated_test_connection();
+        let highlight = |color: &str| {
+            json!({
+                "id": "h1", "moduleId": "kjv", "type": "highlight", "color": color,
+                "createdAt": "2025-01-01T00:00:00Z", "styleId": "legacy-yellow",
+                "startRef": { "book": "Gen", "chapter": 15, "verse": 6 },
+                "endRef": { "book": "Gen", "chapter": 15, "verse": 6 },
+            })
+        };
+        annotations::bulk_insert(&mut conn, &[highlight("yellow")]).unwrap();
+        let edit = |color: &str| Annotation::from_value(&highlight(color)).unwrap();
+        annotations::update(&conn, edit("blue"), None).unwrap();
+        let remote = RemoteWrite {
+            updated_at: "2030-01-01T00:00:00.000Z".into(),
+            device_id: "dev-remote".into(),
+        };
+        annotations::update(&conn, edit("green"), Some(&remote)).unwrap();
+        annotations::delete_one(&conn, "h1", "2030-01-02T00:00:00.000Z", "dev-local").unwrap();
+
+        let h = history(&conn, "h1").unwrap();
+        assert_eq!(h.table, "annotations");
+        let ops: Vec<_> = h.versions.iter().map(|v| (v.version, v.op)).collect();
+        assert_eq!(
+            ops,
+            [
+                (4, HistoryOp::Delete),
+                (3, HistoryOp::Update),
+                (2, HistoryOp::Update),
+                (1, HistoryOp::Create),
+            ]
+        );
+        let synced = &h.versions[1];
+        assert!(!synced.local);
+        assert_eq!(synced.resolution, Some(Resolution::Newer));
+        assert_eq!(synced.device_id.as_deref(), Some("dev-remote"));
+        assert_eq!(
+            synced.changes,
+            [FieldChange {
+                field: "color".into(),
+                before: json!("blue"),
+                after: json!("green"),
+            }]
+        );
+        assert!(h.versions[2].local && h.versions[2].resolution.is_none());
+
+        let err = revert(&mut conn, "h1", 4).unwrap_err();
+        assert_eq!(err.kind, DbErrorKind::Invalid);
+        let restored = revert(&mut conn, "h1", 2).unwrap();
+        assert_eq!(restored["color"], "blue");
+        let h = history(&conn, "h1").unwrap();
+        assert_eq!(h.versions[0].version, 5);
+        assert_eq!(h.versions[0].op, HistoryOp::Create);
+        assert!(h.versions[0].local);
+
+        // Nothing is old enough to purge yet.
+        assert_eq!(purge_expired(&conn).unwrap(), 0);
+    }
+}
@@ -0,0 +1,107 @@
+use serde::Serialize;
+
+/// What went wrong, coarse enough for the TS layer to branch on (e.g. retry a
+/// `busy`, offer repair for `corrupt`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DbErrorKind {
+    /// The requested row does not exist.
+    NotFound,
+    /// Another connection held the lock past the busy timeout.
+    Busy,
+    /// SQLite reported a malformed database or a file that isn't one.
+    Corrupt,
+    /// The caller sent something the command can't store.
+    Invalid,
+    /// Filesystem trouble outside SQLite (app data dir, copying files).
+    Io,
+    /// A schema migration failed to apply (the database was left unchanged).
+    Migration,
+    /// The database was migrated by a newer app version than this one.
+    SchemaTooNew,
+    /// Writes are paused while sync brings data in (see `write_lock`).
+    DatabaseLocked,
+    /// Any other SQLite error.
+    Sqlite,
+}
+
+/// Serializes to `{ kind, message }`, mirroring `SyncError` in sync_client so
+/// the frontend can handle both the same way.
+#[derive(Debug, Serialize)]
+pub struct DbError {
+    pub kind: DbErrorKind,
+    pub message: String,
+}
+
+impl DbError {
+    pub(crate) fn new(kind: DbErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+    pub(crate) fn invalid(message: impl Into<String>) -> Self {
+        Self::new(DbErrorKind::Invalid, message)
+    }
+    pub(crate) fn io(message: impl Into<String>) -> Self {
+        Self::new(DbErrorKind::Io, message)
+    }
+    /// Prefix the message with what was being done, keeping the kind.
+    pub(crate) fn context(self, what: impl std::fmt::Display) -> Self {
+        Self::new(self.kind, format!("{what}: {}", self.message))
+    }
+}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(e: rusqlite::Error) -> Self {
+        use rusqlite::ErrorCode;
+        let kind = match &e {
+            rusqlite::Error::QueryReturnedNoRows => DbErrorKind::NotFound,
+            rusqlite::Error::SqliteFailure(f, _) => match f.code {
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => DbErrorKind::Busy,
+                ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => DbErrorKind::Corrupt,
+                _ => DbErrorKind::Sqlite,
+            },
+            _ => DbErrorKind::Sqlite,
+        };
+        Self::new(kind, e.to_string())
+    }
+}
+
+impl std::fmt::Display for DbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_sqlite_errors() {
+        let busy = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        );
+        assert_eq!(DbError::from(busy).kind, DbErrorKind::Busy);
+        let corrupt = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+            None,
+        );
+        assert_eq!(DbError::from(corrupt).kind, DbErrorKind::Corrupt);
+        assert_eq!(
+            DbError::from(rusqlite::Error::QueryReturnedNoRows).kind,
+            DbErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn serializes_kind_in_snake_case() {
+        let json = serde_json::to_value(DbError::new(DbErrorKind::NotFound, "gone")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "kind": "not_found", "message": "gone" })
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! Highlight styles: the user's palette of named looks for marking text,
+//! each combining a fill, a text color, an underline, a border and a symbol
+//! overlay ("Promise": yellow fill in a red box with a star). Kept in the
+//! synced `highlight_styles` table of migration 26; a text annotation points
+//! at one by `styleId`.
+//!
+//! Annotations keep their own flat type and color beside the style, as the
+//! look for apps that don't know styles and for a style since deleted.
+//! Migration 26 turned every color-only look in use into a `legacy-` style
+//! and pointed its marks at it.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::annotations::{SymbolPlacement, UnderlineStyle};
+use super::data_rows::{self, DataRow};
+use super::{with_connection, with_reader, DbError};
+
+/// Longest style name accepted, in characters.
+const MAX_NAME: usize = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StyleUnderline {
+    pub color: String,
+    #[serde(default = "solid")]
+    pub style: UnderlineStyle,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BorderShape {
+    #[default]
+    Box,
+    Rounded,
+    Circle,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StyleBorder {
+    pub color: String,
+    #[serde(default)]
+    pub shape: BorderShape,
+    /// Any underline style but `wavy`.
+    #[serde(default = "solid")]
+    pub style: UnderlineStyle,
+}
+
+/// A symbol drawn over or above the marked words.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StyleSymbol {
+    /// A `SymbolKey`.
+    pub symbol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<SymbolPlacement>,
+}
+
+fn solid() -> UnderlineStyle {
+    UnderlineStyle::Solid
+}
+
+/// One style of the palette, as stored in `highlight_styles.data`. Colors
+/// are `HighlightColor` names or `#rrggbb`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct HighlightStyle {
+    pub id: String,
+    pub name: String,
+    /// The background.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill: Option<String>,
+    #[serde(rename = "textColor", default, skip_serializing_if = "Option::is_none")]
+    pub text_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underline: Option<StyleUnderline>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<StyleBorder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<StyleSymbol>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DataRow for HighlightStyle {
+    const TABLE: &'static str = "highlight_styles";
+    const NOUN: &'static str = "highlight style";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StyleEntry {
+    #[serde(flatten)]
+    pub style: HighlightStyle,
+    /// Annotations drawn with it.
+    pub uses: i64,
+}
+
+fn tidy(color: &mut String, part: &str) -> Result<(), DbError> {
+    *color = color.trim().to_string();
+    if color.is_empty() {
+        return Err(DbError::invalid(format!("A style's {part} needs a color")));
+    }
+    Ok(())
+}
+
+/// Tidy `style` and check it can be stored beside `others`, the rest of
+/// the palette.
+fn check(style: &mut HighlightStyle, others: &[HighlightStyle]) -> Result<(), DbError> {
+    style.name = style.name.trim().to_string();
+    if style.id.trim().is_empty() {
+        return Err(DbError::invalid("Highlight style has no id"));
+    }
+    if style.name.is_empty() || style.name.chars().count() > MAX_NAME {
+        return Err(DbError::invalid(format!(
+            "A style's name must be 1 to {MAX_NAME} characters"
+        )));
+    }
+    for (color, part) in [(&mut style.fill, "fill"), (&mut style.text_color, "text")] {
+        if let Some(c) = color {
+            tidy(c, part)?;
+        }
+    }
+    if let Some(underline) = &mut style.underline {
+        tidy(&mut underline.color, "underline")?;
+    }
+    if let Some(border) = &mut style.border {
+        tidy(&mut border.color, "border")?;
+        if border.style == UnderlineStyle::Wavy {
+            return Err(DbError::invalid("A border can't be wavy"));
+        }
+    }
+    if let Some(symbol) = &mut style.symbol {
+        symbol.symbol = symbol.symbol.trim().to_string();
+        if symbol.symbol.is_empty() {
+            return Err(DbError::invalid("A style's symbol is empty"));
+        }
+    }
+    if style.fill.is_none()
+        && style.text_color.is_none()
+        && style.underline.is_none()
+        && style.border.is_none()
+    {
+        return Err(DbError::invalid(format!(
+            "{} needs a fill, text color, underline or border; a symbol alone is a symbol mark",
+            style.name
+        )));
+    }
+    if others
+        .iter()
+        .any(|o| o.id != style.id && o.name.eq_ignore_ascii_case(&style.name))
+    {
+        return Err(DbError::invalid(format!(
+            "There is already a style named {}",
+            style.name
+        )));
+    }
+    Ok(())
+}
+
+/// Every style, by name. Rows that don't parse are skipped.
+pub(crate) fn styles(conn: &Connection) -> Result<Vec<HighlightStyle>, DbError> {
+    let mut styles = data_rows::all::<HighlightStyle>(conn)?;
+    styles.sort_by_cached_key(|s| (s.name.to_lowercase(), s.id.clone()));
+    Ok(styles)
+}
+
+/// The palette with how many annotations each style draws.
+pub(crate) fn palette(conn: &Connection) -> Result<Vec<StyleEntry>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT json_extract(data, '$.styleId'), COUNT(*) FROM annotations
+         WHERE json_extract(data, '$.styleId') IS NOT NULL GROUP BY 1",
+    )?;
+    let uses = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+    Ok(styles(conn)?
+        .into_iter()
+        .map(|style| StyleEntry {
+            uses: uses.get(&style.id).copied().unwrap_or(0),
+            style,
+        })
+        .collect())
+}
+
+/// Add `style` to the palette. Fails `Invalid` if its id or name is taken.
+pub(crate) fn create(
+    conn: &Connection,
+    mut style: HighlightStyle,
+) -> Result<HighlightStyle, DbError> {
+    check(&mut style, &styles(conn)?)?;
+    data_rows::insert(conn, style)
+}
+
+/// Save an edited `style` over the stored one of its id; the marks drawn
+/// with it change with it.
+pub(crate) fn update(
+    conn: &Connection,
+    mut style: HighlightStyle,
+) -> Result<HighlightStyle, DbError> {
+    check(&mut style, &styles(conn)?)?;
+    data_rows::replace(conn, style)
+}
+
+/// Remove a style from the palette; false if there was none. Its marks
+/// stay, drawn in their own type and color.
+pub(crate) fn delete(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    data_rows::delete(conn, "highlight_styles", id)
+}
+
+/// The style palette by name, with how many marks each draws.
+#[tauri::command]
+pub async fn get_highlight_styles(app: tauri::AppHandle) -> Result<Vec<StyleEntry>, DbError> {
+    with_reader(&app, palette).await
+}
+
+#[tauri::command]
+pub async fn create_highlight_style(
+    app: tauri::AppHandle,
+    style: HighlightStyle,
+) -> Result<HighlightStyle, DbError> {
+    with_connection(&app, move |conn| create(conn, style)).await
+}
+
+#[tauri::command]
+pub async fn update_highlight_style(
+    app: tauri::AppHandle,
+    style: HighlightStyle,
+) -> Result<HighlightStyle, DbError> {
+    with_connection(&app, move |conn| update(conn, style)).await
+}
+
+/// Remove a style, leaving its marks in their own colors.
+#[tauri::command]
+pub async fn delete_highlight_style(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| delete(conn, &id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrated_test_connection, migrations, test_connection, DbErrorKind};
+
+    fn style(id: &str, name: &str) -> HighlightStyle {
+        HighlightStyle {
+            id: id.into(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn migrates_color_only_marks_into_styles() {
+        let mut conn = test_connection();
+        conn.execute_batch(
+            "CREATE TABLE schema_version (
+                id INTEGER PRIMARY KEY CHECK (id = 1), version INTEGER NOT NULL,
+                updated_at TEXT NOT NULL);
+             INSERT INTO schema_version VALUES (1, 13, '2025-01-01');
+             INSERT INTO annotations (id, module_id, type, data, created_at, updated_at) VALUES
+               ('a1', 'kjv', 'highlight', '{\"color\":\"yellow\"}', 'x', 'x'),
+               ('a2', 'kjv', 'highlight', '{\"color\":\"yellow\"}', 'x', 'x'),
+               ('a3', 'kjv', 'underline', '{\"color\":\"red\",\"underlineStyle\":\"wavy\"}', 'x', 'x'),
+               ('a4', 'kjv', 'underline', '{\"color\":\"red\"}', 'x', 'x'),
+               ('a5', 'kjv', 'symbol', '{\"symbol\":\"star\",\"color\":\"blue\"}', 'x', 'x');",
+        )
+        .unwrap();
+        migrations::migrate(&mut conn, false).unwrap();
+
+        let entries = palette(&conn).unwrap();
+        let found: Vec<_> = entries
+            .iter()
+            .map(|e| (e.style.id.as_str(), e.style.name.as_str(), e.uses))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("legacy-underline-red-solid", "Red underline", 1),
+                ("legacy-underline-red-wavy", "Red wavy underline", 1),
+                ("legacy-highlight-yellow", "Yellow highlight", 2),
+            ]
+        );
+        assert_eq!(entries[2].style.fill.as_deref(), Some("yellow"));
+        assert_eq!(
+            entries[1].style.underline,
+            Some(StyleUnderline {
+                color: "red".into(),
+                style: UnderlineStyle::Wavy
+            })
+        );
+        let symbol: String = conn
+            .query_row("SELECT data FROM annotations WHERE id = 'a5'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert!(!symbol.contains("styleId"), "{symbol}");
+    }
+
+    #[test]
+    fn keeps_a_synced_palette_of_combined_styles() {
+        let conn = migrated_test_connection();
+        let mut promise = style("s1", " Promise ");
+        promise.fill = Some("yellow".into());
+        promise.border = Some(StyleBorder {
+            color: " red ".into(),
+            shape: BorderShape::Rounded,
+            style: UnderlineStyle::Dashed,
+        });
+        promise.symbol = Some(StyleSymbol {
+            symbol: "star".into(),
+            color: None,
+            placement: Some(SymbolPlacement::Above),
+        });
+        let saved = create(&conn, promise).unwrap();
+        assert_eq!(
+            (saved.name.as_str(), saved.border.unwrap().color.as_str()),
+            ("Promise", "red")
+        );
+
+        let mut symbol_only = style("s2", "Star");
+        symbol_only.symbol = saved.symbol.clone();
+        assert!(create(&conn, symbol_only)
+            .unwrap_err()
+            .message
+            .contains("symbol alone"));
+        let mut dup = style("s3", "promise");
+        dup.text_color = Some("blue".into());
+        assert!(create(&conn, dup.clone())
+            .unwrap_err()
+            .message
+            .contains("already a style"));
+        dup.name = "Warning".into();
+        dup.border = Some(StyleBorder {
+            color: "red".into(),
+            shape: BorderShape::Box,
+            style: UnderlineStyle::Wavy,
+        });
+        assert_eq!(create(&conn, dup).unwrap_err().kind, DbErrorKind::Invalid);
+    }
+}
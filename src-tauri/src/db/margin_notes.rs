@@ -0,0 +1,281 @@
+//! Margin notes: a note on a whole chapter, or on a pericope within it,
+//! rather than on a verse ("the chapter of the new birth", written beside
+//! John 3 as in a paper Bible's margin). Kept in the synced `margin_notes`
+//! table of migration 28, apart from verse notes, which belong to one
+//! translation's verse; a margin note belongs to the chapter in every
+//! translation, as chapter titles do.
+//!
+//! A note on a pericope keeps the passage's range and title rather than a
+//! pericope set's number, which is local to an installed set, and shows in
+//! the margin of every chapter the passage spans.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::data_rows::{self, DataRow};
+use super::{with_connection, with_reader, DbError};
+use crate::content::books;
+
+/// Which margin of the chapter a note is written in.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum MarginSide {
+    /// Above the chapter, where a theme or title goes.
+    Top,
+    Left,
+    #[default]
+    Right,
+    /// Below the chapter.
+    Bottom,
+}
+
+/// Where in its margin a note sits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Placement {
+    #[serde(default)]
+    pub side: MarginSide,
+    /// The verse it is written beside in a side margin; none for the top of
+    /// its chapter or passage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verse: Option<i64>,
+    /// Its place among notes in the same spot, from 0.
+    #[serde(default)]
+    pub order: i64,
+}
+
+/// The passage a note on a pericope covers, from its chapter's `verse` to
+/// `end_chapter` `end_verse`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PericopeSpan {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub verse: i64,
+    #[serde(rename = "endChapter")]
+    pub end_chapter: i64,
+    #[serde(rename = "endVerse")]
+    pub end_verse: i64,
+}
+
+/// A margin note, as stored in `margin_notes.data`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MarginNote {
+    pub id: String,
+    /// OSIS book.
+    pub book: String,
+    /// The chapter it is on, or its passage begins in.
+    pub chapter: i64,
+    /// The passage it is on; none for the whole chapter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pericope: Option<PericopeSpan>,
+    /// Markdown.
+    pub content: String,
+    #[serde(default)]
+    pub placement: Placement,
+    /// A highlight color name or `#rrggbb`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(rename = "studyId", default, skip_serializing_if = "Option::is_none")]
+    pub study_id: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl MarginNote {
+    /// The last chapter whose margin it shows in.
+    fn end_chapter(&self) -> i64 {
+        self.pericope
+            .as_ref()
+            .map_or(self.chapter, |p| p.end_chapter)
+    }
+}
+
+impl DataRow for MarginNote {
+    const TABLE: &'static str = "margin_notes";
+    const NOUN: &'static str = "margin note";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+/// Tidy `note` and check it can be stored.
+fn check(note: &mut MarginNote) -> Result<(), DbError> {
+    note.content = note.content.trim().to_string();
+    if note.id.trim().is_empty() {
+        return Err(DbError::invalid("Margin note has no id"));
+    }
+    if books::position(&note.book).is_none() || note.chapter < 1 {
+        return Err(DbError::invalid(format!(
+            "{} {} is not a chapter",
+            note.book, note.chapter
+        )));
+    }
+    if note.content.is_empty() {
+        return Err(DbError::invalid(
+            "A margin note needs something written in it",
+        ));
+    }
+    if let Some(pericope) = &mut note.pericope {
+        pericope.title = pericope
+            .title
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        if pericope.verse < 1
+            || pericope.end_verse < 1
+            || (pericope.end_chapter, pericope.end_verse) < (note.chapter, pericope.verse)
+        {
+            return Err(DbError::invalid(format!(
+                "The passage of a margin note must run forwards from {} {}",
+                note.book, note.chapter
+            )));
+        }
+    }
+    if note.placement.verse.is_some_and(|v| v < 1) {
+        return Err(DbError::invalid("A margin note sits beside a verse from 1"));
+    }
+    Ok(())
+}
+
+/// The notes in the margins of `book` `chapter`: its own and those of the
+/// passages that span it, of `study_id` and of no study, in margin order.
+pub(crate) fn chapter_notes(
+    conn: &Connection,
+    book: &str,
+    chapter: i64,
+    study_id: Option<&str>,
+) -> Result<Vec<MarginNote>, DbError> {
+    let mut notes: Vec<_> = data_rows::all::<MarginNote>(conn)?
+        .into_iter()
+        .filter(|n| n.book == book && (n.chapter..=n.end_chapter()).contains(&chapter))
+        .filter(|n| n.study_id.is_none() || n.study_id.as_deref() == study_id)
+        .collect();
+    notes.sort_by_cached_key(|n| {
+        (
+            n.placement.side,
+            n.placement.verse.unwrap_or(0),
+            n.placement.order,
+            n.created_at.clone(),
+            n.id.clone(),
+        )
+    });
+    Ok(notes)
+}
+
+pub(crate) fn create(conn: &Connection, mut note: MarginNote) -> Result<MarginNote, DbError> {
+    check(&mut note)?;
+    data_rows::insert(conn, note)
+}
+
+/// Save an edited or moved note over the stored one of its id.
+pub(crate) fn update(conn: &Connection, mut note: MarginNote) -> Result<MarginNote, DbError> {
+    check(&mut note)?;
+    data_rows::replace(conn, note)
+}
+
+pub(crate) fn delete(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    data_rows::delete(conn, "margin_notes", id)
+}
+
+/// The margin notes of a chapter, with those of passages spanning it.
+#[tauri::command]
+pub async fn get_margin_notes(
+    app: tauri::AppHandle,
+    book: String,
+    chapter: i64,
+    study_id: Option<String>,
+) -> Result<Vec<MarginNote>, DbError> {
+    with_reader(&app, move |conn| {
+        chapter_notes(conn, &book, chapter, study_id.as_deref())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn create_margin_note(
+    app: tauri::AppHandle,
+    note: MarginNote,
+) -> Result<MarginNote, DbError> {
+    with_connection(&app, move |conn| create(conn, note)).await
+}
+
+#[tauri::command]
+pub async fn update_margin_note(
+    app: tauri::AppHandle,
+    note: MarginNote,
+) -> Result<MarginNote, DbError> {
+    with_connection(&app, move |conn| update(conn, note)).await
+}
+
+#[tauri::command]
+pub async fn delete_margin_note(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| delete(conn, &id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+
+    fn note(id: &str, chapter: i64, content: &str) -> MarginNote {
+        MarginNote {
+            id: id.into(),
+            book: "Luke".into(),
+            chapter,
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn places_chapter_and_passage_notes_in_the_margins_they_span() {
+        let conn = migrated_test_connection();
+        let theme = create(&conn, note("theme", 15, " Lost and found ")).unwrap();
+        assert_eq!(theme.content, "Lost and found");
+        let mut passage = note("shrewd", 15, "Use of wealth");
+        passage.pericope = Some(PericopeSpan {
+            title: Some("The Shrewd Manager ".into()),
+            verse: 32,
+            end_chapter: 16,
+            end_verse: 13,
+        });
+        passage.placement = Placement {
+            side: MarginSide::Left,
+            verse: Some(32),
+            order: 0,
+        };
+        create(&conn, passage.clone()).unwrap();
+        let mut top = note("heading", 16, "Money");
+        top.placement.side = MarginSide::Top;
+        top.study_id = Some("s1".into());
+        create(&conn, top).unwrap();
+
+        let ids = |chapter, study| {
+            chapter_notes(&conn, "Luke", chapter, study)
+                .unwrap()
+                .into_iter()
+                .map(|n| n.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(15, None), ["shrewd", "theme"]);
+        assert_eq!(ids(16, None), ["shrewd"]);
+        assert_eq!(ids(16, Some("s1")), ["heading", "shrewd"]);
+        assert!(ids(17, Some("s1")).is_empty());
+
+        passage.pericope.as_mut().unwrap().end_chapter = 14;
+        assert!(update(&conn, passage).is_err());
+        assert!(create(&conn, note("blank", 15, "  ")).is_err());
+        assert!(delete(&conn, "theme").unwrap());
+        assert_eq!(ids(15, None), ["shrewd"]);
+    }
+}
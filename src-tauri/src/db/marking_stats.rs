@@ -0,0 +1,306 @@
+//! Marking statistics: how many marks there are of each color, symbol and
+//! tag, by book and chapter, and how densely each chapter is marked, for
+//! the canon heatmap of where study has gone.
+//!
+//! A mark counts where it begins, as `chapter_annotations` places it:
+//! symbols at `ref`, text marks at `startRef`. Density is marks per verse
+//! of the chapter, by the KJV's verse counts, so that short and long
+//! chapters compare; every chapter in scope is listed, marked or not.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::tags::Tags;
+use super::{with_reader, DbError};
+use crate::content::books;
+
+/// Which marks to count.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StatsScope {
+    /// One translation's marks; none for every translation's.
+    #[serde(rename = "moduleId", default)]
+    pub module_id: Option<String>,
+    /// One OSIS book; none for the whole canon.
+    #[serde(default)]
+    pub book: Option<String>,
+}
+
+/// Marks, and how many of them are of each look and tag.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct MarkCounts {
+    pub marks: usize,
+    /// Text marks by color.
+    #[serde(rename = "byColor")]
+    pub by_color: BTreeMap<String, usize>,
+    /// Symbols by `SymbolKey`.
+    #[serde(rename = "bySymbol")]
+    pub by_symbol: BTreeMap<String, usize>,
+    /// Marks by the path of each tag on them.
+    #[serde(rename = "byTag")]
+    pub by_tag: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ChapterStats {
+    pub chapter: i64,
+    #[serde(flatten)]
+    pub counts: MarkCounts,
+    /// Verses a mark begins in.
+    #[serde(rename = "markedVerses")]
+    pub marked_verses: usize,
+    /// Verses in the chapter.
+    pub verses: usize,
+    /// Marks per verse.
+    pub density: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BookStats {
+    pub book: String,
+    #[serde(flatten)]
+    pub counts: MarkCounts,
+    pub chapters: Vec<ChapterStats>,
+}
+
+/// What `get_marking_stats` returns: totals, then each book in canon order.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct MarkingStats {
+    #[serde(flatten)]
+    pub counts: MarkCounts,
+    pub books: Vec<BookStats>,
+    /// The densest chapter's density, to scale a heatmap by.
+    #[serde(rename = "maxDensity")]
+    pub max_density: f64,
+}
+
+impl MarkCounts {
+    fn add(&mut self, mark: &Mark, tags: &[String]) {
+        self.marks += 1;
+        let by = if mark.symbol {
+            &mut self.by_symbol
+        } else {
+            &mut self.by_color
+        };
+        if let Some(look) = &mark.look {
+            *by.entry(look.clone()).or_default() += 1;
+        }
+        for tag in tags {
+            *self.by_tag.entry(tag.clone()).or_default() += 1;
+        }
+    }
+}
+
+/// A mark where it begins.
+struct Mark {
+    id: String,
+    book: String,
+    chapter: i64,
+    verse: i64,
+    symbol: bool,
+    /// Its color, or its symbol.
+    look: Option<String>,
+}
+
+/// Marks in `scope`.
+fn marks(conn: &Connection, scope: &StatsScope) -> Result<Vec<Mark>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, type, book, chapter, verse, look FROM (
+           SELECT id, type, module_id,
+             json_extract(data, CASE type WHEN 'symbol' THEN '$.ref.book' ELSE '$.startRef.book' END) AS book,
+             json_extract(data, CASE type WHEN 'symbol' THEN '$.ref.chapter' ELSE '$.startRef.chapter' END) AS chapter,
+             json_extract(data, CASE type WHEN 'symbol' THEN '$.ref.verse' ELSE '$.startRef.verse' END) AS verse,
+             json_extract(data, CASE type WHEN 'symbol' THEN '$.symbol' ELSE '$.color' END) AS look
+           FROM annotations)
+         WHERE (?1 IS NULL OR module_id = ?1) AND (?2 IS NULL OR book = ?2)
+           AND typeof(chapter) = 'integer' AND typeof(verse) = 'integer'",
+    )?;
+    let marks = stmt
+        .query_map([&scope.module_id, &scope.book], |row| {
+            Ok(Mark {
+                id: row.get(0)?,
+                symbol: row.get::<_, String>(1)? == "symbol",
+                book: row.get(2)?,
+                chapter: row.get(3)?,
+                verse: row.get(4)?,
+                look: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(marks)
+}
+
+/// Tag paths on each annotation, by its id.
+fn annotation_tags(conn: &Connection) -> Result<HashMap<String, Vec<String>>, DbError> {
+    let tags = Tags::load(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT json_extract(data, '$.itemId'), json_extract(data, '$.tagId') FROM tag_links
+         WHERE json_extract(data, '$.itemType') = 'annotation'",
+    )?;
+    let mut by_item: HashMap<String, Vec<String>> = HashMap::new();
+    for row in stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })? {
+        let (item, tag) = row?;
+        if tags.get(&tag).is_some() {
+            by_item.entry(item).or_default().push(tags.path(&tag));
+        }
+    }
+    Ok(by_item)
+}
+
+pub(crate) fn stats(conn: &Connection, scope: &StatsScope) -> Result<MarkingStats, DbError> {
+    if let Some(book) = &scope.book {
+        if books::position(book).is_none() {
+            return Err(DbError::invalid(format!("{book} is not a book")));
+        }
+    }
+    let tags = annotation_tags(conn)?;
+    let mut by_chapter: HashMap<(&str, i64), (MarkCounts, HashSet<i64>)> = HashMap::new();
+    let marks = marks(conn, scope)?;
+    for mark in &marks {
+        let (counts, verses) = by_chapter
+            .entry((mark.book.as_str(), mark.chapter))
+            .or_default();
+        counts.add(mark, tags.get(&mark.id).map_or(&[], Vec::as_slice));
+        verses.insert(mark.verse);
+    }
+
+    let mut stats = MarkingStats::default();
+    for (at, book) in books::BOOKS.iter().enumerate() {
+        if scope.book.as_deref().is_some_and(|b| b != book.osis) {
+            continue;
+        }
+        let chapter_verses = books::KJV_VERSES[at];
+        let last = by_chapter
+            .keys()
+            .filter(|(b, _)| *b == book.osis)
+            .map(|&(_, c)| c)
+            .chain([chapter_verses.len() as i64])
+            .max()
+            .unwrap_or(0);
+        let mut book_stats = BookStats {
+            book: book.osis.to_string(),
+            counts: MarkCounts::default(),
+            chapters: Vec::new(),
+        };
+        for chapter in 1..=last {
+            let (counts, marked) = by_chapter.remove(&(book.osis, chapter)).unwrap_or_default();
+            // A chapter the KJV doesn't have counts the verses marked in it.
+            let verses = usize::try_from(chapter - 1)
+                .ok()
+                .and_then(|i| chapter_verses.get(i))
+                .map_or(marked.len(), |&v| usize::from(v));
+            let density = if verses == 0 {
+                0.0
+            } else {
+                counts.marks as f64 / verses as f64
+            };
+            stats.max_density = stats.max_density.max(density);
+            merge(&mut book_stats.counts, &counts);
+            book_stats.chapters.push(ChapterStats {
+                chapter,
+                counts,
+                marked_verses: marked.len(),
+                verses,
+                density,
+            });
+        }
+        merge(&mut stats.counts, &book_stats.counts);
+        stats.books.push(book_stats);
+    }
+    Ok(stats)
+}
+
+/// Add `part` into `total`.
+fn merge(total: &mut MarkCounts, part: &MarkCounts) {
+    total.marks += part.marks;
+    for (into, from) in [
+        (&mut total.by_color, &part.by_color),
+        (&mut total.by_symbol, &part.by_symbol),
+        (&mut total.by_tag, &part.by_tag),
+    ] {
+        for (key, n) in from {
+            *into.entry(key.clone()).or_default() += n;
+        }
+    }
+}
+
+/// Counts of marks by color, symbol and tag for each book and chapter in
+/// `scope`, with each chapter's density for a heatmap.
+#[tauri::command]
+pub async fn get_marking_stats(
+    app: tauri::AppHandle,
+    scope: StatsScope,
+) -> Result<MarkingStats, DbError> {
+    with_reader(&app, move |conn| stats(conn, &scope)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{annotations, migrated_test_connection, tags, tags::ItemType};
+    use serde_json::json;
+
+    #[test]
+    fn counts_marks_by_look_and_tag_for_each_chapter() {
+        let mut conn = migrated_test_connection();
+        let mark = |id: &str, module: &str, chapter: i64, verse: i64, color: &str| {
+            let at = json!({ "book": "Ruth", "chapter": chapter, "verse": verse });
+            json!({
+                "id": id, "moduleId": module, "type": "highlight",
+                "startRef": at, "endRef": at, "color": color,
+            })
+        };
+        let symbol = json!({
+            "id": "s1", "moduleId": "kjv", "type": "symbol",
+            "ref": { "book": "Ruth", "chapter": 1, "verse": 16 }, "symbol": "heart",
+        });
+        annotations::bulk_insert(
+            &mut conn,
+            &[
+                mark("h1", "kjv", 1, 16, "red"),
+                mark("h2", "kjv", 1, 17, "red"),
+                mark("h3", "kjv", 4, 13, "blue"),
+                mark("other", "esv", 1, 1, "green"),
+                symbol,
+            ],
+        )
+        .unwrap();
+        tags::attach(&mut conn, "Themes/Loyalty", ItemType::Annotation, "h1").unwrap();
+        tags::attach(&mut conn, "Themes/Loyalty", ItemType::Annotation, "s1").unwrap();
+
+        let scope = StatsScope {
+            module_id: Some("kjv".into()),
+            book: Some("Ruth".into()),
+        };
+        let found = stats(&conn, &scope).unwrap();
+        assert_eq!(found.counts.marks, 4);
+        assert_eq!(found.counts.by_color["red"], 2);
+        assert_eq!(found.counts.by_symbol["heart"], 1);
+        assert_eq!(found.counts.by_tag["Themes/Loyalty"], 2);
+        let [ruth] = found.books.as_slice() else {
+            panic!("{:?}", found.books);
+        };
+        assert_eq!(ruth.chapters.len(), 4);
+        let first = &ruth.chapters[0];
+        assert_eq!(
+            (first.counts.marks, first.marked_verses, first.verses),
+            (3, 2, 22)
+        );
+        assert_eq!(ruth.chapters[1].counts, MarkCounts::default());
+        assert_eq!(found.max_density, 3.0 / 22.0);
+
+        let everything = stats(&conn, &StatsScope::default()).unwrap();
+        assert_eq!(everything.books.len(), 66);
+        assert_eq!(everything.counts.marks, 5);
+        assert!(stats(
+            &conn,
+            &StatsScope {
+                book: Some("Narnia".into()),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,723 @@
+//! Marking templates: a word or phrase and the look to mark it with
+//! ("covenant" in a red box), applied to every occurrence in a book at once
+//! instead of marking each by hand. Kept in the synced `marking_templates`
+//! table of migration 27.
+//!
+//! Applying a template searches the translation's text (its mounted content
+//! file, or the chapters cached from an API) and creates one annotation per
+//! occurrence, in one transaction and one undo step. Each carries the
+//! template's id, and the id of a template mark is derived from the
+//! template, translation and place, so applying again only adds what is new
+//! and every device makes the same marks. `preview` counts before anything
+//! is written; `remove_marks` takes a template's marks away again.
+//!
+//! Words are matched as the reader matches key words (keywordMatching.ts):
+//! split on whitespace and dashes, compared without surrounding punctuation.
+//! Offsets are UTF-16 code units, as the reader's JS strings count them.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+use super::annotations::{self, Annotation, UnderlineStyle, VerseRef};
+use super::data_rows::{self, DataRow};
+use super::{anchors, device_id, now_iso, undo, with_connection, with_reader, DbError};
+use crate::content::{self, books};
+
+/// What a template's marks are drawn as, as the text annotation types.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MarkKind {
+    #[default]
+    #[serde(rename = "highlight")]
+    Highlight,
+    #[serde(rename = "textColor")]
+    TextColor,
+    #[serde(rename = "underline")]
+    Underline,
+}
+
+/// A template, as stored in `marking_templates.data`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MarkingTemplate {
+    pub id: String,
+    /// The word or phrase to mark.
+    pub word: String,
+    /// Other forms marked with it (`covenants`, `covenanted`).
+    #[serde(default)]
+    pub variants: Vec<String>,
+    #[serde(rename = "caseSensitive", default)]
+    pub case_sensitive: bool,
+    /// The OSIS book it marks; none for the whole Bible.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub book: Option<String>,
+    #[serde(rename = "type", default)]
+    pub kind: MarkKind,
+    /// A `HighlightColor`.
+    pub color: String,
+    #[serde(
+        rename = "underlineStyle",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub underline_style: Option<UnderlineStyle>,
+    /// The `HighlightStyle` its marks are drawn with; `type` and `color`
+    /// are their flat look.
+    #[serde(rename = "styleId", default, skip_serializing_if = "Option::is_none")]
+    pub style_id: Option<String>,
+    /// The key word its marks count towards.
+    #[serde(rename = "presetId", default, skip_serializing_if = "Option::is_none")]
+    pub preset_id: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DataRow for MarkingTemplate {
+    const TABLE: &'static str = "marking_templates";
+    const NOUN: &'static str = "marking template";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+/// One place a template's words are found.
+#[derive(Debug, Clone, PartialEq)]
+struct Occurrence {
+    verse: VerseRef,
+    start: i64,
+    end: i64,
+    text: String,
+    first_word: i64,
+    last_word: i64,
+}
+
+/// What applying a template would do (`preview_marking_template`).
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct TemplatePreview {
+    /// Every occurrence of its words in the book.
+    pub occurrences: usize,
+    /// Verses they are in.
+    pub verses: usize,
+    /// Occurrences by chapter.
+    pub chapters: BTreeMap<i64, usize>,
+    /// Occurrences the template has already marked.
+    #[serde(rename = "alreadyMarked")]
+    pub already_marked: usize,
+    /// Chapters of the book no text was found for, which it can't mark.
+    #[serde(rename = "textMissing")]
+    pub text_missing: bool,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct TemplateApplied {
+    pub created: usize,
+    #[serde(rename = "alreadyMarked")]
+    pub already_marked: usize,
+}
+
+/// Words of `text` as the reader splits them: runs without whitespace or
+/// em and en dashes, as byte ranges.
+fn split(text: &str) -> Vec<(usize, usize)> {
+    let breaks = |c: char| c.is_whitespace() || c == '\u{2014}' || c == '\u{2013}';
+    let mut words = Vec::new();
+    let mut start = None;
+    for (at, c) in text.char_indices() {
+        match (breaks(c), start) {
+            (true, Some(from)) => {
+                words.push((from, at));
+                start = None;
+            }
+            (false, None) => start = Some(at),
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        words.push((from, text.len()));
+    }
+    words
+}
+
+fn word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// `word` without punctuation around it, folded unless `case_sensitive`.
+fn normalize(word: &str, case_sensitive: bool) -> String {
+    let word = word.trim_matches(|c: char| !word_char(c));
+    if case_sensitive {
+        word.to_string()
+    } else {
+        word.to_lowercase()
+    }
+}
+
+fn units(text: &str) -> i64 {
+    text.encode_utf16().count() as i64
+}
+
+/// Where `phrase` occurs in `text`, trimmed of punctuation but apostrophes.
+fn find(text: &str, phrase: &str, case_sensitive: bool) -> Vec<(usize, usize, i64, i64)> {
+    let wanted: Vec<String> = phrase
+        .split_whitespace()
+        .map(|w| normalize(w, case_sensitive))
+        .filter(|w| !w.is_empty())
+        .collect();
+    let words = split(text);
+    let mut found = Vec::new();
+    if wanted.is_empty() || words.len() < wanted.len() {
+        return found;
+    }
+    let mut i = 0;
+    while i + wanted.len() <= words.len() {
+        let candidate = &words[i..i + wanted.len()];
+        let matches = candidate
+            .iter()
+            .zip(&wanted)
+            .all(|(&(a, b), w)| normalize(&text[a..b], case_sensitive) == *w);
+        if matches {
+            let (from, to) = (candidate[0].0, candidate[candidate.len() - 1].1);
+            let keep = |c: char| word_char(c) || c == '\'';
+            let inner = text[from..to].trim_start_matches(|c: char| !keep(c));
+            let start = to - inner.len();
+            let end = start + inner.trim_end_matches(|c: char| !keep(c)).len();
+            if end > start {
+                found.push((start, end, i as i64, (i + wanted.len() - 1) as i64));
+            }
+            i += wanted.len();
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+impl MarkingTemplate {
+    /// Every occurrence of its word and variants in `text`; where they
+    /// overlap, the longest phrase wins.
+    fn occurrences(&self, verse: &VerseRef, text: &str) -> Vec<Occurrence> {
+        let mut phrases: Vec<&str> = std::iter::once(self.word.as_str())
+            .chain(self.variants.iter().map(String::as_str))
+            .collect();
+        phrases.sort_by_key(|p| std::cmp::Reverse(p.split_whitespace().count()));
+        let mut taken: Vec<(usize, usize)> = Vec::new();
+        let mut found = Vec::new();
+        for phrase in phrases {
+            for (start, end, first, last) in find(text, phrase, self.case_sensitive) {
+                if taken.iter().any(|&(a, b)| start < b && a < end) {
+                    continue;
+                }
+                taken.push((start, end));
+                found.push(Occurrence {
+                    verse: verse.clone(),
+                    start: units(&text[..start]),
+                    end: units(&text[..end]),
+                    text: text[start..end].to_string(),
+                    first_word: first,
+                    last_word: last,
+                });
+            }
+        }
+        found.sort_by_key(|o| o.start);
+        found
+    }
+
+    /// The id of its mark on `occurrence` in `module_id`.
+    fn mark_id(&self, module_id: &str, occurrence: &Occurrence) -> String {
+        let v = &occurrence.verse;
+        format!(
+            "tpl-{}-{module_id}-{}.{}.{}-{}",
+            self.id, v.book, v.chapter, v.verse, occurrence.start
+        )
+    }
+
+    fn mark(&self, module_id: &str, occurrence: &Occurrence) -> Result<Annotation, DbError> {
+        let mut mark = json!({
+            "id": self.mark_id(module_id, occurrence),
+            "moduleId": module_id,
+            "type": self.kind,
+            "startRef": occurrence.verse,
+            "endRef": occurrence.verse,
+            "startOffset": occurrence.start,
+            "endOffset": occurrence.end,
+            "selectedText": occurrence.text,
+            "startWordIndex": occurrence.first_word,
+            "endWordIndex": occurrence.last_word,
+            "color": self.color,
+            "templateId": self.id,
+        });
+        if let Some(line) = self
+            .underline_style
+            .filter(|_| self.kind == MarkKind::Underline)
+        {
+            mark["underlineStyle"] = json!(line);
+        }
+        if let Some(style) = &self.style_id {
+            mark["styleId"] = json!(style);
+        }
+        if let Some(preset) = &self.preset_id {
+            mark["presetId"] = json!(preset);
+        }
+        Annotation::from_value(&mark)
+    }
+
+    /// The books it marks.
+    fn books(&self) -> Vec<String> {
+        match &self.book {
+            Some(book) => vec![book.clone()],
+            None => books::BOOKS.iter().map(|b| b.osis.to_string()).collect(),
+        }
+    }
+}
+
+/// Tidy `template` and check it can be stored.
+fn check(template: &mut MarkingTemplate) -> Result<(), DbError> {
+    template.word = template.word.trim().to_string();
+    template.color = template.color.trim().to_string();
+    template.variants = template
+        .variants
+        .iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && *v != template.word)
+        .collect();
+    if template.id.trim().is_empty() {
+        return Err(DbError::invalid("Marking template has no id"));
+    }
+    if normalize(&template.word, false).is_empty() {
+        return Err(DbError::invalid("A marking template needs a word to mark"));
+    }
+    if template.color.is_empty() {
+        return Err(DbError::invalid(format!(
+            "Say what color to mark {} with",
+            template.word
+        )));
+    }
+    if let Some(book) = &template.book {
+        if books::position(book).is_none() {
+            return Err(DbError::invalid(format!("{book} is not a book")));
+        }
+    }
+    Ok(())
+}
+
+/// Every template, by word. Rows that don't parse are skipped.
+pub(crate) fn templates(conn: &Connection) -> Result<Vec<MarkingTemplate>, DbError> {
+    let mut templates = data_rows::all::<MarkingTemplate>(conn)?;
+    templates.sort_by_cached_key(|t| (t.word.to_lowercase(), t.id.clone()));
+    Ok(templates)
+}
+
+pub(crate) fn template(conn: &Connection, id: &str) -> Result<MarkingTemplate, DbError> {
+    data_rows::get(conn, id)
+}
+
+pub(crate) fn create(
+    conn: &Connection,
+    mut template: MarkingTemplate,
+) -> Result<MarkingTemplate, DbError> {
+    check(&mut template)?;
+    data_rows::insert(conn, template)
+}
+
+/// Save an edited template. Marks it already made stay as they were.
+pub(crate) fn update(
+    conn: &Connection,
+    mut template: MarkingTemplate,
+) -> Result<MarkingTemplate, DbError> {
+    check(&mut template)?;
+    data_rows::replace(conn, template)
+}
+
+/// Delete a template, and with `with_marks` the marks it made.
+pub(crate) fn delete(conn: &mut Connection, id: &str, with_marks: bool) -> Result<bool, DbError> {
+    if with_marks {
+        remove_marks(conn, id, None)?;
+    }
+    data_rows::delete(conn, "marking_templates", id)
+}
+
+/// The verses of `book` in `module_id`, by chapter: from its mounted
+/// content file if it is one, else the chapters cached from its API.
+fn verses(
+    conn: &Connection,
+    module_id: &str,
+    book: &str,
+) -> Result<BTreeMap<i64, BTreeMap<i64, String>>, DbError> {
+    let mut chapters: BTreeMap<i64, BTreeMap<i64, String>> = BTreeMap::new();
+    let mut add = |(chapter, verse, text): (i64, i64, String)| {
+        chapters.entry(chapter).or_default().insert(verse, text);
+    };
+    if let Some(mounted) = content::mounted(module_id) {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT chapter, verse, text FROM \"{}\".verses WHERE book = ?1",
+            mounted.schema
+        ))?;
+        for row in stmt.query_map([book], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))? {
+            add(row?);
+        }
+    } else {
+        let mut stmt = conn.prepare(
+            "SELECT c.chapter, CAST(v.key AS INTEGER), v.value
+             FROM chapter_cache c, json_each(c.verses) v
+             WHERE c.module_id = ?1 AND c.book = ?2 AND v.type = 'text'",
+        )?;
+        for row in stmt.query_map([module_id, book], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))? {
+            add(row?);
+        }
+    }
+    Ok(chapters)
+}
+
+/// Ids of the marks `template_id` has made in `module_id`.
+fn marked(
+    conn: &Connection,
+    template_id: &str,
+    module_id: &str,
+) -> Result<HashSet<String>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM annotations
+         WHERE module_id = ?1 AND json_extract(data, '$.templateId') = ?2",
+    )?;
+    let ids = stmt
+        .query_map([module_id, template_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(ids)
+}
+
+/// Every occurrence of `template`'s words in `module_id`, and whether any
+/// book it marks had no text to search.
+fn search(
+    conn: &Connection,
+    template: &MarkingTemplate,
+    module_id: &str,
+) -> Result<(Vec<Occurrence>, bool), DbError> {
+    let mut found = Vec::new();
+    let mut missing = false;
+    for book in template.books() {
+        let chapters = verses(conn, module_id, &book)?;
+        let expected = books::position(&book).map_or(0, |at| books::KJV_VERSES[at - 1].len());
+        missing |= template.book.is_some() && chapters.len() < expected;
+        for (chapter, verses) in chapters {
+            for (verse, text) in verses {
+                let at = VerseRef {
+                    book: book.clone(),
+                    chapter,
+                    verse,
+                };
+                found.extend(template.occurrences(&at, &text));
+            }
+        }
+    }
+    Ok((found, missing))
+}
+
+pub(crate) fn preview(
+    conn: &Connection,
+    id: &str,
+    module_id: &str,
+) -> Result<TemplatePreview, DbError> {
+    let template = template(conn, id)?;
+    let (found, text_missing) = search(conn, &template, module_id)?;
+    let marked = marked(conn, id, module_id)?;
+    let mut preview = TemplatePreview {
+        occurrences: found.len(),
+        text_missing,
+        ..Default::default()
+    };
+    let mut verses = HashSet::new();
+    for occurrence in &found {
+        *preview
+            .chapters
+            .entry(occurrence.verse.chapter)
+            .or_default() += 1;
+        verses.insert(occurrence.verse.clone());
+        if marked.contains(&template.mark_id(module_id, occurrence)) {
+            preview.already_marked += 1;
+        }
+    }
+    preview.verses = verses.len();
+    Ok(preview)
+}
+
+/// The marks applying `id` to `module_id` would make that aren't made yet,
+/// anchored to their words when the translation is mounted.
+pub(crate) fn new_marks(
+    conn: &Connection,
+    id: &str,
+    module_id: &str,
+) -> Result<(Vec<Annotation>, usize), DbError> {
+    let template = template(conn, id)?;
+    let (found, _) = search(conn, &template, module_id)?;
+    let marked = marked(conn, id, module_id)?;
+    let schema = content::mounted(module_id).map(|m| m.schema);
+    let mut marks = Vec::new();
+    for occurrence in &found {
+        if marked.contains(&template.mark_id(module_id, occurrence)) {
+            continue;
+        }
+        let mut mark = template.mark(module_id, occurrence)?;
+        if let Some(schema) = &schema {
+            anchors::anchor(conn, schema, &mut mark)?;
+        }
+        marks.push(mark);
+    }
+    let already_marked = found.len() - marks.len();
+    Ok((marks, already_marked))
+}
+
+/// Save `marks` in one transaction; all or none.
+pub(crate) fn save_marks(conn: &mut Connection, marks: Vec<Annotation>) -> Result<usize, DbError> {
+    let tx = conn.transaction()?;
+    let mut created = 0;
+    for mark in marks {
+        let exists = tx
+            .query_row(
+                "SELECT 1 FROM annotations WHERE id = ?1",
+                [&mark.common().id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        // Made since it was read, by another apply or by sync.
+        if !exists {
+            annotations::create(&tx, mark)?;
+            created += 1;
+        }
+    }
+    tx.commit()?;
+    Ok(created)
+}
+
+/// Delete the marks `id` made, in `module_id` or every translation, in one
+/// transaction. Returns how many there were.
+pub(crate) fn remove_marks(
+    conn: &mut Connection,
+    id: &str,
+    module_id: Option<&str>,
+) -> Result<usize, DbError> {
+    let device = device_id(conn)?;
+    let tx = conn.transaction()?;
+    let now = now_iso(&tx)?;
+    let ids: Vec<String> = tx
+        .prepare(
+            "SELECT id FROM annotations WHERE json_extract(data, '$.templateId') = ?1
+             AND (?2 IS NULL OR module_id = ?2)",
+        )?
+        .query_map(params![id, module_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    for mark in &ids {
+        annotations::delete_one(&tx, mark, &now, &device)?;
+    }
+    tx.commit()?;
+    Ok(ids.len())
+}
+
+/// Every marking template by word.
+#[tauri::command]
+pub async fn get_marking_templates(app: tauri::AppHandle) -> Result<Vec<MarkingTemplate>, DbError> {
+    with_reader(&app, templates).await
+}
+
+#[tauri::command]
+pub async fn create_marking_template(
+    app: tauri::AppHandle,
+    template: MarkingTemplate,
+) -> Result<MarkingTemplate, DbError> {
+    with_connection(&app, move |conn| create(conn, template)).await
+}
+
+#[tauri::command]
+pub async fn update_marking_template(
+    app: tauri::AppHandle,
+    template: MarkingTemplate,
+) -> Result<MarkingTemplate, DbError> {
+    with_connection(&app, move |conn| update(conn, template)).await
+}
+
+/// Delete a template; its marks stay unless `with_marks`.
+#[tauri::command]
+pub async fn delete_marking_template(
+    app: tauri::AppHandle,
+    id: String,
+    with_marks: bool,
+) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| {
+        let since = undo::last_seq(conn)?;
+        let deleted = delete(conn, &id, with_marks)?;
+        undo::group_since(conn, since)?;
+        Ok(deleted)
+    })
+    .await
+}
+
+/// How many places applying a template to `module_id` would mark, without
+/// marking them.
+#[tauri::command]
+pub async fn preview_marking_template(
+    app: tauri::AppHandle,
+    id: String,
+    module_id: String,
+) -> Result<TemplatePreview, DbError> {
+    with_reader(&app, move |conn| preview(conn, &id, &module_id)).await
+}
+
+/// Mark every occurrence of a template's words in `module_id` not marked
+/// yet, in one transaction and one undo step.
+#[tauri::command]
+pub async fn apply_marking_template(
+    app: tauri::AppHandle,
+    id: String,
+    module_id: String,
+) -> Result<TemplateApplied, DbError> {
+    let (marks, already_marked) = {
+        let (id, module_id) = (id.clone(), module_id.clone());
+        with_reader(&app, move |conn| new_marks(conn, &id, &module_id)).await?
+    };
+    with_connection(&app, move |conn| {
+        let since = undo::last_seq(conn)?;
+        let created = save_marks(conn, marks)?;
+        undo::group_since(conn, since)?;
+        Ok(TemplateApplied {
+            created,
+            already_marked,
+        })
+    })
+    .await
+}
+
+/// Delete the marks a template made, in `module_id` or in every
+/// translation, as one undo step.
+#[tauri::command]
+pub async fn remove_template_markings(
+    app: tauri::AppHandle,
+    id: String,
+    module_id: Option<String>,
+) -> Result<usize, DbError> {
+    with_connection(&app, move |conn| {
+        let since = undo::last_seq(conn)?;
+        let removed = remove_marks(conn, &id, module_id.as_deref())?;
+        undo::group_since(conn, since)?;
+        Ok(removed)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrated_test_connection, DbErrorKind};
+
+    #[test]
+    fn finds_words_as_the_reader_does() {
+        let text = "“Covenant,” said he—the covenant’s sign; the New  Covenant.";
+        let found: Vec<_> = find(text, "covenant", false)
+            .into_iter()
+            .map(|(a, b, first, _)| (&text[a..b], first))
+            .collect();
+        assert_eq!(found, [("Covenant", 0), ("Covenant", 8)]);
+        assert_eq!(find(text, "new covenant", true).len(), 0);
+        let phrase = find(text, "New covenant.", false);
+        assert_eq!(phrase.len(), 1);
+        assert_eq!((phrase[0].2, phrase[0].3), (7, 8));
+
+        let template = MarkingTemplate {
+            id: "t".into(),
+            word: "covenant".into(),
+            variants: vec!["new covenant".into(), "covenant’s".into()],
+            ..Default::default()
+        };
+        let verse = VerseRef {
+            book: "Gen".into(),
+            chapter: 1,
+            verse: 1,
+        };
+        let spans: Vec<_> = template
+            .occurrences(&verse, text)
+            .into_iter()
+            .map(|o| (o.start, o.end, o.text))
+            .collect();
+        // The curly quote before the first one is one UTF-16 unit.
+        assert_eq!(
+            spans,
+            [
+                (1, 9, "Covenant".to_string()),
+                (24, 34, "covenant’s".to_string()),
+                (45, 58, "New  Covenant".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn previews_applies_and_removes_a_template_across_a_book() {
+        let mut conn = migrated_test_connection();
+        conn.execute_batch(
+            "INSERT INTO chapter_cache VALUES
+               ('esv:Gen:9', 'esv', 'Gen', 9,
+                '{\"9\":\"I establish my covenant with you\",\"12\":\"This is the sign of the covenant\"}', 'x'),
+               ('esv:Gen:17', 'esv', 'Gen', 17, '{\"2\":\"that I may make my covenant\"}', 'x'),
+               ('esv:Exod:2', 'esv', 'Exod', 2, '{\"24\":\"God remembered his covenant\"}', 'x');",
+        )
+        .unwrap();
+        let template = create(
+            &conn,
+            MarkingTemplate {
+                id: "cov".into(),
+                word: " covenant ".into(),
+                book: Some("Gen".into()),
+                kind: MarkKind::Underline,
+                color: "red".into(),
+                underline_style: Some(UnderlineStyle::Double),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(template.word, "covenant");
+
+        let before = preview(&conn, "cov", "esv").unwrap();
+        assert_eq!(
+            (before.occurrences, before.verses, before.already_marked),
+            (3, 3, 0)
+        );
+        assert_eq!(before.chapters, BTreeMap::from([(9, 2), (17, 1)]));
+        assert!(before.text_missing);
+
+        let (marks, _) = new_marks(&conn, "cov", "esv").unwrap();
+        assert_eq!(save_marks(&mut conn, marks).unwrap(), 3);
+        let stored: String = conn
+            .query_row(
+                "SELECT data FROM annotations WHERE id = 'tpl-cov-esv-Gen.9.12-24'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        let mark = Annotation::from_value(&serde_json::from_str(&stored).unwrap()).unwrap();
+        let Annotation::Underline(mark) = mark else {
+            panic!("{stored}");
+        };
+        assert_eq!(mark.words.selected_text.as_deref(), Some("covenant"));
+        assert_eq!(mark.underline_style, Some(UnderlineStyle::Double));
+        assert_eq!(mark.common.template_id.as_deref(), Some("cov"));
+
+        let (again, already) = new_marks(&conn, "cov", "esv").unwrap();
+        assert_eq!((again.len(), already), (0, 3));
+        assert_eq!(preview(&conn, "cov", "esv").unwrap().already_marked, 3);
+
+        assert!(delete(&mut conn, "cov", true).unwrap());
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM annotations", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(left, 0);
+        assert_eq!(
+            preview(&conn, "cov", "esv").unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+    }
+}
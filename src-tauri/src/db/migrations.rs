@@ -0,0 +1,369 @@
+//! Forward-only schema migrations, embedded as SQL files under `migrations/`.
+//!
+//! Versions 1–13 were written in TS (`migrateSchema` in sqlite-db.ts) and
+//! still create a fresh database. Everything newer lives here: the frontend
+//! calls `run_database_migrations` right after its baseline, and the version
+//! is tracked in the same `schema_version` row so both sides agree on it.
+//! Pending migrations run in a single transaction, so a failure leaves the
+//! database exactly at its previous version.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use super::{now_iso, with_connection, DbError, DbErrorKind};
+
+/// Last version created by the TS migrations; ours start after it.
+pub(crate) const BASELINE_VERSION: u32 = 13;
+
+pub(crate) struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// Every migration, in order. Never edit or reorder a shipped entry — add a
+/// new one instead.
+pub(crate) const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 14,
+        name: "annotation_location_index",
+        sql: include_str!("migrations/0014_annotation_location_index.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "fulltext_index",
+        sql: include_str!("migrations/0015_fulltext_index.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "trash",
+        sql: include_str!("migrations/0016_trash.sql"),
+    },
+    Migration {
+        version: 17,
+        name: "undo_journal",
+        sql: include_str!("migrations/0017_undo_journal.sql"),
+    },
+    Migration {
+        version: 18,
+        name: "audio_positions",
+        sql: include_str!("migrations/0018_audio_positions.sql"),
+    },
+    Migration {
+        version: 19,
+        name: "note_references",
+        sql: include_str!("migrations/0019_note_references.sql"),
+    },
+    Migration {
+        version: 20,
+        name: "symbol_library",
+        sql: include_str!("migrations/0020_symbol_library.sql"),
+    },
+    Migration {
+        version: 21,
+        name: "tags",
+        sql: include_str!("migrations/0021_tags.sql"),
+    },
+    Migration {
+        version: 22,
+        name: "note_links",
+        sql: include_str!("migrations/0022_note_links.sql"),
+    },
+    Migration {
+        version: 23,
+        name: "bookmarks",
+        sql: include_str!("migrations/0023_bookmarks.sql"),
+    },
+    Migration {
+        version: 24,
+        name: "verse_collections",
+        sql: include_str!("migrations/0024_verse_collections.sql"),
+    },
+    Migration {
+        version: 25,
+        name: "workspaces",
+        sql: include_str!("migrations/0025_workspaces.sql"),
+    },
+    Migration {
+        version: 26,
+        name: "highlight_styles",
+        sql: include_str!("migrations/0026_highlight_styles.sql"),
+    },
+    Migration {
+        version: 27,
+        name: "marking_templates",
+        sql: include_str!("migrations/0027_marking_templates.sql"),
+    },
+    Migration {
+        version: 28,
+        name: "margin_notes",
+        sql: include_str!("migrations/0028_margin_notes.sql"),
+    },
+    Migration {
+        version: 29,
+        name: "resource_links",
+        sql: include_str!("migrations/0029_resource_links.sql"),
+    },
+    Migration {
+        version: 30,
+        name: "tag_link_undo",
+        sql: include_str!("migrations/0030_tag_link_undo.sql"),
+    },
+    Migration {
+        version: 31,
+        name: "edit_history",
+        sql: include_str!("migrations/0031_edit_history.sql"),
+    },
+];
+
+pub(crate) fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(BASELINE_VERSION, |m| m.version)
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct MigrationStep {
+    pub version: u32,
+    pub name: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationReport {
+    #[serde(rename = "fromVersion")]
+    pub from_version: u32,
+    #[serde(rename = "toVersion")]
+    pub to_version: u32,
+    pub applied: Vec<MigrationStep>,
+    /// The migrations ran and were rolled back; nothing changed.
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+}
+
+pub(crate) fn current_version(conn: &Connection) -> Result<u32, DbError> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(0);
+    }
+    Ok(conn
+        .query_row(
+            "SELECT version FROM schema_version WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or(0))
+}
+
+/// Apply every migration newer than the database. With `dry_run` they run
+/// inside a transaction that is rolled back, which proves they would apply
+/// cleanly without touching the file.
+pub(crate) fn migrate(conn: &mut Connection, dry_run: bool) -> Result<MigrationReport, DbError> {
+    migrate_with(conn, MIGRATIONS, dry_run)
+}
+
+fn migrate_with(
+    conn: &mut Connection,
+    migrations: &[Migration],
+    dry_run: bool,
+) -> Result<MigrationReport, DbError> {
+    let from_version = current_version(conn)?;
+    let latest = migrations.last().map_or(BASELINE_VERSION, |m| m.version);
+    if from_version < BASELINE_VERSION {
+        return Err(DbError::new(
+            DbErrorKind::Migration,
+            format!(
+                "Database is at schema version {from_version}; the app's baseline schema (version {BASELINE_VERSION}) has not been applied yet"
+            ),
+        ));
+    }
+    if from_version > latest {
+        return Err(DbError::new(
+            DbErrorKind::SchemaTooNew,
+            format!(
+                "Database schema version {from_version} is newer than this app supports ({latest}); update BibleMarker"
+            ),
+        ));
+    }
+
+    let pending: Vec<&Migration> = migrations
+        .iter()
+        .filter(|m| m.version > from_version)
+        .collect();
+    let tx = conn.transaction()?;
+    for migration in &pending {
+        tx.execute_batch(migration.sql).map_err(|e| {
+            DbError::new(
+                DbErrorKind::Migration,
+                format!(
+                    "Migration {} ({}) failed: {e}",
+                    migration.version, migration.name
+                ),
+            )
+        })?;
+    }
+    let to_version = pending.last().map_or(from_version, |m| m.version);
+    if !pending.is_empty() {
+        let now = now_iso(&tx)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO schema_version (id, version, updated_at) VALUES (1, ?1, ?2)",
+            params![to_version, now],
+        )?;
+    }
+    if dry_run {
+        tx.rollback()?;
+    } else {
+        tx.commit()?;
+    }
+
+    Ok(MigrationReport {
+        from_version,
+        to_version,
+        applied: pending
+            .iter()
+            .map(|m| MigrationStep {
+                version: m.version,
+                name: m.name,
+            })
+            .collect(),
+        dry_run,
+    })
+}
+
+/// Bring the database up to the newest schema. Called by the frontend at
+/// startup, after its baseline migrations.
+#[tauri::command]
+pub async fn run_database_migrations(app: tauri::AppHandle) -> Result<MigrationReport, DbError> {
+    with_connection(&app, |conn| migrate(conn, false)).await
+}
+
+/// Report which migrations would run, executing them in a rolled-back
+/// transaction to catch failures ahead of time.
+#[tauri::command]
+pub async fn dry_run_database_migrations(
+    app: tauri::AppHandle,
+) -> Result<MigrationReport, DbError> {
+    with_connection(&app, |conn| migrate(conn, true)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    fn at_version(version: u32) -> Connection {
+        let conn = test_connection();
+        conn.execute_batch(
+            "CREATE TABLE schema_version (
+                id INTEGER PRIMARY KEY CHECK (id = 1), version INTEGER NOT NULL,
+                updated_at TEXT NOT NULL)",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO schema_version VALUES (1, ?1, '2025-01-01')",
+            [version],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn versions_are_strictly_increasing_after_baseline() {
+        let mut previous = BASELINE_VERSION;
+        for m in MIGRATIONS {
+            assert!(m.version > previous, "migration {} out of order", m.name);
+            previous = m.version;
+        }
+    }
+
+    #[test]
+    fn applies_pending_migrations_once() {
+        let mut conn = at_version(BASELINE_VERSION);
+        let report = migrate(&mut conn, false).unwrap();
+        assert_eq!(report.from_version, BASELINE_VERSION);
+        assert_eq!(report.to_version, latest_version());
+        assert_eq!(report.applied.len(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+        let again = migrate(&mut conn, false).unwrap();
+        assert!(again.applied.is_empty());
+    }
+
+    #[test]
+    fn dry_run_leaves_the_database_untouched() {
+        let mut conn = at_version(BASELINE_VERSION);
+        let report = migrate(&mut conn, true).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.applied.len(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), BASELINE_VERSION);
+    }
+
+    #[test]
+    fn failed_migration_rolls_back_and_names_the_step() {
+        let mut conn = at_version(BASELINE_VERSION);
+        let migrations = [
+            Migration {
+                version: 14,
+                name: "ok",
+                sql: "CREATE TABLE added (id TEXT)",
+            },
+            Migration {
+                version: 15,
+                name: "broken",
+                sql: "ALTER TABLE missing ADD COLUMN x TEXT",
+            },
+        ];
+        let err = migrate_with(&mut conn, &migrations, false).unwrap_err();
+        assert_eq!(err.kind, DbErrorKind::Migration);
+        assert!(err.message.contains("15 (broken)"));
+        assert_eq!(current_version(&conn).unwrap(), BASELINE_VERSION);
+        let added: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'added'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(added, 0);
+    }
+
+    #[test]
+    fn refuses_databases_outside_the_known_range() {
+        let mut newer = at_version(latest_version() + 1);
+        assert_eq!(
+            migrate(&mut newer, false).unwrap_err().kind,
+            DbErrorKind::SchemaTooNew
+        );
+        let mut older = at_version(5);
+        assert_eq!(
+            migrate(&mut older, false).unwrap_err().kind,
+            DbErrorKind::Migration
+        );
+    }
+
+    #[test]
+    fn chapter_query_uses_the_location_index() {
+        let mut conn = at_version(BASELINE_VERSION);
+        migrate(&mut conn, false).unwrap();
+        let plan: Vec<String> = conn
+            .prepare(
+                "EXPLAIN QUERY PLAN SELECT data FROM annotations
+                 WHERE module_id = 'kjv'
+                   AND (CASE type WHEN 'symbol' THEN json_extract(data, '$.ref.book')
+                                  ELSE json_extract(data, '$.startRef.book') END) = 'John'
+                   AND (CASE type WHEN 'symbol' THEN json_extract(data, '$.ref.chapter')
+                                  ELSE json_extract(data, '$.startRef.chapter') END) = 3",
+            )
+            .unwrap()
+            .query_map([], |r| r.get::<_, String>(3))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(
+            plan.iter().any(|p| p.contains("idx_annotations_location")),
+            "{plan:?}"
+        );
+    }
+}
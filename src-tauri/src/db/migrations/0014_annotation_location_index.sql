@@ -0,0 +1,10 @@
+-- Index the chapter an annotation sits in, so db_get_chapter_annotations
+-- doesn't scan every annotation of the translation. The expressions must stay
+-- identical to the WHERE clause in db/annotations.rs for SQLite to use it.
+CREATE INDEX IF NOT EXISTS idx_annotations_location ON annotations (
+    module_id,
+    (CASE type WHEN 'symbol' THEN json_extract(data, '$.ref.book')
+               ELSE json_extract(data, '$.startRef.book') END),
+    (CASE type WHEN 'symbol' THEN json_extract(data, '$.ref.chapter')
+               ELSE json_extract(data, '$.startRef.chapter') END)
+);
@@ -0,0 +1,137 @@
+-- Full-text index over cached verse text, notes and keyword presets.
+--
+-- search_docs says what each indexed document is; search_index holds its text
+-- under the same rowid. Triggers keep both in step with the source tables, so
+-- no caller has to remember to reindex. INSERT OR REPLACE doesn't fire DELETE
+-- triggers, which is why the insert triggers clear any old rows first.
+CREATE TABLE IF NOT EXISTS search_docs (
+    id INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL,          -- 'verse' | 'note' | 'keyword'
+    source_id TEXT NOT NULL,     -- chapter_cache / notes / marking_presets id
+    module_id TEXT,
+    book TEXT,
+    chapter INTEGER,
+    verse INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_search_docs_source ON search_docs(kind, source_id, verse);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    body,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- Verses: one document per verse of a cached chapter.
+CREATE TRIGGER IF NOT EXISTS search_chapter_cache_ai AFTER INSERT ON chapter_cache BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'verse' AND source_id = NEW.id);
+    DELETE FROM search_docs WHERE kind = 'verse' AND source_id = NEW.id;
+    INSERT INTO search_docs (kind, source_id, module_id, book, chapter, verse)
+        SELECT 'verse', NEW.id, NEW.module_id, NEW.book, NEW.chapter, CAST(key AS INTEGER)
+        FROM json_each(NEW.verses);
+    INSERT INTO search_index (rowid, body)
+        SELECT d.id, v.value FROM json_each(NEW.verses) v
+        JOIN search_docs d ON d.kind = 'verse' AND d.source_id = NEW.id
+            AND d.verse = CAST(v.key AS INTEGER);
+END;
+CREATE TRIGGER IF NOT EXISTS search_chapter_cache_au AFTER UPDATE OF id, verses ON chapter_cache BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'verse' AND source_id = OLD.id);
+    DELETE FROM search_docs WHERE kind = 'verse' AND source_id = OLD.id;
+    INSERT INTO search_docs (kind, source_id, module_id, book, chapter, verse)
+        SELECT 'verse', NEW.id, NEW.module_id, NEW.book, NEW.chapter, CAST(key AS INTEGER)
+        FROM json_each(NEW.verses);
+    INSERT INTO search_index (rowid, body)
+        SELECT d.id, v.value FROM json_each(NEW.verses) v
+        JOIN search_docs d ON d.kind = 'verse' AND d.source_id = NEW.id
+            AND d.verse = CAST(v.key AS INTEGER);
+END;
+CREATE TRIGGER IF NOT EXISTS search_chapter_cache_ad AFTER DELETE ON chapter_cache BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'verse' AND source_id = OLD.id);
+    DELETE FROM search_docs WHERE kind = 'verse' AND source_id = OLD.id;
+END;
+
+-- Notes: located at the note's reference.
+CREATE TRIGGER IF NOT EXISTS search_notes_ai AFTER INSERT ON notes BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'note' AND source_id = NEW.id);
+    DELETE FROM search_docs WHERE kind = 'note' AND source_id = NEW.id;
+    INSERT INTO search_docs (kind, source_id, module_id, book, chapter, verse)
+        VALUES ('note', NEW.id, NEW.module_id, json_extract(NEW.ref, '$.book'),
+                json_extract(NEW.ref, '$.chapter'), json_extract(NEW.ref, '$.verse'));
+    INSERT INTO search_index (rowid, body) VALUES (last_insert_rowid(), NEW.content);
+END;
+CREATE TRIGGER IF NOT EXISTS search_notes_au AFTER UPDATE OF id, ref, content ON notes BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'note' AND source_id = OLD.id);
+    DELETE FROM search_docs WHERE kind = 'note' AND source_id = OLD.id;
+    INSERT INTO search_docs (kind, source_id, module_id, book, chapter, verse)
+        VALUES ('note', NEW.id, NEW.module_id, json_extract(NEW.ref, '$.book'),
+                json_extract(NEW.ref, '$.chapter'), json_extract(NEW.ref, '$.verse'));
+    INSERT INTO search_index (rowid, body) VALUES (last_insert_rowid(), NEW.content);
+END;
+CREATE TRIGGER IF NOT EXISTS search_notes_ad AFTER DELETE ON notes BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'note' AND source_id = OLD.id);
+    DELETE FROM search_docs WHERE kind = 'note' AND source_id = OLD.id;
+END;
+
+-- Keywords: the word, its variants and description as one document.
+CREATE TRIGGER IF NOT EXISTS search_marking_presets_ai AFTER INSERT ON marking_presets BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'keyword' AND source_id = NEW.id);
+    DELETE FROM search_docs WHERE kind = 'keyword' AND source_id = NEW.id;
+    INSERT INTO search_docs (kind, source_id)
+        SELECT 'keyword', NEW.id WHERE NEW.word IS NOT NULL;
+    INSERT INTO search_index (rowid, body)
+        SELECT last_insert_rowid(), coalesce(NEW.word, '') || ' ' ||
+            coalesce((SELECT group_concat(json_extract(value, '$.text'), ' ')
+                      FROM json_each(NEW.variants)), '') || ' ' ||
+            coalesce(NEW.description, '')
+        WHERE NEW.word IS NOT NULL;
+END;
+CREATE TRIGGER IF NOT EXISTS search_marking_presets_au AFTER UPDATE OF id, word, variants, description ON marking_presets BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'keyword' AND source_id = OLD.id);
+    DELETE FROM search_docs WHERE kind = 'keyword' AND source_id = OLD.id;
+    INSERT INTO search_docs (kind, source_id)
+        SELECT 'keyword', NEW.id WHERE NEW.word IS NOT NULL;
+    INSERT INTO search_index (rowid, body)
+        SELECT last_insert_rowid(), coalesce(NEW.word, '') || ' ' ||
+            coalesce((SELECT group_concat(json_extract(value, '$.text'), ' ')
+                      FROM json_each(NEW.variants)), '') || ' ' ||
+            coalesce(NEW.description, '')
+        WHERE NEW.word IS NOT NULL;
+END;
+CREATE TRIGGER IF NOT EXISTS search_marking_presets_ad AFTER DELETE ON marking_presets BEGIN
+    DELETE FROM search_index WHERE rowid IN
+        (SELECT id FROM search_docs WHERE kind = 'keyword' AND source_id = OLD.id);
+    DELETE FROM search_docs WHERE kind = 'keyword' AND source_id = OLD.id;
+END;
+
+-- Index what is already there.
+INSERT INTO search_docs (kind, source_id, module_id, book, chapter, verse)
+    SELECT 'verse', c.id, c.module_id, c.book, c.chapter, CAST(v.key AS INTEGER)
+    FROM chapter_cache c, json_each(c.verses) v;
+INSERT INTO search_index (rowid, body)
+    SELECT d.id, v.value FROM chapter_cache c, json_each(c.verses) v
+    JOIN search_docs d ON d.kind = 'verse' AND d.source_id = c.id
+        AND d.verse = CAST(v.key AS INTEGER);
+
+INSERT INTO search_docs (kind, source_id, module_id, book, chapter, verse)
+    SELECT 'note', id, module_id, json_extract(ref, '$.book'),
+           json_extract(ref, '$.chapter'), json_extract(ref, '$.verse')
+    FROM notes;
+INSERT INTO search_index (rowid, body)
+    SELECT d.id, n.content FROM notes n
+    JOIN search_docs d ON d.kind = 'note' AND d.source_id = n.id;
+
+INSERT INTO search_docs (kind, source_id)
+    SELECT 'keyword', id FROM marking_presets WHERE word IS NOT NULL;
+INSERT INTO search_index (rowid, body)
+    SELECT d.id, coalesce(p.word, '') || ' ' ||
+            coalesce((SELECT group_concat(json_extract(value, '$.text'), ' ')
+                      FROM json_each(p.variants)), '') || ' ' ||
+            coalesce(p.description, '')
+    FROM marking_presets p
+    JOIN search_docs d ON d.kind = 'keyword' AND d.source_id = p.id;
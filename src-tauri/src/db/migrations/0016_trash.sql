@@ -0,0 +1,35 @@
+-- Deleted notes and annotations are copied here before the row goes, so a
+-- mistaken delete can be undone (db/trash.rs). The live tables keep their
+-- hard-delete semantics, which is what sync tombstones and every TS query
+-- expect; `row` holds the deleted row's columns as a JSON object.
+CREATE TABLE trash (
+    id INTEGER PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    row_id TEXT NOT NULL,
+    row TEXT NOT NULL,
+    deleted_at TEXT NOT NULL
+);
+CREATE INDEX idx_trash_deleted_at ON trash (deleted_at);
+CREATE INDEX idx_trash_row ON trash (table_name, row_id);
+
+-- INSERT OR REPLACE doesn't fire these (recursive_triggers is off), so only
+-- real deletes land in the trash, not saves.
+CREATE TRIGGER trash_notes_bd BEFORE DELETE ON notes BEGIN
+    INSERT INTO trash (table_name, row_id, row, deleted_at)
+    VALUES ('notes', old.id, json_object(
+        'id', old.id, 'module_id', old.module_id, 'ref', old.ref,
+        'range', old.range, 'content', old.content,
+        'created_at', old.created_at, 'updated_at', old.updated_at,
+        'sync_status', old.sync_status, 'device_id', old.device_id
+    ), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER trash_annotations_bd BEFORE DELETE ON annotations BEGIN
+    INSERT INTO trash (table_name, row_id, row, deleted_at)
+    VALUES ('annotations', old.id, json_object(
+        'id', old.id, 'module_id', old.module_id, 'type', old.type,
+        'data', old.data, 'preset_id', old.preset_id,
+        'created_at', old.created_at, 'updated_at', old.updated_at,
+        'sync_status', old.sync_status, 'device_id', old.device_id
+    ), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
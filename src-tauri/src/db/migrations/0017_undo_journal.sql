@@ -0,0 +1,134 @@
+-- Undo journal for annotation and note edits (db/undo.rs). Each local write
+-- records the row before and after it, as JSON objects of its columns (NULL
+-- when the row didn't exist), so undo writes `before` back and redo `after`.
+-- Entries with the same `at` are one step: 'now' is fixed for a whole
+-- statement, so a multi-row delete is undone together, and native batches
+-- stamp their entries to match (undo::group_since).
+CREATE TABLE undo_journal (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_name TEXT NOT NULL,
+    row_id TEXT NOT NULL,
+    before TEXT,
+    after TEXT,
+    at TEXT NOT NULL,
+    undone INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX idx_undo_journal_step ON undo_journal (undone, at);
+
+-- Holds a row only inside the transaction that replays an undo or redo, so
+-- the replay isn't journaled itself. Never committed with a row in it.
+CREATE TABLE undo_replay (active INTEGER PRIMARY KEY);
+
+-- The same, around a delete sync applies (undo::delete_synced): another
+-- device's delete isn't this one's to undo, and mustn't drop its redo.
+CREATE TABLE sync_apply (active INTEGER PRIMARY KEY);
+
+-- Saves are INSERT OR REPLACE, so a BEFORE INSERT trigger sees both new rows
+-- and the row about to be replaced. Rows written by sync as 'synced' are
+-- someone else's edit and stay off the stack, as do deletes sync applies. A
+-- new edit drops the redo entries, and only the last 500 entries are kept.
+CREATE TRIGGER undo_annotations_bi BEFORE INSERT ON annotations
+WHEN new.sync_status = 'pending' AND NOT EXISTS (SELECT 1 FROM undo_replay)
+BEGIN
+    DELETE FROM undo_journal WHERE undone = 1;
+    INSERT INTO undo_journal (table_name, row_id, before, after, at)
+    VALUES ('annotations', new.id,
+        (SELECT json_object(
+            'id', id, 'module_id', module_id, 'type', type, 'data', data,
+            'preset_id', preset_id, 'created_at', created_at,
+            'updated_at', updated_at, 'sync_status', sync_status,
+            'device_id', device_id
+        ) FROM annotations WHERE id = new.id),
+        json_object(
+            'id', new.id, 'module_id', new.module_id, 'type', new.type,
+            'data', new.data, 'preset_id', new.preset_id,
+            'created_at', new.created_at, 'updated_at', new.updated_at,
+            'sync_status', new.sync_status, 'device_id', new.device_id
+        ),
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM undo_journal WHERE seq <= (SELECT MAX(seq) FROM undo_journal) - 500;
+END;
+
+CREATE TRIGGER undo_annotations_bd BEFORE DELETE ON annotations
+WHEN NOT EXISTS (SELECT 1 FROM undo_replay) AND NOT EXISTS (SELECT 1 FROM sync_apply)
+BEGIN
+    DELETE FROM undo_journal WHERE undone = 1;
+    INSERT INTO undo_journal (table_name, row_id, before, after, at)
+    VALUES ('annotations', old.id,
+        json_object(
+            'id', old.id, 'module_id', old.module_id, 'type', old.type,
+            'data', old.data, 'preset_id', old.preset_id,
+            'created_at', old.created_at, 'updated_at', old.updated_at,
+            'sync_status', old.sync_status, 'device_id', old.device_id
+        ),
+        NULL,
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM undo_journal WHERE seq <= (SELECT MAX(seq) FROM undo_journal) - 500;
+END;
+
+CREATE TRIGGER undo_notes_bi BEFORE INSERT ON notes
+WHEN new.sync_status = 'pending' AND NOT EXISTS (SELECT 1 FROM undo_replay)
+BEGIN
+    DELETE FROM undo_journal WHERE undone = 1;
+    INSERT INTO undo_journal (table_name, row_id, before, after, at)
+    VALUES ('notes', new.id,
+        (SELECT json_object(
+            'id', id, 'module_id', module_id, 'ref', ref, 'range', range,
+            'content', content, 'created_at', created_at,
+            'updated_at', updated_at, 'sync_status', sync_status,
+            'device_id', device_id
+        ) FROM notes WHERE id = new.id),
+        json_object(
+            'id', new.id, 'module_id', new.module_id, 'ref', new.ref,
+            'range', new.range, 'content', new.content,
+            'created_at', new.created_at, 'updated_at', new.updated_at,
+            'sync_status', new.sync_status, 'device_id', new.device_id
+        ),
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM undo_journal WHERE seq <= (SELECT MAX(seq) FROM undo_journal) - 500;
+END;
+
+CREATE TRIGGER undo_notes_bd BEFORE DELETE ON notes
+WHEN NOT EXISTS (SELECT 1 FROM undo_replay) AND NOT EXISTS (SELECT 1 FROM sync_apply)
+BEGIN
+    DELETE FROM undo_journal WHERE undone = 1;
+    INSERT INTO undo_journal (table_name, row_id, before, after, at)
+    VALUES ('notes', old.id,
+        json_object(
+            'id', old.id, 'module_id', old.module_id, 'ref', old.ref,
+            'range', old.range, 'content', old.content,
+            'created_at', old.created_at, 'updated_at', old.updated_at,
+            'sync_status', old.sync_status, 'device_id', old.device_id
+        ),
+        NULL,
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM undo_journal WHERE seq <= (SELECT MAX(seq) FROM undo_journal) - 500;
+END;
+
+-- The trash of migration 16 now skips the same deletes: those an undo replay
+-- or sync makes were never this device's mistake to take back.
+DROP TRIGGER trash_notes_bd;
+CREATE TRIGGER trash_notes_bd BEFORE DELETE ON notes
+WHEN NOT EXISTS (SELECT 1 FROM undo_replay) AND NOT EXISTS (SELECT 1 FROM sync_apply)
+BEGIN
+    INSERT INTO trash (table_name, row_id, row, deleted_at)
+    VALUES ('notes', old.id, json_object(
+        'id', old.id, 'module_id', old.module_id, 'ref', old.ref,
+        'range', old.range, 'content', old.content,
+        'created_at', old.created_at, 'updated_at', old.updated_at,
+        'sync_status', old.sync_status, 'device_id', old.device_id
+    ), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+DROP TRIGGER trash_annotations_bd;
+CREATE TRIGGER trash_annotations_bd BEFORE DELETE ON annotations
+WHEN NOT EXISTS (SELECT 1 FROM undo_replay) AND NOT EXISTS (SELECT 1 FROM sync_apply)
+BEGIN
+    INSERT INTO trash (table_name, row_id, row, deleted_at)
+    VALUES ('annotations', old.id, json_object(
+        'id', old.id, 'module_id', old.module_id, 'type', old.type,
+        'data', old.data, 'preset_id', old.preset_id,
+        'created_at', old.created_at, 'updated_at', old.updated_at,
+        'sync_status', old.sync_status, 'device_id', old.device_id
+    ), strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
@@ -0,0 +1,10 @@
+-- Where the listener is in each audio Bible (content/audio.rs), keyed by the
+-- recording's id, so listening resumes on the account's other devices.
+CREATE TABLE audio_positions (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
@@ -0,0 +1,35 @@
+-- Verses each note's text refers to ("compare with Heb 11:6"), so the reader
+-- can show related notes on any passage (db/note_references.rs).
+--
+-- Reading references out of text needs the Rust parser, which triggers can't
+-- call, so the triggers only queue the notes that changed and the queue is
+-- worked through before the index is read. Derived from notes and rebuilt
+-- from them; not synced.
+CREATE TABLE note_references (
+    note_id TEXT NOT NULL,
+    book TEXT NOT NULL,
+    chapter INTEGER NOT NULL,
+    verse INTEGER NOT NULL,          -- first verse referred to
+    end_chapter INTEGER NOT NULL,
+    end_verse INTEGER NOT NULL,      -- last verse (a chapter's last, for whole chapters)
+    osis TEXT NOT NULL,
+    label TEXT NOT NULL,
+    PRIMARY KEY (note_id, osis)
+);
+CREATE INDEX idx_note_references_book ON note_references(book, chapter, end_chapter);
+
+CREATE TABLE note_reference_queue (note_id TEXT PRIMARY KEY);
+
+CREATE TRIGGER note_references_ai AFTER INSERT ON notes BEGIN
+    INSERT OR IGNORE INTO note_reference_queue VALUES (NEW.id);
+END;
+CREATE TRIGGER note_references_au AFTER UPDATE OF id, content ON notes BEGIN
+    INSERT OR IGNORE INTO note_reference_queue VALUES (OLD.id);
+    INSERT OR IGNORE INTO note_reference_queue VALUES (NEW.id);
+END;
+CREATE TRIGGER note_references_ad AFTER DELETE ON notes BEGIN
+    INSERT OR IGNORE INTO note_reference_queue VALUES (OLD.id);
+END;
+
+-- Existing notes are read the first time the index is used.
+INSERT INTO note_reference_queue SELECT id FROM notes;
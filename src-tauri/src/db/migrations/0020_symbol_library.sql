@@ -0,0 +1,10 @@
+-- The user's own symbol vocabulary for inductive study (db/symbol_library.rs):
+-- a shape and color with what it stands for, e.g. a purple triangle for God.
+CREATE TABLE symbol_library (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
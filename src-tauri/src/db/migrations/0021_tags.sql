@@ -0,0 +1,25 @@
+-- Hierarchical tags on annotations, notes and observation lists
+-- (db/tags.rs): `tags` holds each tag's name, parent and color, and
+-- `tag_links` one row per tag on an item. A link's id is made of the tag,
+-- item type and item id, so two devices tagging the same item agree on one
+-- row.
+CREATE TABLE tags (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
+
+CREATE TABLE tag_links (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
+CREATE INDEX idx_tag_links_tag ON tag_links (json_extract(data, '$.tagId'));
+CREATE INDEX idx_tag_links_item
+    ON tag_links (json_extract(data, '$.itemType'), json_extract(data, '$.itemId'));
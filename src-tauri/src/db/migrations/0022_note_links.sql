@@ -0,0 +1,18 @@
+-- Wiki-style links between notes ("see [[Covenant]]"), so a note can list
+-- the notes that link to it (db/backlinks.rs). `target` is the link as
+-- written with case and spacing folded; a note is linked by its title (its
+-- first heading) or its id. Links that name a passage ("[[Heb 11:6]]") go
+-- to `note_references` instead.
+--
+-- Filled from the same queue as `note_references`; derived from notes and
+-- rebuilt from them, not synced.
+CREATE TABLE note_links (
+    note_id TEXT NOT NULL,
+    target TEXT NOT NULL,
+    label TEXT NOT NULL,             -- the link as first written in the note
+    PRIMARY KEY (note_id, target)
+);
+CREATE INDEX idx_note_links_target ON note_links(target);
+
+-- Read every note again so existing notes' links are indexed.
+INSERT OR IGNORE INTO note_reference_queue SELECT id FROM notes;
@@ -0,0 +1,21 @@
+-- Bookmarks: passages marked for coming back to, filed in folders
+-- (db/bookmarks.rs), apart from highlights so a reading place needn't be a
+-- special highlight color. A bookmark's folder is its `folderId`.
+CREATE TABLE bookmark_folders (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
+
+CREATE TABLE bookmarks (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
+CREATE INDEX idx_bookmarks_folder ON bookmarks (json_extract(data, '$.folderId'));
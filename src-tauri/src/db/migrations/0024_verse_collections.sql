@@ -0,0 +1,11 @@
+-- Named verse collections ("Promises", "Memory Verses 2025"): ordered
+-- passages, each with an optional note (db/collections.rs). One row per
+-- collection with its entries inline, so a reorder is one write.
+CREATE TABLE verse_collections (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
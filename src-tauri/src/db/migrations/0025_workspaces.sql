@@ -0,0 +1,10 @@
+-- Topical study workspaces (db/workspaces.rs): a topic and the tags, verse
+-- collections, notes and key words that belong to it, by id.
+CREATE TABLE workspaces (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
@@ -0,0 +1,50 @@
+-- Highlight styles (db/highlight_styles.rs): named looks combining a fill,
+-- text color, underline, border and symbol overlay, which text annotations
+-- point at by `styleId`.
+CREATE TABLE highlight_styles (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
+
+-- Each color-only look already in use becomes a style. The id is derived
+-- from the look, so every device migrating its own copy ends up with the
+-- same rows, and they are stamped at the epoch so an edit made to one on
+-- any device wins over them. Not logged for sync for the same reason.
+INSERT OR IGNORE INTO highlight_styles (id, data, created_at, updated_at, sync_status)
+SELECT
+    'legacy-' || type || '-' || color || iif(type = 'underline', '-' || line, ''),
+    -- Patched onto an empty object, which drops the parts that are null.
+    json_patch('{}', json_object(
+        'id', 'legacy-' || type || '-' || color || iif(type = 'underline', '-' || line, ''),
+        'name', upper(substr(color, 1, 1)) || substr(color, 2) || ' ' || CASE type
+            WHEN 'highlight' THEN 'highlight'
+            WHEN 'textColor' THEN 'text'
+            ELSE iif(line = 'solid', '', line || ' ') || 'underline'
+        END,
+        'fill', iif(type = 'highlight', color, NULL),
+        'textColor', iif(type = 'textColor', color, NULL),
+        'underline', json(iif(type = 'underline', json_object('color', color, 'style', line), NULL)),
+        'createdAt', '1970-01-01T00:00:00.000Z',
+        'updatedAt', '1970-01-01T00:00:00.000Z'
+    )),
+    '1970-01-01T00:00:00.000Z', '1970-01-01T00:00:00.000Z', 'synced'
+FROM (
+    SELECT DISTINCT type, json_extract(data, '$.color') AS color,
+        iif(type = 'underline', coalesce(json_extract(data, '$.underlineStyle'), 'solid'), NULL) AS line
+    FROM annotations
+    WHERE type IN ('highlight', 'textColor', 'underline')
+      AND json_extract(data, '$.color') IS NOT NULL
+);
+
+-- Point the existing marks at them. Their own type and color stay, for apps
+-- that don't know styles.
+UPDATE annotations SET data = json_set(data, '$.styleId',
+    'legacy-' || type || '-' || json_extract(data, '$.color')
+    || iif(type = 'underline', '-' || coalesce(json_extract(data, '$.underlineStyle'), 'solid'), ''))
+WHERE type IN ('highlight', 'textColor', 'underline')
+  AND json_extract(data, '$.color') IS NOT NULL
+  AND json_extract(data, '$.styleId') IS NULL;
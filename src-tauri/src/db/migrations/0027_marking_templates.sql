@@ -0,0 +1,11 @@
+-- Marking templates (db/marking_templates.rs): a word or phrase and the
+-- look to mark it with ("covenant" in a red box), applied to every
+-- occurrence in a book at once.
+CREATE TABLE marking_templates (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
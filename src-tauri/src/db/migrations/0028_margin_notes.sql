@@ -0,0 +1,11 @@
+-- Margin notes (db/margin_notes.rs): a note on a chapter, or on a pericope
+-- within it, rather than on a verse, placed in the chapter's margin the way
+-- a chapter's theme is written beside it in a paper Bible.
+CREATE TABLE margin_notes (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
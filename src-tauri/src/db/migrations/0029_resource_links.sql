@@ -0,0 +1,11 @@
+-- Resource links (db/resource_links.rs): a note or annotation's link to
+-- something outside the app (a web article, a Logos resource, a local file),
+-- with the title and description fetched for it.
+CREATE TABLE resource_links (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    sync_status TEXT DEFAULT 'pending',
+    device_id TEXT
+);
@@ -0,0 +1,41 @@
+-- Tag links join the undo journal of migration 17 (db/undo.rs), so moving
+-- marks and notes from one tag to another (db/bulk_edit.rs), like tagging
+-- one item, is undone with the edits made beside it. Same rules as the
+-- annotation and note triggers: only local ('pending') writes outside a
+-- replay are journaled, never deletes sync applies, and the last 500 entries
+-- kept.
+CREATE TRIGGER undo_tag_links_bi BEFORE INSERT ON tag_links
+WHEN new.sync_status = 'pending' AND NOT EXISTS (SELECT 1 FROM undo_replay)
+BEGIN
+    DELETE FROM undo_journal WHERE undone = 1;
+    INSERT INTO undo_journal (table_name, row_id, before, after, at)
+    VALUES ('tag_links', new.id,
+        (SELECT json_object(
+            'id', id, 'data', data, 'created_at', created_at,
+            'updated_at', updated_at, 'sync_status', sync_status,
+            'device_id', device_id
+        ) FROM tag_links WHERE id = new.id),
+        json_object(
+            'id', new.id, 'data', new.data, 'created_at', new.created_at,
+            'updated_at', new.updated_at, 'sync_status', new.sync_status,
+            'device_id', new.device_id
+        ),
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM undo_journal WHERE seq <= (SELECT MAX(seq) FROM undo_journal) - 500;
+END;
+
+CREATE TRIGGER undo_tag_links_bd BEFORE DELETE ON tag_links
+WHEN NOT EXISTS (SELECT 1 FROM undo_replay) AND NOT EXISTS (SELECT 1 FROM sync_apply)
+BEGIN
+    DELETE FROM undo_journal WHERE undone = 1;
+    INSERT INTO undo_journal (table_name, row_id, before, after, at)
+    VALUES ('tag_links', old.id,
+        json_object(
+            'id', old.id, 'data', old.data, 'created_at', old.created_at,
+            'updated_at', old.updated_at, 'sync_status', old.sync_status,
+            'device_id', old.device_id
+        ),
+        NULL,
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM undo_journal WHERE seq <= (SELECT MAX(seq) FROM undo_journal) - 500;
+END;
@@ -0,0 +1,83 @@
+-- Edit history of each annotation and note (db/edit_history.rs): every
+-- version a row has had, appended from triggers so local saves, native
+-- batches, undo and rows written by sync are all recorded. `row` holds the
+-- row's columns after the write as a JSON object, NULL for a delete;
+-- `sync_status` and `device_id` are the row's own, so a version from
+-- another device shows as such. Each row keeps its last 50 versions.
+CREATE TABLE edit_history (
+    id INTEGER PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    row_id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    row TEXT,
+    updated_at TEXT,
+    sync_status TEXT,
+    device_id TEXT,
+    at TEXT NOT NULL
+);
+CREATE UNIQUE INDEX idx_edit_history_row ON edit_history (table_name, row_id, version);
+CREATE INDEX idx_edit_history_at ON edit_history (at);
+
+-- Saves are INSERT OR REPLACE; with recursive_triggers off the replaced row
+-- fires no delete trigger, so a save is one version.
+CREATE TRIGGER edit_history_annotations_ai AFTER INSERT ON annotations
+BEGIN
+    INSERT INTO edit_history
+        (table_name, row_id, version, row, updated_at, sync_status, device_id, at)
+    VALUES ('annotations', new.id,
+        COALESCE((SELECT MAX(version) FROM edit_history
+                  WHERE table_name = 'annotations' AND row_id = new.id), 0) + 1,
+        json_object(
+            'id', new.id, 'module_id', new.module_id, 'type', new.type,
+            'data', new.data, 'preset_id', new.preset_id,
+            'created_at', new.created_at, 'updated_at', new.updated_at,
+            'sync_status', new.sync_status, 'device_id', new.device_id
+        ),
+        new.updated_at, new.sync_status, new.device_id,
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM edit_history WHERE table_name = 'annotations' AND row_id = new.id
+        AND version <= (SELECT MAX(version) FROM edit_history
+                        WHERE table_name = 'annotations' AND row_id = new.id) - 50;
+END;
+
+CREATE TRIGGER edit_history_annotations_bd BEFORE DELETE ON annotations
+BEGIN
+    INSERT INTO edit_history
+        (table_name, row_id, version, row, updated_at, sync_status, device_id, at)
+    VALUES ('annotations', old.id,
+        COALESCE((SELECT MAX(version) FROM edit_history
+                  WHERE table_name = 'annotations' AND row_id = old.id), 0) + 1,
+        NULL, NULL, NULL, NULL,
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
+
+CREATE TRIGGER edit_history_notes_ai AFTER INSERT ON notes
+BEGIN
+    INSERT INTO edit_history
+        (table_name, row_id, version, row, updated_at, sync_status, device_id, at)
+    VALUES ('notes', new.id,
+        COALESCE((SELECT MAX(version) FROM edit_history
+                  WHERE table_name = 'notes' AND row_id = new.id), 0) + 1,
+        json_object(
+            'id', new.id, 'module_id', new.module_id, 'ref', new.ref,
+            'range', new.range, 'content', new.content,
+            'created_at', new.created_at, 'updated_at', new.updated_at,
+            'sync_status', new.sync_status, 'device_id', new.device_id
+        ),
+        new.updated_at, new.sync_status, new.device_id,
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    DELETE FROM edit_history WHERE table_name = 'notes' AND row_id = new.id
+        AND version <= (SELECT MAX(version) FROM edit_history
+                        WHERE table_name = 'notes' AND row_id = new.id) - 50;
+END;
+
+CREATE TRIGGER edit_history_notes_bd BEFORE DELETE ON notes
+BEGIN
+    INSERT INTO edit_history
+        (table_name, row_id, version, row, updated_at, sync_status, device_id, at)
+    VALUES ('notes', old.id,
+        COALESCE((SELECT MAX(version) FROM edit_history
+                  WHERE table_name = 'notes' AND row_id = old.id), 0) + 1,
+        NULL, NULL, NULL, NULL,
+        strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+END;
@@ -0,0 +1,218 @@
+//! Native access to the user database for operations too heavy for
+//! tauri-plugin-sql string queries from JS.
+//!
+//! The TS layer still owns the baseline schema (migrations in `sqlite-db.ts`)
+//! and most reads and writes. This module keeps its own rusqlite connections
+//! to the same `biblemarker.db` (see `connections`), with a busy timeout so
+//! they wait out the plugin's writes instead of failing. Every write here also
+//! records `change_log` rows exactly like `recordChange`, so the sync engine
+//! can't tell which side made it.
+
+pub mod anchors;
+pub mod annotations;
+pub mod attachments;
+pub mod backlinks;
+pub mod bookmarks;
+pub mod bulk_edit;
+pub mod collections;
+pub mod connections;
+pub mod data_rows;
+pub mod demo;
+pub mod dump;
+pub mod edit_history;
+mod error;
+pub mod highlight_styles;
+pub mod margin_notes;
+pub mod marking_stats;
+pub mod marking_templates;
+pub mod migrations;
+pub mod note_references;
+pub mod observation_lists;
+pub mod propagation;
+pub mod resource_links;
+pub mod search;
+pub mod snapshots;
+pub mod symbol_library;
+pub mod tags;
+pub mod trash;
+pub mod undo;
+pub mod workspaces;
+pub mod write_lock;
+
+pub use error::{DbError, DbErrorKind};
+
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+/// File name of the user database in the app data dir (see `getSqliteDb`).
+pub(crate) const DB_FILE: &str = "biblemarker.db";
+
+/// The user database, or the throwaway one in demo mode (see `demo`).
+pub(crate) fn db_path(app: &tauri::AppHandle) -> Result<PathBuf, DbError> {
+    if let Some(dir) = demo::dir() {
+        return Ok(dir.join(DB_FILE));
+    }
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| DbError::io(format!("Cannot determine app data dir: {e}")))?;
+    Ok(dir.join(DB_FILE))
+}
+
+pub(crate) fn open(path: &Path) -> Result<Connection, DbError> {
+    let conn = Connection::open(path)?;
+    configure(&conn)?;
+    Ok(conn)
+}
+
+/// Connection settings shared by every native connection. WAL is what
+/// tauri-plugin-sql already uses; asking for it again is a no-op then, and
+/// keeps a database created from Rust in the same mode.
+pub(crate) fn configure(conn: &Connection) -> Result<(), DbError> {
+    conn.busy_timeout(connections::busy_timeout())?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    Ok(())
+}
+
+/// Run `f` on the single writer connection (see `connections`), off the
+/// async runtime.
+pub(crate) async fn with_connection<T, F>(app: &tauri::AppHandle, f: F) -> Result<T, DbError>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<T, DbError> + Send + 'static,
+{
+    let path = db_path(app)?;
+    tauri::async_runtime::spawn_blocking(move || connections::manager(&path).write(f))
+        .await
+        .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Database task failed: {e}")))?
+}
+
+/// `with_connection`, for writing a change sync brought in: it goes through
+/// while the sync engine has writes paused (see `write_lock`).
+pub(crate) async fn with_sync_connection<T, F>(app: &tauri::AppHandle, f: F) -> Result<T, DbError>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<T, DbError> + Send + 'static,
+{
+    let path = db_path(app)?;
+    tauri::async_runtime::spawn_blocking(move || connections::manager(&path).write_synced(f))
+        .await
+        .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Database task failed: {e}")))?
+}
+
+/// Run `f` on a pooled read-only connection, off the async runtime.
+pub(crate) async fn with_reader<T, F>(app: &tauri::AppHandle, f: F) -> Result<T, DbError>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> Result<T, DbError> + Send + 'static,
+{
+    let path = db_path(app)?;
+    tauri::async_runtime::spawn_blocking(move || connections::manager(&path).read(f))
+        .await
+        .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Database task failed: {e}")))?
+}
+
+/// The device id the TS layer generated (`initDeviceId` in sqlite-db.ts).
+pub(crate) fn device_id(conn: &Connection) -> Result<String, DbError> {
+    crate::sync::merge::local_device_id(conn)?
+        .ok_or_else(|| DbError::invalid("Local database has not been initialized yet"))
+}
+
+/// Current time in the `Date.toISOString()` format the TS layer stores.
+pub(crate) fn now_iso(conn: &Connection) -> Result<String, DbError> {
+    Ok(
+        conn.query_row("SELECT strftime('%Y-%m-%dT%H:%M:%fZ', 'now')", [], |row| {
+            row.get(0)
+        })?,
+    )
+}
+
+/// Mirror of `recordChange` (sqlite-db.ts): log the write for the next journal
+/// flush and keep `sync_tombstones` in step.
+pub(crate) fn record_change(
+    conn: &Connection,
+    table: &str,
+    op: &str,
+    row_id: &str,
+    data: Option<&str>,
+    updated_at: &str,
+    device_id: &str,
+) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT INTO change_log (table_name, op, row_id, data, updated_at, device_id, flushed)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
+        params![table, op, row_id, data, updated_at, device_id],
+    )?;
+    if op == "delete" {
+        conn.execute(
+            "INSERT INTO sync_tombstones (table_name, row_id, deleted_at, device_id)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (table_name, row_id) DO UPDATE SET
+               deleted_at = excluded.deleted_at, device_id = excluded.device_id
+             WHERE excluded.deleted_at > sync_tombstones.deleted_at",
+            params![table, row_id, updated_at, device_id],
+        )?;
+    } else {
+        conn.execute(
+            "DELETE FROM sync_tombstones WHERE table_name = ?1 AND row_id = ?2",
+            params![table, row_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Minimal copy of the TS schema for the tables this module touches.
+#[cfg(test)]
+pub(crate) fn test_connection() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE annotations (
+            id TEXT PRIMARY KEY, module_id TEXT NOT NULL, type TEXT NOT NULL,
+            data TEXT NOT NULL, preset_id TEXT, created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL, sync_status TEXT DEFAULT 'pending', device_id TEXT);
+         CREATE TABLE change_log (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT, table_name TEXT NOT NULL,
+            op TEXT NOT NULL, row_id TEXT NOT NULL, data TEXT,
+            updated_at TEXT NOT NULL, device_id TEXT NOT NULL,
+            flushed INTEGER NOT NULL DEFAULT 0);
+         CREATE TABLE sync_tombstones (
+            table_name TEXT NOT NULL, row_id TEXT NOT NULL,
+            deleted_at TEXT NOT NULL, device_id TEXT NOT NULL,
+            PRIMARY KEY (table_name, row_id));
+         CREATE TABLE notes (
+            id TEXT PRIMARY KEY, module_id TEXT NOT NULL, ref TEXT NOT NULL, range TEXT,
+            content TEXT NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL,
+            sync_status TEXT DEFAULT 'pending', device_id TEXT);
+         CREATE TABLE marking_presets (
+            id TEXT PRIMARY KEY, word TEXT, variants TEXT NOT NULL, symbol TEXT,
+            highlight TEXT, category TEXT, description TEXT,
+            auto_suggest INTEGER NOT NULL DEFAULT 1, usage_count INTEGER NOT NULL DEFAULT 0,
+            book_scope TEXT, chapter_scope INTEGER, scopes TEXT, module_scope TEXT,
+            study_id TEXT, created_at TEXT NOT NULL, updated_at TEXT NOT NULL,
+            sync_status TEXT DEFAULT 'pending', device_id TEXT);
+         CREATE TABLE chapter_cache (
+            id TEXT PRIMARY KEY, module_id TEXT NOT NULL, book TEXT NOT NULL,
+            chapter INTEGER NOT NULL, verses TEXT NOT NULL, cached_at TEXT NOT NULL);
+         CREATE TABLE sync_config (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+         INSERT INTO sync_config VALUES ('device_id', 'dev-local');",
+    )
+    .unwrap();
+    conn
+}
+
+/// `test_connection` at the baseline version with every native migration
+/// applied.
+#[cfg(test)]
+pub(crate) fn migrated_test_connection() -> Connection {
+    let mut conn = test_connection();
+    conn.execute_batch(
+        "CREATE TABLE schema_version (
+            id INTEGER PRIMARY KEY CHECK (id = 1), version INTEGER NOT NULL,
+            updated_at TEXT NOT NULL);
+         INSERT INTO schema_version VALUES (1, 13, '2025-01-01');",
+    )
+    .unwrap();
+    migrations::migrate(&mut conn, false).unwrap();
+    conn
+}
@@ -0,0 +1,190 @@
+//! Verses notes refer to in their text ("compare with Heb 11:6"), indexed by
+//! migration 19 so the reader can surface related notes on any passage.
+//!
+//! Notes are written from TS and by sync, so the index can't be kept on
+//! write: triggers queue each note that changes, and `refresh` reads the
+//! queued notes' references (`content::references::find`) before the index
+//! is asked. While sync holds writes the index answers as it stands. The
+//! same pass indexes the notes' wiki links (`backlinks`).
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::{backlinks, with_connection, with_reader, DbError, DbErrorKind};
+use crate::content::books;
+use crate::content::references;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ReferencingNote {
+    #[serde(rename = "noteId")]
+    pub note_id: String,
+    #[serde(rename = "moduleId")]
+    pub module_id: String,
+    /// Where the note itself is attached (its `VerseRef`).
+    #[serde(rename = "noteRef")]
+    pub note_ref: serde_json::Value,
+    pub content: String,
+    /// How the note's text writes each reference touching the passage, e.g.
+    /// `Hebrews 11:6`.
+    pub mentions: Vec<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
+/// Index the queued notes' references, returning how many notes were read.
+pub(crate) fn refresh(conn: &mut Connection) -> Result<usize, DbError> {
+    let tx = conn.transaction()?;
+    let queued: Vec<(String, Option<String>)> = tx
+        .prepare(
+            "SELECT q.note_id, n.content FROM note_reference_queue q
+             LEFT JOIN notes n ON n.id = q.note_id",
+        )?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (note_id, content) in &queued {
+        tx.execute("DELETE FROM note_references WHERE note_id = ?1", [note_id])?;
+        let mut cited = references::find(content.as_deref().unwrap_or_default());
+        cited.extend(backlinks::index(&tx, note_id, content.as_deref())?);
+        for found in cited {
+            let index = books::position(&found.book).unwrap_or(1) - 1;
+            let last_verse = books::KJV_VERSES[index]
+                .get(found.end_chapter as usize - 1)
+                .copied()
+                .unwrap_or_default();
+            tx.execute(
+                "INSERT OR IGNORE INTO note_references
+                 (note_id, book, chapter, verse, end_chapter, end_verse, osis, label)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    note_id,
+                    found.book,
+                    found.chapter,
+                    found.verse.unwrap_or(1),
+                    found.end_chapter,
+                    found.end_verse.unwrap_or(i64::from(last_verse)),
+                    found.osis,
+                    found.label,
+                ],
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM note_reference_queue WHERE note_id = ?1",
+            [note_id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(queued.len())
+}
+
+/// Work through the queue, unless sync holds writes, before the indexes
+/// are read.
+pub(crate) async fn catch_up(app: &tauri::AppHandle) -> Result<(), DbError> {
+    match with_connection(app, refresh).await {
+        Err(e) if e.kind == DbErrorKind::DatabaseLocked => Ok(()),
+        result => result.map(|_| ()),
+    }
+}
+
+/// Notes whose text refers to `book` `chapter` from `verse` to `end_verse`
+/// (the whole chapter without them), most recently edited first.
+pub(crate) fn referencing(
+    conn: &Connection,
+    book: &str,
+    chapter: i64,
+    verse: Option<i64>,
+    end_verse: Option<i64>,
+) -> Result<Vec<ReferencingNote>, DbError> {
+    let first = verse.unwrap_or(1);
+    let last = end_verse.or(verse).unwrap_or(i64::MAX);
+    let mut stmt = conn.prepare(
+        "SELECT n.id, n.module_id, n.ref, n.content, n.updated_at, group_concat(r.label, char(31))
+         FROM note_references r JOIN notes n ON n.id = r.note_id
+         WHERE r.book = ?1 AND (r.chapter, r.verse) <= (?2, ?4)
+           AND (r.end_chapter, r.end_verse) >= (?2, ?3)
+         GROUP BY n.id
+         ORDER BY n.updated_at DESC, n.id",
+    )?;
+    let notes = stmt
+        .query_map(params![book, chapter, first, last], |row| {
+            let note_ref: String = row.get(2)?;
+            let mentions: String = row.get(5)?;
+            Ok(ReferencingNote {
+                note_id: row.get(0)?,
+                module_id: row.get(1)?,
+                note_ref: serde_json::from_str(&note_ref).unwrap_or_default(),
+                content: row.get(3)?,
+                mentions: mentions.split('\u{1f}').map(str::to_string).collect(),
+                updated_at: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(notes)
+}
+
+/// Notes that mention `book` `chapter` (from `verse` to `end_verse`, or the
+/// whole chapter) anywhere in their text, for showing related notes beside
+/// a passage.
+#[tauri::command]
+pub async fn get_notes_referencing(
+    app: tauri::AppHandle,
+    book: String,
+    chapter: i64,
+    verse: Option<i64>,
+    end_verse: Option<i64>,
+) -> Result<Vec<ReferencingNote>, DbError> {
+    catch_up(&app).await?;
+    with_reader(&app, move |conn| {
+        referencing(conn, &book, chapter, verse, end_verse)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+
+    fn note(conn: &Connection, id: &str, content: &str) {
+        conn.execute(
+            "INSERT OR REPLACE INTO notes (id, module_id, ref, content, created_at, updated_at)
+             VALUES (?1, 'kjv', '{\"book\":\"John\",\"chapter\":3,\"verse\":16}', ?2,
+                     '2025-01-01', ?1)",
+            params![id, content],
+        )
+        .unwrap();
+    }
+
+    fn ids(conn: &Connection, chapter: i64, verse: Option<i64>) -> Vec<String> {
+        referencing(conn, "Heb", chapter, verse, None)
+            .unwrap()
+            .into_iter()
+            .map(|n| n.note_id)
+            .collect()
+    }
+
+    #[test]
+    fn indexes_note_references_as_notes_change() {
+        let mut conn = migrated_test_connection();
+        note(&conn, "n1", "Faith pleases God: compare with Heb 11:6.");
+        note(&conn, "n2", "All of Heb 11, and Heb 12:1-2 after it.");
+        assert_eq!(refresh(&mut conn).unwrap(), 2);
+        assert_eq!(refresh(&mut conn).unwrap(), 0);
+
+        assert_eq!(ids(&conn, 11, Some(6)), ["n2", "n1"]);
+        assert_eq!(ids(&conn, 11, Some(7)), ["n2"]);
+        assert_eq!(ids(&conn, 12, None), ["n2"]);
+        let found = referencing(&conn, "Heb", 11, Some(6), None).unwrap();
+        assert_eq!(found[1].mentions, ["Hebrews 11:6"]);
+        assert_eq!(found[1].note_ref["chapter"], 3);
+
+        note(&conn, "n1", "Nothing to see here.");
+        conn.execute("DELETE FROM notes WHERE id = 'n2'", [])
+            .unwrap();
+        refresh(&mut conn).unwrap();
+        assert!(ids(&conn, 11, Some(6)).is_empty());
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM note_references", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rows, 0);
+    }
+}
@@ -0,0 +1,596 @@
+//! Observation lists generated from markings: everything the text says
+//! about a key word, gathered from the marks made on it instead of copied
+//! out by hand. Lists are the TS layer's generic `observation_lists` rows;
+//! a generated one is an ordinary list, shown, exported and synced like any
+//! other, that also records in `generatedFrom` what it was made from.
+//!
+//! Each item is one mark's fragment of its verse (the words it covers, or
+//! the verse when it covers none), in canonical order, and keeps its mark's
+//! `annotationId`. Refreshing a list adds items for marks made since and
+//! drops those whose mark is gone, keeping what was written on the rest and
+//! every item added by hand.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+use super::annotations::{Annotation, VerseRef};
+use super::tags::{self, ItemType};
+use super::{device_id, now_iso, record_change, with_connection, DbError, DbErrorKind};
+use crate::content::{self, books};
+
+/// The marks a list is made from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum ListSource {
+    /// Marks made with a key word (`MarkingPreset`).
+    #[serde(rename = "keyWord")]
+    KeyWord {
+        #[serde(rename = "presetId")]
+        preset_id: String,
+    },
+    /// Marks tagged with a tag (an id or path) or any tag under it.
+    #[serde(rename = "tag")]
+    Tag { tag: String },
+    /// Marks drawn with a `HighlightStyle`.
+    #[serde(rename = "style")]
+    Style {
+        #[serde(rename = "styleId")]
+        style_id: String,
+    },
+}
+
+/// Where a list's marks may be, as the TS `ObservationScope`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ListScope {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub book: Option<String>,
+    /// None for the whole book.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<i64>,
+}
+
+/// What a generated list was made from, kept on it to refresh it by.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Generator {
+    pub source: ListSource,
+    /// The translation whose marks it gathers; none for every one.
+    #[serde(rename = "moduleId", default, skip_serializing_if = "Option::is_none")]
+    pub module_id: Option<String>,
+}
+
+/// One observation, as stored in a list's `items`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ObservationItem {
+    pub id: String,
+    pub content: String,
+    #[serde(rename = "verseRef")]
+    pub verse_ref: VerseRef,
+    #[serde(
+        rename = "annotationId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub annotation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A list, as stored in `observation_lists.data`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ObservationList {
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<ListScope>,
+    #[serde(default)]
+    pub items: Vec<ObservationItem>,
+    #[serde(rename = "keyWordId")]
+    pub key_word_id: String,
+    #[serde(rename = "studyId", default, skip_serializing_if = "Option::is_none")]
+    pub study_id: Option<String>,
+    #[serde(
+        rename = "generatedFrom",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub generated_from: Option<Generator>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// What `generate_observation_list` makes a list from.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListRequest {
+    pub source: ListSource,
+    #[serde(default)]
+    pub scope: Option<ListScope>,
+    #[serde(rename = "moduleId", default)]
+    pub module_id: Option<String>,
+    /// The key word the list is about; by default the source's, or the one
+    /// its marks are made with most.
+    #[serde(rename = "keyWordId", default)]
+    pub key_word_id: Option<String>,
+    /// By default the key word's word.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(rename = "studyId", default)]
+    pub study_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ListRefresh {
+    pub list: ObservationList,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Where a mark begins, and where in its verse, to order marks by.
+fn start_of(mark: &Annotation) -> (VerseRef, i64) {
+    match mark {
+        Annotation::Highlight(a) | Annotation::TextColor(a) => {
+            (a.span.start_ref.clone(), a.words.start_offset.unwrap_or(0))
+        }
+        Annotation::Underline(a) => (a.span.start_ref.clone(), a.words.start_offset.unwrap_or(0)),
+        Annotation::Symbol(a) => (
+            a.verse_ref.clone(),
+            a.words.start_offset.or(a.word_index).unwrap_or(0),
+        ),
+    }
+}
+
+fn selected_text(mark: &Annotation) -> Option<&str> {
+    let words = match mark {
+        Annotation::Highlight(a) | Annotation::TextColor(a) => &a.words,
+        Annotation::Underline(a) => &a.words,
+        Annotation::Symbol(a) => &a.words,
+    };
+    words
+        .selected_text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+fn canonical(at: &VerseRef) -> (usize, i64, i64) {
+    (
+        books::position(&at.book).unwrap_or(usize::MAX),
+        at.chapter,
+        at.verse,
+    )
+}
+
+/// The marks `source` names, as stored.
+fn source_marks(conn: &Connection, source: &ListSource) -> Result<Vec<Value>, DbError> {
+    let (sql, key) = match source {
+        ListSource::Tag { tag } => {
+            return Ok(tags::items(conn, tag, true)?
+                .into_iter()
+                .filter(|found| found.item_type == ItemType::Annotation)
+                .map(|found| found.item)
+                .collect());
+        }
+        ListSource::KeyWord { preset_id } => (
+            "SELECT data FROM annotations WHERE preset_id = ?1",
+            preset_id,
+        ),
+        ListSource::Style { style_id } => (
+            "SELECT data FROM annotations WHERE json_extract(data, '$.styleId') = ?1",
+            style_id,
+        ),
+    };
+    let rows: Vec<String> = conn
+        .prepare(sql)?
+        .query_map([key], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(rows
+        .iter()
+        .filter_map(|data| serde_json::from_str(data).ok())
+        .collect())
+}
+
+/// The marks of `generator` within `scope`, in canonical order.
+fn marks(
+    conn: &Connection,
+    generator: &Generator,
+    scope: Option<&ListScope>,
+) -> Result<Vec<Annotation>, DbError> {
+    let mut marks: Vec<(VerseRef, i64, Annotation)> = source_marks(conn, &generator.source)?
+        .iter()
+        .filter_map(|value| Annotation::from_value(value).ok())
+        .filter(|mark| {
+            generator
+                .module_id
+                .as_ref()
+                .is_none_or(|id| *id == mark.common().module_id)
+        })
+        .map(|mark| {
+            let (at, offset) = start_of(&mark);
+            (at, offset, mark)
+        })
+        .filter(|(at, _, _)| {
+            scope.is_none_or(|scope| {
+                scope.book.as_ref().is_none_or(|book| *book == at.book)
+                    && (scope.chapters.is_empty() || scope.chapters.contains(&at.chapter))
+            })
+        })
+        .collect();
+    marks.sort_by_cached_key(|(at, offset, mark)| {
+        (canonical(at), *offset, mark.common().id.clone())
+    });
+    Ok(marks.into_iter().map(|(_, _, mark)| mark).collect())
+}
+
+/// Verse texts, read a chapter at a time: from the translation's mounted
+/// content file, or the chapter cached from its API.
+struct Verses<'c> {
+    conn: &'c Connection,
+    chapters: HashMap<(String, String, i64), HashMap<i64, String>>,
+}
+
+impl<'c> Verses<'c> {
+    fn new(conn: &'c Connection) -> Self {
+        Self {
+            conn,
+            chapters: HashMap::new(),
+        }
+    }
+
+    fn text(&mut self, module_id: &str, at: &VerseRef) -> Result<Option<String>, DbError> {
+        let key = (module_id.to_string(), at.book.clone(), at.chapter);
+        if !self.chapters.contains_key(&key) {
+            let verses = match content::mounted(module_id) {
+                Some(mounted) => {
+                    content::chapter(self.conn, &mounted.schema, &at.book, at.chapter)?
+                        .into_iter()
+                        .collect()
+                }
+                None => {
+                    let cached: Option<String> = self
+                        .conn
+                        .query_row(
+                            "SELECT verses FROM chapter_cache
+                             WHERE module_id = ?1 AND book = ?2 AND chapter = ?3",
+                            params![module_id, at.book, at.chapter],
+                            |row| row.get(0),
+                        )
+                        .optional()?;
+                    cached
+                        .and_then(|v| serde_json::from_str::<HashMap<String, String>>(&v).ok())
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|(verse, text)| Some((verse.parse().ok()?, text)))
+                        .collect()
+                }
+            };
+            self.chapters.insert(key.clone(), verses);
+        }
+        Ok(self.chapters[&key].get(&at.verse).cloned())
+    }
+}
+
+/// The item for `mark`: the words it covers, else its verse, else its
+/// reference when the verse's text isn't here.
+fn item(verses: &mut Verses, mark: &Annotation, now: &str) -> Result<ObservationItem, DbError> {
+    let (at, _) = start_of(mark);
+    let common = mark.common();
+    let content = match selected_text(mark) {
+        Some(text) => text.to_string(),
+        None => match verses.text(&common.module_id, &at)? {
+            Some(text) if !text.trim().is_empty() => text.trim().to_string(),
+            _ => format!("{} {}:{}", at.book, at.chapter, at.verse),
+        },
+    };
+    Ok(ObservationItem {
+        id: format!("mark-{}", common.id),
+        content,
+        verse_ref: at,
+        annotation_id: Some(common.id.clone()),
+        notes: None,
+        created_at: now.to_string(),
+        updated_at: now.to_string(),
+        extra: Map::new(),
+    })
+}
+
+/// The key word a list from `marks` is about when none is given.
+fn key_word_of(source: &ListSource, marks: &[Annotation]) -> Option<String> {
+    if let ListSource::KeyWord { preset_id } = source {
+        return Some(preset_id.clone());
+    }
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for mark in marks {
+        if let Some(preset) = &mark.common().preset_id {
+            *uses.entry(preset).or_default() += 1;
+        }
+    }
+    uses.into_iter()
+        .max_by(|(a, a_uses), (b, b_uses)| a_uses.cmp(b_uses).then(b.cmp(a)))
+        .map(|(preset, _)| preset.to_string())
+}
+
+fn preset_word(conn: &Connection, id: &str) -> Result<Option<String>, DbError> {
+    Ok(conn
+        .query_row(
+            "SELECT word FROM marking_presets WHERE id = ?1",
+            [id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()?
+        .flatten())
+}
+
+pub(crate) fn list(conn: &Connection, id: &str) -> Result<ObservationList, DbError> {
+    let data: Option<String> = conn
+        .query_row(
+            "SELECT data FROM observation_lists WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let data = data
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("No observation list {id}")))?;
+    serde_json::from_str(&data)
+        .map_err(|e| DbError::invalid(format!("Observation list {id} can't be read: {e}")))
+}
+
+/// Store `list` as the TS layer does (`sqliteSaveToTableWithStudyId`) and
+/// log it for sync.
+fn write(conn: &Connection, list: &ObservationList) -> Result<(), DbError> {
+    let device = device_id(conn)?;
+    let data = serde_json::to_string(list)
+        .map_err(|e| DbError::invalid(format!("Cannot store observation list: {e}")))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO observation_lists
+         (id, data, study_id, created_at, updated_at, sync_status, device_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, 'pending', ?6)",
+        params![
+            list.id,
+            data,
+            list.study_id,
+            list.created_at,
+            list.updated_at,
+            device
+        ],
+    )?;
+    record_change(
+        conn,
+        "observation_lists",
+        "upsert",
+        &list.id,
+        Some(&data),
+        &list.updated_at,
+        &device,
+    )
+}
+
+/// Make and store a list of the marks `request` names.
+pub(crate) fn generate(
+    conn: &Connection,
+    id: &str,
+    request: ListRequest,
+) -> Result<ObservationList, DbError> {
+    if let Some(book) = request.scope.as_ref().and_then(|s| s.book.as_ref()) {
+        if books::position(book).is_none() {
+            return Err(DbError::invalid(format!("{book} is not a book")));
+        }
+    }
+    let generator = Generator {
+        source: request.source,
+        module_id: request.module_id,
+    };
+    let marks = marks(conn, &generator, request.scope.as_ref())?;
+    let key_word_id = request
+        .key_word_id
+        .filter(|id| !id.trim().is_empty())
+        .or_else(|| key_word_of(&generator.source, &marks))
+        .ok_or_else(|| {
+            DbError::invalid(
+                "None of these marks is of a key word; choose the one the list is about",
+            )
+        })?;
+    let title = match request.title.filter(|t| !t.trim().is_empty()) {
+        Some(title) => title.trim().to_string(),
+        None => preset_word(conn, &key_word_id)?.unwrap_or_else(|| "Observations".to_string()),
+    };
+    let now = now_iso(conn)?;
+    let mut verses = Verses::new(conn);
+    let items = marks
+        .iter()
+        .map(|mark| item(&mut verses, mark, &now))
+        .collect::<Result<_, _>>()?;
+    let list = ObservationList {
+        id: id.to_string(),
+        title,
+        scope: request.scope,
+        items,
+        key_word_id,
+        study_id: request.study_id,
+        generated_from: Some(generator),
+        created_at: now.clone(),
+        updated_at: now,
+        extra: Map::new(),
+    };
+    write(conn, &list)?;
+    Ok(list)
+}
+
+/// Bring generated list `id` up to date with its marks.
+pub(crate) fn refresh(conn: &Connection, id: &str) -> Result<ListRefresh, DbError> {
+    let mut list = list(conn, id)?;
+    let generator = list
+        .generated_from
+        .clone()
+        .ok_or_else(|| DbError::invalid(format!("{} wasn't made from markings", list.title)))?;
+    let marks = marks(conn, &generator, list.scope.as_ref())?;
+    let current: HashSet<&str> = marks.iter().map(|m| m.common().id.as_str()).collect();
+    let before = list.items.len();
+    list.items.retain(|item| {
+        item.annotation_id
+            .as_deref()
+            .is_none_or(|a| current.contains(a))
+    });
+    let removed = before - list.items.len();
+    let listed: HashSet<String> = list
+        .items
+        .iter()
+        .filter_map(|item| item.annotation_id.clone())
+        .collect();
+    let now = now_iso(conn)?;
+    let mut verses = Verses::new(conn);
+    let mut added = 0;
+    for mark in &marks {
+        if !listed.contains(&mark.common().id) {
+            list.items.push(item(&mut verses, mark, &now)?);
+            added += 1;
+        }
+    }
+    if added + removed > 0 {
+        let order: HashMap<&str, usize> = marks
+            .iter()
+            .enumerate()
+            .map(|(at, mark)| (mark.common().id.as_str(), at))
+            .collect();
+        list.items.sort_by_cached_key(|item| {
+            let mark = item.annotation_id.as_deref().and_then(|a| order.get(a));
+            (
+                canonical(&item.verse_ref),
+                mark.copied().unwrap_or(usize::MAX),
+            )
+        });
+        list.updated_at = now;
+        write(conn, &list)?;
+    }
+    Ok(ListRefresh {
+        list,
+        added,
+        removed,
+    })
+}
+
+/// Make an observation list of the marks of a key word, tag or style in
+/// `scope`, linked to them, in canonical order.
+#[tauri::command]
+pub async fn generate_observation_list(
+    app: tauri::AppHandle,
+    id: String,
+    request: ListRequest,
+) -> Result<ObservationList, DbError> {
+    with_connection(&app, move |conn| generate(conn, &id, request)).await
+}
+
+/// Add what has been marked since to a generated list, and drop items
+/// whose mark is gone.
+#[tauri::command]
+pub async fn refresh_observation_list(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<ListRefresh, DbError> {
+    with_connection(&app, move |conn| refresh(conn, &id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{annotations, migrated_test_connection};
+    use serde_json::json;
+
+    #[test]
+    fn lists_a_key_words_marks_in_order_and_refreshes_them() {
+        let mut conn = migrated_test_connection();
+        conn.execute_batch(
+            "CREATE TABLE observation_lists (
+                id TEXT PRIMARY KEY, key_word_id TEXT, study_id TEXT, data TEXT NOT NULL,
+                created_at TEXT NOT NULL, updated_at TEXT NOT NULL,
+                sync_status TEXT DEFAULT 'pending', device_id TEXT);
+             INSERT INTO marking_presets (id, word, variants, created_at, updated_at)
+               VALUES ('p-love', 'love', '[]', 'x', 'x');
+             INSERT INTO chapter_cache VALUES ('kjv:1John:4', 'kjv', '1John', 4,
+               '{\"8\":\"He that loveth not knoweth not God; for God is love.\"}', 'x');",
+        )
+        .unwrap();
+        let mark = |id: &str, book: &str, chapter: i64, verse: i64, text: Option<&str>| {
+            let at = json!({ "book": book, "chapter": chapter, "verse": verse });
+            let mut mark = json!({
+                "id": id, "moduleId": "kjv", "type": "highlight", "presetId": "p-love",
+                "startRef": at, "endRef": at, "color": "red", "startOffset": 3, "endOffset": 7,
+            });
+            if let Some(text) = text {
+                mark["selectedText"] = json!(text);
+            }
+            mark
+        };
+        let mut other = mark("other", "Gen", 22, 2, None);
+        other["presetId"] = json!("p-faith");
+        annotations::bulk_insert(
+            &mut conn,
+            &[
+                mark("in-john", "John", 3, 16, Some(" loved ")),
+                mark("in-1john", "1John", 4, 8, None),
+                mark("in-gen", "Gen", 22, 2, Some("lovest")),
+                other,
+            ],
+        )
+        .unwrap();
+
+        let request = ListRequest {
+            source: ListSource::KeyWord {
+                preset_id: "p-love".into(),
+            },
+            scope: None,
+            module_id: Some("kjv".into()),
+            key_word_id: None,
+            title: None,
+            study_id: None,
+        };
+        let list = generate(&conn, "l1", request).unwrap();
+        assert_eq!(list.title, "love");
+        let items: Vec<_> = list
+            .items
+            .iter()
+            .map(|i| (i.verse_ref.book.as_str(), i.content.as_str()))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("Gen", "lovest"),
+                ("John", "loved"),
+                (
+                    "1John",
+                    "He that loveth not knoweth not God; for God is love."
+                ),
+            ]
+        );
+        assert_eq!(list.items[1].annotation_id.as_deref(), Some("in-john"));
+
+        let mut stored = super::list(&conn, "l1").unwrap();
+        stored.items[1].notes = Some("the giving love".into());
+        write(&conn, &stored).unwrap();
+        annotations::delete_one(&conn, "in-gen", "y", "dev-local").unwrap();
+        annotations::bulk_insert(&mut conn, &[mark("in-rom", "Rom", 5, 8, Some("love"))]).unwrap();
+        let refreshed = refresh(&conn, "l1").unwrap();
+        assert_eq!((refreshed.added, refreshed.removed), (1, 1));
+        let ids: Vec<_> = refreshed
+            .list
+            .items
+            .iter()
+            .map(|i| i.annotation_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(ids, ["in-john", "in-rom", "in-1john"]);
+        assert_eq!(
+            refreshed.list.items[0].notes.as_deref(),
+            Some("the giving love")
+        );
+    }
+}
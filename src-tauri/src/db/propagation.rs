@@ -0,0 +1,211 @@
+//! Marks carried across translations: a highlight made on Rom 8:1 in one
+//! translation shows on Rom 8:1 in every other, unless it is translation
+//! locked (see `Common::translation_locked`).
+//!
+//! Nothing is copied. Reading a chapter, `get_chapter_annotations` adds the
+//! other translations' marks on it, renumbered through `versification` (a
+//! Hebrew Bible's Malachi 3:19 is Malachi 4:1 elsewhere) and placed by
+//! `anchors::carry` on the same words where this translation's text has
+//! them, or else on their whole verses. They come back flagged `carried`,
+//! which `Annotation::check` refuses, so a carried copy is never saved over
+//! the mark it came from; deleting one deletes that mark.
+
+use rusqlite::Connection;
+use std::collections::btree_map::{BTreeMap, Entry};
+
+use super::anchors;
+use super::annotations::{self, Annotation, VerseRef};
+use super::DbError;
+use crate::content::versification::{self, Scheme};
+use crate::content::{self, mounted};
+
+/// The verses `annotation` is on, first to last.
+fn refs_mut(annotation: &mut Annotation) -> Vec<&mut VerseRef> {
+    match annotation {
+        Annotation::Highlight(a) | Annotation::TextColor(a) => {
+            vec![&mut a.span.start_ref, &mut a.span.end_ref]
+        }
+        Annotation::Underline(a) => vec![&mut a.span.start_ref, &mut a.span.end_ref],
+        Annotation::Symbol(a) => std::iter::once(&mut a.verse_ref)
+            .chain(a.end_ref.as_mut())
+            .collect(),
+    }
+}
+
+/// Renumber `annotation` from `from` to `to`, returning the chapters it now
+/// starts and ends in; None when it is only on verses `to` doesn't number
+/// (a psalm title the KJV has no verse for).
+fn renumber(annotation: &mut Annotation, from: Scheme, to: Scheme) -> Option<(i64, i64)> {
+    let mut refs = refs_mut(annotation);
+    for r in refs.iter_mut() {
+        let (chapter, verse) = versification::map(from, to, &r.book, r.chapter, r.verse);
+        r.chapter = chapter;
+        r.verse = verse;
+    }
+    let (first, last) = (refs.first()?, refs.last()?);
+    if last.verse == 0 {
+        return None;
+    }
+    let chapters = (first.chapter, last.chapter);
+    // A psalm title's mark that runs on into the psalm starts at its first verse.
+    for r in refs {
+        r.verse = r.verse.max(1);
+    }
+    Some(chapters)
+}
+
+/// Other translations' marks on `book` `chapter`, carried into `module_id`:
+/// numbered as it numbers verses and placed on its words (see the module
+/// docs). Translation-locked marks stay where they are.
+pub(crate) fn carried(
+    conn: &Connection,
+    module_id: &str,
+    book: &str,
+    chapter: i64,
+) -> Result<Vec<Annotation>, DbError> {
+    let to = versification::of_module(conn, module_id)?;
+    let others: Vec<String> = conn
+        .prepare("SELECT DISTINCT module_id FROM annotations WHERE module_id <> ?1")?
+        .query_map([module_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    // This translation's text, when mounted, read once per chapter needed.
+    let schema = mounted(module_id).map(|content| content.schema);
+    let mut text: BTreeMap<i64, BTreeMap<i64, String>> = BTreeMap::new();
+
+    let mut carried = Vec::new();
+    for other in others {
+        let from = versification::of_module(conn, &other)?;
+        // Chapter breaks move by at most a chapter between numberings.
+        let (first, last) = if from == to {
+            (chapter, chapter)
+        } else {
+            (chapter - 1, chapter + 1)
+        };
+        let rows = annotations::chapters_annotations(conn, &other, book, first, last)?;
+        for mut annotation in annotations::readable(&rows) {
+            if annotation.common().locked_to_translation() {
+                continue;
+            }
+            let Some((start, end)) = renumber(&mut annotation, from, to) else {
+                continue;
+            };
+            if start > chapter || end < chapter {
+                continue;
+            }
+            let verses = match &schema {
+                Some(schema) => Some(match text.entry(start) {
+                    Entry::Occupied(verses) => &*verses.into_mut(),
+                    Entry::Vacant(verses) => {
+                        &*verses.insert(content::chapter(conn, schema, book, start)?)
+                    }
+                }),
+                None => None,
+            };
+            anchors::carry(&mut annotation, verses);
+            annotation.common_mut().carried = true;
+            carried.push(annotation);
+        }
+    }
+    Ok(carried)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+    use serde_json::json;
+
+    #[test]
+    fn carries_unlocked_marks_into_other_translations_on_their_verses() {
+        let mut conn = migrated_test_connection();
+        let mark = |id: &str, module: &str, extra: serde_json::Value| {
+            let mut mark = json!({
+                "id": id, "moduleId": module, "type": "highlight", "color": "yellow",
+                "startRef": { "book": "Rom", "chapter": 8, "verse": 1 },
+                "endRef": { "book": "Rom", "chapter": 8, "verse": 1 },
+                "startWordIndex": 4, "endWordIndex": 5, "selectedText": "no condemnation",
+            });
+            mark.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            mark
+        };
+        annotations::bulk_insert(
+            &mut conn,
+            &[
+                mark("esv-1", "esv", json!({})),
+                mark("esv-locked", "esv", json!({ "translationLocked": true })),
+                mark("esv-keyword", "esv", json!({ "presetId": "p1" })),
+                mark(
+                    "esv-keyword-shared",
+                    "esv",
+                    json!({ "presetId": "p1", "translationLocked": false }),
+                ),
+                mark("nasb-1", "nasb", json!({})),
+            ],
+        )
+        .unwrap();
+
+        let ids = |marks: &[Annotation]| {
+            let mut ids: Vec<_> = marks.iter().map(|a| a.common().id.clone()).collect();
+            ids.sort();
+            ids
+        };
+        let into_nasb = carried(&conn, "nasb", "Rom", 8).unwrap();
+        assert_eq!(ids(&into_nasb), ["esv-1", "esv-keyword-shared"]);
+        let Annotation::Highlight(esv) =
+            into_nasb.iter().find(|a| a.common().id == "esv-1").unwrap()
+        else {
+            panic!("not a highlight");
+        };
+        // NASB's text isn't mounted, so the mark covers its verse.
+        assert!(esv.common.carried);
+        assert_eq!(esv.common.module_id, "esv");
+        assert_eq!(esv.span.start_ref.verse, 1);
+        assert_eq!(esv.words.start_word_index, None);
+        assert!(carried(&conn, "nasb", "Rom", 7).unwrap().is_empty());
+        assert_eq!(ids(&carried(&conn, "esv", "Rom", 8).unwrap()), ["nasb-1"]);
+
+        let err = annotations::update(&conn, into_nasb[0].clone(), None).unwrap_err();
+        assert_eq!(err.kind, crate::db::DbErrorKind::Invalid);
+
+        // Where its text is mounted, a carried mark finds its words there.
+        let mut christ = Annotation::from_value(&mark(
+            "c",
+            "esv",
+            json!({ "startWordIndex": 10, "endWordIndex": 12, "selectedText": "in Christ Jesus" }),
+        ))
+        .unwrap();
+        let nasb = BTreeMap::from([(
+            1,
+            "Therefore there is now no condemnation at all for those who are in Christ Jesus."
+                .to_string(),
+        )]);
+        assert!(anchors::carry(&mut christ, Some(&nasb)));
+        let Annotation::Highlight(c) = &christ else {
+            panic!("not a highlight");
+        };
+        assert_eq!(
+            (c.words.start_word_index, c.words.end_word_index),
+            (Some(12), Some(14))
+        );
+        let mut gone = Annotation::from_value(&mark(
+            "g",
+            "esv",
+            json!({ "selectedText": "kept safe", "startWordIndex": 0, "endWordIndex": 1 }),
+        ))
+        .unwrap();
+        assert!(!anchors::carry(&mut gone, Some(&nasb)));
+
+        // Into a Hebrew numbering, KJV Malachi 4:1 is 3:19.
+        let mut mal = Annotation::from_value(&mark("m", "esv", json!({}))).unwrap();
+        for r in refs_mut(&mut mal) {
+            (r.book, r.chapter) = ("Mal".into(), 4);
+        }
+        assert_eq!(
+            renumber(&mut mal, Scheme::Kjv, Scheme::Hebrew),
+            Some((3, 3))
+        );
+        assert_eq!(refs_mut(&mut mal)[0].verse, 19);
+    }
+}
@@ -0,0 +1,526 @@
+//! Resource links: a note's or annotation's link to something outside the
+//! app, such as a commentary online, a Logos resource or a PDF on disk, so
+//! sermon preparation can hang its reading off the marks it was done for.
+//! Kept in the synced `resource_links` table of migration 29, one row per
+//! link, pointing at its item by type and id as tag links do; a link
+//! outlives its item, which may come back from the trash.
+//!
+//! `fetch_resource_metadata` fills in what the link is: a web page's title
+//! and description from its HTML, a Logos link's resource and reference from
+//! its URI, a file's name. It is kept apart from the caption the user gives,
+//! so refetching never overwrites what they wrote.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+use super::data_rows::{self, DataRow};
+use super::tags::ItemType;
+use super::{now_iso, with_connection, with_reader, DbError, DbErrorKind};
+use crate::content::xml;
+
+/// Most of a page read for its metadata; the head comes well before this.
+const MAX_PAGE_BYTES: usize = 512 * 1024;
+
+/// How long a page's server has to answer.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest fetched title or description kept, in characters.
+const MAX_META: usize = 300;
+
+/// What a link points at, told from its URL on save.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceKind {
+    #[default]
+    Web,
+    /// A `logosres:` or `logos4:` URI, or a `ref.ly` short link.
+    Logos,
+    /// A `file://` URL.
+    File,
+}
+
+/// What fetching found about a link.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResourceMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The site, publisher or Logos resource it comes from.
+    #[serde(rename = "siteName", default, skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+    #[serde(rename = "fetchedAt", default)]
+    pub fetched_at: String,
+    /// Why nothing could be fetched, when nothing could.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A link, as stored in `resource_links.data`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResourceLink {
+    pub id: String,
+    #[serde(rename = "itemType")]
+    pub item_type: Option<ItemType>,
+    #[serde(rename = "itemId")]
+    pub item_id: String,
+    pub url: String,
+    /// Set from `url` on save.
+    #[serde(default)]
+    pub kind: ResourceKind,
+    /// The user's caption; shown before the fetched title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ResourceMetadata>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DataRow for ResourceLink {
+    const TABLE: &'static str = "resource_links";
+    const NOUN: &'static str = "resource link";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+/// The kind of `url`, and the URL as stored: trimmed, and a bare absolute
+/// path made a `file://` URL.
+fn classify(url: &str) -> Result<(ResourceKind, String), DbError> {
+    let url = url.trim();
+    let invalid = || DbError::invalid(format!("`{url}` is not a web, Logos or file link"));
+    let lower = url.to_ascii_lowercase();
+    if lower.starts_with("logosres:") || lower.starts_with("logos4:") {
+        return Ok((ResourceKind::Logos, url.to_string()));
+    }
+    if std::path::Path::new(url).is_absolute() && !lower.contains("://") {
+        let file = reqwest::Url::from_file_path(url).map_err(|_| invalid())?;
+        return Ok((ResourceKind::File, file.to_string()));
+    }
+    let parsed = reqwest::Url::parse(url).map_err(|_| invalid())?;
+    match parsed.scheme() {
+        "file" => Ok((ResourceKind::File, parsed.to_string())),
+        "http" | "https" if parsed.host_str() == Some("ref.ly") => {
+            Ok((ResourceKind::Logos, parsed.to_string()))
+        }
+        "http" | "https" if parsed.host_str().is_some() => {
+            Ok((ResourceKind::Web, parsed.to_string()))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Tidy `link` and check it can be stored.
+fn check(link: &mut ResourceLink) -> Result<(), DbError> {
+    if link.id.trim().is_empty() {
+        return Err(DbError::invalid("Resource link has no id"));
+    }
+    if link.item_type.is_none() || link.item_id.trim().is_empty() {
+        return Err(DbError::invalid(format!(
+            "Resource link {} is not on a note, annotation or list",
+            link.id
+        )));
+    }
+    (link.kind, link.url) = classify(&link.url)?;
+    link.label = link
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string);
+    Ok(())
+}
+
+/// The links on one item, oldest first.
+pub(crate) fn item_links(
+    conn: &Connection,
+    item_type: ItemType,
+    item_id: &str,
+) -> Result<Vec<ResourceLink>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, data FROM resource_links
+         WHERE json_extract(data, '$.itemId') = ?1",
+    )?;
+    let rows = stmt
+        .query_map([item_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut links: Vec<ResourceLink> = rows
+        .into_iter()
+        .filter_map(|(id, data)| data_rows::parse::<ResourceLink>(&id, &data))
+        .filter(|l| l.item_type == Some(item_type))
+        .collect();
+    links.sort_by_cached_key(|l| (l.created_at.clone(), l.id.clone()));
+    Ok(links)
+}
+
+fn not_found(id: &str) -> DbError {
+    DbError::new(DbErrorKind::NotFound, format!("No resource link {id}"))
+}
+
+pub(crate) fn create(conn: &Connection, mut link: ResourceLink) -> Result<ResourceLink, DbError> {
+    check(&mut link)?;
+    data_rows::insert(conn, link)
+}
+
+/// Save an edited link. Metadata fetched for the old URL is dropped when the
+/// URL changes.
+pub(crate) fn update(conn: &Connection, mut link: ResourceLink) -> Result<ResourceLink, DbError> {
+    check(&mut link)?;
+    let stored: ResourceLink =
+        data_rows::find(conn, &link.id)?.ok_or_else(|| not_found(&link.id))?;
+    if stored.url != link.url {
+        link.metadata = None;
+    }
+    link.created_at = stored.created_at;
+    data_rows::save(conn, &mut link)?;
+    Ok(link)
+}
+
+pub(crate) fn delete(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    data_rows::delete(conn, "resource_links", id)
+}
+
+/// Keep what was fetched for link `id`, unless it was deleted or pointed
+/// elsewhere meanwhile.
+fn save_metadata(
+    conn: &Connection,
+    id: &str,
+    url: &str,
+    mut metadata: ResourceMetadata,
+) -> Result<ResourceLink, DbError> {
+    let mut link: ResourceLink = data_rows::find(conn, id)?.ok_or_else(|| not_found(id))?;
+    if link.url != url {
+        return Ok(link);
+    }
+    metadata.fetched_at = now_iso(conn)?;
+    link.metadata = Some(metadata);
+    data_rows::save(conn, &mut link)?;
+    Ok(link)
+}
+
+/// `text` with its whitespace collapsed and entities decoded, cut to
+/// `MAX_META` characters; none if empty.
+fn tidy(text: &str) -> Option<String> {
+    let text = xml::decode(text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    match text.char_indices().nth(MAX_META) {
+        _ if text.is_empty() => None,
+        Some((cut, _)) => Some(format!("{}…", &text[..cut])),
+        None => Some(text),
+    }
+}
+
+/// The attributes of the HTML tag starting at `tag` (after its name), with
+/// lowercased names.
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = tag;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        if name_end == 0 {
+            return attrs;
+        }
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+        let Some(after) = rest.strip_prefix('=') else {
+            attrs.push((name, String::new()));
+            continue;
+        };
+        let after = after.trim_start();
+        let (value, next) = match after.chars().next() {
+            Some(q @ ('"' | '\'')) => {
+                let body = &after[1..];
+                let end = body.find(q).unwrap_or(body.len());
+                (&body[..end], &body[(end + 1).min(body.len())..])
+            }
+            _ => {
+                let end = after
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        attrs.push((name, value.to_string()));
+        rest = next;
+    }
+}
+
+/// A web page's title, description and site name, from its Open Graph tags
+/// or, without them, its `<title>` and description meta tag.
+fn page_metadata(html: &str) -> ResourceMetadata {
+    // ASCII lowercasing keeps byte offsets, so matches index `html` too.
+    let lower = html.to_ascii_lowercase();
+    let head = lower.find("</head").unwrap_or(lower.len());
+    let mut tags: Vec<(String, String)> = Vec::new();
+    for (at, _) in lower[..head].match_indices("<meta") {
+        let start = at + "<meta".len();
+        let end = lower[start..].find('>').map_or(head, |e| start + e);
+        let attrs = attributes(&html[start..end]);
+        let key = attrs
+            .iter()
+            .find(|(n, _)| n == "property" || n == "name")
+            .map(|(_, v)| v.to_ascii_lowercase());
+        let content = attrs.iter().find(|(n, _)| n == "content");
+        if let (Some(key), Some((_, content))) = (key, content) {
+            tags.push((key, content.clone()));
+        }
+    }
+    let tag = |key: &str| {
+        tags.iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| tidy(v))
+    };
+    let title = tag("og:title").or_else(|| {
+        let start = lower[..head].find("<title")?;
+        let open = start + lower[start..].find('>')? + 1;
+        let close = open + lower[open..].find("</title")?;
+        tidy(&html[open..close])
+    });
+    ResourceMetadata {
+        title,
+        description: tag("og:description").or_else(|| tag("description")),
+        site_name: tag("og:site_name"),
+        ..Default::default()
+    }
+}
+
+/// A Logos URI's resource and reference, as in
+/// `logosres:esv;ref=BibleESV.Jn3.16`.
+fn logos_metadata(url: &str) -> ResourceMetadata {
+    let body = url.split_once(':').map_or(url, |(_, body)| body);
+    let mut parts = body.split(';');
+    let resource = parts.next().and_then(tidy);
+    let reference = parts
+        .find_map(|p| p.strip_prefix("ref="))
+        .map(|r| r.split_once('.').map_or(r, |(_, r)| r))
+        .and_then(tidy);
+    ResourceMetadata {
+        title: reference.or_else(|| resource.clone()),
+        site_name: resource.map(|r| format!("Logos: {r}")),
+        ..Default::default()
+    }
+}
+
+/// A local file's name, and whether it is there.
+fn file_metadata(url: &str) -> ResourceMetadata {
+    let path = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.to_file_path().ok());
+    let Some(path) = path else {
+        return ResourceMetadata {
+            error: Some("Not a file on this device".into()),
+            ..Default::default()
+        };
+    };
+    ResourceMetadata {
+        title: path.file_name().and_then(|n| tidy(&n.to_string_lossy())),
+        error: (!path.exists()).then(|| "The file is not on this device".into()),
+        ..Default::default()
+    }
+}
+
+/// Fetch the page at `url` and read its metadata; a document that isn't
+/// HTML is titled by the last segment of its path.
+async fn web_metadata(url: &str) -> Result<ResourceMetadata, String> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut res = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "text/html,*/*;q=0.5")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !res.status().is_success() {
+        return Err(format!("The server answered {}", res.status()));
+    }
+    let html = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|t| t.contains("html"));
+    if !html {
+        let name = res
+            .url()
+            .path_segments()
+            .and_then(|mut s| s.next_back().map(str::to_string));
+        return Ok(ResourceMetadata {
+            title: name.and_then(|n| tidy(&n)),
+            site_name: res.url().host_str().map(str::to_string),
+            ..Default::default()
+        });
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = res.chunk().await.map_err(|e| e.to_string())? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_PAGE_BYTES {
+            break;
+        }
+    }
+    let mut metadata = page_metadata(&String::from_utf8_lossy(&body));
+    if metadata.site_name.is_none() {
+        metadata.site_name = res.url().host_str().map(str::to_string);
+    }
+    Ok(metadata)
+}
+
+/// The links on a note or annotation.
+#[tauri::command]
+pub async fn get_resource_links(
+    app: tauri::AppHandle,
+    item_type: ItemType,
+    item_id: String,
+) -> Result<Vec<ResourceLink>, DbError> {
+    with_reader(&app, move |conn| item_links(conn, item_type, &item_id)).await
+}
+
+#[tauri::command]
+pub async fn create_resource_link(
+    app: tauri::AppHandle,
+    link: ResourceLink,
+) -> Result<ResourceLink, DbError> {
+    with_connection(&app, move |conn| create(conn, link)).await
+}
+
+#[tauri::command]
+pub async fn update_resource_link(
+    app: tauri::AppHandle,
+    link: ResourceLink,
+) -> Result<ResourceLink, DbError> {
+    with_connection(&app, move |conn| update(conn, link)).await
+}
+
+#[tauri::command]
+pub async fn delete_resource_link(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| delete(conn, &id)).await
+}
+
+/// Fetch what link `id` points at and keep it on the link. A page that
+/// can't be fetched is not an error: the link keeps the reason in
+/// `metadata.error`, and is fetched again when asked.
+#[tauri::command]
+pub async fn fetch_resource_metadata(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<ResourceLink, DbError> {
+    let lookup = id.clone();
+    let link = with_reader(&app, move |conn| {
+        data_rows::find::<ResourceLink>(conn, &lookup)
+    })
+    .await?
+    .ok_or_else(|| not_found(&id))?;
+    let metadata =
+        match link.kind {
+            ResourceKind::Logos if !link.url.starts_with("http") => logos_metadata(&link.url),
+            ResourceKind::File => file_metadata(&link.url),
+            ResourceKind::Web | ResourceKind::Logos => web_metadata(&link.url)
+                .await
+                .unwrap_or_else(|e| ResourceMetadata {
+                    error: Some(e),
+                    ..Default::default()
+                }),
+        };
+    with_connection(&app, move |conn| {
+        save_metadata(conn, &id, &link.url, metadata)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+
+    #[test]
+    fn reads_titles_from_pages_and_logos_uris() {
+        let page = r#"<html><HEAD><title>ignored</title>
+            <meta property="og:title" content="The Covenant &amp; the Promise">
+            <meta name=description content='A study of Genesis 15'>
+            <meta property="og:site_name" content="Ligonier"></head>
+            <body><meta property="og:title" content="not in the head"></body></html>"#;
+        let meta = page_metadata(page);
+        assert_eq!(meta.title.as_deref(), Some("The Covenant & the Promise"));
+        assert_eq!(meta.description.as_deref(), Some("A study of Genesis 15"));
+        assert_eq!(meta.site_name.as_deref(), Some("Ligonier"));
+        let plain = page_metadata("<title>\n  Romans   8 </title>");
+        assert_eq!(plain.title.as_deref(), Some("Romans 8"));
+
+        let logos = logos_metadata("logosres:esv;ref=BibleESV.Jn3.16");
+        assert_eq!(logos.title.as_deref(), Some("Jn3.16"));
+        assert_eq!(logos.site_name.as_deref(), Some("Logos: esv"));
+    }
+
+    #[test]
+    fn classifies_links_and_keeps_them_on_their_item() {
+        let conn = migrated_test_connection();
+        let link = |id: &str, url: &str| ResourceLink {
+            id: id.into(),
+            item_type: Some(ItemType::Note),
+            item_id: "n1".into(),
+            url: url.into(),
+            ..Default::default()
+        };
+        let web = create(&conn, link("l1", " https://example.org/genesis-15 ")).unwrap();
+        assert_eq!(
+            (web.kind, web.url.as_str()),
+            (ResourceKind::Web, "https://example.org/genesis-15")
+        );
+        let logos = create(&conn, link("l2", "logosres:esv;ref=BibleESV.Ge15.6")).unwrap();
+        assert_eq!(logos.kind, ResourceKind::Logos);
+        assert_eq!(
+            create(&conn, link("l3", "https://ref.ly/Ge15.6"))
+                .unwrap()
+                .kind,
+            ResourceKind::Logos
+        );
+        assert!(create(&conn, link("l4", "mailto:pastor@example.org")).is_err());
+        let mut other = link("l5", "https://example.org");
+        other.item_type = Some(ItemType::Annotation);
+        create(&conn, other).unwrap();
+
+        let fetched = ResourceMetadata {
+            title: Some("Genesis 15".into()),
+            ..Default::default()
+        };
+        save_metadata(&conn, "l1", &web.url, fetched).unwrap();
+        let mut moved: ResourceLink = data_rows::find(&conn, "l1").unwrap().unwrap();
+        assert!(!moved.metadata.as_ref().unwrap().fetched_at.is_empty());
+        moved.label = Some(" Sproul ".into());
+        assert!(update(&conn, moved.clone()).unwrap().metadata.is_some());
+        moved.url = "https://example.org/genesis-17".into();
+        let moved = update(&conn, moved).unwrap();
+        assert_eq!(
+            (moved.label.as_deref(), moved.metadata),
+            (Some("Sproul"), None)
+        );
+
+        let ids: Vec<_> = item_links(&conn, ItemType::Note, "n1")
+            .unwrap()
+            .into_iter()
+            .map(|l| l.id)
+            .collect();
+        assert_eq!(ids, ["l1", "l2", "l3"]);
+        assert!(delete(&conn, "l2").unwrap());
+        assert_eq!(item_links(&conn, ItemType::Note, "n1").unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,256 @@
+//! Ranked full-text search over the FTS5 index built by migration 15.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::{with_reader, DbError};
+
+/// Marks a match in `snippet()` output; control characters never occur in
+/// verse text or notes, so they can't be confused with content.
+const MATCH_START: char = '\u{2}';
+const MATCH_END: char = '\u{3}';
+
+/// Hits returned when the caller doesn't pass a limit.
+pub(crate) const DEFAULT_LIMIT: u32 = 100;
+
+/// Which kind of document to search.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FulltextScope {
+    All,
+    Bible,
+    Notes,
+    Keywords,
+}
+
+impl FulltextScope {
+    fn kind(self) -> Option<&'static str> {
+        match self {
+            Self::All => None,
+            Self::Bible => Some("verse"),
+            Self::Notes => Some("note"),
+            Self::Keywords => Some("keyword"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FulltextHit {
+    /// `verse`, `note` or `keyword`.
+    pub kind: String,
+    /// Row id in chapter_cache, notes or marking_presets.
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    #[serde(rename = "moduleId")]
+    pub module_id: Option<String>,
+    pub book: Option<String>,
+    pub chapter: Option<i64>,
+    pub verse: Option<i64>,
+    /// The whole indexed text (verse, note content, keyword terms).
+    pub text: String,
+    /// Excerpt around the best match.
+    pub snippet: String,
+    /// `[start, end)` of each matched term within `snippet`, in UTF-16 code
+    /// units so JS can pass them straight to `slice`.
+    pub highlights: Vec<(usize, usize)>,
+    /// bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+/// Turn what the user typed into an FTS5 query: every word must appear, as a
+/// prefix, and nothing they type is parsed as FTS syntax.
+pub(crate) fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\"*"))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Strip the match markers from a snippet, recording where they were.
+pub(crate) fn split_highlights(marked: &str) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(marked.len());
+    let mut highlights = Vec::new();
+    let mut offset = 0;
+    let mut start = None;
+    for c in marked.chars() {
+        match c {
+            MATCH_START => start = Some(offset),
+            MATCH_END => {
+                if let Some(s) = start.take() {
+                    highlights.push((s, offset));
+                }
+            }
+            _ => {
+                text.push(c);
+                offset += c.len_utf16();
+            }
+        }
+    }
+    (text, highlights)
+}
+
+pub(crate) fn search(
+    conn: &Connection,
+    query: &str,
+    scope: FulltextScope,
+    module_id: Option<&str>,
+    limit: u32,
+) -> Result<Vec<FulltextHit>, DbError> {
+    let Some(expression) = match_expression(query) else {
+        return Ok(Vec::new());
+    };
+    let mut stmt = conn.prepare(
+        "SELECT d.kind, d.source_id, d.module_id, d.book, d.chapter, d.verse,
+                snippet(search_index, 0, char(2), char(3), '…', 24), rank,
+                search_index.body
+         FROM search_index JOIN search_docs d ON d.id = search_index.rowid
+         WHERE search_index MATCH ?1
+           AND (?2 IS NULL OR d.kind = ?2)
+           AND (?3 IS NULL OR d.module_id IS NULL OR d.module_id = ?3)
+         ORDER BY rank
+         LIMIT ?4",
+    )?;
+    let hits = stmt
+        .query_map(params![expression, scope.kind(), module_id, limit], |row| {
+            let (snippet, highlights) = split_highlights(&row.get::<_, String>(6)?);
+            Ok(FulltextHit {
+                kind: row.get(0)?,
+                source_id: row.get(1)?,
+                module_id: row.get(2)?,
+                book: row.get(3)?,
+                chapter: row.get(4)?,
+                verse: row.get(5)?,
+                text: row.get(8)?,
+                snippet,
+                highlights,
+                rank: row.get(7)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(hits)
+}
+
+/// Search verse text, notes and keywords, best matches first.
+#[tauri::command]
+pub async fn search_fulltext(
+    app: tauri::AppHandle,
+    query: String,
+    scope: FulltextScope,
+    module_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<FulltextHit>, DbError> {
+    with_reader(&app, move |conn| {
+        search(
+            conn,
+            &query,
+            scope,
+            module_id.as_deref(),
+            limit.unwrap_or(DEFAULT_LIMIT),
+        )
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrations, test_connection};
+
+    fn indexed() -> Connection {
+        let mut conn = test_connection();
+        conn.execute_batch(
+            "CREATE TABLE schema_version (
+                id INTEGER PRIMARY KEY CHECK (id = 1), version INTEGER NOT NULL,
+                updated_at TEXT NOT NULL);
+             INSERT INTO schema_version VALUES (1, 13, '2025-01-01');
+             INSERT INTO chapter_cache VALUES ('kjv:John:1', 'kjv', 'John', 1,
+                '{\"1\":\"In the beginning was the Word\",\"29\":\"Behold the Lamb of God\"}',
+                '2025-01-01');",
+        )
+        .unwrap();
+        // The existing chapter is picked up by the migration's backfill.
+        migrations::migrate(&mut conn, false).unwrap();
+        conn
+    }
+
+    fn kinds(hits: &[FulltextHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.kind.as_str()).collect()
+    }
+
+    #[test]
+    fn builds_prefix_queries_without_fts_syntax() {
+        assert_eq!(
+            match_expression("lamb \"god").as_deref(),
+            Some("\"lamb\"* \"god\"*")
+        );
+        assert_eq!(match_expression("  \"\" "), None);
+    }
+
+    #[test]
+    fn finds_backfilled_verses_with_highlight_offsets() {
+        let conn = indexed();
+        let hits = search(&conn, "lamb", FulltextScope::All, None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        let hit = &hits[0];
+        assert_eq!((hit.kind.as_str(), hit.verse), ("verse", Some(29)));
+        assert_eq!(hit.snippet, "Behold the Lamb of God");
+        let (start, end) = hit.highlights[0];
+        assert_eq!(&hit.snippet[start..end], "Lamb");
+    }
+
+    #[test]
+    fn triggers_keep_the_index_in_step() {
+        let conn = indexed();
+        conn.execute_batch(
+            "INSERT INTO notes VALUES ('n1', 'kjv', '{\"book\":\"John\",\"chapter\":1,\"verse\":29}',
+                NULL, 'The lamb points to the Passover', '2025-01-01', '2025-01-01', NULL, NULL);
+             INSERT INTO marking_presets (id, word, variants, description, created_at, updated_at)
+                VALUES ('p1', 'Passover', '[{\"text\":\"feast\"}]', NULL, '2025-01-01', '2025-01-01');",
+        )
+        .unwrap();
+        assert_eq!(
+            search(&conn, "passover", FulltextScope::All, None, 10)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            kinds(&search(&conn, "feast", FulltextScope::Keywords, None, 10).unwrap()),
+            ["keyword"]
+        );
+
+        // INSERT OR REPLACE is how the app saves; the old text must drop out.
+        conn.execute(
+            "INSERT OR REPLACE INTO notes VALUES ('n1', 'kjv', '{\"book\":\"John\",\"chapter\":1,\"verse\":29}',
+                NULL, 'Rewritten', '2025-01-01', '2025-01-02', NULL, NULL)",
+            [],
+        )
+        .unwrap();
+        assert!(search(&conn, "lamb", FulltextScope::Notes, None, 10)
+            .unwrap()
+            .is_empty());
+
+        conn.execute("DELETE FROM chapter_cache", []).unwrap();
+        assert!(search(&conn, "beginning", FulltextScope::Bible, None, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn filters_by_module_but_keeps_keywords() {
+        let conn = indexed();
+        conn.execute(
+            "INSERT INTO marking_presets (id, word, variants, created_at, updated_at)
+             VALUES ('p1', 'Word', '[]', '2025-01-01', '2025-01-01')",
+            [],
+        )
+        .unwrap();
+        let esv = search(&conn, "word", FulltextScope::All, Some("esv"), 10).unwrap();
+        assert_eq!(kinds(&esv), ["keyword"]);
+        let kjv = search(&conn, "word", FulltextScope::All, Some("kjv"), 10).unwrap();
+        assert_eq!(kjv.len(), 2);
+    }
+}
@@ -0,0 +1,311 @@
+//! Rolling snapshots of the user data, independent of manual backups.
+//!
+//! While the user is editing, a background thread writes a SQL dump (see
+//! `dump`) to `snapshots/` next to the database at most every 15 minutes,
+//! and only when something changed since the last one; the newest 10 are
+//! kept. Restoring loads a snapshot exactly like `import_sql_dump`, after
+//! snapshotting the current data so the restore can itself be undone.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::dump::{self, SqlDumpReport};
+use super::{with_connection, DbError, DbErrorKind};
+
+const SNAPSHOT_DIR: &str = "snapshots";
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+
+/// Snapshots kept; older ones are deleted as new ones are written.
+const KEEP_SNAPSHOTS: usize = 10;
+
+/// Minimum time between two scheduled snapshots.
+const SNAPSHOT_INTERVAL_SECS: i64 = 15 * 60;
+
+/// How often the scheduler looks for new edits.
+const SNAPSHOT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// sync_config keys (local to the device) for the last snapshot: the
+/// `change_log` sequence it covers, and when it was taken.
+const LAST_SNAPSHOT_SEQ_KEY: &str = "last_snapshot_seq";
+const LAST_SNAPSHOT_AT_KEY: &str = "last_snapshot_at";
+
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    pub id: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub bytes: u64,
+}
+
+pub(crate) fn snapshot_dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(SNAPSHOT_DIR)
+}
+
+fn snapshot_file(dir: &Path, id: &str) -> Result<PathBuf, DbError> {
+    let valid = id.strip_prefix(SNAPSHOT_PREFIX).is_some_and(|rest| {
+        !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit() || c == '-')
+    });
+    if !valid {
+        return Err(DbError::invalid(format!("`{id}` is not a snapshot id")));
+    }
+    Ok(dir.join(format!("{id}.sql")))
+}
+
+/// Every local write bumps this, including ones `change_log` has since pruned.
+fn change_seq(conn: &Connection) -> Result<i64, DbError> {
+    Ok(conn
+        .query_row(
+            "SELECT seq FROM sqlite_sequence WHERE name = 'change_log'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or(0))
+}
+
+/// Whether the scheduler should snapshot now: there are edits the last
+/// snapshot doesn't have, and it's at least `SNAPSHOT_INTERVAL_SECS` old.
+pub(crate) fn snapshot_due(conn: &Connection) -> Result<bool, DbError> {
+    let (last_seq, age): (Option<i64>, Option<i64>) = conn.query_row(
+        "SELECT
+            (SELECT CAST(value AS INTEGER) FROM sync_config WHERE key = ?1),
+            (SELECT CAST((julianday('now') - julianday(value)) * 86400 AS INTEGER)
+             FROM sync_config WHERE key = ?2)",
+        params![LAST_SNAPSHOT_SEQ_KEY, LAST_SNAPSHOT_AT_KEY],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    if change_seq(conn)? <= last_seq.unwrap_or(0) {
+        return Ok(false);
+    }
+    Ok(age.is_none_or(|a| a >= SNAPSHOT_INTERVAL_SECS))
+}
+
+fn read_snapshot(path: &Path) -> Option<Snapshot> {
+    let id = path.file_stem()?.to_str()?.to_string();
+    if !id.starts_with(SNAPSHOT_PREFIX) || path.extension()? != "sql" {
+        return None;
+    }
+    let bytes = std::fs::metadata(path).ok()?.len();
+    // The header is the first few short lines.
+    let mut head = Vec::with_capacity(512);
+    std::fs::File::open(path)
+        .ok()?
+        .take(512)
+        .read_to_end(&mut head)
+        .ok()?;
+    let head = String::from_utf8_lossy(&head);
+    let created_at = dump::header(&head, "created")?.to_string();
+    Some(Snapshot {
+        id,
+        created_at,
+        bytes,
+    })
+}
+
+/// Snapshots in `dir`, newest first.
+pub(crate) fn list(dir: &Path) -> Vec<Snapshot> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<Snapshot> = entries
+        .filter_map(|entry| read_snapshot(&entry.ok()?.path()))
+        .collect();
+    // Ids are timestamps, so they sort by time.
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+    snapshots
+}
+
+/// Write a snapshot of `conn` to `dir` and drop the ones past `KEEP_SNAPSHOTS`.
+pub(crate) fn take(conn: &Connection, dir: &Path) -> Result<Snapshot, DbError> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| DbError::io(format!("Failed to create {}: {e}", dir.display())))?;
+    let seq = change_seq(conn)?;
+    let stamp: String = conn.query_row(
+        "SELECT replace(strftime('%Y%m%d-%H%M%f', 'now'), '.', '')",
+        [],
+        |row| row.get(0),
+    )?;
+    let id = format!("{SNAPSHOT_PREFIX}{stamp}");
+    let path = snapshot_file(dir, &id)?;
+    let report = dump::export_file(conn, &path)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO sync_config (key, value) VALUES (?1, ?2), (?3, ?4)",
+        params![
+            LAST_SNAPSHOT_SEQ_KEY,
+            seq.to_string(),
+            LAST_SNAPSHOT_AT_KEY,
+            report.created_at
+        ],
+    )?;
+
+    for old in list(dir).iter().skip(KEEP_SNAPSHOTS) {
+        if let Ok(stale) = snapshot_file(dir, &old.id) {
+            let _ = std::fs::remove_file(stale);
+        }
+    }
+    Ok(Snapshot {
+        id,
+        created_at: report.created_at,
+        bytes: std::fs::metadata(&path).map_or(0, |m| m.len()),
+    })
+}
+
+/// Replace the user data with snapshot `id`, snapshotting the current data
+/// first. The restore is logged for sync like a dump import, so it wins over
+/// the state the other devices hold rather than being synced back over.
+pub(crate) fn restore(
+    conn: &mut Connection,
+    dir: &Path,
+    id: &str,
+) -> Result<SqlDumpReport, DbError> {
+    let path = snapshot_file(dir, id)?;
+    // Read it before `take` can rotate it out.
+    let sql = match std::fs::read_to_string(&path) {
+        Ok(sql) => sql,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(DbError::new(
+                DbErrorKind::NotFound,
+                format!("No snapshot `{id}`"),
+            ))
+        }
+        Err(e) => return Err(DbError::io(format!("Failed to read snapshot `{id}`: {e}"))),
+    };
+    take(conn, dir)?;
+    dump::import_report(conn, path.display().to_string(), &sql)
+}
+
+/// Rolling snapshots, newest first.
+#[tauri::command]
+pub fn list_snapshots(app: tauri::AppHandle) -> Result<Vec<Snapshot>, DbError> {
+    Ok(list(&snapshot_dir(&super::db_path(&app)?)))
+}
+
+#[tauri::command]
+pub async fn restore_snapshot(app: tauri::AppHandle, id: String) -> Result<SqlDumpReport, DbError> {
+    let dir = snapshot_dir(&super::db_path(&app)?);
+    with_connection(&app, move |conn| restore(conn, &dir, &id)).await
+}
+
+/// Start the background thread that takes the rolling snapshots. Locked or
+/// busy databases are skipped until the next check.
+pub fn spawn_rolling_snapshots(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SNAPSHOT_CHECK_INTERVAL);
+        let Ok(path) = super::db_path(&app_handle) else {
+            continue;
+        };
+        // The frontend creates the database; never create it from here.
+        if !path.exists() {
+            continue;
+        }
+        let dir = snapshot_dir(&path);
+        let result = super::connections::manager(&path).write(move |conn| {
+            if snapshot_due(conn)? {
+                take(conn, &dir).map(Some)
+            } else {
+                Ok(None)
+            }
+        });
+        match result {
+            Ok(Some(snapshot)) => println!("[snapshots] saved {}", snapshot.id),
+            Ok(None) => {}
+            Err(e) if e.kind == DbErrorKind::DatabaseLocked => {}
+            Err(e) => println!("[snapshots] skipped: {e}"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+    use crate::test_util::ScratchDir;
+
+    fn save_note(conn: &Connection, id: &str, content: &str) {
+        conn.execute(
+            "INSERT OR REPLACE INTO notes VALUES (?1, 'kjv', '{\"book\":\"John\"}', NULL, ?2,
+                '2025-01-01', '2025-01-02', 'pending', 'dev-local')",
+            params![id, content],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO change_log (table_name, op, row_id, updated_at, device_id)
+             VALUES ('notes', 'upsert', ?1, '2025-01-02', 'dev-local')",
+            [id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn snapshots_wait_for_edits_and_the_interval() {
+        let conn = migrated_test_connection();
+        assert!(!snapshot_due(&conn).unwrap(), "nothing to save yet");
+        save_note(&conn, "n1", "first");
+        assert!(snapshot_due(&conn).unwrap());
+
+        let dir = ScratchDir::new("snapshots-due");
+        take(&conn, &dir).unwrap();
+        assert!(!snapshot_due(&conn).unwrap(), "no edits since");
+        save_note(&conn, "n1", "second");
+        assert!(!snapshot_due(&conn).unwrap(), "too soon");
+        conn.execute(
+            "UPDATE sync_config SET value = '2020-01-01T00:00:00.000Z' WHERE key = ?1",
+            [LAST_SNAPSHOT_AT_KEY],
+        )
+        .unwrap();
+        assert!(snapshot_due(&conn).unwrap());
+    }
+
+    #[test]
+    fn keeps_the_newest_and_restores_one() {
+        let mut conn = migrated_test_connection();
+        let dir = ScratchDir::new("snapshots-restore");
+        save_note(&conn, "n1", "original");
+        let first = take(&conn, &dir).unwrap();
+        for _ in 0..KEEP_SNAPSHOTS {
+            std::thread::sleep(Duration::from_millis(2));
+            take(&conn, &dir).unwrap();
+        }
+        let kept = list(&dir);
+        assert_eq!(kept.len(), KEEP_SNAPSHOTS);
+        assert!(kept.iter().all(|s| s.id != first.id), "oldest rotated out");
+        assert!(kept[0].id > kept[1].id, "newest first");
+
+        save_note(&conn, "n1", "edited");
+        let report = restore(&mut conn, &dir, &kept[0].id).unwrap();
+        assert!(report
+            .tables
+            .iter()
+            .any(|t| t.table == "notes" && t.rows == 1));
+        let content: String = conn
+            .query_row("SELECT content FROM notes WHERE id = 'n1'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(content, "original");
+        let (data, at): (String, String) = conn
+            .query_row(
+                "SELECT data, updated_at FROM change_log
+                 WHERE row_id = 'n1' AND op = 'upsert' ORDER BY seq DESC LIMIT 1",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert!(data.contains("original"), "the restore reaches sync");
+        assert!(at.as_str() > "2025-01-02");
+        let newest = &list(&dir)[0];
+        let saved = std::fs::read_to_string(snapshot_file(&dir, &newest.id).unwrap()).unwrap();
+        assert!(
+            saved.contains("'edited'"),
+            "the replaced data was snapshotted"
+        );
+
+        let missing = restore(&mut conn, &dir, "snapshot-19990101-000000000").unwrap_err();
+        assert_eq!(missing.kind, DbErrorKind::NotFound);
+        let bad = restore(&mut conn, &dir, "../biblemarker").unwrap_err();
+        assert_eq!(bad.kind, DbErrorKind::Invalid);
+    }
+}
@@ -0,0 +1,305 @@
+//! The symbol library: the personal vocabulary of inductive study, where a
+//! shape in a color stands for something (a purple triangle for God, a red
+//! cloud for the covenant), kept in the synced `symbol_library` table of
+//! migration 20 so every device marks with the same meanings.
+//!
+//! Rows are generic data rows like `places`, so sync, merges, snapshots and
+//! dumps carry them with no code of their own. Usage is counted from the
+//! symbol annotations and marking presets drawn with the same shape and
+//! color; the library doesn't own them, and deleting an entry leaves them be.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::data_rows::{self, DataRow};
+use super::{with_connection, with_reader, DbError};
+
+/// Longest shape name or glyph accepted.
+const MAX_SHAPE: usize = 32;
+
+/// One symbol of the library, as stored in `symbol_library.data`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LibrarySymbol {
+    pub id: String,
+    /// A `SymbolKey` (`triangle`, `cloud`), or a glyph of the user's own.
+    pub shape: String,
+    /// A highlight color name or `#rrggbb`; none for a symbol drawn in the
+    /// text color.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// What the symbol stands for, e.g. `God` or `covenant`.
+    pub meaning: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DataRow for LibrarySymbol {
+    const TABLE: &'static str = "symbol_library";
+    const NOUN: &'static str = "library symbol";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+/// How often a library symbol is drawn.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct SymbolUsage {
+    /// Symbol annotations with its shape (and color, if it has one).
+    pub annotations: i64,
+    /// Marking presets that mark with it.
+    pub presets: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LibraryEntry {
+    #[serde(flatten)]
+    pub symbol: LibrarySymbol,
+    pub usage: SymbolUsage,
+}
+
+/// Tidy `symbol` and check it can be stored beside `others`, the rest of
+/// the library.
+fn check(symbol: &mut LibrarySymbol, others: &[LibrarySymbol]) -> Result<(), DbError> {
+    symbol.shape = symbol.shape.trim().to_string();
+    symbol.meaning = symbol.meaning.trim().to_string();
+    symbol.color = symbol
+        .color
+        .take()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    if symbol.id.trim().is_empty() {
+        return Err(DbError::invalid("Library symbol has no id"));
+    }
+    if symbol.shape.is_empty() || symbol.shape.chars().count() > MAX_SHAPE {
+        return Err(DbError::invalid(format!(
+            "A symbol's shape must be 1 to {MAX_SHAPE} characters"
+        )));
+    }
+    if symbol.meaning.is_empty() {
+        return Err(DbError::invalid(format!(
+            "Say what the {} symbol means",
+            symbol.shape
+        )));
+    }
+    let same_color = |a: &Option<String>, b: &Option<String>| match (a, b) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        (None, None) => true,
+        _ => false,
+    };
+    if let Some(taken) = others.iter().find(|o| {
+        o.id != symbol.id
+            && o.shape.eq_ignore_ascii_case(&symbol.shape)
+            && same_color(&o.color, &symbol.color)
+    }) {
+        return Err(DbError::invalid(format!(
+            "{} already means {}",
+            describe(taken),
+            taken.meaning
+        )));
+    }
+    Ok(())
+}
+
+/// `purple triangle`, or just `triangle` without a color.
+fn describe(symbol: &LibrarySymbol) -> String {
+    match &symbol.color {
+        Some(color) => format!("{color} {}", symbol.shape),
+        None => symbol.shape.clone(),
+    }
+}
+
+/// Every library symbol, by meaning. Rows that don't parse are skipped.
+pub(crate) fn symbols(conn: &Connection) -> Result<Vec<LibrarySymbol>, DbError> {
+    let mut symbols = data_rows::all::<LibrarySymbol>(conn)?;
+    symbols.sort_by_cached_key(|s| (s.meaning.to_lowercase(), s.id.clone()));
+    Ok(symbols)
+}
+
+/// How often each (shape, color) pair is drawn, from `sql` selecting shape,
+/// color and a count.
+fn counts(conn: &Connection, sql: &str) -> Result<HashMap<(String, String), i64>, DbError> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |row| {
+        let shape: String = row.get(0)?;
+        let color: Option<String> = row.get(1)?;
+        Ok((
+            (
+                shape.to_lowercase(),
+                color.unwrap_or_default().to_lowercase(),
+            ),
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+    let mut counts = HashMap::new();
+    for row in rows {
+        let (key, n) = row?;
+        *counts.entry(key).or_insert(0) += n;
+    }
+    Ok(counts)
+}
+
+/// Uses of `symbol` in `counts`: of its color if it has one, else of any.
+fn used(counts: &HashMap<(String, String), i64>, symbol: &LibrarySymbol) -> i64 {
+    let shape = symbol.shape.to_lowercase();
+    match &symbol.color {
+        Some(color) => counts
+            .get(&(shape, color.to_lowercase()))
+            .copied()
+            .unwrap_or(0),
+        None => counts
+            .iter()
+            .filter(|((s, _), _)| *s == shape)
+            .map(|(_, n)| n)
+            .sum(),
+    }
+}
+
+/// The library with each symbol's usage.
+pub(crate) fn library(conn: &Connection) -> Result<Vec<LibraryEntry>, DbError> {
+    let annotations = counts(
+        conn,
+        "SELECT json_extract(data, '$.symbol'), json_extract(data, '$.color'), COUNT(*)
+         FROM annotations WHERE type = 'symbol' AND json_extract(data, '$.symbol') IS NOT NULL
+         GROUP BY 1, 2",
+    )?;
+    let presets = counts(
+        conn,
+        "SELECT symbol, json_extract(highlight, '$.color'), COUNT(*)
+         FROM marking_presets WHERE symbol IS NOT NULL GROUP BY 1, 2",
+    )?;
+    Ok(symbols(conn)?
+        .into_iter()
+        .map(|symbol| LibraryEntry {
+            usage: SymbolUsage {
+                annotations: used(&annotations, &symbol),
+                presets: used(&presets, &symbol),
+            },
+            symbol,
+        })
+        .collect())
+}
+
+/// Add `symbol` to the library. Fails `Invalid` if its id is taken or its
+/// shape and color already mean something.
+pub(crate) fn create(
+    conn: &Connection,
+    mut symbol: LibrarySymbol,
+) -> Result<LibrarySymbol, DbError> {
+    check(&mut symbol, &symbols(conn)?)?;
+    data_rows::insert(conn, symbol)
+}
+
+/// Save an edited `symbol` over the stored one of its id.
+pub(crate) fn update(
+    conn: &Connection,
+    mut symbol: LibrarySymbol,
+) -> Result<LibrarySymbol, DbError> {
+    check(&mut symbol, &symbols(conn)?)?;
+    data_rows::replace(conn, symbol)
+}
+
+/// Remove a symbol from the library; false if there was none.
+pub(crate) fn delete(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    data_rows::delete(conn, "symbol_library", id)
+}
+
+/// The symbol library by meaning, with how often each symbol is drawn.
+#[tauri::command]
+pub async fn get_symbol_library(app: tauri::AppHandle) -> Result<Vec<LibraryEntry>, DbError> {
+    with_reader(&app, library).await
+}
+
+/// Add a symbol to the library.
+#[tauri::command]
+pub async fn create_library_symbol(
+    app: tauri::AppHandle,
+    symbol: LibrarySymbol,
+) -> Result<LibrarySymbol, DbError> {
+    with_connection(&app, move |conn| create(conn, symbol)).await
+}
+
+/// Save an edited library symbol.
+#[tauri::command]
+pub async fn update_library_symbol(
+    app: tauri::AppHandle,
+    symbol: LibrarySymbol,
+) -> Result<LibrarySymbol, DbError> {
+    with_connection(&app, move |conn| update(conn, symbol)).await
+}
+
+/// Remove a symbol from the library, leaving the marks drawn with it.
+#[tauri::command]
+pub async fn delete_library_symbol(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| delete(conn, &id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrated_test_connection, DbErrorKind};
+    use rusqlite::params;
+
+    fn symbol(id: &str, shape: &str, color: Option<&str>, meaning: &str) -> LibrarySymbol {
+        LibrarySymbol {
+            id: id.into(),
+            shape: shape.into(),
+            color: color.map(str::to_string),
+            meaning: meaning.into(),
+            ..Default::default()
+        }
+    }
+
+    fn mark(conn: &Connection, id: &str, symbol: &str, color: &str) {
+        conn.execute(
+            "INSERT INTO annotations (id, module_id, type, data, created_at, updated_at)
+             VALUES (?1, 'kjv', 'symbol', json_object('symbol', ?2, 'color', ?3), 'x', 'x')",
+            params![id, symbol, color],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn keeps_a_synced_library_with_usage_counts() {
+        let conn = migrated_test_connection();
+        create(&conn, symbol("god", " triangle ", Some("purple"), "God")).unwrap();
+        create(&conn, symbol("cov", "cloud", Some("red"), "covenant")).unwrap();
+        let err = create(&conn, symbol("dup", "Triangle", Some("Purple"), "Father")).unwrap_err();
+        assert_eq!(err.kind, DbErrorKind::Invalid);
+        assert!(err.message.contains("already means God"), "{}", err.message);
+        assert_eq!(
+            create(&conn, symbol("x", "star", None, " "))
+                .unwrap_err()
+                .kind,
+            DbErrorKind::Invalid
+        );
+
+        mark(&conn, "a1", "triangle", "purple");
+        mark(&conn, "a2", "triangle", "purple");
+        mark(&conn, "a3", "triangle", "blue");
+        let entries = library(&conn).unwrap();
+        let meanings: Vec<_> = entries.iter().map(|e| e.symbol.meaning.as_str()).collect();
+        assert_eq!(meanings, ["covenant", "God"]);
+        assert_eq!(entries[1].symbol.shape, "triangle");
+        assert_eq!(entries[1].usage.annotations, 2);
+        assert_eq!(entries[0].usage, SymbolUsage::default());
+
+        let mut edited = entries[1].symbol.clone();
+        edited.color = None;
+        edited.meaning = "Godhead".into();
+        update(&conn, edited).unwrap();
+        assert_eq!(library(&conn).unwrap()[1].usage.annotations, 3);
+    }
+}
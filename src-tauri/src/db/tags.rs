@@ -0,0 +1,879 @@
+//! Tags: a hierarchy of labels ("Attributes of God/Faithfulness") put on
+//! highlights and other annotations, notes and observation lists, in the
+//! synced `tags` and `tag_links` tables of migration 21.
+//!
+//! A tag stores its own name and its parent's id, so a path is read by
+//! walking up and a rename or move rewrites one row. Tags and links are
+//! generic data rows, carried by sync, merges and dumps like `places`. A
+//! link outlives its item (the item may come back from the trash); items
+//! that are gone are left out of `get_items_by_tag`.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use super::data_rows::{self, DataRow};
+use super::{with_connection, with_reader, DbError, DbErrorKind};
+use crate::sync::merge::entity_json_sql;
+
+/// Separates the levels of a tag path.
+const SEPARATOR: char = '/';
+
+/// Deepest a tag path may go; also what stops a walk up a damaged
+/// hierarchy that loops.
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Tag {
+    pub id: String,
+    /// This level's name, without its parents'.
+    pub name: String,
+    #[serde(rename = "parentId", default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// A highlight color name or `#rrggbb`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DataRow for Tag {
+    const TABLE: &'static str = "tags";
+    const NOUN: &'static str = "tag";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+/// What a tag can be put on.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ItemType {
+    #[serde(rename = "annotation")]
+    Annotation,
+    #[serde(rename = "note")]
+    Note,
+    /// An observation list, a list of verses on a key word.
+    #[serde(rename = "verseList")]
+    VerseList,
+}
+
+impl ItemType {
+    fn table(self) -> &'static str {
+        match self {
+            Self::Annotation => "annotations",
+            Self::Note => "notes",
+            Self::VerseList => "observation_lists",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Annotation => "annotation",
+            Self::Note => "note",
+            Self::VerseList => "verseList",
+        }
+    }
+}
+
+/// One tag on one item, as stored in `tag_links.data`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TagLink {
+    pub id: String,
+    #[serde(rename = "tagId")]
+    pub tag_id: String,
+    #[serde(rename = "itemType")]
+    pub item_type: ItemType,
+    #[serde(rename = "itemId")]
+    pub item_id: String,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+}
+
+impl DataRow for TagLink {
+    const TABLE: &'static str = "tag_links";
+    const NOUN: &'static str = "tag link";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+/// A tag with where it sits and how much it's used.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TagInfo {
+    #[serde(flatten)]
+    pub tag: Tag,
+    /// Its names from the top down, joined by `/`.
+    pub path: String,
+    pub depth: usize,
+    /// Items tagged with it directly, not through a subtag.
+    pub items: i64,
+}
+
+/// An item found by a tag.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TaggedItem {
+    #[serde(rename = "itemType")]
+    pub item_type: ItemType,
+    #[serde(rename = "itemId")]
+    pub item_id: String,
+    /// Paths of the tags it was found by: the tag asked for, or its subtags.
+    pub tags: Vec<String>,
+    /// The item as the TS layer stores it.
+    pub item: Value,
+}
+
+/// The levels of `path`, trimmed. Fails `Invalid` on an empty level.
+fn levels(path: &str) -> Result<Vec<String>, DbError> {
+    let levels: Vec<String> = path
+        .split(SEPARATOR)
+        .map(|l| l.trim().to_string())
+        .collect();
+    if levels.iter().any(String::is_empty) {
+        return Err(DbError::invalid(format!(
+            "`{path}` is not a tag path: no level may be empty"
+        )));
+    }
+    if levels.len() > MAX_DEPTH {
+        return Err(DbError::invalid(format!(
+            "Tags nest at most {MAX_DEPTH} levels deep"
+        )));
+    }
+    Ok(levels)
+}
+
+fn link_id(tag_id: &str, item_type: ItemType, item_id: &str) -> String {
+    format!("{tag_id}|{}|{item_id}", item_type.name())
+}
+
+/// The whole hierarchy, by id. Rows that don't parse are skipped.
+pub(crate) struct Tags {
+    by_id: HashMap<String, Tag>,
+}
+
+impl Tags {
+    pub(crate) fn load(conn: &Connection) -> Result<Self, DbError> {
+        let by_id = data_rows::all::<Tag>(conn)?
+            .into_iter()
+            .map(|tag| (tag.id.clone(), tag))
+            .collect();
+        Ok(Self { by_id })
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<&Tag> {
+        self.by_id.get(id)
+    }
+
+    /// `id` and its ancestors, nearest first. A missing parent ends the
+    /// walk, leaving its children at the top.
+    pub(crate) fn lineage(&self, id: &str) -> Vec<&Tag> {
+        let mut lineage = Vec::new();
+        let mut next = self.get(id);
+        while let Some(tag) = next {
+            if lineage.len() == MAX_DEPTH {
+                break;
+            }
+            lineage.push(tag);
+            next = tag.parent_id.as_deref().and_then(|p| self.get(p));
+        }
+        lineage
+    }
+
+    pub(crate) fn path(&self, id: &str) -> String {
+        let mut names: Vec<&str> = self.lineage(id).iter().map(|t| t.name.as_str()).collect();
+        names.reverse();
+        names.join("/")
+    }
+
+    fn children(&self, id: Option<&str>) -> Vec<&Tag> {
+        self.by_id
+            .values()
+            .filter(|t| t.parent_id.as_deref() == id && Some(t.id.as_str()) != id)
+            .collect()
+    }
+
+    fn child_named(&self, parent: Option<&str>, name: &str) -> Option<&Tag> {
+        self.children(parent)
+            .into_iter()
+            .filter(|t| t.name.eq_ignore_ascii_case(name))
+            .min_by(|a, b| a.id.cmp(&b.id))
+    }
+
+    /// `id` and every tag under it.
+    pub(crate) fn subtree(&self, id: &str) -> Vec<String> {
+        let mut found = vec![id.to_string()];
+        let mut at = 0;
+        while at < found.len() {
+            let children: Vec<String> = self
+                .children(Some(found[at].as_str()))
+                .into_iter()
+                .map(|t| t.id.clone())
+                .filter(|c| !found.contains(c))
+                .collect();
+            found.extend(children);
+            at += 1;
+        }
+        found
+    }
+
+    /// The tag `tag` names: its id, or its path.
+    pub(crate) fn find(&self, tag: &str) -> Option<&Tag> {
+        if let Some(found) = self.get(tag) {
+            return Some(found);
+        }
+        let mut at: Option<&Tag> = None;
+        for level in levels(tag).ok()? {
+            at = Some(self.child_named(at.map(|t| t.id.as_str()), &level)?);
+        }
+        at
+    }
+
+    fn resolve(&self, tag: &str) -> Result<&Tag, DbError> {
+        self.find(tag)
+            .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("No tag `{tag}`")))
+    }
+}
+
+/// Store `tag` as of now, keeping when it was created.
+fn save_tag(conn: &Connection, tags: &mut Tags, mut tag: Tag) -> Result<Tag, DbError> {
+    data_rows::save(conn, &mut tag)?;
+    tags.by_id.insert(tag.id.clone(), tag.clone());
+    Ok(tag)
+}
+
+/// A new tag id. Tags made on two devices before they sync get different
+/// ids; merging joins them.
+fn new_id(conn: &Connection) -> Result<String, DbError> {
+    Ok(
+        conn.query_row("SELECT 'tag-' || lower(hex(randomblob(8)))", [], |row| {
+            row.get(0)
+        })?,
+    )
+}
+
+/// The tag at `path`, made with any levels it lacks.
+fn ensure(conn: &Connection, tags: &mut Tags, path: &[String]) -> Result<Option<Tag>, DbError> {
+    let mut parent: Option<Tag> = None;
+    for level in path {
+        let parent_id = parent.as_ref().map(|p| p.id.clone());
+        let existing = tags.child_named(parent_id.as_deref(), level).cloned();
+        parent = Some(match existing {
+            Some(tag) => tag,
+            None => {
+                let tag = Tag {
+                    id: new_id(conn)?,
+                    name: level.clone(),
+                    parent_id,
+                    ..Default::default()
+                };
+                save_tag(conn, tags, tag)?
+            }
+        });
+    }
+    Ok(parent)
+}
+
+fn links_of(conn: &Connection, tag_ids: &[String]) -> Result<Vec<TagLink>, DbError> {
+    let mut stmt =
+        conn.prepare_cached("SELECT data FROM tag_links WHERE json_extract(data, '$.tagId') = ?1")?;
+    let mut links = Vec::new();
+    for id in tag_ids {
+        let rows = stmt
+            .query_map([id], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        links.extend(
+            rows.iter()
+                .filter_map(|d| serde_json::from_str::<TagLink>(d).ok()),
+        );
+    }
+    Ok(links)
+}
+
+/// Put tag `tag_id` on an item; false if it was already there.
+fn link(
+    conn: &Connection,
+    tag_id: &str,
+    item_type: ItemType,
+    item_id: &str,
+) -> Result<bool, DbError> {
+    let id = link_id(tag_id, item_type, item_id);
+    if data_rows::created_at(conn, "tag_links", &id)?.is_some() {
+        return Ok(false);
+    }
+    let mut link = TagLink {
+        id,
+        tag_id: tag_id.to_string(),
+        item_type,
+        item_id: item_id.to_string(),
+        created_at: String::new(),
+        updated_at: String::new(),
+    };
+    data_rows::save(conn, &mut link)?;
+    Ok(true)
+}
+
+fn info(conn: &Connection, tags: &Tags, tag: &Tag) -> Result<TagInfo, DbError> {
+    let items: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM tag_links WHERE json_extract(data, '$.tagId') = ?1",
+        [&tag.id],
+        |row| row.get(0),
+    )?;
+    Ok(TagInfo {
+        path: tags.path(&tag.id),
+        depth: tags.lineage(&tag.id).len() - 1,
+        items,
+        tag: tag.clone(),
+    })
+}
+
+/// Every tag in path order, with its direct use.
+pub(crate) fn list(conn: &Connection) -> Result<Vec<TagInfo>, DbError> {
+    let tags = Tags::load(conn)?;
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    let mut stmt =
+        conn.prepare("SELECT json_extract(data, '$.tagId'), COUNT(*) FROM tag_links GROUP BY 1")?;
+    for row in stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })? {
+        let (id, n) = row?;
+        counts.insert(id, n);
+    }
+    let mut list: Vec<TagInfo> = tags
+        .by_id
+        .values()
+        .map(|tag| TagInfo {
+            path: tags.path(&tag.id),
+            depth: tags.lineage(&tag.id).len() - 1,
+            items: counts.get(&tag.id).copied().unwrap_or(0),
+            tag: tag.clone(),
+        })
+        .collect();
+    list.sort_by_cached_key(|t| (t.path.to_lowercase(), t.tag.id.clone()));
+    Ok(list)
+}
+
+/// Make the tag at `path`, with any parents it lacks. Fails `Invalid` if
+/// it exists.
+pub(crate) fn create(
+    conn: &mut Connection,
+    path: &str,
+    color: Option<String>,
+) -> Result<TagInfo, DbError> {
+    let levels = levels(path)?;
+    let tx = conn.transaction()?;
+    let mut tags = Tags::load(&tx)?;
+    if let Some(existing) = tags.find(path) {
+        return Err(DbError::invalid(format!(
+            "The tag `{}` already exists",
+            tags.path(&existing.id)
+        )));
+    }
+    let mut tag = ensure(&tx, &mut tags, &levels)?.expect("a path has a level");
+    if color.is_some() {
+        tag.color = color;
+        tag = save_tag(&tx, &mut tags, tag)?;
+    }
+    let info = info(&tx, &tags, &tag)?;
+    tx.commit()?;
+    Ok(info)
+}
+
+/// Set or clear a tag's color.
+pub(crate) fn set_color(
+    conn: &Connection,
+    tag: &str,
+    color: Option<String>,
+) -> Result<TagInfo, DbError> {
+    let mut tags = Tags::load(conn)?;
+    let mut found = tags.resolve(tag)?.clone();
+    found.color = color.filter(|c| !c.trim().is_empty());
+    let saved = save_tag(conn, &mut tags, found)?;
+    info(conn, &tags, &saved)
+}
+
+/// Rename `tag`, or move it with everything under it: `path` is where it
+/// goes, and its parents are made if they don't exist. Fails `Invalid` when
+/// another tag is already there (merge them instead) or it would go under
+/// itself.
+pub(crate) fn rename(conn: &mut Connection, tag: &str, path: &str) -> Result<TagInfo, DbError> {
+    let levels = levels(path)?;
+    let tx = conn.transaction()?;
+    let mut tags = Tags::load(&tx)?;
+    let mut moving = tags.resolve(tag)?.clone();
+    let (name, parents) = levels.split_last().expect("a path has a level");
+    let parent = ensure(&tx, &mut tags, parents)?;
+    let parent_id = parent.map(|p| p.id);
+    if let Some(parent) = &parent_id {
+        if tags.subtree(&moving.id).contains(parent) {
+            return Err(DbError::invalid(format!(
+                "`{}` can't go under itself",
+                tags.path(&moving.id)
+            )));
+        }
+    }
+    if let Some(taken) = tags.child_named(parent_id.as_deref(), name) {
+        if taken.id != moving.id {
+            return Err(DbError::invalid(format!(
+                "The tag `{}` already exists; merge the two instead",
+                tags.path(&taken.id)
+            )));
+        }
+    }
+    moving.name = name.clone();
+    moving.parent_id = parent_id;
+    let saved = save_tag(&tx, &mut tags, moving)?;
+    let info = info(&tx, &tags, &saved)?;
+    tx.commit()?;
+    Ok(info)
+}
+
+/// Fold `from` into `into`: its items are tagged `into` instead, its
+/// subtags move under `into` (joining any of the same name there), and it
+/// is deleted. Returns how many links were moved.
+fn fold(conn: &Connection, tags: &mut Tags, from: &str, into: &str) -> Result<usize, DbError> {
+    let mut moved = 0;
+    for old in links_of(conn, &[from.to_string()])? {
+        if link(conn, into, old.item_type, &old.item_id)? {
+            moved += 1;
+        }
+        data_rows::delete(conn, "tag_links", &old.id)?;
+    }
+    let children: Vec<Tag> = tags.children(Some(from)).into_iter().cloned().collect();
+    for mut child in children {
+        match tags
+            .child_named(Some(into), &child.name)
+            .map(|t| t.id.clone())
+        {
+            Some(same) => moved += fold(conn, tags, &child.id, &same)?,
+            None => {
+                child.parent_id = Some(into.to_string());
+                save_tag(conn, tags, child)?;
+            }
+        }
+    }
+    data_rows::delete(conn, "tags", from)?;
+    tags.by_id.remove(from);
+    Ok(moved)
+}
+
+/// Merge tag `from` into tag `into`, returning `into` as it is after.
+pub(crate) fn merge(conn: &mut Connection, from: &str, into: &str) -> Result<TagInfo, DbError> {
+    let tx = conn.transaction()?;
+    let mut tags = Tags::load(&tx)?;
+    let from = tags.resolve(from)?.id.clone();
+    let into = tags.resolve(into)?.id.clone();
+    if from == into {
+        return Err(DbError::invalid("A tag can't be merged into itself"));
+    }
+    if tags.subtree(&from).contains(&into) {
+        return Err(DbError::invalid(format!(
+            "`{}` is under `{}`; rename it instead",
+            tags.path(&into),
+            tags.path(&from)
+        )));
+    }
+    fold(&tx, &mut tags, &from, &into)?;
+    let merged = tags.resolve(&into)?.clone();
+    let info = info(&tx, &tags, &merged)?;
+    tx.commit()?;
+    Ok(info)
+}
+
+/// Move the items `pick` accepts from tag `from` to tag `to` (an id, or a
+/// path, made if new), inside the caller's transaction; the tags stay.
+/// Returns the items moved, in link order.
+pub(crate) fn relink(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+    mut pick: impl FnMut(ItemType, &str) -> bool,
+) -> Result<Vec<(ItemType, String)>, DbError> {
+    let mut tags = Tags::load(conn)?;
+    let from = tags.resolve(from)?.id.clone();
+    let to = match tags.find(to) {
+        Some(found) => found.id.clone(),
+        None => {
+            ensure(conn, &mut tags, &levels(to)?)?
+                .expect("a path has a level")
+                .id
+        }
+    };
+    if from == to {
+        return Err(DbError::invalid(
+            "Items can't be moved to the tag they are on",
+        ));
+    }
+    let mut moved = Vec::new();
+    let mut old_links = links_of(conn, &[from])?;
+    old_links.sort_by(|a, b| a.id.cmp(&b.id));
+    for old in old_links {
+        if !pick(old.item_type, &old.item_id) {
+            continue;
+        }
+        link(conn, &to, old.item_type, &old.item_id)?;
+        data_rows::delete(conn, "tag_links", &old.id)?;
+        moved.push((old.item_type, old.item_id));
+    }
+    Ok(moved)
+}
+
+/// Delete `tag`, its subtags and their links (not the items). Returns how
+/// many tags went.
+pub(crate) fn delete(conn: &mut Connection, tag: &str) -> Result<usize, DbError> {
+    let tx = conn.transaction()?;
+    let tags = Tags::load(&tx)?;
+    let Some(found) = tags.find(tag) else {
+        return Ok(0);
+    };
+    let subtree = tags.subtree(&found.id);
+    for old in links_of(&tx, &subtree)? {
+        data_rows::delete(&tx, "tag_links", &old.id)?;
+    }
+    for id in &subtree {
+        data_rows::delete(&tx, "tags", id)?;
+    }
+    tx.commit()?;
+    Ok(subtree.len())
+}
+
+/// Tag an item with `tag` (an id, or a path, made if new). Fails
+/// `NotFound` if there is no such item.
+pub(crate) fn attach(
+    conn: &mut Connection,
+    tag: &str,
+    item_type: ItemType,
+    item_id: &str,
+) -> Result<TagInfo, DbError> {
+    let tx = conn.transaction()?;
+    let exists = tx
+        .query_row(
+            &format!("SELECT 1 FROM {} WHERE id = ?1", item_type.table()),
+            [item_id],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if !exists {
+        return Err(DbError::new(
+            DbErrorKind::NotFound,
+            format!("No {} {item_id}", item_type.name()),
+        ));
+    }
+    let mut tags = Tags::load(&tx)?;
+    let found = match tags.find(tag) {
+        Some(found) => found.clone(),
+        None => ensure(&tx, &mut tags, &levels(tag)?)?.expect("a path has a level"),
+    };
+    link(&tx, &found.id, item_type, item_id)?;
+    let info = info(&tx, &tags, &found)?;
+    tx.commit()?;
+    Ok(info)
+}
+
+/// Take `tag` off an item; false if it wasn't on it.
+pub(crate) fn detach(
+    conn: &Connection,
+    tag: &str,
+    item_type: ItemType,
+    item_id: &str,
+) -> Result<bool, DbError> {
+    let tags = Tags::load(conn)?;
+    let Some(found) = tags.find(tag) else {
+        return Ok(false);
+    };
+    data_rows::delete(conn, "tag_links", &link_id(&found.id, item_type, item_id))
+}
+
+/// The tags on an item, in path order.
+pub(crate) fn item_tags(
+    conn: &Connection,
+    item_type: ItemType,
+    item_id: &str,
+) -> Result<Vec<TagInfo>, DbError> {
+    let tags = Tags::load(conn)?;
+    let ids: Vec<String> = conn
+        .prepare(
+            "SELECT json_extract(data, '$.tagId') FROM tag_links
+             WHERE json_extract(data, '$.itemType') = ?1 AND json_extract(data, '$.itemId') = ?2",
+        )?
+        .query_map(params![item_type.name(), item_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut found = Vec::new();
+    for id in ids {
+        if let Some(tag) = tags.get(&id) {
+            found.push(info(conn, &tags, tag)?);
+        }
+    }
+    found.sort_by_cached_key(|t| t.path.to_lowercase());
+    Ok(found)
+}
+
+/// Items tagged `tag`, and with `recursive` those tagged with any tag under
+/// it, each once: annotations, then notes, then lists, newest first.
+pub(crate) fn items(
+    conn: &Connection,
+    tag: &str,
+    recursive: bool,
+) -> Result<Vec<TaggedItem>, DbError> {
+    let tags = Tags::load(conn)?;
+    let found = tags.resolve(tag)?;
+    let ids = if recursive {
+        tags.subtree(&found.id)
+    } else {
+        vec![found.id.clone()]
+    };
+    let mut by_item: BTreeMap<(ItemType, String), Vec<String>> = BTreeMap::new();
+    for link in links_of(conn, &ids)? {
+        by_item
+            .entry((link.item_type, link.item_id))
+            .or_default()
+            .push(tags.path(&link.tag_id));
+    }
+    let mut items = Vec::new();
+    for ((item_type, item_id), mut paths) in by_item {
+        let sql = format!(
+            "SELECT {}, updated_at FROM {} WHERE id = ?1",
+            entity_json_sql(item_type.table()),
+            item_type.table()
+        );
+        let row: Option<(String, String)> = conn
+            .query_row(&sql, [&item_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?;
+        let Some((data, updated_at)) = row else {
+            continue;
+        };
+        paths.sort();
+        paths.dedup();
+        items.push((
+            updated_at,
+            TaggedItem {
+                item_type,
+                item_id,
+                tags: paths,
+                item: serde_json::from_str(&data).unwrap_or(Value::Null),
+            },
+        ));
+    }
+    items.sort_by(|(a_at, a), (b_at, b)| a.item_type.cmp(&b.item_type).then(b_at.cmp(a_at)));
+    Ok(items.into_iter().map(|(_, item)| item).collect())
+}
+
+/// Every tag, in path order, with how many items carry it directly.
+#[tauri::command]
+pub async fn list_tags(app: tauri::AppHandle) -> Result<Vec<TagInfo>, DbError> {
+    with_reader(&app, list).await
+}
+
+/// Make a tag; a nested path ("Attributes of God/Faithfulness") makes its
+/// parents too.
+#[tauri::command]
+pub async fn create_tag(
+    app: tauri::AppHandle,
+    path: String,
+    color: Option<String>,
+) -> Result<TagInfo, DbError> {
+    with_connection(&app, move |conn| create(conn, &path, color)).await
+}
+
+/// Set or clear the color of a tag, given by id or path.
+#[tauri::command]
+pub async fn update_tag(
+    app: tauri::AppHandle,
+    tag: String,
+    color: Option<String>,
+) -> Result<TagInfo, DbError> {
+    with_connection(&app, move |conn| set_color(conn, &tag, color)).await
+}
+
+/// Rename or move a tag, with its subtags, to `path`.
+#[tauri::command]
+pub async fn rename_tag(
+    app: tauri::AppHandle,
+    tag: String,
+    path: String,
+) -> Result<TagInfo, DbError> {
+    with_connection(&app, move |conn| rename(conn, &tag, &path)).await
+}
+
+/// Merge tag `from` into `into`, which keeps its items and subtags.
+#[tauri::command]
+pub async fn merge_tags(
+    app: tauri::AppHandle,
+    from: String,
+    into: String,
+) -> Result<TagInfo, DbError> {
+    with_connection(&app, move |conn| merge(conn, &from, &into)).await
+}
+
+/// Delete a tag and its subtags, untagging their items.
+#[tauri::command]
+pub async fn delete_tag(app: tauri::AppHandle, tag: String) -> Result<usize, DbError> {
+    with_connection(&app, move |conn| delete(conn, &tag)).await
+}
+
+#[tauri::command]
+pub async fn tag_item(
+    app: tauri::AppHandle,
+    tag: String,
+    item_type: ItemType,
+    item_id: String,
+) -> Result<TagInfo, DbError> {
+    with_connection(&app, move |conn| attach(conn, &tag, item_type, &item_id)).await
+}
+
+#[tauri::command]
+pub async fn untag_item(
+    app: tauri::AppHandle,
+    tag: String,
+    item_type: ItemType,
+    item_id: String,
+) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| detach(conn, &tag, item_type, &item_id)).await
+}
+
+#[tauri::command]
+pub async fn get_item_tags(
+    app: tauri::AppHandle,
+    item_type: ItemType,
+    item_id: String,
+) -> Result<Vec<TagInfo>, DbError> {
+    with_reader(&app, move |conn| item_tags(conn, item_type, &item_id)).await
+}
+
+/// Items tagged `tag` (an id or path), and with `recursive` its subtags'.
+#[tauri::command]
+pub async fn get_items_by_tag(
+    app: tauri::AppHandle,
+    tag: String,
+    recursive: bool,
+) -> Result<Vec<TaggedItem>, DbError> {
+    with_reader(&app, move |conn| items(conn, &tag, recursive)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+
+    fn ids(items: &[TaggedItem]) -> Vec<&str> {
+        items.iter().map(|i| i.item_id.as_str()).collect()
+    }
+
+    #[test]
+    fn nests_merges_and_finds_items_by_tag() {
+        let mut conn = migrated_test_connection();
+        conn.execute_batch(
+            "INSERT INTO annotations (id, module_id, type, data, created_at, updated_at)
+             VALUES ('h1', 'kjv', 'highlight', '{\"id\":\"h1\"}', 'x', '2025-01-02');
+             INSERT INTO notes (id, module_id, ref, content, created_at, updated_at)
+             VALUES ('n1', 'kjv', '{}', 'He is faithful', 'x', '2025-01-01');",
+        )
+        .unwrap();
+        let faithfulness = create(
+            &mut conn,
+            "Attributes of God / Faithfulness",
+            Some("blue".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            (faithfulness.path.as_str(), faithfulness.depth),
+            ("Attributes of God/Faithfulness", 1)
+        );
+        assert_eq!(
+            create(&mut conn, "attributes of god", None)
+                .unwrap_err()
+                .kind,
+            DbErrorKind::Invalid
+        );
+        assert_eq!(
+            create(&mut conn, "A//B", None).unwrap_err().kind,
+            DbErrorKind::Invalid
+        );
+
+        attach(&mut conn, &faithfulness.tag.id, ItemType::Annotation, "h1").unwrap();
+        attach(&mut conn, "Attributes of God/Love", ItemType::Note, "n1").unwrap();
+        attach(&mut conn, "Attributes of God", ItemType::Note, "n1").unwrap();
+        assert_eq!(
+            attach(&mut conn, "Love", ItemType::Note, "gone")
+                .unwrap_err()
+                .kind,
+            DbErrorKind::NotFound
+        );
+
+        assert_eq!(
+            ids(&items(&conn, "Attributes of God", false).unwrap()),
+            ["n1"]
+        );
+        let all = items(&conn, "attributes of god", true).unwrap();
+        assert_eq!(ids(&all), ["h1", "n1"]);
+        assert_eq!(all[1].tags, ["Attributes of God", "Attributes of God/Love"]);
+        assert_eq!(all[1].item["content"], "He is faithful");
+
+        // Moving a tag keeps its items; a taken path has to be merged.
+        let moved = rename(&mut conn, "Attributes of God/Love", "Themes/Love").unwrap();
+        assert_eq!((moved.path.as_str(), moved.items), ("Themes/Love", 1));
+        assert_eq!(
+            rename(&mut conn, "Themes/Love", "Attributes of God/Faithfulness")
+                .unwrap_err()
+                .kind,
+            DbErrorKind::Invalid
+        );
+        assert_eq!(
+            rename(&mut conn, "Themes", "Themes/Love/Deeper")
+                .unwrap_err()
+                .kind,
+            DbErrorKind::Invalid
+        );
+
+        create(&mut conn, "Covenant/Faithfulness", None).unwrap();
+        attach(
+            &mut conn,
+            "Covenant/Faithfulness",
+            ItemType::Annotation,
+            "h1",
+        )
+        .unwrap();
+        attach(&mut conn, "Covenant/Faithfulness", ItemType::Note, "n1").unwrap();
+        let merged = merge(&mut conn, "Covenant", "Attributes of God").unwrap();
+        assert_eq!(merged.path, "Attributes of God");
+        let paths: Vec<String> = list(&conn).unwrap().into_iter().map(|t| t.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "Attributes of God",
+                "Attributes of God/Faithfulness",
+                "Themes",
+                "Themes/Love"
+            ]
+        );
+        let faithful = items(&conn, "Attributes of God/Faithfulness", false).unwrap();
+        assert_eq!(ids(&faithful), ["h1", "n1"]);
+        assert_eq!(
+            item_tags(&conn, ItemType::Annotation, "h1").unwrap().len(),
+            1
+        );
+
+        assert!(detach(&conn, "Themes/Love", ItemType::Note, "n1").unwrap());
+        assert_eq!(delete(&mut conn, "Attributes of God").unwrap(), 2);
+        let links: i64 = conn
+            .query_row("SELECT COUNT(*) FROM tag_links", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(links, 0);
+    }
+}
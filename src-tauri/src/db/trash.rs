@@ -0,0 +1,408 @@
+//! Trash for deleted notes and annotations.
+//!
+//! Migration 16 copies each deleted row into `trash` from a trigger, so every
+//! local delete path — the TS layer, bulk commands — is covered without the
+//! live tables growing a `deleted_at` column that every query and the sync
+//! merge would have to filter on. Deletes that sync applies or an undo replays
+//! are skipped (migration 17). Restoring writes the row back as a fresh local
+//! edit, so it also wins over the tombstone on other devices. Items older than
+//! 30 days are purged.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::{device_id, now_iso, record_change, with_connection, DbError, DbErrorKind};
+
+/// Days an item stays restorable.
+pub(crate) const RETENTION_DAYS: i64 = 30;
+
+/// Tables whose rows [`write_row`] can put back, and the columns saved for
+/// them: by the trash triggers (0016_trash.sql) for notes and annotations,
+/// and by the undo journal (0017, 0030_tag_link_undo.sql), which also keeps
+/// `tag_links` rows. Tag links have no trash; they come back through undo.
+const SAVED_ROW_COLUMNS: &[(&str, &[&str])] = &[
+    (
+        "notes",
+        &[
+            "id",
+            "module_id",
+            "ref",
+            "range",
+            "content",
+            "created_at",
+            "updated_at",
+            "sync_status",
+            "device_id",
+        ],
+    ),
+    (
+        "annotations",
+        &[
+            "id",
+            "module_id",
+            "type",
+            "data",
+            "preset_id",
+            "created_at",
+            "updated_at",
+            "sync_status",
+            "device_id",
+        ],
+    ),
+    (
+        "tag_links",
+        &[
+            "id",
+            "data",
+            "created_at",
+            "updated_at",
+            "sync_status",
+            "device_id",
+        ],
+    ),
+];
+
+#[derive(Debug, Serialize)]
+pub struct TrashItem {
+    pub id: i64,
+    pub table: String,
+    #[serde(rename = "rowId")]
+    pub row_id: String,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: String,
+    /// The deleted note or annotation as the TS layer shapes it.
+    pub item: Value,
+}
+
+fn parse_json(value: &Value) -> Value {
+    value
+        .as_str()
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or(Value::Null)
+}
+
+fn columns(table: &str) -> Result<&'static [&'static str], DbError> {
+    SAVED_ROW_COLUMNS
+        .iter()
+        .find(|(name, _)| *name == table)
+        .map(|(_, columns)| *columns)
+        .ok_or_else(|| DbError::invalid(format!("Cannot restore rows of `{table}`")))
+}
+
+/// The TS object for a trashed row — what `saveNote`/`saveAnnotation` would
+/// have logged to `change_log` for it.
+pub(super) fn item_object(table: &str, row: &Value) -> Value {
+    match table {
+        "annotations" | "tag_links" => parse_json(&row["data"]),
+        _ => {
+            let mut note = json!({
+                "id": row["id"],
+                "moduleId": row["module_id"],
+                "ref": parse_json(&row["ref"]),
+                "content": row["content"],
+                "createdAt": row["created_at"],
+                "updatedAt": row["updated_at"],
+            });
+            if let Some(range) = Some(parse_json(&row["range"])).filter(|r| !r.is_null()) {
+                note["range"] = range;
+            }
+            note
+        }
+    }
+}
+
+fn to_item(id: i64, table: String, row_id: String, deleted_at: String, row: &str) -> TrashItem {
+    let row: Value = serde_json::from_str(row).unwrap_or(Value::Null);
+    TrashItem {
+        item: item_object(&table, &row),
+        id,
+        table,
+        row_id,
+        deleted_at,
+    }
+}
+
+/// Drop items deleted more than `RETENTION_DAYS` ago.
+pub(crate) fn purge_expired(conn: &Connection) -> Result<usize, DbError> {
+    Ok(conn.execute(
+        "DELETE FROM trash
+         WHERE deleted_at < strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?1)",
+        [format!("-{RETENTION_DAYS} days")],
+    )?)
+}
+
+/// Restorable items, most recently deleted first.
+pub(crate) fn list(conn: &Connection) -> Result<Vec<TrashItem>, DbError> {
+    purge_expired(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, table_name, row_id, deleted_at, row FROM trash
+         ORDER BY deleted_at DESC, id DESC",
+    )?;
+    let items = stmt
+        .query_map([], |row| {
+            Ok(to_item(
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                &row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(items)
+}
+
+/// Put trash item `id` back into its table as a new local edit.
+pub(crate) fn restore(conn: &mut Connection, id: i64) -> Result<TrashItem, DbError> {
+    let device = device_id(conn)?;
+    let tx = conn.transaction()?;
+    let (table, row_id, deleted_at, row): (String, String, String, String) = tx
+        .query_row(
+            "SELECT table_name, row_id, deleted_at, row FROM trash WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?
+        .ok_or_else(|| DbError::new(DbErrorKind::NotFound, format!("Trash item {id} not found")))?;
+    // Only known tables reach the SQL below.
+    columns(&table)?;
+    let exists: bool = tx.query_row(
+        &format!("SELECT COUNT(*) > 0 FROM \"{table}\" WHERE id = ?1"),
+        [&row_id],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Err(DbError::invalid(format!(
+            "`{row_id}` was re-created after it was deleted"
+        )));
+    }
+
+    let now = now_iso(&tx)?;
+    let row: Value = serde_json::from_str(&row)
+        .map_err(|e| DbError::invalid(format!("Trash item {id} is unreadable: {e}")))?;
+    let item = write_row(&tx, &table, row, &now, &device)?;
+    tx.execute("DELETE FROM trash WHERE id = ?1", [id])?;
+    tx.commit()?;
+    Ok(TrashItem {
+        id,
+        table,
+        row_id,
+        deleted_at,
+        item,
+    })
+}
+
+/// Write a saved row (a JSON object of its columns) back into `table` as a
+/// local edit made `now`, logging it for sync. Returns the TS-shaped item.
+pub(super) fn write_row(
+    conn: &Connection,
+    table: &str,
+    mut row: Value,
+    now: &str,
+    device: &str,
+) -> Result<Value, DbError> {
+    let columns = columns(table)?;
+    let row_id = row["id"]
+        .as_str()
+        .ok_or_else(|| DbError::invalid(format!("Saved `{table}` row has no id")))?
+        .to_string();
+    row["updated_at"] = now.into();
+    row["sync_status"] = "pending".into();
+    row["device_id"] = device.into();
+    if table != "notes" {
+        let mut data = parse_json(&row["data"]);
+        if data.is_object() {
+            data["updatedAt"] = now.into();
+            row["data"] = data.to_string().into();
+        }
+    }
+
+    let list = columns.join(", ");
+    let values = columns
+        .iter()
+        .map(|c| format!("json_extract(?1, '$.{c}')"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    conn.execute(
+        &format!("INSERT OR REPLACE INTO \"{table}\" ({list}) SELECT {values}"),
+        [row.to_string()],
+    )?;
+    let item = item_object(table, &row);
+    record_change(
+        conn,
+        table,
+        "upsert",
+        &row_id,
+        Some(&item.to_string()),
+        now,
+        device,
+    )?;
+    Ok(item)
+}
+
+/// Permanently delete trash items deleted before `older_than` (RFC 3339), or
+/// all of them.
+pub(crate) fn empty(conn: &Connection, older_than: Option<&str>) -> Result<usize, DbError> {
+    Ok(conn.execute(
+        "DELETE FROM trash WHERE ?1 IS NULL OR deleted_at < ?1",
+        params![older_than],
+    )?)
+}
+
+/// Deleted notes and annotations that can still be restored.
+#[tauri::command]
+pub async fn list_trash(app: tauri::AppHandle) -> Result<Vec<TrashItem>, DbError> {
+    with_connection(&app, |conn| list(conn)).await
+}
+
+/// Restore trash item `id`, returning what was restored.
+#[tauri::command]
+pub async fn restore_item(app: tauri::AppHandle, id: i64) -> Result<TrashItem, DbError> {
+    with_connection(&app, move |conn| restore(conn, id)).await
+}
+
+/// Permanently delete trashed items (only those deleted before `older_than`
+/// if given). Returns how many were removed.
+#[tauri::command]
+pub async fn empty_trash(
+    app: tauri::AppHandle,
+    older_than: Option<String>,
+) -> Result<usize, DbError> {
+    with_connection(&app, move |conn| empty(conn, older_than.as_deref())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrated_test_connection, undo};
+
+    fn add_note(conn: &Connection, id: &str) {
+        conn.execute(
+            "INSERT INTO notes VALUES (?1, 'kjv', '{\"book\":\"John\",\"chapter\":3,\"verse\":16}',
+                NULL, 'For God so loved', '2025-01-01T00:00:00.000Z',
+                '2025-01-02T00:00:00.000Z', 'synced', 'dev-other')",
+            [id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn deletes_land_in_the_trash_but_saves_do_not() {
+        let mut conn = migrated_test_connection();
+        add_note(&conn, "n1");
+        // How the app saves: replacing the row must not trash the old one.
+        conn.execute(
+            "INSERT OR REPLACE INTO notes VALUES ('n1', 'kjv', '{\"book\":\"John\",\"chapter\":3,\"verse\":16}',
+                NULL, 'Edited', '2025-01-01T00:00:00.000Z', '2025-01-03T00:00:00.000Z', 'pending', 'dev-local')",
+            [],
+        )
+        .unwrap();
+        assert!(list(&conn).unwrap().is_empty());
+
+        conn.execute("DELETE FROM notes WHERE id = 'n1'", [])
+            .unwrap();
+        let items = list(&conn).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].table, "notes");
+        assert_eq!(items[0].item["content"], "Edited");
+        assert_eq!(items[0].item["ref"]["verse"], 16);
+
+        // Nor do another device's deletes, or an undo taking back a create.
+        add_note(&conn, "n2");
+        assert!(undo::delete_synced(&mut conn, "notes", "n2").unwrap());
+        conn.execute("UPDATE undo_journal SET at = '2025-01-01'", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO notes VALUES ('n3', 'kjv', '{\"book\":\"John\",\"chapter\":3,\"verse\":17}',
+                NULL, 'Not to condemn', '2025-01-01T00:00:00.000Z', '2025-01-03T00:00:00.000Z', 'pending', 'dev-local')",
+            [],
+        )
+        .unwrap();
+        undo::replay(&mut conn, false).unwrap().unwrap();
+        assert_eq!(list(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn restore_reinserts_the_row_as_a_new_local_edit() {
+        let mut conn = migrated_test_connection();
+        add_note(&conn, "n1");
+        conn.execute("DELETE FROM notes WHERE id = 'n1'", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO sync_tombstones VALUES ('notes', 'n1', '2025-02-01T00:00:00.000Z', 'dev-other')",
+            [],
+        )
+        .unwrap();
+        let id = list(&conn).unwrap()[0].id;
+
+        let restored = restore(&mut conn, id).unwrap();
+        assert_eq!(restored.item["moduleId"], "kjv");
+        let (content, status, device, updated): (String, String, String, String) = conn
+            .query_row(
+                "SELECT content, sync_status, device_id, updated_at FROM notes WHERE id = 'n1'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            (content.as_str(), status.as_str(), device.as_str()),
+            ("For God so loved", "pending", "dev-local")
+        );
+        assert!(updated.as_str() > "2025-02-01", "must beat the tombstone");
+        let tombstones: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sync_tombstones", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(tombstones, 0);
+        assert!(list(&conn).unwrap().is_empty());
+        assert_eq!(
+            restore(&mut conn, id).unwrap_err().kind,
+            DbErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn restoring_an_annotation_refreshes_its_data() {
+        let mut conn = migrated_test_connection();
+        crate::db::annotations::bulk_insert(
+            &mut conn,
+            &[json!({
+                "id": "a1", "moduleId": "kjv", "type": "highlight",
+                "startRef": { "book": "John", "chapter": 3, "verse": 16 },
+                "endRef": { "book": "John", "chapter": 3, "verse": 16 },
+                "color": "yellow", "updatedAt": "2025-01-01T00:00:00.000Z"
+            })],
+        )
+        .unwrap();
+        conn.execute("DELETE FROM annotations", []).unwrap();
+        let id = list(&conn).unwrap()[0].id;
+        let restored = restore(&mut conn, id).unwrap();
+        assert_eq!(restored.item["startRef"]["book"], "John");
+        let data: String = conn
+            .query_row("SELECT data FROM annotations WHERE id = 'a1'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        let data: Value = serde_json::from_str(&data).unwrap();
+        assert_ne!(data["updatedAt"], "2025-01-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn old_items_are_purged_and_trash_can_be_emptied() {
+        let conn = migrated_test_connection();
+        add_note(&conn, "old");
+        add_note(&conn, "recent");
+        conn.execute("DELETE FROM notes", []).unwrap();
+        conn.execute(
+            "UPDATE trash SET deleted_at = '2020-01-01T00:00:00.000Z' WHERE row_id = 'old'",
+            [],
+        )
+        .unwrap();
+        let items = list(&conn).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].row_id, "recent");
+
+        assert_eq!(empty(&conn, Some("2000-01-01")).unwrap(), 0);
+        assert_eq!(empty(&conn, None).unwrap(), 1);
+    }
+}
@@ -0,0 +1,369 @@
+//! Undo and redo for annotation, note and tagging edits.
+//!
+//! Migration 17's triggers journal every local write to `annotations` and
+//! `notes` with the row before and after it, whichever side made it — a
+//! highlight saved from TS, a note edit, a native bulk batch — and migration
+//! 30's every write to `tag_links`. Undo writes the `before` rows back and
+//! redo the `after` rows, both as fresh local edits so sync carries them to
+//! other devices. Because the journal is in the database it survives reloads.
+//! Deletes that sync applies go through `delete_synced`, which keeps them off
+//! the journal the way a replay keeps itself off.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{
+    device_id, now_iso, record_change, trash, with_connection, with_reader, with_sync_connection,
+    DbError,
+};
+use crate::sync::merge::SYNCED_TABLES;
+
+/// Steps returned on each side by `get_undo_stack`.
+const STACK_LIMIT: usize = 50;
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UndoAction {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoEntry {
+    pub table: String,
+    #[serde(rename = "rowId")]
+    pub row_id: String,
+    /// What the original edit did to the row.
+    pub action: UndoAction,
+}
+
+/// One undoable step: every write journaled together.
+#[derive(Debug, Serialize)]
+pub struct UndoStep {
+    pub at: String,
+    pub entries: Vec<UndoEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoStack {
+    /// Most recent first.
+    pub undo: Vec<UndoStep>,
+    /// Next to redo first.
+    pub redo: Vec<UndoStep>,
+}
+
+/// The latest journal entry, to pass to `group_since` after a batch.
+pub(crate) fn last_seq(conn: &Connection) -> Result<i64, DbError> {
+    Ok(conn.query_row(
+        "SELECT COALESCE(MAX(seq), 0) FROM undo_journal",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Make everything journaled after `seq` one step, so a batch spanning many
+/// statements is undone at once.
+pub(crate) fn group_since(conn: &Connection, seq: i64) -> Result<(), DbError> {
+    conn.execute(
+        "UPDATE undo_journal
+         SET at = (SELECT at FROM undo_journal WHERE seq > ?1 ORDER BY seq LIMIT 1)
+         WHERE seq > ?1 AND undone = 0",
+        [seq],
+    )?;
+    Ok(())
+}
+
+fn action(before: bool, after: bool) -> UndoAction {
+    match (before, after) {
+        (false, _) => UndoAction::Create,
+        (_, false) => UndoAction::Delete,
+        _ => UndoAction::Update,
+    }
+}
+
+fn steps(conn: &Connection, undone: bool) -> Result<Vec<UndoStep>, DbError> {
+    let order = if undone { "ASC" } else { "DESC" };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT at, table_name, row_id, before IS NOT NULL, after IS NOT NULL
+         FROM undo_journal WHERE undone = ?1 ORDER BY seq {order}"
+    ))?;
+    let mut rows = stmt.query([undone])?;
+    let mut steps: Vec<UndoStep> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let at: String = row.get(0)?;
+        let entry = UndoEntry {
+            table: row.get(1)?,
+            row_id: row.get(2)?,
+            action: action(row.get(3)?, row.get(4)?),
+        };
+        if let Some(step) = steps.last_mut().filter(|step| step.at == at) {
+            step.entries.push(entry);
+        } else if steps.len() == STACK_LIMIT {
+            break;
+        } else {
+            steps.push(UndoStep {
+                at,
+                entries: vec![entry],
+            });
+        }
+    }
+    Ok(steps)
+}
+
+pub(crate) fn stack(conn: &Connection) -> Result<UndoStack, DbError> {
+    Ok(UndoStack {
+        undo: steps(conn, false)?,
+        redo: steps(conn, true)?,
+    })
+}
+
+/// Undo (or with `redo`, redo) the next step. `None` when there is nothing
+/// to do.
+pub(crate) fn replay(conn: &mut Connection, redo: bool) -> Result<Option<UndoStep>, DbError> {
+    let device = device_id(conn)?;
+    let tx = conn.transaction()?;
+    let next = if redo {
+        "SELECT at FROM undo_journal WHERE undone = 1 ORDER BY seq LIMIT 1"
+    } else {
+        "SELECT at FROM undo_journal WHERE undone = 0 ORDER BY seq DESC LIMIT 1"
+    };
+    let Some(at) = tx
+        .query_row(next, [], |row| row.get::<_, String>(0))
+        .optional()?
+    else {
+        return Ok(None);
+    };
+
+    // Undo unwinds the step's writes newest first; redo replays them in order.
+    let order = if redo { "ASC" } else { "DESC" };
+    let entries = tx
+        .prepare(&format!(
+            "SELECT table_name, row_id, before, after FROM undo_journal
+             WHERE undone = ?1 AND at = ?2 ORDER BY seq {order}"
+        ))?
+        .query_map(params![redo, at], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let now = now_iso(&tx)?;
+    tx.execute("INSERT INTO undo_replay (active) VALUES (1)", [])?;
+    let mut step = UndoStep {
+        at: at.clone(),
+        entries: Vec::with_capacity(entries.len()),
+    };
+    for (table, row_id, before, after) in entries {
+        step.entries.push(UndoEntry {
+            action: action(before.is_some(), after.is_some()),
+            table: table.clone(),
+            row_id: row_id.clone(),
+        });
+        match if redo { after } else { before } {
+            Some(row) => {
+                let row: Value = serde_json::from_str(&row).map_err(|e| {
+                    DbError::invalid(format!("Undo entry for `{row_id}` is unreadable: {e}"))
+                })?;
+                trash::write_row(&tx, &table, row, &now, &device)?;
+                // It's back, so an older trashed copy is no longer restorable.
+                tx.execute(
+                    "DELETE FROM trash WHERE table_name = ?1 AND row_id = ?2",
+                    params![table, row_id],
+                )?;
+            }
+            None => {
+                // `table` came from the journal, which only the triggers write.
+                tx.execute(&format!("DELETE FROM \"{table}\" WHERE id = ?1"), [&row_id])?;
+                record_change(&tx, &table, "delete", &row_id, None, &now, &device)?;
+            }
+        }
+    }
+    tx.execute(
+        "UPDATE undo_journal SET undone = ?1 WHERE undone = ?2 AND at = ?3",
+        params![!redo, redo, at],
+    )?;
+    tx.execute("DELETE FROM undo_replay", [])?;
+    tx.commit()?;
+    Ok(Some(step))
+}
+
+/// Undo the most recent annotation or note edit. Returns what was undone.
+#[tauri::command]
+pub async fn undo_last(app: tauri::AppHandle) -> Result<Option<UndoStep>, DbError> {
+    with_connection(&app, |conn| replay(conn, false)).await
+}
+
+/// Redo the most recently undone step.
+#[tauri::command]
+pub async fn redo(app: tauri::AppHandle) -> Result<Option<UndoStep>, DbError> {
+    with_connection(&app, |conn| replay(conn, true)).await
+}
+
+/// Delete `row_id` from `table` because another device deleted it, with a
+/// `sync_apply` row held for the transaction so the delete isn't journaled
+/// and leaves the redo entries alone. Returns whether the row was there.
+pub(crate) fn delete_synced(
+    conn: &mut Connection,
+    table: &str,
+    row_id: &str,
+) -> Result<bool, DbError> {
+    if !SYNCED_TABLES.contains(&table) {
+        return Err(DbError::invalid(format!("Invalid table name: {table}")));
+    }
+    let tx = conn.transaction()?;
+    tx.execute("INSERT INTO sync_apply (active) VALUES (1)", [])?;
+    let deleted = tx.execute(&format!("DELETE FROM \"{table}\" WHERE id = ?1"), [row_id])?;
+    tx.execute("DELETE FROM sync_apply", [])?;
+    tx.commit()?;
+    Ok(deleted > 0)
+}
+
+/// Apply a delete sync brought in (`applyRemoteChange` in sqlite-db.ts).
+#[tauri::command]
+pub async fn apply_remote_delete(
+    app: tauri::AppHandle,
+    table_name: String,
+    row_id: String,
+) -> Result<bool, DbError> {
+    with_sync_connection(&app, move |conn| delete_synced(conn, &table_name, &row_id)).await
+}
+
+#[tauri::command]
+pub async fn get_undo_stack(app: tauri::AppHandle) -> Result<UndoStack, DbError> {
+    with_reader(&app, stack).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+
+    fn save_note(conn: &Connection, id: &str, content: &str, status: &str) {
+        conn.execute(
+            "INSERT OR REPLACE INTO notes VALUES (?1, 'kjv',
+                '{\"book\":\"John\",\"chapter\":3,\"verse\":16}', NULL, ?2,
+                '2025-01-01T00:00:00.000Z', '2025-01-02T00:00:00.000Z', ?3, 'dev-local')",
+            params![id, content, status],
+        )
+        .unwrap();
+    }
+
+    fn content(conn: &Connection, id: &str) -> Option<String> {
+        conn.query_row("SELECT content FROM notes WHERE id = ?1", [id], |r| {
+            r.get(0)
+        })
+        .optional()
+        .unwrap()
+    }
+
+    #[test]
+    fn undo_and_redo_walk_a_note_through_its_edits() {
+        let mut conn = migrated_test_connection();
+        save_note(&conn, "n1", "first", "pending");
+        // Statements in one test run can share a millisecond; keep steps apart.
+        conn.execute("UPDATE undo_journal SET at = '2025-01-01'", [])
+            .unwrap();
+        save_note(&conn, "n1", "second", "pending");
+
+        let journal = stack(&conn).unwrap();
+        assert_eq!(journal.undo.len(), 2);
+        assert_eq!(journal.undo[0].entries[0].action, UndoAction::Update);
+        assert_eq!(journal.undo[1].entries[0].action, UndoAction::Create);
+
+        replay(&mut conn, false).unwrap().unwrap();
+        assert_eq!(content(&conn, "n1").as_deref(), Some("first"));
+        replay(&mut conn, false).unwrap().unwrap();
+        assert_eq!(content(&conn, "n1"), None);
+        assert!(replay(&mut conn, false).unwrap().is_none());
+
+        replay(&mut conn, true).unwrap().unwrap();
+        assert_eq!(content(&conn, "n1").as_deref(), Some("first"));
+        replay(&mut conn, true).unwrap().unwrap();
+        assert_eq!(content(&conn, "n1").as_deref(), Some("second"));
+        assert!(replay(&mut conn, true).unwrap().is_none());
+
+        // Replays are logged for sync but never journaled themselves.
+        let journal = stack(&conn).unwrap();
+        assert_eq!((journal.undo.len(), journal.redo.len()), (2, 0));
+        let logged: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM change_log WHERE row_id = 'n1'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(logged, 4);
+    }
+
+    #[test]
+    fn a_multi_row_delete_is_one_step_and_a_new_edit_drops_redo() {
+        let mut conn = migrated_test_connection();
+        save_note(&conn, "n1", "one", "synced");
+        save_note(&conn, "n2", "two", "synced");
+        assert!(stack(&conn).unwrap().undo.is_empty(), "sync writes");
+
+        conn.execute("DELETE FROM notes", []).unwrap();
+        let step = replay(&mut conn, false).unwrap().unwrap();
+        assert_eq!(step.entries.len(), 2);
+        assert_eq!(content(&conn, "n1").as_deref(), Some("one"));
+        assert_eq!(content(&conn, "n2").as_deref(), Some("two"));
+        let trashed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM trash", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(trashed, 0, "restored rows leave the trash");
+
+        assert_eq!(stack(&conn).unwrap().redo.len(), 1);
+        save_note(&conn, "n3", "three", "pending");
+        assert!(stack(&conn).unwrap().redo.is_empty());
+    }
+
+    #[test]
+    fn native_batches_undo_as_one_step() {
+        let mut conn = migrated_test_connection();
+        let since = last_seq(&conn).unwrap();
+        for (i, id) in ["n1", "n2", "n3"].iter().enumerate() {
+            save_note(&conn, id, "x", "pending");
+            conn.execute(
+                "UPDATE undo_journal SET at = ?1 WHERE row_id = ?2",
+                params![format!("2025-01-0{}", i + 1), id],
+            )
+            .unwrap();
+        }
+        assert_eq!(stack(&conn).unwrap().undo.len(), 3);
+        group_since(&conn, since).unwrap();
+        assert_eq!(stack(&conn).unwrap().undo.len(), 1);
+
+        replay(&mut conn, false).unwrap().unwrap();
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM notes", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[test]
+    fn a_synced_delete_leaves_the_undo_and_redo_stacks_alone() {
+        let mut conn = migrated_test_connection();
+        save_note(&conn, "n1", "one", "pending");
+        conn.execute("UPDATE undo_journal SET at = '2025-01-01'", [])
+            .unwrap();
+        save_note(&conn, "n2", "two", "pending");
+        replay(&mut conn, false).unwrap().unwrap();
+        let sizes = |conn: &Connection| {
+            let journal = stack(conn).unwrap();
+            (journal.undo.len(), journal.redo.len())
+        };
+        assert_eq!(sizes(&conn), (1, 1));
+
+        assert!(delete_synced(&mut conn, "notes", "n1").unwrap());
+        assert_eq!(content(&conn, "n1"), None);
+        assert_eq!(sizes(&conn), (1, 1));
+        assert!(!delete_synced(&mut conn, "notes", "n1").unwrap());
+        assert!(delete_synced(&mut conn, "undo_journal", "1").is_err());
+    }
+}
@@ -0,0 +1,704 @@
+//! Topical study workspaces: one topic ("Grace in Romans") and the tags,
+//! verse collections, notes and key words that belong to it, kept by id in
+//! the synced `workspaces` table of migration 25.
+//!
+//! A workspace only points at its members, which stay where they are and
+//! can belong to several workspaces; a member deleted elsewhere is listed as
+//! missing rather than removed. A workspace can be exported to a `.bmstudy`
+//! file holding it, its members, the items its tags are on and the marks
+//! made with its key words. The file is laid out like a sync bundle, so it
+//! is imported by the same merge, newest wins, as `import_sync_bundle`.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use super::data_rows::{self, DataRow};
+use super::tags::{self, Tags};
+use super::{backlinks, device_id, now_iso, with_connection, with_reader, DbError, DbErrorKind};
+use crate::sync::merge::{self, MergeReport};
+
+/// Identifies the file as an exported study (`bmstudy_manifest.format`).
+const STUDY_FORMAT: &str = "bmstudy";
+
+/// Bumped when the file layout changes; newer files are refused.
+const STUDY_VERSION: u32 = 1;
+
+/// Longest workspace name accepted, in characters.
+const MAX_NAME: usize = 200;
+
+/// A workspace, as stored in `workspaces.data`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Its tags; their subtags come with them.
+    #[serde(rename = "tagIds", default)]
+    pub tag_ids: Vec<String>,
+    /// Its verse collections.
+    #[serde(rename = "collectionIds", default)]
+    pub collection_ids: Vec<String>,
+    #[serde(rename = "noteIds", default)]
+    pub note_ids: Vec<String>,
+    /// Its key words (marking presets).
+    #[serde(rename = "keyWordIds", default)]
+    pub key_word_ids: Vec<String>,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: String,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl DataRow for Workspace {
+    const TABLE: &'static str = "workspaces";
+    const NOUN: &'static str = "workspace";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn timestamps(&mut self) -> (&mut String, &mut String) {
+        (&mut self.created_at, &mut self.updated_at)
+    }
+}
+
+/// What can belong to a workspace.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub enum MemberKind {
+    #[serde(rename = "tag")]
+    Tag,
+    #[serde(rename = "collection")]
+    Collection,
+    #[serde(rename = "note")]
+    Note,
+    #[serde(rename = "keyWord")]
+    KeyWord,
+}
+
+impl MemberKind {
+    fn table(self) -> &'static str {
+        match self {
+            Self::Tag => "tags",
+            Self::Collection => "verse_collections",
+            Self::Note => "notes",
+            Self::KeyWord => "marking_presets",
+        }
+    }
+}
+
+impl Workspace {
+    fn members_mut(&mut self, kind: MemberKind) -> &mut Vec<String> {
+        match kind {
+            MemberKind::Tag => &mut self.tag_ids,
+            MemberKind::Collection => &mut self.collection_ids,
+            MemberKind::Note => &mut self.note_ids,
+            MemberKind::KeyWord => &mut self.key_word_ids,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TagSummary {
+    pub id: String,
+    pub path: String,
+    /// Items carrying it or one of its subtags.
+    pub items: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CollectionSummary {
+    pub id: String,
+    pub name: String,
+    pub entries: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct NoteSummary {
+    pub id: String,
+    /// Its first line, when that is a heading.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(rename = "ref")]
+    pub verse_ref: Value,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct KeyWordSummary {
+    pub id: String,
+    pub word: Option<String>,
+    /// Annotations made with it.
+    pub marks: i64,
+}
+
+/// A workspace with what its members are (`get_workspace_summary`).
+#[derive(Debug, Serialize, PartialEq)]
+pub struct WorkspaceSummary {
+    pub workspace: Workspace,
+    pub tags: Vec<TagSummary>,
+    pub collections: Vec<CollectionSummary>,
+    pub notes: Vec<NoteSummary>,
+    #[serde(rename = "keyWords")]
+    pub key_words: Vec<KeyWordSummary>,
+    /// Ids of members deleted since they were added.
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StudyExport {
+    pub path: String,
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+    /// Rows written, by table.
+    pub tables: Vec<(String, usize)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StudyImport {
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+    pub name: String,
+    pub merge: MergeReport,
+}
+
+fn tidy(text: Option<String>) -> Option<String> {
+    text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+fn check_name(name: &str) -> Result<String, DbError> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME {
+        return Err(DbError::invalid(format!(
+            "A workspace's name must be 1 to {MAX_NAME} characters"
+        )));
+    }
+    Ok(name)
+}
+
+/// Every workspace, by name. Rows that don't parse are skipped.
+pub(crate) fn workspaces(conn: &Connection) -> Result<Vec<Workspace>, DbError> {
+    let mut workspaces = data_rows::all::<Workspace>(conn)?;
+    workspaces.sort_by_cached_key(|w| (w.name.to_lowercase(), w.id.clone()));
+    Ok(workspaces)
+}
+
+pub(crate) fn workspace(conn: &Connection, id: &str) -> Result<Workspace, DbError> {
+    data_rows::get(conn, id)
+}
+
+pub(crate) fn create(
+    conn: &Connection,
+    name: &str,
+    description: Option<String>,
+) -> Result<Workspace, DbError> {
+    let mut workspace = Workspace {
+        id: conn.query_row("SELECT 'ws-' || lower(hex(randomblob(8)))", [], |row| {
+            row.get(0)
+        })?,
+        name: check_name(name)?,
+        description: tidy(description),
+        ..Default::default()
+    };
+    data_rows::save(conn, &mut workspace)?;
+    Ok(workspace)
+}
+
+/// Rename a workspace or change its description.
+pub(crate) fn edit(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    description: Option<String>,
+) -> Result<Workspace, DbError> {
+    let mut workspace = workspace(conn, id)?;
+    workspace.name = check_name(name)?;
+    workspace.description = tidy(description);
+    data_rows::save(conn, &mut workspace)?;
+    Ok(workspace)
+}
+
+/// Delete a workspace; its members stay.
+pub(crate) fn delete(conn: &Connection, id: &str) -> Result<bool, DbError> {
+    data_rows::delete(conn, "workspaces", id)
+}
+
+fn exists(conn: &Connection, table: &str, id: &str) -> Result<bool, DbError> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT 1 FROM {table} WHERE id = ?1"),
+            [id],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Add an item to a workspace. Fails `NotFound` if there's no such item; a
+/// tag may be given by path.
+pub(crate) fn add_member(
+    conn: &Connection,
+    id: &str,
+    kind: MemberKind,
+    item_id: &str,
+) -> Result<Workspace, DbError> {
+    let mut workspace = workspace(conn, id)?;
+    let item_id = match kind {
+        MemberKind::Tag => Tags::load(conn)?.find(item_id).map(|t| t.id.clone()),
+        _ => exists(conn, kind.table(), item_id)?.then(|| item_id.to_string()),
+    }
+    .ok_or_else(|| {
+        DbError::new(
+            DbErrorKind::NotFound,
+            format!("No {} {item_id}", kind.table()),
+        )
+    })?;
+    let members = workspace.members_mut(kind);
+    if !members.contains(&item_id) {
+        members.push(item_id);
+        data_rows::save(conn, &mut workspace)?;
+    }
+    Ok(workspace)
+}
+
+pub(crate) fn remove_member(
+    conn: &Connection,
+    id: &str,
+    kind: MemberKind,
+    item_id: &str,
+) -> Result<Workspace, DbError> {
+    let mut workspace = workspace(conn, id)?;
+    let members = workspace.members_mut(kind);
+    let before = members.len();
+    members.retain(|m| m != item_id);
+    if members.len() != before {
+        data_rows::save(conn, &mut workspace)?;
+    }
+    Ok(workspace)
+}
+
+pub(crate) fn summary(conn: &Connection, id: &str) -> Result<WorkspaceSummary, DbError> {
+    let workspace = workspace(conn, id)?;
+    let mut missing = Vec::new();
+
+    let all_tags = Tags::load(conn)?;
+    let mut tag_summaries = Vec::new();
+    for tag_id in &workspace.tag_ids {
+        if all_tags.get(tag_id).is_none() {
+            missing.push(tag_id.clone());
+            continue;
+        }
+        tag_summaries.push(TagSummary {
+            id: tag_id.clone(),
+            path: all_tags.path(tag_id),
+            items: tags::items(conn, tag_id, true)?.len(),
+        });
+    }
+
+    let mut collections = Vec::new();
+    for collection_id in &workspace.collection_ids {
+        match super::collections::collection(conn, collection_id) {
+            Ok(c) => collections.push(CollectionSummary {
+                id: c.id,
+                name: c.name,
+                entries: c.entries.len(),
+            }),
+            Err(e) if e.kind == DbErrorKind::NotFound => missing.push(collection_id.clone()),
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut notes = Vec::new();
+    for note_id in &workspace.note_ids {
+        let note = conn
+            .query_row(
+                "SELECT content, ref, updated_at FROM notes WHERE id = ?1",
+                [note_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        match note {
+            Some((content, verse_ref, updated_at)) => notes.push(NoteSummary {
+                id: note_id.clone(),
+                title: backlinks::title(&content),
+                verse_ref: serde_json::from_str(&verse_ref).unwrap_or_default(),
+                updated_at,
+            }),
+            None => missing.push(note_id.clone()),
+        }
+    }
+
+    let mut key_words = Vec::new();
+    for preset_id in &workspace.key_word_ids {
+        let word: Option<Option<String>> = conn
+            .query_row(
+                "SELECT word FROM marking_presets WHERE id = ?1",
+                [preset_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(word) = word else {
+            missing.push(preset_id.clone());
+            continue;
+        };
+        let marks = conn.query_row(
+            "SELECT COUNT(*) FROM annotations WHERE preset_id = ?1",
+            [preset_id],
+            |row| row.get(0),
+        )?;
+        key_words.push(KeyWordSummary {
+            id: preset_id.clone(),
+            word,
+            marks,
+        });
+    }
+
+    Ok(WorkspaceSummary {
+        workspace,
+        tags: tag_summaries,
+        collections,
+        notes,
+        key_words,
+        missing,
+    })
+}
+
+/// The rows an exported study holds, by table.
+fn study_rows(
+    conn: &Connection,
+    workspace: &Workspace,
+) -> Result<Vec<(&'static str, BTreeSet<String>)>, DbError> {
+    let all_tags = Tags::load(conn)?;
+    let mut tag_ids = BTreeSet::new();
+    for tag_id in &workspace.tag_ids {
+        tag_ids.extend(all_tags.subtree(tag_id));
+        tag_ids.extend(all_tags.lineage(tag_id).iter().map(|t| t.id.clone()));
+    }
+    let mut link_ids = BTreeSet::new();
+    let mut items: [BTreeSet<String>; 3] = Default::default();
+    let mut stmt = conn.prepare(
+        "SELECT id, json_extract(data, '$.itemType'), json_extract(data, '$.itemId')
+         FROM tag_links WHERE json_extract(data, '$.tagId') = ?1",
+    )?;
+    for tag_id in workspace.tag_ids.iter().flat_map(|t| all_tags.subtree(t)) {
+        let rows: Vec<(String, String, String)> = stmt
+            .query_map([&tag_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        for (link_id, item_type, item_id) in rows {
+            link_ids.insert(link_id);
+            let slot = match item_type.as_str() {
+                "annotation" => 0,
+                "note" => 1,
+                _ => 2,
+            };
+            items[slot].insert(item_id);
+        }
+    }
+    let [mut annotations, mut notes, lists] = items;
+    notes.extend(workspace.note_ids.iter().cloned());
+    let presets: BTreeSet<String> = workspace.key_word_ids.iter().cloned().collect();
+    let mut marks = conn.prepare("SELECT id FROM annotations WHERE preset_id = ?1")?;
+    for preset_id in &presets {
+        let ids: Vec<String> = marks
+            .query_map([preset_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        annotations.extend(ids);
+    }
+    Ok(vec![
+        ("workspaces", BTreeSet::from([workspace.id.clone()])),
+        ("tags", tag_ids),
+        ("tag_links", link_ids),
+        (
+            "verse_collections",
+            workspace.collection_ids.iter().cloned().collect(),
+        ),
+        ("notes", notes),
+        ("marking_presets", presets),
+        ("annotations", annotations),
+        ("observation_lists", lists),
+    ])
+}
+
+/// Copy `workspace` and what it holds into the empty database attached as
+/// `study`, with its manifest.
+fn write_study(conn: &Connection, workspace: &Workspace) -> Result<Vec<(String, usize)>, DbError> {
+    let mut tables = Vec::new();
+    for (table, ids) in study_rows(conn, workspace)? {
+        if !merge::table_exists(conn, "main", table)? {
+            continue;
+        }
+        conn.execute(
+            &format!("CREATE TABLE study.{table} AS SELECT * FROM main.{table} WHERE 0"),
+            [],
+        )?;
+        let ids = serde_json::to_string(&ids).unwrap_or_default();
+        let rows = conn.execute(
+            &format!(
+                "INSERT INTO study.{table} SELECT * FROM main.{table}
+                 WHERE id IN (SELECT value FROM json_each(?1))"
+            ),
+            [ids],
+        )?;
+        tables.push((table.to_string(), rows));
+    }
+    conn.execute_batch(
+        "CREATE TABLE study.bmstudy_manifest (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+    )?;
+    let entries = [
+        ("format", STUDY_FORMAT.to_string()),
+        ("version", STUDY_VERSION.to_string()),
+        ("workspace_id", workspace.id.clone()),
+        ("name", workspace.name.clone()),
+        ("device_id", device_id(conn)?),
+        ("created_at", now_iso(conn)?),
+    ];
+    for (key, value) in entries {
+        conn.execute(
+            "INSERT INTO study.bmstudy_manifest (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+    }
+    Ok(tables)
+}
+
+pub(crate) fn export(conn: &Connection, id: &str, out: &Path) -> Result<StudyExport, DbError> {
+    let workspace = workspace(conn, id)?;
+    // Built beside the destination and renamed into place, as bundles are.
+    let tmp = out.with_extension("bmstudy.tmp");
+    let _ = std::fs::remove_file(&tmp);
+    conn.execute("ATTACH DATABASE ?1 AS study", [tmp.to_string_lossy()])
+        .map_err(|e| DbError::from(e).context(format!("Failed to create {}", tmp.display())))?;
+    let result = write_study(conn, &workspace);
+    let _ = conn.execute("DETACH DATABASE study", []);
+    match result {
+        Ok(tables) => {
+            std::fs::rename(&tmp, out)
+                .map_err(|e| DbError::io(format!("Failed to write {}: {e}", out.display())))?;
+            Ok(StudyExport {
+                path: out.display().to_string(),
+                workspace_id: workspace.id,
+                tables,
+            })
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            Err(e.context("Export failed"))
+        }
+    }
+}
+
+/// The exported workspace's id and name, from the manifest of the study
+/// attached as `schema`.
+fn read_manifest(conn: &Connection, schema: &str) -> Result<(String, String), String> {
+    let not_a_study = || "Not an exported BibleMarker study".to_string();
+    if !merge::table_exists(conn, schema, "bmstudy_manifest").map_err(|e| e.to_string())? {
+        return Err(not_a_study());
+    }
+    let get = |key: &str| -> Result<Option<String>, String> {
+        conn.query_row(
+            &format!("SELECT value FROM {schema}.bmstudy_manifest WHERE key = ?1"),
+            [key],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read the study's manifest: {e}"))
+    };
+    if get("format")?.as_deref() != Some(STUDY_FORMAT) {
+        return Err(not_a_study());
+    }
+    let version: u32 = get("version")?
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(not_a_study)?;
+    if version > STUDY_VERSION {
+        return Err(format!(
+            "This study was exported by a newer version of BibleMarker (format {version}); update the app to import it"
+        ));
+    }
+    Ok((
+        get("workspace_id")?.ok_or_else(not_a_study)?,
+        get("name")?.unwrap_or_default(),
+    ))
+}
+
+/// Merge the study exported at `path` into the local database.
+pub(crate) fn import(conn: &mut Connection, path: &Path) -> Result<StudyImport, DbError> {
+    let mut manifest = None;
+    let merge = merge::merge_file(conn, path, |conn| {
+        manifest = Some(read_manifest(conn, "remote")?);
+        Ok(())
+    })?;
+    let (workspace_id, name) = manifest.unwrap_or_default();
+    Ok(StudyImport {
+        workspace_id,
+        name,
+        merge,
+    })
+}
+
+/// Every workspace by name.
+#[tauri::command]
+pub async fn get_workspaces(app: tauri::AppHandle) -> Result<Vec<Workspace>, DbError> {
+    with_reader(&app, workspaces).await
+}
+
+/// Start an empty workspace for a topic.
+#[tauri::command]
+pub async fn create_workspace(
+    app: tauri::AppHandle,
+    name: String,
+    description: Option<String>,
+) -> Result<Workspace, DbError> {
+    with_connection(&app, move |conn| create(conn, &name, description)).await
+}
+
+#[tauri::command]
+pub async fn update_workspace(
+    app: tauri::AppHandle,
+    id: String,
+    name: String,
+    description: Option<String>,
+) -> Result<Workspace, DbError> {
+    with_connection(&app, move |conn| edit(conn, &id, &name, description)).await
+}
+
+/// Delete a workspace, leaving its tags, collections, notes and key words.
+#[tauri::command]
+pub async fn delete_workspace(app: tauri::AppHandle, id: String) -> Result<bool, DbError> {
+    with_connection(&app, move |conn| delete(conn, &id)).await
+}
+
+/// Put a tag, verse collection, note or key word in a workspace.
+#[tauri::command]
+pub async fn add_to_workspace(
+    app: tauri::AppHandle,
+    id: String,
+    kind: MemberKind,
+    item_id: String,
+) -> Result<Workspace, DbError> {
+    with_connection(&app, move |conn| add_member(conn, &id, kind, &item_id)).await
+}
+
+#[tauri::command]
+pub async fn remove_from_workspace(
+    app: tauri::AppHandle,
+    id: String,
+    kind: MemberKind,
+    item_id: String,
+) -> Result<Workspace, DbError> {
+    with_connection(&app, move |conn| remove_member(conn, &id, kind, &item_id)).await
+}
+
+/// A workspace with its members' names and sizes.
+#[tauri::command]
+pub async fn get_workspace_summary(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<WorkspaceSummary, DbError> {
+    with_reader(&app, move |conn| summary(conn, &id)).await
+}
+
+/// Write a workspace and everything in it to a `.bmstudy` file at `path`.
+#[tauri::command]
+pub async fn export_workspace(
+    app: tauri::AppHandle,
+    id: String,
+    path: String,
+) -> Result<StudyExport, DbError> {
+    let out = PathBuf::from(path);
+    with_connection(&app, move |conn| export(conn, &id, &out)).await
+}
+
+/// Merge a `.bmstudy` file someone shared into the local database.
+#[tauri::command]
+pub async fn import_workspace(app: tauri::AppHandle, path: String) -> Result<StudyImport, DbError> {
+    let file = PathBuf::from(path);
+    with_connection(&app, move |conn| import(conn, &file)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrated_test_connection;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn groups_a_topic_and_exports_it_as_one_study() {
+        let mut conn = migrated_test_connection();
+        conn.execute_batch(
+            "INSERT INTO notes (id, module_id, ref, content, created_at, updated_at) VALUES
+               ('n1', 'kjv', '{\"book\":\"Rom\",\"chapter\":5,\"verse\":1}', '# Peace\nwith God', 'x', 'x'),
+               ('n2', 'kjv', '{}', 'tagged only', 'x', 'x'),
+               ('n3', 'kjv', '{}', 'not in the study', 'x', 'x');
+             INSERT INTO marking_presets (id, word, variants, created_at, updated_at)
+               VALUES ('p1', 'grace', '[]', 'x', 'x');
+             INSERT INTO annotations (id, module_id, type, data, preset_id, created_at, updated_at)
+               VALUES ('a1', 'kjv', 'highlight', '{}', 'p1', 'x', 'x'),
+                      ('a2', 'kjv', 'highlight', '{}', NULL, 'x', 'x');",
+        )
+        .unwrap();
+        let topic = create(&conn, " Grace in Romans ", None).unwrap();
+        tags::attach(&mut conn, "Themes/Grace", tags::ItemType::Note, "n2").unwrap();
+        add_member(&conn, &topic.id, MemberKind::Tag, "themes/grace").unwrap();
+        add_member(&conn, &topic.id, MemberKind::Note, "n1").unwrap();
+        add_member(&conn, &topic.id, MemberKind::Note, "gone-later").unwrap_err();
+        add_member(&conn, &topic.id, MemberKind::KeyWord, "p1").unwrap();
+        let again = add_member(&conn, &topic.id, MemberKind::Note, "n1").unwrap();
+        assert_eq!(again.note_ids, ["n1"]);
+
+        let found = summary(&conn, &topic.id).unwrap();
+        assert_eq!(found.workspace.name, "Grace in Romans");
+        assert_eq!(
+            (found.tags[0].path.as_str(), found.tags[0].items),
+            ("Themes/Grace", 1)
+        );
+        assert_eq!(found.notes[0].title.as_deref(), Some("Peace"));
+        assert_eq!(
+            (found.key_words[0].word.as_deref(), found.key_words[0].marks),
+            (Some("grace"), 1)
+        );
+
+        let dir = ScratchDir::new("study");
+        let file = dir.join("grace.bmstudy");
+        let exported = export(&conn, &topic.id, &file).unwrap();
+        let count = |table: &str| {
+            exported
+                .tables
+                .iter()
+                .find(|(t, _)| t == table)
+                .map_or(0, |(_, n)| *n)
+        };
+        // Both tags of the path, the tagged note and the key word's mark.
+        assert_eq!(
+            (count("tags"), count("notes"), count("annotations")),
+            (2, 2, 1)
+        );
+
+        let mut other = migrated_test_connection();
+        let imported = import(&mut other, &file).unwrap();
+        assert_eq!(
+            (imported.workspace_id.as_str(), imported.name.as_str()),
+            (topic.id.as_str(), "Grace in Romans")
+        );
+        let shared = summary(&other, &topic.id).unwrap();
+        assert!(shared.missing.is_empty(), "{:?}", shared.missing);
+        assert_eq!(shared.tags[0].items, 1);
+        assert_eq!(
+            import(&mut other, &dir.join("nothing.bmstudy"))
+                .unwrap_err()
+                .kind,
+            DbErrorKind::NotFound
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Read-only mode for the native layer.
+//!
+//! Writing to the database while sync is still bringing data in — a storage
+//! provider materializing the file, or the engine downloading a snapshot it
+//! is about to apply — can leave it inconsistent. The sync engine locks writes
+//! for that window with `set_db_write_lock`; until it unlocks, every native
+//! write (commands, merges, imports, idle maintenance) fails with
+//! `DatabaseLocked`, while reads carry on. Unlocking emits `db://writable`.
+//! The lock lives in memory only, so a restart always clears it.
+
+use serde::Serialize;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Emitter;
+
+use super::{DbError, DbErrorKind};
+
+/// Emitted with the new `WriteLockState` when writes are allowed again.
+pub(crate) const WRITABLE_EVENT: &str = "db://writable";
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct WriteLockState {
+    pub locked: bool,
+    /// Why writes are paused, as given by whoever locked them.
+    pub reason: Option<String>,
+    /// When the lock was taken, in Unix milliseconds.
+    #[serde(rename = "lockedAt")]
+    pub locked_at: Option<u64>,
+}
+
+pub(crate) struct WriteLock(Mutex<Option<(String, u64)>>);
+
+pub(crate) static WRITE_LOCK: WriteLock = WriteLock::new();
+
+impl WriteLock {
+    pub(crate) const fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+
+    fn held(&self) -> MutexGuard<'_, Option<(String, u64)>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn state(&self) -> WriteLockState {
+        let held = self.held();
+        WriteLockState {
+            locked: held.is_some(),
+            reason: held.as_ref().map(|(reason, _)| reason.clone()),
+            locked_at: held.as_ref().map(|(_, at)| *at),
+        }
+    }
+
+    /// Lock or unlock writes. Returns the new state and whether it changed.
+    pub(crate) fn set(&self, locked: bool, reason: Option<String>) -> (WriteLockState, bool) {
+        let changed = {
+            let mut held = self.held();
+            let changed = held.is_some() != locked;
+            *held = locked.then(|| {
+                let at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_millis() as u64);
+                let kept = held.as_ref().map(|(_, at)| *at).unwrap_or(at);
+                (reason.unwrap_or_else(|| "sync in progress".into()), kept)
+            });
+            changed
+        };
+        (self.state(), changed)
+    }
+
+    /// `DatabaseLocked` while writes are paused.
+    pub(crate) fn check(&self) -> Result<(), DbError> {
+        match self.held().as_ref() {
+            Some((reason, _)) => Err(DbError::new(
+                DbErrorKind::DatabaseLocked,
+                format!("The database is read-only for now ({reason})"),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Pause (or resume) native writes. Locking again only updates the reason.
+#[tauri::command]
+pub fn set_db_write_lock(
+    app: tauri::AppHandle,
+    locked: bool,
+    reason: Option<String>,
+) -> WriteLockState {
+    let (state, changed) = WRITE_LOCK.set(locked, reason);
+    if changed && !locked {
+        let _ = app.emit(WRITABLE_EVENT, state.clone());
+    }
+    state
+}
+
+#[tauri::command]
+pub fn get_db_write_lock_state() -> WriteLockState {
+    WRITE_LOCK.state()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locking_rejects_writes_until_unlocked() {
+        let lock = WriteLock::new();
+        assert!(lock.check().is_ok());
+
+        let (state, changed) = lock.set(true, Some("downloading snapshot".into()));
+        assert!(changed && state.locked);
+        assert_eq!(state.reason.as_deref(), Some("downloading snapshot"));
+        let err = lock.check().unwrap_err();
+        assert_eq!(err.kind, DbErrorKind::DatabaseLocked);
+        assert!(err.message.contains("downloading snapshot"));
+
+        let (again, changed) = lock.set(true, None);
+        assert!(!changed);
+        assert_eq!(again.locked_at, state.locked_at, "keeps the original time");
+
+        let (state, changed) = lock.set(false, None);
+        assert!(changed && !state.locked);
+        assert_eq!(state.reason, None);
+        assert!(lock.check().is_ok());
+    }
+}
@@ -1,22 +1,27 @@
 //! Database maintenance commands (corruption recovery, etc.).
 //! These are independent of any sync transport.
 
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 use tauri::command;
 
+use crate::db::{self, DbError, DbErrorKind};
+use crate::sync::merge;
+
 /// Delete the local database files so a fresh DB can be created.
 /// Called from JS when corruption is detected at runtime.
 #[command]
 pub fn delete_local_database(app_handle: tauri::AppHandle) -> Result<String, String> {
-    use tauri::Manager;
-
-    let app_data = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Cannot determine app data dir: {}", e))?;
+    let db_file = db::db_path(&app_handle).map_err(|e| e.message)?;
 
-    let db_file = app_data.join("biblemarker.db");
-    let wal_file = app_data.join("biblemarker.db-wal");
-    let shm_file = app_data.join("biblemarker.db-shm");
+    db::write_lock::WRITE_LOCK.check().map_err(|e| e.message)?;
+    db::connections::close_connections();
+    let wal_file = db_file.with_extension("db-wal");
+    let shm_file = db_file.with_extension("db-shm");
 
     for f in [&db_file, &wal_file, &shm_file] {
         if f.exists() {
@@ -27,3 +32,972 @@ pub fn delete_local_database(app_handle: tauri::AppHandle) -> Result<String, Str
 
     Ok("Local database deleted".into())
 }
+
+/// Result of `PRAGMA wal_checkpoint(TRUNCATE)`.
+#[derive(Debug, Serialize)]
+pub struct CheckpointResult {
+    /// Another connection kept the checkpoint from finishing; try again later.
+    pub busy: bool,
+    /// Frames that were in the WAL.
+    #[serde(rename = "logFrames")]
+    pub log_frames: i64,
+    /// Frames copied back into the main file.
+    #[serde(rename = "checkpointedFrames")]
+    pub checkpointed_frames: i64,
+}
+
+/// Copy the whole WAL into the main file and truncate it, so the `.db` file
+/// alone is a complete copy of the database.
+pub(crate) fn checkpoint(conn: &Connection) -> Result<CheckpointResult, DbError> {
+    Ok(
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+            Ok(CheckpointResult {
+                busy: row.get::<_, i64>(0)? != 0,
+                log_frames: row.get(1)?,
+                checkpointed_frames: row.get(2)?,
+            })
+        })?,
+    )
+}
+
+/// Fold the `-wal` sidecar back into `biblemarker.db`. Run before copying the
+/// database file anywhere (backups, moving to a new device).
+#[command]
+pub async fn checkpoint_database(
+    app_handle: tauri::AppHandle,
+) -> Result<CheckpointResult, DbError> {
+    db::with_connection(&app_handle, |conn| checkpoint(conn)).await
+}
+
+/// `PRAGMA integrity_check`/`quick_check` stop after this many problems.
+const MAX_REPORTED_PROBLEMS: usize = 100;
+
+/// Upper bound on rowids probed one by one past a damaged page, so a bogus
+/// `max(rowid)` can't stall a repair.
+const MAX_PROBED_ROWIDS: i64 = 1_000_000;
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    /// `quick_check` or `integrity_check`.
+    pub mode: &'static str,
+    /// SQLite's descriptions of what is wrong (empty when `ok`).
+    pub problems: Vec<String>,
+}
+
+pub(crate) fn check_integrity(conn: &Connection, full: bool) -> Result<IntegrityReport, DbError> {
+    let mode = if full {
+        "integrity_check"
+    } else {
+        "quick_check"
+    };
+    let rows = conn
+        .prepare(&format!("PRAGMA {mode}({MAX_REPORTED_PROBLEMS})"))
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(DbError::from);
+    let problems = match rows {
+        Ok(rows) if rows.len() == 1 && rows[0] == "ok" => Vec::new(),
+        Ok(rows) => rows,
+        // Too damaged for the check to finish: that is the answer.
+        Err(e) if e.kind == DbErrorKind::Corrupt => vec![e.message],
+        Err(e) => return Err(e),
+    };
+    Ok(IntegrityReport {
+        ok: problems.is_empty(),
+        mode,
+        problems,
+    })
+}
+
+/// Check the database for corruption. `full` runs the slower
+/// `integrity_check`, which also verifies indexes against their tables.
+#[command]
+pub async fn check_database_integrity(
+    app_handle: tauri::AppHandle,
+    full: Option<bool>,
+) -> Result<IntegrityReport, DbError> {
+    let full = full.unwrap_or(false);
+    db::with_reader(&app_handle, move |conn| check_integrity(conn, full)).await
+}
+
+/// What a repair got back from one table.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TableSalvage {
+    pub table: String,
+    pub recovered: usize,
+    /// Rows that were located but could not be read back.
+    pub lost: usize,
+    /// False when the rows couldn't all be enumerated, so more may be gone
+    /// than `lost` says.
+    pub complete: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepairReport {
+    pub tables: Vec<TableSalvage>,
+    /// Tables, indexes, triggers or views whose definition couldn't be recreated.
+    #[serde(rename = "lostObjects")]
+    pub lost_objects: Vec<String>,
+    /// The damaged original, kept next to the repaired database.
+    #[serde(rename = "corruptCopy")]
+    pub corrupt_copy: String,
+}
+
+struct SchemaObject {
+    kind: String,
+    name: String,
+    sql: String,
+}
+
+/// Copy every readable row of `table` from `damaged` into `main`: in one
+/// statement if the table reads cleanly, otherwise row by row, so a bad page
+/// costs only the rows stored on it.
+fn salvage_table(conn: &Connection, table: &str) -> TableSalvage {
+    let mut report = TableSalvage {
+        table: table.to_string(),
+        recovered: 0,
+        lost: 0,
+        complete: true,
+    };
+    let columns = match merge::columns(conn, "main", table) {
+        Ok(columns) => columns,
+        Err(_) => {
+            report.complete = false;
+            return report;
+        }
+    };
+    let list = columns
+        .iter()
+        .map(|c| format!("\"{c}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let copy = format!(
+        "INSERT INTO main.\"{table}\" (rowid, {list}) SELECT rowid, {list} FROM damaged.\"{table}\""
+    );
+    if let Ok(rows) = conn.execute(&copy, []) {
+        report.recovered = rows;
+        return report;
+    }
+
+    let copy_one = format!("{copy} WHERE rowid = ?1");
+    let mut copy_row = |rowid: i64| match conn.execute(&copy_one, [rowid]) {
+        Ok(rows) => report.recovered += rows,
+        Err(_) => report.lost += 1,
+    };
+
+    // Walk the table until the scan itself hits damage...
+    let mut last = None;
+    if let Ok(mut stmt) = conn.prepare(&format!("SELECT rowid FROM damaged.\"{table}\"")) {
+        if let Ok(rows) = stmt.query_map([], |row| row.get::<_, i64>(0)) {
+            for rowid in rows {
+                match rowid {
+                    Ok(rowid) => {
+                        copy_row(rowid);
+                        last = Some(rowid);
+                    }
+                    Err(_) => {
+                        report.complete = false;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    if !report.complete {
+        // ...then look up the remaining rowids directly; the b-tree's interior
+        // pages usually still lead to the leaves after the damaged one.
+        let max = conn
+            .query_row(
+                &format!("SELECT max(rowid) FROM damaged.\"{table}\""),
+                [],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .ok()
+            .flatten();
+        if let Some(max) = max {
+            let first = last.map_or(1, |rowid| rowid + 1);
+            for rowid in first..=max.min(first.saturating_add(MAX_PROBED_ROWIDS)) {
+                copy_row(rowid);
+            }
+        }
+    }
+    report
+}
+
+/// Rebuild `damaged` into a fresh database at `out`, recreating its schema
+/// and copying whatever rows can still be read. Indexes and triggers are
+/// created after the data so triggers don't fire during the copy.
+pub(crate) fn salvage(
+    damaged: &Path,
+    out: &Path,
+) -> Result<(Vec<TableSalvage>, Vec<String>), DbError> {
+    let _ = std::fs::remove_file(out);
+    let conn = Connection::open(out)?;
+    conn.execute("ATTACH DATABASE ?1 AS damaged", [damaged.to_string_lossy()])?;
+    let objects = {
+        let mut stmt = conn.prepare(
+            "SELECT type, name, sql FROM damaged.sqlite_master
+             WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%' ORDER BY rowid",
+        )?;
+        let objects = stmt
+            .query_map([], |row| {
+                Ok(SchemaObject {
+                    kind: row.get(0)?,
+                    name: row.get(1)?,
+                    sql: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        objects
+    };
+
+    // Virtual tables (the FTS index) recreate their own shadow tables.
+    let virtual_prefixes: Vec<String> = objects
+        .iter()
+        .filter(|o| {
+            o.kind == "table"
+                && o.sql
+                    .to_ascii_uppercase()
+                    .starts_with("CREATE VIRTUAL TABLE")
+        })
+        .map(|o| format!("{}_", o.name))
+        .collect();
+    let is_shadow = |name: &str| virtual_prefixes.iter().any(|p| name.starts_with(p));
+
+    let mut lost_objects = Vec::new();
+    let mut tables = Vec::new();
+    for object in objects
+        .iter()
+        .filter(|o| o.kind == "table" && !is_shadow(&o.name))
+    {
+        if conn.execute_batch(&object.sql).is_ok() {
+            tables.push(salvage_table(&conn, &object.name));
+        } else {
+            lost_objects.push(object.name.clone());
+        }
+    }
+    for object in objects.iter().filter(|o| o.kind != "table") {
+        if conn.execute_batch(&object.sql).is_err() {
+            lost_objects.push(object.name.clone());
+        }
+    }
+    conn.execute("DETACH DATABASE damaged", [])?;
+    Ok((tables, lost_objects))
+}
+
+/// `path` with `suffix` appended to its file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Move the database at `path` and its `-wal`/`-shm` sidecars to `to`.
+fn quarantine(path: &Path, to: &Path) -> Result<(), DbError> {
+    for suffix in ["", "-wal", "-shm"] {
+        let from = with_suffix(path, suffix);
+        if from.exists() {
+            std::fs::rename(&from, with_suffix(to, suffix))
+                .map_err(|e| DbError::io(format!("Failed to move {}: {e}", from.display())))?;
+        }
+    }
+    Ok(())
+}
+
+fn repair_file(path: &Path) -> Result<RepairReport, DbError> {
+    let repaired = with_suffix(path, ".repaired");
+    let (tables, lost_objects) = salvage(path, &repaired).inspect_err(|_| {
+        let _ = std::fs::remove_file(&repaired);
+    })?;
+
+    // Keep the original (with its WAL, which may hold committed pages) as
+    // biblemarker.corrupt.db, then move the rebuilt file into place.
+    let corrupt = path.with_extension("corrupt.db");
+    quarantine(path, &corrupt)?;
+    std::fs::rename(&repaired, path)
+        .map_err(|e| DbError::io(format!("Failed to install repaired database: {e}")))?;
+
+    Ok(RepairReport {
+        tables,
+        lost_objects,
+        corrupt_copy: corrupt.display().to_string(),
+    })
+}
+
+/// Salvage a damaged database into a fresh file and swap it in, reporting
+/// what couldn't be recovered. The frontend must close its own connection
+/// first and reopen afterwards.
+#[command]
+pub async fn repair_database(app_handle: tauri::AppHandle) -> Result<RepairReport, DbError> {
+    let path = db::db_path(&app_handle)?;
+    db::write_lock::WRITE_LOCK.check()?;
+    db::connections::close_connections();
+    tauri::async_runtime::spawn_blocking(move || repair_file(&path))
+        .await
+        .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Repair task failed: {e}")))?
+}
+
+/// Auto-backups (autoBackup.ts) live in `<app data>/backups`, named so that
+/// sorting by name sorts by time.
+const BACKUP_DIR: &str = "backups";
+const BACKUP_PREFIX: &str = "BibleMarker-auto-backup-";
+
+/// Event emitted when startup found the database corrupt and replaced it.
+const RECOVERED_EVENT: &str = "db://recovered";
+
+/// What startup recovery did with a corrupt database.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseRecovery {
+    /// Why the database was considered corrupt.
+    pub problems: Vec<String>,
+    /// Where the corrupt file was moved.
+    #[serde(rename = "quarantinedPath")]
+    pub quarantined_path: String,
+    /// Newest backup that passed verification. The frontend restores it into
+    /// the fresh database it creates on open.
+    #[serde(rename = "backupPath")]
+    pub backup_path: Option<String>,
+    #[serde(rename = "backupTimestamp")]
+    pub backup_timestamp: Option<String>,
+    /// With no usable backup, the rows salvaged from the corrupt file instead.
+    pub salvaged: Option<Vec<TableSalvage>>,
+}
+
+/// Recovery performed at startup, held until the frontend asks for it (the
+/// event fires before any webview is listening).
+static PENDING_RECOVERY: Mutex<Option<DatabaseRecovery>> = Mutex::new(None);
+
+/// The backup's timestamp if `json` has the shape `validateBackup` in
+/// backup.ts requires: version, timestamp, preferences and every data array.
+fn verify_backup(json: &str) -> Option<String> {
+    let backup: serde_json::Value = serde_json::from_str(json).ok()?;
+    let text = |v: &serde_json::Value| {
+        v.as_str()
+            .filter(|s| !s.trim().is_empty())
+            .map(str::to_string)
+    };
+    text(&backup["version"])?;
+    let timestamp = text(&backup["timestamp"])?;
+    let data = backup.get("data")?.as_object()?;
+    data.get("preferences")?.as_object()?;
+    for field in [
+        "annotations",
+        "sectionHeadings",
+        "chapterTitles",
+        "notes",
+        "markingPresets",
+        "studies",
+        "multiTranslationViews",
+        "observationLists",
+        "applications",
+    ] {
+        data.get(field)?.as_array()?;
+    }
+    Some(timestamp)
+}
+
+/// Newest auto-backup in `dir` that parses and verifies, with its timestamp.
+fn latest_verified_backup(dir: &Path) -> Option<(PathBuf, String)> {
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(BACKUP_PREFIX) && n.ends_with(".json"))
+        })
+        .collect();
+    backups.sort();
+    backups.into_iter().rev().find_map(|path| {
+        let json = std::fs::read_to_string(&path).ok()?;
+        verify_backup(&json).map(|timestamp| (path, timestamp))
+    })
+}
+
+/// Problems found by a quick check of the database at `path`; empty when
+/// it's healthy or doesn't exist yet.
+fn startup_problems(path: &Path) -> Result<Vec<String>, DbError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let report = Connection::open(path)
+        .map_err(DbError::from)
+        .and_then(|conn| check_integrity(&conn, false));
+    match report {
+        Ok(report) => Ok(report.problems),
+        Err(e) if e.kind == DbErrorKind::Corrupt => Ok(vec![e.message]),
+        Err(e) => Err(e),
+    }
+}
+
+/// If the database at `path` is corrupt, quarantine it as
+/// `biblemarker.corrupt-<unix time>.db` and pick a replacement: the newest
+/// verified backup from `backups`, or failing that a salvaged copy of the
+/// corrupt file. With a backup, nothing is left at `path`, so the frontend
+/// creates a fresh database and restores into it.
+pub(crate) fn recover_if_corrupt(
+    path: &Path,
+    backups: &Path,
+) -> Result<Option<DatabaseRecovery>, DbError> {
+    let problems = startup_problems(path)?;
+    if problems.is_empty() {
+        return Ok(None);
+    }
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let quarantined = path.with_extension(format!("corrupt-{stamp}.db"));
+    quarantine(path, &quarantined)?;
+
+    let mut recovery = DatabaseRecovery {
+        problems,
+        quarantined_path: quarantined.display().to_string(),
+        backup_path: None,
+        backup_timestamp: None,
+        salvaged: None,
+    };
+    if let Some((backup, timestamp)) = latest_verified_backup(backups) {
+        recovery.backup_path = Some(backup.display().to_string());
+        recovery.backup_timestamp = Some(timestamp);
+    } else {
+        let salvaged = with_suffix(path, ".repaired");
+        match salvage(&quarantined, &salvaged) {
+            Ok((tables, _)) => {
+                std::fs::rename(&salvaged, path).map_err(|e| {
+                    DbError::io(format!("Failed to install salvaged database: {e}"))
+                })?;
+                recovery.salvaged = Some(tables);
+            }
+            // Start empty rather than not at all; the corrupt file is kept.
+            Err(_) => {
+                let _ = std::fs::remove_file(&salvaged);
+            }
+        }
+    }
+    Ok(Some(recovery))
+}
+
+/// Check the database before the frontend opens it, replacing a corrupt one
+/// (see `recover_if_corrupt`). Emits `db://recovered` and keeps the details
+/// for `take_database_recovery`.
+pub fn recover_on_startup(app_handle: &tauri::AppHandle) {
+    use tauri::{Emitter, Manager};
+
+    let Ok(path) = db::db_path(app_handle) else {
+        return;
+    };
+    let Ok(app_data) = app_handle.path().app_data_dir() else {
+        return;
+    };
+    match recover_if_corrupt(&path, &app_data.join(BACKUP_DIR)) {
+        Ok(Some(recovery)) => {
+            println!(
+                "[db_recovery] quarantined corrupt database at {} ({})",
+                recovery.quarantined_path,
+                match &recovery.backup_path {
+                    Some(backup) => format!("restoring {backup}"),
+                    None => "no verified backup; salvaged what was readable".into(),
+                }
+            );
+            let _ = app_handle.emit(RECOVERED_EVENT, recovery.clone());
+            *PENDING_RECOVERY.lock().unwrap_or_else(|e| e.into_inner()) = Some(recovery);
+        }
+        Ok(None) => {}
+        Err(e) => println!("[db_recovery] startup check failed: {e}"),
+    }
+}
+
+/// The recovery startup performed, if any. Returned once; the frontend
+/// restores the backup and tells the user what happened.
+#[command]
+pub fn take_database_recovery() -> Option<DatabaseRecovery> {
+    PENDING_RECOVERY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+}
+
+/// sync_config key holding when `maintain` last ran (local to the device).
+const LAST_VACUUM_KEY: &str = "last_vacuum_at";
+
+/// How often the idle scheduler looks at the database.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// The scheduler only vacuums when nothing was written for this long...
+const IDLE_AFTER_SECS: i64 = 10 * 60;
+
+/// ...and the last maintenance run is at least this old.
+const MAINTENANCE_INTERVAL_SECS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Serialize)]
+pub struct MaintenanceReport {
+    #[serde(rename = "bytesBefore")]
+    pub bytes_before: i64,
+    #[serde(rename = "bytesAfter")]
+    pub bytes_after: i64,
+    #[serde(rename = "reclaimedBytes")]
+    pub reclaimed_bytes: i64,
+    #[serde(rename = "ranAt")]
+    pub ran_at: String,
+}
+
+fn database_bytes(conn: &Connection) -> Result<i64, DbError> {
+    Ok(conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// VACUUM, ANALYZE and `PRAGMA optimize`, then checkpoint so the smaller
+/// file actually lands on disk instead of in the WAL.
+pub(crate) fn maintain(conn: &Connection) -> Result<MaintenanceReport, DbError> {
+    let bytes_before = database_bytes(conn)?;
+    conn.execute_batch("VACUUM; ANALYZE; PRAGMA optimize;")?;
+    checkpoint(conn)?;
+    let bytes_after = database_bytes(conn)?;
+    let ran_at = db::now_iso(conn)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO sync_config (key, value) VALUES (?1, ?2)",
+        [LAST_VACUUM_KEY, &ran_at],
+    )?;
+    Ok(MaintenanceReport {
+        bytes_before,
+        bytes_after,
+        reclaimed_bytes: bytes_before - bytes_after,
+        ran_at,
+    })
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: i64,
+    /// Space used by the table's own pages (not its indexes).
+    pub bytes: i64,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct IndexStats {
+    pub name: String,
+    pub table: String,
+    pub bytes: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseStats {
+    /// Size of the main database file.
+    #[serde(rename = "fileBytes")]
+    pub file_bytes: i64,
+    /// Size of the `-wal` sidecar; 0 right after a checkpoint.
+    #[serde(rename = "walBytes")]
+    pub wal_bytes: i64,
+    #[serde(rename = "pageSize")]
+    pub page_size: i64,
+    #[serde(rename = "pageCount")]
+    pub page_count: i64,
+    /// Unused pages that `maintain` (VACUUM) would give back.
+    #[serde(rename = "freePages")]
+    pub free_pages: i64,
+    #[serde(rename = "lastVacuumAt")]
+    pub last_vacuum_at: Option<String>,
+    /// Annotation counts by type (`highlight`, `symbol`, ...).
+    pub annotations: BTreeMap<String, i64>,
+    pub tables: Vec<TableStats>,
+    pub indexes: Vec<IndexStats>,
+}
+
+/// Row counts and page usage for every table and index. Virtual tables are
+/// skipped; the pages behind the FTS index show up as its shadow tables.
+pub(crate) fn database_stats(conn: &Connection) -> Result<DatabaseStats, DbError> {
+    let pragma = |name: &str| -> Result<i64, DbError> {
+        Ok(conn.query_row(&format!("PRAGMA {name}"), [], |row| row.get(0))?)
+    };
+    let page_size = pragma("page_size")?;
+    let page_count = pragma("page_count")?;
+    let free_pages = pragma("freelist_count")?;
+
+    let mut bytes: BTreeMap<String, i64> = BTreeMap::new();
+    {
+        let mut stmt = conn.prepare("SELECT name, SUM(pgsize) FROM dbstat GROUP BY name")?;
+        for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+            let (name, size) = row?;
+            bytes.insert(name, size);
+        }
+    }
+
+    let mut tables = Vec::new();
+    let mut indexes = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT type, name, tbl_name FROM sqlite_master
+         WHERE type IN ('table', 'index')
+           AND name NOT LIKE 'sqlite_%'
+           AND coalesce(sql, '') NOT LIKE 'CREATE VIRTUAL TABLE%'
+         ORDER BY name",
+    )?;
+    let objects = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (kind, name, table) in objects {
+        let size = bytes.get(&name).copied().unwrap_or(0);
+        if kind == "table" {
+            let rows = conn.query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+                row.get(0)
+            })?;
+            tables.push(TableStats {
+                name,
+                rows,
+                bytes: size,
+            });
+        } else {
+            indexes.push(IndexStats {
+                name,
+                table,
+                bytes: size,
+            });
+        }
+    }
+
+    let mut annotations = BTreeMap::new();
+    if tables.iter().any(|t| t.name == "annotations") {
+        let mut stmt = conn.prepare("SELECT type, COUNT(*) FROM annotations GROUP BY type")?;
+        for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+            let (kind, count) = row?;
+            annotations.insert(kind, count);
+        }
+    }
+
+    let last_vacuum_at = conn
+        .query_row(
+            "SELECT value FROM sync_config WHERE key = ?1",
+            [LAST_VACUUM_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    Ok(DatabaseStats {
+        file_bytes: page_count * page_size,
+        wal_bytes: 0,
+        page_size,
+        page_count,
+        free_pages,
+        last_vacuum_at,
+        annotations,
+        tables,
+        indexes,
+    })
+}
+
+/// Sizes and row counts for the settings screen, so users can see what the
+/// database holds and whether maintenance would help.
+#[command]
+pub async fn get_database_stats(app_handle: tauri::AppHandle) -> Result<DatabaseStats, DbError> {
+    let path = db::db_path(&app_handle)?;
+    let mut stats = db::with_reader(&app_handle, database_stats).await?;
+    let file_len = |p: &Path| std::fs::metadata(p).map_or(0, |m| m.len() as i64);
+    stats.file_bytes = file_len(&path).max(stats.file_bytes);
+    stats.wal_bytes = file_len(&with_suffix(&path, "-wal"));
+    Ok(stats)
+}
+
+/// Whether the idle scheduler should run `maintain` now: it's been a while
+/// since the last run and nothing has been written recently.
+pub(crate) fn maintenance_due(conn: &Connection) -> Result<bool, DbError> {
+    let seconds_since = |sql: &str| -> Result<Option<i64>, DbError> {
+        Ok(conn.query_row(
+            &format!("SELECT CAST((julianday('now') - julianday(({sql}))) * 86400 AS INTEGER)"),
+            [],
+            |row| row.get(0),
+        )?)
+    };
+    let since_vacuum = seconds_since(&format!(
+        "SELECT value FROM sync_config WHERE key = '{LAST_VACUUM_KEY}'"
+    ))?;
+    if since_vacuum.is_some_and(|s| s < MAINTENANCE_INTERVAL_SECS) {
+        return Ok(false);
+    }
+    let since_write = seconds_since("SELECT MAX(updated_at) FROM change_log")?;
+    Ok(since_write.is_none_or(|s| s >= IDLE_AFTER_SECS))
+}
+
+/// Compact the database and refresh query-planner statistics.
+#[command]
+pub async fn maintain_database(app_handle: tauri::AppHandle) -> Result<MaintenanceReport, DbError> {
+    db::with_connection(&app_handle, |conn| maintain(conn)).await
+}
+
+/// Start a background thread that purges expired trash and edit history and
+/// runs `maintain` about weekly, whenever the database has been idle for a
+/// while. Errors are logged and retried on the next check; a busy database
+/// just waits for the next one.
+pub fn spawn_idle_maintenance(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(IDLE_CHECK_INTERVAL);
+        let Ok(path) = db::db_path(&app_handle) else {
+            continue;
+        };
+        // The frontend creates the database; never create it from here.
+        if !path.exists() {
+            continue;
+        }
+        let result = db::connections::manager(&path).write(|conn| {
+            if maintenance_due(conn)? {
+                db::trash::purge_expired(conn)?;
+                db::edit_history::purge_expired(conn)?;
+                maintain(conn).map(Some)
+            } else {
+                Ok(None)
+            }
+        });
+        match result {
+            Ok(Some(report)) => println!(
+                "[idle_maintenance] reclaimed {} bytes",
+                report.reclaimed_bytes
+            ),
+            Ok(None) => {}
+            Err(e) => println!("[idle_maintenance] skipped: {e}"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn checkpoint_truncates_the_wal() {
+        let dir = ScratchDir::new("checkpoint");
+        let path = dir.join(db::DB_FILE);
+        let conn = db::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE t (x TEXT); INSERT INTO t VALUES ('a');")
+            .unwrap();
+        let wal = dir.join(format!("{}-wal", db::DB_FILE));
+        assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+
+        let result = checkpoint(&conn).unwrap();
+        assert!(!result.busy);
+        assert_eq!(result.log_frames, result.checkpointed_frames);
+        assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+
+        drop(conn);
+    }
+
+    /// A database whose `verses` table spans ~60 pages, with page 10 (a leaf
+    /// in the middle of the table) overwritten.
+    fn damaged_database(dir: &Path) -> PathBuf {
+        let path = dir.join(db::DB_FILE);
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "PRAGMA page_size = 4096;
+             CREATE TABLE verses (id INTEGER PRIMARY KEY, body TEXT NOT NULL);
+             CREATE TABLE notes (id TEXT PRIMARY KEY, content TEXT NOT NULL);
+             INSERT INTO notes VALUES ('n1', 'kept');
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2000)
+             INSERT INTO verses SELECT i, printf('%.100c', 'x') FROM n;
+             CREATE INDEX idx_verses_body ON verses(body);",
+        )
+        .unwrap();
+        drop(conn);
+
+        use std::io::{Seek, SeekFrom, Write};
+        let mut file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(9 * 4096)).unwrap();
+        file.write_all(&[0xA5; 4096]).unwrap();
+        path
+    }
+
+    #[test]
+    fn integrity_check_reports_damage() {
+        let dir = ScratchDir::new("integrity");
+        let healthy = Connection::open_in_memory().unwrap();
+        assert!(check_integrity(&healthy, true).unwrap().ok);
+
+        let path = damaged_database(&dir);
+        let conn = Connection::open(&path).unwrap();
+        let report = check_integrity(&conn, true).unwrap();
+        assert!(!report.ok);
+        assert!(!report.problems.is_empty());
+        drop(conn);
+    }
+
+    #[test]
+    fn repair_salvages_readable_rows_and_keeps_the_original() {
+        let dir = ScratchDir::new("repair");
+        let path = damaged_database(&dir);
+
+        let report = repair_file(&path).unwrap();
+        let verses = report.tables.iter().find(|t| t.table == "verses").unwrap();
+        assert!(!verses.complete);
+        assert!(
+            verses.recovered > 1900 && verses.recovered < 2000,
+            "{verses:?}"
+        );
+        let notes = report.tables.iter().find(|t| t.table == "notes").unwrap();
+        assert_eq!((notes.recovered, notes.lost, notes.complete), (1, 0, true));
+        assert!(std::path::Path::new(&report.corrupt_copy).exists());
+
+        let repaired = Connection::open(&path).unwrap();
+        assert!(check_integrity(&repaired, true).unwrap().ok);
+        let index: i64 = repaired
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'idx_verses_body'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(index, 1);
+        drop(repaired);
+    }
+
+    fn backup_json(timestamp: &str) -> String {
+        serde_json::json!({
+            "version": "1.0",
+            "timestamp": timestamp,
+            "data": {
+                "preferences": {},
+                "annotations": [], "sectionHeadings": [], "chapterTitles": [],
+                "notes": [], "markingPresets": [], "studies": [],
+                "multiTranslationViews": [], "observationLists": [], "applications": []
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn verifies_backups_like_the_frontend() {
+        assert_eq!(
+            verify_backup(&backup_json("2025-03-01T10:00:00Z")).as_deref(),
+            Some("2025-03-01T10:00:00Z")
+        );
+        assert_eq!(verify_backup("{\"version\":\"1.0\""), None);
+        let missing_notes = backup_json("2025-03-01").replace("\"notes\":[],", "");
+        assert_eq!(verify_backup(&missing_notes), None);
+    }
+
+    #[test]
+    fn recovery_quarantines_and_picks_the_newest_verified_backup() {
+        let dir = ScratchDir::new("recover-backup");
+        let path = damaged_database(&dir);
+        let backups = dir.join(BACKUP_DIR);
+        std::fs::create_dir_all(&backups).unwrap();
+        let older = backups.join(format!("{BACKUP_PREFIX}2025-03-01-100000.json"));
+        std::fs::write(&older, backup_json("2025-03-01T10:00:00Z")).unwrap();
+        // Newer but truncated mid-write: skipped.
+        std::fs::write(
+            backups.join(format!("{BACKUP_PREFIX}2025-03-02-100000.json")),
+            "{\"version\":",
+        )
+        .unwrap();
+
+        let recovery = recover_if_corrupt(&path, &backups).unwrap().unwrap();
+        assert!(!recovery.problems.is_empty());
+        assert_eq!(
+            recovery.backup_path.as_deref(),
+            Some(older.display().to_string().as_str())
+        );
+        assert!(recovery.salvaged.is_none());
+        assert!(Path::new(&recovery.quarantined_path).exists());
+        assert!(!path.exists(), "frontend starts from a fresh database");
+    }
+
+    #[test]
+    fn recovery_without_a_backup_salvages_the_corrupt_file() {
+        let dir = ScratchDir::new("recover-salvage");
+        let path = damaged_database(&dir);
+
+        let recovery = recover_if_corrupt(&path, &dir.join(BACKUP_DIR))
+            .unwrap()
+            .unwrap();
+        assert!(recovery.backup_path.is_none());
+        let tables = recovery.salvaged.unwrap();
+        assert!(tables
+            .iter()
+            .any(|t| t.table == "notes" && t.recovered == 1));
+        let conn = Connection::open(&path).unwrap();
+        assert!(check_integrity(&conn, true).unwrap().ok);
+        drop(conn);
+
+        // Healthy now, so the next startup leaves it alone.
+        assert!(recover_if_corrupt(&path, &dir.join(BACKUP_DIR))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn maintain_reclaims_space_and_records_the_run() {
+        let conn = db::test_connection();
+        conn.execute_batch(
+            "CREATE TABLE filler (body TEXT);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 500)
+             INSERT INTO filler SELECT printf('%.1000c', 'x') FROM n;
+             DELETE FROM filler;",
+        )
+        .unwrap();
+        assert!(maintenance_due(&conn).unwrap());
+
+        let report = maintain(&conn).unwrap();
+        assert!(report.reclaimed_bytes > 400_000, "{report:?}");
+        assert!(!maintenance_due(&conn).unwrap(), "just ran");
+    }
+
+    #[test]
+    fn stats_count_rows_and_pages() {
+        let conn = db::test_connection();
+        conn.execute_batch(
+            "INSERT INTO annotations (id, module_id, type, data, created_at, updated_at)
+             VALUES ('a1', 'kjv', 'highlight', '{}', '2025-01-01', '2025-01-01'),
+                    ('a2', 'kjv', 'highlight', '{}', '2025-01-01', '2025-01-01'),
+                    ('a3', 'kjv', 'symbol', '{}', '2025-01-01', '2025-01-01');
+             CREATE INDEX idx_notes_module ON notes(module_id);
+             CREATE VIRTUAL TABLE fts USING fts5(body);",
+        )
+        .unwrap();
+        maintain(&conn).unwrap();
+
+        let stats = database_stats(&conn).unwrap();
+        assert_eq!(stats.annotations.get("highlight"), Some(&2));
+        assert_eq!(stats.annotations.get("symbol"), Some(&1));
+        let annotations = stats
+            .tables
+            .iter()
+            .find(|t| t.name == "annotations")
+            .unwrap();
+        assert_eq!(annotations.rows, 3);
+        assert!(annotations.bytes > 0);
+        assert!(stats.tables.iter().all(|t| t.name != "fts"));
+        assert!(stats
+            .indexes
+            .iter()
+            .any(|i| i.name == "idx_notes_module" && i.table == "notes"));
+        assert_eq!(stats.file_bytes, stats.page_count * stats.page_size);
+        assert!(stats.last_vacuum_at.is_some());
+    }
+
+    #[test]
+    fn maintenance_waits_for_the_database_to_go_idle() {
+        let conn = db::test_connection();
+        conn.execute(
+            "INSERT INTO change_log (table_name, op, row_id, updated_at, device_id)
+             VALUES ('notes', 'upsert', 'n1', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), 'dev')",
+            [],
+        )
+        .unwrap();
+        assert!(!maintenance_due(&conn).unwrap());
+        conn.execute(
+            "UPDATE change_log SET updated_at = '2020-01-01T00:00:00.000Z'",
+            [],
+        )
+        .unwrap();
+        assert!(maintenance_due(&conn).unwrap());
+    }
+}
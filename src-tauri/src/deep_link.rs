@@ -0,0 +1,225 @@
+//! `bible://` and `biblemarker://` links into the app, from other apps, web
+//! pages and sermon slides:
+//!
+//! - `bible://John.3.16`, `bible://Rom 8:28-39` — any reference the parser
+//!   reads (see `content::references`), with `/` for `.` ("John/3/16")
+//! - `biblemarker://passage/Jn 3:16?translation=ESV`, or `passage?ref=...`
+//! - `biblemarker://note/<id>`
+//! - `biblemarker://search?q=living water`
+//!
+//! Both schemes take every form. Links are parsed into a `DeepLink` and
+//! emitted as `deep-link://open`; ones arriving before the frontend listens
+//! (the link that launched the app, say) wait for `take_deep_links`. A link
+//! only navigates or searches, so a web page can't change anything with one.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{command, Emitter};
+
+use crate::content::references::{self, ScriptureReference};
+
+/// URL schemes registered for the app (`plugins.deep-link` in tauri.conf.json).
+const SCHEMES: &[&str] = &["bible", "biblemarker"];
+
+/// Emitted with a `DeepLink` when a link is opened.
+const OPEN_EVENT: &str = "deep-link://open";
+
+/// Query parameters naming the passage.
+const REFERENCE_PARAMS: &[&str] = &["ref", "reference", "passage"];
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DeepLink {
+    Passage {
+        references: Vec<ScriptureReference>,
+        /// Module id to read the passage in, when the link names one.
+        translation: Option<String>,
+    },
+    Note {
+        id: String,
+    },
+    Search {
+        query: String,
+    },
+}
+
+/// Links waiting for the frontend; `None` once it has taken them and listens.
+static PENDING: Mutex<Option<Vec<DeepLink>>> = Mutex::new(Some(Vec::new()));
+
+/// `s` with `%XX` escapes decoded and `+` as a space.
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (_, Some(byte)) => {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', None) => out.push(b' '),
+            (byte, None) => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn passage(text: &str, translation: Option<String>) -> Option<DeepLink> {
+    let references = references::parse(text);
+    (!references.is_empty()).then_some(DeepLink::Passage {
+        references,
+        translation,
+    })
+}
+
+/// What `link` opens, if it's one of the app's links.
+pub(crate) fn parse(link: &str) -> Option<DeepLink> {
+    let (scheme, rest) = link.trim().split_once(':')?;
+    if !SCHEMES.iter().any(|s| s.eq_ignore_ascii_case(scheme)) {
+        return None;
+    }
+    let rest = rest.trim_start_matches('/');
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let path = path.trim_end_matches('/');
+    let params: HashMap<String, String> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key).to_ascii_lowercase(), decode(value))
+        })
+        .collect();
+    let param = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| params.get(*name))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let translation = param(&["translation", "version"]);
+
+    let (kind, value) = path.split_once('/').unwrap_or((path, ""));
+    let value = Some(decode(value).trim().to_string()).filter(|v| !v.is_empty());
+    match kind.to_ascii_lowercase().as_str() {
+        "note" => Some(DeepLink::Note {
+            id: value.or_else(|| param(&["id"]))?,
+        }),
+        "search" => Some(DeepLink::Search {
+            query: param(&["q", "query"]).or(value)?,
+        }),
+        "passage" => passage(&value.or_else(|| param(REFERENCE_PARAMS))?, translation),
+        _ => {
+            let text = param(REFERENCE_PARAMS).unwrap_or_else(|| decode(path).replace('/', "."));
+            passage(&text, translation)
+        }
+    }
+}
+
+/// Open `link`: emit it, or keep it until the frontend takes pending links.
+/// Links that aren't the app's are ignored.
+pub(crate) fn open(app: &tauri::AppHandle, link: &str) {
+    let Some(link) = parse(link) else {
+        return;
+    };
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    match pending.as_mut() {
+        Some(waiting) => waiting.push(link),
+        None => {
+            let _ = app.emit(OPEN_EVENT, link);
+        }
+    }
+}
+
+/// Open the links the app is launched with, and every link after.
+pub(crate) fn listen(app: &tauri::AppHandle) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    // Installers register the schemes; AppImages and dev builds only do
+    // when running.
+    #[cfg(any(windows, target_os = "linux"))]
+    let _ = app.deep_link().register_all();
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open(&handle, url.as_str());
+        }
+    });
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            open(app, url.as_str());
+        }
+    }
+}
+
+/// Links opened before the frontend listened for `deep-link://open`; later
+/// ones are only emitted.
+#[command]
+pub fn take_deep_links() -> Vec<DeepLink> {
+    PENDING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osis(link: &str) -> Vec<String> {
+        match parse(link) {
+            Some(DeepLink::Passage { references, .. }) => {
+                references.into_iter().map(|r| r.osis).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_passages_notes_and_searches() {
+        assert_eq!(osis("bible://John.3.16"), ["John.3.16"]);
+        assert_eq!(osis("bible://John/3/16/"), ["John.3.16"]);
+        assert_eq!(osis("bible:Rom%208:28-39"), ["Rom.8.28-Rom.8.39"]);
+        assert_eq!(osis("BIBLE://Jn 3:16; Ps 23"), ["John.3.16", "Ps.23"]);
+        assert_eq!(osis("bible://?ref=Gen+1"), ["Gen.1"]);
+        assert_eq!(
+            parse("biblemarker://passage/Jn%203:16?translation=ESV"),
+            Some(DeepLink::Passage {
+                references: references::parse("John 3:16"),
+                translation: Some("ESV".to_string()),
+            })
+        );
+        assert_eq!(
+            osis("biblemarker://passage?ref=Matt%205-7#top"),
+            ["Matt.5-Matt.7"]
+        );
+
+        assert_eq!(
+            parse("biblemarker://note/abc-123"),
+            Some(DeepLink::Note {
+                id: "abc-123".to_string()
+            })
+        );
+        assert_eq!(
+            parse("bible://search?q=living+water%21"),
+            Some(DeepLink::Search {
+                query: "living water!".to_string()
+            })
+        );
+
+        assert_eq!(parse("https://biblemarker.app/John.3.16"), None);
+        assert_eq!(parse("biblemarker://note/"), None);
+        assert_eq!(parse("biblemarker://search"), None);
+        assert_eq!(parse("bible://settings/delete"), None);
+        assert_eq!(decode("100%"), "100%");
+    }
+}
@@ -37,7 +37,7 @@ fn is_allowed_dest(dest: &Path, sword_dir: &Path) -> bool {
 ///
 /// `digest` 0.11 returns an `Array` from `finalize()` that no longer implements
 /// `LowerHex`, so `format!("{:x}", ...)` is no longer available.
-fn to_hex(bytes: &[u8]) -> String {
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
     use std::fmt::Write;
     let mut s = String::with_capacity(bytes.len() * 2);
     for b in bytes {
@@ -47,7 +47,7 @@ fn to_hex(bytes: &[u8]) -> String {
 }
 
 /// Stream-hash a file on disk with SHA-256. Returns hex digest.
-fn hash_file(path: &Path) -> Result<String, std::io::Error> {
+pub(crate) fn hash_file(path: &Path) -> Result<String, std::io::Error> {
     let mut file = std::fs::File::open(path)?;
     let mut hasher = Sha256::new();
     let mut buf = vec![0u8; HASH_BUFFER_SIZE];
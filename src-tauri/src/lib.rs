@@ -5,9 +5,21 @@ mod mobile;
 #[cfg(mobile)]
 pub use mobile::*;
 
-// Database maintenance (corruption recovery)
+// Verse references on the clipboard: on-demand and global-shortcut lookup
+mod clipboard;
+
+// Read-only translation content databases, attached alongside the user database
+mod content;
+
+// Native database layer (rusqlite) for hot paths
+mod db;
+
+// Database maintenance (integrity/repair, vacuum, WAL checkpoints)
 mod db_maintenance;
 
+// bible:// and biblemarker:// links into passages, notes and search
+mod deep_link;
+
 // File download (bypasses webview CORS)
 mod download;
 
@@ -17,9 +29,19 @@ mod flatpak;
 // Authenticated download for Lockman-licensed modules (NASB)
 mod signed_download;
 
+// Translation catalog and downloads
+mod translations;
+
+// Native sync helpers (database/bundle merge, blob encryption)
+mod sync;
+
 // Sync-server client: email-OTP auth + secure session-token storage
 mod sync_client;
 
+// Scratch directories and other helpers shared by unit tests
+#[cfg(test)]
+mod test_util;
+
 pub type SetupHook = Box<dyn FnOnce(&mut App) -> Result<(), Box<dyn std::error::Error>> + Send>;
 
 #[derive(Default)]
@@ -42,8 +64,27 @@ impl AppBuilder {
     }
 
     pub fn run(self) {
+        db::demo::init();
         let setup = self.setup;
-        let mut builder = tauri::Builder::default()
+        let mut builder = tauri::Builder::default();
+
+        // Must come first: a second launch (how Windows and Linux open a
+        // link) hands its arguments to the running app and exits, and the
+        // deep-link feature passes link arguments on to `deep_link::listen`.
+        #[cfg(desktop)]
+        {
+            builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+                use tauri::Manager;
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.unminimize();
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }));
+        }
+
+        builder = builder
+            .plugin(tauri_plugin_deep_link::init())
             .plugin(tauri_plugin_dialog::init())
             .plugin(tauri_plugin_fs::init())
             .plugin(tauri_plugin_sql::Builder::new().build())
@@ -51,7 +92,14 @@ impl AppBuilder {
 
         #[cfg(desktop)]
         {
-            builder = builder.plugin(tauri_plugin_process::init());
+            builder = builder
+                .plugin(tauri_plugin_process::init())
+                .plugin(tauri_plugin_clipboard_manager::init())
+                .plugin(
+                    tauri_plugin_global_shortcut::Builder::new()
+                        .with_handler(clipboard::on_shortcut)
+                        .build(),
+                );
             // Skip the in-app updater under Flatpak — Flathub manages updates.
             if !flatpak::is_flatpak() {
                 builder = builder.plugin(tauri_plugin_updater::Builder::new().build());
@@ -60,7 +108,176 @@ impl AppBuilder {
 
         builder
             .invoke_handler(tauri::generate_handler![
+                db::annotations::get_chapter_annotations,
+                db::annotations::create_annotation,
+                db::annotations::update_annotation,
+                db::annotations::delete_annotation,
+                db::annotations::db_bulk_insert_markings,
+                db::annotations::bulk_apply_markings,
+                db::anchors::reanchor_annotations,
+                db::anchors::get_orphaned_annotations,
+                db::migrations::run_database_migrations,
+                db::migrations::dry_run_database_migrations,
+                db::search::search_fulltext,
+                db::note_references::get_notes_referencing,
+                db::backlinks::get_backlinks,
+                db::backlinks::get_unlinked_mentions,
+                db::symbol_library::get_symbol_library,
+                db::symbol_library::create_library_symbol,
+                db::symbol_library::update_library_symbol,
+                db::symbol_library::delete_library_symbol,
+                db::tags::list_tags,
+                db::tags::create_tag,
+                db::tags::update_tag,
+                db::tags::rename_tag,
+                db::tags::merge_tags,
+                db::tags::delete_tag,
+                db::tags::tag_item,
+                db::tags::untag_item,
+                db::tags::get_item_tags,
+                db::tags::get_items_by_tag,
+                db::bookmarks::get_bookmarks,
+                db::bookmarks::create_bookmark,
+                db::bookmarks::update_bookmark,
+                db::bookmarks::delete_bookmark,
+                db::bookmarks::get_bookmark_folders,
+                db::bookmarks::create_bookmark_folder,
+                db::bookmarks::update_bookmark_folder,
+                db::bookmarks::delete_bookmark_folder,
+                db::collections::get_verse_collections,
+                db::collections::get_verse_collection,
+                db::collections::create_verse_collection,
+                db::collections::update_verse_collection,
+                db::collections::delete_verse_collection,
+                db::collections::add_to_verse_collection,
+                db::collections::add_current_selection_to_list,
+                db::collections::update_verse_collection_entry,
+                db::collections::remove_from_verse_collection,
+                db::collections::reorder_verse_collection,
+                db::collections::export_verse_collection,
+                db::workspaces::get_workspaces,
+                db::workspaces::create_workspace,
+                db::workspaces::update_workspace,
+                db::workspaces::delete_workspace,
+                db::workspaces::add_to_workspace,
+                db::workspaces::remove_from_workspace,
+                db::workspaces::get_workspace_summary,
+                db::workspaces::export_workspace,
+                db::workspaces::import_workspace,
+                db::highlight_styles::get_highlight_styles,
+                db::highlight_styles::create_highlight_style,
+                db::highlight_styles::update_highlight_style,
+                db::highlight_styles::delete_highlight_style,
+                db::marking_templates::get_marking_templates,
+                db::marking_templates::create_marking_template,
+                db::marking_templates::update_marking_template,
+                db::marking_templates::delete_marking_template,
+                db::marking_templates::preview_marking_template,
+                db::marking_templates::apply_marking_template,
+                db::marking_templates::remove_template_markings,
+                db::marking_stats::get_marking_stats,
+                db::observation_lists::generate_observation_list,
+                db::observation_lists::refresh_observation_list,
+                db::margin_notes::get_margin_notes,
+                db::margin_notes::create_margin_note,
+                db::margin_notes::update_margin_note,
+                db::margin_notes::delete_margin_note,
+                db::attachments::add_attachment,
+                db::attachments::get_attachment_path,
+                db::attachments::get_attachment_usage,
+                db::attachments::collect_attachment_garbage,
+                db::resource_links::get_resource_links,
+                db::resource_links::create_resource_link,
+                db::resource_links::update_resource_link,
+                db::resource_links::delete_resource_link,
+                db::resource_links::fetch_resource_metadata,
+                db::bulk_edit::bulk_edit_annotations,
+                db::edit_history::get_annotation_history,
+                db::edit_history::revert_annotation,
+                db::demo::get_database_mode,
+                db::demo::seed_demo_database,
+                db::snapshots::list_snapshots,
+                db::snapshots::restore_snapshot,
+                db::trash::list_trash,
+                db::trash::restore_item,
+                db::trash::empty_trash,
+                db::undo::undo_last,
+                db::undo::redo,
+                db::undo::get_undo_stack,
+                db::undo::apply_remote_delete,
+                db::dump::export_sql_dump,
+                db::dump::import_sql_dump,
+                db::write_lock::set_db_write_lock,
+                db::write_lock::get_db_write_lock_state,
                 db_maintenance::delete_local_database,
+                db_maintenance::checkpoint_database,
+                db_maintenance::check_database_integrity,
+                db_maintenance::repair_database,
+                db_maintenance::maintain_database,
+                db_maintenance::get_database_stats,
+                db_maintenance::take_database_recovery,
+                db::connections::set_database_busy_timeout,
+                content::mount_content,
+                content::unmount_content,
+                content::list_mounted_content,
+                content::get_content_chapter,
+                content::words::get_word_data,
+                content::words::get_morphology,
+                content::words::decode_morphology,
+                content::interlinear::get_interlinear,
+                content::footnotes::search_footnotes,
+                content::lexicon::list_lexicons,
+                content::lexicon::import_lexicon,
+                content::lexicon::import_sword_lexicon,
+                content::lexicon::remove_lexicon,
+                content::lexicon::lookup_lexicon,
+                content::pericopes::list_pericope_sets,
+                content::pericopes::import_pericopes,
+                content::pericopes::import_pericopes_from_translation,
+                content::pericopes::remove_pericope_set,
+                content::pericopes::get_pericopes,
+                content::pericopes::get_pericope_at,
+                content::pericopes::search_pericopes,
+                content::pericopes::get_pericope_annotations,
+                content::audio::import_audio_manifest,
+                content::audio::list_audio_bibles,
+                content::audio::remove_audio_bible,
+                content::audio::get_chapter_audio,
+                content::audio::save_playback_position,
+                content::audio::get_playback_position,
+                content::usfm::import_usfm,
+                content::usx::import_usx,
+                content::usx::import_dbl_bundle,
+                content::sword::scan_sword_library,
+                content::sword::list_sword_modules,
+                content::sword::import_sword_modules,
+                content::zefania::import_zefania,
+                content::zefania::import_opensong,
+                content::esword::import_esword,
+                content::theword::import_theword,
+                content::theword::import_mysword,
+                content::original::import_sblgnt,
+                content::original::import_wlc,
+                content::plaintext::preview_text_import,
+                content::plaintext::import_text,
+                content::parallel::get_parallel_chapter,
+                content::references::parse_references,
+                content::references::check_references,
+                content::citation::format_reference,
+                content::ranges::reference_ranges,
+                content::canon::get_canon_structure,
+                clipboard::lookup_clipboard_reference,
+                clipboard::set_clipboard_shortcut,
+                deep_link::take_deep_links,
+                translations::catalog::list_available_translations,
+                translations::catalog::download_translation,
+                translations::manager::list_installed_translations,
+                translations::manager::get_translation_info,
+                translations::manager::update_translation,
+                translations::manager::remove_translation,
+                translations::license::get_translation_license,
+                translations::license::set_translation_license,
+                translations::integrity::verify_translation,
                 download::download_file,
                 download::install_bundled_module,
                 flatpak::check_flatpak,
@@ -75,14 +292,33 @@ impl AppBuilder {
                 sync_client::sync_list,
                 sync_client::sync_remove,
                 sync_client::delete_account,
+                sync_client::cancel_sync,
+                sync_client::setup_sync_encryption,
+                sync_client::rotate_sync_encryption,
+                sync_client::disable_sync_encryption,
+                sync_client::get_sync_encryption_status,
+                sync_client::create_sync_pairing,
+                sync_client::accept_sync_pairing,
+                sync::merge::merge_remote_database,
+                sync::bundle::export_sync_bundle,
+                sync::bundle::import_sync_bundle,
             ])
             .setup(move |app| {
                 if let Some(setup) = setup {
                     (setup)(app)?;
                 }
+                db_maintenance::recover_on_startup(app.handle());
+                deep_link::listen(app.handle());
+                db_maintenance::spawn_idle_maintenance(app.handle().clone());
+                db::snapshots::spawn_rolling_snapshots(app.handle().clone());
                 Ok(())
             })
-            .run(tauri::generate_context!())
-            .expect("error while running tauri application");
+            .build(tauri::generate_context!())
+            .expect("error while running tauri application")
+            .run(|_, event| {
+                if let tauri::RunEvent::Exit = event {
+                    db::demo::cleanup();
+                }
+            });
     }
 }
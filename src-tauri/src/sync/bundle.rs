@@ -0,0 +1,281 @@
+//! Manual sync bundles (`.bmsync`) for devices that never share a network.
+//!
+//! A bundle is a small SQLite file holding copies of the synced rows (and
+//! tombstones) changed since an optional cutoff, plus a `bmsync_manifest`
+//! table describing where and when it was made. Carry it over on a USB stick
+//! and `import_sync_bundle` merges it with the same engine as
+//! `merge_remote_database`, so newest-wins, tombstones and change logging all
+//! behave exactly as they do for a full database file.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::merge::{self, MergeReport, SYNCED_TABLES};
+use crate::db::{self, DbError};
+
+/// Identifies the file as a bundle (`bmsync_manifest.format`).
+const BUNDLE_FORMAT: &str = "bmsync";
+
+/// Bumped when the bundle layout changes; newer bundles are refused.
+pub(crate) const BUNDLE_VERSION: u32 = 1;
+
+/// What a bundle contains, as stored in its `bmsync_manifest` table.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct BundleManifest {
+    pub version: u32,
+    #[serde(rename = "deviceId")]
+    pub device_id: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    /// Only rows changed after this instant were included (`None` = everything).
+    pub since: Option<String>,
+    pub rows: usize,
+    pub tombstones: usize,
+}
+
+/// Copy rows changed after `since` from `main` into the attached `bundle`
+/// schema and write its manifest.
+pub(crate) fn write_bundle(
+    conn: &Connection,
+    device_id: &str,
+    since: Option<&str>,
+) -> rusqlite::Result<BundleManifest> {
+    let mut rows = 0;
+    for &table in SYNCED_TABLES {
+        if !merge::table_exists(conn, "main", table)? {
+            continue;
+        }
+        let has_updated_at = merge::columns(conn, "main", table)?
+            .iter()
+            .any(|c| c == "updated_at");
+        let copy = format!("CREATE TABLE bundle.{table} AS SELECT * FROM main.{table}");
+        if has_updated_at {
+            conn.execute(
+                &format!("{copy} WHERE ?1 IS NULL OR updated_at > ?1"),
+                [since],
+            )?;
+        } else {
+            conn.execute(&copy, [])?;
+        }
+        rows += conn.query_row(&format!("SELECT COUNT(*) FROM bundle.{table}"), [], |r| {
+            r.get::<_, i64>(0)
+        })? as usize;
+    }
+
+    let mut tombstones = 0;
+    if merge::table_exists(conn, "main", "sync_tombstones")? {
+        conn.execute(
+            "CREATE TABLE bundle.sync_tombstones AS SELECT * FROM main.sync_tombstones
+             WHERE ?1 IS NULL OR deleted_at > ?1",
+            [since],
+        )?;
+        tombstones = conn.query_row("SELECT COUNT(*) FROM bundle.sync_tombstones", [], |r| {
+            r.get::<_, i64>(0)
+        })? as usize;
+    }
+
+    let created_at: String =
+        conn.query_row("SELECT strftime('%Y-%m-%dT%H:%M:%fZ', 'now')", [], |r| {
+            r.get(0)
+        })?;
+    conn.execute_batch(
+        "CREATE TABLE bundle.bmsync_manifest (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+    )?;
+    let entries = [
+        ("format", Some(BUNDLE_FORMAT.to_string())),
+        ("version", Some(BUNDLE_VERSION.to_string())),
+        ("device_id", Some(device_id.to_string())),
+        ("created_at", Some(created_at.clone())),
+        ("since", since.map(str::to_string)),
+        ("rows", Some(rows.to_string())),
+        ("tombstones", Some(tombstones.to_string())),
+    ];
+    for (key, value) in entries {
+        if let Some(value) = value {
+            conn.execute(
+                "INSERT INTO bundle.bmsync_manifest (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )?;
+        }
+    }
+
+    Ok(BundleManifest {
+        version: BUNDLE_VERSION,
+        device_id: device_id.to_string(),
+        created_at,
+        since: since.map(str::to_string),
+        rows,
+        tombstones,
+    })
+}
+
+/// Read and check the manifest of a bundle attached as `schema`.
+pub(crate) fn read_manifest(conn: &Connection, schema: &str) -> Result<BundleManifest, String> {
+    let not_a_bundle = || "Not a BibleMarker sync bundle".to_string();
+    if !merge::table_exists(conn, schema, "bmsync_manifest").map_err(|e| e.to_string())? {
+        return Err(not_a_bundle());
+    }
+    let get = |key: &str| -> Result<Option<String>, String> {
+        conn.query_row(
+            &format!("SELECT value FROM {schema}.bmsync_manifest WHERE key = ?1"),
+            [key],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read bundle manifest: {e}"))
+    };
+    let count = |key: &str| -> Result<usize, String> {
+        Ok(get(key)?.and_then(|v| v.parse().ok()).unwrap_or(0))
+    };
+
+    if get("format")?.as_deref() != Some(BUNDLE_FORMAT) {
+        return Err(not_a_bundle());
+    }
+    let version: u32 = get("version")?
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(not_a_bundle)?;
+    if version > BUNDLE_VERSION {
+        return Err(format!(
+            "This bundle was made by a newer version of BibleMarker (format {version}); update the app to import it"
+        ));
+    }
+    Ok(BundleManifest {
+        version,
+        device_id: get("device_id")?.unwrap_or_default(),
+        created_at: get("created_at")?.unwrap_or_default(),
+        since: get("since")?,
+        rows: count("rows")?,
+        tombstones: count("tombstones")?,
+    })
+}
+
+fn export_file(
+    conn: &Connection,
+    out: &Path,
+    since: Option<&str>,
+) -> Result<BundleManifest, DbError> {
+    let device_id = db::device_id(conn)?;
+
+    // Build next to the destination and rename into place, so a failed export
+    // never leaves a half-written bundle under the chosen name.
+    let tmp = out.with_extension("bmsync.tmp");
+    let _ = std::fs::remove_file(&tmp);
+    conn.execute("ATTACH DATABASE ?1 AS bundle", [tmp.to_string_lossy()])
+        .map_err(|e| DbError::from(e).context(format!("Failed to create {}", tmp.display())))?;
+    let result = write_bundle(conn, &device_id, since);
+    let _ = conn.execute("DETACH DATABASE bundle", []);
+
+    match result {
+        Ok(manifest) => {
+            std::fs::rename(&tmp, out)
+                .map_err(|e| DbError::io(format!("Failed to write {}: {e}", out.display())))?;
+            Ok(manifest)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            Err(DbError::from(e).context("Export failed"))
+        }
+    }
+}
+
+/// Package synced rows changed after `since` (RFC 3339; omit for everything)
+/// into a `.bmsync` bundle at `path`.
+#[tauri::command]
+pub async fn export_sync_bundle(
+    app: tauri::AppHandle,
+    path: String,
+    since: Option<String>,
+) -> Result<BundleManifest, DbError> {
+    let out = PathBuf::from(path);
+    db::with_connection(&app, move |conn| export_file(conn, &out, since.as_deref())).await
+}
+
+/// Merge the `.bmsync` bundle at `path` into the local database.
+#[tauri::command]
+pub async fn import_sync_bundle(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<MergeReport, DbError> {
+    let bundle = PathBuf::from(path);
+    db::with_connection(&app, move |conn| {
+        merge::merge_file(conn, &bundle, |conn| {
+            read_manifest(conn, "remote").map(|_| ())
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE annotations (
+                id TEXT PRIMARY KEY, module_id TEXT NOT NULL, type TEXT NOT NULL,
+                data TEXT NOT NULL, preset_id TEXT, created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL, sync_status TEXT, device_id TEXT);
+             CREATE TABLE sync_tombstones (
+                table_name TEXT NOT NULL, row_id TEXT NOT NULL,
+                deleted_at TEXT NOT NULL, device_id TEXT NOT NULL,
+                PRIMARY KEY (table_name, row_id));
+             INSERT INTO annotations VALUES
+                ('old', 'kjv', 'highlight', '{}', NULL, '2024-01-01', '2024-01-01', NULL, 'dev-a'),
+                ('new', 'kjv', 'highlight', '{}', NULL, '2025-01-01', '2025-03-01', NULL, 'dev-a');
+             INSERT INTO sync_tombstones VALUES
+                ('annotations', 'gone', '2025-02-01', 'dev-a'),
+                ('annotations', 'ancient', '2023-01-01', 'dev-a');
+             ATTACH DATABASE ':memory:' AS bundle;",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn ids(conn: &Connection, sql: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        stmt.query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn includes_only_rows_changed_since_cutoff() {
+        let conn = setup();
+        let manifest = write_bundle(&conn, "dev-a", Some("2025-01-15")).unwrap();
+        assert_eq!(manifest.rows, 1);
+        assert_eq!(manifest.tombstones, 1);
+        assert_eq!(ids(&conn, "SELECT id FROM bundle.annotations"), ["new"]);
+        assert_eq!(
+            ids(&conn, "SELECT row_id FROM bundle.sync_tombstones"),
+            ["gone"]
+        );
+    }
+
+    #[test]
+    fn no_cutoff_exports_everything_and_manifest_round_trips() {
+        let conn = setup();
+        let written = write_bundle(&conn, "dev-a", None).unwrap();
+        assert_eq!(written.rows, 2);
+        assert_eq!(written.tombstones, 2);
+        assert_eq!(read_manifest(&conn, "bundle").unwrap(), written);
+    }
+
+    #[test]
+    fn rejects_plain_databases_and_newer_formats() {
+        let conn = setup();
+        assert!(read_manifest(&conn, "main").unwrap_err().contains("Not a"));
+
+        write_bundle(&conn, "dev-a", None).unwrap();
+        conn.execute(
+            "UPDATE bundle.bmsync_manifest SET value = '99' WHERE key = 'version'",
+            [],
+        )
+        .unwrap();
+        assert!(read_manifest(&conn, "bundle")
+            .unwrap_err()
+            .contains("newer version"));
+    }
+}
@@ -0,0 +1,394 @@
+//! End-to-end encryption of sync blobs.
+//!
+//! When enabled, every blob `sync_write` uploads is sealed with AES-256-GCM
+//! before it leaves the device, and `sync_read` opens it again, so the sync
+//! server (and anyone with access to it) only ever stores ciphertext. The
+//! engine above the transport is unaware of it.
+//!
+//! The key is derived from a user passphrase with PBKDF2-HMAC-SHA256. The salt
+//! and iteration count are not secret and live on the server in
+//! [`PARAMS_KEY`] so a second device can derive the same key from the same
+//! passphrase; a sealed check value there lets it reject a wrong passphrase
+//! up front instead of failing on the first journal.
+//!
+//! Keys are kept in the OS keychain under the signed-in account (see
+//! `sync_client`), never in the synced database. Rotation adds a key rather than replacing it:
+//! older blobs name their key id and stay readable until compaction rewrites
+//! them.
+//!
+//! A device that already has the keys can hand them to a new one without the
+//! passphrase: [`encode_pairing`] packs the keyring into a short-lived string
+//! the UI renders as a QR code, and [`decode_pairing`] unpacks a scanned one.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit as _, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::{STANDARD as B64, URL_SAFE_NO_PAD};
+use base64::Engine as _;
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// Blob key (outside every device folder) holding the [`KeyParams`].
+pub(crate) const PARAMS_KEY: &str = "keys/encryption.json";
+
+/// OWASP's 2023 recommendation for PBKDF2-HMAC-SHA256.
+pub(crate) const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Prefix (with format version) of a pairing string.
+const PAIRING_PREFIX: &str = "bmpair1:";
+
+/// How long a pairing string stays valid. It carries raw keys, so it should
+/// only live as long as the QR code is on screen.
+pub(crate) const PAIRING_TTL_SECS: u64 = 10 * 60;
+
+/// Plaintext sealed into [`KeyParams::check`] to verify a passphrase.
+const CHECK_PLAINTEXT: &str = "biblemarker-sync-key-check";
+
+/// Server-side description of the current key (no secret material).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct KeyParams {
+    #[serde(rename = "keyId")]
+    pub(crate) key_id: String,
+    /// Base64 PBKDF2 salt.
+    pub(crate) salt: String,
+    pub(crate) iterations: u32,
+    /// [`CHECK_PLAINTEXT`] sealed with the derived key.
+    pub(crate) check: String,
+}
+
+/// A derived key and the id blobs use to name it.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct StoredKey {
+    pub(crate) id: String,
+    /// Base64 AES-256 key.
+    pub(crate) key: String,
+}
+
+/// The local key file: every key this device can open, plus the one it seals with.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub(crate) struct Keyring {
+    pub(crate) current: Option<String>,
+    pub(crate) keys: Vec<StoredKey>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    bmenc: u32,
+    kid: String,
+    nonce: String,
+    ct: String,
+}
+
+impl Keyring {
+    fn key_bytes(&self, id: &str) -> Option<[u8; 32]> {
+        let stored = self.keys.iter().find(|k| k.id == id)?;
+        B64.decode(&stored.key).ok()?.try_into().ok()
+    }
+
+    /// Add `key` and make it current; replaces an existing entry with the same id.
+    pub(crate) fn install(&mut self, id: &str, key: &[u8; 32]) {
+        self.keys.retain(|k| k.id != id);
+        self.keys.push(StoredKey {
+            id: id.to_string(),
+            key: B64.encode(key),
+        });
+        self.current = Some(id.to_string());
+    }
+
+    /// Adopt every key from `other` and its current key; keys only this
+    /// device has are kept.
+    pub(crate) fn absorb(&mut self, other: Keyring) {
+        for key in other.keys {
+            self.keys.retain(|k| k.id != key.id);
+            self.keys.push(key);
+        }
+        if other.current.is_some() {
+            self.current = other.current;
+        }
+    }
+
+    /// Seal `plaintext` with the current key, or `None` when encryption is off.
+    pub(crate) fn seal(&self, plaintext: &str) -> Result<Option<String>, String> {
+        let Some(id) = self.current.as_deref() else {
+            return Ok(None);
+        };
+        let key = self
+            .key_bytes(id)
+            .ok_or_else(|| format!("current key {id} missing from keyring"))?;
+        seal_with(&key, id, plaintext).map(Some)
+    }
+
+    /// Open a blob. Blobs that aren't envelopes are returned as-is, so data
+    /// written before encryption was turned on stays readable.
+    pub(crate) fn open(&self, content: &str) -> Result<String, String> {
+        let Some(envelope) = parse_envelope(content) else {
+            return Ok(content.to_string());
+        };
+        let key = self.key_bytes(&envelope.kid).ok_or_else(|| {
+            format!(
+                "blob is encrypted with key {} — enter the sync passphrase on this device",
+                envelope.kid
+            )
+        })?;
+        open_envelope(&key, &envelope)
+    }
+}
+
+/// What a pairing QR code carries.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PairingPayload {
+    /// The pairing device's sync account; the scanner must be signed in to it.
+    #[serde(rename = "accountId")]
+    pub(crate) account_id: String,
+    /// Unix seconds after which the payload is refused.
+    pub(crate) exp: u64,
+    pub(crate) keyring: Keyring,
+}
+
+pub(crate) fn encode_pairing(payload: &PairingPayload) -> Result<String, String> {
+    let json = serde_json::to_vec(payload).map_err(|e| format!("serialize pairing: {e}"))?;
+    Ok(format!("{PAIRING_PREFIX}{}", URL_SAFE_NO_PAD.encode(json)))
+}
+
+/// Parse a scanned pairing string, refusing it once `now` (Unix seconds) is
+/// past its expiry or if it carries no current key.
+pub(crate) fn decode_pairing(text: &str, now: u64) -> Result<PairingPayload, String> {
+    let encoded = text
+        .trim()
+        .strip_prefix(PAIRING_PREFIX)
+        .ok_or("not a BibleMarker pairing code")?;
+    let json = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|_| "pairing code is damaged")?;
+    let payload: PairingPayload =
+        serde_json::from_slice(&json).map_err(|_| "pairing code is damaged")?;
+    if now > payload.exp {
+        return Err("pairing code has expired — show a new one on the other device".into());
+    }
+    match payload.keyring.current.as_deref() {
+        Some(id) if payload.keyring.key_bytes(id).is_some() => Ok(payload),
+        _ => Err("pairing code carries no usable key".into()),
+    }
+}
+
+fn parse_envelope(content: &str) -> Option<Envelope> {
+    // Cheap pre-check so ordinary journal JSON isn't parsed twice.
+    if !content.trim_start().starts_with("{\"bmenc\"") {
+        return None;
+    }
+    let envelope: Envelope = serde_json::from_str(content).ok()?;
+    (envelope.bmenc == 1).then_some(envelope)
+}
+
+fn seal_with(key: &[u8; 32], id: &str, plaintext: &str) -> Result<String, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ct = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| "encryption failed".to_string())?;
+    serde_json::to_string(&Envelope {
+        bmenc: 1,
+        kid: id.to_string(),
+        nonce: B64.encode(nonce),
+        ct: B64.encode(ct),
+    })
+    .map_err(|e| format!("serialize envelope: {e}"))
+}
+
+fn open_envelope(key: &[u8; 32], envelope: &Envelope) -> Result<String, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = B64
+        .decode(&envelope.nonce)
+        .map_err(|e| format!("bad nonce: {e}"))?;
+    if nonce.len() != 12 {
+        return Err("bad nonce length".into());
+    }
+    let ct = B64
+        .decode(&envelope.ct)
+        .map_err(|e| format!("bad ciphertext: {e}"))?;
+    let plain = cipher
+        .decrypt(Nonce::from_slice(&nonce), ct.as_slice())
+        .map_err(|_| "decryption failed (wrong key or tampered blob)".to_string())?;
+    String::from_utf8(plain).map_err(|e| format!("decrypted blob is not UTF-8: {e}"))
+}
+
+/// PBKDF2-HMAC-SHA256 with a single 32-byte output block (RFC 8018 §5.2).
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let prf =
+        Hmac::<Sha256>::new_from_slice(passphrase.as_bytes()).expect("HMAC accepts any key length");
+    let mut mac = prf.clone();
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+    let mut u: [u8; 32] = mac.finalize().into_bytes().into();
+    let mut out = u;
+    for _ in 1..iterations {
+        let mut mac = prf.clone();
+        mac.update(&u);
+        u = mac.finalize().into_bytes().into();
+        for (o, b) in out.iter_mut().zip(u.iter()) {
+            *o ^= b;
+        }
+    }
+    out
+}
+
+/// Fresh salt + key id for a new passphrase; returns the params to publish and
+/// the derived key.
+pub(crate) fn new_params(passphrase: &str) -> Result<(KeyParams, [u8; 32]), String> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let mut id = [0u8; 8];
+    OsRng.fill_bytes(&mut id);
+    let key_id = id.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    let key = derive_key(passphrase, &salt, PBKDF2_ITERATIONS);
+    let check = seal_with(&key, &key_id, CHECK_PLAINTEXT)?;
+    Ok((
+        KeyParams {
+            key_id,
+            salt: B64.encode(salt),
+            iterations: PBKDF2_ITERATIONS,
+            check,
+        },
+        key,
+    ))
+}
+
+/// Derive the key for published `params`, rejecting a wrong passphrase.
+pub(crate) fn unlock(params: &KeyParams, passphrase: &str) -> Result<[u8; 32], String> {
+    let salt = B64
+        .decode(&params.salt)
+        .map_err(|e| format!("bad salt: {e}"))?;
+    let key = derive_key(passphrase, &salt, params.iterations);
+    let envelope = parse_envelope(&params.check).ok_or("bad key check value")?;
+    match open_envelope(&key, &envelope) {
+        Ok(text) if text == CHECK_PLAINTEXT => Ok(key),
+        _ => Err("wrong passphrase".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pbkdf2_matches_rfc7914_vector() {
+        // RFC 7914 §11, PBKDF2-HMAC-SHA256 (first 32 bytes), c = 1.
+        let key = derive_key("passwd", b"salt", 1);
+        let hex: String = key.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(
+            hex,
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+    }
+
+    fn ring_with(id: &str, key: [u8; 32]) -> Keyring {
+        let mut ring = Keyring::default();
+        ring.install(id, &key);
+        ring
+    }
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let ring = ring_with("k1", [7u8; 32]);
+        let sealed = ring.seal(r#"{"version":1}"#).unwrap().unwrap();
+        assert!(sealed.starts_with(r#"{"bmenc":1"#));
+        assert!(!sealed.contains("version"));
+        assert_eq!(ring.open(&sealed).unwrap(), r#"{"version":1}"#);
+    }
+
+    #[test]
+    fn plaintext_passes_through_and_disabled_ring_does_not_seal() {
+        let ring = Keyring::default();
+        assert!(ring.seal("x").unwrap().is_none());
+        assert_eq!(ring.open(r#"{"entries":[]}"#).unwrap(), r#"{"entries":[]}"#);
+    }
+
+    #[test]
+    fn rotation_keeps_old_blobs_readable() {
+        let mut ring = ring_with("old", [1u8; 32]);
+        let old_blob = ring.seal("before").unwrap().unwrap();
+        ring.install("new", &[2u8; 32]);
+        let new_blob = ring.seal("after").unwrap().unwrap();
+        assert!(new_blob.contains(r#""kid":"new""#));
+        assert_eq!(ring.open(&old_blob).unwrap(), "before");
+        assert_eq!(ring.open(&new_blob).unwrap(), "after");
+    }
+
+    #[test]
+    fn unknown_key_and_tampering_are_errors() {
+        let ring = ring_with("k1", [7u8; 32]);
+        let sealed = ring.seal("secret").unwrap().unwrap();
+        let other = ring_with("k2", [7u8; 32]);
+        assert!(other.open(&sealed).unwrap_err().contains("passphrase"));
+
+        let mut envelope: Envelope = serde_json::from_str(&sealed).unwrap();
+        let mut ct = B64.decode(&envelope.ct).unwrap();
+        ct[0] ^= 1;
+        envelope.ct = B64.encode(ct);
+        let tampered = serde_json::to_string(&envelope).unwrap();
+        assert!(ring.open(&tampered).is_err());
+    }
+
+    #[test]
+    fn pairing_round_trips_and_absorbs_keys() {
+        let mut source = ring_with("old", [1u8; 32]);
+        source.install("new", &[2u8; 32]);
+        let blob = source.seal("hello").unwrap().unwrap();
+        let code = encode_pairing(&PairingPayload {
+            account_id: "acct".into(),
+            exp: 1_000,
+            keyring: source,
+        })
+        .unwrap();
+        assert!(code.starts_with("bmpair1:"));
+
+        let payload = decode_pairing(&code, 999).unwrap();
+        assert_eq!(payload.account_id, "acct");
+        let mut target = ring_with("local", [3u8; 32]);
+        target.absorb(payload.keyring);
+        assert_eq!(target.current.as_deref(), Some("new"));
+        assert_eq!(target.keys.len(), 3);
+        assert_eq!(target.open(&blob).unwrap(), "hello");
+    }
+
+    #[test]
+    fn pairing_rejects_expired_and_foreign_codes() {
+        let code = encode_pairing(&PairingPayload {
+            account_id: "acct".into(),
+            exp: 1_000,
+            keyring: ring_with("k", [1u8; 32]),
+        })
+        .unwrap();
+        assert!(decode_pairing(&code, 1_001)
+            .err()
+            .unwrap()
+            .contains("expired"));
+        assert!(decode_pairing("https://example.com", 0).is_err());
+        let empty = encode_pairing(&PairingPayload {
+            account_id: "acct".into(),
+            exp: 1_000,
+            keyring: Keyring::default(),
+        })
+        .unwrap();
+        assert!(decode_pairing(&empty, 0)
+            .err()
+            .unwrap()
+            .contains("no usable key"));
+    }
+
+    #[test]
+    fn unlock_rejects_wrong_passphrase() {
+        let params = KeyParams {
+            key_id: "k".into(),
+            salt: B64.encode(b"salt"),
+            iterations: 1,
+            check: seal_with(&derive_key("right", b"salt", 1), "k", CHECK_PLAINTEXT).unwrap(),
+        };
+        assert!(unlock(&params, "wrong").is_err());
+        assert_eq!(
+            unlock(&params, "right").unwrap(),
+            derive_key("right", b"salt", 1)
+        );
+    }
+}
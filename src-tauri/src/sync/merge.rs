@@ -0,0 +1,712 @@
+//! Merge another BibleMarker database file into the local one.
+//!
+//! Used when a second device's database is available as a file (copied over,
+//! restored from a backup, etc.) and both sides carry edits made offline.
+//! Instead of replacing the local file, every synced table is merged row by row
+//! with the same rule the journal sync uses in `applyRemoteChange`
+//! (src/lib/sqlite-db.ts): the newest `updated_at` wins. Exact ties are broken
+//! by `device_id` so two devices merging each other's files converge on the
+//! same row.
+//!
+//! Rows that only exist locally are kept, so this is a union — a merge cannot
+//! lose highlights that one side added. Deletions travel as `sync_tombstones`
+//! rows: a remote tombstone newer than the local row deletes it, and a local
+//! tombstone keeps an older remote copy from coming back. Every row the merge
+//! writes or deletes is recorded in `change_log`, so the next journal flush
+//! carries it to the rest of the account's devices.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::db::{DbError, DbErrorKind};
+
+/// Mirrors `SYNCED_TABLES` in src/lib/table-registry.ts.
+pub(crate) const SYNCED_TABLES: &[&str] = &[
+    "annotations",
+    "section_headings",
+    "chapter_titles",
+    "notes",
+    "marking_presets",
+    "studies",
+    "multi_translation_views",
+    "observation_lists",
+    "time_expressions",
+    "places",
+    "people",
+    "conclusions",
+    "interpretations",
+    "applications",
+    "entity_notes",
+    "keyword_exclusions",
+    "audio_positions",
+    "symbol_library",
+    "tags",
+    "tag_links",
+    "bookmark_folders",
+    "bookmarks",
+    "verse_collections",
+    "workspaces",
+    "highlight_styles",
+    "marking_templates",
+    "margin_notes",
+    "resource_links",
+    "preferences",
+];
+
+/// Per-table outcome of a merge.
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct TableMerge {
+    pub table: String,
+    /// Remote rows that did not exist locally.
+    pub inserted: usize,
+    /// Local rows replaced by a newer remote row.
+    pub updated: usize,
+    /// Local rows removed because the remote database deleted them later.
+    pub deleted: usize,
+    /// Remote rows ignored because the local copy is newer or was deleted here.
+    #[serde(rename = "keptLocal")]
+    pub kept_local: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MergeReport {
+    pub tables: Vec<TableMerge>,
+    /// Total remote rows written into the local database.
+    pub applied: usize,
+}
+
+/// The entity JSON the TS layer would have logged for a row of `table`, as a
+/// SQL expression over that table's columns. Data tables store it verbatim;
+/// the structured tables (see `applyStructuredUpsert`) are rebuilt from their
+/// columns in the shape their `sqliteSave*` functions accept.
+pub(crate) fn entity_json_sql(table: &str) -> &'static str {
+    match table {
+        "section_headings" => {
+            "json_object('id', id, 'beforeRef', json(before_ref), 'title', title, \
+             'coversUntil', json(covers_until), 'studyId', study_id, \
+             'createdAt', created_at, 'updatedAt', updated_at)"
+        }
+        "chapter_titles" => {
+            "json_object('id', id, 'book', book, 'chapter', chapter, 'title', title, \
+             'theme', theme, 'supportingPresetIds', json(supporting_preset_ids), \
+             'studyId', study_id, 'createdAt', created_at, 'updatedAt', updated_at)"
+        }
+        "notes" => {
+            "json_object('id', id, 'moduleId', module_id, 'ref', json(\"ref\"), \
+             'range', json(\"range\"), 'content', content, \
+             'createdAt', created_at, 'updatedAt', updated_at)"
+        }
+        "marking_presets" => {
+            "json_object('id', id, 'word', word, 'variants', json(variants), \
+             'symbol', symbol, 'highlight', json(highlight), 'category', category, \
+             'description', description, 'autoSuggest', json(iif(auto_suggest, 'true', 'false')), \
+             'usageCount', usage_count, 'scopes', json(scopes), 'moduleScope', module_scope, \
+             'studyId', study_id, 'createdAt', created_at, 'updatedAt', updated_at)"
+        }
+        "studies" => {
+            "json_object('id', id, 'name', name, 'book', book, \
+             'isActive', json(iif(is_active, 'true', 'false')), \
+             'createdAt', created_at, 'updatedAt', updated_at)"
+        }
+        _ => "data",
+    }
+}
+
+/// The local device id the TS layer generated (`initDeviceId` in sqlite-db.ts).
+pub(crate) fn local_device_id(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM sync_config WHERE key = 'device_id'",
+        [],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub(crate) fn table_exists(conn: &Connection, schema: &str, table: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM {schema}.sqlite_master WHERE type = 'table' AND name = ?1"),
+        [table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|n| n > 0)
+}
+
+pub(crate) fn columns(
+    conn: &Connection,
+    schema: &str,
+    table: &str,
+) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {schema}.table_info({table})"))?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(names)
+}
+
+/// Merge every synced table from the attached `remote` schema into `main`.
+/// The caller owns the transaction.
+pub(crate) fn merge_attached(conn: &Connection, device_id: &str) -> rusqlite::Result<MergeReport> {
+    let mut report = MergeReport::default();
+    let local_tombstones = table_exists(conn, "main", "sync_tombstones")?;
+    let remote_tombstones = table_exists(conn, "remote", "sync_tombstones")?;
+
+    for &table in SYNCED_TABLES {
+        if !table_exists(conn, "main", table)? || !table_exists(conn, "remote", table)? {
+            continue;
+        }
+        let local_cols = columns(conn, "main", table)?;
+        let remote_cols = columns(conn, "remote", table)?;
+        let shared: Vec<&String> = local_cols
+            .iter()
+            .filter(|c| remote_cols.contains(c))
+            .collect();
+        if !shared.iter().any(|c| *c == "id") || !shared.iter().any(|c| *c == "updated_at") {
+            continue;
+        }
+        let has_device = shared.iter().any(|c| *c == "device_id");
+        let col_list = shared
+            .iter()
+            .map(|c| c.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut stats = TableMerge {
+            table: table.to_string(),
+            ..Default::default()
+        };
+
+        if remote_tombstones {
+            stats.deleted = apply_remote_tombstones(conn, table, local_tombstones, device_id)?;
+        }
+
+        let tie_break = if has_device {
+            "OR (r.updated_at = l.updated_at AND COALESCE(r.device_id, '') > COALESCE(l.device_id, ''))"
+        } else {
+            ""
+        };
+        // A local deletion at or after the remote version wins over it, exactly
+        // like applyRemoteChange's tombstone check.
+        let tombstoned = if local_tombstones {
+            format!(
+                "EXISTS (SELECT 1 FROM main.sync_tombstones t
+                         WHERE t.table_name = '{table}' AND t.row_id = r.id
+                           AND t.deleted_at >= r.updated_at)"
+            )
+        } else {
+            "0".to_string()
+        };
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.id, l.id IS NULL,
+                    (r.updated_at > l.updated_at {tie_break}),
+                    {tombstoned}
+             FROM remote.{table} r LEFT JOIN main.{table} l ON l.id = r.id"
+        ))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, bool>(1)?,
+                    row.get::<_, Option<bool>>(2)?.unwrap_or(false),
+                    row.get::<_, bool>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // Copied rows are another device's edits, stored as sync stores them
+        // ('synced'), so the undo triggers of migration 17 leave them be.
+        let select_list = shared
+            .iter()
+            .map(|c| match c.as_str() {
+                "sync_status" => "'synced'",
+                c => c,
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let copy_sql = format!(
+            "INSERT OR REPLACE INTO main.{table} ({col_list})
+             SELECT {select_list} FROM remote.{table} WHERE id = ?1"
+        );
+        let json_sql = format!(
+            "SELECT {}, updated_at FROM main.{table} WHERE id = ?1",
+            entity_json_sql(table)
+        );
+
+        for (id, missing_locally, remote_newer, deleted_here) in rows {
+            if deleted_here || (!missing_locally && !remote_newer) {
+                stats.kept_local += 1;
+                continue;
+            }
+            conn.execute(&copy_sql, [&id])?;
+            if local_tombstones {
+                conn.execute(
+                    "DELETE FROM main.sync_tombstones WHERE table_name = ?1 AND row_id = ?2",
+                    params![table, id],
+                )?;
+            }
+            // Journal with the row's own timestamp so other devices resolve it
+            // against the same updated_at this database now holds.
+            let (data, updated_at): (Option<String>, String) =
+                conn.query_row(&json_sql, [&id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            log_change(
+                conn,
+                table,
+                "upsert",
+                &id,
+                data.as_deref(),
+                &updated_at,
+                device_id,
+            )?;
+            if missing_locally {
+                stats.inserted += 1;
+            } else {
+                stats.updated += 1;
+            }
+        }
+
+        report.applied += stats.inserted + stats.updated + stats.deleted;
+        if stats.inserted + stats.updated + stats.deleted + stats.kept_local > 0 {
+            report.tables.push(stats);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Delete local rows of `table` that the remote database deleted after their
+/// last local edit, and carry the remote tombstones over. Returns the number
+/// of rows deleted.
+fn apply_remote_tombstones(
+    conn: &Connection,
+    table: &str,
+    local_tombstones: bool,
+    device_id: &str,
+) -> rusqlite::Result<usize> {
+    let mut stmt = conn.prepare(
+        "SELECT row_id, deleted_at, device_id FROM remote.sync_tombstones WHERE table_name = ?1",
+    )?;
+    let tombstones = stmt
+        .query_map([table], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut deleted = 0;
+    for (row_id, deleted_at, deleted_by) in tombstones {
+        let removed = conn.execute(
+            &format!("DELETE FROM main.{table} WHERE id = ?1 AND updated_at <= ?2"),
+            params![row_id, deleted_at],
+        )?;
+        if removed > 0 {
+            deleted += removed;
+            log_change(conn, table, "delete", &row_id, None, &deleted_at, device_id)?;
+        }
+        if local_tombstones {
+            conn.execute(
+                "INSERT INTO main.sync_tombstones (table_name, row_id, deleted_at, device_id)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (table_name, row_id) DO UPDATE SET
+                   deleted_at = excluded.deleted_at, device_id = excluded.device_id
+                 WHERE excluded.deleted_at > sync_tombstones.deleted_at",
+                params![table, row_id, deleted_at, deleted_by],
+            )?;
+        }
+    }
+    Ok(deleted)
+}
+
+fn log_change(
+    conn: &Connection,
+    table: &str,
+    op: &str,
+    row_id: &str,
+    data: Option<&str>,
+    updated_at: &str,
+    device_id: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO main.change_log (table_name, op, row_id, data, updated_at, device_id, flushed)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
+        params![table, op, row_id, data, updated_at, device_id],
+    )?;
+    Ok(())
+}
+
+/// `path` as a read-only SQLite URI, percent-encoded so a `?`, `#` or `%` in
+/// it is read as part of the file name.
+fn read_only_uri(path: &Path) -> String {
+    let mut uri = String::from("file:");
+    for &b in path.as_os_str().as_encoded_bytes() {
+        if b.is_ascii_alphanumeric() || b"/-._~".contains(&b) {
+            uri.push(b as char);
+        } else {
+            uri.push_str(&format!("%{b:02X}"));
+        }
+    }
+    uri.push_str("?mode=ro");
+    uri
+}
+
+/// Attach `remote` read-only and merge it into the local database `conn` in
+/// one transaction. `validate` runs against the attached file first and can
+/// refuse it.
+pub(crate) fn merge_file(
+    conn: &mut Connection,
+    remote: &Path,
+    validate: impl FnOnce(&Connection) -> Result<(), String>,
+) -> Result<MergeReport, DbError> {
+    if !remote.exists() {
+        return Err(DbError::new(
+            DbErrorKind::NotFound,
+            format!("Database not found: {}", remote.display()),
+        ));
+    }
+    let local = conn.path().map(PathBuf::from);
+    if let (Some(Ok(a)), Ok(b)) = (local.map(|p| p.canonicalize()), remote.canonicalize()) {
+        if a == b {
+            return Err(DbError::invalid(
+                "Cannot merge the local database into itself",
+            ));
+        }
+    }
+
+    let device_id = crate::db::device_id(conn)?;
+
+    let remote_uri = read_only_uri(remote);
+    conn.execute("ATTACH DATABASE ?1 AS remote", [&remote_uri])
+        .map_err(|e| DbError::from(e).context(format!("Failed to open {}", remote.display())))?;
+
+    if let Err(e) = validate(conn) {
+        let _ = conn.execute("DETACH DATABASE remote", []);
+        return Err(DbError::invalid(e));
+    }
+
+    let result = (|| {
+        let tx = conn.transaction()?;
+        // The other file's deletes stay off the undo journal, as sync's do
+        // (undo::delete_synced).
+        let marked = table_exists(&tx, "main", "sync_apply")?;
+        if marked {
+            tx.execute("INSERT INTO sync_apply (active) VALUES (1)", [])?;
+        }
+        let report = merge_attached(&tx, &device_id)?;
+        if marked {
+            tx.execute("DELETE FROM sync_apply", [])?;
+        }
+        tx.commit()?;
+        Ok::<_, rusqlite::Error>(report)
+    })();
+    let _ = conn.execute("DETACH DATABASE remote", []);
+
+    result.map_err(|e| DbError::from(e).context("Merge failed"))
+}
+
+/// Merge the BibleMarker database at `path` into the local database.
+/// The other file is opened read-only and is never modified.
+#[tauri::command]
+pub async fn merge_remote_database(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<MergeReport, DbError> {
+    let remote = PathBuf::from(path);
+    crate::db::with_connection(&app, move |conn| merge_file(conn, &remote, |_| Ok(()))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("ATTACH DATABASE ':memory:' AS remote", [])
+            .unwrap();
+        for schema in ["main", "remote"] {
+            conn.execute_batch(&format!(
+                "CREATE TABLE {schema}.annotations (
+                    id TEXT PRIMARY KEY, module_id TEXT NOT NULL, type TEXT NOT NULL,
+                    data TEXT NOT NULL, preset_id TEXT, created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL, sync_status TEXT, device_id TEXT);
+                 CREATE TABLE {schema}.studies (
+                    id TEXT PRIMARY KEY, name TEXT NOT NULL, book TEXT,
+                    is_active INTEGER NOT NULL DEFAULT 0, created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL, sync_status TEXT, device_id TEXT);"
+            ))
+            .unwrap();
+        }
+        conn.execute_batch(
+            "CREATE TABLE change_log (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT, table_name TEXT NOT NULL,
+                op TEXT NOT NULL, row_id TEXT NOT NULL, data TEXT,
+                updated_at TEXT NOT NULL, device_id TEXT NOT NULL,
+                flushed INTEGER NOT NULL DEFAULT 0);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn add_tombstones(conn: &Connection) {
+        for schema in ["main", "remote"] {
+            conn.execute_batch(&format!(
+                "CREATE TABLE {schema}.sync_tombstones (
+                    table_name TEXT NOT NULL, row_id TEXT NOT NULL,
+                    deleted_at TEXT NOT NULL, device_id TEXT NOT NULL,
+                    PRIMARY KEY (table_name, row_id));"
+            ))
+            .unwrap();
+        }
+    }
+
+    fn tombstone(conn: &Connection, schema: &str, id: &str, deleted_at: &str) {
+        conn.execute(
+            &format!("INSERT INTO {schema}.sync_tombstones VALUES ('annotations', ?1, ?2, 'dev')"),
+            params![id, deleted_at],
+        )
+        .unwrap();
+    }
+
+    fn exists(conn: &Connection, id: &str) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) FROM annotations WHERE id = ?1",
+            [id],
+            |r| r.get::<_, i64>(0),
+        )
+        .unwrap()
+            > 0
+    }
+
+    fn add_annotation(conn: &Connection, schema: &str, id: &str, updated: &str, device: &str) {
+        conn.execute(
+            &format!(
+                "INSERT INTO {schema}.annotations
+                 (id, module_id, type, data, created_at, updated_at, device_id)
+                 VALUES (?1, 'KJV', 'highlight', ?2, ?3, ?3, ?4)"
+            ),
+            params![
+                id,
+                format!(r#"{{"id":"{id}","from":"{schema}"}}"#),
+                updated,
+                device
+            ],
+        )
+        .unwrap();
+    }
+
+    fn data_of(conn: &Connection, id: &str) -> String {
+        conn.query_row("SELECT data FROM annotations WHERE id = ?1", [id], |r| {
+            r.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn merges_a_file_whose_path_has_uri_characters() {
+        let dir = ScratchDir::new("merge-#1?ro%20");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("other #2.db");
+        let other = Connection::open(&path).unwrap();
+        other
+            .execute_batch(
+                "CREATE TABLE annotations (
+                    id TEXT PRIMARY KEY, module_id TEXT NOT NULL, type TEXT NOT NULL,
+                    data TEXT NOT NULL, preset_id TEXT, created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL, sync_status TEXT, device_id TEXT);",
+            )
+            .unwrap();
+        add_annotation(&other, "main", "ipad", "2025-01-02T00:00:00.000Z", "b");
+        drop(other);
+
+        let mut conn = crate::db::test_connection();
+        let report = merge_file(&mut conn, &path, |_| Ok(())).unwrap();
+        assert_eq!(report.applied, 1);
+        assert!(exists(&conn, "ipad"));
+    }
+
+    #[test]
+    fn keeps_merged_pending_rows_off_the_undo_journal() {
+        let dir = ScratchDir::new("merge-undo");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("other.db");
+        let other = crate::db::migrated_test_connection();
+        add_annotation(&other, "main", "ipad", "2025-01-02T00:00:00.000Z", "b");
+        other
+            .execute("VACUUM INTO ?1", [path.to_str().unwrap()])
+            .unwrap();
+        drop(other);
+
+        let mut conn = crate::db::migrated_test_connection();
+        add_annotation(&conn, "main", "mine", "2025-01-01T00:00:00.000Z", "a");
+        conn.execute("UPDATE undo_journal SET undone = 1", [])
+            .unwrap();
+        merge_file(&mut conn, &path, |_| Ok(())).unwrap();
+        assert!(exists(&conn, "ipad"));
+        let journal: Vec<(String, i64)> = conn
+            .prepare("SELECT row_id, undone FROM undo_journal")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        // Only the local edit, its redo untouched.
+        assert_eq!(journal, [("mine".to_string(), 1)]);
+    }
+
+    #[test]
+    fn unions_rows_from_both_sides() {
+        let conn = setup();
+        add_annotation(&conn, "main", "mac", "2025-01-01T00:00:00.000Z", "a");
+        add_annotation(&conn, "remote", "ipad", "2025-01-02T00:00:00.000Z", "b");
+
+        let report = merge_attached(&conn, "a").unwrap();
+        assert_eq!(report.applied, 1);
+        assert_eq!(report.tables[0].inserted, 1);
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM annotations", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn newest_updated_at_wins() {
+        let conn = setup();
+        add_annotation(
+            &conn,
+            "main",
+            "older-local",
+            "2025-01-01T00:00:00.000Z",
+            "a",
+        );
+        add_annotation(
+            &conn,
+            "remote",
+            "older-local",
+            "2025-02-01T00:00:00.000Z",
+            "b",
+        );
+        add_annotation(
+            &conn,
+            "main",
+            "newer-local",
+            "2025-03-01T00:00:00.000Z",
+            "a",
+        );
+        add_annotation(
+            &conn,
+            "remote",
+            "newer-local",
+            "2025-02-01T00:00:00.000Z",
+            "b",
+        );
+
+        let report = merge_attached(&conn, "a").unwrap();
+        assert_eq!(
+            report.tables[0],
+            TableMerge {
+                table: "annotations".into(),
+                inserted: 0,
+                updated: 1,
+                deleted: 0,
+                kept_local: 1
+            }
+        );
+        assert!(data_of(&conn, "older-local").contains("remote"));
+        assert!(data_of(&conn, "newer-local").contains("main"));
+    }
+
+    #[test]
+    fn ties_break_on_device_id_in_both_directions() {
+        let ts = "2025-01-01T00:00:00.000Z";
+        let conn = setup();
+        add_annotation(&conn, "main", "x", ts, "aaa");
+        add_annotation(&conn, "remote", "x", ts, "bbb");
+        merge_attached(&conn, "aaa").unwrap();
+        assert!(data_of(&conn, "x").contains("remote"));
+
+        let conn = setup();
+        add_annotation(&conn, "main", "x", ts, "bbb");
+        add_annotation(&conn, "remote", "x", ts, "aaa");
+        merge_attached(&conn, "bbb").unwrap();
+        assert!(data_of(&conn, "x").contains("main"));
+    }
+
+    #[test]
+    fn records_winning_rows_in_change_log() {
+        let conn = setup();
+        conn.execute(
+            "INSERT INTO remote.studies (id, name, book, is_active, created_at, updated_at, device_id)
+             VALUES ('s1', 'Romans', 'Rom', 1, 't0', 't1', 'b')",
+            [],
+        )
+        .unwrap();
+
+        merge_attached(&conn, "local-device").unwrap();
+        let (table, op, data, device): (String, String, String, String) = conn
+            .query_row(
+                "SELECT table_name, op, data, device_id FROM change_log",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(table, "studies");
+        assert_eq!(op, "upsert");
+        assert_eq!(device, "local-device");
+        let parsed: serde_json::Value = serde_json::from_str(&data).unwrap();
+        assert_eq!(parsed["name"], "Romans");
+        assert_eq!(parsed["isActive"], true);
+    }
+
+    #[test]
+    fn remote_tombstone_deletes_older_local_row() {
+        let conn = setup();
+        add_tombstones(&conn);
+        add_annotation(&conn, "main", "gone", "2025-01-01T00:00:00.000Z", "a");
+        add_annotation(&conn, "main", "edited", "2025-03-01T00:00:00.000Z", "a");
+        tombstone(&conn, "remote", "gone", "2025-02-01T00:00:00.000Z");
+        tombstone(&conn, "remote", "edited", "2025-02-01T00:00:00.000Z");
+
+        let report = merge_attached(&conn, "a").unwrap();
+        assert_eq!(report.tables[0].deleted, 1);
+        assert!(!exists(&conn, "gone"));
+        // Edited locally after the remote delete: the edit wins.
+        assert!(exists(&conn, "edited"));
+        let op: String = conn
+            .query_row("SELECT op FROM change_log WHERE row_id = 'gone'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(op, "delete");
+        let carried: i64 = conn
+            .query_row("SELECT COUNT(*) FROM main.sync_tombstones", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(carried, 2);
+    }
+
+    #[test]
+    fn local_tombstone_blocks_resurrection() {
+        let conn = setup();
+        add_tombstones(&conn);
+        tombstone(&conn, "main", "x", "2025-02-01T00:00:00.000Z");
+        add_annotation(&conn, "remote", "x", "2025-01-01T00:00:00.000Z", "b");
+        add_annotation(&conn, "remote", "y", "2025-03-01T00:00:00.000Z", "b");
+        tombstone(&conn, "main", "y", "2025-02-01T00:00:00.000Z");
+
+        let report = merge_attached(&conn, "a").unwrap();
+        assert!(!exists(&conn, "x"));
+        // Re-created remotely after the local delete: it comes back.
+        assert!(exists(&conn, "y"));
+        assert_eq!(report.tables[0].kept_local, 1);
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM main.sync_tombstones", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(left, 1);
+    }
+}
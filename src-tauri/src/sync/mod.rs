@@ -0,0 +1,8 @@
+//! Native sync helpers used alongside `sync_client` (the HTTP transport to the
+//! sync server): merging another database file or a `.bmsync` bundle into the
+//! local one, and the blob encryption the transport applies when end-to-end
+//! encryption is on.
+
+pub mod bundle;
+pub mod crypto;
+pub mod merge;
@@ -15,12 +15,23 @@
 //! Errors are structured (`{ kind, statusCode, message }`) so the TS layer can
 //! branch: 401 → re-auth, 0/5xx → retry, other 4xx → fatal. See `offline.ts`.
 
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use tauri::Manager;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use crate::sync::crypto::{self, KeyParams, Keyring, PairingPayload};
 
 const SYNC_BASE: &str = "https://biblemarker.app";
 const SESSION_FILE: &str = "sync_session.json";
+/// Where the encryption keyring is kept: the OS keychain, one entry per
+/// account, except on Android (see "End-to-end encryption" below).
+#[cfg(not(target_os = "android"))]
+const KEYCHAIN_SERVICE: &str = "app.biblemarker.sync-keys";
+#[cfg(target_os = "android")]
+const KEYS_FILE: &str = "sync_keys.json";
 
 // ============================================================================
 // Structured error
@@ -54,6 +65,12 @@ impl SyncError {
     fn protocol(message: impl Into<String>) -> Self {
         Self::new("server", 1, message)
     }
+    fn cancelled() -> Self {
+        Self::new("cancelled", 1, "sync cancelled")
+    }
+    fn encryption(message: impl Into<String>) -> Self {
+        Self::new("encryption", 1, message)
+    }
     fn from_response(status: reqwest::StatusCode, body: &str) -> Self {
         let code = status.as_u16();
         let kind = match code {
@@ -68,6 +85,15 @@ impl SyncError {
     }
 }
 
+impl std::fmt::Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
+
+// Lets an upload body stream fail with it.
+impl std::error::Error for SyncError {}
+
 /// Pull the `error` field out of a `{ "error": "..." }` body, if present.
 fn extract_error(body: &str) -> Option<String> {
     let value: serde_json::Value = serde_json::from_str(body).ok()?;
@@ -82,27 +108,40 @@ fn extract_error(body: &str) -> Option<String> {
 pub(crate) struct StoredSession {
     pub(crate) token: String,
     pub(crate) account_id: String,
+    /// Encryption keys were saved for this session, so a keychain that
+    /// can't be read is an error rather than "no keys".
+    #[serde(default)]
+    pub(crate) has_keys: bool,
 }
 
-fn session_path(app: &tauri::AppHandle) -> Result<PathBuf, SyncError> {
+fn private_path(app: &tauri::AppHandle, file: &str) -> Result<PathBuf, SyncError> {
     let dir = app
         .path()
         .app_data_dir()
         .map_err(|e| SyncError::storage(format!("no app data dir: {e}")))?;
-    Ok(dir.join(SESSION_FILE))
+    std::fs::create_dir_all(&dir).map_err(|e| SyncError::storage(format!("create dir: {e}")))?;
+    Ok(dir.join(file))
+}
+
+fn session_path(app: &tauri::AppHandle) -> Result<PathBuf, SyncError> {
+    private_path(app, SESSION_FILE)
 }
 
 fn store_session(app: &tauri::AppHandle, token: &str, account_id: &str) -> Result<(), SyncError> {
+    write_session(
+        app,
+        &StoredSession {
+            token: token.to_string(),
+            account_id: account_id.to_string(),
+            has_keys: false,
+        },
+    )
+}
+
+fn write_session(app: &tauri::AppHandle, session: &StoredSession) -> Result<(), SyncError> {
     let path = session_path(app)?;
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| SyncError::storage(format!("create dir: {e}")))?;
-    }
-    let json = serde_json::to_string(&StoredSession {
-        token: token.to_string(),
-        account_id: account_id.to_string(),
-    })
-    .map_err(|e| SyncError::storage(format!("serialize session: {e}")))?;
+    let json = serde_json::to_string(session)
+        .map_err(|e| SyncError::storage(format!("serialize session: {e}")))?;
     write_private_file(&path, &json)
         .map_err(|e| SyncError::storage(format!("write session: {e}")))?;
     Ok(())
@@ -212,10 +251,12 @@ pub fn get_session_account(app: tauri::AppHandle) -> Option<String> {
     read_session(&app).map(|s| s.account_id)
 }
 
-/// Sign out: best-effort server revoke, then drop the local token.
+/// Sign out: best-effort server revoke, then drop the local token and the
+/// account's encryption keys.
 #[tauri::command]
 pub async fn auth_revoke(app: tauri::AppHandle) -> Result<(), SyncError> {
     if let Some(session) = read_session(&app) {
+        delete_keys(&app, &session.account_id);
         // Best-effort — even if the network call fails, we still clear locally.
         let _ = reqwest::Client::new()
             .post(format!("{SYNC_BASE}/auth/revoke"))
@@ -235,6 +276,374 @@ pub fn clear_session_token(app: tauri::AppHandle) -> Result<(), SyncError> {
     Ok(())
 }
 
+// ============================================================================
+// Transfer progress + cancellation
+//
+// Blob transfers emit `sync://progress` so the UI can show a bar for large
+// snapshots instead of looking frozen. `cancel_sync` bumps a generation
+// counter; any transfer that started under an older generation stops at its
+// next chunk with a `cancelled`-kind error.
+// ============================================================================
+
+const PROGRESS_EVENT: &str = "sync://progress";
+
+/// Emit at most once per this many bytes while streaming a blob.
+const PROGRESS_STEP_BYTES: u64 = 64 * 1024;
+
+static CANCEL_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SyncProgress {
+    /// `"upload"` or `"download"`.
+    pub phase: &'static str,
+    /// Blob key being transferred, e.g. `"{device}/journal/000012.json"`.
+    pub key: String,
+    #[serde(rename = "bytesTransferred")]
+    pub bytes_transferred: u64,
+    /// `None` when the server sent no Content-Length.
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: Option<u64>,
+    pub done: bool,
+}
+
+/// A handle on the cancel generation current when a transfer started.
+#[derive(Clone, Copy)]
+struct CancelGuard(u64);
+
+impl CancelGuard {
+    fn start() -> Self {
+        Self(CANCEL_GENERATION.load(Ordering::SeqCst))
+    }
+    fn check(&self) -> Result<(), SyncError> {
+        if CANCEL_GENERATION.load(Ordering::SeqCst) != self.0 {
+            return Err(SyncError::cancelled());
+        }
+        Ok(())
+    }
+}
+
+fn emit_progress(app: &tauri::AppHandle, progress: SyncProgress) {
+    // Progress is best-effort; a missing listener must not fail the transfer.
+    let _ = app.emit(PROGRESS_EVENT, progress);
+}
+
+/// Abort every in-flight blob transfer. The engine sees a `cancelled` error
+/// and returns to idle without counting it as a failure.
+#[tauri::command]
+pub fn cancel_sync() {
+    CANCEL_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+// ============================================================================
+// End-to-end encryption
+//
+// Optional: once a passphrase is set, `sync_write` seals every blob and
+// `sync_read` opens it (see `sync::crypto`). The keyring is kept in the OS
+// keychain under the signed-in account's id, and deleted on sign-out, so it
+// never outlives the session it belongs to. Android has no keychain the
+// `keyring` crate reaches; there it is an app-private file tagged with the
+// account. The KDF parameters are published at `crypto::PARAMS_KEY` for
+// other devices to join.
+//
+// The keyring is read once per session and kept in memory, not per blob.
+// A keychain that can't be reached (no Secret Service on a Linux desktop)
+// only fails sync once this session has saved keys; until then it has none.
+// ============================================================================
+
+/// The keyring loaded for the signed-in account.
+static KEYRING_CACHE: Mutex<Option<(String, Keyring)>> = Mutex::new(None);
+
+fn cached_keyring() -> std::sync::MutexGuard<'static, Option<(String, Keyring)>> {
+    KEYRING_CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(not(target_os = "android"))]
+fn keychain_entry(account_id: &str) -> Result<keyring::Entry, SyncError> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, account_id)
+        .map_err(|e| SyncError::storage(format!("open keychain: {e}")))
+}
+
+/// The keyring JSON stored for `account_id`, if any.
+#[cfg(not(target_os = "android"))]
+fn read_keys(_app: &tauri::AppHandle, account_id: &str) -> Result<Option<String>, SyncError> {
+    match keychain_entry(account_id)?.get_password() {
+        Ok(json) => Ok(Some(json)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(SyncError::storage(format!("read keychain: {e}"))),
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn write_keys(_app: &tauri::AppHandle, account_id: &str, json: &str) -> Result<(), SyncError> {
+    keychain_entry(account_id)?
+        .set_password(json)
+        .map_err(|e| SyncError::storage(format!("write keychain: {e}")))
+}
+
+#[cfg(not(target_os = "android"))]
+fn delete_keys(_app: &tauri::AppHandle, account_id: &str) {
+    *cached_keyring() = None;
+    if let Ok(entry) = keychain_entry(account_id) {
+        let _ = entry.delete_credential(); // ignore "no entry"
+    }
+}
+
+#[cfg(target_os = "android")]
+#[derive(Serialize, Deserialize)]
+struct StoredKeys {
+    account_id: String,
+    keys: String,
+}
+
+#[cfg(target_os = "android")]
+fn read_keys(app: &tauri::AppHandle, account_id: &str) -> Result<Option<String>, SyncError> {
+    let path = private_path(app, KEYS_FILE)?;
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(SyncError::storage(format!("read key file: {e}"))),
+    };
+    let stored: StoredKeys = serde_json::from_str(&contents)
+        .map_err(|e| SyncError::storage(format!("bad key file: {e}")))?;
+    Ok((stored.account_id == account_id).then_some(stored.keys))
+}
+
+#[cfg(target_os = "android")]
+fn write_keys(app: &tauri::AppHandle, account_id: &str, json: &str) -> Result<(), SyncError> {
+    let path = private_path(app, KEYS_FILE)?;
+    let stored = serde_json::to_string(&StoredKeys {
+        account_id: account_id.to_string(),
+        keys: json.to_string(),
+    })
+    .map_err(|e| SyncError::storage(format!("serialize keys: {e}")))?;
+    write_private_file(&path, &stored).map_err(|e| SyncError::storage(format!("write keys: {e}")))
+}
+
+#[cfg(target_os = "android")]
+fn delete_keys(app: &tauri::AppHandle, _account_id: &str) {
+    *cached_keyring() = None;
+    if let Ok(path) = private_path(app, KEYS_FILE) {
+        let _ = std::fs::remove_file(path); // ignore "not found"
+    }
+}
+
+/// The signed-in account's keyring; empty when signed out or never set up.
+fn load_keyring(app: &tauri::AppHandle) -> Result<Keyring, SyncError> {
+    let Some(session) = read_session(app) else {
+        return Ok(Keyring::default());
+    };
+    let mut cache = cached_keyring();
+    if let Some((account_id, keyring)) = cache.as_ref() {
+        if *account_id == session.account_id {
+            return Ok(keyring.clone());
+        }
+    }
+    let keyring = match read_keys(app, &session.account_id) {
+        Ok(Some(json)) => serde_json::from_str(&json)
+            .map_err(|e| SyncError::storage(format!("bad stored keys: {e}")))?,
+        Ok(None) => Keyring::default(),
+        Err(e) if !session.has_keys => {
+            println!("[sync] No keychain, syncing without keys: {}", e.message);
+            Keyring::default()
+        }
+        Err(e) => return Err(e),
+    };
+    *cache = Some((session.account_id, keyring.clone()));
+    Ok(keyring)
+}
+
+fn save_keyring(app: &tauri::AppHandle, keyring: &Keyring) -> Result<(), SyncError> {
+    let mut session =
+        read_session(app).ok_or_else(|| SyncError::new("auth", 401, "not signed in"))?;
+    let json = serde_json::to_string(keyring)
+        .map_err(|e| SyncError::storage(format!("serialize keys: {e}")))?;
+    write_keys(app, &session.account_id, &json)?;
+    if !session.has_keys {
+        session.has_keys = true;
+        write_session(app, &session)?;
+    }
+    *cached_keyring() = Some((session.account_id, keyring.clone()));
+    Ok(())
+}
+
+/// Returned by the encryption commands so the settings UI can show the state.
+#[derive(Debug, Serialize)]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    #[serde(rename = "keyId")]
+    pub key_id: Option<String>,
+}
+
+impl From<&Keyring> for EncryptionStatus {
+    fn from(keyring: &Keyring) -> Self {
+        Self {
+            enabled: keyring.current.is_some(),
+            key_id: keyring.current.clone(),
+        }
+    }
+}
+
+async fn fetch_params(token: &str) -> Result<Option<KeyParams>, SyncError> {
+    let res = reqwest::Client::new()
+        .get(format!("{SYNC_BASE}/sync/blob/{}", crypto::PARAMS_KEY))
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(SyncError::network)?;
+    let status = res.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let text = res.text().await.map_err(SyncError::network)?;
+    if !status.is_success() {
+        return Err(SyncError::from_response(status, &text));
+    }
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| SyncError::protocol(format!("bad encryption params: {e}")))
+}
+
+async fn publish_params(token: &str, params: &KeyParams) -> Result<(), SyncError> {
+    let body = serde_json::to_string(params)
+        .map_err(|e| SyncError::encryption(format!("serialize params: {e}")))?;
+    let res = reqwest::Client::new()
+        .put(format!("{SYNC_BASE}/sync/blob/{}", crypto::PARAMS_KEY))
+        .bearer_auth(token)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(SyncError::network)?;
+    parse_empty(res).await
+}
+
+/// PBKDF2 at the production iteration count takes a noticeable fraction of a
+/// second, so keep it off the async runtime.
+async fn derive_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, SyncError> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| SyncError::encryption(format!("key derivation task failed: {e}")))?
+        .map_err(SyncError::encryption)
+}
+
+/// Turn on encryption with `passphrase`. If another device already set up
+/// encryption for this account, the passphrase must match theirs; otherwise
+/// fresh parameters are generated and published.
+#[tauri::command]
+pub async fn setup_sync_encryption(
+    app: tauri::AppHandle,
+    passphrase: String,
+) -> Result<EncryptionStatus, SyncError> {
+    let token = require_token(&app)?;
+    let mut keyring = load_keyring(&app)?;
+    let (key_id, key) = match fetch_params(&token).await? {
+        Some(params) => {
+            let key_id = params.key_id.clone();
+            let key = derive_blocking(move || crypto::unlock(&params, &passphrase)).await?;
+            (key_id, key)
+        }
+        None => {
+            let (params, key) = derive_blocking(move || crypto::new_params(&passphrase)).await?;
+            publish_params(&token, &params).await?;
+            (params.key_id, key)
+        }
+    };
+    keyring.install(&key_id, &key);
+    save_keyring(&app, &keyring)?;
+    Ok(EncryptionStatus::from(&keyring))
+}
+
+/// Switch to a new passphrase. New blobs are sealed with the new key; the old
+/// key stays in the keyring so existing blobs remain readable. Other devices
+/// must enter the new passphrase before they can read what this one writes.
+#[tauri::command]
+pub async fn rotate_sync_encryption(
+    app: tauri::AppHandle,
+    new_passphrase: String,
+) -> Result<EncryptionStatus, SyncError> {
+    let token = require_token(&app)?;
+    let mut keyring = load_keyring(&app)?;
+    if keyring.current.is_none() {
+        return Err(SyncError::encryption("encryption is not enabled"));
+    }
+    let (params, key) = derive_blocking(move || crypto::new_params(&new_passphrase)).await?;
+    publish_params(&token, &params).await?;
+    keyring.install(&params.key_id, &key);
+    save_keyring(&app, &keyring)?;
+    Ok(EncryptionStatus::from(&keyring))
+}
+
+/// Stop encrypting new blobs from this device and withdraw the published
+/// parameters. Keys are kept so already-encrypted blobs stay readable.
+#[tauri::command]
+pub async fn disable_sync_encryption(app: tauri::AppHandle) -> Result<EncryptionStatus, SyncError> {
+    let token = require_token(&app)?;
+    let mut keyring = load_keyring(&app)?;
+    let res = reqwest::Client::new()
+        .delete(format!("{SYNC_BASE}/sync/blob/{}", crypto::PARAMS_KEY))
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(SyncError::network)?;
+    parse_empty(res).await?;
+    keyring.current = None;
+    save_keyring(&app, &keyring)?;
+    Ok(EncryptionStatus::from(&keyring))
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A short-lived pairing string (render it as a QR code) that lets another
+/// device signed in to the same account adopt this device's keys without
+/// typing the passphrase. Valid for `crypto::PAIRING_TTL_SECS`.
+#[tauri::command]
+pub fn create_sync_pairing(app: tauri::AppHandle) -> Result<String, SyncError> {
+    let session = read_session(&app).ok_or_else(|| SyncError::new("auth", 401, "not signed in"))?;
+    let keyring = load_keyring(&app)?;
+    if keyring.current.is_none() {
+        return Err(SyncError::encryption("encryption is not enabled"));
+    }
+    crypto::encode_pairing(&PairingPayload {
+        account_id: session.account_id,
+        exp: unix_now() + crypto::PAIRING_TTL_SECS,
+        keyring,
+    })
+    .map_err(SyncError::encryption)
+}
+
+/// Adopt the keys from a scanned pairing string. Refuses codes from another
+/// account so a stray QR can't point this device at someone else's key.
+#[tauri::command]
+pub fn accept_sync_pairing(
+    app: tauri::AppHandle,
+    payload: String,
+) -> Result<EncryptionStatus, SyncError> {
+    let session = read_session(&app).ok_or_else(|| SyncError::new("auth", 401, "not signed in"))?;
+    let pairing = crypto::decode_pairing(&payload, unix_now()).map_err(SyncError::encryption)?;
+    if pairing.account_id != session.account_id {
+        return Err(SyncError::encryption(
+            "pairing code belongs to a different sync account",
+        ));
+    }
+    let mut keyring = load_keyring(&app)?;
+    keyring.absorb(pairing.keyring);
+    save_keyring(&app, &keyring)?;
+    Ok(EncryptionStatus::from(&keyring))
+}
+
+/// Whether this device is encrypting, and with which key.
+#[tauri::command]
+pub fn get_sync_encryption_status(app: tauri::AppHandle) -> Result<EncryptionStatus, SyncError> {
+    Ok(EncryptionStatus::from(&load_keyring(&app)?))
+}
+
 // ============================================================================
 // Sync transport commands (Phase 3)
 //
@@ -271,27 +680,62 @@ pub async fn sync_write(
     content: String,
 ) -> Result<(), SyncError> {
     let token = require_token(&app)?;
+    let content = match load_keyring(&app)?.seal(&content) {
+        Ok(Some(sealed)) => sealed,
+        Ok(None) => content,
+        Err(e) => return Err(SyncError::encryption(e)),
+    };
+    let guard = CancelGuard::start();
+    let total = content.len() as u64;
+    let progress = move |key: &str, bytes_transferred, done| SyncProgress {
+        phase: "upload",
+        key: key.to_string(),
+        bytes_transferred,
+        total_bytes: Some(total),
+        done,
+    };
+    emit_progress(&app, progress(&key, 0, false));
+
+    // Stream the body so large snapshots report progress and can be cancelled.
+    let chunks: Vec<Vec<u8>> = content
+        .as_bytes()
+        .chunks(PROGRESS_STEP_BYTES as usize)
+        .map(<[u8]>::to_vec)
+        .collect();
+    let (stream_app, stream_key) = (app.clone(), key.clone());
+    let mut sent = 0u64;
+    let body = futures_util::stream::iter(chunks).map(move |chunk| {
+        guard.check()?;
+        sent += chunk.len() as u64;
+        emit_progress(&stream_app, progress(&stream_key, sent, false));
+        Ok::<_, SyncError>(chunk)
+    });
     let res = reqwest::Client::new()
         .put(format!("{SYNC_BASE}/sync/blob/{key}"))
         .bearer_auth(token)
         .header("Content-Type", "application/json")
-        .body(content)
+        .header("Content-Length", total)
+        .body(reqwest::Body::wrap_stream(body))
         .send()
-        .await
-        .map_err(SyncError::network)?;
-    parse_empty(res).await
+        .await;
+    guard.check()?;
+    parse_empty(res.map_err(SyncError::network)?).await?;
+    emit_progress(&app, progress(&key, total, true));
+    Ok(())
 }
 
 /// Read the blob at `key`. `None` (HTTP 404) means absent.
 #[tauri::command]
 pub async fn sync_read(app: tauri::AppHandle, key: String) -> Result<Option<String>, SyncError> {
     let token = require_token(&app)?;
-    let res = reqwest::Client::new()
+    let guard = CancelGuard::start();
+    let mut res = reqwest::Client::new()
         .get(format!("{SYNC_BASE}/sync/blob/{key}"))
         .bearer_auth(token)
         .send()
         .await
         .map_err(SyncError::network)?;
+    guard.check()?;
     let status = res.status();
     if status == reqwest::StatusCode::NOT_FOUND {
         return Ok(None);
@@ -300,8 +744,45 @@ pub async fn sync_read(app: tauri::AppHandle, key: String) -> Result<Option<Stri
         let body = res.text().await.unwrap_or_default();
         return Err(SyncError::from_response(status, &body));
     }
-    let text = res.text().await.map_err(SyncError::network)?;
-    Ok(Some(text))
+
+    // Stream the body so large snapshots report progress and can be cancelled.
+    let total_bytes = res.content_length();
+    let mut body = Vec::with_capacity(total_bytes.unwrap_or(0) as usize);
+    let mut last_emitted = 0u64;
+    while let Some(chunk) = res.chunk().await.map_err(SyncError::network)? {
+        guard.check()?;
+        body.extend_from_slice(&chunk);
+        let received = body.len() as u64;
+        if received - last_emitted >= PROGRESS_STEP_BYTES {
+            last_emitted = received;
+            emit_progress(
+                &app,
+                SyncProgress {
+                    phase: "download",
+                    key: key.clone(),
+                    bytes_transferred: received,
+                    total_bytes,
+                    done: false,
+                },
+            );
+        }
+    }
+    emit_progress(
+        &app,
+        SyncProgress {
+            phase: "download",
+            key,
+            bytes_transferred: body.len() as u64,
+            total_bytes,
+            done: true,
+        },
+    );
+    let text = String::from_utf8(body)
+        .map_err(|e| SyncError::protocol(format!("blob is not valid UTF-8: {e}")))?;
+    load_keyring(&app)?
+        .open(&text)
+        .map(Some)
+        .map_err(SyncError::encryption)
 }
 
 /// List the immediate children of `prefix` (`""` = account root).
@@ -339,7 +820,8 @@ pub async fn sync_remove(app: tauri::AppHandle, key: String) -> Result<(), SyncE
 }
 
 /// Permanently delete the signed-in account and all its server-side data
-/// (`DELETE /account`), then drop the local token. The token is cleared **only
+/// (`DELETE /account`), then drop the local token and encryption keys. The
+/// token is cleared **only
 /// on success** so a failed delete can be retried — deliberately unlike
 /// `auth_revoke`, which drops the token unconditionally.
 #[tauri::command]
@@ -352,6 +834,9 @@ pub async fn delete_account(app: tauri::AppHandle) -> Result<(), SyncError> {
         .await
         .map_err(SyncError::network)?;
     parse_empty(res).await?;
+    if let Some(session) = read_session(&app) {
+        delete_keys(&app, &session.account_id);
+    }
     delete_session(&app);
     Ok(())
 }
@@ -417,6 +902,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cancel_sync_trips_guards_started_before_it() {
+        let guard = CancelGuard::start();
+        assert!(guard.check().is_ok());
+        cancel_sync();
+        let err = guard.check().unwrap_err();
+        assert_eq!(err.kind, "cancelled");
+        assert_eq!(err.status_code, 1);
+        assert!(CancelGuard::start().check().is_ok());
+    }
+
+    #[test]
+    fn progress_serializes_camel_case() {
+        let json = serde_json::to_value(SyncProgress {
+            phase: "download",
+            key: "dev/snapshots/1.json".into(),
+            bytes_transferred: 10,
+            total_bytes: None,
+            done: false,
+        })
+        .unwrap();
+        assert_eq!(json["bytesTransferred"], 10);
+        assert!(json["totalBytes"].is_null());
+    }
+
     #[test]
     fn network_error_is_status_zero() {
         // status 0 is what offline.ts treats as a retryable network failure.
@@ -0,0 +1,39 @@
+//! Helpers shared by the unit tests of several modules.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// An empty directory under the system temp dir, removed again on drop so
+/// test runs don't leave databases behind.
+pub(crate) struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    /// `bm-{name}-{pid}`, emptied first if an earlier run left it behind.
+    /// Tests run in parallel, so `name` must be unique across the crate.
+    pub(crate) fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("bm-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Deref for ScratchDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for ScratchDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
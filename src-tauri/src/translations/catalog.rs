@@ -0,0 +1,470 @@
+//! The hosted translation catalog.
+//!
+//! `catalog.json` on biblemarker.app lists freely licensed translations
+//! already built as content files. `download_translation` fetches one into
+//! `<app data>/content/<id>.db.<sha>.part`, resuming a partial file with a
+//! `Range` request, checks its size and SHA-256 against the catalog, then
+//! installs and mounts it. A checksum in the partial file's name keeps a
+//! download of a newer build from resuming an older one.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{command, Emitter};
+
+use crate::content::{self, MountedContent};
+use crate::db::{self, DbError, DbErrorKind};
+use crate::download::hash_file;
+
+const CATALOG_URL: &str = "https://biblemarker.app/translations/catalog.json";
+
+/// Hosts translation files may be fetched from; the catalog can't point
+/// downloads anywhere else.
+const ALLOWED_HOSTS: &[&str] = &["biblemarker.app"];
+
+/// Emitted with a `DownloadProgress` as a translation downloads.
+const PROGRESS_EVENT: &str = "translations://download-progress";
+
+/// Emit at most once per this many bytes while streaming a download.
+const PROGRESS_STEP_BYTES: u64 = 256 * 1024;
+
+/// A translation the catalog offers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AvailableTranslation {
+    /// Translation id, which is also its content file's `module_id`.
+    pub id: String,
+    pub name: String,
+    pub abbreviation: Option<String>,
+    /// BCP 47 language tag, e.g. `en`.
+    pub language: String,
+    pub license: String,
+    /// Copyright notice exports must carry, when the license needs one.
+    #[serde(default)]
+    pub copyright: Option<String>,
+    /// Most verses one export may quote, when the publisher limits it.
+    #[serde(rename = "quoteLimit", default)]
+    pub quote_limit: Option<u32>,
+    #[serde(default = "default_versification")]
+    pub versification: String,
+    /// Catalog build of the content file; bumped when it is rebuilt.
+    pub version: String,
+    /// Content file URL, possibly relative to the catalog.
+    pub url: String,
+    /// Size of the content file in bytes.
+    pub size: u64,
+    /// Hex SHA-256 of the content file.
+    pub sha256: String,
+    /// Whether a content file for `id` is already installed (not from the
+    /// catalog; filled in when listing).
+    #[serde(default)]
+    pub installed: bool,
+}
+
+fn default_versification() -> String {
+    "KJV".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct Catalog {
+    translations: Vec<AvailableTranslation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub id: String,
+    pub received: u64,
+    pub total: u64,
+    pub done: bool,
+}
+
+/// The catalog as last fetched, so a download doesn't fetch it again.
+static CATALOG: Mutex<Vec<AvailableTranslation>> = Mutex::new(Vec::new());
+
+/// Ids being downloaded; a second download of one is refused.
+static DOWNLOADING: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+fn catalog() -> MutexGuard<'static, Vec<AvailableTranslation>> {
+    CATALOG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn downloading() -> MutexGuard<'static, Option<HashSet<String>>> {
+    DOWNLOADING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Marks `id` as downloading until dropped.
+struct Downloading(String);
+
+impl Downloading {
+    fn start(id: &str) -> Result<Self, DbError> {
+        if !downloading()
+            .get_or_insert_with(HashSet::new)
+            .insert(id.to_string())
+        {
+            return Err(DbError::new(
+                DbErrorKind::Busy,
+                format!("`{id}` is already downloading"),
+            ));
+        }
+        Ok(Self(id.to_string()))
+    }
+}
+
+impl Drop for Downloading {
+    fn drop(&mut self) {
+        if let Some(ids) = downloading().as_mut() {
+            ids.remove(&self.0);
+        }
+    }
+}
+
+fn network(e: reqwest::Error) -> DbError {
+    DbError::io(format!("Download failed: {e}"))
+}
+
+/// `url` resolved against the catalog, if it is `https://` on an allowed host.
+fn resolve_url(url: &str) -> Option<reqwest::Url> {
+    let url = reqwest::Url::parse(CATALOG_URL).ok()?.join(url).ok()?;
+    let allowed = url.scheme() == "https"
+        && url
+            .host_str()
+            .is_some_and(|host| ALLOWED_HOSTS.contains(&host));
+    allowed.then_some(url)
+}
+
+/// Read `catalog.json`, keeping the entries that can be downloaded.
+fn parse_catalog(src: &str) -> Result<Vec<AvailableTranslation>, DbError> {
+    let catalog: Catalog = serde_json::from_str(src)
+        .map_err(|e| DbError::invalid(format!("The translation catalog is malformed: {e}")))?;
+    let mut seen = HashSet::new();
+    Ok(catalog
+        .translations
+        .into_iter()
+        .filter(|t| {
+            let ok = content::slug(&t.id).is_ok()
+                && resolve_url(&t.url).is_some()
+                && t.sha256.len() == 64
+                && t.sha256.bytes().all(|b| b.is_ascii_hexdigit())
+                && seen.insert(t.id.clone());
+            if !ok {
+                println!("[catalog] Skipping catalog entry `{}`", t.id);
+            }
+            ok
+        })
+        .map(|t| AvailableTranslation {
+            sha256: t.sha256.to_ascii_lowercase(),
+            installed: false,
+            ..t
+        })
+        .collect())
+}
+
+async fn fetch_catalog() -> Result<Vec<AvailableTranslation>, DbError> {
+    let response = reqwest::get(CATALOG_URL).await.map_err(network)?;
+    if !response.status().is_success() {
+        return Err(DbError::io(format!(
+            "Could not fetch the translation catalog: HTTP {}",
+            response.status()
+        )));
+    }
+    let translations = parse_catalog(&response.text().await.map_err(network)?)?;
+    *catalog() = translations.clone();
+    Ok(translations)
+}
+
+/// Where a partial download of `entry` is kept.
+fn partial_path(target: &Path, entry: &AvailableTranslation) -> PathBuf {
+    PathBuf::from(format!("{}.{}.part", target.display(), &entry.sha256[..12]))
+}
+
+/// Fetch `url` into `partial`, continuing from what's already there.
+async fn fetch(
+    app: &tauri::AppHandle,
+    entry: &AvailableTranslation,
+    url: reqwest::Url,
+    partial: &Path,
+) -> Result<(), DbError> {
+    let progress = |received: u64| {
+        let _ = app.emit(
+            PROGRESS_EVENT,
+            DownloadProgress {
+                id: entry.id.clone(),
+                received,
+                total: entry.size,
+                done: false,
+            },
+        );
+    };
+    let client = reqwest::Client::new();
+    // Once more from the start if the server won't resume.
+    for _ in 0..2 {
+        let have = std::fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+        if have > entry.size {
+            let _ = std::fs::remove_file(partial);
+            continue;
+        }
+        if have == entry.size {
+            return Ok(());
+        }
+        let mut request = client.get(url.clone());
+        if have > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={have}-"));
+        }
+        let mut response = request.send().await.map_err(network)?;
+        let status = response.status();
+        if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            let _ = std::fs::remove_file(partial);
+            continue;
+        }
+        if !status.is_success() {
+            return Err(DbError::io(format!("Download failed: HTTP {status}")));
+        }
+        // A 200 is the whole file, whatever was asked for.
+        let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(resumed)
+            .write(true)
+            .truncate(!resumed)
+            .open(partial)
+            .map_err(|e| DbError::io(format!("Failed to write {}: {e}", partial.display())))?;
+        let mut received = if resumed { have } else { 0 };
+        let mut last_emitted = received;
+        progress(received);
+        while let Some(chunk) = response.chunk().await.map_err(network)? {
+            file.write_all(&chunk)
+                .map_err(|e| DbError::io(format!("Failed to write {}: {e}", partial.display())))?;
+            received += chunk.len() as u64;
+            if received > entry.size {
+                break;
+            }
+            if received - last_emitted >= PROGRESS_STEP_BYTES {
+                last_emitted = received;
+                progress(received);
+            }
+        }
+        file.flush()
+            .map_err(|e| DbError::io(format!("Failed to write {}: {e}", partial.display())))?;
+        return Ok(());
+    }
+    Err(DbError::io(format!(
+        "The server would not send `{}` from the start",
+        entry.id
+    )))
+}
+
+/// Check a finished download against the catalog, removing it if damaged.
+fn check_download(entry: &AvailableTranslation, partial: &Path) -> Result<(), DbError> {
+    let size = std::fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    let sha256 = hash_file(partial)
+        .map_err(|e| DbError::io(format!("Failed to read {}: {e}", partial.display())))?;
+    if size != entry.size || sha256 != entry.sha256 {
+        let _ = std::fs::remove_file(partial);
+        return Err(DbError::new(
+            DbErrorKind::Corrupt,
+            format!(
+                "The download of `{}` is damaged ({size} bytes, SHA-256 {sha256}); try again",
+                entry.id
+            ),
+        ));
+    }
+    content::inspect(partial, &entry.id)?;
+    Ok(())
+}
+
+/// Check a finished download against the catalog and move it into place.
+fn install(
+    app: &tauri::AppHandle,
+    entry: &AvailableTranslation,
+    partial: &Path,
+    target: &Path,
+) -> Result<MountedContent, DbError> {
+    check_download(entry, partial)?;
+    {
+        let conn = rusqlite::Connection::open(partial)?;
+        for (key, value) in [
+            ("catalog_version", entry.version.as_str()),
+            ("license", entry.license.as_str()),
+            ("language", entry.language.as_str()),
+            ("versification", entry.versification.as_str()),
+        ] {
+            content::set_info(&conn, key, value)?;
+        }
+        let quote_limit = entry.quote_limit.map(|l| l.to_string());
+        for (key, value) in [
+            ("copyright", entry.copyright.as_deref()),
+            ("quote_limit", quote_limit.as_deref()),
+        ] {
+            if let Some(value) = value {
+                content::set_info(&conn, key, value)?;
+            }
+        }
+        // Builds from before checksums get them from the verified file.
+        content::record_checksums(&conn, None)?;
+    }
+    std::fs::rename(partial, target)
+        .map_err(|e| DbError::io(format!("Failed to install {}: {e}", target.display())))?;
+
+    let remount = content::mounted(&entry.id).is_some();
+    let mounted = content::mount(&entry.id, target)?;
+    if remount {
+        // Readers still attached to the old file pick up the new one.
+        db::connections::manager(&db::db_path(app)?).release_readers();
+    }
+    Ok(mounted)
+}
+
+/// Fetch the catalog, marking the translations already installed.
+#[command]
+pub async fn list_available_translations(
+    app: tauri::AppHandle,
+) -> Result<Vec<AvailableTranslation>, DbError> {
+    let mut translations = fetch_catalog().await?;
+    for t in &mut translations {
+        t.installed = content::content_path(&app, &t.id).is_ok_and(|path| path.exists());
+    }
+    Ok(translations)
+}
+
+/// Catalog entry `id`, from the last fetch unless `refresh`.
+pub(crate) async fn entry(id: &str, refresh: bool) -> Result<AvailableTranslation, DbError> {
+    let cached = catalog().iter().find(|t| t.id == id).cloned();
+    let translations = match cached {
+        Some(entry) if !refresh => return Ok(entry),
+        _ => fetch_catalog().await?,
+    };
+    translations
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| {
+            DbError::new(
+                DbErrorKind::NotFound,
+                format!("`{id}` is not in the translation catalog"),
+            )
+        })
+}
+
+/// Whether `id` is downloading right now.
+pub(crate) fn is_downloading(id: &str) -> bool {
+    downloading().as_ref().is_some_and(|ids| ids.contains(id))
+}
+
+/// Download `entry`, install it (replacing an installed copy) and mount it.
+pub(crate) async fn download(
+    app: &tauri::AppHandle,
+    entry: AvailableTranslation,
+) -> Result<MountedContent, DbError> {
+    let url = resolve_url(&entry.url)
+        .ok_or_else(|| DbError::invalid(format!("Refusing to download from {}", entry.url)))?;
+    let _downloading = Downloading::start(&entry.id)?;
+    let target = content::content_path(app, &entry.id)?;
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| DbError::io(format!("Failed to create {}: {e}", dir.display())))?;
+    }
+    let partial = partial_path(&target, &entry);
+
+    fetch(app, &entry, url, &partial).await?;
+    let mounted = tauri::async_runtime::spawn_blocking({
+        let app = app.clone();
+        let entry = entry.clone();
+        move || install(&app, &entry, &partial, &target)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Install task failed: {e}")))??;
+    let _ = app.emit(
+        PROGRESS_EVENT,
+        DownloadProgress {
+            id: entry.id,
+            received: entry.size,
+            total: entry.size,
+            done: true,
+        },
+    );
+    Ok(mounted)
+}
+
+/// Download `entry` without installing it, returning the checked file for
+/// the caller to take what it needs from and remove.
+pub(crate) async fn download_copy(
+    app: &tauri::AppHandle,
+    entry: &AvailableTranslation,
+) -> Result<PathBuf, DbError> {
+    let url = resolve_url(&entry.url)
+        .ok_or_else(|| DbError::invalid(format!("Refusing to download from {}", entry.url)))?;
+    let _downloading = Downloading::start(&entry.id)?;
+    let partial = partial_path(&content::content_path(app, &entry.id)?, entry);
+    fetch(app, entry, url, &partial).await?;
+    tauri::async_runtime::spawn_blocking({
+        let entry = entry.clone();
+        let partial = partial.clone();
+        move || check_download(&entry, &partial)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Install task failed: {e}")))??;
+    let _ = app.emit(
+        PROGRESS_EVENT,
+        DownloadProgress {
+            id: entry.id.clone(),
+            received: entry.size,
+            total: entry.size,
+            done: true,
+        },
+    );
+    Ok(partial)
+}
+
+/// Download catalog translation `id`, install it (replacing an installed
+/// copy) and mount it. An interrupted download resumes where it stopped.
+#[command]
+pub async fn download_translation(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<MountedContent, DbError> {
+    let entry = entry(&id, false).await?;
+    download(&app, entry).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downloads_only_from_allowed_hosts() {
+        assert_eq!(
+            resolve_url("web.db").unwrap().as_str(),
+            "https://biblemarker.app/translations/web.db"
+        );
+        assert!(resolve_url("https://biblemarker.app/other/kjv.db").is_some());
+        assert!(resolve_url("http://biblemarker.app/translations/web.db").is_none());
+        assert!(resolve_url("https://biblemarker.app.evil.com/web.db").is_none());
+        assert!(resolve_url("file:///etc/passwd").is_none());
+    }
+
+    #[test]
+    fn keeps_catalog_entries_that_can_be_downloaded() {
+        let sha = "AB".repeat(32);
+        let src = format!(
+            r#"{{"translations": [
+                {{"id": "web", "name": "World English Bible", "abbreviation": "WEB",
+                  "language": "en", "license": "Public Domain", "version": "2024.1",
+                  "url": "web.db", "size": 10, "sha256": "{sha}"}},
+                {{"id": "web", "name": "Duplicate", "language": "en", "license": "",
+                  "version": "1", "url": "web.db", "size": 10, "sha256": "{sha}"}},
+                {{"id": "evil", "name": "Elsewhere", "language": "en", "license": "",
+                  "version": "1", "url": "https://example.com/evil.db", "size": 10,
+                  "sha256": "{sha}"}},
+                {{"id": "short", "name": "Bad sum", "language": "en", "license": "",
+                  "version": "1", "url": "short.db", "size": 10, "sha256": "abc"}}
+            ]}}"#
+        );
+        let translations = parse_catalog(&src).unwrap();
+        assert_eq!(translations.len(), 1);
+        let web = &translations[0];
+        assert_eq!(web.name, "World English Bible");
+        assert_eq!(web.versification, "KJV");
+        assert_eq!(web.sha256, "ab".repeat(32));
+        assert!(parse_catalog("{}").is_err());
+    }
+}
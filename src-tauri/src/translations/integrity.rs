@@ -0,0 +1,312 @@
+//! Checking installed translations against the checksums recorded when
+//! they were imported or downloaded, and repairing them from the catalog.
+//!
+//! Every content file keeps a verse count and SHA-256 per book in
+//! `book_checksums`. A book whose verses are gone, fewer or different is
+//! damaged. A catalog translation is repaired by fetching the same build
+//! again and copying just the damaged books out of it, so undamaged books
+//! and terms recorded since stay as they are; a file SQLite itself reports
+//! as corrupt is reinstalled whole.
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::command;
+
+use super::catalog;
+use super::manager::installed_path;
+use crate::content;
+use crate::db::{self, DbError, DbErrorKind};
+
+/// Tables holding a book's text and everything anchored in it.
+const BOOK_TABLES: &[&str] = &[
+    "books",
+    "verses",
+    "verse_html",
+    "headings",
+    "footnotes",
+    "footnote_index",
+    "words",
+    "red_letter",
+    "paragraphs",
+    "alignments",
+];
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DamagedBook {
+    pub book: String,
+    /// `missing` (no verses left), `incomplete` (fewer or more verses than
+    /// recorded) or `corrupted` (verse text changed).
+    pub problem: String,
+    pub verses: i64,
+    pub expected: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct IntegrityReport {
+    pub id: String,
+    /// SQLite found the file itself sound.
+    pub intact: bool,
+    /// Checksums were recorded; files imported before they were have
+    /// nothing to check against.
+    pub recorded: bool,
+    /// Books checked against their checksums.
+    pub books: usize,
+    pub damaged: Vec<DamagedBook>,
+    /// Books copied back from the catalog by a repair.
+    pub repaired: Vec<String>,
+    /// A repair replaced the whole file.
+    pub reinstalled: bool,
+}
+
+/// The report on a file nothing could be checked in.
+fn unchecked(id: &str) -> IntegrityReport {
+    IntegrityReport {
+        id: id.to_string(),
+        intact: false,
+        recorded: false,
+        books: 0,
+        damaged: Vec::new(),
+        repaired: Vec::new(),
+        reinstalled: false,
+    }
+}
+
+/// Compare the content file open as `conn` with its recorded checksums.
+fn check(conn: &Connection, id: &str) -> Result<IntegrityReport, DbError> {
+    let mut report = unchecked(id);
+    let quick_check: Option<String> = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .optional()
+        .unwrap_or(None);
+    if quick_check.as_deref() != Some("ok") {
+        return Ok(report);
+    }
+    report.intact = true;
+    if !content::has_table(conn, "main", "book_checksums")? {
+        return Ok(report);
+    }
+    report.recorded = true;
+    let recorded: BTreeMap<String, (i64, String)> = conn
+        .prepare("SELECT book, verses, sha256 FROM book_checksums")?
+        .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+        .collect::<rusqlite::Result<_>>()?;
+    let found = content::checksums(conn, "main")?;
+    report.books = recorded.len();
+    for (book, (expected, sha256)) in recorded {
+        let (verses, found_sha256) = found.get(&book).cloned().unwrap_or_default();
+        let problem = if verses == 0 {
+            "missing"
+        } else if verses != expected {
+            "incomplete"
+        } else if found_sha256 != sha256 {
+            "corrupted"
+        } else {
+            continue;
+        };
+        report.damaged.push(DamagedBook {
+            book,
+            problem: problem.to_string(),
+            verses,
+            expected,
+        });
+    }
+    Ok(report)
+}
+
+/// Check the content file at `path`; one SQLite can't open counts as not
+/// intact.
+fn verify(path: &Path, id: &str) -> Result<IntegrityReport, DbError> {
+    match Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) {
+        Ok(conn) => check(&conn, id),
+        Err(_) => Ok(unchecked(id)),
+    }
+}
+
+/// Replace `books` of the content file open as `conn` with the copy's,
+/// returning the books the copy had.
+fn copy_books(
+    conn: &mut Connection,
+    copy: &Path,
+    books: &[String],
+) -> Result<Vec<String>, DbError> {
+    conn.execute("ATTACH DATABASE ?1 AS repair", [copy.display().to_string()])?;
+    let copied = (|| {
+        let tables: Vec<&str> = BOOK_TABLES
+            .iter()
+            .copied()
+            .filter(|t| {
+                content::has_table(conn, "main", t).unwrap_or(false)
+                    && content::has_table(conn, "repair", t).unwrap_or(false)
+            })
+            .collect();
+        let tx = conn.transaction()?;
+        let mut copied = Vec::new();
+        for book in books {
+            let verses: i64 = tx.query_row(
+                "SELECT COUNT(*) FROM repair.verses WHERE book = ?1",
+                [book],
+                |row| row.get(0),
+            )?;
+            if verses == 0 {
+                continue;
+            }
+            for table in &tables {
+                tx.execute(&format!("DELETE FROM main.{table} WHERE book = ?1"), [book])?;
+                tx.execute(
+                    &format!(
+                        "INSERT INTO main.{table} SELECT * FROM repair.{table} WHERE book = ?1"
+                    ),
+                    [book],
+                )?;
+            }
+            copied.push(book.clone());
+        }
+        content::record_checksums(&tx, Some(&copied))?;
+        tx.commit()?;
+        Ok::<_, DbError>(copied)
+    })();
+    conn.execute("DETACH DATABASE repair", params![])?;
+    copied
+}
+
+/// Check installed translation `id` against the checksums recorded when it
+/// was imported or downloaded. With `repair`, a catalog translation's
+/// damaged books are downloaded again and the file checked once more.
+#[command]
+pub async fn verify_translation(
+    app: tauri::AppHandle,
+    id: String,
+    repair: Option<bool>,
+) -> Result<IntegrityReport, DbError> {
+    let path = installed_path(&app, &id)?;
+    let report = tauri::async_runtime::spawn_blocking({
+        let (path, id) = (path.clone(), id.clone());
+        move || verify(&path, &id)
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Verify task failed: {e}")))??;
+    if !repair.unwrap_or(false) || (report.intact && report.damaged.is_empty()) {
+        return Ok(report);
+    }
+
+    let current = if report.intact {
+        content::read_info(&path)?.remove("catalog_version")
+    } else {
+        // Nothing can be read from it; a catalog id is repaired from the
+        // latest build.
+        None
+    };
+    if report.intact && current.is_none() {
+        return Err(DbError::invalid(format!(
+            "`{id}` was imported, not downloaded; import it again to repair it"
+        )));
+    }
+    let entry = catalog::entry(&id, true).await?;
+    if !report.intact {
+        catalog::download(&app, entry).await?;
+        let mut repaired = verify(&path, &id)?;
+        repaired.reinstalled = true;
+        return Ok(repaired);
+    }
+    if current.as_deref() != Some(entry.version.as_str()) {
+        return Err(DbError::invalid(format!(
+            "The catalog now has a newer build of `{id}`; update it instead"
+        )));
+    }
+
+    let copy = catalog::download_copy(&app, &entry).await?;
+    let damaged: Vec<String> = report.damaged.iter().map(|b| b.book.clone()).collect();
+    let result = tauri::async_runtime::spawn_blocking({
+        let (path, copy, id) = (path.clone(), copy.clone(), id.clone());
+        move || {
+            let repaired = copy_books(&mut Connection::open(&path)?, &copy, &damaged)?;
+            Ok::<_, DbError>(IntegrityReport {
+                repaired,
+                ..verify(&path, &id)?
+            })
+        }
+    })
+    .await
+    .map_err(|e| DbError::new(DbErrorKind::Sqlite, format!("Repair task failed: {e}")));
+    let _ = std::fs::remove_file(&copy);
+    let report = result??;
+    if content::mounted(&id).is_some() {
+        // Readers still holding the damaged pages read them again.
+        db::connections::manager(&db::db_path(&app)?).release_readers();
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    fn content_file(path: &Path, verses: &[(&str, i64, &str)]) -> Connection {
+        let conn = content::create(path, "web", "World English Bible").unwrap();
+        for (book, verse, text) in verses {
+            conn.execute(
+                "INSERT INTO verses VALUES (?1, 1, ?2, ?3)",
+                params![book, verse, text],
+            )
+            .unwrap();
+        }
+        content::record_checksums(&conn, None).unwrap();
+        conn
+    }
+
+    #[test]
+    fn finds_damaged_books_and_copies_them_back() {
+        let dir = ScratchDir::new("translation-integrity");
+        let verses = [
+            ("Gen", 1, "In the beginning"),
+            ("Gen", 2, "The earth was formless"),
+            ("Ruth", 1, "In the days"),
+            ("John", 1, "In the beginning was the Word"),
+        ];
+        let copy = dir.join("copy.db");
+        content_file(&copy, &verses);
+        let path = dir.join("web.db");
+        let mut conn = content_file(&path, &verses);
+        assert!(check(&conn, "web").unwrap().damaged.is_empty());
+
+        conn.execute_batch(
+            "DELETE FROM verses WHERE book = 'Ruth';
+             DELETE FROM verses WHERE book = 'Gen' AND verse = 2;
+             UPDATE verses SET text = 'In the beginning was the Wrod' WHERE book = 'John';",
+        )
+        .unwrap();
+        let report = check(&conn, "web").unwrap();
+        assert!(report.intact && report.recorded);
+        assert_eq!(report.books, 3);
+        let problems: Vec<(&str, &str)> = report
+            .damaged
+            .iter()
+            .map(|b| (b.book.as_str(), b.problem.as_str()))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                ("Gen", "incomplete"),
+                ("John", "corrupted"),
+                ("Ruth", "missing")
+            ]
+        );
+
+        let damaged = ["Gen", "John", "Ruth", "Rev"].map(String::from);
+        assert_eq!(
+            copy_books(&mut conn, &copy, &damaged).unwrap(),
+            ["Gen", "John", "Ruth"]
+        );
+        assert!(check(&conn, "web").unwrap().damaged.is_empty());
+
+        let old = Connection::open_in_memory().unwrap();
+        old.execute_batch(
+            "CREATE TABLE verses (book TEXT, chapter INTEGER, verse INTEGER, text TEXT)",
+        )
+        .unwrap();
+        assert!(!check(&old, "old").unwrap().recorded);
+    }
+}
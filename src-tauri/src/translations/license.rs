@@ -0,0 +1,172 @@
+//! License terms of installed translations, and what exports may quote.
+//!
+//! Terms live in the translation's `content_info`: `license` and
+//! `copyright` as the catalog or the importer's source gave them, and
+//! `attribution` and `quote_limit` when the publisher's terms need more
+//! than the license says. Translations that aren't freely licensed, or
+//! whose terms are unknown, are restricted: an export quotes at most
+//! `quote_limit` verses (by default the 500 most publishers allow without
+//! asking) and always carries the attribution.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::command;
+
+use super::manager::installed_path;
+use crate::content;
+use crate::db::DbError;
+
+/// Verses a restricted translation's exports may quote when its terms
+/// don't say.
+const DEFAULT_QUOTE_LIMIT: u32 = 500;
+
+/// Licenses that allow quoting any amount with attribution.
+const FREE_LICENSES: &[&str] = &[
+    "public domain",
+    "cc0",
+    "cc by",
+    "cc-by",
+    "creative commons",
+    "gpl",
+    "gfdl",
+];
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TranslationLicense {
+    pub id: String,
+    pub license: Option<String>,
+    pub copyright: Option<String>,
+    /// What an export must carry: the recorded attribution, else the
+    /// copyright notice, else the name and license.
+    pub attribution: String,
+    /// Most verses one export may quote; `None` for no limit.
+    #[serde(rename = "quoteLimit")]
+    pub quote_limit: Option<u32>,
+    pub restricted: bool,
+}
+
+/// Whether `license` allows quoting freely.
+fn is_free(license: &str) -> bool {
+    let license = license.to_lowercase();
+    !license.contains("copyrighted") && FREE_LICENSES.iter().any(|free| license.contains(free))
+}
+
+/// The terms `info` (a content file's `content_info`) records for `id`.
+pub(crate) fn terms(id: &str, info: &BTreeMap<String, String>) -> TranslationLicense {
+    let get = |key: &str| {
+        info.get(key)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let license = get("license");
+    let copyright = get("copyright");
+    let free = license.as_deref().is_some_and(is_free);
+    let quote_limit = match get("quote_limit").and_then(|l| l.parse().ok()) {
+        Some(limit) => Some(limit),
+        None if free => None,
+        None => Some(DEFAULT_QUOTE_LIMIT),
+    };
+    let name = get("name").unwrap_or_else(|| id.to_string());
+    let attribution = get("attribution")
+        .or_else(|| copyright.clone())
+        .unwrap_or_else(|| match &license {
+            Some(license) => format!("{name} — {license}."),
+            None => name,
+        });
+    TranslationLicense {
+        id: id.to_string(),
+        license,
+        copyright,
+        attribution,
+        quote_limit,
+        restricted: quote_limit.is_some(),
+    }
+}
+
+/// The license terms of installed translation `id`.
+#[command]
+pub fn get_translation_license(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<TranslationLicense, DbError> {
+    let path = installed_path(&app, &id)?;
+    Ok(terms(&id, &content::read_info(&path)?))
+}
+
+/// Record the license terms of installed translation `id`, for one imported
+/// without them or whose publisher asks for more. Empty or missing values
+/// clear what was recorded.
+#[command]
+pub fn set_translation_license(
+    app: tauri::AppHandle,
+    id: String,
+    license: Option<String>,
+    copyright: Option<String>,
+    attribution: Option<String>,
+    quote_limit: Option<u32>,
+) -> Result<TranslationLicense, DbError> {
+    let path = installed_path(&app, &id)?;
+    {
+        let conn = Connection::open(&path)?;
+        let quote_limit = quote_limit.map(|l| l.to_string());
+        for (key, value) in [
+            ("license", license),
+            ("copyright", copyright),
+            ("attribution", attribution),
+            ("quote_limit", quote_limit),
+        ] {
+            match value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                Some(value) => content::set_info(&conn, key, value)?,
+                None => {
+                    conn.execute("DELETE FROM content_info WHERE key = ?1", [key])?;
+                }
+            }
+        }
+    }
+    Ok(terms(&id, &content::read_info(&path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn restricts_translations_not_freely_licensed() {
+        let web = terms(
+            "WEB",
+            &info(&[
+                ("name", "World English Bible"),
+                ("license", "Public Domain"),
+            ]),
+        );
+        assert_eq!(
+            (web.restricted, web.quote_limit, web.attribution.as_str()),
+            (false, None, "World English Bible — Public Domain.")
+        );
+
+        let sword = terms(
+            "NASB",
+            &info(&[
+                (
+                    "license",
+                    "Copyrighted; Permission to distribute granted to CrossWire",
+                ),
+                ("copyright", "Copyright © The Lockman Foundation"),
+            ]),
+        );
+        assert_eq!(sword.quote_limit, Some(DEFAULT_QUOTE_LIMIT));
+        assert_eq!(sword.attribution, "Copyright © The Lockman Foundation");
+
+        let unknown = terms("mine", &info(&[("quote_limit", "100")]));
+        assert_eq!((unknown.restricted, unknown.quote_limit), (true, Some(100)));
+        assert_eq!(unknown.attribution, "mine");
+    }
+}
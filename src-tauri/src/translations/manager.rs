@@ -0,0 +1,306 @@
+//! Installed translations: listing, inspecting, updating and removing them.
+//!
+//! A translation is installed when `<app data>/content/<id>.db` exists,
+//! whichever importer or catalog download wrote it. Removing one deletes
+//! only that file. Highlights, symbols and notes made in it live in the user
+//! database and sync like any others, so they are kept and show again if the
+//! translation is reinstalled; while any exist, removal has to be forced so
+//! the UI can warn that they'll be hidden meanwhile.
+
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::Path;
+use tauri::{command, Manager};
+
+use super::catalog;
+use crate::content::{self, CONTENT_DIR};
+use crate::db::{self, DbError, DbErrorKind};
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct InstalledTranslation {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    /// Size of the content file in bytes.
+    pub size: u64,
+    pub mounted: bool,
+    /// Importer that built the file (`usfm`, `sword`, ...); `None` for
+    /// catalog downloads built elsewhere.
+    #[serde(rename = "sourceFormat")]
+    pub source_format: Option<String>,
+    /// Catalog build, for translations downloaded from the catalog.
+    #[serde(rename = "catalogVersion")]
+    pub catalog_version: Option<String>,
+}
+
+/// User data attached to a translation.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct Markings {
+    /// Highlights, symbols and other verse annotations.
+    pub annotations: i64,
+    pub notes: i64,
+}
+
+impl Markings {
+    fn total(&self) -> i64 {
+        self.annotations + self.notes
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranslationInfo {
+    #[serde(flatten)]
+    pub installed: InstalledTranslation,
+    pub license: Option<String>,
+    pub language: Option<String>,
+    /// Versification the source declared; importers lay verses out as the
+    /// source numbers them.
+    pub versification: Option<String>,
+    pub version: Option<String>,
+    pub verses: i64,
+    pub markings: Markings,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranslationUpdate {
+    pub id: String,
+    /// False when the installed copy was already the catalog's latest.
+    pub updated: bool,
+    pub version: String,
+}
+
+/// What the content file at `path` holds, if it is one.
+fn installed(path: &Path) -> Result<InstalledTranslation, DbError> {
+    let info = content::read_info(path)?;
+    let id = info
+        .get("module_id")
+        .cloned()
+        .ok_or_else(|| DbError::invalid(format!("{} is not a content file", path.display())))?;
+    Ok(InstalledTranslation {
+        name: info.get("name").cloned().unwrap_or_else(|| id.clone()),
+        path: path.display().to_string(),
+        size: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        mounted: content::mounted(&id).is_some(),
+        source_format: info.get("source_format").cloned(),
+        catalog_version: info.get("catalog_version").cloned(),
+        id,
+    })
+}
+
+/// The translations installed in content directory `dir`, by name.
+fn list(dir: &Path) -> Vec<InstalledTranslation> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut translations: Vec<InstalledTranslation> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|path| match installed(&path) {
+            Ok(t) => Some(t),
+            Err(e) => {
+                println!("[translations] Skipping {}: {e}", path.display());
+                None
+            }
+        })
+        .collect();
+    translations.sort_by_key(|t| t.name.to_lowercase());
+    translations
+}
+
+/// Markings made in translation `id`. Module ids are matched without case,
+/// as content file names are.
+fn markings(conn: &Connection, id: &str) -> Result<Markings, DbError> {
+    let count = |table: &str| -> Result<i64, DbError> {
+        Ok(conn.query_row(
+            &format!("SELECT COUNT(*) FROM {table} WHERE module_id = ?1 COLLATE NOCASE"),
+            [id],
+            |row| row.get(0),
+        )?)
+    };
+    Ok(Markings {
+        annotations: count("annotations")?,
+        notes: count("notes")?,
+    })
+}
+
+/// The content file of installed translation `id`.
+pub(super) fn installed_path(
+    app: &tauri::AppHandle,
+    id: &str,
+) -> Result<std::path::PathBuf, DbError> {
+    let path = content::content_path(app, id)?;
+    if !path.exists() {
+        return Err(DbError::new(
+            DbErrorKind::NotFound,
+            format!("Translation `{id}` is not installed"),
+        ));
+    }
+    Ok(path)
+}
+
+#[command]
+pub fn list_installed_translations(
+    app: tauri::AppHandle,
+) -> Result<Vec<InstalledTranslation>, DbError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| DbError::io(format!("Cannot determine app data dir: {e}")))?
+        .join(CONTENT_DIR);
+    Ok(list(&dir))
+}
+
+/// License, language, versification and size of installed translation
+/// `id`, with the markings made in it.
+#[command]
+pub async fn get_translation_info(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<TranslationInfo, DbError> {
+    let path = installed_path(&app, &id)?;
+    let installed = installed(&path)?;
+    let verses = content::inspect(&path, &installed.id)?;
+    let info = content::read_info(&path)?;
+    let markings = db::with_reader(&app, move |conn| markings(conn, &id)).await?;
+    Ok(TranslationInfo {
+        installed,
+        license: info.get("license").cloned(),
+        language: info.get("language").cloned(),
+        versification: info.get("versification").cloned(),
+        version: info.get("version").cloned(),
+        verses,
+        markings,
+    })
+}
+
+/// Uninstall translation `id`: unmount it and delete its content file (and
+/// any partial download). Its markings are kept; while it has any, `force`
+/// must be set. Returns the markings left without their translation.
+#[command]
+pub async fn remove_translation(
+    app: tauri::AppHandle,
+    id: String,
+    force: Option<bool>,
+) -> Result<Markings, DbError> {
+    if catalog::is_downloading(&id) {
+        return Err(DbError::new(
+            DbErrorKind::Busy,
+            format!("`{id}` is downloading; wait for it to finish"),
+        ));
+    }
+    let path = installed_path(&app, &id)?;
+    let installed = installed(&path)?;
+    let markings = {
+        let id = installed.id.clone();
+        db::with_reader(&app, move |conn| markings(conn, &id)).await?
+    };
+    if markings.total() > 0 && force != Some(true) {
+        return Err(DbError::invalid(format!(
+            "`{id}` still has {} markings. They are kept, but hidden until it is \
+             installed again; remove it with `force` to go ahead",
+            markings.total()
+        )));
+    }
+
+    if content::unmount(&installed.id)? {
+        // Readers holding the file open let go of it before it's deleted.
+        db::connections::manager(&db::db_path(&app)?).release_readers();
+    }
+    std::fs::remove_file(&path)
+        .map_err(|e| DbError::io(format!("Failed to remove {}: {e}", path.display())))?;
+    // Downloads of it that never finished.
+    let prefix = format!(
+        "{}.",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    if let Some(entries) = path.parent().and_then(|dir| std::fs::read_dir(dir).ok()) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with(&prefix) && name.ends_with(".part") {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+    println!(
+        "[translations] Removed {id}, keeping {} markings",
+        markings.total()
+    );
+    Ok(markings)
+}
+
+/// Replace catalog translation `id` with the catalog's latest build if it
+/// has a newer one. Markings carry over, since verses keep their references.
+#[command]
+pub async fn update_translation(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<TranslationUpdate, DbError> {
+    let path = installed_path(&app, &id)?;
+    let installed = installed(&path)?;
+    let Some(current) = installed.catalog_version else {
+        return Err(DbError::invalid(format!(
+            "`{id}` was imported, not downloaded; import it again to update it"
+        )));
+    };
+    let entry = catalog::entry(&installed.id, true).await?;
+    if entry.version == current {
+        return Ok(TranslationUpdate {
+            id: installed.id,
+            updated: false,
+            version: current,
+        });
+    }
+    let version = entry.version.clone();
+    catalog::download(&app, entry).await?;
+    Ok(TranslationUpdate {
+        id: installed.id,
+        updated: true,
+        version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn lists_content_files_by_name() {
+        let dir = ScratchDir::new("installed");
+        for (id, name) in [("web", "World English Bible"), ("asv", "American Standard")] {
+            let conn = content::create(&dir.join(format!("{id}.db")), id, name).unwrap();
+            content::set_info(&conn, "source_format", "usfm").unwrap();
+        }
+        std::fs::write(dir.join("notes.db"), b"not a database").unwrap();
+        std::fs::write(dir.join("web.db.0123456789ab.part"), b"partial").unwrap();
+
+        let translations = list(&dir);
+        let ids: Vec<&str> = translations.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["asv", "web"]);
+        assert_eq!(translations[1].source_format.as_deref(), Some("usfm"));
+        assert!(translations[1].size > 0);
+        assert!(!translations[1].mounted);
+    }
+
+    #[test]
+    fn counts_markings_in_a_translation() {
+        let conn = db::test_connection();
+        conn.execute_batch(
+            "INSERT INTO annotations (id, module_id, type, data, created_at, updated_at)
+                 VALUES ('a1', 'kjv', 'highlight', '{}', 'now', 'now'),
+                        ('a2', 'KJV', 'symbol', '{}', 'now', 'now'),
+                        ('a3', 'web', 'highlight', '{}', 'now', 'now');
+             INSERT INTO notes (id, module_id, ref, content, created_at, updated_at)
+                 VALUES ('n1', 'kjv', '{}', 'note', 'now', 'now');",
+        )
+        .unwrap();
+        assert_eq!(
+            markings(&conn, "kjv").unwrap(),
+            Markings {
+                annotations: 2,
+                notes: 1
+            }
+        );
+        assert_eq!(markings(&conn, "asv").unwrap().total(), 0);
+    }
+}
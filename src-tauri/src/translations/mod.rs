@@ -0,0 +1,15 @@
+//! Installing and managing translations. An installed translation is a
+//! content file (see `content`), written by an importer or downloaded from
+//! the catalog; the manager lists, inspects, updates and removes them.
+
+// Hosted catalog of freely licensed translations and resumable downloads
+pub mod catalog;
+
+// Installed translations: list, inspect, update, remove
+pub mod manager;
+
+// License terms and the quoting limits exports follow
+pub mod license;
+
+// Per-book checksums: verifying installed translations and repairing them
+pub mod integrity;
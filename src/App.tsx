@@ -11,6 +11,7 @@ import { useStudyStore } from '@/stores/studyStore';
 import { useMultiTranslationStore } from '@/stores/multiTranslationStore';
 import { useListStore } from '@/stores/listStore';
 import { useKeywordExclusionStore } from '@/stores/keywordExclusionStore';
+import { useHighlightStyleStore } from '@/stores/highlightStyleStore';
 import { useMarkingPresetStore } from '@/stores/markingPresetStore';
 import { usePeopleStore } from '@/stores/peopleStore';
 import { usePlaceStore } from '@/stores/placeStore';
@@ -40,7 +41,9 @@ import { UndoToast, ToastHost, ConfirmDialogHost } from '@/components/shared';
 import { initializeSync, shutdownSync } from '@/lib/sync';
 import { useFeatureFlagsStore } from '@/stores/featureFlagsStore';
 import { checkForUpdateIfDue, fetchWhatsNew, fetchWhatsNewForced } from '@/lib/updateCheck';
-import { isCapacitor } from '@/lib/platform';
+import { isAndroid, isCapacitor, isIOS, isTauri } from '@/lib/platform';
+import { onClipboardReference, setClipboardShortcut } from '@/lib/references';
+import { onDeepLink, openDeepLink, takeDeepLinks, type DeepLink } from '@/lib/deepLinks';
 import { UpdateBanner, WhatsNewModal } from '@/components/shared';
 
 function GlobalUndoToast() {
@@ -129,6 +132,9 @@ export default function App() {
         setIsCheckingOnboarding(false);
         // Load exclusions after DB is ready (needs v6 schema)
         loadExclusions();
+        useHighlightStyleStore.getState().loadStyles().catch(err => {
+          console.error('Error loading highlight styles:', err);
+        });
       } catch (err) {
         console.error('Error loading preferences:', err);
         setIsCheckingOnboarding(false);
@@ -171,6 +177,14 @@ export default function App() {
       fetchWhatsNew().then(result => {
         if (result) setWhatsNew(result);
       });
+
+      if (isTauri() && !isIOS() && !isAndroid()) {
+        getPreferences()
+          .then(prefs => setClipboardShortcut(prefs.clipboardShortcut))
+          .catch(err => {
+            console.error('[App] Failed to set clipboard lookup shortcut:', err);
+          });
+      }
     }, 0);
 
     return () => {
@@ -200,6 +214,52 @@ export default function App() {
     };
   }, []);
 
+  // Desktop: go to references looked up on the clipboard (lookup shortcut or G)
+  useEffect(() => {
+    if (!isTauri() || isIOS() || isAndroid()) return;
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    onClipboardReference(({ book, chapter, verse }) => {
+      const { navigateToVerse, setLocation } = useBibleStore.getState();
+      if (verse != null) {
+        navigateToVerse(book, chapter, verse, true);
+      } else {
+        setLocation(book, chapter, true);
+      }
+    }).then(stop => {
+      if (cancelled) stop();
+      else unlisten = stop;
+    });
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+
+  // bible:// and biblemarker:// links: open any that launched the app, then each one after
+  useEffect(() => {
+    if (!isTauri()) return;
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    const open = (link: DeepLink) => {
+      openDeepLink(link).catch(err => {
+        console.error('[App] Failed to open link:', err);
+      });
+    };
+    onDeepLink(open).then(async stop => {
+      if (cancelled) {
+        stop();
+        return;
+      }
+      unlisten = stop;
+      (await takeDeepLinks()).forEach(open);
+    });
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+
   // When sync applies remote changes, refresh UI and re-load API configs
   // (API keys may have synced from another device but loadApiConfigs ran before sync completed)
   useEffect(() => {
@@ -217,6 +277,7 @@ export default function App() {
       await Promise.all([
         useMarkingPresetStore.getState().loadPresets(),
         useKeywordExclusionStore.getState().loadExclusions(),
+        useHighlightStyleStore.getState().loadStyles(),
         usePeopleStore.getState().loadPeople(),
         usePlaceStore.getState().loadPlaces(),
         useTimeStore.getState().loadTimeExpressions(),
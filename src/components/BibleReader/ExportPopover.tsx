@@ -7,6 +7,8 @@
  * auto-opens each in the system default PDF viewer.
  *
  * One chapter cap, copyright in the popover and on every PDF page footer.
+ * Installed translations' license terms can cap it further and supply the
+ * attribution.
  */
 
 import { useEffect, useState, type RefObject } from 'react';
@@ -33,6 +35,7 @@ import {
 import { buildStudyObservationPdf, observationFilename } from '@/lib/observation-pdf';
 import { buildChapterAndStudyPdf, chapterAndStudyFilename } from '@/lib/combined-pdf';
 import { savePdfBytes } from '@/lib/pdf/save';
+import { getTranslationLicense, type TranslationLicense } from '@/lib/translations';
 
 interface ExportPopoverProps {
   translation: ApiTranslation;
@@ -75,6 +78,19 @@ export function ExportPopover({ translation, book, chapter, verses, onClose, tri
   const [headings, setHeadings] = useState<SectionHeading[]>([]);
   const [notes, setNotes] = useState<Note[]>([]);
   const [chapterTitle, setChapterTitle] = useState<ChapterTitle | null>(null);
+  const [license, setLicense] = useState<TranslationLicense | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    // Only installed translations have recorded terms; others keep the
+    // provider's attribution.
+    getTranslationLicense(translation.id)
+      .then((terms) => { if (!cancelled) setLicense(terms); })
+      .catch(() => { if (!cancelled) setLicense(null); });
+    return () => {
+      cancelled = true;
+    };
+  }, [translation.id]);
 
   useEffect(() => {
     let cancelled = false;
@@ -136,6 +152,7 @@ export function ExportPopover({ translation, book, chapter, verses, onClose, tri
         presets,
         exclusions,
         activeStudyId,
+        license,
       };
 
       // Both selected → one combined PDF. Otherwise the single chosen section.
@@ -181,7 +198,12 @@ export function ExportPopover({ translation, book, chapter, verses, onClose, tri
   };
 
   const bookName = getBookById(book)?.name ?? book;
-  const attribution = getTranslationAttribution(translation);
+  const attribution = getTranslationAttribution(translation, license);
+  const selectedVerses = wholeChapter
+    ? verses.length
+    : verses.filter((v) => v.ref.verse >= startVerse && v.ref.verse <= endVerse).length;
+  const quoteLimit = license?.quoteLimit ?? null;
+  const capped = includeChapter && quoteLimit != null && selectedVerses > quoteLimit;
   const busy = action.status === 'busy';
 
   return (
@@ -266,6 +288,11 @@ export function ExportPopover({ translation, book, chapter, verses, onClose, tri
           )}
 
           <div className="pt-3 border-t border-scripture-border/30">
+            {capped && (
+              <p className="text-[11px] text-scripture-muted leading-relaxed">
+                {translation.name}'s license allows quoting {quoteLimit} verses; only the first {quoteLimit} are exported.
+              </p>
+            )}
             <p className="text-[11px] text-scripture-muted leading-relaxed">{attribution}</p>
           </div>
         </div>
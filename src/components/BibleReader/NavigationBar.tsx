@@ -53,6 +53,7 @@ export function NavigationBar() {
   // Keeping this commented for now as dropdowns shouldn't lock scroll
   // useScrollLock(anyPickerOpen);
   const [showSearch, setShowSearch] = useState(false);
+  const [searchQuery, setSearchQuery] = useState('');
 
   const [translations, setTranslations] = useState<ApiTranslation[]>([]);
   const [currentVerse, setCurrentVerse] = useState<number | null>(null);
@@ -222,6 +223,7 @@ export function NavigationBar() {
     const handleKeyDown = (e: KeyboardEvent) => {
       if ((e.metaKey || e.ctrlKey) && e.key === 'f') {
         e.preventDefault();
+        setSearchQuery('');
         setShowSearch(true);
       }
     };
@@ -252,7 +254,20 @@ export function NavigationBar() {
     setShowSearch(false);
   };
 
-  const openSearch = () => { closeAllPanels(); setShowSearch(true); };
+  const openSearch = () => { closeAllPanels(); setSearchQuery(''); setShowSearch(true); };
+
+  // Deep links (biblemarker://search?q=...) open search with their query
+  useEffect(() => {
+    const handleOpenSearch = (e: Event) => {
+      const { query = '' } = (e as CustomEvent<{ query?: string }>).detail ?? {};
+      closeAllPanels();
+      setSearchQuery(query);
+      setShowSearch(true);
+    };
+    window.addEventListener('openSearch', handleOpenSearch);
+    return () => window.removeEventListener('openSearch', handleOpenSearch);
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, []); // closeAllPanels only calls state setters, which are stable
   const openExport = () => { closeAllPanels(); setShowExportPopover(true); };
   // Toggle one panel closed/open, closing every other panel first.
   const togglePanel = (isOpen: boolean, setOpen: (v: boolean) => void) => {
@@ -417,6 +432,8 @@ export function NavigationBar() {
       {/* Search Modal - rendered outside nav to escape backdrop-blur stacking context */}
       {showSearch && (
         <Search
+          key={searchQuery}
+          initialQuery={searchQuery}
           onClose={() => setShowSearch(false)}
           onNavigate={(book, chapter, verse) => {
             setLocation(book, chapter);
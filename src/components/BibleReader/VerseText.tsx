@@ -6,7 +6,7 @@
 
 import { useState, useRef, useMemo } from 'react';
 import type { Verse, VerseRef } from '@/types';
-import type { Annotation, TextAnnotation, SymbolAnnotation, SymbolKey } from '@/types';
+import type { Annotation, TextAnnotation, SymbolAnnotation, SymbolKey, HighlightColor } from '@/types';
 import { getHighlightColorHex } from '@/types';
 import { SymbolIcon, getSymbolMarkup } from '@/lib/symbolDisplay';
 import { CrossReferencePopup } from './CrossReferencePopup';
@@ -18,6 +18,9 @@ import { filterPresetsByStudy } from '@/lib/studyFilter';
 import { getDebugFlagsSync } from '@/lib/debug';
 import { useKeywordExclusionStore } from '@/stores/keywordExclusionStore';
 import { useUndoToastStore } from '@/stores/undoToastStore';
+import { useHighlightStyleStore } from '@/stores/highlightStyleStore';
+import { styleDeclarations } from '@/lib/highlightStyles';
+import { spansVerse, spanInVerse } from '@/lib/annotationSpan';
 
 interface VerseTextProps {
   verse: Verse;
@@ -43,6 +46,7 @@ export function VerseText({ verse, annotations, moduleId, isSelected, onRemoveAn
   const { presets } = useMarkingPresetStore();
   const { activeStudyId } = useStudyStore();
   const { exclusions } = useKeywordExclusionStore();
+  const highlightStyles = useHighlightStyleStore(s => s.styles);
 
   // Filter presets by active study (null = global only; study = global + study)
   const filteredPresets = useMemo(
@@ -371,10 +375,9 @@ export function VerseText({ verse, annotations, moduleId, isSelected, onRemoveAn
   const renderAnnotatedText = (sourceText: string): string => {
     const verseNum = verse.ref.verse;
     
-    // Get annotations that apply to this verse
-    const verseAnnotations = textAnnotations.filter(ann => {
-      return ann.startRef.verse <= verseNum && ann.endRef.verse >= verseNum;
-    });
+    // Get annotations that apply to this verse, including spans that
+    // start or end in another verse or chapter
+    const verseAnnotations = textAnnotations.filter(ann => spansVerse(ann, verse.ref));
 
     // Get center symbols for this verse
     const verseCenterSymbols = centerSymbols.filter(sym => {
@@ -404,40 +407,23 @@ export function VerseText({ verse, annotations, moduleId, isSelected, onRemoveAn
 
     const ranges: AnnotationRange[] = [];
 
-    // Add text annotations - prefer word indices, fall back to character offsets
+    // Add text annotations - prefer word indices, fall back to character offsets.
+    // A span across verses covers this one from its start, whole, or up to its end.
+    const verseWords = splitIntoWords(plainText);
     for (const ann of verseAnnotations) {
-      if (ann.startRef.verse === verseNum && ann.endRef.verse === verseNum) {
-        // Try word indices first (new approach)
-        let charOffsets: { start: number; end: number } | null = null;
-        
-        if (ann.startWordIndex !== undefined && ann.endWordIndex !== undefined) {
-          charOffsets = wordIndicesToCharOffsets(plainText, ann.startWordIndex, ann.endWordIndex);
-        }
-        
-        // Fall back to character offsets (backward compatibility)
-        if (!charOffsets && ann.startOffset !== undefined && ann.endOffset !== undefined) {
-          // Validate and clamp offsets to text bounds
-          const start = Math.max(0, Math.min(ann.startOffset, plainText.length));
-          const end = Math.max(start, Math.min(ann.endOffset, plainText.length));
-          charOffsets = { start, end };
-        }
-        
-        if (charOffsets) {
-          // Ensure offsets are within bounds
-          charOffsets.start = Math.max(0, Math.min(charOffsets.start, plainText.length));
-          charOffsets.end = Math.max(charOffsets.start, Math.min(charOffsets.end, plainText.length));
-          let range = ranges.find(r => r.start === charOffsets!.start && r.end === charOffsets!.end);
-          if (!range) {
-            range = { 
-              start: charOffsets.start, 
-              end: charOffsets.end, 
-              textAnnotations: [], 
-              symbolAnnotations: [] 
-            };
-            ranges.push(range);
-          }
-          range.textAnnotations.push(ann);
+      const charOffsets = spanInVerse(ann, verse.ref, plainText, verseWords);
+      if (charOffsets) {
+        let range = ranges.find(r => r.start === charOffsets.start && r.end === charOffsets.end);
+        if (!range) {
+          range = { 
+            start: charOffsets.start, 
+            end: charOffsets.end, 
+            textAnnotations: [], 
+            symbolAnnotations: [] 
+          };
+          ranges.push(range);
         }
+        range.textAnnotations.push(ann);
       }
     }
 
@@ -637,6 +623,17 @@ export function VerseText({ verse, annotations, moduleId, isSelected, onRemoveAn
               segmentSymbolsMap.set(sym.symbol, sym);
             }
           }
+          // A style's symbol overlay is drawn like a symbol mark on the same words
+          for (const ann of range.textAnnotations) {
+            const overlay = ann.styleId ? highlightStyles.get(ann.styleId)?.symbol : undefined;
+            if (overlay && !segmentSymbolsMap.has(overlay.symbol)) {
+              segmentSymbolsMap.set(overlay.symbol, {
+                id: ann.id, moduleId: ann.moduleId, createdAt: ann.createdAt, updatedAt: ann.updatedAt,
+                type: 'symbol', ref: ann.startRef, position: 'center', placement: overlay.placement,
+                symbol: overlay.symbol, color: overlay.color as HighlightColor | undefined,
+              });
+            }
+          }
         }
       }
 
@@ -667,6 +664,11 @@ export function VerseText({ verse, annotations, moduleId, isSelected, onRemoveAn
 
         for (const ann of segment.annotations) {
           annotationIds.push(ann.id);
+          const style = ann.styleId ? highlightStyles.get(ann.styleId) : undefined;
+          if (style) {
+            combinedStyles.push(...styleDeclarations(style));
+            continue;
+          }
           if (ann.type === 'highlight') {
             combinedStyles.push(`background-color: ${getHighlightColorHex(ann.color)}40`);
           }
@@ -688,7 +690,7 @@ export function VerseText({ verse, annotations, moduleId, isSelected, onRemoveAn
           const symAnn = segment.symbols[0];
           const symbolMarkup = getSymbolMarkup(symAnn.symbol);
           const symbolColor = symAnn.color ? getHighlightColorHex(symAnn.color) : undefined;
-          annotationIds.push(symAnn.id);
+          if (!annotationIds.includes(symAnn.id)) annotationIds.push(symAnn.id);
           const classNames = `symbol-inline annotation-group ${annotationIds.map(id => `annotation-${id}`).join(' ')}`;
           const removeButton = `<button class="annotation-remove" data-annotation-ids="${annotationIds.join(',')}" title="Remove annotation" aria-label="Remove annotation"></button>`;
 
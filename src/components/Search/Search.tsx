@@ -7,6 +7,7 @@
 import { useState, useEffect, useRef } from 'react';
 import { useBibleStore } from '@/stores/bibleStore';
 import { searchAll, type SearchResult, type SearchScope } from '@/lib/search';
+import { suggestReferences } from '@/lib/references';
 import { getBookById } from '@/types';
 import { useModal } from '@/hooks/useModal';
 import { ModalBackdrop } from '@/components/shared';
@@ -15,17 +16,21 @@ import { Z_INDEX } from '@/lib/modalConstants';
 type DisplayScope = SearchScope;
 
 interface SearchProps {
+  /** Query to start with, e.g. from a search deep link */
+  initialQuery?: string;
   onClose: () => void;
   onNavigate: (book: string, chapter: number, verse?: number) => void;
 }
 
-export function Search({ onClose, onNavigate }: SearchProps) {
+export function Search({ initialQuery = '', onClose, onNavigate }: SearchProps) {
   const { currentModuleId, currentBook, currentChapter } = useBibleStore();
-  const [query, setQuery] = useState('');
+  const [query, setQuery] = useState(initialQuery);
   const [results, setResults] = useState<SearchResult[]>([]);
   const [isSearching, setIsSearching] = useState(false);
   const [scope, setScope] = useState<DisplayScope>('all');
   const [selectedIndex, setSelectedIndex] = useState(0);
+  /** Corrected references for a query that misspells a book ("Galations 5") */
+  const [suggestions, setSuggestions] = useState<string[]>([]);
   const inputRef = useRef<HTMLInputElement>(null);
   const resultsRef = useRef<HTMLDivElement>(null);
   
@@ -53,14 +58,22 @@ export function Search({ onClose, onNavigate }: SearchProps) {
           );
           setResults(searchResults);
           setSelectedIndex(0);
+          // A query with numbers that found nothing may be a misspelled reference
+          setSuggestions(
+            searchResults.length === 0 && scope !== 'chapter' && /\d/.test(query)
+              ? await suggestReferences(query)
+              : []
+          );
         } catch (error) {
           console.error('Search error:', error);
           setResults([]);
+          setSuggestions([]);
         } finally {
           setIsSearching(false);
         }
       } else {
         setResults([]);
+        setSuggestions([]);
       }
     }, 300); // Debounce search
 
@@ -254,6 +267,23 @@ export function Search({ onClose, onNavigate }: SearchProps) {
           ) : results.length === 0 && query.trim() ? (
             <div className="p-8 text-center text-scripture-muted text-sm">
               No results found
+              {suggestions.length > 0 && (
+                <div className="mt-2">
+                  Did you mean{' '}
+                  {suggestions.map((suggestion, index) => (
+                    <span key={suggestion}>
+                      {index > 0 && ', '}
+                      <button
+                        onClick={() => setQuery(suggestion)}
+                        className="text-scripture-accent hover:underline font-medium"
+                      >
+                        {suggestion}
+                      </button>
+                    </span>
+                  ))}
+                  ?
+                </div>
+              )}
             </div>
           ) : results.length > 0 ? (
             <div className="p-4 space-y-2">
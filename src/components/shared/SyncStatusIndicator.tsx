@@ -11,6 +11,8 @@ import {
   type SyncStatus,
   getSyncStatusMessage,
   getSyncStatusIcon,
+  pauseSync,
+  resumeSync,
 } from '@/lib/sync';
 import { useSyncStatus, getStatusColorClass } from '@/hooks/useSyncStatus';
 import { usePanelStore } from '@/stores/panelStore';
@@ -231,6 +233,21 @@ export function SyncDetailsPanel({
               Sign in
             </button>
           )}
+          {canManuallySync(status.state) && (
+            <button
+              onClick={() => void (status.state === 'paused' ? resumeSync() : pauseSync())}
+              disabled={isSyncing}
+              className="
+                px-4 py-2 text-sm rounded
+                bg-scripture-elevated hover:bg-scripture-border
+                text-scripture-muted hover:text-scripture-text
+                disabled:opacity-50 disabled:cursor-not-allowed
+                transition-colors
+              "
+            >
+              {status.state === 'paused' ? 'Resume' : 'Pause'}
+            </button>
+          )}
           {canManuallySync(status.state) && (
             <button
               onClick={onSync}
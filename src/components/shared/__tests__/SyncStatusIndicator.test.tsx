@@ -9,6 +9,8 @@ import type { SyncStatus } from '@/lib/sync';
 
 // Mock the sync module
 const mockOnSyncStatusChange = vi.fn();
+const mockPauseSync = vi.fn();
+const mockResumeSync = vi.fn();
 vi.mock('@/lib/sync', () => ({
   onSyncStatusChange: (...args: unknown[]) => mockOnSyncStatusChange(...args),
   getSyncStatusMessage: vi.fn((status: SyncStatus) => {
@@ -18,6 +20,8 @@ vi.mock('@/lib/sync', () => ({
   }),
   getSyncStatusIcon: vi.fn(() => '→'),
   triggerSync: vi.fn(),
+  pauseSync: (...args: unknown[]) => mockPauseSync(...args),
+  resumeSync: (...args: unknown[]) => mockResumeSync(...args),
 }));
 
 // Mock panelStore
@@ -35,6 +39,7 @@ function makeStatus(overrides: Partial<SyncStatus> = {}): SyncStatus {
     pending_changes: 0,
     error: null,
     connected_devices: [],
+    next_retry: null,
     ...overrides,
   };
 }
@@ -92,4 +97,27 @@ describe('SyncStatusIndicator (compact)', () => {
     fireEvent.click(screen.getByRole('button', { name: /sync status/i }));
     expect(screen.getByRole('button', { name: /sign in/i })).toBeTruthy();
   });
+
+  it('offers Resume while paused and Pause otherwise', () => {
+    mockOnSyncStatusChange.mockImplementation((cb: (s: SyncStatus) => void) => {
+      cb(makeStatus({ state: 'paused', pending_changes: 3 }));
+      return () => {};
+    });
+
+    render(<SyncStatusIndicator compact className="p-2 rounded-lg" />);
+    fireEvent.click(screen.getByRole('button', { name: /sync status/i }));
+    fireEvent.click(screen.getByRole('button', { name: /resume/i }));
+    expect(mockResumeSync).toHaveBeenCalledOnce();
+    expect(screen.getByRole('button', { name: /sync now/i })).toBeTruthy();
+
+    cleanup();
+    mockOnSyncStatusChange.mockImplementation((cb: (s: SyncStatus) => void) => {
+      cb(makeStatus({ state: 'synced' }));
+      return () => {};
+    });
+    render(<SyncStatusIndicator compact className="p-2 rounded-lg" />);
+    fireEvent.click(screen.getByRole('button', { name: /sync status/i }));
+    fireEvent.click(screen.getByRole('button', { name: /pause/i }));
+    expect(mockPauseSync).toHaveBeenCalledOnce();
+  });
 });
@@ -8,7 +8,7 @@ import { useCallback } from 'react';
 import { useBibleStore } from '@/stores/bibleStore';
 import { useAnnotationStore } from '@/stores/annotationStore';
 import { useStudyStore } from '@/stores/studyStore';
-import { saveAnnotation, deleteAnnotation, findSisterAnnotations, getAnnotationById, getChapterAnnotations, getChapterHeadings, saveSectionHeading, deleteSectionHeading, getChapterTitle, saveChapterTitle, deleteChapterTitle, getChapterNotes, saveNote, deleteNote, getMarkingPreset } from '@/lib/database';
+import { saveAnnotation, saveAnnotations, applyMarkings, deleteAnnotation, findSisterAnnotations, getAnnotationById, getChapterAnnotations, getChapterHeadings, saveSectionHeading, deleteSectionHeading, getChapterTitle, saveChapterTitle, deleteChapterTitle, getChapterNotes, saveNote, deleteNote, getMarkingPreset } from '@/lib/database';
 import type { Annotation, TextAnnotation, SymbolAnnotation, HighlightColor, SymbolKey, SectionHeading, ChapterTitle, Note, MarkingPreset, Verse } from '@/types';
 import { presetHasDecoration } from '@/types';
 import { autoAddToObservationTracker } from '@/lib/observationAutoAdd';
@@ -355,7 +355,7 @@ export function useAnnotations() {
       }
     }
 
-    await Promise.all(toSave.map(a => saveAnnotation(a)));
+    await saveAnnotations(toSave);
 
     if (createdIds.length > 0) {
       await loadAnnotations();
@@ -391,7 +391,7 @@ export function useAnnotations() {
       for (const sid of await findSisterAnnotations(ann)) sisterIdSet.add(sid);
     }
     const allIds = [...new Set([...ids, ...sisterIdSet])];
-    await Promise.all(allIds.map(id => deleteAnnotation(id)));
+    await applyMarkings(allIds.map(id => ({ op: 'delete' as const, id })));
     await loadAnnotations();
     window.dispatchEvent(new CustomEvent('annotationsUpdated'));
   }, [loadAnnotations]);
@@ -7,6 +7,8 @@
 
 import { useEffect } from 'react';
 import { useBibleStore } from '@/stores/bibleStore';
+import { isTauri } from '@/lib/platform';
+import { lookupClipboardReference } from '@/lib/references';
 
 interface KeyboardShortcutsOptions {
   onToolbarTool?: (toolIndex: number) => void;
@@ -84,6 +86,15 @@ export function useKeyboardShortcuts({
         return;
       }
 
+      // G: go to the reference on the clipboard (desktop; App navigates on the event)
+      if (e.key === 'g' && !e.metaKey && !e.ctrlKey && !e.altKey && !e.shiftKey && isTauri()) {
+        e.preventDefault();
+        lookupClipboardReference().catch((err) => {
+          console.error('[shortcuts] Clipboard lookup failed:', err);
+        });
+        return;
+      }
+
       // Toolbar shortcuts (number keys 1-4)
       // Only trigger when not in an input and not holding modifier keys
       if (
@@ -127,6 +138,7 @@ export function getKeyboardShortcutsHelp(): Array<{ keys: string[]; description:
     { keys: ['←', '→'], description: 'Previous/Next chapter' },
     { keys: ['J', 'K'], description: 'Next/Previous chapter (vim-style)' },
     { keys: ['Cmd/Ctrl', 'F'], description: 'Search (handled by NavigationBar)' },
+    { keys: ['G'], description: 'Go to the reference on the clipboard (desktop)' },
     { keys: ['1'], description: 'Mark (Key Words)' },
     { keys: ['2'], description: 'Observe' },
     { keys: ['3'], description: 'Analyze' },
@@ -42,6 +42,8 @@ export function getStatusColorClass(state: SyncStatus['state']): string {
     case 'synced': return 'text-scripture-success';
     case 'syncing': return 'text-scripture-info';
     case 'offline': return 'text-scripture-warning';
+    case 'retrying': return 'text-scripture-warning';
+    case 'paused': return 'text-scripture-muted';
     case 'error': return 'text-scripture-error';
     case 'auth-expired': return 'text-scripture-warning';
     case 'disabled':
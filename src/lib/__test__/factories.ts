@@ -60,6 +60,7 @@ export function makeSyncStatus(overrides?: Partial<SyncStatus>): SyncStatus {
     pending_changes: 0,
     error: null,
     connected_devices: [],
+    next_retry: null,
     ...overrides,
   };
 }
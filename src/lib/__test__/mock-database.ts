@@ -10,6 +10,8 @@ export const getSyncDiagnostics = vi.fn().mockResolvedValue({});
 // Annotations
 export const getChapterAnnotations = vi.fn().mockResolvedValue([]);
 export const saveAnnotation = vi.fn().mockResolvedValue('ann-id');
+export const saveAnnotations = vi.fn().mockResolvedValue(0);
+export const applyMarkings = vi.fn().mockResolvedValue({ saved: 0, deleted: 0, failed: 0, results: [] });
 export const deleteAnnotation = vi.fn().mockResolvedValue(undefined);
 export const clearBookAnnotations = vi.fn().mockResolvedValue(0);
 
@@ -153,7 +155,7 @@ export const sqlExecute = vi.fn().mockResolvedValue(undefined);
 export function resetMockDatabase(): void {
   const allMocks = [
     initDatabase, closeDatabase, getSyncDiagnostics,
-    getChapterAnnotations, saveAnnotation, deleteAnnotation, clearBookAnnotations,
+    getChapterAnnotations, saveAnnotation, saveAnnotations, applyMarkings, deleteAnnotation, clearBookAnnotations,
     getChapterHeadings, saveSectionHeading, deleteSectionHeading, getAllSectionHeadings,
     getChapterTitle, saveChapterTitle, deleteChapterTitle, getAllChapterTitles,
     getChapterNotes, saveNote, deleteNote, getAllNotes,
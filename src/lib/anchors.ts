@@ -0,0 +1,39 @@
+/**
+ * Text Anchors
+ *
+ * Word-level marks carry an anchor (the marked words, their neighbours and a
+ * fingerprint of the verse), so when a translation is re-imported with
+ * changed text the backend (db/anchors.rs) can move each mark onto its
+ * words again, or flag it as orphaned instead of drawing it on other words.
+ * Re-imports run the pass themselves; run it by hand after swapping a
+ * translation's file any other way.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { Annotation } from '@/types';
+
+/** What a re-anchoring pass did (`ReanchorReport` in Rust). */
+export interface ReanchorReport {
+  moduleId: string;
+  checked: number;
+  /** On a verse that hadn't changed. */
+  intact: number;
+  /** Anchored, or re-anchored, where they stood. */
+  anchored: number;
+  /** Moved onto their words. */
+  repaired: number;
+  /** Ids of marks whose words are gone. */
+  orphaned: string[];
+  /** Marks on whole verses or across verses, which need no anchor. */
+  skipped: number;
+}
+
+/** Check every mark of a mounted translation against its current text. */
+export async function reanchorAnnotations(moduleId: string): Promise<ReanchorReport> {
+  return invoke<ReanchorReport>('reanchor_annotations', { moduleId });
+}
+
+/** Marks flagged as having lost their words, newest first, as stored. */
+export async function getOrphanedAnnotations(moduleId?: string): Promise<Annotation[]> {
+  return invoke<Annotation[]>('get_orphaned_annotations', { moduleId: moduleId ?? null });
+}
@@ -0,0 +1,31 @@
+import { describe, it, expect } from 'vitest'
+import { spansVerse, spanInVerse, type AnnotationSpan } from './annotationSpan'
+import { splitIntoWords } from './keywordMatching'
+
+const ref = (chapter: number, verse: number) => ({ book: 'John', chapter, verse })
+
+describe('spanInVerse', () => {
+  // From "loved" in 3:16 to "world" in 3:17.
+  const span: AnnotationSpan = {
+    startRef: ref(3, 16), endRef: ref(3, 17), startWordIndex: 3, endWordIndex: 9,
+  }
+  const v16 = 'For God so loved the world'
+  const v17 = 'For God did not send his Son into the world to condemn'
+
+  it('covers the first verse from its start and the last up to its end', () => {
+    expect(spanInVerse(span, ref(3, 16), v16, splitIntoWords(v16))).toEqual({ start: 11, end: v16.length })
+    const inLast = spanInVerse(span, ref(3, 17), v17, splitIntoWords(v17))
+    expect(v17.slice(inLast!.start, inLast!.end)).toBe('For God did not send his Son into the world')
+    expect(spanInVerse(span, ref(3, 18), v16, splitIntoWords(v16))).toBeNull()
+  })
+
+  it('takes in whole verses between, across chapters', () => {
+    const across: AnnotationSpan = { startRef: ref(3, 36), endRef: ref(4, 2), startOffset: 4, endOffset: 3 }
+    expect(spansVerse(across, ref(4, 1))).toBe(true)
+    expect(spansVerse(across, ref(3, 35))).toBe(false)
+    expect(spansVerse(across, { ...ref(4, 1), book: 'Acts' })).toBe(false)
+    expect(spanInVerse(across, ref(4, 1), v16, [])).toEqual({ start: 0, end: v16.length })
+    expect(spanInVerse(across, ref(3, 36), v16, [])).toEqual({ start: 4, end: v16.length })
+    expect(spanInVerse(across, ref(4, 2), v16, [])).toEqual({ start: 0, end: 3 })
+  })
+})
@@ -0,0 +1,69 @@
+/**
+ * Annotation Spans
+ *
+ * Where a text annotation falls in the verses it covers. Its start counts
+ * in its first verse (`startWordIndex`, `startOffset`) and its end in its
+ * last (`endWordIndex`, `endOffset`), so a mark across verses, or chapters,
+ * covers its first verse from its start, every verse between whole, and its
+ * last verse up to its end. The same model as `WordSpan` in
+ * db/annotations.rs.
+ */
+
+import type { TextAnnotation, VerseRef } from '@/types';
+
+export type AnnotationSpan = Pick<
+  TextAnnotation,
+  'startRef' | 'endRef' | 'startWordIndex' | 'endWordIndex' | 'startOffset' | 'endOffset'
+>;
+
+/** A word of a verse's text, by character position. */
+export interface WordPosition {
+  startIndex: number;
+  endIndex: number;
+}
+
+function compareVerses(a: VerseRef, b: VerseRef): number {
+  return a.chapter - b.chapter || a.verse - b.verse;
+}
+
+/** Whether `span` covers verse `ref`, in its chapter or across chapters. */
+export function spansVerse(span: Pick<AnnotationSpan, 'startRef' | 'endRef'>, ref: VerseRef): boolean {
+  return (
+    span.startRef.book === ref.book &&
+    compareVerses(span.startRef, ref) <= 0 &&
+    compareVerses(ref, span.endRef) <= 0
+  );
+}
+
+/**
+ * The characters of verse `ref` that `span` covers, given the verse's `text`
+ * and its `words`. Word indices come first, then character offsets. A mark
+ * within one verse with neither isn't placed (null); one across verses
+ * without them covers its first and last verses whole.
+ */
+export function spanInVerse(
+  span: AnnotationSpan,
+  ref: VerseRef,
+  text: string,
+  words: WordPosition[]
+): { start: number; end: number } | null {
+  if (!text || !spansVerse(span, ref)) return null;
+  const clamp = (at: number) => Math.max(0, Math.min(at, text.length));
+  const first = compareVerses(span.startRef, ref) === 0;
+  const last = compareVerses(span.endRef, ref) === 0;
+  const startWord = span.startWordIndex !== undefined ? words[span.startWordIndex] : undefined;
+  const endWord = span.endWordIndex !== undefined ? words[span.endWordIndex] : undefined;
+
+  if (first && last) {
+    if (startWord && endWord) return { start: startWord.startIndex, end: endWord.endIndex };
+    if (span.startOffset === undefined || span.endOffset === undefined) return null;
+    const start = clamp(span.startOffset);
+    return { start, end: Math.max(start, clamp(span.endOffset)) };
+  }
+
+  let start = 0;
+  let end = text.length;
+  if (first) start = startWord?.startIndex ?? clamp(span.startOffset ?? 0);
+  if (last) end = endWord?.endIndex ?? clamp(span.endOffset ?? text.length);
+  return { start, end: Math.max(start, end) };
+}
@@ -0,0 +1,11 @@
+import { describe, it, expect } from 'vitest'
+import { attachmentIds } from './attachments'
+
+describe('attachmentIds', () => {
+  it('finds each linked attachment once and ignores links that are not attachment ids', () => {
+    const map = `${'a'.repeat(64)}.jpg`
+    const scan = `${'0f'.repeat(32)}.pdf`
+    const content = `![map](attachment:${map}) see [scan](attachment:${scan}), again attachment:${map}. attachment:nope.png`
+    expect(attachmentIds(content)).toEqual([map, scan])
+  })
+})
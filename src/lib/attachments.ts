@@ -0,0 +1,54 @@
+/**
+ * Note Attachments
+ *
+ * Images and PDFs attached to notes, stored once each by content hash under
+ * the sync container's `Documents/attachments` (db/attachments.rs). A note
+ * links a file by writing `attachment:<id>` in its markdown, so the link
+ * syncs with the note's text; files no note links are deleted by
+ * `collectAttachmentGarbage` once a day old.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface Attachment {
+  /** `<sha256>.<ext>`. */
+  id: string;
+  mime: string;
+  size: number;
+  path: string;
+  /** What a note writes to link it, e.g. `![map](attachment:<id>)`. */
+  link: string;
+}
+
+export interface AttachmentUsage {
+  files: number;
+  bytes: number;
+  /** Files no note links. */
+  orphans: number;
+  orphanBytes: number;
+}
+
+const LINK = /attachment:([0-9a-f]{64}\.(?:png|jpg|gif|webp|heic|pdf))/g;
+
+/** The attachment ids linked in a note's markdown, each once, in order. */
+export function attachmentIds(content: string): string[] {
+  return [...new Set(Array.from(content.matchAll(LINK), (m) => m[1]))];
+}
+
+/** Copy the image or PDF at `path` into the store. Adding the same file again returns the stored one. */
+export async function addAttachment(path: string): Promise<Attachment> {
+  return invoke<Attachment>('add_attachment', { path });
+}
+
+/** Rejected with `NotFound` while a file added on another device hasn't synced yet. */
+export async function getAttachmentPath(id: string): Promise<string> {
+  return invoke<string>('get_attachment_path', { id });
+}
+
+export async function getAttachmentUsage(): Promise<AttachmentUsage> {
+  return invoke<AttachmentUsage>('get_attachment_usage');
+}
+
+export async function collectAttachmentGarbage(): Promise<{ removed: string[]; freedBytes: number }> {
+  return invoke('collect_attachment_garbage');
+}
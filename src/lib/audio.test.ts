@@ -0,0 +1,26 @@
+import { describe, it, expect } from 'vitest';
+import { verseAt, type ChapterAudio } from './audio';
+
+const john1: ChapterAudio = {
+  book: 'John',
+  chapter: 1,
+  url: 'https://example.org/JHN_01.mp3',
+  duration: 30,
+  verses: [
+    { verse: 1, start: 0, end: 6.5 },
+    { verse: 2, start: 6.5, end: 12 },
+    { verse: 4, start: 12, end: 30 },
+  ],
+};
+
+describe('verseAt', () => {
+  it('finds the verse being read', () => {
+    expect(verseAt(john1, 0)).toBe(1);
+    expect(verseAt(john1, 6.5)).toBe(2);
+    expect(verseAt(john1, 29)).toBe(4);
+  });
+
+  it('is null without verse timing', () => {
+    expect(verseAt({ ...john1, verses: [] }, 3)).toBeNull();
+  });
+});
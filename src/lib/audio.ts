@@ -0,0 +1,92 @@
+/**
+ * Audio Bibles
+ *
+ * Chapter-by-chapter recordings of a translation, imported from a manifest
+ * (see `content/audio.rs` for its format) and kept beside the translation
+ * content. Where the listener stopped is saved natively in the synced
+ * `audio_positions` table, so listening resumes on the account's other
+ * devices along with the reading position.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** An installed audio Bible (`AudioBibleInfo` in Rust). */
+export interface AudioBible {
+  id: string;
+  name: string;
+  /** Translation whose text the recording reads, if the manifest says. */
+  moduleId: string | null;
+  chapters: number;
+  /** OSIS ids of the books it has audio for, in canonical order. */
+  books: string[];
+  path: string;
+}
+
+/** When a verse is read (`VerseTiming` in Rust), in seconds. */
+export interface VerseTiming {
+  verse: number;
+  start: number;
+  /** Null for the last verse when the manifest gives no duration. */
+  end: number | null;
+}
+
+/** One chapter's audio (`ChapterAudio` in Rust). */
+export interface ChapterAudio {
+  book: string;
+  chapter: number;
+  /** `http(s)` URL, or the path of a local file. */
+  url: string;
+  duration: number | null;
+  /** Empty when the manifest has no verse timing. */
+  verses: VerseTiming[];
+}
+
+/** Where the listener stopped in a recording (`PlaybackPosition` in Rust). */
+export interface PlaybackPosition {
+  audioId: string;
+  book: string;
+  chapter: number;
+  /** Filled in from the verse timing when saved without one. */
+  verse?: number | null;
+  /** Seconds into the chapter's audio. */
+  seconds: number;
+  updatedAt?: string | null;
+}
+
+/** Import the manifest at `path`, replacing an audio Bible of the same id. */
+export async function importAudioManifest(path: string): Promise<AudioBible> {
+  return invoke<AudioBible>('import_audio_manifest', { path });
+}
+
+export async function listAudioBibles(): Promise<AudioBible[]> {
+  return invoke<AudioBible[]>('list_audio_bibles');
+}
+
+/** Remove an audio Bible; false if it wasn't installed. */
+export async function removeAudioBible(id: string): Promise<boolean> {
+  return invoke<boolean>('remove_audio_bible', { id });
+}
+
+/** The audio of a chapter, or null if the recording doesn't have it. */
+export async function getChapterAudio(audioId: string, book: string, chapter: number): Promise<ChapterAudio | null> {
+  return invoke<ChapterAudio | null>('get_chapter_audio', { audioId, book, chapter });
+}
+
+export async function savePlaybackPosition(position: PlaybackPosition): Promise<PlaybackPosition> {
+  return invoke<PlaybackPosition>('save_playback_position', { position });
+}
+
+/** Where the listener stopped in `audioId`, on any device. */
+export async function getPlaybackPosition(audioId: string): Promise<PlaybackPosition | null> {
+  return invoke<PlaybackPosition | null>('get_playback_position', { audioId });
+}
+
+/** The verse being read `seconds` into `audio`, for following along. */
+export function verseAt(audio: ChapterAudio, seconds: number): number | null {
+  let verse: number | null = null;
+  for (const timing of audio.verses) {
+    if (timing.start > seconds) break;
+    verse = timing.verse;
+  }
+  return verse;
+}
@@ -9,6 +9,7 @@
 import { getPreferences as getDbPreferences, updatePreferences as updateDbPreferences, type AutoBackupConfig, sqlSelect, sqlExecute } from './database';
 import { type BackupData, assembleBackupData } from './backup';
 import { isTauri, isCapacitor } from './platform';
+import { isDemoDatabase } from './sqlite-db';
 
 /** Backup file metadata */
 export interface BackupFileMetadata {
@@ -284,6 +285,11 @@ export async function performBackup(): Promise<BackupFileMetadata | null> {
     if (!config.enabled) {
       return null;
     }
+    // Rotation would delete the user's real backups to make room for demo data.
+    if (await isDemoDatabase()) {
+      console.log('[AutoBackup] Skipped for the demo database');
+      return null;
+    }
 
     console.log('[AutoBackup] Creating backup...');
     const backup = await createBackupData();
@@ -328,6 +328,133 @@ describe('restoreBackup', () => {
     expect(importedData.keywordExclusions).toEqual([expect.objectContaining({ id: 'ke-1' })])
   })
 
+  it('restores the symbol library, dropping entries without a meaning', async () => {
+    const backup = makeFullBackup()
+    backup.data.symbolLibrary = [
+      { id: 'sym-1', shape: 'triangle', color: 'purple', meaning: 'God' },
+      { id: 'sym-2', shape: 'cloud', meaning: ' ' },
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.symbolLibrary).toEqual([expect.objectContaining({ id: 'sym-1' })])
+  })
+
+  it('restores tags and their links, dropping links to unknown item types', async () => {
+    const backup = makeFullBackup()
+    backup.data.tags = [
+      { id: 'tag-1', name: 'Attributes of God' },
+      { id: 'tag-2', name: 'Faithfulness', parentId: 'tag-1', color: 'blue' },
+    ]
+    backup.data.tagLinks = [
+      { id: 'tag-2|note|n-1', tagId: 'tag-2', itemType: 'note', itemId: 'n-1' },
+      { id: 'tag-2|study|s-1', tagId: 'tag-2', itemType: 'study' as never, itemId: 's-1' },
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.tags).toHaveLength(2)
+    expect(importedData.tagLinks).toEqual([expect.objectContaining({ id: 'tag-2|note|n-1' })])
+  })
+
+  it('restores bookmarks and their folders, dropping bookmarks without a verse', async () => {
+    const backup = makeFullBackup()
+    backup.data.bookmarkFolders = [{ id: 'bf-1', name: 'Studying now' }]
+    backup.data.bookmarks = [
+      { id: 'bm-1', ref: { book: 'Rom', chapter: 8, verse: 28 }, folderId: 'bf-1' },
+      { id: 'bm-2', ref: { book: 'Rom', chapter: 0, verse: 1 } },
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.bookmarkFolders).toEqual([expect.objectContaining({ id: 'bf-1' })])
+    expect(importedData.bookmarks).toEqual([expect.objectContaining({ id: 'bm-1' })])
+  })
+
+  it('restores verse collections, dropping ones with broken entries', async () => {
+    const backup = makeFullBackup()
+    backup.data.verseCollections = [
+      { id: 'vc-1', name: 'Promises', entries: [{ id: 'vce-1', ref: { book: 'Phil', chapter: 4, verse: 13 } }] },
+      { id: 'vc-2', name: 'Broken', entries: [{ id: 'vce-2', ref: { book: '', chapter: 1, verse: 1 } }] },
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.verseCollections).toEqual([expect.objectContaining({ id: 'vc-1' })])
+  })
+
+  it('restores workspaces, dropping ones with malformed member lists', async () => {
+    const backup = makeFullBackup()
+    backup.data.workspaces = [
+      { id: 'ws-1', name: 'Grace in Romans', tagIds: ['tag-1'], collectionIds: [], noteIds: ['n-1'], keyWordIds: [] },
+      { id: 'ws-2', name: 'Broken', tagIds: 'tag-1' as never, collectionIds: [], noteIds: [], keyWordIds: [] },
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.workspaces).toEqual([expect.objectContaining({ id: 'ws-1' })])
+  })
+
+  it('restores highlight styles, dropping ones with nothing to draw', async () => {
+    const backup = makeFullBackup()
+    backup.data.highlightStyles = [
+      { id: 'hs-1', name: 'Promise', fill: 'yellow', border: { color: 'red', shape: 'rounded', style: 'solid' } },
+      { id: 'hs-2', name: 'Star only', symbol: { symbol: 'star' } },
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.highlightStyles).toEqual([expect.objectContaining({ id: 'hs-1' })])
+  })
+
+  it('restores marking templates, dropping ones with no word', async () => {
+    const backup = makeFullBackup()
+    backup.data.markingTemplates = [
+      { id: 'mt-1', word: 'covenant', variants: [], caseSensitive: false, type: 'highlight', color: 'red' },
+      { id: 'mt-2', word: ' ', variants: [], caseSensitive: false, type: 'highlight', color: 'red' },
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.markingTemplates).toEqual([expect.objectContaining({ id: 'mt-1' })])
+  })
+
+  it('restores margin notes, dropping ones with a backwards passage', async () => {
+    const backup = makeFullBackup()
+    backup.data.marginNotes = [
+      { id: 'mn-1', book: 'John', chapter: 3, content: 'The new birth', placement: { side: 'top', order: 0 } },
+      {
+        id: 'mn-2', book: 'John', chapter: 3, content: 'Nicodemus',
+        pericope: { verse: 21, endChapter: 3, endVerse: 1 }, placement: { side: 'right', order: 0 },
+      },
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.marginNotes).toEqual([expect.objectContaining({ id: 'mn-1' })])
+  })
+
+  it('restores resource links, dropping ones on no item', async () => {
+    const backup = makeFullBackup()
+    backup.data.resourceLinks = [
+      { id: 'rl-1', itemType: 'note', itemId: 'n1', url: 'https://example.org/genesis-15', kind: 'web' },
+      { id: 'rl-2', itemId: '', url: 'logosres:esv' } as never,
+    ]
+
+    await restoreBackup(backup)
+
+    const importedData = mockImportAllData.mock.calls[0][0]
+    expect(importedData.resourceLinks).toEqual([expect.objectContaining({ id: 'rl-1' })])
+  })
+
   it('rejects restore when all section headings are invalid', async () => {
     const backup = makeFullBackup()
     backup.data.sectionHeadings = [{ id: 'sh-1', title: '' } as never]
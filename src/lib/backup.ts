@@ -24,6 +24,15 @@ import type { InterpretationEntry } from '@/types';
 import type { ApplicationEntry } from '@/types';
 import type { EntityNote } from '@/types';
 import type { KeywordExclusion } from '@/types';
+import type { LibrarySymbol } from './symbolLibrary';
+import type { Tag, TagLink } from './tags';
+import type { Bookmark, BookmarkFolder } from './bookmarks';
+import type { VerseCollection } from './collections';
+import type { Workspace } from './workspaces';
+import type { HighlightStyle } from './highlightStyles';
+import type { MarkingTemplate } from './markingTemplates';
+import type { MarginNote } from './marginNotes';
+import type { ResourceLink } from './resourceLinks';
 import {
   validateAnnotation,
   validateSectionHeading,
@@ -41,6 +50,17 @@ import {
   validateTimeExpression,
   validateEntityNote,
   validateKeywordExclusion,
+  validateLibrarySymbol,
+  validateTag,
+  validateTagLink,
+  validateBookmarkFolder,
+  validateBookmark,
+  validateVerseCollection,
+  validateWorkspace,
+  validateHighlightStyle,
+  validateMarkingTemplate,
+  validateMarginNote,
+  validateResourceLink,
   validateArray,
   ValidationError,
 } from './validation';
@@ -67,6 +87,17 @@ export interface BackupData {
     applications: ApplicationEntry[];
     entityNotes?: EntityNote[];
     keywordExclusions?: KeywordExclusion[];
+    symbolLibrary?: LibrarySymbol[];
+    tags?: Tag[];
+    tagLinks?: TagLink[];
+    bookmarkFolders?: BookmarkFolder[];
+    bookmarks?: Bookmark[];
+    verseCollections?: VerseCollection[];
+    workspaces?: Workspace[];
+    highlightStyles?: HighlightStyle[];
+    markingTemplates?: MarkingTemplate[];
+    marginNotes?: MarginNote[];
+    resourceLinks?: ResourceLink[];
     cachedChapters?: Array<{
       id: string;
       moduleId: string;
@@ -162,6 +193,17 @@ export async function assembleBackupData(): Promise<BackupData> {
       applications: allData.applications,
       entityNotes: allData.entityNotes,
       keywordExclusions: allData.keywordExclusions,
+      symbolLibrary: allData.symbolLibrary ?? [],
+      tags: allData.tags ?? [],
+      tagLinks: allData.tagLinks ?? [],
+      bookmarkFolders: allData.bookmarkFolders ?? [],
+      bookmarks: allData.bookmarks ?? [],
+      verseCollections: allData.verseCollections ?? [],
+      workspaces: allData.workspaces ?? [],
+      highlightStyles: allData.highlightStyles ?? [],
+      markingTemplates: allData.markingTemplates ?? [],
+      marginNotes: allData.marginNotes ?? [],
+      resourceLinks: allData.resourceLinks ?? [],
     },
   };
 }
@@ -631,6 +673,79 @@ export async function restoreBackup(backup: BackupData): Promise<void> {
       validatedKeywordExclusions = valid;
     }
 
+    // Validate symbol library
+    let validatedSymbolLibrary: LibrarySymbol[] = [];
+    if (backup.data.symbolLibrary && backup.data.symbolLibrary.length > 0) {
+      const { valid } = validateArray(backup.data.symbolLibrary, validateLibrarySymbol, 'library symbol');
+      validatedSymbolLibrary = valid;
+    }
+
+    // Validate tags and their links
+    let validatedTags: Tag[] = [];
+    if (backup.data.tags && backup.data.tags.length > 0) {
+      const { valid } = validateArray(backup.data.tags, validateTag, 'tag');
+      validatedTags = valid;
+    }
+    let validatedTagLinks: TagLink[] = [];
+    if (backup.data.tagLinks && backup.data.tagLinks.length > 0) {
+      const { valid } = validateArray(backup.data.tagLinks, validateTagLink, 'tag link');
+      validatedTagLinks = valid;
+    }
+
+    // Validate bookmarks and their folders
+    let validatedBookmarkFolders: BookmarkFolder[] = [];
+    if (backup.data.bookmarkFolders && backup.data.bookmarkFolders.length > 0) {
+      const { valid } = validateArray(backup.data.bookmarkFolders, validateBookmarkFolder, 'bookmark folder');
+      validatedBookmarkFolders = valid;
+    }
+    let validatedBookmarks: Bookmark[] = [];
+    if (backup.data.bookmarks && backup.data.bookmarks.length > 0) {
+      const { valid } = validateArray(backup.data.bookmarks, validateBookmark, 'bookmark');
+      validatedBookmarks = valid;
+    }
+
+    // Validate verse collections
+    let validatedVerseCollections: VerseCollection[] = [];
+    if (backup.data.verseCollections && backup.data.verseCollections.length > 0) {
+      const { valid } = validateArray(backup.data.verseCollections, validateVerseCollection, 'verse collection');
+      validatedVerseCollections = valid;
+    }
+
+    // Validate workspaces
+    let validatedWorkspaces: Workspace[] = [];
+    if (backup.data.workspaces && backup.data.workspaces.length > 0) {
+      const { valid } = validateArray(backup.data.workspaces, validateWorkspace, 'workspace');
+      validatedWorkspaces = valid;
+    }
+
+    // Validate highlight styles
+    let validatedHighlightStyles: HighlightStyle[] = [];
+    if (backup.data.highlightStyles && backup.data.highlightStyles.length > 0) {
+      const { valid } = validateArray(backup.data.highlightStyles, validateHighlightStyle, 'highlight style');
+      validatedHighlightStyles = valid;
+    }
+
+    // Validate marking templates
+    let validatedMarkingTemplates: MarkingTemplate[] = [];
+    if (backup.data.markingTemplates && backup.data.markingTemplates.length > 0) {
+      const { valid } = validateArray(backup.data.markingTemplates, validateMarkingTemplate, 'marking template');
+      validatedMarkingTemplates = valid;
+    }
+
+    // Validate margin notes
+    let validatedMarginNotes: MarginNote[] = [];
+    if (backup.data.marginNotes && backup.data.marginNotes.length > 0) {
+      const { valid } = validateArray(backup.data.marginNotes, validateMarginNote, 'margin note');
+      validatedMarginNotes = valid;
+    }
+
+    // Validate resource links
+    let validatedResourceLinks: ResourceLink[] = [];
+    if (backup.data.resourceLinks && backup.data.resourceLinks.length > 0) {
+      const { valid } = validateArray(backup.data.resourceLinks, validateResourceLink, 'resource link');
+      validatedResourceLinks = valid;
+    }
+
     // --- Create safety backup before clearing database ---
     try {
       const { performBackup } = await import('./autoBackup');
@@ -661,6 +776,17 @@ export async function restoreBackup(backup: BackupData): Promise<void> {
       applications: validatedApplications,
       entityNotes: validatedEntityNotes,
       keywordExclusions: validatedKeywordExclusions,
+      symbolLibrary: validatedSymbolLibrary,
+      tags: validatedTags,
+      tagLinks: validatedTagLinks,
+      bookmarkFolders: validatedBookmarkFolders,
+      bookmarks: validatedBookmarks,
+      verseCollections: validatedVerseCollections,
+      workspaces: validatedWorkspaces,
+      highlightStyles: validatedHighlightStyles,
+      markingTemplates: validatedMarkingTemplates,
+      marginNotes: validatedMarginNotes,
+      resourceLinks: validatedResourceLinks,
       preferences: backup.data.preferences || null,
     });
 
@@ -0,0 +1,80 @@
+/**
+ * Bookmarks
+ *
+ * Passages kept for coming back to ("where I'm studying"), optionally filed
+ * in folders. Kept natively in the synced `bookmarks` and `bookmark_folders`
+ * tables (db/bookmarks.rs), apart from highlights: a bookmark marks no words
+ * and has no color.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { VerseRef } from '@/types';
+
+/** A bookmark (`Bookmark` in Rust). */
+export interface Bookmark {
+  id: string;
+  ref: VerseRef;
+  /** The last verse of a bookmarked passage, in the same book. */
+  endRef?: VerseRef;
+  /** The translation it was made in. */
+  moduleId?: string;
+  label?: string;
+  /** Absent at the top level. */
+  folderId?: string;
+  /** ISO timestamps, set by the backend. */
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+export interface BookmarkFolder {
+  id: string;
+  name: string;
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+export interface BookmarkFolderEntry extends BookmarkFolder {
+  /** How many bookmarks it holds. */
+  bookmarks: number;
+}
+
+/** Bookmarks newest first: those in `folderId`, or every one. */
+export async function getBookmarks(folderId?: string): Promise<Bookmark[]> {
+  return invoke<Bookmark[]>('get_bookmarks', { folderId: folderId ?? null });
+}
+
+/** Rejected if it isn't on a verse, or its folder doesn't exist. */
+export async function createBookmark(bookmark: Omit<Bookmark, 'id'> & { id?: string }): Promise<Bookmark> {
+  return invoke<Bookmark>('create_bookmark', {
+    bookmark: { ...bookmark, id: bookmark.id ?? crypto.randomUUID() },
+  });
+}
+
+export async function updateBookmark(bookmark: Bookmark): Promise<Bookmark> {
+  return invoke<Bookmark>('update_bookmark', { bookmark });
+}
+
+export async function deleteBookmark(id: string): Promise<boolean> {
+  return invoke<boolean>('delete_bookmark', { id });
+}
+
+/** Folders by name, with how many bookmarks each holds. */
+export async function getBookmarkFolders(): Promise<BookmarkFolderEntry[]> {
+  return invoke<BookmarkFolderEntry[]>('get_bookmark_folders');
+}
+
+/** Rejected if another folder has the name. */
+export async function createBookmarkFolder(name: string): Promise<BookmarkFolder> {
+  return invoke<BookmarkFolder>('create_bookmark_folder', {
+    folder: { id: crypto.randomUUID(), name },
+  });
+}
+
+export async function renameBookmarkFolder(folder: BookmarkFolder, name: string): Promise<BookmarkFolder> {
+  return invoke<BookmarkFolder>('update_bookmark_folder', { folder: { ...folder, name } });
+}
+
+/** Delete a folder; its bookmarks move to the top level unless `withBookmarks`. */
+export async function deleteBookmarkFolder(id: string, withBookmarks = false): Promise<boolean> {
+  return invoke<boolean>('delete_bookmark_folder', { id, withBookmarks });
+}
@@ -0,0 +1,10 @@
+import { describe, it, expect } from 'vitest'
+import { startOfToday } from './bulkEdit'
+
+describe('startOfToday', () => {
+  it('is local midnight of the given day', () => {
+    const now = new Date(2025, 5, 1, 21, 30)
+    const start = new Date(startOfToday(now))
+    expect([start.getFullYear(), start.getMonth(), start.getDate(), start.getHours(), start.getMinutes()]).toEqual([2025, 5, 1, 0, 0])
+  })
+})
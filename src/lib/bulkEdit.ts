@@ -0,0 +1,58 @@
+/**
+ * Bulk Edits
+ *
+ * One change made to every mark or note a filter picks: recolor the marks
+ * tagged X, move the notes on one tag to another, delete the marks made in a
+ * chapter today. Run natively in one transaction (db/bulk_edit.rs) and
+ * journaled as a single undo step, so `sqliteUndoLast` takes the whole edit
+ * back; `dryRun` reports what would change without changing it.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface BulkFilter {
+  moduleId?: string;
+  /** OSIS book. */
+  book?: string;
+  /** A chapter of `book`; marks and notes spanning it match. */
+  chapter?: number;
+  /** Tagged with this tag (id or path) or one under it. */
+  tag?: string;
+  /** ISO instant; see `startOfToday` for marks made today. */
+  createdSince?: string;
+  color?: string;
+  itemType?: 'annotation' | 'note';
+}
+
+export type BulkEdit =
+  | { op: 'recolor'; color: string }
+  | { op: 'retag'; from: string; to: string }
+  | { op: 'delete' };
+
+export interface BulkEditSummary {
+  matched: number;
+  changed: number;
+  ids: string[];
+  /** The undo step it made; absent for a dry run or when nothing changed. */
+  undoAt?: string;
+  dryRun: boolean;
+}
+
+/**
+ * Apply `edit` to what `filter` picks. Recolor and delete are of marks only
+ * and are rejected without a filter.
+ */
+export async function bulkEditAnnotations(
+  edit: BulkEdit,
+  filter: BulkFilter,
+  options: { dryRun?: boolean } = {}
+): Promise<BulkEditSummary> {
+  return invoke<BulkEditSummary>('bulk_edit_annotations', {
+    request: { ...edit, filter, dryRun: options.dryRun ?? false },
+  });
+}
+
+/** Local midnight of `now`'s day as an ISO instant, for `createdSince`. */
+export function startOfToday(now: Date = new Date()): string {
+  return new Date(now.getFullYear(), now.getMonth(), now.getDate()).toISOString();
+}
@@ -0,0 +1,55 @@
+/**
+ * Canon Structure
+ *
+ * Book order, chapter counts and verses per chapter as a versification
+ * numbers them, from the backend (content/canon.rs), so navigation,
+ * reading-plan math and validation share one source. Fetched once per
+ * versification and cached for the session.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** One book of the canon (`CanonBook` in Rust). */
+export interface CanonBook {
+  /** OSIS id, as `BookInfo.id`. */
+  id: string;
+  name: string;
+  testament: 'OT' | 'NT';
+  /** Position in the canon, from 1. */
+  order: number;
+  chapters: number;
+  /** Verses in each chapter; `verses[0]` is chapter 1. */
+  verses: number[];
+}
+
+export interface CanonStructure {
+  /** `KJV` or `Leningrad`; unknown schemes come back as KJV. */
+  versification: string;
+  books: CanonBook[];
+  totalVerses: number;
+}
+
+const cache = new Map<string, Promise<CanonStructure>>();
+
+/** The canon as `versification` (a translation's, default KJV) numbers it. */
+export function getCanonStructure(versification = 'KJV'): Promise<CanonStructure> {
+  const key = versification.toLowerCase();
+  let structure = cache.get(key);
+  if (!structure) {
+    structure = invoke<CanonStructure>('get_canon_structure', { versification });
+    // A failed fetch is tried again next time rather than cached.
+    structure.catch(() => cache.delete(key));
+    cache.set(key, structure);
+  }
+  return structure;
+}
+
+/** Verses in `book` `chapter`, or 0 if the book has no such chapter. */
+export async function getVerseCount(
+  book: string,
+  chapter: number,
+  versification?: string,
+): Promise<number> {
+  const { books } = await getCanonStructure(versification);
+  return books.find((b) => b.id === book)?.verses[chapter - 1] ?? 0;
+}
@@ -0,0 +1,109 @@
+/**
+ * Verse Collections
+ *
+ * Named, ordered lists of passages ("Promises", "Memory Verses 2025"), each
+ * entry with an optional note. Unlike observation lists they aren't tied to a
+ * key word. Kept natively in the synced `verse_collections` table
+ * (db/collections.rs), one row per collection with its entries in the order
+ * the user put them.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { VerseRef } from '@/types';
+import { useAnnotationStore } from '@/stores/annotationStore';
+
+export interface CollectionEntry {
+  id: string;
+  ref: VerseRef;
+  /** The last verse of a passage, in the same book. */
+  endRef?: VerseRef;
+  note?: string;
+  addedAt?: string;
+}
+
+/** A collection (`VerseCollection` in Rust). */
+export interface VerseCollection {
+  id: string;
+  name: string;
+  description?: string;
+  entries: CollectionEntry[];
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+export type NewCollectionEntry = Pick<CollectionEntry, 'ref' | 'endRef' | 'note'>;
+
+export interface EntriesAdded {
+  collection: VerseCollection;
+  added: number;
+  /** Passages left out because the collection already had them. */
+  alreadyThere: number;
+}
+
+export type CollectionExportFormat = 'markdown' | 'text' | 'csv';
+
+export async function getVerseCollections(): Promise<VerseCollection[]> {
+  return invoke<VerseCollection[]>('get_verse_collections');
+}
+
+export async function getVerseCollection(id: string): Promise<VerseCollection> {
+  return invoke<VerseCollection>('get_verse_collection', { id });
+}
+
+/** Rejected if another collection has the name. */
+export async function createVerseCollection(name: string, description?: string): Promise<VerseCollection> {
+  return invoke<VerseCollection>('create_verse_collection', { name, description: description ?? null });
+}
+
+export async function updateVerseCollection(id: string, name: string, description?: string): Promise<VerseCollection> {
+  return invoke<VerseCollection>('update_verse_collection', { id, name, description: description ?? null });
+}
+
+export async function deleteVerseCollection(id: string): Promise<boolean> {
+  return invoke<boolean>('delete_verse_collection', { id });
+}
+
+/** Add passages at `position` (the end by default), skipping ones already there. */
+export async function addToVerseCollection(
+  listId: string,
+  entries: NewCollectionEntry[],
+  position?: number
+): Promise<EntriesAdded> {
+  return invoke<EntriesAdded>('add_to_verse_collection', { listId, entries, position: position ?? null });
+}
+
+/**
+ * Add each verse selected in the reader to a collection. Resolves to null
+ * when nothing is selected.
+ */
+export async function addCurrentSelectionToList(listId: string): Promise<EntriesAdded | null> {
+  const selection = useAnnotationStore.getState().selection;
+  if (!selection) return null;
+  const { book, chapter, startVerse, endVerse } = selection;
+  return invoke<EntriesAdded>('add_current_selection_to_list', {
+    listId,
+    selection: { book, chapter, startVerse, endVerse },
+  });
+}
+
+export async function updateVerseCollectionEntry(listId: string, entryId: string, note?: string): Promise<VerseCollection> {
+  return invoke<VerseCollection>('update_verse_collection_entry', { listId, entryId, note: note ?? null });
+}
+
+export async function removeFromVerseCollection(listId: string, entryIds: string[]): Promise<VerseCollection> {
+  return invoke<VerseCollection>('remove_from_verse_collection', { listId, entryIds });
+}
+
+/** Put the entries in the order of `entryIds`, which must name each once. */
+export async function reorderVerseCollection(listId: string, entryIds: string[]): Promise<VerseCollection> {
+  return invoke<VerseCollection>('reorder_verse_collection', { listId, entryIds });
+}
+
+/** The collection written out, with verse text from `moduleId` if it is mounted. */
+export async function exportVerseCollection(
+  listId: string,
+  format: CollectionExportFormat = 'markdown',
+  moduleId?: string
+): Promise<string> {
+  return invoke<string>('export_verse_collection', { listId, format, moduleId: moduleId ?? null });
+}
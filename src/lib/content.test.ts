@@ -0,0 +1,23 @@
+import { describe, it, expect } from 'vitest';
+import { isRedLetter, type ContentChapter } from './content';
+
+const chapter: ContentChapter = {
+  verses: { 1: 'Jesus said, “Follow me.” And they did.' },
+  redLetter: { 1: [[12, 24]] },
+  footnotes: {},
+  headings: {},
+  paragraphs: {},
+};
+
+describe('isRedLetter', () => {
+  it('finds ranges inside the words of Christ', () => {
+    expect(isRedLetter(chapter, 1, 12, 24)).toBe(true);
+    expect(isRedLetter(chapter, 1, 13)).toBe(true);
+  });
+
+  it('rejects ranges running outside them', () => {
+    expect(isRedLetter(chapter, 1, 0, 13)).toBe(false);
+    expect(isRedLetter(chapter, 1, 25)).toBe(false);
+    expect(isRedLetter(chapter, 2, 0)).toBe(false);
+  });
+});
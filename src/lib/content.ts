@@ -0,0 +1,448 @@
+/**
+ * Translation Content
+ *
+ * Bible text for installed translations lives in read-only content databases
+ * (one per translation, in `<app data>/content`), separate from the user
+ * database so sync never moves it. Mounting one attaches it to the native
+ * database connections.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { VerseRef } from '@/types';
+
+/** A content database attached to the native connections. */
+export interface MountedContent {
+  moduleId: string;
+  /** Schema name it is attached under, e.g. `content_kjv`. */
+  schema: string;
+  path: string;
+  verses: number;
+}
+
+/**
+ * Attach a translation's content file. Without `path` the default
+ * `<app data>/content/<module>.db` is used.
+ */
+export async function mountContent(moduleId: string, path?: string): Promise<MountedContent> {
+  return invoke<MountedContent>('mount_content', { moduleId, path: path ?? null });
+}
+
+/** Detach a translation's content file; false if it wasn't mounted. */
+export async function unmountContent(moduleId: string): Promise<boolean> {
+  return invoke<boolean>('unmount_content', { moduleId });
+}
+
+export async function listMountedContent(): Promise<MountedContent[]> {
+  return invoke<MountedContent[]>('list_mounted_content');
+}
+
+/** One chapter of a mounted translation (`ContentChapter` in Rust). */
+export interface ContentChapter {
+  /** Verse text keyed by verse number. */
+  verses: Record<number, string>;
+  /**
+   * Words of Christ as `[start, end)` character offsets into each verse's
+   * text; verses without any are left out.
+   */
+  redLetter: Record<number, [number, number][]>;
+  /** Footnotes of each verse, in order; verses without any are left out. */
+  footnotes: Record<number, ChapterFootnote[]>;
+  /** Headings before each verse, in order. */
+  headings: Record<number, ChapterHeading[]>;
+  /** Paragraphs and poetry lines beginning in each verse, in order. */
+  paragraphs: Record<number, ParagraphBreak[]>;
+}
+
+/** A heading of a content chapter (`ChapterHeading` in Rust). */
+export interface ChapterHeading {
+  /** USFM style: `s1`, `ms`, `d` (a psalm's title), ... */
+  style: string;
+  text: string;
+}
+
+/** Where a paragraph or poetry line begins (`ParagraphBreak` in Rust). */
+export interface ParagraphBreak {
+  /** Character offset in the verse text. */
+  position: number;
+  /** USFM style: `p`, `m`, `q1`, `q2`, `b` (a blank line between stanzas), ... */
+  style: string;
+  poetry: boolean;
+  /** Indentation level: 1 for `q1` or `pi1`, 0 for a flush paragraph. */
+  indent: number;
+}
+
+/** A footnote of a content chapter (`ChapterFootnote` in Rust). */
+export interface ChapterFootnote {
+  caller: string;
+  /** Character offset in the verse text the note is anchored after. */
+  position: number;
+  text: string;
+  kind: 'footnote' | 'translation' | 'variant' | 'study';
+}
+
+export async function getContentChapter(moduleId: string, book: string, chapter: number): Promise<ContentChapter> {
+  return invoke<ContentChapter>('get_content_chapter', { moduleId, book, chapter });
+}
+
+/** A verse of one translation, in its own numbering (`ParallelVerse` in Rust). */
+export interface ParallelVerse {
+  chapter: number;
+  verse: number;
+  text: string;
+}
+
+/** One chapter in several translations (`ParallelChapter` in Rust). */
+export interface ParallelChapter {
+  book: string;
+  chapter: number;
+  /** Translation ids, in column order. */
+  translations: string[];
+  rows: {
+    /**
+     * Verse in the first translation's numbering; 0 for a psalm title only
+     * another translation numbers.
+     */
+    verse: number;
+    /** Each translation's verses for the row; empty where it has none. */
+    cells: ParallelVerse[][];
+  }[];
+}
+
+/**
+ * One chapter in up to four mounted translations, aligned verse by verse on
+ * the first. Translations numbered differently (a Hebrew Bible's Malachi
+ * 3:19-24 for an English Malachi 4) are mapped onto the first's numbering.
+ */
+export async function getParallelChapter(
+  book: string,
+  chapter: number,
+  translationIds: string[],
+): Promise<ParallelChapter> {
+  return invoke<ParallelChapter>('get_parallel_chapter', { book, chapter, translationIds });
+}
+
+/**
+ * Whether characters `start` to `end` of a verse lie within the words of
+ * Christ, for scoping searches and markings to them.
+ */
+export function isRedLetter(chapter: ContentChapter, verse: number, start: number, end = start + 1): boolean {
+  return (chapter.redLetter[verse] ?? []).some(([from, to]) => from <= start && end <= to);
+}
+
+/** A footnote matching a search (`FootnoteHit` in Rust). */
+export interface FootnoteHit {
+  moduleId: string;
+  book: string;
+  chapter: number;
+  verse: number;
+  /** The whole footnote. */
+  text: string;
+  /** Excerpt around the best match. */
+  snippet: string;
+  /** `[start, end)` of each matched term within `snippet`. */
+  highlights: [number, number][];
+  /** bm25 score; lower is a better match. */
+  rank: number;
+}
+
+/** Ranked search over the footnotes of mounted translations, or of one. */
+export async function searchFootnotes(query: string, moduleId?: string, limit?: number): Promise<FootnoteHit[]> {
+  return invoke<FootnoteHit[]>('search_footnotes', { query, moduleId: moduleId ?? null, limit: limit ?? null });
+}
+
+/** Strong's data of one word of a verse (`WordData` in Rust). */
+export interface WordData {
+  /** The word as shown, punctuation and all. */
+  word: string;
+  /** e.g. `["H7225"]`; empty when the source left the word untagged. */
+  strongs: string[];
+  gloss: string | null;
+}
+
+/**
+ * Strong's numbers of word `wordIndex` (0-based, counting runs of
+ * non-whitespace as the reader does) of a verse in a mounted translation.
+ * Rejects with `NotFound` for translations without tagged words.
+ */
+export async function getWordData(moduleId: string, verse: VerseRef, wordIndex: number): Promise<WordData> {
+  return invoke<WordData>('get_word_data', {
+    moduleId,
+    verse: { book: verse.book, chapter: verse.chapter, verse: verse.verse },
+    wordIndex,
+  });
+}
+
+/** A morphology code read as parsing (`Parsing` in Rust). */
+export interface Parsing {
+  /** e.g. `V-PAI-3S` (Robinson) or `HVqp3ms` (OSHM). */
+  code: string;
+  language: 'greek' | 'hebrew' | 'aramaic' | null;
+  /** e.g. `Verb: present active indicative, third person singular`; null
+   * for codes of a scheme the decoder doesn't read. */
+  description: string | null;
+}
+
+export interface Morphology {
+  word: string;
+  /** One per code on the word; empty when the source gave it none. */
+  parsings: Parsing[];
+}
+
+/**
+ * Morphology of word `wordIndex` (counted as for `getWordData`) of a verse
+ * in a mounted translation. Rejects with `NotFound` for untagged
+ * translations.
+ */
+export async function getMorphology(moduleId: string, verse: VerseRef, wordIndex: number): Promise<Morphology> {
+  return invoke<Morphology>('get_morphology', {
+    moduleId,
+    verse: { book: verse.book, chapter: verse.chapter, verse: verse.verse },
+    wordIndex,
+  });
+}
+
+/** Read a morphology code from another source (e.g. a SWORD module). */
+export async function decodeMorphology(code: string): Promise<Parsing> {
+  return invoke<Parsing>('decode_morphology', { code });
+}
+
+export interface OriginalWord {
+  /** The word in Hebrew, Aramaic or Greek. */
+  text: string;
+  lemma: string | null;
+  strongs: string[];
+  parsing: Parsing | null;
+}
+
+/** Original words over the translation words they became. */
+export interface InterlinearRow {
+  /** Empty for translation words nothing is aligned with. */
+  originals: OriginalWord[];
+  /** Word indexes as for `getWordData`, in order. */
+  words: number[];
+  text: string;
+}
+
+export interface Interlinear {
+  text: string;
+  /** In verse order; together they cover every word of `text`. */
+  rows: InterlinearRow[];
+}
+
+/**
+ * A verse of an aligned translation (e.g. unfoldingWord aligned USFM) as
+ * interlinear rows. Rejects with `NotFound` for unaligned translations.
+ */
+export async function getInterlinear(moduleId: string, verse: VerseRef): Promise<Interlinear> {
+  return invoke<Interlinear>('get_interlinear', {
+    moduleId,
+    verse: { book: verse.book, chapter: verse.chapter, verse: verse.verse },
+  });
+}
+
+/** Something an importer had to guess at or drop. */
+export interface ImportWarning {
+  file: string;
+  line: number | null;
+  message: string;
+}
+
+/** What a translation import wrote (`ImportReport` in Rust). */
+export interface ImportReport {
+  moduleId: string;
+  path: string;
+  /** OSIS ids of the imported books, in canonical order. */
+  books: string[];
+  verses: number;
+  headings: number;
+  footnotes: number;
+  warnings: ImportWarning[];
+  /** Warnings past the ones listed. */
+  moreWarnings: number;
+}
+
+export interface ImportProgress {
+  moduleId: string;
+  file: string;
+  done: number;
+  total: number;
+}
+
+/** Follow an import as it reads each source file. Returns an unlisten function. */
+export async function onImportProgress(listener: (progress: ImportProgress) => void): Promise<() => void> {
+  return listen<ImportProgress>('content://import-progress', (event) => listener(event.payload));
+}
+
+/**
+ * Import USFM files (one book each, as translations are usually shipped) as
+ * translation `moduleId`, replacing its content file. A mounted translation
+ * switches to the new file right away.
+ */
+export async function importUsfm(moduleId: string, paths: string[], name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_usfm', { moduleId, name: name ?? null, paths });
+}
+
+/**
+ * Import USX files as translation `moduleId`. Verses keep their paragraph
+ * and character styles as `html`.
+ */
+export async function importUsx(moduleId: string, paths: string[], name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_usx', { moduleId, name: name ?? null, paths });
+}
+
+/**
+ * Import an unzipped Digital Bible Library text bundle (the directory with
+ * `metadata.xml`). The id and name default to the bundle's abbreviation and
+ * name.
+ */
+export async function importDblBundle(path: string, moduleId?: string, name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_dbl_bundle', { path, moduleId: moduleId ?? null, name: name ?? null });
+}
+
+/** Import Zefania XML Bibles as translation `moduleId`. */
+export async function importZefania(moduleId: string, paths: string[], name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_zefania', { moduleId, name: name ?? null, paths });
+}
+
+/**
+ * Import OpenSong Bibles as translation `moduleId`. Books with names that
+ * aren't English are matched by their place in the file.
+ */
+export async function importOpenSong(moduleId: string, paths: string[], name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_opensong', { moduleId, name: name ?? null, paths });
+}
+
+/**
+ * Import an e-Sword `.bblx` Bible and mount it. The id and name default to
+ * the module's abbreviation and title.
+ */
+export async function importEsword(path: string, moduleId?: string, name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_esword', { path, moduleId: moduleId ?? null, name: name ?? null });
+}
+
+/**
+ * Import a theWord Bible (`.ont`, `.ot` or `.nt`) and mount it. The id and
+ * name default to the module's short title and description.
+ */
+export async function importTheWord(path: string, moduleId?: string, name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_theword', { path, moduleId: moduleId ?? null, name: name ?? null });
+}
+
+/**
+ * Import a MySword Bible (`.bbl.mybible`) and mount it. The id and name
+ * default to the module's abbreviation and title.
+ */
+export async function importMySword(path: string, moduleId?: string, name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_mysword', { path, moduleId: moduleId ?? null, name: name ?? null });
+}
+
+/**
+ * Import the SBL Greek New Testament from MorphGNT files, with each word's
+ * parsing. The id defaults to `SBLGNT`.
+ */
+export async function importSblgnt(paths: string[], moduleId?: string, name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_sblgnt', { moduleId: moduleId ?? null, name: name ?? null, paths });
+}
+
+/**
+ * Import the Westminster Leningrad Codex from the Open Scriptures Hebrew
+ * Bible's OSIS files, with Strong's numbers and morphology. Pass
+ * `accents` false to keep the vowel points but drop cantillation. The id
+ * defaults to `WLC`.
+ */
+export async function importWlc(
+  paths: string[],
+  moduleId?: string,
+  name?: string,
+  accents?: boolean,
+): Promise<ImportReport> {
+  return invoke<ImportReport>('import_wlc', { moduleId: moduleId ?? null, name: name ?? null, paths, accents: accents ?? null });
+}
+
+/** Where a text or CSV file keeps each verse (`TextLayout` in Rust). */
+export interface TextLayout {
+  /** Column separator; `null` for lines led by a reference (`Gen 1:1 In the beginning`). */
+  delimiter: string | null;
+  /** The first line names the columns. */
+  hasHeader: boolean;
+  /** Column (from 0) of a whole reference; otherwise `book`, `chapter` and `verse` give one each. */
+  reference: number | null;
+  book: number | null;
+  chapter: number | null;
+  verse: number | null;
+  /** Runs to the end of the line when it's the last column. */
+  text: number;
+}
+
+/** How much of a book a text file has, against the KJV numbering. */
+export interface BookCoverage {
+  book: string;
+  verses: number;
+  expected: number;
+  /** The first gaps, as `3:5`, `3:5-9` or `4` (a whole chapter). */
+  missing: string[];
+  missingVerses: number;
+  /** Verses the KJV numbering doesn't have. */
+  extra: string[];
+}
+
+/** What importing a text or CSV file would give (`TextImportPreview` in Rust). */
+export interface TextImportPreview {
+  /** The layout read with: the one given, or the guess. */
+  layout: TextLayout;
+  /** The header's column names, or the first line's fields. */
+  columns: string[];
+  sample: { book: string; chapter: number; verse: number; text: string }[];
+  verses: number;
+  books: BookCoverage[];
+  /** OSIS ids of the books the file has nothing of. */
+  missingBooks: string[];
+  warnings: ImportWarning[];
+  moreWarnings: number;
+}
+
+/**
+ * Read a verse-per-line text or CSV file without importing it: guesses the
+ * layout unless one is given, and reports coverage gaps and problems so the
+ * mapping can be corrected first.
+ */
+export async function previewTextImport(path: string, layout?: TextLayout): Promise<TextImportPreview> {
+  return invoke<TextImportPreview>('preview_text_import', { path, layout: layout ?? null });
+}
+
+/** Import a text or CSV file, read with `layout`, as translation `moduleId` and mount it. */
+export async function importText(moduleId: string, path: string, layout: TextLayout, name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_text', { moduleId, name: name ?? null, path, layout });
+}
+
+/** A Bible module in a SWORD library (`SwordModule` in Rust). */
+export interface SwordModule {
+  /** Module name, e.g. `KJV`; also the translation id it imports as. */
+  name: string;
+  description: string;
+  language: string | null;
+  version: string | null;
+  versification: string;
+  library: string;
+  /** Why the module can't be imported, if it can't. */
+  problem: string | null;
+}
+
+/**
+ * Scan a SWORD library (by default the user's `~/.sword`) for Bible modules.
+ * Scanned libraries are remembered for `listSwordModules` until the app quits.
+ */
+export async function scanSwordLibrary(path?: string): Promise<SwordModule[]> {
+  return invoke<SwordModule[]>('scan_sword_library', { path: path ?? null });
+}
+
+/** Bible modules from every library scanned this session. */
+export async function listSwordModules(): Promise<SwordModule[]> {
+  return invoke<SwordModule[]>('list_sword_modules');
+}
+
+/** Import SWORD modules from `library` into content files, one report each. */
+export async function importSwordModules(library: string, modules: string[]): Promise<ImportReport[]> {
+  return invoke<ImportReport[]>('import_sword_modules', { library, modules });
+}
@@ -24,10 +24,20 @@ import type { ApplicationEntry } from '@/types';
 import type { UserPreferences } from '@/types';
 import type { KeywordExclusion } from '@/types';
 import type { EntityNote } from '@/types';
+import type { LibrarySymbol } from './symbolLibrary';
+import type { Tag, TagLink } from './tags';
+import type { Bookmark, BookmarkFolder } from './bookmarks';
+import type { VerseCollection } from './collections';
+import type { Workspace } from './workspaces';
+import type { HighlightStyle } from './highlightStyles';
+import type { MarkingTemplate } from './markingTemplates';
+import type { MarginNote } from './marginNotes';
+import type { ResourceLink } from './resourceLinks';
 
 export type { UserPreferences, ApiConfigRecord, OnboardingState, AutoBackupConfig } from '@/types';
 
 import { waitForTauriInternals } from './platform';
+import type { DatabaseRecovery, FulltextScope, MarkingOperation, UndoStep } from './sqlite-db';
 
 // Lazy-load sqlite-db module
 let sqliteModule: typeof import('./sqlite-db') | null = null;
@@ -52,6 +62,8 @@ async function logChange(
   try {
     const mod = await sqlite();
     await mod.recordChange(tableName, op, rowId, data ? JSON.stringify(data) : null);
+    const engine = await import('./sync-engine');
+    engine.notifyLocalWrite();
   } catch (error) {
     // Don't fail the main operation if change logging fails
     console.error('[DB] Failed to log change:', error);
@@ -66,14 +78,138 @@ export async function initDatabase(): Promise<void> {
   await waitForTauriInternals();
   const mod = await sqlite();
   await mod.getSqliteDb();
+  recoveryPromise ??= restoreAfterStartupRecovery();
+  await recoveryPromise;
   await mod.sqliteCleanupOrphanedStudyRecords();
 }
 
+let recoveryPromise: Promise<void> | null = null;
+
+/**
+ * If the backend quarantined a corrupt database at startup, restore the
+ * backup it picked into the fresh database and announce it with a
+ * `databaseRecovered` window event (detail: `DatabaseRecovery` plus
+ * `restored`).
+ */
+async function restoreAfterStartupRecovery(): Promise<void> {
+  const mod = await sqlite();
+  let recovery: DatabaseRecovery | null;
+  try {
+    recovery = await mod.sqliteTakeRecovery();
+  } catch (error) {
+    console.warn('[Database] Could not read startup recovery state:', error);
+    return;
+  }
+  if (!recovery) return;
+
+  let restored = false;
+  if (recovery.backupPath) {
+    try {
+      const { readTextFile } = await import('@tauri-apps/plugin-fs');
+      const backup = JSON.parse(await readTextFile(recovery.backupPath));
+      const { restoreBackup } = await import('./backup');
+      await restoreBackup(backup);
+      restored = true;
+      console.log('[Database] Restored', recovery.backupPath, 'after quarantining', recovery.quarantinedPath);
+    } catch (error) {
+      console.error('[Database] Failed to restore backup after corruption:', error);
+    }
+  }
+  window.dispatchEvent(new CustomEvent('databaseRecovered', { detail: { ...recovery, restored } }));
+}
+
 export async function closeDatabase(): Promise<void> {
   const mod = await sqlite();
   await mod.closeSqliteDb();
 }
 
+export type { NativeDbError, NativeDbErrorKind } from './sqlite-db';
+export type { CheckpointResult, DatabaseRecovery, DatabaseStats, IntegrityReport, MaintenanceReport, RepairReport } from './sqlite-db';
+
+/**
+ * Compact the database and refresh planner statistics. The backend also
+ * runs this on its own about weekly once the database has been idle.
+ */
+export async function maintainDatabase() {
+  const mod = await sqlite();
+  return mod.sqliteMaintain();
+}
+
+/** Per-table row counts, file size and free pages for the settings screen. */
+export async function getDatabaseStats() {
+  const mod = await sqlite();
+  return mod.sqliteGetDatabaseStats();
+}
+
+export type { SqlDumpReport } from './sqlite-db';
+
+/**
+ * Export the user data as a plain-text SQL dump: one INSERT per row, stable
+ * order, so dumps can be diffed or edited and loaded back.
+ */
+export async function exportSqlDump(path: string) {
+  const mod = await sqlite();
+  return mod.sqliteExportSqlDump(path);
+}
+
+/**
+ * Load a SQL dump made by {@link exportSqlDump}, replacing the tables it
+ * contains. Rows keep their dumped sync state, as with a backup restore.
+ */
+export async function importSqlDump(path: string) {
+  const mod = await sqlite();
+  const report = await mod.sqliteImportSqlDump(path);
+  window.dispatchEvent(new CustomEvent('syncDataChanged', {
+    detail: { applied: report.tables.reduce((n, t) => n + t.rows, 0), tables: report.tables.map(t => t.table) },
+  }));
+  return report;
+}
+
+export type { Snapshot } from './sqlite-db';
+
+/**
+ * Snapshots the backend takes while the user edits (at most every 15
+ * minutes, newest 10 kept), newest first.
+ */
+export async function listSnapshots() {
+  const mod = await sqlite();
+  return mod.sqliteListSnapshots();
+}
+
+/**
+ * Roll the user data back to a snapshot. The data it replaces is snapshotted
+ * first, so restoring can be undone by restoring that one.
+ */
+export async function restoreSnapshot(id: string) {
+  const mod = await sqlite();
+  const report = await mod.sqliteRestoreSnapshot(id);
+  window.dispatchEvent(new CustomEvent('syncDataChanged', {
+    detail: { applied: report.tables.reduce((n, t) => n + t.rows, 0), tables: report.tables.map(t => t.table) },
+  }));
+  return report;
+}
+
+/** Check the database for corruption (`full` also verifies every index). */
+export async function checkDatabaseIntegrity(full = false) {
+  const mod = await sqlite();
+  return mod.sqliteCheckIntegrity(full);
+}
+
+/** Salvage a damaged database into a fresh file, reporting what was lost. */
+export async function repairDatabase() {
+  const mod = await sqlite();
+  return mod.sqliteRepairDatabase();
+}
+
+/**
+ * Fold the WAL sidecar back into biblemarker.db so the file alone is a
+ * complete copy. `busy` means another reader held it open; retry later.
+ */
+export async function checkpointDatabase() {
+  const mod = await sqlite();
+  return mod.sqliteCheckpoint();
+}
+
 export type { SyncDiagnostics } from './sqlite-db';
 
 // Re-export the synchronous device-id accessor so components import it from the
@@ -85,6 +221,13 @@ export async function getSyncDiagnostics() {
   return mod.getSyncDiagnostics();
 }
 
+export type { MigrationReport } from './sqlite-db';
+
+export async function dryRunMigrations() {
+  const mod = await sqlite();
+  return mod.dryRunMigrations();
+}
+
 // ============================================================================
 // Annotation Operations
 // ============================================================================
@@ -98,23 +241,86 @@ export async function getChapterAnnotations(
   return mod.sqliteGetChapterAnnotations(moduleId, book, chapter);
 }
 
+export type { FulltextHit, FulltextScope } from './sqlite-db';
+
+/** Ranked full-text search over cached verse text, notes and keywords. */
+export async function searchFulltext(
+  query: string,
+  scope: FulltextScope,
+  moduleId?: string,
+  limit?: number
+) {
+  const mod = await sqlite();
+  return mod.sqliteSearchFulltext(query, scope, moduleId, limit);
+}
+
 /** Per-preset count of keyword marks within a book (for study-report legends). */
 export async function getBookKeywordMarkCounts(book: string): Promise<Record<string, number>> {
   const mod = await sqlite();
   return mod.sqliteGetBookKeywordMarkCounts(book);
 }
 
+/** Ask the sync engine to push soon, after a write logged natively. */
+async function notifyNativeWrite(): Promise<void> {
+  const engine = await import('./sync-engine');
+  engine.notifyLocalWrite();
+}
+
+/** Save a new annotation; the backend validates it and logs the change. */
 export async function saveAnnotation(annotation: Annotation): Promise<string> {
   const mod = await sqlite();
   const result = await mod.sqliteSaveAnnotation(annotation);
-  await logChange('annotations', 'upsert', annotation.id, annotation);
+  await notifyNativeWrite();
+  return result;
+}
+
+/** Save an edited annotation over the stored one, returning it as saved. */
+export async function updateAnnotation(annotation: Annotation): Promise<Annotation> {
+  const mod = await sqlite();
+  const saved = await mod.sqliteUpdateAnnotation(annotation);
+  await notifyNativeWrite();
+  return saved;
+}
+
+/**
+ * Lock a mark to its own translation, or let it show in the others. Edits the
+ * stored mark, so it works from a carried copy too.
+ */
+export async function setAnnotationTranslationLocked(id: string, locked: boolean): Promise<Annotation | null> {
+  const stored = await getAnnotationById(id);
+  if (!stored) return null;
+  return updateAnnotation({ ...stored, translationLocked: locked });
+}
+
+/** Save a batch of annotations (e.g. every match of a keyword) in one transaction. */
+export async function saveAnnotations(annotations: Annotation[]): Promise<number> {
+  const mod = await sqlite();
+  const saved = await mod.sqliteSaveAnnotations(annotations);
+  if (saved > 0) await notifyNativeWrite();
+  return saved;
+}
+
+export type { BulkApplyResult, MarkingOperation } from './sqlite-db';
+
+/**
+ * Apply many annotation upserts/deletes in one transaction. Bad items are
+ * skipped and reported in `results` rather than failing the batch.
+ */
+export async function applyMarkings(operations: MarkingOperation[]) {
+  const mod = await sqlite();
+  const result = await mod.sqliteApplyMarkings(operations);
+  if (result.saved + result.deleted > 0) await notifyNativeWrite();
+  for (const item of result.results) {
+    if (item.status === 'failed') {
+      console.warn(`[DB] Marking operation ${item.index} (${item.id ?? 'no id'}) failed:`, item.error);
+    }
+  }
   return result;
 }
 
 export async function deleteAnnotation(id: string): Promise<void> {
   const mod = await sqlite();
-  await mod.sqliteDeleteAnnotation(id);
-  await logChange('annotations', 'delete', id);
+  if (await mod.sqliteDeleteAnnotation(id)) await notifyNativeWrite();
 }
 
 /**
@@ -360,6 +566,39 @@ export async function deleteNote(id: string): Promise<void> {
   await logChange('notes', 'delete', id);
 }
 
+export type { ReferencingNote } from './sqlite-db';
+
+/**
+ * Notes whose text mentions `book` `chapter` (`verse` to `endVerse`, or the
+ * whole chapter), e.g. "compare with Heb 11:6", most recently edited first.
+ */
+export async function getNotesReferencing(
+  book: string,
+  chapter: number,
+  verse?: number,
+  endVerse?: number
+) {
+  const mod = await sqlite();
+  return mod.sqliteGetNotesReferencing(book, chapter, verse, endVerse);
+}
+
+export type { Backlink, UnlinkedMention } from './sqlite-db';
+
+/**
+ * Notes linking to a note, by `[[its title]]` (its first heading) or
+ * `[[its id]]`, or citing the passage it is on, most recently edited first.
+ */
+export async function getBacklinks(noteId: string) {
+  const mod = await sqlite();
+  return mod.sqliteGetBacklinks(noteId);
+}
+
+/** Notes that say `term` in plain text, e.g. a note's title not yet linked. */
+export async function getUnlinkedMentions(term: string) {
+  const mod = await sqlite();
+  return mod.sqliteGetUnlinkedMentions(term);
+}
+
 export async function getAllNotes(): Promise<Note[]> {
   const mod = await sqlite();
   const db = await mod.getSqliteDb();
@@ -383,6 +622,70 @@ export async function getAllNotes(): Promise<Note[]> {
   }));
 }
 
+// ============================================================================
+// Trash Operations
+// ============================================================================
+
+export type { TrashItem } from './sqlite-db';
+
+/** Deleted notes and annotations still within their 30-day window, newest first. */
+export async function listTrash() {
+  const mod = await sqlite();
+  return mod.sqliteListTrash();
+}
+
+/**
+ * Restore a trashed note or annotation. The backend logs it as a fresh edit,
+ * so it also comes back on other devices.
+ */
+export async function restoreItem(id: number) {
+  const mod = await sqlite();
+  const restored = await mod.sqliteRestoreItem(id);
+  const engine = await import('./sync-engine');
+  engine.notifyLocalWrite();
+  window.dispatchEvent(new CustomEvent('annotationsUpdated'));
+  return restored;
+}
+
+export async function emptyTrash(olderThan?: Date) {
+  const mod = await sqlite();
+  return mod.sqliteEmptyTrash(olderThan?.toISOString());
+}
+
+// ============================================================================
+// Undo / Redo
+// ============================================================================
+
+export type { UndoStack, UndoStep } from './sqlite-db';
+
+async function afterReplay(step: UndoStep | null) {
+  if (step) {
+    const engine = await import('./sync-engine');
+    engine.notifyLocalWrite();
+    window.dispatchEvent(new CustomEvent('annotationsUpdated'));
+  }
+  return step;
+}
+
+/**
+ * Undo the latest annotation or note edit (a whole batch counts as one).
+ * The journal lives in the database, so this works across reloads.
+ */
+export async function undoLast() {
+  const mod = await sqlite();
+  return afterReplay(await mod.sqliteUndoLast());
+}
+
+export async function redo() {
+  const mod = await sqlite();
+  return afterReplay(await mod.sqliteRedo());
+}
+
+export async function getUndoStack() {
+  const mod = await sqlite();
+  return mod.sqliteGetUndoStack();
+}
+
 // ============================================================================
 // Marking Preset Operations
 // ============================================================================
@@ -763,6 +1066,26 @@ export interface DatabaseExportData {
   applications: ApplicationEntry[];
   entityNotes: EntityNote[];
   keywordExclusions: KeywordExclusion[];
+  /** Absent in exports from before the symbol library. */
+  symbolLibrary?: LibrarySymbol[];
+  /** Absent in exports from before tags. */
+  tags?: Tag[];
+  tagLinks?: TagLink[];
+  /** Absent in exports from before bookmarks. */
+  bookmarkFolders?: BookmarkFolder[];
+  bookmarks?: Bookmark[];
+  /** Absent in exports from before verse collections. */
+  verseCollections?: VerseCollection[];
+  /** Absent in exports from before workspaces. */
+  workspaces?: Workspace[];
+  /** Absent in exports from before highlight styles. */
+  highlightStyles?: HighlightStyle[];
+  /** Absent in exports from before marking templates. */
+  markingTemplates?: MarkingTemplate[];
+  /** Absent in exports from before margin notes. */
+  marginNotes?: MarginNote[];
+  /** Absent in exports from before resource links. */
+  resourceLinks?: ResourceLink[];
   preferences: UserPreferences | null;
 }
 
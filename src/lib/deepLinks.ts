@@ -0,0 +1,74 @@
+/**
+ * Deep Links
+ *
+ * `bible://` and `biblemarker://` links from other apps, web pages and
+ * slides, parsed by the backend into passages, notes and searches (see
+ * deep_link.rs for the forms). Links that arrive before the app is ready are
+ * held until `takeDeepLinks`; later ones come as `deep-link://open` events.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useBibleStore } from '@/stores/bibleStore';
+import { getAllTranslations } from './bible-api';
+import { getAllNotes } from './database';
+import type { ScriptureReference } from './references';
+
+export type DeepLink =
+  | { kind: 'passage'; references: ScriptureReference[]; translation: string | null }
+  | { kind: 'note'; id: string }
+  | { kind: 'search'; query: string };
+
+/** Links opened before the app listened; later ones only come as events. */
+export async function takeDeepLinks(): Promise<DeepLink[]> {
+  return invoke<DeepLink[]>('take_deep_links');
+}
+
+/** Subscribe to `deep-link://open`, emitted when a link is opened. */
+export async function onDeepLink(listener: (link: DeepLink) => void): Promise<() => void> {
+  return listen<DeepLink>('deep-link://open', (event) => listener(event.payload));
+}
+
+function goTo(book: string, chapter: number, verse: number | null | undefined) {
+  const { navigateToVerse, setLocation } = useBibleStore.getState();
+  if (verse != null) {
+    navigateToVerse(book, chapter, verse, true);
+  } else {
+    setLocation(book, chapter, true);
+  }
+}
+
+/**
+ * Go where `link` points: the passage's first reference (in its translation,
+ * if installed), the note's verse, or the search with its query filled in.
+ */
+export async function openDeepLink(link: DeepLink): Promise<void> {
+  switch (link.kind) {
+    case 'passage': {
+      const [first] = link.references;
+      if (!first) return;
+      if (link.translation) {
+        const wanted = link.translation.toLowerCase();
+        const translations = await getAllTranslations();
+        const match = translations.find(
+          (t) => t.id.toLowerCase() === wanted || t.abbreviation.toLowerCase() === wanted,
+        );
+        if (match) useBibleStore.getState().setCurrentModule(match.id);
+      }
+      goTo(first.book, first.chapter, first.verse);
+      return;
+    }
+    case 'note': {
+      const note = (await getAllNotes()).find((n) => n.id === link.id);
+      if (!note) {
+        console.warn('[deepLinks] No note with id', link.id);
+        return;
+      }
+      goTo(note.ref.book, note.ref.chapter, note.ref.verse);
+      return;
+    }
+    case 'search':
+      window.dispatchEvent(new CustomEvent('openSearch', { detail: { query: link.query } }));
+      return;
+  }
+}
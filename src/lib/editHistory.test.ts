@@ -0,0 +1,16 @@
+import { describe, it, expect } from 'vitest'
+import { describeVersion, type HistoryVersion } from './editHistory'
+
+const version = (v: Partial<HistoryVersion>): HistoryVersion => ({
+  version: 1, op: 'update', at: '2025-06-01T00:00:00Z', local: true, changes: [], ...v,
+})
+
+describe('describeVersion', () => {
+  it('says where a version came from and why sync kept it', () => {
+    expect(describeVersion(version({ op: 'create' }))).toBe('Created on this device')
+    expect(describeVersion(version({ local: false, deviceId: 'ipad', resolution: 'newer' })))
+      .toBe('Edited on ipad; kept by sync as the later edit')
+    expect(describeVersion(version({ local: false, resolution: 'deviceTiebreak' })))
+      .toBe('Edited on another device at the same moment as this one; kept by sync on device order')
+  })
+})
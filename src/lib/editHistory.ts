@@ -0,0 +1,72 @@
+/**
+ * Edit History
+ *
+ * Every version a note or annotation has had, recorded natively on each write
+ * (db/edit_history.rs): local saves, undo and rows written by sync alike. A
+ * version from another device says why it replaced the one before, so a
+ * note that changed after a sync can be explained and, if need be, reverted.
+ * Versions are kept for 180 days, 50 per item.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type HistoryOp = 'create' | 'update' | 'delete';
+
+/** Why sync let another device's version replace the one before it. */
+export type Resolution = 'newer' | 'deviceTiebreak';
+
+export interface FieldChange {
+  field: string;
+  /** `null` where the field was absent. */
+  before: unknown;
+  after: unknown;
+}
+
+export interface HistoryVersion {
+  version: number;
+  op: HistoryOp;
+  /** When this device recorded it. */
+  at: string;
+  /** When it was edited, on whichever device; what sync compares. */
+  updatedAt?: string;
+  deviceId?: string;
+  /** Made on this device rather than arriving by sync. */
+  local: boolean;
+  resolution?: Resolution;
+  /** The note or annotation as stored; absent for a delete. */
+  item?: Record<string, unknown>;
+  /** Fields that differ from the version before, `updatedAt` aside. */
+  changes: FieldChange[];
+}
+
+export interface ItemHistory {
+  table: 'annotations' | 'notes';
+  id: string;
+  /** Most recent first. */
+  versions: HistoryVersion[];
+}
+
+/** Every version of note or annotation `id`, most recent first. */
+export async function getAnnotationHistory(id: string): Promise<ItemHistory> {
+  return invoke<ItemHistory>('get_annotation_history', { id });
+}
+
+/** Put `id` back as it was at `version`, itself saved as a new version. Returns the item as restored. */
+export async function revertAnnotation(id: string, version: number): Promise<Record<string, unknown>> {
+  return invoke<Record<string, unknown>>('revert_annotation', { id, version });
+}
+
+/** One line on where a version came from, for the history list. */
+export function describeVersion(version: HistoryVersion): string {
+  const what = version.op === 'create' ? 'Created' : version.op === 'delete' ? 'Deleted' : 'Edited';
+  if (version.local) return `${what} on this device`;
+  const where = version.deviceId ? `on ${version.deviceId}` : 'on another device';
+  switch (version.resolution) {
+    case 'newer':
+      return `${what} ${where}; kept by sync as the later edit`;
+    case 'deviceTiebreak':
+      return `${what} ${where} at the same moment as this one; kept by sync on device order`;
+    default:
+      return `${what} ${where}`;
+  }
+}
@@ -0,0 +1,43 @@
+/**
+ * Generated Observation Lists
+ *
+ * Observation lists made from markings: every mark of a key word, tag or
+ * highlight style in a scope, as the fragment of its verse it marks, in
+ * canonical order. Built natively (db/observation_lists.rs) and stored as an
+ * ordinary observation list, so it shows, exports and syncs like one; each
+ * item keeps its mark's `annotationId`, and the list records what it was
+ * made from in `generatedFrom` so it can be refreshed.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { ObservationList, ObservationListSource, ObservationScope } from '@/types';
+
+export interface ObservationListRequest {
+  source: ObservationListSource;
+  scope?: ObservationScope;
+  /** Only this translation's marks. */
+  moduleId?: string;
+  /** The key word the list is about; by default the source's, or the one most of its marks are made with. */
+  keyWordId?: string;
+  /** By default the key word's word. */
+  title?: string;
+  studyId?: string;
+}
+
+/**
+ * Generate and save a list. Rejected when no key word can be told for it:
+ * pass `keyWordId` for a tag or style whose marks aren't of a key word.
+ */
+export async function generateObservationList(
+  request: ObservationListRequest & { id?: string }
+): Promise<ObservationList> {
+  const { id, ...rest } = request;
+  return invoke<ObservationList>('generate_observation_list', { id: id ?? crypto.randomUUID(), request: rest });
+}
+
+/** Add items for marks made since and drop those whose mark is gone. Notes on kept items stay. */
+export async function refreshObservationList(
+  id: string
+): Promise<{ list: ObservationList; added: number; removed: number }> {
+  return invoke('refresh_observation_list', { id });
+}
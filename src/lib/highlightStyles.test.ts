@@ -0,0 +1,40 @@
+import { describe, it, expect } from 'vitest'
+import { styleDeclarations, legacyLook, withHighlightStyle, type HighlightStyle } from './highlightStyles'
+import { HIGHLIGHT_COLORS, type TextAnnotation } from '@/types'
+
+const promise: HighlightStyle = {
+  id: 'hs-1',
+  name: 'Promise',
+  fill: 'yellow',
+  underline: { color: '#123456', style: 'wavy' },
+  border: { color: 'red', shape: 'rounded', style: 'dashed' },
+  symbol: { symbol: 'star' },
+}
+
+describe('styleDeclarations', () => {
+  it('combines every text part of a style', () => {
+    expect(styleDeclarations(promise)).toEqual([
+      `background-color: ${HIGHLIGHT_COLORS.yellow}40`,
+      'text-decoration: underline',
+      'text-decoration-color: #123456',
+      'text-decoration-style: wavy',
+      `border: 1px dashed ${HIGHLIGHT_COLORS.red}`,
+      'border-radius: 0.3em',
+      'box-decoration-break: clone',
+    ])
+  })
+})
+
+describe('withHighlightStyle', () => {
+  it('keeps the closest flat look beside the style id', () => {
+    const underlined: TextAnnotation = {
+      id: 'a1', moduleId: 'kjv', type: 'underline', color: 'blue', underlineStyle: 'dotted',
+      startRef: { book: 'Rom', chapter: 5, verse: 1 }, endRef: { book: 'Rom', chapter: 5, verse: 1 },
+      createdAt: new Date(), updatedAt: new Date(),
+    }
+    const styled = withHighlightStyle(underlined, promise)
+    expect(styled).toMatchObject({ type: 'highlight', color: 'yellow', styleId: 'hs-1' })
+    expect(styled.underlineStyle).toBeUndefined()
+    expect(legacyLook({ id: 'hs-2', name: 'Own', textColor: '#abcdef' })).toEqual({ type: 'textColor', color: 'gray' })
+  })
+})
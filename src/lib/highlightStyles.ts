@@ -0,0 +1,108 @@
+/**
+ * Highlight Styles
+ *
+ * The user's palette of named looks for marking text, each combining a fill,
+ * a text color, an underline, a border and a symbol overlay ("Promise":
+ * yellow fill in a red box with a star). Kept natively in the synced
+ * `highlight_styles` table (db/highlight_styles.rs); a text annotation points
+ * at one by `styleId` and keeps its own flat type and color as the look for
+ * older apps and for a style since deleted.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { HIGHLIGHT_COLORS, getHighlightColorHex } from '@/types';
+import type { HighlightColor, TextAnnotation, UnderlineStyle, SymbolKey } from '@/types';
+
+export type BorderShape = 'box' | 'rounded' | 'circle';
+
+/** A style of the palette (`HighlightStyle` in Rust). Colors are highlight color names or `#rrggbb`. */
+export interface HighlightStyle {
+  id: string;
+  name: string;
+  /** The background. */
+  fill?: string;
+  textColor?: string;
+  underline?: { color: string; style: UnderlineStyle };
+  /** Any line style but `wavy`. */
+  border?: { color: string; shape: BorderShape; style: Exclude<UnderlineStyle, 'wavy'> };
+  symbol?: { symbol: SymbolKey; color?: string; placement?: 'above' | 'overlay' };
+  /** ISO timestamps, set by the backend. */
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+export interface HighlightStyleEntry extends HighlightStyle {
+  /** Annotations drawn with it. */
+  uses: number;
+}
+
+/** The palette by name, with how many marks each style draws. */
+export async function getHighlightStyles(): Promise<HighlightStyleEntry[]> {
+  return invoke<HighlightStyleEntry[]>('get_highlight_styles');
+}
+
+/**
+ * Add a style. Rejected if its name is taken, or it has none of a fill, text
+ * color, underline or border (a symbol alone is a symbol mark).
+ */
+export async function createHighlightStyle(style: Omit<HighlightStyle, 'id'> & { id?: string }): Promise<HighlightStyle> {
+  return invoke<HighlightStyle>('create_highlight_style', {
+    style: { ...style, id: style.id ?? crypto.randomUUID() },
+  });
+}
+
+/** Save an edited style; every mark drawn with it changes with it. */
+export async function updateHighlightStyle(style: HighlightStyle): Promise<HighlightStyle> {
+  return invoke<HighlightStyle>('update_highlight_style', { style });
+}
+
+/** Remove a style; its marks stay, drawn in their own type and color. */
+export async function deleteHighlightStyle(id: string): Promise<boolean> {
+  return invoke<boolean>('delete_highlight_style', { id });
+}
+
+function hex(color: string): string {
+  return color.startsWith('#') ? color : getHighlightColorHex(color);
+}
+
+/** The CSS declarations that draw a style's text parts; its symbol is drawn apart. */
+export function styleDeclarations(style: HighlightStyle): string[] {
+  const css: string[] = [];
+  if (style.fill) css.push(`background-color: ${hex(style.fill)}40`);
+  if (style.textColor) css.push(`color: ${hex(style.textColor)}`);
+  if (style.underline) {
+    css.push('text-decoration: underline');
+    css.push(`text-decoration-color: ${hex(style.underline.color)}`);
+    css.push(`text-decoration-style: ${style.underline.style}`);
+  }
+  if (style.border) {
+    const radius = { box: '0', rounded: '0.3em', circle: '999px' }[style.border.shape];
+    css.push(`border: ${style.border.style === 'double' ? 3 : 1}px ${style.border.style} ${hex(style.border.color)}`);
+    css.push(`border-radius: ${radius}`);
+    css.push('box-decoration-break: clone');
+  }
+  return css;
+}
+
+/** A named highlight color, or gray for a `#rrggbb` of the user's own. */
+function named(color: string | undefined): HighlightColor {
+  return color && color in HIGHLIGHT_COLORS ? (color as HighlightColor) : 'gray';
+}
+
+/**
+ * The flat type and color that come closest to a style, for the fields a
+ * styled annotation keeps: its fill, else its underline, text color or border.
+ */
+export function legacyLook(style: HighlightStyle): Pick<TextAnnotation, 'type' | 'color' | 'underlineStyle'> {
+  if (style.fill) return { type: 'highlight', color: named(style.fill) };
+  if (style.underline) {
+    return { type: 'underline', color: named(style.underline.color), underlineStyle: style.underline.style };
+  }
+  if (style.textColor) return { type: 'textColor', color: named(style.textColor) };
+  return { type: 'underline', color: named(style.border?.color), underlineStyle: style.border?.style };
+}
+
+/** `annotation` drawn with `style`. */
+export function withHighlightStyle(annotation: TextAnnotation, style: HighlightStyle): TextAnnotation {
+  return { ...annotation, underlineStyle: undefined, ...legacyLook(style), styleId: style.id };
+}
@@ -0,0 +1,67 @@
+/**
+ * Lexicons
+ *
+ * Strong's-numbered Hebrew and Greek lexicons (Strong's own dictionaries,
+ * Thayer, BDB, ...) imported into read-only files beside the translation
+ * content, for the word-study popup.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** An installed lexicon (`Lexicon` in Rust). */
+export interface Lexicon {
+  id: string;
+  name: string;
+  /** `hebrew`, `greek`, or null for lexicons of both. */
+  language: 'hebrew' | 'greek' | null;
+  entries: number;
+  /** Importer that built it (`openscriptures`, `esword`, `sword`). */
+  sourceFormat: string;
+  path: string;
+}
+
+/** One lexicon's entry for a Strong's number (`LexiconEntry` in Rust). */
+export interface LexiconEntry {
+  lexicon: string;
+  lexiconName: string;
+  /** Normalized number, e.g. `H7225`. */
+  strongs: string;
+  /** The word in Hebrew or Greek. */
+  lemma: string | null;
+  transliteration: string | null;
+  pronunciation: string | null;
+  derivation: string | null;
+  definition: string;
+  /** How the KJV translates it. */
+  usage: string | null;
+}
+
+export async function listLexicons(): Promise<Lexicon[]> {
+  return invoke<Lexicon[]>('list_lexicons');
+}
+
+/**
+ * Import an Open Scriptures Strong's dictionary (`.js`/`.json`) or an
+ * e-Sword lexicon (`.lexi`/`.lexx`). The id defaults to the file name.
+ */
+export async function importLexicon(path: string, lexiconId?: string, name?: string): Promise<Lexicon> {
+  return invoke<Lexicon>('import_lexicon', { path, lexiconId: lexiconId ?? null, name: name ?? null });
+}
+
+/** Import a Strong's-keyed SWORD lexicon (`StrongsGreek`, `Thayer`, ...). */
+export async function importSwordLexicon(library: string, module: string): Promise<Lexicon> {
+  return invoke<Lexicon>('import_sword_lexicon', { library, module });
+}
+
+/** Delete a lexicon; false if it wasn't installed. */
+export async function removeLexicon(id: string): Promise<boolean> {
+  return invoke<boolean>('remove_lexicon', { id });
+}
+
+/**
+ * Entries for a Strong's number (`H7225`, `G3056`) from every installed
+ * lexicon. Rejects with `NotFound` when no lexicon is installed.
+ */
+export async function lookupLexicon(strongsNumber: string): Promise<LexiconEntry[]> {
+  return invoke<LexiconEntry[]>('lookup_lexicon', { strongsNumber });
+}
@@ -0,0 +1,76 @@
+/**
+ * Margin Notes
+ *
+ * Notes on a whole chapter, or on a pericope within it, written in its margin
+ * as chapter themes are in a paper Bible, rather than on one verse. Kept
+ * natively in the synced `margin_notes` table (db/margin_notes.rs); a margin
+ * note belongs to the chapter in every translation, and a note on a passage
+ * shows in the margin of each chapter it spans.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type MarginSide = 'top' | 'left' | 'right' | 'bottom';
+
+/** Where in its margin a note sits. */
+export interface MarginPlacement {
+  side: MarginSide;
+  /** The verse it is written beside; absent for the top of its chapter or passage. */
+  verse?: number;
+  /** Its place among notes in the same spot, from 0. */
+  order: number;
+}
+
+/** The passage a note on a pericope covers, from its chapter's `verse` to `endChapter` `endVerse`. */
+export interface MarginPericope {
+  title?: string;
+  verse: number;
+  endChapter: number;
+  endVerse: number;
+}
+
+/** A margin note (`MarginNote` in Rust). */
+export interface MarginNote {
+  id: string;
+  /** OSIS book. */
+  book: string;
+  /** The chapter it is on, or its passage begins in. */
+  chapter: number;
+  /** Absent for a note on the whole chapter. */
+  pericope?: MarginPericope;
+  /** Markdown. */
+  content: string;
+  placement: MarginPlacement;
+  /** A highlight color name or `#rrggbb`. */
+  color?: string;
+  studyId?: string;
+  /** ISO timestamps, set by the backend. */
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+/** The notes in a chapter's margins, its passages' included: those of `studyId` and of no study. */
+export async function getMarginNotes(book: string, chapter: number, studyId?: string): Promise<MarginNote[]> {
+  return invoke<MarginNote[]>('get_margin_notes', { book, chapter, studyId: studyId ?? null });
+}
+
+export async function createMarginNote(
+  note: Omit<MarginNote, 'id' | 'placement'> & { id?: string; placement?: Partial<MarginPlacement> }
+): Promise<MarginNote> {
+  return invoke<MarginNote>('create_margin_note', {
+    note: {
+      ...note,
+      id: note.id ?? crypto.randomUUID(),
+      placement: { side: 'right', order: 0, ...note.placement },
+    },
+  });
+}
+
+/** Save an edited or moved note. */
+export async function updateMarginNote(note: MarginNote): Promise<MarginNote> {
+  return invoke<MarginNote>('update_margin_note', { note });
+}
+
+export async function deleteMarginNote(id: string): Promise<boolean> {
+  return invoke<boolean>('delete_margin_note', { id });
+}
@@ -0,0 +1,12 @@
+import { describe, it, expect } from 'vitest'
+import { heatLevel } from './markingStats'
+
+describe('heatLevel', () => {
+  it('keeps unmarked chapters blank and gives any mark at least the first shade', () => {
+    expect(heatLevel(0, 2)).toBe(0)
+    expect(heatLevel(0.01, 2)).toBe(1)
+    expect(heatLevel(1, 2)).toBe(2)
+    expect(heatLevel(2, 2)).toBe(4)
+    expect(heatLevel(1, 0)).toBe(0)
+  })
+})
@@ -0,0 +1,62 @@
+/**
+ * Marking Statistics
+ *
+ * Counts of marks by color, symbol and tag for each book and chapter, and
+ * each chapter's density (marks per verse), for a heatmap of the canon
+ * showing where study has concentrated. Computed natively
+ * (db/marking_stats.rs); a mark counts in the chapter it begins in.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface MarkingStatsScope {
+  /** One translation's marks; absent for every translation's. */
+  moduleId?: string;
+  /** One OSIS book; absent for the whole canon. */
+  book?: string;
+}
+
+export interface MarkCounts {
+  marks: number;
+  /** Text marks by highlight color. */
+  byColor: Record<string, number>;
+  /** Symbols by symbol key. */
+  bySymbol: Record<string, number>;
+  /** Marks by tag path. */
+  byTag: Record<string, number>;
+}
+
+export interface ChapterMarkingStats extends MarkCounts {
+  chapter: number;
+  /** Verses a mark begins in. */
+  markedVerses: number;
+  verses: number;
+  /** Marks per verse. */
+  density: number;
+}
+
+export interface BookMarkingStats extends MarkCounts {
+  book: string;
+  /** Every chapter of the book, marked or not. */
+  chapters: ChapterMarkingStats[];
+}
+
+export interface MarkingStats extends MarkCounts {
+  /** Books in canon order. */
+  books: BookMarkingStats[];
+  /** The densest chapter's density. */
+  maxDensity: number;
+}
+
+export async function getMarkingStats(scope: MarkingStatsScope = {}): Promise<MarkingStats> {
+  return invoke<MarkingStats>('get_marking_stats', { scope });
+}
+
+/**
+ * Which of `levels` shades a chapter of `density` gets in a heatmap scaled to
+ * `maxDensity`: 0 for an unmarked chapter, up to `levels - 1` for the densest.
+ */
+export function heatLevel(density: number, maxDensity: number, levels = 5): number {
+  if (density <= 0 || maxDensity <= 0) return 0;
+  return Math.max(1, Math.ceil((density / maxDensity) * (levels - 1)));
+}
@@ -0,0 +1,97 @@
+/**
+ * Marking Templates
+ *
+ * A word or phrase and the look to mark it with ("covenant" in a red box),
+ * applied to every occurrence in a book at once. Kept natively in the synced
+ * `marking_templates` table (db/marking_templates.rs); the backend searches
+ * the translation's text and creates the marks in one transaction, each
+ * carrying the template's `templateId` so they can be removed together.
+ * Words match as key words do in keywordMatching.ts.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { legacyLook, type HighlightStyle } from './highlightStyles';
+import type { HighlightColor, UnderlineStyle } from '@/types';
+
+/** A template (`MarkingTemplate` in Rust). */
+export interface MarkingTemplate {
+  id: string;
+  /** The word or phrase to mark. */
+  word: string;
+  /** Other forms marked with it. */
+  variants: string[];
+  caseSensitive: boolean;
+  /** The OSIS book it marks; absent for the whole Bible. */
+  book?: string;
+  type: 'highlight' | 'textColor' | 'underline';
+  color: HighlightColor;
+  underlineStyle?: UnderlineStyle;
+  /** The highlight style its marks are drawn with; `type` and `color` are their flat look. */
+  styleId?: string;
+  /** The key word its marks count towards. */
+  presetId?: string;
+  /** ISO timestamps, set by the backend. */
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+/** What applying a template to a translation would do. */
+export interface TemplatePreview {
+  occurrences: number;
+  verses: number;
+  /** Occurrences by chapter number. */
+  chapters: Record<string, number>;
+  /** Occurrences it has already marked. */
+  alreadyMarked: number;
+  /** Some chapters of its book have no text to search (not cached yet). */
+  textMissing: boolean;
+}
+
+export interface TemplateApplied {
+  created: number;
+  alreadyMarked: number;
+}
+
+/** Every template, by word. */
+export async function getMarkingTemplates(): Promise<MarkingTemplate[]> {
+  return invoke<MarkingTemplate[]>('get_marking_templates');
+}
+
+export async function createMarkingTemplate(
+  template: Omit<MarkingTemplate, 'id'> & { id?: string }
+): Promise<MarkingTemplate> {
+  return invoke<MarkingTemplate>('create_marking_template', {
+    template: { ...template, id: template.id ?? crypto.randomUUID() },
+  });
+}
+
+/** Save an edited template; marks it already made stay as they were. */
+export async function updateMarkingTemplate(template: MarkingTemplate): Promise<MarkingTemplate> {
+  return invoke<MarkingTemplate>('update_marking_template', { template });
+}
+
+/** Remove a template, and with `withMarks` every mark it made. */
+export async function deleteMarkingTemplate(id: string, withMarks = false): Promise<boolean> {
+  return invoke<boolean>('delete_marking_template', { id, withMarks });
+}
+
+/** Count what applying a template to `moduleId` would mark, without marking it. */
+export async function previewMarkingTemplate(id: string, moduleId: string): Promise<TemplatePreview> {
+  return invoke<TemplatePreview>('preview_marking_template', { id, moduleId });
+}
+
+/** Mark every occurrence not marked yet, as one undo step. */
+export async function applyMarkingTemplate(id: string, moduleId: string): Promise<TemplateApplied> {
+  return invoke<TemplateApplied>('apply_marking_template', { id, moduleId });
+}
+
+/** Delete the marks a template made, in `moduleId` or in every translation. */
+export async function removeTemplateMarkings(id: string, moduleId?: string): Promise<number> {
+  return invoke<number>('remove_template_markings', { id, moduleId });
+}
+
+/** `template` drawn with `style`, keeping the closest flat look beside it. */
+export function withTemplateStyle(template: MarkingTemplate, style: HighlightStyle): MarkingTemplate {
+  const { type, color, underlineStyle } = legacyLook(style);
+  return { ...template, type, color, underlineStyle, styleId: style.id };
+}
@@ -4,8 +4,9 @@
  */
 
 import { describe, expect, it } from 'vitest';
-import { formatRangeLabel, getTranslationAttribution } from './passage-pdf';
+import { formatRangeLabel, getTranslationAttribution, quotableVerses } from './passage-pdf';
 import type { ApiTranslation } from '@/lib/bible-api';
+import type { TranslationLicense } from '@/lib/translations';
 
 const ESV: ApiTranslation = {
   id: 'ESV',
@@ -45,6 +46,28 @@ describe('getTranslationAttribution', () => {
   });
 });
 
+describe('translation license terms', () => {
+  const restricted: TranslationLicense = {
+    id: 'NASB',
+    license: 'Copyrighted',
+    copyright: null,
+    attribution: 'Scripture taken from the NASB®. Used by permission.',
+    quoteLimit: 2,
+    restricted: true,
+  };
+
+  it('uses the recorded attribution over the provider copyright', () => {
+    expect(getTranslationAttribution(NASB, restricted)).toBe(restricted.attribution);
+    expect(getTranslationAttribution(NASB, null)).toMatch(/Lockman/);
+  });
+
+  it('caps the quoted verses at the quote limit', () => {
+    expect(quotableVerses([1, 2, 3], restricted)).toEqual([1, 2]);
+    expect(quotableVerses([1, 2, 3], { ...restricted, quoteLimit: null })).toEqual([1, 2, 3]);
+    expect(quotableVerses([1, 2, 3])).toEqual([1, 2, 3]);
+  });
+});
+
 describe('formatRangeLabel', () => {
   it('returns book + chapter when no range is given', () => {
     expect(formatRangeLabel('John', 3)).toBe('John 3');
@@ -31,6 +31,7 @@ import { filterPresetsByStudy } from '@/lib/studyFilter';
 import { PageWriter, hexToRgb, loadJsPDF, type JsPDFDoc } from '@/lib/pdf/page-writer';
 import { buildIconCache, iconCacheKey } from '@/lib/pdf/symbol-cache';
 import { openSavedPdf } from '@/lib/pdf/save';
+import type { TranslationLicense } from '@/lib/translations';
 
 export { openSavedPdf };
 
@@ -52,10 +53,14 @@ export interface BuildPassagePdfInput {
   exclusions?: KeywordExclusion[];
   /** Active study, used to filter presets. */
   activeStudyId?: string | null;
+  /** License terms of an installed translation; caps the verses quoted and
+   *  supplies the footer attribution. */
+  license?: TranslationLicense | null;
 }
 
 /** Per-translation attribution string used in the PDF footer and the popover. */
-export function getTranslationAttribution(translation: ApiTranslation): string {
+export function getTranslationAttribution(translation: ApiTranslation, license?: TranslationLicense | null): string {
+  if (license?.attribution) return license.attribution;
   if (translation.id === 'ESV' || translation.id === 'eng-ESV') return ESV_COPYRIGHT;
   const sword = getModuleCopyright(translation.id);
   if (sword?.text) return sword.text;
@@ -63,6 +68,12 @@ export function getTranslationAttribution(translation: ApiTranslation): string {
   return `${translation.name} — public domain.`;
 }
 
+/** The verses an export may quote under `license` — the first `quoteLimit` of them. */
+export function quotableVerses<T>(verses: T[], license?: TranslationLicense | null): T[] {
+  const limit = license?.quoteLimit;
+  return limit != null && verses.length > limit ? verses.slice(0, limit) : verses;
+}
+
 export function formatRangeLabel(book: string, chapter: number, range?: { start: number; end: number }): string {
   const bookName = getBookById(book)?.name ?? book;
   if (!range) return `${bookName} ${chapter}`;
@@ -298,7 +309,10 @@ export async function renderPassageIntoDoc(doc: JsPDFDoc, input: BuildPassagePdf
   const filteredPresets = filterPresetsByStudy(presets, input.activeStudyId ?? null);
 
   const range = verseRange ?? { start: verses[0]?.ref.verse ?? 1, end: verses[verses.length - 1]?.ref.verse ?? 0 };
-  const inRange = verses.filter((v) => v.ref.verse >= range.start && v.ref.verse <= range.end);
+  const inRange = quotableVerses(
+    verses.filter((v) => v.ref.verse >= range.start && v.ref.verse <= range.end),
+    input.license,
+  );
 
   // Per-verse combined annotation lists (persisted + virtual keyword matches).
   // Built once up front so we can pre-rasterize every unique symbol icon
@@ -358,7 +372,7 @@ export async function renderPassageIntoDoc(doc: JsPDFDoc, input: BuildPassagePdf
     }
   }
 
-  writer.drawRunningFooter(getTranslationAttribution(translation));
+  writer.drawRunningFooter(getTranslationAttribution(translation, input.license));
 }
 
 export async function buildPassagePdf(input: BuildPassagePdfInput): Promise<Uint8Array> {
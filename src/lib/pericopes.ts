@@ -0,0 +1,85 @@
+/**
+ * Pericopes
+ *
+ * Named passages ("The Parable of the Prodigal Son", Luke 15:11–32) from
+ * imported pericope sets or a translation's section headings, for
+ * navigating and marking by passage unit. Lookups use the first installed
+ * set unless given a `setId`.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { ScriptureReference } from './references';
+
+/** A named passage (`Pericope` in Rust). */
+export interface Pericope {
+  /** Its number in the set, in canonical order. */
+  id: number;
+  title: string;
+  /** The passage, verse to verse. */
+  reference: ScriptureReference;
+}
+
+/** An installed pericope set (`PericopeSet` in Rust). */
+export interface PericopeSet {
+  id: string;
+  name: string;
+  pericopes: number;
+  /** `tsv`, `json`, or `headings` for one built from a translation. */
+  sourceFormat: string;
+  path: string;
+}
+
+export async function listPericopeSets(): Promise<PericopeSet[]> {
+  return invoke<PericopeSet[]>('list_pericope_sets');
+}
+
+/**
+ * Import a tab-separated (`.tsv`/`.txt`, `reference<TAB>title` a line) or
+ * JSON (`[{ title, reference }]`) pericope list. The id defaults to the file
+ * name.
+ */
+export async function importPericopes(path: string, setId?: string, name?: string): Promise<PericopeSet> {
+  return invoke<PericopeSet>('import_pericopes', { path, setId: setId ?? null, name: name ?? null });
+}
+
+/** Build the set `<module>-headings` from a translation's section headings. */
+export async function importPericopesFromTranslation(moduleId: string): Promise<PericopeSet> {
+  return invoke<PericopeSet>('import_pericopes_from_translation', { moduleId });
+}
+
+/** Delete a pericope set; false if it wasn't installed. */
+export async function removePericopeSet(id: string): Promise<boolean> {
+  return invoke<boolean>('remove_pericope_set', { id });
+}
+
+/** Pericopes overlapping a chapter, in order; empty when no set is installed. */
+export async function getPericopes(book: string, chapter: number, setId?: string): Promise<Pericope[]> {
+  return invoke<Pericope[]>('get_pericopes', { book, chapter, setId: setId ?? null });
+}
+
+/** The pericope a verse belongs to, or null. */
+export async function getPericopeAt(
+  book: string,
+  chapter: number,
+  verse: number,
+  setId?: string,
+): Promise<Pericope | null> {
+  return invoke<Pericope | null>('get_pericope_at', { book, chapter, verse, setId: setId ?? null });
+}
+
+/** Pericopes titled with every word of `query` ("prodigal son"), at most 50. */
+export async function searchPericopes(query: string, setId?: string): Promise<Pericope[]> {
+  return invoke<Pericope[]>('search_pericopes', { query, setId: setId ?? null });
+}
+
+/**
+ * Annotations of a translation that begin within a pericope, as stored
+ * (dates as ISO strings).
+ */
+export async function getPericopeAnnotations(
+  moduleId: string,
+  pericopeId: number,
+  setId?: string,
+): Promise<unknown[]> {
+  return invoke<unknown[]>('get_pericope_annotations', { moduleId, pericopeId, setId: setId ?? null });
+}
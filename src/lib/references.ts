@@ -0,0 +1,192 @@
+/**
+ * Verse References
+ *
+ * Parsing the references people type ("Jn 3:16-18; Rom 8; 1 Pet 1:3") with
+ * the native parser, which knows book abbreviations, ranges, chapter-only
+ * references and lists, in English and the other citation languages, plus
+ * the user's own book aliases. Search, note links and quick navigation use it,
+ * as does the desktop lookup of a reference copied to the clipboard. A
+ * misspelled book ("Galations 5") comes back with the books it might mean.
+ * References are written back out in the user's citation style.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { isTauri } from './platform';
+import { getPreferences } from './database';
+import { parseVerseRef } from '@/types';
+import type { CitationPreferences, VerseRef } from '@/types';
+
+/** A normalized reference (`ScriptureReference` in Rust). */
+export interface ScriptureReference {
+  /** OSIS book id. */
+  book: string;
+  chapter: number;
+  /** Null for whole chapters (or, with chapter 1, a whole book). */
+  verse: number | null;
+  endChapter: number;
+  endVerse: number | null;
+  /** e.g. `John.3.16-John.3.18`. */
+  osis: string;
+  /** e.g. `John 3:16–18`. */
+  label: string;
+}
+
+/**
+ * Every reference in `text`, in order; parts that don't parse are left out.
+ * `aliases` maps extra names to the book each stands for.
+ */
+export async function parseReferences(
+  text: string,
+  aliases?: Record<string, string>,
+): Promise<ScriptureReference[]> {
+  return invoke<ScriptureReference[]>('parse_references', { text, aliases });
+}
+
+/** A book a misspelled name might mean (`BookSuggestion` in Rust). */
+export interface BookSuggestion {
+  /** OSIS book id. */
+  book: string;
+  /** The name it is close to, e.g. `Galatians`. */
+  name: string;
+  /** The misspelled part with that name instead, e.g. `Galatians 5`. */
+  reference: string;
+  /** Letters to add, drop, change or swap. */
+  distance: number;
+}
+
+/** Why text didn't read as references (`ReferenceError` in Rust). */
+export interface ReferenceError {
+  kind: 'unknown_book' | 'invalid';
+  message: string;
+  /** The part that didn't read, e.g. `Galations 5`. */
+  text: string;
+  /** Best first; only for `unknown_book`. */
+  suggestions: BookSuggestion[];
+}
+
+export function isReferenceError(err: unknown): err is ReferenceError {
+  return typeof err === 'object' && err !== null && 'kind' in err && 'suggestions' in err;
+}
+
+/**
+ * Every reference in `text`, rejecting with a `ReferenceError` if any part
+ * doesn't read.
+ */
+export async function checkReferences(
+  text: string,
+  aliases?: Record<string, string>,
+): Promise<ScriptureReference[]> {
+  return invoke<ScriptureReference[]>('check_references', { text, aliases });
+}
+
+/**
+ * `text` with its misspelled book corrected, best first ("Galations 5" →
+ * "Galatians 5"); empty when it reads, or nothing close names a book.
+ */
+export async function suggestReferences(text: string): Promise<string[]> {
+  if (!isTauri() || !text.trim()) return [];
+  try {
+    const { bookAliases } = await getPreferences();
+    await checkReferences(text, bookAliases);
+    return [];
+  } catch (err) {
+    if (!isReferenceError(err)) {
+      console.error('[references] check_references failed', err);
+      return [];
+    }
+    return err.suggestions.map((s) => text.replace(err.text, s.reference));
+  }
+}
+
+/**
+ * The first verse `text` refers to, when it starts with a verse reference.
+ * Outside the app (or if the native parser fails) falls back to the simple
+ * "Book C:V" / OSIS forms.
+ */
+export async function findVerseReference(text: string): Promise<VerseRef | null> {
+  if (!text.trim()) return null;
+  if (isTauri()) {
+    try {
+      const { bookAliases } = await getPreferences();
+      const [first] = await parseReferences(text, bookAliases);
+      if (!first) return null;
+      return first.verse != null ? { book: first.book, chapter: first.chapter, verse: first.verse } : null;
+    } catch (err) {
+      console.error('[references] parse_references failed', err);
+    }
+  }
+  return parseVerseRef(text.trim());
+}
+
+/**
+ * The verse reference on the clipboard, if it holds one (desktop). A found
+ * reference is also sent to `onClipboardReference` listeners.
+ */
+export async function lookupClipboardReference(): Promise<ScriptureReference | null> {
+  return invoke<ScriptureReference | null>('lookup_clipboard_reference');
+}
+
+/**
+ * Make `shortcut` (e.g. "CmdOrCtrl+Shift+L") the system-wide shortcut that
+ * looks up the clipboard; empty or undefined turns it off.
+ */
+export async function setClipboardShortcut(shortcut?: string): Promise<void> {
+  await invoke('set_clipboard_shortcut', { shortcut: shortcut || null });
+}
+
+/** Subscribe to `clipboard://reference`, emitted when a clipboard lookup finds a reference. */
+export async function onClipboardReference(
+  listener: (reference: ScriptureReference) => void,
+): Promise<() => void> {
+  return listen<ScriptureReference>('clipboard://reference', (event) => listener(event.payload));
+}
+
+/**
+ * `ref` written the way `citation` cites references: full names,
+ * abbreviations or compact forms, in its language and with its separators.
+ */
+export async function formatReference(
+  ref: ScriptureReference | VerseRef,
+  citation: CitationPreferences = {},
+): Promise<string> {
+  const reference = 'endChapter' in ref
+    ? ref
+    : { ...ref, endChapter: ref.chapter, endVerse: ref.verse };
+  return invoke<string>('format_reference', {
+    reference,
+    style: citation.style,
+    locale: citation.locale,
+    separators: citation.separators,
+  });
+}
+
+type RangeOperation = 'union' | 'intersection' | 'contains' | 'splitByChapter';
+
+function referenceRanges<T>(
+  operation: RangeOperation,
+  references: ScriptureReference[],
+  other?: ScriptureReference[],
+): Promise<T> {
+  return invoke<T>('reference_ranges', { operation, references, other });
+}
+
+/** The verses of any of `references`, merged and in canonical order. */
+export function unionReferences(references: ScriptureReference[]): Promise<ScriptureReference[]> {
+  return referenceRanges('union', references);
+}
+
+/** The verses both `a` and `b` cover, e.g. a chapter's part of a pericope. */
+export function intersectReferences(a: ScriptureReference[], b: ScriptureReference[]): Promise<ScriptureReference[]> {
+  return referenceRanges('intersection', a, b);
+}
+
+/** Whether every verse of `reference` is in one of `references`. */
+export function referencesContain(references: ScriptureReference[], reference: ScriptureReference): Promise<boolean> {
+  return referenceRanges('contains', references, [reference]);
+}
+
+/** `references` as one reference per chapter they touch. */
+export function splitReferencesByChapter(references: ScriptureReference[]): Promise<ScriptureReference[]> {
+  return referenceRanges('splitByChapter', references);
+}
@@ -0,0 +1,70 @@
+/**
+ * Resource Links
+ *
+ * Links from a note or annotation to something outside the app: a web
+ * article or commentary, a Logos resource (`logosres:` URI or `ref.ly` link)
+ * or a local file. Kept natively in the synced `resource_links` table
+ * (db/resource_links.rs), which tells the link's kind from its URL on save;
+ * `fetchResourceMetadata` has the backend read the page's title and
+ * description, kept apart from the user's own label.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type ResourceKind = 'web' | 'logos' | 'file';
+
+export type LinkedItemType = 'annotation' | 'note' | 'verseList';
+
+export interface ResourceMetadata {
+  title?: string;
+  description?: string;
+  /** The site, publisher or Logos resource it comes from. */
+  siteName?: string;
+  fetchedAt: string;
+  /** Why nothing could be fetched, when nothing could. */
+  error?: string;
+}
+
+/** A link (`ResourceLink` in Rust). */
+export interface ResourceLink {
+  id: string;
+  itemType: LinkedItemType;
+  itemId: string;
+  /** A bare absolute path is stored as a `file://` URL. */
+  url: string;
+  /** Set by the backend from `url`. */
+  kind?: ResourceKind;
+  /** The user's caption. */
+  label?: string;
+  /** Dropped when the URL changes. */
+  metadata?: ResourceMetadata;
+  /** ISO timestamps, set by the backend. */
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+/** What to show for a link: the user's label, the fetched title, or the URL. */
+export function resourceLinkTitle(link: ResourceLink): string {
+  return link.label || link.metadata?.title || link.url;
+}
+
+export async function getResourceLinks(itemType: LinkedItemType, itemId: string): Promise<ResourceLink[]> {
+  return invoke<ResourceLink[]>('get_resource_links', { itemType, itemId });
+}
+
+export async function createResourceLink(link: Omit<ResourceLink, 'id'> & { id?: string }): Promise<ResourceLink> {
+  return invoke<ResourceLink>('create_resource_link', { link: { ...link, id: link.id ?? crypto.randomUUID() } });
+}
+
+export async function updateResourceLink(link: ResourceLink): Promise<ResourceLink> {
+  return invoke<ResourceLink>('update_resource_link', { link });
+}
+
+export async function deleteResourceLink(id: string): Promise<boolean> {
+  return invoke<boolean>('delete_resource_link', { id });
+}
+
+/** Fetch the link's title and description. A page that can't be read comes back with `metadata.error` set. */
+export async function fetchResourceMetadata(id: string): Promise<ResourceLink> {
+  return invoke<ResourceLink>('fetch_resource_metadata', { id });
+}
@@ -1,5 +1,13 @@
-import { describe, it, expect } from 'vitest';
-import { parseVerseReference } from './search';
+import { describe, it, expect, vi } from 'vitest';
+import { parseVerseReference, searchNotes } from './search';
+
+const db = vi.hoisted(() => ({
+  searchFulltext: vi.fn(),
+  getAllNotes: vi.fn(),
+}));
+
+vi.mock('./database', () => db);
+vi.mock('./bible-api', () => ({ searchModuleText: vi.fn(), fetchChapter: vi.fn() }));
 
 describe('parseVerseReference', () => {
   it('parses OSIS format (Gen.1.1)', () => {
@@ -54,3 +62,37 @@ describe('parseVerseReference', () => {
     expect(result).toEqual({ book: 'Gen', chapter: 1, verse: 1 });
   });
 });
+
+describe('searchNotes', () => {
+  it('maps ranked full-text hits to note results', async () => {
+    db.searchFulltext.mockResolvedValueOnce([
+      {
+        kind: 'note', sourceId: 'n1', moduleId: 'kjv', book: 'John', chapter: 1, verse: 29,
+        text: 'The lamb points to the Passover', snippet: 'The lamb points…',
+        highlights: [[4, 8]], rank: -1.2,
+      },
+    ]);
+
+    const results = await searchNotes('lamb', 'kjv');
+
+    expect(db.searchFulltext).toHaveBeenCalledWith('lamb', 'notes', 'kjv', 100);
+    expect(results).toEqual([{
+      type: 'note', book: 'John', chapter: 1, verse: 29,
+      text: 'The lamb points to the Passover', context: 'The lamb points…',
+      moduleId: 'kjv', noteId: 'n1',
+    }]);
+    expect(db.getAllNotes).not.toHaveBeenCalled();
+  });
+
+  it('falls back to scanning notes when the index is unavailable', async () => {
+    db.searchFulltext.mockRejectedValueOnce({ kind: 'sqlite', message: 'no such table: search_index' });
+    db.getAllNotes.mockResolvedValueOnce([
+      { id: 'n2', moduleId: 'kjv', ref: { book: 'Gen', chapter: 1, verse: 1 }, content: 'In the beginning' },
+    ]);
+    vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+    const results = await searchNotes('beginning');
+
+    expect(results.map(r => r.noteId)).toEqual(['n2']);
+  });
+});
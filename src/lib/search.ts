@@ -14,9 +14,12 @@ import {
   getAllConclusions,
   getAllInterpretations,
   getAllApplications,
+  searchFulltext,
 } from './database';
+import type { FulltextHit } from './database';
 import { searchModuleText, fetchChapter } from './bible-api';
 import { parseVerseRef } from '@/types';
+import { findVerseReference } from './references';
 import type { VerseRef } from '@/types';
 
 export interface SearchResult {
@@ -67,6 +70,31 @@ async function searchCachedChapters(
   query: string,
   moduleId?: string,
   limit = 100
+): Promise<SearchResult[]> {
+  try {
+    const hits = await searchFulltext(query, 'bible', moduleId, limit);
+    return hits.map(hit => ({
+      ...hitLocation(hit),
+      type: 'verse' as const,
+      text: hit.text,
+      context: hit.snippet,
+      moduleId: hit.moduleId ?? undefined,
+    }));
+  } catch (error) {
+    // Index unavailable (e.g. its migration failed) — fall back to scanning.
+    console.warn('[Search] Full-text index unavailable, scanning cache:', error);
+    return scanCachedChapters(query, moduleId, limit);
+  }
+}
+
+function hitLocation(hit: FulltextHit): Pick<SearchResult, 'book' | 'chapter' | 'verse'> {
+  return { book: hit.book ?? '', chapter: hit.chapter ?? 0, verse: hit.verse ?? 0 };
+}
+
+async function scanCachedChapters(
+  query: string,
+  moduleId?: string,
+  limit = 100
 ): Promise<SearchResult[]> {
   const normalizedQuery = query.toLowerCase();
   const results: SearchResult[] = [];
@@ -114,6 +142,20 @@ export async function searchNotes(
 ): Promise<SearchResult[]> {
   if (!query.trim()) return [];
 
+  try {
+    const hits = await searchFulltext(query, 'notes', moduleId, limit);
+    return hits.map(hit => ({
+      ...hitLocation(hit),
+      type: 'note' as const,
+      text: hit.text,
+      context: hit.snippet,
+      moduleId: hit.moduleId ?? undefined,
+      noteId: hit.sourceId,
+    }));
+  } catch (error) {
+    console.warn('[Search] Full-text index unavailable, scanning notes:', error);
+  }
+
   const normalizedQuery = query.toLowerCase();
   const results: SearchResult[] = [];
 
@@ -336,7 +378,7 @@ export async function searchAll(
   if (!query.trim()) return [];
 
   // First, check if query is a verse reference (but not for chapter scope)
-  const verseRef = scope !== 'chapter' ? parseVerseReference(query) : null;
+  const verseRef = scope !== 'chapter' ? await findVerseReference(query) : null;
   if (verseRef) {
     return searchVerseReference(verseRef, scope, moduleId, limit);
   }
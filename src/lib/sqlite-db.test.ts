@@ -19,6 +19,9 @@ const state = vi.hoisted(() => ({
   schemaVersion: 11 as number | null,
   // Rows returned for "SELECT updated_at FROM <table> WHERE id = ?"
   existingRow: null as { updated_at: string } | null,
+  // Row returned for "SELECT deleted_at FROM sync_tombstones ..."
+  tombstone: null as { deleted_at: string } | null,
+  integrity: 'ok',
 }));
 
 vi.mock('@tauri-apps/api/core', () => ({
@@ -33,7 +36,7 @@ vi.mock('@tauri-apps/plugin-sql', () => {
     }),
     select: vi.fn(async (sql: string) => {
       if (sql.includes('integrity_check')) {
-        return [{ integrity_check: 'ok' }];
+        return [{ integrity_check: state.integrity }];
       }
       if (sql.includes('FROM schema_version')) {
         return state.schemaVersion === null ? [] : [{ version: state.schemaVersion }];
@@ -44,6 +47,9 @@ vi.mock('@tauri-apps/plugin-sql', () => {
       if (sql.includes('PRAGMA table_info')) {
         return [{ name: 'id' }, { name: 'data' }, { name: 'study_id' }];
       }
+      if (sql.includes('FROM sync_tombstones')) {
+        return state.tombstone ? [state.tombstone] : [];
+      }
       if (sql.includes('SELECT updated_at FROM')) {
         return state.existingRow ? [state.existingRow] : [];
       }
@@ -69,6 +75,8 @@ beforeEach(() => {
   state.executeCalls = [];
   state.schemaVersion = 11;
   state.existingRow = null;
+  state.tombstone = null;
+  state.integrity = 'ok';
 });
 
 describe('applyRemoteChange', () => {
@@ -185,8 +193,10 @@ describe('applyRemoteChange', () => {
  * previously untested.
  */
 describe('applyRemoteChange — structured tables', () => {
-  it('upserts annotations as synced with the remote timestamp and device', async () => {
+  it('upserts annotations natively as synced with the remote timestamp and device', async () => {
     const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke).mockClear();
     const obj = {
       id: 'ann-1', moduleId: 'NASB', type: 'highlight', presetId: 'p1',
       createdAt: '2026-01-01T00:00:00.000Z',
@@ -195,12 +205,11 @@ describe('applyRemoteChange — structured tables', () => {
 
     await mod.applyRemoteChange('annotations', 'upsert', 'ann-1', data, '2026-01-02T00:00:00.000Z', 'remote-dev');
 
-    const inserts = findCalls(/INSERT OR REPLACE INTO annotations/);
-    expect(inserts).toHaveLength(1);
-    expect(inserts[0].params).toEqual([
-      'ann-1', 'NASB', 'highlight', data, 'p1',
-      '2026-01-01T00:00:00.000Z', '2026-01-02T00:00:00.000Z', 'synced', 'remote-dev',
-    ]);
+    expect(invoke).toHaveBeenCalledWith('update_annotation', {
+      annotation: obj,
+      remote: { updatedAt: '2026-01-02T00:00:00.000Z', deviceId: 'remote-dev' },
+    });
+    expect(findCalls(/INSERT OR REPLACE INTO annotations/)).toHaveLength(0);
   });
 
   it('normalizes legacy bookScope/chapterScope into scopes for marking_presets', async () => {
@@ -251,7 +260,7 @@ describe('schema migration v11', () => {
 
     const versionWrites = findCalls(/INSERT OR REPLACE INTO schema_version/);
     expect(versionWrites).toHaveLength(1);
-    expect(versionWrites[0].params?.[0]).toBe(11);
+    expect(versionWrites[0].params?.[0]).toBe(13);
   });
 
   it('does not run the backfill when already at v11', async () => {
@@ -263,6 +272,185 @@ describe('schema migration v11', () => {
   });
 });
 
+describe('tombstones', () => {
+  it('records a tombstone for a remote delete even when the row is absent', async () => {
+    const mod = await loadModule();
+
+    const applied = await mod.applyRemoteChange(
+      'annotations', 'delete', 'ann-1', null, '2026-02-01T00:00:00.000Z', 'remote-dev'
+    );
+
+    expect(applied).toBe(true);
+    const tombstones = findCalls(/INSERT INTO sync_tombstones/);
+    expect(tombstones).toHaveLength(1);
+    expect(tombstones[0].params).toEqual(['annotations', 'ann-1', '2026-02-01T00:00:00.000Z', 'remote-dev']);
+  });
+
+  it('applies a remote delete natively, off the undo journal', async () => {
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+
+    await mod.applyRemoteChange(
+      'notes', 'delete', 'note-1', null, '2026-02-01T00:00:00.000Z', 'remote-dev'
+    );
+
+    expect(invoke).toHaveBeenCalledWith('apply_remote_delete', { tableName: 'notes', rowId: 'note-1' });
+    expect(findCalls(/DELETE FROM notes/)).toHaveLength(0);
+  });
+
+  it('does not resurrect a deleted row from an older upsert', async () => {
+    const mod = await loadModule();
+    state.tombstone = { deleted_at: '2026-02-01T00:00:00.000Z' };
+
+    const applied = await mod.applyRemoteChange(
+      'interpretations', 'upsert', 'int-1', JSON.stringify({ id: 'int-1' }), '2026-01-15T00:00:00.000Z', 'remote-dev'
+    );
+
+    expect(applied).toBe(false);
+    expect(findCalls(/INSERT OR REPLACE INTO interpretations/)).toHaveLength(0);
+  });
+
+  it('applies an upsert newer than the tombstone and clears it', async () => {
+    const mod = await loadModule();
+    state.tombstone = { deleted_at: '2026-02-01T00:00:00.000Z' };
+
+    const applied = await mod.applyRemoteChange(
+      'interpretations', 'upsert', 'int-1', JSON.stringify({ id: 'int-1' }), '2026-03-01T00:00:00.000Z', 'remote-dev'
+    );
+
+    expect(applied).toBe(true);
+    expect(findCalls(/INSERT OR REPLACE INTO interpretations/)).toHaveLength(1);
+    expect(findCalls(/DELETE FROM sync_tombstones/)).toHaveLength(1);
+  });
+
+  it('prunes tombstones older than the retention window', async () => {
+    const mod = await loadModule();
+    await mod.pruneTombstones(30);
+
+    const prunes = findCalls(/DELETE FROM sync_tombstones WHERE deleted_at < \?/);
+    expect(prunes).toHaveLength(1);
+    const cutoff = Date.parse(prunes[0].params?.[0] as string);
+    expect(Date.now() - cutoff).toBeGreaterThanOrEqual(30 * 24 * 60 * 60 * 1000 - 1000);
+  });
+});
+
+describe('schema migration v12', () => {
+  it('creates the sync_tombstones table', async () => {
+    state.schemaVersion = 11;
+    const mod = await loadModule();
+    await mod.getSqliteDb();
+
+    expect(findCalls(/CREATE TABLE IF NOT EXISTS sync_tombstones/)).toHaveLength(1);
+  });
+});
+
+describe('corruption recovery', () => {
+  it('repairs a corrupt database instead of deleting it', async () => {
+    state.integrity = '*** in database main *** Page 10: btree page corrupt';
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    // get_database_mode, then repair_database, are the first commands init invokes
+    vi.mocked(invoke).mockClear().mockResolvedValueOnce(null).mockResolvedValueOnce({
+      tables: [], lostObjects: [], corruptCopy: '/data/biblemarker.corrupt.db',
+    });
+    const warnSpy = vi.spyOn(console, 'warn').mockImplementation(() => {});
+
+    await mod.getSqliteDb();
+
+    expect(invoke).toHaveBeenCalledWith('repair_database');
+    expect(invoke).not.toHaveBeenCalledWith('delete_local_database');
+    warnSpy.mockRestore();
+  });
+
+  it('falls back to deleting when the repair fails', async () => {
+    state.integrity = 'file is not a database';
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke)
+      .mockClear()
+      .mockResolvedValueOnce(null)
+      .mockRejectedValueOnce({ kind: 'corrupt', message: 'file is not a database' });
+    const warnSpy = vi.spyOn(console, 'warn').mockImplementation(() => {});
+    const errorSpy = vi.spyOn(console, 'error').mockImplementation(() => {});
+
+    await mod.getSqliteDb();
+
+    expect(invoke).toHaveBeenCalledWith('delete_local_database');
+    warnSpy.mockRestore();
+    errorSpy.mockRestore();
+  });
+});
+
+describe('native migrations', () => {
+  it('runs the Rust migrations after the baseline schema', async () => {
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    await mod.getSqliteDb();
+
+    expect(invoke).toHaveBeenCalledWith('run_database_migrations');
+  });
+
+  it('keeps opening the database when a native migration fails', async () => {
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke)
+      .mockResolvedValueOnce(null)
+      .mockRejectedValueOnce({ kind: 'migration', message: 'Migration 14 failed' });
+    const errorSpy = vi.spyOn(console, 'error').mockImplementation(() => {});
+
+    await expect(mod.getSqliteDb()).resolves.toBeDefined();
+    expect(errorSpy).toHaveBeenCalled();
+    errorSpy.mockRestore();
+  });
+});
+
+describe('demo mode', () => {
+  it('opens and seeds the throwaway database the backend names', async () => {
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    const { default: Database } = await import('@tauri-apps/plugin-sql');
+    vi.mocked(invoke)
+      .mockClear()
+      .mockResolvedValueOnce({ demo: true, path: '/tmp/biblemarker-demo-1/biblemarker.db' });
+    vi.mocked(Database.load).mockClear();
+
+    await mod.getSqliteDb();
+
+    expect(Database.load).toHaveBeenCalledWith('sqlite:/tmp/biblemarker-demo-1/biblemarker.db');
+    expect(invoke).toHaveBeenCalledWith('seed_demo_database');
+    await expect(mod.isDemoDatabase()).resolves.toBe(true);
+  });
+
+  it('uses the real database otherwise', async () => {
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    const { default: Database } = await import('@tauri-apps/plugin-sql');
+    vi.mocked(invoke).mockClear();
+    vi.mocked(Database.load).mockClear();
+
+    await expect(mod.isDemoDatabase()).resolves.toBe(false);
+    expect(Database.load).toHaveBeenCalledWith('sqlite:biblemarker.db');
+    expect(invoke).not.toHaveBeenCalledWith('seed_demo_database');
+  });
+});
+
+describe('sync history', () => {
+  it('inserts a cycle and trims to the newest rows', async () => {
+    const mod = await loadModule();
+    await mod.recordSyncHistory({
+      started_at: '2026-01-01T00:00:00.000Z', duration_ms: 120, direction: 'pull',
+      pushed: 0, pulled: 3, bytes_up: 0, bytes_down: 2048, result: 'ok', error: null,
+    });
+
+    const inserts = findCalls(/INSERT INTO sync_history/);
+    expect(inserts).toHaveLength(1);
+    expect(inserts[0].params).toEqual([
+      '2026-01-01T00:00:00.000Z', 120, 'pull', 0, 3, 0, 2048, 'ok', null,
+    ]);
+    expect(findCalls(/DELETE FROM sync_history/)).toHaveLength(1);
+  });
+});
+
 describe('sqliteClearDatabase', () => {
   it('clears entity_notes and keyword_exclusions along with the other data tables', async () => {
     // The shared test setup defines a partial window without dispatchEvent.
@@ -280,3 +468,41 @@ describe('sqliteClearDatabase', () => {
     }
   });
 });
+
+describe('native annotation commands', () => {
+  it('revives dates on chapter annotations returned by the backend', async () => {
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke).mockResolvedValueOnce([
+      {
+        id: 'a1', moduleId: 'kjv', type: 'highlight',
+        startRef: { book: 'John', chapter: 3, verse: 16 },
+        endRef: { book: 'John', chapter: 3, verse: 16 },
+        color: 'yellow',
+        createdAt: '2025-01-01T00:00:00.000Z', updatedAt: '2025-01-02T00:00:00.000Z',
+      },
+    ]);
+    const anns = await mod.sqliteGetChapterAnnotations('kjv', 'John', 3);
+    expect(invoke).toHaveBeenCalledWith('get_chapter_annotations', { moduleId: 'kjv', book: 'John', chapter: 3 });
+    expect(anns[0].createdAt).toBeInstanceOf(Date);
+    expect(anns[0].updatedAt.toISOString()).toBe('2025-01-02T00:00:00.000Z');
+  });
+
+  it('skips the backend for an empty batch', async () => {
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke).mockClear();
+    expect(await mod.sqliteSaveAnnotations([])).toBe(0);
+    expect(invoke).not.toHaveBeenCalledWith('db_bulk_insert_markings', expect.anything());
+  });
+
+  it('sends mixed marking batches as one bulk_apply_markings payload', async () => {
+    const mod = await loadModule();
+    const { invoke } = await import('@tauri-apps/api/core');
+    const result = { saved: 0, deleted: 1, failed: 0, results: [{ index: 0, id: 'a1', status: 'deleted', error: null }] };
+    vi.mocked(invoke).mockClear().mockResolvedValueOnce(result);
+    const operations = [{ op: 'delete' as const, id: 'a1' }];
+    expect(await mod.sqliteApplyMarkings(operations)).toEqual(result);
+    expect(invoke).toHaveBeenCalledWith('bulk_apply_markings', { payload: { operations } });
+  });
+});
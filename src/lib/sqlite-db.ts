@@ -7,6 +7,7 @@
 
 import Database from '@tauri-apps/plugin-sql';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import type {
   Annotation,
   SectionHeading,
@@ -26,6 +27,16 @@ import type { ApplicationEntry } from '@/types';
 import type { EntityNote } from '@/types';
 import type { KeywordExclusion } from '@/types';
 import type { UserPreferences } from '@/types';
+import type { PlaybackPosition } from './audio';
+import type { LibrarySymbol } from './symbolLibrary';
+import type { Tag, TagLink } from './tags';
+import type { Bookmark, BookmarkFolder } from './bookmarks';
+import type { VerseCollection } from './collections';
+import type { Workspace } from './workspaces';
+import type { HighlightStyle } from './highlightStyles';
+import type { MarkingTemplate } from './markingTemplates';
+import type { MarginNote } from './marginNotes';
+import type { ResourceLink } from './resourceLinks';
 import {
   VALID_TABLE_NAMES,
   SYNCED_TABLES,
@@ -46,6 +57,14 @@ let sqliteDb: Database | null = null;
 let dbInitPromise: Promise<Database> | null = null;
 let cachedDeviceId: string | null = null;
 
+/** Which database the backend uses (`DatabaseMode` in Rust). */
+export interface DatabaseMode {
+  demo: boolean;
+  path: string;
+}
+
+let databaseMode: DatabaseMode | null = null;
+
 /**
  * Get or initialize the SQLite database connection.
  * Uses a promise singleton so concurrent callers all wait for the same init,
@@ -59,36 +78,62 @@ export async function getSqliteDb(): Promise<Database> {
 }
 
 async function initSqliteDb(): Promise<Database> {
+  // Started with --demo, the backend keeps a throwaway database in the temp
+  // dir; open that one instead of the user's.
+  databaseMode = await invoke<DatabaseMode | null>('get_database_mode').catch(() => null);
+  const demo = databaseMode?.demo === true;
   // Always use local storage — sync is handled by journal files
-  const dbPath = 'sqlite:biblemarker.db';
-  console.log('[SQLite] Using local database');
+  const dbPath = demo ? `sqlite:${databaseMode!.path}` : 'sqlite:biblemarker.db';
+  console.log(demo ? `[SQLite] Using demo database at ${databaseMode!.path}` : '[SQLite] Using local database');
 
   // Connect to SQLite database
   sqliteDb = await Database.load(dbPath);
 
-  // Run integrity check — if corrupt (e.g. from bad iCloud migration), wipe and start fresh
+  // Run integrity check — if corrupt (e.g. from bad iCloud migration), salvage
+  // what can be read into a fresh file, or wipe and start fresh if even that fails
   const isHealthy = await checkDatabaseIntegrity(sqliteDb);
   if (!isHealthy) {
-    console.warn('[SQLite] Corrupt database detected, deleting and starting fresh');
+    console.warn('[SQLite] Corrupt database detected, attempting repair');
     await sqliteDb.close();
     sqliteDb = null;
-    // Delete the corrupt database files via Rust command
     try {
-      await invoke('delete_local_database');
-      console.log('[SQLite] Corrupt database files deleted');
-    } catch (e) {
-      console.error('[SQLite] Failed to delete corrupt database:', e);
+      const report = await invoke<RepairReport>('repair_database');
+      console.log('[SQLite] Database repaired; damaged original kept at', report.corruptCopy, report);
+    } catch (repairError) {
+      console.error('[SQLite] Repair failed, deleting corrupt database:', repairError);
+      // Delete the corrupt database files via Rust command
+      try {
+        await invoke('delete_local_database');
+        console.log('[SQLite] Corrupt database files deleted');
+      } catch (e) {
+        console.error('[SQLite] Failed to delete corrupt database:', e);
+      }
     }
-    // Re-open — the SQL plugin will create a fresh empty database
+    // Re-open — the SQL plugin will create a fresh empty database if it was deleted
     sqliteDb = await Database.load(dbPath);
   }
 
   // Initialize schema (sets cachedDeviceId via initDeviceId)
   await initializeSchema(sqliteDb);
 
+  if (demo) {
+    const seeded = await invoke<number>('seed_demo_database');
+    if (seeded > 0) console.log(`[SQLite] Seeded demo database with ${seeded} sample rows`);
+  }
+
   return sqliteDb;
 }
 
+/**
+ * Whether this run uses the throwaway demo database (`--demo`). Sync and
+ * auto-backups stay off then, so a demo never reaches the user's account or
+ * backup folder.
+ */
+export async function isDemoDatabase(): Promise<boolean> {
+  await getSqliteDb();
+  return databaseMode?.demo === true;
+}
+
 /**
  * Run a quick integrity check on the database.
  * Returns true if healthy, false if corrupt.
@@ -110,6 +155,184 @@ async function checkDatabaseIntegrity(db: Database): Promise<boolean> {
   }
 }
 
+export type NativeDbErrorKind =
+  | 'not_found' | 'busy' | 'corrupt' | 'invalid' | 'io' | 'migration' | 'schema_too_new'
+  | 'database_locked' | 'sqlite';
+
+/** Error thrown by native database commands (`DbError` in Rust). */
+export interface NativeDbError {
+  kind: NativeDbErrorKind;
+  message: string;
+}
+
+export function isNativeDbError(error: unknown): error is NativeDbError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    'kind' in error &&
+    'message' in error &&
+    !('statusCode' in error)
+  );
+}
+
+/** How long native connections wait on a lock before failing with `busy`. */
+export async function sqliteSetBusyTimeout(timeoutMs: number): Promise<void> {
+  await invoke('set_database_busy_timeout', { timeoutMs });
+}
+
+/** Whether native writes are paused (they fail `database_locked` meanwhile). */
+export interface WriteLockState {
+  locked: boolean;
+  reason: string | null;
+  /** Unix milliseconds. */
+  lockedAt: number | null;
+}
+
+/** Pause or resume native writes, e.g. while sync downloads data to apply. */
+export async function sqliteSetWriteLock(locked: boolean, reason?: string): Promise<WriteLockState> {
+  return invoke<WriteLockState>('set_db_write_lock', { locked, reason: reason ?? null });
+}
+
+export async function sqliteGetWriteLockState(): Promise<WriteLockState> {
+  return invoke<WriteLockState>('get_db_write_lock_state');
+}
+
+/** Subscribe to `db://writable`, emitted when a write lock is released. */
+export async function onDatabaseWritable(listener: (state: WriteLockState) => void): Promise<() => void> {
+  return listen<WriteLockState>('db://writable', event => listener(event.payload));
+}
+
+export interface CheckpointResult {
+  busy: boolean;
+  logFrames: number;
+  checkpointedFrames: number;
+}
+
+/** `PRAGMA wal_checkpoint(TRUNCATE)` via the native layer. */
+export async function sqliteCheckpoint(): Promise<CheckpointResult> {
+  await getSqliteDb();
+  return invoke<CheckpointResult>('checkpoint_database');
+}
+
+export interface IntegrityReport {
+  ok: boolean;
+  mode: 'quick_check' | 'integrity_check';
+  problems: string[];
+}
+
+export interface RepairReport {
+  tables: { table: string; recovered: number; lost: number; complete: boolean }[];
+  lostObjects: string[];
+  corruptCopy: string;
+}
+
+/** Native `PRAGMA quick_check` (or `integrity_check` when `full`). */
+export async function sqliteCheckIntegrity(full = false): Promise<IntegrityReport> {
+  await getSqliteDb();
+  return invoke<IntegrityReport>('check_database_integrity', { full });
+}
+
+/**
+ * Rebuild the database from whatever is still readable. The plugin
+ * connection is closed for the swap and reopened (and migrated) afterwards.
+ */
+export async function sqliteRepairDatabase(): Promise<RepairReport> {
+  await closeSqliteDb();
+  try {
+    return await invoke<RepairReport>('repair_database');
+  } finally {
+    await getSqliteDb();
+  }
+}
+
+export interface DatabaseStats {
+  fileBytes: number;
+  walBytes: number;
+  pageSize: number;
+  pageCount: number;
+  /** Pages a VACUUM (`sqliteMaintain`) would give back. */
+  freePages: number;
+  lastVacuumAt: string | null;
+  /** Annotation counts by type, e.g. `{ highlight: 3412, symbol: 80 }`. */
+  annotations: Record<string, number>;
+  tables: { name: string; rows: number; bytes: number }[];
+  indexes: { name: string; table: string; bytes: number }[];
+}
+
+/** Row counts, sizes and free space from the native layer. */
+export async function sqliteGetDatabaseStats(): Promise<DatabaseStats> {
+  await getSqliteDb();
+  return invoke<DatabaseStats>('get_database_stats');
+}
+
+/** What {@link sqliteExportSqlDump} wrote or {@link sqliteImportSqlDump} loaded. */
+export interface SqlDumpReport {
+  path: string;
+  schemaVersion: number;
+  createdAt: string;
+  tables: { table: string; rows: number }[];
+}
+
+/** Write the synced user data to `path` as a plain-text SQL dump. */
+export async function sqliteExportSqlDump(path: string): Promise<SqlDumpReport> {
+  await getSqliteDb();
+  return invoke<SqlDumpReport>('export_sql_dump', { path });
+}
+
+/** Replace the tables a SQL dump names with its rows, all or nothing. */
+export async function sqliteImportSqlDump(path: string): Promise<SqlDumpReport> {
+  await getSqliteDb();
+  return invoke<SqlDumpReport>('import_sql_dump', { path });
+}
+
+/** A rolling snapshot the backend took of the user data (`Snapshot` in Rust). */
+export interface Snapshot {
+  id: string;
+  createdAt: string;
+  bytes: number;
+}
+
+/** Rolling snapshots, newest first. */
+export async function sqliteListSnapshots(): Promise<Snapshot[]> {
+  await getSqliteDb();
+  return invoke<Snapshot[]>('list_snapshots');
+}
+
+/** Replace the user data with a snapshot; the current data is snapshotted first. */
+export async function sqliteRestoreSnapshot(id: string): Promise<SqlDumpReport> {
+  await getSqliteDb();
+  return invoke<SqlDumpReport>('restore_snapshot', { id });
+}
+
+/** What the backend did at startup after finding the database corrupt. */
+export interface DatabaseRecovery {
+  problems: string[];
+  quarantinedPath: string;
+  /** Newest verified auto-backup, to be restored into the fresh database. */
+  backupPath: string | null;
+  backupTimestamp: string | null;
+  /** Set instead of `backupPath` when no backup verified and rows were salvaged. */
+  salvaged: RepairReport['tables'] | null;
+}
+
+/** Startup recovery details; returned once, null when the database was healthy. */
+export async function sqliteTakeRecovery(): Promise<DatabaseRecovery | null> {
+  return invoke<DatabaseRecovery | null>('take_database_recovery');
+}
+
+export interface MaintenanceReport {
+  bytesBefore: number;
+  bytesAfter: number;
+  reclaimedBytes: number;
+  ranAt: string;
+}
+
+/** VACUUM + ANALYZE + `PRAGMA optimize` via the native layer. */
+export async function sqliteMaintain(): Promise<MaintenanceReport> {
+  await getSqliteDb();
+  return invoke<MaintenanceReport>('maintain_database');
+}
+
 /**
  * Close the database connection
  */
@@ -118,13 +341,15 @@ export async function closeSqliteDb(): Promise<void> {
     await sqliteDb.close();
     sqliteDb = null;
   }
+  // Let the next getSqliteDb() reopen instead of returning the closed handle.
+  dbInitPromise = null;
 }
 
 // ============================================================================
 // Schema Initialization
 // ============================================================================
 
-const SCHEMA_VERSION = 11;
+const SCHEMA_VERSION = 13;
 
 /**
  * Safety net: ensure all expected tables exist.
@@ -197,10 +422,43 @@ async function initializeSchema(db: Database): Promise<void> {
   // Safety net: ensure tables exist even if version was bumped without creating them
   await ensureTablesExist(db);
 
+  // Versions after the TS baseline are embedded in the Rust backend
+  // (src-tauri/src/db/migrations). A failure leaves the database at its
+  // previous version, which this build still reads fine, so log and go on.
+  await runNativeMigrations();
+
   // Load or generate device ID from sync_config (local SQLite, not iCloud-synced localStorage)
   await initDeviceId(db);
 }
 
+/** Outcome of the native migration runner (`MigrationReport` in Rust). */
+export interface MigrationReport {
+  fromVersion: number;
+  toVersion: number;
+  applied: { version: number; name: string }[];
+  dryRun: boolean;
+}
+
+async function runNativeMigrations(): Promise<void> {
+  try {
+    const report = await invoke<MigrationReport | null>('run_database_migrations');
+    if (report && report.applied.length > 0) {
+      console.log(`[SQLite] Migrated schema ${report.fromVersion} -> ${report.toVersion}`);
+    }
+  } catch (error) {
+    console.error('[SQLite] Native schema migration failed:', error);
+  }
+}
+
+/**
+ * Check which native migrations are pending by running them in a
+ * rolled-back transaction.
+ */
+export async function dryRunMigrations(): Promise<MigrationReport> {
+  await getSqliteDb();
+  return invoke<MigrationReport>('dry_run_database_migrations');
+}
+
 /**
  * Load or generate a device-unique ID stored in sync_config.
  * localStorage is NOT safe for this because iCloud syncs WebKit localStorage across devices.
@@ -456,6 +714,42 @@ async function migrateSchema(
     console.log('[SQLite] v11 migration: backfilled study_id columns from JSON data');
   }
 
+  // Version 12: Tombstones. A deleted row leaves (table, id, deleted_at) behind
+  // so an older upsert arriving later from another device can't resurrect it.
+  if (fromVersion < 12) {
+    await db.execute(`
+      CREATE TABLE IF NOT EXISTS sync_tombstones (
+        table_name TEXT NOT NULL,
+        row_id TEXT NOT NULL,
+        deleted_at TEXT NOT NULL,
+        device_id TEXT NOT NULL,
+        PRIMARY KEY (table_name, row_id)
+      )
+    `);
+    await db.execute(`CREATE INDEX IF NOT EXISTS idx_sync_tombstones_deleted_at ON sync_tombstones(deleted_at)`);
+    console.log('[SQLite] v12 migration: added sync_tombstones table');
+  }
+
+  // Version 13: Sync history — one row per sync cycle, for "when did this
+  // device last pull?" debugging. Local bookkeeping only, never synced.
+  if (fromVersion < 13) {
+    await db.execute(`
+      CREATE TABLE IF NOT EXISTS sync_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        started_at TEXT NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        direction TEXT NOT NULL,
+        pushed INTEGER NOT NULL DEFAULT 0,
+        pulled INTEGER NOT NULL DEFAULT 0,
+        bytes_up INTEGER NOT NULL DEFAULT 0,
+        bytes_down INTEGER NOT NULL DEFAULT 0,
+        result TEXT NOT NULL,
+        error TEXT
+      )
+    `);
+    console.log('[SQLite] v13 migration: added sync_history table');
+  }
+
   // Update schema version
   await db.execute(
     `INSERT OR REPLACE INTO schema_version (id, version, updated_at) VALUES (1, ?, ?)`,
@@ -777,25 +1071,100 @@ export async function sqliteGetChapterAnnotations(
   book: string,
   chapter: number
 ): Promise<Annotation[]> {
-  const db = await getSqliteDb();
-  const rows = await db.select<{ id: string; data: string }[]>(
-    `SELECT id, data FROM annotations WHERE module_id = ?`,
-    [moduleId]
-  );
+  // Filtered natively (src-tauri/src/db) rather than parsing every
+  // annotation of the translation here. Other translations' marks carried
+  // into this one come after its own, with `carried` set.
+  const anns = await invoke<Annotation[]>('get_chapter_annotations', { moduleId, book, chapter });
+  return anns.map((ann) => {
+    ann.createdAt = new Date(ann.createdAt);
+    ann.updatedAt = new Date(ann.updatedAt);
+    return ann;
+  });
+}
 
-  return rows
-    .map((row) => {
-      const ann = JSON.parse(row.data) as Annotation;
-      ann.createdAt = new Date(ann.createdAt);
-      ann.updatedAt = new Date(ann.updatedAt);
-      return ann;
-    })
-    .filter((ann) => {
-      if (ann.type === 'symbol') {
-        return ann.ref.book === book && ann.ref.chapter === chapter;
-      }
-      return ann.startRef.book === book && ann.startRef.chapter === chapter;
-    });
+/** Ranked hit from the native FTS5 index (`FulltextHit` in Rust). */
+export interface FulltextHit {
+  kind: 'verse' | 'note' | 'keyword';
+  sourceId: string;
+  moduleId: string | null;
+  book: string | null;
+  chapter: number | null;
+  verse: number | null;
+  text: string;
+  snippet: string;
+  /** [start, end) of each matched term within `snippet`. */
+  highlights: [number, number][];
+  rank: number;
+}
+
+export type FulltextScope = 'all' | 'bible' | 'notes' | 'keywords';
+
+export async function sqliteSearchFulltext(
+  query: string,
+  scope: FulltextScope,
+  moduleId?: string,
+  limit?: number
+): Promise<FulltextHit[]> {
+  await getSqliteDb();
+  return invoke<FulltextHit[]>('search_fulltext', { query, scope, moduleId, limit });
+}
+
+/** A note whose text mentions a passage (`ReferencingNote` in Rust). */
+export interface ReferencingNote {
+  noteId: string;
+  moduleId: string;
+  /** Where the note itself is attached. */
+  noteRef: Note['ref'];
+  content: string;
+  /** The note's references that touch the passage, e.g. "Hebrews 11:6". */
+  mentions: string[];
+  updatedAt: string;
+}
+
+export async function sqliteGetNotesReferencing(
+  book: string,
+  chapter: number,
+  verse?: number,
+  endVerse?: number
+): Promise<ReferencingNote[]> {
+  await getSqliteDb();
+  return invoke<ReferencingNote[]>('get_notes_referencing', { book, chapter, verse, endVerse });
+}
+
+/** A note linking to another, or citing its passage (`Backlink` in Rust). */
+export interface Backlink {
+  noteId: string;
+  moduleId: string;
+  noteRef: Note['ref'];
+  /** Its first line, when that is a Markdown heading. */
+  title?: string;
+  content: string;
+  /** Its `[[…]]` links naming the note, as written, e.g. "Covenant|the covenant". */
+  links: string[];
+  /** Its references touching the note's passage, e.g. "Hebrews 11:6". */
+  passages: string[];
+  updatedAt: string;
+}
+
+/** A note saying a term without linking it (`UnlinkedMention` in Rust). */
+export interface UnlinkedMention {
+  noteId: string;
+  moduleId: string;
+  noteRef: Note['ref'];
+  title?: string;
+  /** Each mention with the text around it. */
+  mentions: string[];
+  updatedAt: string;
+}
+
+export async function sqliteGetBacklinks(noteId: string): Promise<Backlink[]> {
+  await getSqliteDb();
+  return invoke<Backlink[]>('get_backlinks', { noteId });
+}
+
+export async function sqliteGetUnlinkedMentions(term: string): Promise<UnlinkedMention[]> {
+  await getSqliteDb();
+  return invoke<UnlinkedMention[]>('get_unlinked_mentions', { term });
 }
 
 /**
@@ -831,35 +1200,70 @@ interface RemoteSyncMeta {
   deviceId: string;
 }
 
+/**
+ * Save a new annotation natively (`create_annotation`), or with `remote` a
+ * change sync brought in (`update_annotation`). The backend validates it and,
+ * for a local save, logs the change, so callers must not also call
+ * recordChange.
+ */
 export async function sqliteSaveAnnotation(annotation: Annotation, remote?: RemoteSyncMeta): Promise<string> {
-  const db = await getSqliteDb();
-  const syncStatus = remote ? 'synced' : 'pending';
-  const updatedAt = remote?.updatedAt ?? toISOString(new Date());
-  const deviceId = remote?.deviceId ?? getDeviceId();
+  await getSqliteDb();
+  if (remote) {
+    await invoke('update_annotation', { annotation, remote });
+  } else {
+    await invoke('create_annotation', { annotation });
+  }
+  return annotation.id;
+}
 
-  await db.execute(
-    `INSERT OR REPLACE INTO annotations
-     (id, module_id, type, data, preset_id, created_at, updated_at, sync_status, device_id)
-     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)`,
-    [
-      annotation.id,
-      annotation.moduleId,
-      annotation.type,
-      JSON.stringify(annotation),
-      annotation.presetId ?? null,
-      toISOString(annotation.createdAt),
-      updatedAt,
-      syncStatus,
-      deviceId,
-    ]
-  );
+/** Save an edited annotation over the stored one; its change is logged natively. */
+export async function sqliteUpdateAnnotation(annotation: Annotation): Promise<Annotation> {
+  await getSqliteDb();
+  const saved = await invoke<Annotation>('update_annotation', { annotation, remote: null });
+  return { ...saved, createdAt: new Date(saved.createdAt), updatedAt: new Date(saved.updatedAt) };
+}
 
-  return annotation.id;
+/**
+ * Save many local annotations in one native transaction. The Rust side writes
+ * the rows and their change_log entries together, so callers must not also
+ * call recordChange.
+ */
+export async function sqliteSaveAnnotations(annotations: Annotation[]): Promise<number> {
+  if (annotations.length === 0) return 0;
+  const { saved } = await invoke<{ saved: number }>('db_bulk_insert_markings', { annotations });
+  return saved;
 }
 
-export async function sqliteDeleteAnnotation(id: string): Promise<void> {
-  const db = await getSqliteDb();
-  await db.execute(`DELETE FROM annotations WHERE id = ?`, [id]);
+export type MarkingOperation =
+  | { op: 'upsert'; annotation: Annotation }
+  | { op: 'delete'; id: string };
+
+export interface BulkApplyResult {
+  saved: number;
+  deleted: number;
+  failed: number;
+  /** One per operation, in order. Failed items were not written. */
+  results: {
+    index: number;
+    id: string | null;
+    status: 'saved' | 'deleted' | 'not_found' | 'failed';
+    error: string | null;
+  }[];
+}
+
+/**
+ * Apply a mixed batch of annotation upserts and deletes in one native
+ * transaction. Change logging happens in Rust, as for sqliteSaveAnnotations.
+ */
+export async function sqliteApplyMarkings(operations: MarkingOperation[]): Promise<BulkApplyResult> {
+  if (operations.length === 0) return { saved: 0, deleted: 0, failed: 0, results: [] };
+  return invoke<BulkApplyResult>('bulk_apply_markings', { payload: { operations } });
+}
+
+/** Delete an annotation natively, which logs the delete; false if there was none. */
+export async function sqliteDeleteAnnotation(id: string): Promise<boolean> {
+  await getSqliteDb();
+  return invoke<boolean>('delete_annotation', { id });
 }
 
 // ============================================================================
@@ -1085,6 +1489,68 @@ export async function sqliteDeleteNote(id: string): Promise<void> {
   await db.execute(`DELETE FROM notes WHERE id = ?`, [id]);
 }
 
+// ============================================================================
+// Trash Operations
+// ============================================================================
+
+/**
+ * A deleted note or annotation, restorable for 30 days. `item` is the row as
+ * saved (dates are ISO strings, as in change_log).
+ */
+export interface TrashItem {
+  id: number;
+  table: 'notes' | 'annotations';
+  rowId: string;
+  deletedAt: string;
+  item: Record<string, unknown>;
+}
+
+export async function sqliteListTrash(): Promise<TrashItem[]> {
+  await getSqliteDb();
+  return invoke<TrashItem[]>('list_trash');
+}
+
+/** Put a trashed item back; logged as a new local edit so it syncs. */
+export async function sqliteRestoreItem(id: number): Promise<TrashItem> {
+  return invoke<TrashItem>('restore_item', { id });
+}
+
+/** Permanently delete trashed items, only those deleted before `olderThan` if given. */
+export async function sqliteEmptyTrash(olderThan?: string): Promise<number> {
+  return invoke<number>('empty_trash', { olderThan: olderThan ?? null });
+}
+
+// ============================================================================
+// Undo Journal
+// ============================================================================
+
+/** One undoable step: the annotation/note/tag link writes made together. */
+export interface UndoStep {
+  at: string;
+  entries: { table: 'notes' | 'annotations' | 'tag_links'; rowId: string; action: 'create' | 'update' | 'delete' }[];
+}
+
+export interface UndoStack {
+  /** Most recent first. */
+  undo: UndoStep[];
+  /** Next to redo first. */
+  redo: UndoStep[];
+}
+
+/** Undo the latest annotation or note edit; null when there is none. */
+export async function sqliteUndoLast(): Promise<UndoStep | null> {
+  return invoke<UndoStep | null>('undo_last');
+}
+
+export async function sqliteRedo(): Promise<UndoStep | null> {
+  return invoke<UndoStep | null>('redo');
+}
+
+export async function sqliteGetUndoStack(): Promise<UndoStack> {
+  await getSqliteDb();
+  return invoke<UndoStack>('get_undo_stack');
+}
+
 // ============================================================================
 // Marking Preset Operations
 // ============================================================================
@@ -1514,6 +1980,28 @@ export interface SqliteExportData {
   applications: ApplicationEntry[];
   entityNotes: EntityNote[];
   keywordExclusions: KeywordExclusion[];
+  /** Absent in exports from before audio Bibles. */
+  audioPositions?: (PlaybackPosition & { id: string })[];
+  /** Absent in exports from before the symbol library. */
+  symbolLibrary?: LibrarySymbol[];
+  /** Absent in exports from before tags. */
+  tags?: Tag[];
+  tagLinks?: TagLink[];
+  /** Absent in exports from before bookmarks. */
+  bookmarkFolders?: BookmarkFolder[];
+  bookmarks?: Bookmark[];
+  /** Absent in exports from before verse collections. */
+  verseCollections?: VerseCollection[];
+  /** Absent in exports from before workspaces. */
+  workspaces?: Workspace[];
+  /** Absent in exports from before highlight styles. */
+  highlightStyles?: HighlightStyle[];
+  /** Absent in exports from before marking templates. */
+  markingTemplates?: MarkingTemplate[];
+  /** Absent in exports from before margin notes. */
+  marginNotes?: MarginNote[];
+  /** Absent in exports from before resource links. */
+  resourceLinks?: ResourceLink[];
   preferences: UserPreferences | null;
 }
 
@@ -1545,6 +2033,18 @@ export async function sqliteExportAll(): Promise<SqliteExportData> {
   const multiTranslationViews = await sqliteGetAllFromTable<MultiTranslationView>('multi_translation_views');
   const entityNotes = await sqliteGetAllFromTable<EntityNote>('entity_notes');
   const keywordExclusions = await sqliteGetAllFromTable<KeywordExclusion>('keyword_exclusions');
+  const audioPositions = await sqliteGetAllFromTable<PlaybackPosition & { id: string }>('audio_positions');
+  const symbolLibrary = await sqliteGetAllFromTable<LibrarySymbol>('symbol_library');
+  const tags = await sqliteGetAllFromTable<Tag>('tags');
+  const tagLinks = await sqliteGetAllFromTable<TagLink>('tag_links');
+  const bookmarkFolders = await sqliteGetAllFromTable<BookmarkFolder>('bookmark_folders');
+  const bookmarks = await sqliteGetAllFromTable<Bookmark>('bookmarks');
+  const verseCollections = await sqliteGetAllFromTable<VerseCollection>('verse_collections');
+  const workspaces = await sqliteGetAllFromTable<Workspace>('workspaces');
+  const highlightStyles = await sqliteGetAllFromTable<HighlightStyle>('highlight_styles');
+  const markingTemplates = await sqliteGetAllFromTable<MarkingTemplate>('marking_templates');
+  const marginNotes = await sqliteGetAllFromTable<MarginNote>('margin_notes');
+  const resourceLinks = await sqliteGetAllFromTable<ResourceLink>('resource_links');
 
   // Get headings and titles
   const headingRows = await db.select<
@@ -1633,6 +2133,18 @@ export async function sqliteExportAll(): Promise<SqliteExportData> {
     applications,
     entityNotes,
     keywordExclusions,
+    audioPositions,
+    symbolLibrary,
+    tags,
+    tagLinks,
+    bookmarkFolders,
+    bookmarks,
+    verseCollections,
+    workspaces,
+    highlightStyles,
+    markingTemplates,
+    marginNotes,
+    resourceLinks,
     preferences,
   };
 }
@@ -1645,10 +2157,8 @@ export async function sqliteImportAll(data: SqliteExportData): Promise<void> {
   // Note: tauri-plugin-sql doesn't support transactions directly,
   // so we'll just do sequential inserts
 
-  // Import annotations
-  for (const ann of data.annotations) {
-    await sqliteSaveAnnotation(ann);
-  }
+  // Import annotations (validated and written natively in one transaction)
+  await sqliteSaveAnnotations(data.annotations);
 
   // Import section headings
   for (const heading of data.sectionHeadings) {
@@ -1706,6 +2216,42 @@ export async function sqliteImportAll(data: SqliteExportData): Promise<void> {
   for (const item of data.keywordExclusions) {
     await sqliteSaveToTable('keyword_exclusions', item);
   }
+  for (const item of data.audioPositions ?? []) {
+    await sqliteSaveToTable('audio_positions', item);
+  }
+  for (const item of data.symbolLibrary ?? []) {
+    await sqliteSaveToTable('symbol_library', item);
+  }
+  for (const item of data.tags ?? []) {
+    await sqliteSaveToTable('tags', item);
+  }
+  for (const item of data.tagLinks ?? []) {
+    await sqliteSaveToTable('tag_links', item);
+  }
+  for (const item of data.bookmarkFolders ?? []) {
+    await sqliteSaveToTable('bookmark_folders', item);
+  }
+  for (const item of data.bookmarks ?? []) {
+    await sqliteSaveToTable('bookmarks', item);
+  }
+  for (const item of data.verseCollections ?? []) {
+    await sqliteSaveToTable('verse_collections', item);
+  }
+  for (const item of data.workspaces ?? []) {
+    await sqliteSaveToTable('workspaces', item);
+  }
+  for (const item of data.highlightStyles ?? []) {
+    await sqliteSaveToTable('highlight_styles', item);
+  }
+  for (const item of data.markingTemplates ?? []) {
+    await sqliteSaveToTable('marking_templates', item);
+  }
+  for (const item of data.marginNotes ?? []) {
+    await sqliteSaveToTable('margin_notes', item);
+  }
+  for (const item of data.resourceLinks ?? []) {
+    await sqliteSaveToTable('resource_links', item);
+  }
 
   // Import preferences
   if (data.preferences) {
@@ -1739,6 +2285,16 @@ export async function recordChange(
      VALUES (?, ?, ?, ?, ?, ?, 0)`,
     [tableName, op, rowId, data ?? null, now, deviceId]
   );
+
+  if (op === 'delete') {
+    await writeTombstone(tableName, rowId, now, deviceId);
+  } else {
+    // Re-created locally (e.g. undo): the row is live again.
+    await db.execute(
+      `DELETE FROM sync_tombstones WHERE table_name = ? AND row_id = ?`,
+      [tableName, rowId]
+    );
+  }
 }
 
 /**
@@ -1760,6 +2316,17 @@ export async function getUnflushedChanges(): Promise<{
   );
 }
 
+/**
+ * Count changes not yet flushed to a journal file.
+ */
+export async function countUnflushedChanges(): Promise<number> {
+  const db = await getSqliteDb();
+  const rows = await db.select<{ count: number }[]>(
+    `SELECT COUNT(*) as count FROM change_log WHERE flushed = 0`
+  );
+  return rows[0]?.count ?? 0;
+}
+
 /**
  * Mark changes as flushed (written to journal file).
  */
@@ -1810,6 +2377,126 @@ export async function setSyncWatermark(remoteDeviceId: string, lastSeq: number):
   );
 }
 
+// ============================================================================
+// Sync History
+// ============================================================================
+
+/** Rows kept in sync_history; older cycles are dropped on insert. */
+const SYNC_HISTORY_LIMIT = 500;
+
+export interface SyncHistoryEntry {
+  id: number;
+  started_at: string;
+  duration_ms: number;
+  /** 'push' | 'pull' | 'both' | 'none' — which way data actually moved */
+  direction: string;
+  /** Local change_log entries flushed to the backend */
+  pushed: number;
+  /** Remote changes applied locally */
+  pulled: number;
+  bytes_up: number;
+  bytes_down: number;
+  /** 'ok' | 'error' | 'cancelled' */
+  result: string;
+  error: string | null;
+}
+
+/** Append one sync cycle to the history, keeping the newest SYNC_HISTORY_LIMIT rows. */
+export async function recordSyncHistory(entry: Omit<SyncHistoryEntry, 'id'>): Promise<void> {
+  const db = await getSqliteDb();
+  await db.execute(
+    `INSERT INTO sync_history
+       (started_at, duration_ms, direction, pushed, pulled, bytes_up, bytes_down, result, error)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)`,
+    [
+      entry.started_at, entry.duration_ms, entry.direction, entry.pushed, entry.pulled,
+      entry.bytes_up, entry.bytes_down, entry.result, entry.error,
+    ]
+  );
+  await db.execute(
+    `DELETE FROM sync_history WHERE id <= (SELECT MAX(id) FROM sync_history) - ?`,
+    [SYNC_HISTORY_LIMIT]
+  );
+}
+
+/** The most recent sync cycles, newest first. */
+export async function getSyncHistory(limit = 50): Promise<SyncHistoryEntry[]> {
+  const db = await getSqliteDb();
+  return db.select<SyncHistoryEntry[]>(
+    `SELECT * FROM sync_history ORDER BY id DESC LIMIT ?`,
+    [limit]
+  );
+}
+
+// ============================================================================
+// Tombstones
+// ============================================================================
+
+/** Default tombstone retention; override with the `tombstone_gc_days` sync_config key. */
+export const DEFAULT_TOMBSTONE_GC_DAYS = 90;
+
+export interface Tombstone {
+  table_name: string;
+  row_id: string;
+  deleted_at: string;
+  device_id: string;
+}
+
+/** Record a deletion, keeping the later timestamp if one already exists. */
+async function writeTombstone(
+  tableName: string,
+  rowId: string,
+  deletedAt: string,
+  deviceId: string
+): Promise<void> {
+  const db = await getSqliteDb();
+  await db.execute(
+    `INSERT INTO sync_tombstones (table_name, row_id, deleted_at, device_id)
+     VALUES (?, ?, ?, ?)
+     ON CONFLICT (table_name, row_id) DO UPDATE SET
+       deleted_at = excluded.deleted_at, device_id = excluded.device_id
+     WHERE excluded.deleted_at > sync_tombstones.deleted_at`,
+    [tableName, rowId, deletedAt, deviceId]
+  );
+}
+
+/** When `rowId` was deleted, or null if it has no tombstone. */
+async function getTombstoneTime(tableName: string, rowId: string): Promise<string | null> {
+  const db = await getSqliteDb();
+  const rows = await db.select<{ deleted_at: string }[]>(
+    `SELECT deleted_at FROM sync_tombstones WHERE table_name = ? AND row_id = ?`,
+    [tableName, rowId]
+  );
+  return rows[0]?.deleted_at ?? null;
+}
+
+/** All tombstones, for inclusion in snapshots. */
+export async function getTombstones(): Promise<Tombstone[]> {
+  const db = await getSqliteDb();
+  return db.select<Tombstone[]>(
+    `SELECT table_name, row_id, deleted_at, device_id FROM sync_tombstones`
+  );
+}
+
+/**
+ * Garbage-collect tombstones older than `retentionDays`. After this, a device
+ * that was offline for longer than the window can resurrect those rows — the
+ * window trades that risk against the table growing forever.
+ */
+export async function pruneTombstones(retentionDays: number): Promise<void> {
+  const db = await getSqliteDb();
+  const cutoff = new Date(Date.now() - retentionDays * 24 * 60 * 60 * 1000).toISOString();
+  await db.execute(`DELETE FROM sync_tombstones WHERE deleted_at < ?`, [cutoff]);
+}
+
+/**
+ * Drop the pull watermark for one remote device (after it is forgotten).
+ */
+export async function clearSyncWatermark(remoteDeviceId: string): Promise<void> {
+  const db = await getSqliteDb();
+  await db.execute(`DELETE FROM sync_watermarks WHERE device_id = ?`, [remoteDeviceId]);
+}
+
 /**
  * Clear all per-device pull watermarks. Used on account deletion so a later
  * re-sign-in (possibly to a re-created remote) bootstraps cleanly rather than
@@ -1876,7 +2563,11 @@ export async function applyRemoteChange(
     if (existing.length > 0 && existing[0].updated_at > remoteUpdatedAt) {
       return false; // Local is newer, skip
     }
-    await db.execute(`DELETE FROM ${tableName} WHERE id = ?`, [rowId]);
+    // Tombstone even when the row is absent here, so a stale upsert from a
+    // third device arriving later is still recognized as deleted.
+    await writeTombstone(tableName, rowId, remoteUpdatedAt, remoteDeviceId);
+    // Natively, so the delete stays off the undo journal (db/undo.rs).
+    await invoke('apply_remote_delete', { tableName, rowId });
     return true;
   }
 
@@ -1889,6 +2580,12 @@ export async function applyRemoteChange(
     return false; // Local is newer, skip
   }
 
+  // Deleted at or after this version was written: stay deleted.
+  const deletedAt = await getTombstoneTime(tableName, rowId);
+  if (deletedAt !== null && deletedAt >= remoteUpdatedAt) {
+    return false;
+  }
+
   if (!data) return false;
 
   // Apply based on table type
@@ -1922,6 +2619,13 @@ export async function applyRemoteChange(
     }
   }
 
+  if (deletedAt !== null) {
+    await db.execute(
+      `DELETE FROM sync_tombstones WHERE table_name = ? AND row_id = ?`,
+      [tableName, rowId]
+    );
+  }
+
   return true;
 }
 
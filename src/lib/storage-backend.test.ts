@@ -6,7 +6,7 @@ vi.mock('@tauri-apps/api/core', () => ({
   invoke: (...args: unknown[]) => invoke(...args),
 }));
 
-import { HttpStorageBackend } from './storage-backend';
+import { HttpStorageBackend, MeteredStorageBackend } from './storage-backend';
 
 describe('HttpStorageBackend', () => {
   let backend: HttpStorageBackend;
@@ -49,3 +49,32 @@ describe('HttpStorageBackend', () => {
     expect(invoke).toHaveBeenCalledWith('sync_remove', { key: 'device-1/0000000001.json' });
   });
 });
+
+describe('MeteredStorageBackend', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('counts bytes written and read, and resets', async () => {
+    const metered = new MeteredStorageBackend(new HttpStorageBackend());
+    invoke.mockResolvedValueOnce(undefined);
+    await metered.write('device-1/0000000001.json', '12345');
+    invoke.mockResolvedValueOnce('abc');
+    await metered.readText('device-1/meta.json');
+    invoke.mockResolvedValueOnce(null);
+    await metered.readText('device-1/missing.json');
+
+    expect(metered.bytesWritten).toBe(5);
+    expect(metered.bytesRead).toBe(3);
+    metered.resetCounters();
+    expect(metered.bytesWritten).toBe(0);
+    expect(metered.bytesRead).toBe(0);
+  });
+
+  it('does not count a failed write', async () => {
+    const metered = new MeteredStorageBackend(new HttpStorageBackend());
+    invoke.mockRejectedValueOnce({ kind: 'network', statusCode: 0, message: 'offline' });
+    await expect(metered.write('k', 'abc')).rejects.toBeDefined();
+    expect(metered.bytesWritten).toBe(0);
+  });
+});
@@ -62,3 +62,43 @@ export class HttpStorageBackend implements StorageBackend {
     await invoke('sync_remove', { key });
   }
 }
+
+/**
+ * Pass-through backend that tallies payload bytes, so the engine can record
+ * how much each sync cycle moved (sync history) without touching call sites.
+ * Bytes are UTF-16 code units of the JSON text — what the engine handed over,
+ * not the encoded wire size.
+ */
+export class MeteredStorageBackend implements StorageBackend {
+  bytesWritten = 0;
+  bytesRead = 0;
+  private readonly inner: StorageBackend;
+
+  constructor(inner: StorageBackend) {
+    this.inner = inner;
+  }
+
+  resetCounters(): void {
+    this.bytesWritten = 0;
+    this.bytesRead = 0;
+  }
+
+  async write(key: string, content: string): Promise<void> {
+    await this.inner.write(key, content);
+    this.bytesWritten += content.length;
+  }
+
+  async readText(key: string): Promise<string | null> {
+    const text = await this.inner.readText(key);
+    if (text !== null) this.bytesRead += text.length;
+    return text;
+  }
+
+  list(prefix: string): Promise<ListEntry[]> {
+    return this.inner.list(prefix);
+  }
+
+  remove(key: string): Promise<void> {
+    return this.inner.remove(key);
+  }
+}
@@ -0,0 +1,61 @@
+/**
+ * Symbol Library
+ *
+ * The user's own inductive-study vocabulary: a shape in a color that stands
+ * for something (a purple triangle for God, a red cloud for the covenant).
+ * Kept natively in the synced `symbol_library` table (db/symbol_library.rs),
+ * which backs up and syncs with the study data; usage is counted from the
+ * symbol annotations and marking presets drawn with the same shape and color.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** One symbol of the library (`LibrarySymbol` in Rust). */
+export interface LibrarySymbol {
+  id: string;
+  /** A `SymbolKey` (`triangle`, `cloud`), or a glyph of the user's own. */
+  shape: string;
+  /** A highlight color or `#rrggbb`; absent for a symbol in the text color. */
+  color?: string;
+  /** What the symbol stands for, e.g. `God`. */
+  meaning: string;
+  description?: string;
+  /** ISO timestamps, set by the backend. */
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+/** How often a library symbol is drawn (`SymbolUsage` in Rust). */
+export interface SymbolUsage {
+  /** Symbol annotations of its shape, and of its color if it has one. */
+  annotations: number;
+  presets: number;
+}
+
+export interface LibraryEntry extends LibrarySymbol {
+  usage: SymbolUsage;
+}
+
+/** The library by meaning, with each symbol's usage. */
+export async function getSymbolLibrary(): Promise<LibraryEntry[]> {
+  return invoke<LibraryEntry[]>('get_symbol_library');
+}
+
+/**
+ * Add a symbol. Rejected if its shape and color already mean something else
+ * or it has no meaning.
+ */
+export async function createLibrarySymbol(symbol: Omit<LibrarySymbol, 'id'> & { id?: string }): Promise<LibrarySymbol> {
+  return invoke<LibrarySymbol>('create_library_symbol', {
+    symbol: { ...symbol, id: symbol.id ?? crypto.randomUUID() },
+  });
+}
+
+export async function updateLibrarySymbol(symbol: LibrarySymbol): Promise<LibrarySymbol> {
+  return invoke<LibrarySymbol>('update_library_symbol', { symbol });
+}
+
+/** Remove a symbol from the library; the marks drawn with it stay. */
+export async function deleteLibrarySymbol(id: string): Promise<boolean> {
+  return invoke<boolean>('delete_library_symbol', { id });
+}
@@ -17,6 +17,9 @@ import {
   signOut,
   clearLocalSession,
   deleteAccount,
+  setupSyncEncryption,
+  rotateSyncEncryption,
+  acceptSyncPairing,
   isSyncError,
 } from './sync-account';
 
@@ -65,6 +68,20 @@ describe('sync-account', () => {
     expect(invoke).toHaveBeenCalledWith('delete_account');
   });
 
+  it('encryption setup and rotation pass the passphrase to Rust', async () => {
+    invoke.mockResolvedValue({ enabled: true, keyId: 'k1' });
+    expect(await setupSyncEncryption('correct horse')).toEqual({ enabled: true, keyId: 'k1' });
+    expect(invoke).toHaveBeenCalledWith('setup_sync_encryption', { passphrase: 'correct horse' });
+    await rotateSyncEncryption('battery staple');
+    expect(invoke).toHaveBeenCalledWith('rotate_sync_encryption', { newPassphrase: 'battery staple' });
+  });
+
+  it('acceptSyncPairing hands the scanned payload to Rust', async () => {
+    invoke.mockResolvedValue({ enabled: true, keyId: 'k2' });
+    await acceptSyncPairing('bmpair1:abc');
+    expect(invoke).toHaveBeenCalledWith('accept_sync_pairing', { payload: 'bmpair1:abc' });
+  });
+
   it('deleteAccount propagates a SyncError so the caller can retry', async () => {
     const err = Object.assign(new Error('offline'), { kind: 'network', statusCode: 0 });
     invoke.mockRejectedValueOnce(err);
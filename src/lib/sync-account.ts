@@ -7,6 +7,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 export type SyncErrorKind =
   | 'network'
@@ -18,7 +19,12 @@ export type SyncErrorKind =
   // Server rejected a blob PUT for exceeding the per-account object quota (507).
   // Non-retryable (isNetworkError excludes 507) and distinct from a local
   // 'storage' (token file) error.
-  | 'storage_full';
+  | 'storage_full'
+  // A transfer aborted by `cancel_sync`. Not a failure — the engine goes idle.
+  | 'cancelled'
+  // End-to-end encryption: wrong passphrase, or a blob sealed with a key this
+  // device doesn't have (another device rotated). Needs the user, not a retry.
+  | 'encryption';
 
 /** Structured error surfaced by the Rust sync commands. */
 export interface SyncError {
@@ -84,3 +90,64 @@ export async function clearLocalSession(): Promise<void> {
 export async function deleteAccount(): Promise<void> {
   await invoke('delete_account');
 }
+
+/** Payload of the `sync://progress` event emitted by blob transfers. */
+export interface SyncProgress {
+  phase: 'upload' | 'download';
+  key: string;
+  bytesTransferred: number;
+  /** `null` when the server sent no Content-Length. */
+  totalBytes: number | null;
+  done: boolean;
+}
+
+/** Subscribe to blob transfer progress. Resolves to an unsubscribe function. */
+export async function onSyncProgress(listener: (progress: SyncProgress) => void): Promise<() => void> {
+  return listen<SyncProgress>('sync://progress', event => listener(event.payload));
+}
+
+/** Abort in-flight blob transfers; they reject with a `cancelled` SyncError. */
+export async function cancelSyncTransfers(): Promise<void> {
+  await invoke('cancel_sync');
+}
+
+/** Whether this device seals sync blobs, and with which key id. */
+export interface EncryptionStatus {
+  enabled: boolean;
+  keyId: string | null;
+}
+
+/**
+ * Turn on end-to-end encryption. Joins the account's existing passphrase if
+ * another device set one (rejecting a mismatch), otherwise creates it.
+ */
+export async function setupSyncEncryption(passphrase: string): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>('setup_sync_encryption', { passphrase });
+}
+
+/** Switch to a new passphrase; older blobs stay readable on this device. */
+export async function rotateSyncEncryption(newPassphrase: string): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>('rotate_sync_encryption', { newPassphrase });
+}
+
+/** Stop encrypting new blobs and withdraw the account's published passphrase parameters. */
+export async function disableSyncEncryption(): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>('disable_sync_encryption');
+}
+
+export async function getSyncEncryptionStatus(): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>('get_sync_encryption_status');
+}
+
+/**
+ * A pairing string (valid ~10 minutes) for rendering as a QR code. Another
+ * device on the same account scans it to get the keys without the passphrase.
+ */
+export async function createSyncPairing(): Promise<string> {
+  return invoke<string>('create_sync_pairing');
+}
+
+/** Adopt the keys from a scanned pairing string. */
+export async function acceptSyncPairing(payload: string): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>('accept_sync_pairing', { payload });
+}
@@ -13,31 +13,51 @@ vi.mock('@tauri-apps/api/core', () => ({
   invoke: vi.fn(),
 }))
 
-vi.mock('./sqlite-db', () => ({
-  getSqliteDb: vi.fn().mockResolvedValue({ select: vi.fn().mockResolvedValue([]) }),
-  getDeviceId: vi.fn().mockReturnValue('device-aaaa-bbbb-cccc-ddddeeeeeeee'),
-  getUnflushedChanges: vi.fn().mockResolvedValue([]),
-  markChangesFlushed: vi.fn().mockResolvedValue(undefined),
-  pruneChangeLog: vi.fn().mockResolvedValue(undefined),
-  getSyncWatermark: vi.fn().mockResolvedValue(0),
-  setSyncWatermark: vi.fn().mockResolvedValue(undefined),
-  getSyncConfig: vi.fn().mockResolvedValue(null),
-  setSyncConfig: vi.fn().mockResolvedValue(undefined),
-  applyRemoteChange: vi.fn().mockResolvedValue(true),
-  sqliteExportAll: vi.fn().mockResolvedValue({
-    annotations: [], sectionHeadings: [], chapterTitles: [], notes: [],
-    markingPresets: [], studies: [], multiTranslationViews: [],
-    observationLists: [], timeExpressions: [],
-    places: [], people: [], conclusions: [], interpretations: [],
-    applications: [], preferences: null,
-  }),
-  SYNCED_TABLES: new Set([
-    'annotations', 'section_headings', 'chapter_titles', 'notes',
-    'marking_presets', 'studies', 'multi_translation_views',
-    'observation_lists', 'time_expressions',
-    'places', 'people', 'conclusions', 'interpretations', 'applications', 'preferences',
-  ]),
-}))
+// The `./sqlite-db` mock every test starts from; each passes only the
+// functions it changes. Hoisted so the top-level `vi.mock` can use it too.
+const { mockSqliteDb, everyTable } = vi.hoisted(() => {
+  const mockSqliteDb = (overrides: Record<string, unknown> = {}) => ({
+    getSqliteDb: vi.fn().mockResolvedValue({ select: vi.fn().mockResolvedValue([]) }),
+    getDeviceId: vi.fn().mockReturnValue('device-aaaa-bbbb-cccc-ddddeeeeeeee'),
+    getUnflushedChanges: vi.fn().mockResolvedValue([]),
+    countUnflushedChanges: vi.fn().mockResolvedValue(0),
+    markChangesFlushed: vi.fn().mockResolvedValue(undefined),
+    pruneChangeLog: vi.fn().mockResolvedValue(undefined),
+    getSyncWatermark: vi.fn().mockResolvedValue(0),
+    setSyncWatermark: vi.fn().mockResolvedValue(undefined),
+    getTombstones: vi.fn().mockResolvedValue([]),
+    pruneTombstones: vi.fn().mockResolvedValue(undefined),
+    recordSyncHistory: vi.fn().mockResolvedValue(undefined),
+    getSyncHistory: vi.fn().mockResolvedValue([]),
+    DEFAULT_TOMBSTONE_GC_DAYS: 90,
+    getSyncConfig: vi.fn().mockResolvedValue(null),
+    setSyncConfig: vi.fn().mockResolvedValue(undefined),
+    applyRemoteChange: vi.fn().mockResolvedValue(true),
+    sqliteSetWriteLock: vi.fn().mockResolvedValue({ locked: false, reason: null, lockedAt: null }),
+    sqliteExportAll: vi.fn(),
+    SYNCED_TABLES: new Set(['annotations']),
+    ...overrides,
+  })
+  // Overrides that sync every table, each exporting empty.
+  const everyTable = () => ({
+    sqliteExportAll: vi.fn().mockResolvedValue({
+      annotations: [], sectionHeadings: [], chapterTitles: [], notes: [],
+      markingPresets: [], studies: [], multiTranslationViews: [],
+      observationLists: [], timeExpressions: [],
+      places: [], people: [], conclusions: [], interpretations: [],
+      applications: [], preferences: null,
+    }),
+    SYNCED_TABLES: new Set([
+      'annotations', 'section_headings', 'chapter_titles', 'notes',
+      'marking_presets', 'studies', 'multi_translation_views',
+      'observation_lists', 'time_expressions',
+      'places', 'people', 'conclusions', 'interpretations', 'applications', 'preferences',
+    ]),
+  })
+  return { mockSqliteDb, everyTable }
+})
+
+vi.mock('./sqlite-db', () => mockSqliteDb(everyTable()))
 
 describe('ChangeEntry type', () => {
   it('represents a valid upsert entry', () => {
@@ -76,6 +96,7 @@ describe('SyncEngineStatus', () => {
       pendingChanges: 0,
       connectedDevices: [],
       error: null,
+      nextRetryAt: null,
     }
     expect(status.state).toBe('disabled')
     expect(status.connectedDevices).toEqual([])
@@ -88,6 +109,7 @@ describe('SyncEngineStatus', () => {
       pendingChanges: 3,
       connectedDevices: ['Mac', 'iPhone'],
       error: null,
+      nextRetryAt: null,
     }
     expect(status.connectedDevices).toHaveLength(2)
     expect(status.pendingChanges).toBe(3)
@@ -111,31 +133,7 @@ describe('sync engine status listener', () => {
     vi.doMock('@tauri-apps/api/core', () => ({
       invoke: vi.fn(),
     }))
-    vi.doMock('./sqlite-db', () => ({
-      getSqliteDb: vi.fn().mockResolvedValue({ select: vi.fn().mockResolvedValue([]) }),
-      getDeviceId: vi.fn().mockReturnValue('device-aaaa-bbbb-cccc-ddddeeeeeeee'),
-      getUnflushedChanges: vi.fn().mockResolvedValue([]),
-      markChangesFlushed: vi.fn().mockResolvedValue(undefined),
-      pruneChangeLog: vi.fn().mockResolvedValue(undefined),
-      getSyncWatermark: vi.fn().mockResolvedValue(0),
-      setSyncWatermark: vi.fn().mockResolvedValue(undefined),
-      getSyncConfig: vi.fn().mockResolvedValue(null),
-      setSyncConfig: vi.fn().mockResolvedValue(undefined),
-      applyRemoteChange: vi.fn().mockResolvedValue(true),
-      sqliteExportAll: vi.fn().mockResolvedValue({
-        annotations: [], sectionHeadings: [], chapterTitles: [], notes: [],
-        markingPresets: [], studies: [], multiTranslationViews: [],
-        observationLists: [], timeExpressions: [],
-        places: [], people: [], conclusions: [], interpretations: [],
-        applications: [], preferences: null,
-      }),
-      SYNCED_TABLES: new Set([
-        'annotations', 'section_headings', 'chapter_titles', 'notes',
-        'marking_presets', 'studies', 'multi_translation_views',
-        'observation_lists', 'time_expressions',
-        'places', 'people', 'conclusions', 'interpretations', 'applications', 'preferences',
-      ]),
-    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb(everyTable()))
 
     const mod = await import('./sync-engine')
     onSyncEngineStatusChange = mod.onSyncEngineStatusChange
@@ -169,6 +167,14 @@ describe('sync engine status listener', () => {
     expect(status1).toEqual(status2)
     expect(status1).not.toBe(status2) // different object references
   })
+
+  it('reports the unflushed change_log count as pendingChanges', async () => {
+    const sqliteDb = await import('./sqlite-db')
+    vi.mocked(sqliteDb.countUnflushedChanges).mockResolvedValueOnce(4)
+    const { refreshPendingChanges } = await import('./sync-engine')
+    await refreshPendingChanges()
+    expect(getSyncEngineStatus().pendingChanges).toBe(4)
+  })
 })
 
 describe('initSyncEngine', () => {
@@ -188,20 +194,7 @@ describe('initSyncEngine', () => {
     vi.doMock('@tauri-apps/api/core', () => ({
       invoke: vi.fn(),
     }))
-    vi.doMock('./sqlite-db', () => ({
-      getSqliteDb: vi.fn().mockResolvedValue({ select: vi.fn().mockResolvedValue([]) }),
-      getDeviceId: vi.fn().mockReturnValue('device-aaaa-bbbb-cccc-ddddeeeeeeee'),
-      getUnflushedChanges: vi.fn().mockResolvedValue([]),
-      markChangesFlushed: vi.fn().mockResolvedValue(undefined),
-      pruneChangeLog: vi.fn().mockResolvedValue(undefined),
-      getSyncWatermark: vi.fn().mockResolvedValue(0),
-      setSyncWatermark: vi.fn().mockResolvedValue(undefined),
-      getSyncConfig: vi.fn().mockResolvedValue(null),
-      setSyncConfig: vi.fn().mockResolvedValue(undefined),
-      applyRemoteChange: vi.fn().mockResolvedValue(true),
-      sqliteExportAll: vi.fn(),
-      SYNCED_TABLES: new Set(['annotations']),
-    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb())
     vi.doMock('./sync-account', () => ({
       getSignedInAccount: vi.fn().mockResolvedValue(null),
       clearLocalSession: vi.fn(),
@@ -230,17 +223,8 @@ describe('initSyncEngine', () => {
     vi.doMock('@tauri-apps/api/core', () => ({
       invoke: mockInvoke,
     }))
-    vi.doMock('./sqlite-db', () => ({
+    vi.doMock('./sqlite-db', () => mockSqliteDb({
       getSqliteDb: vi.fn().mockResolvedValue({ select: vi.fn().mockResolvedValue([{ max_seq: 0 }]) }),
-      getDeviceId: vi.fn().mockReturnValue('device-aaaa-bbbb-cccc-ddddeeeeeeee'),
-      getUnflushedChanges: vi.fn().mockResolvedValue([]),
-      markChangesFlushed: vi.fn().mockResolvedValue(undefined),
-      pruneChangeLog: vi.fn().mockResolvedValue(undefined),
-      getSyncWatermark: vi.fn().mockResolvedValue(0),
-      setSyncWatermark: vi.fn().mockResolvedValue(undefined),
-      getSyncConfig: vi.fn().mockResolvedValue(null),
-      setSyncConfig: vi.fn().mockResolvedValue(undefined),
-      applyRemoteChange: vi.fn().mockResolvedValue(true),
       sqliteExportAll: vi.fn().mockResolvedValue({
         annotations: [], sectionHeadings: [], chapterTitles: [], notes: [],
         markingPresets: [], studies: [], multiTranslationViews: [],
@@ -248,7 +232,6 @@ describe('initSyncEngine', () => {
         places: [], people: [], conclusions: [], interpretations: [],
         applications: [], preferences: null, entityNotes: [], keywordExclusions: [],
       }),
-      SYNCED_TABLES: new Set(['annotations']),
     }))
     vi.doMock('./sync-account', () => ({
       getSignedInAccount: vi.fn().mockResolvedValue('account-123'),
@@ -267,6 +250,414 @@ describe('initSyncEngine', () => {
   })
 })
 
+describe('computeBackoffDelay', () => {
+  it('doubles from 30s and caps at 5m without jitter', async () => {
+    const { computeBackoffDelay } = await import('./sync-engine')
+    const noJitter = () => 0.5
+    expect([1, 2, 3, 4, 5, 9].map(n => computeBackoffDelay(n, noJitter)))
+      .toEqual([30_000, 60_000, 120_000, 240_000, 300_000, 300_000])
+  })
+
+  it('stays within ±20% jitter', async () => {
+    const { computeBackoffDelay } = await import('./sync-engine')
+    expect(computeBackoffDelay(1, () => 0)).toBe(24_000)
+    expect(computeBackoffDelay(1, () => 1)).toBe(36_000)
+  })
+})
+
+describe('transient failures', () => {
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  it('reports retrying with the next attempt time instead of an error', async () => {
+    vi.resetModules()
+
+    const offline = { kind: 'network', statusCode: 0, message: 'network error: offline' }
+    const mockInvoke = vi.fn().mockRejectedValue(offline)
+
+    vi.doMock('@tauri-apps/plugin-fs', () => ({
+      readDir: vi.fn(),
+      readTextFile: vi.fn(),
+      mkdir: vi.fn(),
+      remove: vi.fn(),
+    }))
+    vi.doMock('@tauri-apps/api/core', () => ({
+      invoke: mockInvoke,
+    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb())
+    vi.doMock('./sync-account', () => ({
+      getSignedInAccount: vi.fn().mockResolvedValue('account-123'),
+      clearLocalSession: vi.fn(),
+      isSyncError: (e: unknown) => typeof e === 'object' && e !== null && 'kind' in e,
+    }))
+
+    const { initSyncEngine, getSyncEngineStatus } = await import('./sync-engine')
+    const before = Date.now()
+    await initSyncEngine()
+
+    const status = getSyncEngineStatus()
+    expect(status.state).toBe('retrying')
+    expect(status.error).toBe('network error: offline')
+    const next = Date.parse(status.nextRetryAt!)
+    expect(next - before).toBeGreaterThanOrEqual(24_000)
+    expect(next - before).toBeLessThanOrEqual(36_000 + 1_000)
+  })
+})
+
+describe('offline', () => {
+  afterEach(() => {
+    vi.restoreAllMocks()
+    vi.unstubAllGlobals()
+  })
+
+  it('queues instead of failing while the device has no connection', async () => {
+    vi.resetModules()
+    vi.stubGlobal('navigator', { onLine: false })
+
+    const mockInvoke = vi.fn().mockResolvedValue([])
+
+    vi.doMock('@tauri-apps/plugin-fs', () => ({
+      readDir: vi.fn(),
+      readTextFile: vi.fn(),
+      mkdir: vi.fn(),
+      remove: vi.fn(),
+    }))
+    vi.doMock('@tauri-apps/api/core', () => ({
+      invoke: mockInvoke,
+    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb({
+      countUnflushedChanges: vi.fn().mockResolvedValue(4),
+    }))
+    vi.doMock('./sync-account', () => ({
+      getSignedInAccount: vi.fn().mockResolvedValue('account-123'),
+      clearLocalSession: vi.fn(),
+      isSyncError: vi.fn().mockReturnValue(false),
+    }))
+
+    const { initSyncEngine, getSyncEngineStatus } = await import('./sync-engine')
+    await initSyncEngine()
+
+    const status = getSyncEngineStatus()
+    expect(status.state).toBe('offline')
+    expect(status.pendingChanges).toBe(4)
+    expect(status.nextRetryAt).toBeNull()
+    expect(mockInvoke).not.toHaveBeenCalled()
+  })
+})
+
+describe('cancelled transfers', () => {
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  it('returns to idle without reporting an error', async () => {
+    vi.resetModules()
+
+    const cancelled = { kind: 'cancelled', statusCode: 1, message: 'sync cancelled' }
+    const mockInvoke = vi.fn().mockImplementation((cmd: string) =>
+      cmd === 'sync_write' ? Promise.reject(cancelled) : Promise.resolve([]))
+
+    vi.doMock('@tauri-apps/plugin-fs', () => ({
+      readDir: vi.fn(),
+      readTextFile: vi.fn(),
+      mkdir: vi.fn(),
+      remove: vi.fn(),
+    }))
+    vi.doMock('@tauri-apps/api/core', () => ({
+      invoke: mockInvoke,
+    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb({
+      getUnflushedChanges: vi.fn().mockResolvedValue([{
+        seq: 1, table_name: 'annotations', op: 'upsert', row_id: 'ann-1',
+        data: '{}', updated_at: '2025-01-01T00:00:00.000Z',
+        device_id: 'device-aaaa-bbbb-cccc-ddddeeeeeeee',
+      }]),
+      countUnflushedChanges: vi.fn().mockResolvedValue(1),
+    }))
+    vi.doMock('./sync-account', () => ({
+      getSignedInAccount: vi.fn().mockResolvedValue('account-123'),
+      clearLocalSession: vi.fn(),
+      isSyncError: (e: unknown) => typeof e === 'object' && e !== null && 'kind' in e,
+    }))
+
+    const { initSyncEngine, getSyncEngineStatus } = await import('./sync-engine')
+
+    await initSyncEngine()
+
+    const status = getSyncEngineStatus()
+    expect(status.state).toBe('idle')
+    expect(status.error).toBeNull()
+    expect(status.pendingChanges).toBe(1)
+
+    const sqliteDb = await import('./sqlite-db')
+    expect(sqliteDb.recordSyncHistory).toHaveBeenCalledWith(expect.objectContaining({
+      result: 'cancelled', pushed: 0, direction: 'none',
+    }))
+  })
+})
+
+describe('pauseSync / resumeSync', () => {
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  it('starts paused when sync_paused is persisted, and resumes on demand', async () => {
+    vi.resetModules()
+
+    const mockInvoke = vi.fn().mockResolvedValue([])
+    const config = new Map<string, string>([['sync_paused', '1']])
+    const mockSetSyncConfig = vi.fn(async (key: string, value: string) => { config.set(key, value) })
+
+    vi.doMock('@tauri-apps/plugin-fs', () => ({
+      readDir: vi.fn(),
+      readTextFile: vi.fn(),
+      mkdir: vi.fn(),
+      remove: vi.fn(),
+    }))
+    vi.doMock('@tauri-apps/api/core', () => ({
+      invoke: mockInvoke,
+    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb({
+      getSyncConfig: vi.fn(async (key: string) => config.get(key) ?? null),
+      setSyncConfig: mockSetSyncConfig,
+    }))
+    vi.doMock('./sync-account', () => ({
+      getSignedInAccount: vi.fn().mockResolvedValue('account-123'),
+      clearLocalSession: vi.fn(),
+      isSyncError: vi.fn().mockReturnValue(false),
+    }))
+
+    const { initSyncEngine, getSyncEngineStatus, resumeSync, pauseSync, isSyncPaused } = await import('./sync-engine')
+
+    await initSyncEngine()
+    expect(getSyncEngineStatus().state).toBe('paused')
+    expect(mockInvoke).not.toHaveBeenCalled()
+
+    await resumeSync()
+    expect(isSyncPaused()).toBe(false)
+    expect(config.get('sync_paused')).toBe('0')
+    expect(mockInvoke).toHaveBeenCalledWith('sync_list', expect.anything())
+    expect(getSyncEngineStatus().state).toBe('idle')
+
+    await pauseSync()
+    expect(config.get('sync_paused')).toBe('1')
+    expect(getSyncEngineStatus().state).toBe('paused')
+  })
+})
+
+describe('device registry', () => {
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  it('lists devices from meta.json and forgets a stale one', async () => {
+    vi.resetModules()
+
+    const self = 'device-aaaa-bbbb-cccc-ddddeeeeeeee'
+    const stale = '11111111-2222-3333-4444-555555555555'
+    const blobs = new Map<string, string>([
+      [`${stale}/meta.json`, JSON.stringify({
+        deviceId: stale, deviceName: 'Old iPad', platform: 'ios', lastSeq: 7,
+        createdAt: '2024-01-01T00:00:00.000Z', updatedAt: '2024-06-01T00:00:00.000Z',
+      })],
+      [`${stale}/0000000007.json`, '{}'],
+      [`snapshots/${stale}_7.json`, '{}'],
+    ])
+    const list = (prefix: string) => {
+      const names = new Set<string>()
+      for (const key of blobs.keys()) {
+        if (prefix === '') names.add(key.split('/')[0])
+        else if (key.startsWith(`${prefix}/`)) names.add(key.slice(prefix.length + 1))
+      }
+      return Array.from(names).map(name => ({ name, isDirectory: prefix === '' }))
+    }
+    const mockInvoke = vi.fn(async (cmd: string, args: { key?: string; prefix?: string }) => {
+      if (cmd === 'sync_list') return list(args.prefix ?? '')
+      if (cmd === 'sync_read') return blobs.get(args.key!) ?? null
+      if (cmd === 'sync_remove') { blobs.delete(args.key!); return undefined }
+      return undefined
+    })
+    const mockClearWatermark = vi.fn().mockResolvedValue(undefined)
+
+    vi.doMock('@tauri-apps/plugin-fs', () => ({
+      readDir: vi.fn(),
+      readTextFile: vi.fn(),
+      mkdir: vi.fn(),
+      remove: vi.fn(),
+    }))
+    vi.doMock('@tauri-apps/api/core', () => ({
+      invoke: mockInvoke,
+    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb({
+      getDeviceId: vi.fn().mockReturnValue(self),
+      getSyncWatermark: vi.fn().mockResolvedValue(7),
+      clearSyncWatermark: mockClearWatermark,
+    }))
+    vi.doMock('./sync-account', () => ({
+      getSignedInAccount: vi.fn().mockResolvedValue('account-123'),
+      clearLocalSession: vi.fn(),
+      isSyncError: vi.fn().mockReturnValue(false),
+    }))
+
+    const { initSyncEngine, listSyncDevices, forgetDevice } = await import('./sync-engine')
+    await initSyncEngine()
+
+    const devices = await listSyncDevices()
+    expect(devices).toEqual([expect.objectContaining({
+      deviceId: stale, deviceName: 'Old iPad', lastSeq: 7, isCurrent: false,
+    })])
+
+    await expect(forgetDevice(self)).rejects.toThrow(/current device/)
+    await forgetDevice(stale)
+    expect(blobs.size).toBe(0)
+    expect(mockClearWatermark).toHaveBeenCalledWith(stale)
+    expect(await listSyncDevices()).toEqual([])
+  })
+})
+
+describe('snapshot bootstrap', () => {
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  it('applies native annotation writes while it holds native writes paused', async () => {
+    vi.resetModules()
+
+    const remote = '11111111-2222-3333-4444-555555555555'
+    const annotation = (id: string) => ({
+      id, moduleId: 'kjv', type: 'highlight', color: 'yellow',
+      startRef: { book: 'Rom', chapter: 8, verse: 1 }, endRef: { book: 'Rom', chapter: 8, verse: 1 },
+      createdAt: '2025-01-01T00:00:00.000Z', updatedAt: '2025-01-01T00:00:00.000Z',
+    })
+    const blobs = new Map<string, string>([
+      [`${remote}/meta.json`, JSON.stringify({ deviceId: remote, lastSeq: 3 })],
+      [`snapshots/${remote}_3.json`, JSON.stringify({
+        version: 1, device: remote, atSeq: 3, createdAt: '2025-01-02T00:00:00.000Z',
+        tables: { annotations: [annotation('a1'), annotation('a2')] },
+      })],
+    ])
+    const list = (prefix: string) => {
+      const names = new Set<string>()
+      for (const key of blobs.keys()) {
+        if (prefix === '') names.add(key.split('/')[0])
+        else if (key.startsWith(`${prefix}/`)) names.add(key.slice(prefix.length + 1))
+      }
+      return Array.from(names).map(name => ({ name, isDirectory: prefix === '' }))
+    }
+    // The native side as the writer queue behaves: a paused lock stops local
+    // writes but lets through the ones sync brings in (`remote` set).
+    let locked = false
+    const saved: string[] = []
+    const mockInvoke = vi.fn(async (cmd: string, args: { key?: string; prefix?: string; annotation?: { id: string }; remote?: unknown }) => {
+      if (cmd === 'sync_list') return list(args.prefix ?? '')
+      if (cmd === 'sync_read') return blobs.get(args.key!) ?? null
+      if (cmd === 'update_annotation') {
+        if (locked && !args.remote) throw { kind: 'database_locked', message: 'The database is read-only for now' }
+        saved.push(args.annotation!.id)
+        return args.annotation
+      }
+      return undefined
+    })
+    const mockSetWatermark = vi.fn().mockResolvedValue(undefined)
+
+    vi.doMock('@tauri-apps/plugin-fs', () => ({
+      readDir: vi.fn(),
+      readTextFile: vi.fn(),
+      mkdir: vi.fn(),
+      remove: vi.fn(),
+    }))
+    vi.doMock('@tauri-apps/api/core', () => ({
+      invoke: mockInvoke,
+    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb({
+      setSyncWatermark: mockSetWatermark,
+      // As sqlite-db applies an annotation: natively, with the remote's metadata.
+      applyRemoteChange: vi.fn(async (table: string, _op: string, _id: string, data: string, updatedAt: string, deviceId: string) => {
+        if (table !== 'annotations') return false
+        await mockInvoke('update_annotation', { annotation: JSON.parse(data), remote: { updatedAt, deviceId } })
+        return true
+      }),
+      sqliteSetWriteLock: vi.fn(async (lock: boolean) => {
+        locked = lock
+        return { locked, reason: null, lockedAt: null }
+      }),
+      sqliteExportAll: vi.fn().mockResolvedValue({ annotations: [] }),
+    }))
+    vi.doMock('./sync-account', () => ({
+      getSignedInAccount: vi.fn().mockResolvedValue('account-123'),
+      clearLocalSession: vi.fn(),
+      isSyncError: vi.fn().mockReturnValue(false),
+    }))
+
+    const { initSyncEngine } = await import('./sync-engine')
+    await initSyncEngine()
+
+    expect(saved).toEqual(['a1', 'a2'])
+    expect(mockSetWatermark).toHaveBeenCalledWith(remote, 3)
+    expect(locked).toBe(false)
+  })
+})
+
+describe('sync scopes', () => {
+  afterEach(() => {
+    vi.restoreAllMocks()
+  })
+
+  it('pushes only in-scope tables but consumes the whole change_log', async () => {
+    vi.resetModules()
+
+    const config = new Map<string, string>([['sync_scopes', 'annotations']])
+    const mockInvoke = vi.fn(async (cmd: string, _args?: unknown) => (cmd === 'sync_list' ? [] : undefined))
+    const mockMarkFlushed = vi.fn().mockResolvedValue(undefined)
+
+    vi.doMock('@tauri-apps/plugin-fs', () => ({
+      readDir: vi.fn(),
+      readTextFile: vi.fn(),
+      mkdir: vi.fn(),
+      remove: vi.fn(),
+    }))
+    vi.doMock('@tauri-apps/api/core', () => ({
+      invoke: mockInvoke,
+    }))
+    vi.doMock('./sqlite-db', () => mockSqliteDb({
+      getUnflushedChanges: vi.fn()
+        .mockResolvedValueOnce([
+          { seq: 1, table_name: 'annotations', row_id: 'a1', op: 'upsert', data: '{"id":"a1"}', updated_at: '2025-01-01T00:00:00.000Z', device_id: 'device-aaaa-bbbb-cccc-ddddeeeeeeee' },
+          { seq: 2, table_name: 'preferences', row_id: 'main', op: 'upsert', data: '{"id":"main"}', updated_at: '2025-01-01T00:00:01.000Z', device_id: 'device-aaaa-bbbb-cccc-ddddeeeeeeee' },
+        ])
+        .mockResolvedValue([]),
+      markChangesFlushed: mockMarkFlushed,
+      getSyncConfig: vi.fn(async (key: string) => config.get(key) ?? null),
+      setSyncConfig: vi.fn(async (key: string, value: string) => { config.set(key, value) }),
+      SYNCED_TABLES: new Set(['annotations', 'preferences']),
+    }))
+    vi.doMock('./sync-account', () => ({
+      getSignedInAccount: vi.fn().mockResolvedValue('account-123'),
+      clearLocalSession: vi.fn(),
+      isSyncError: vi.fn().mockReturnValue(false),
+    }))
+
+    const { initSyncEngine, getSyncScopes } = await import('./sync-engine')
+    expect(await getSyncScopes()).toEqual(['annotations'])
+
+    await initSyncEngine()
+
+    const journalWrite = mockInvoke.mock.calls.find(
+      ([cmd, args]) => cmd === 'sync_write' && !(args as { key: string }).key.endsWith('meta.json')
+    )
+    expect(journalWrite).toBeDefined()
+    const journal = JSON.parse((journalWrite![1] as { content: string }).content)
+    expect(journal.entries.map((e: ChangeEntry) => e.table)).toEqual(['annotations'])
+    expect(mockMarkFlushed).toHaveBeenCalledWith(2)
+  })
+
+  it('refuses an empty selection', async () => {
+    const { setSyncScopes } = await import('./sync-engine')
+    await expect(setSyncScopes([])).rejects.toThrow(/at least one/)
+  })
+})
+
 describe('disableSync', () => {
   it('sets state to signed-out so the user can sign back in', async () => {
     vi.resetModules()
@@ -282,19 +673,8 @@ describe('disableSync', () => {
     vi.doMock('@tauri-apps/api/core', () => ({
       invoke: vi.fn(),
     }))
-    vi.doMock('./sqlite-db', () => ({
-      getSqliteDb: vi.fn().mockResolvedValue({ select: vi.fn().mockResolvedValue([]) }),
-      getDeviceId: vi.fn().mockReturnValue('device-aaaa-bbbb-cccc-ddddeeeeeeee'),
-      getUnflushedChanges: vi.fn().mockResolvedValue([]),
-      markChangesFlushed: vi.fn().mockResolvedValue(undefined),
-      pruneChangeLog: vi.fn().mockResolvedValue(undefined),
-      getSyncWatermark: vi.fn().mockResolvedValue(0),
-      setSyncWatermark: vi.fn().mockResolvedValue(undefined),
-      getSyncConfig: vi.fn().mockResolvedValue(null),
+    vi.doMock('./sqlite-db', () => mockSqliteDb({
       setSyncConfig: mockSetSyncConfig,
-      applyRemoteChange: vi.fn().mockResolvedValue(true),
-      sqliteExportAll: vi.fn(),
-      SYNCED_TABLES: new Set(['annotations']),
     }))
 
     const { disableSync, getSyncEngineStatus } = await import('./sync-engine')
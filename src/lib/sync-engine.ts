@@ -11,21 +11,39 @@
  * - Conflict resolution: newest wins (by updated_at timestamp)
  */
 
-import { HttpStorageBackend, type StorageBackend } from './storage-backend';
+import { HttpStorageBackend, MeteredStorageBackend } from './storage-backend';
 import { getSignedInAccount, clearLocalSession, isSyncError } from './sync-account';
 import {
   getUnflushedChanges,
+  countUnflushedChanges,
   markChangesFlushed,
   pruneChangeLog,
   getSyncWatermark,
   setSyncWatermark,
+  clearSyncWatermark,
+  clearSyncWatermarks,
+  getTombstones,
+  pruneTombstones,
+  DEFAULT_TOMBSTONE_GC_DAYS,
+  type Tombstone,
+  recordSyncHistory,
+  getSyncHistory as sqliteGetSyncHistory,
+  type SyncHistoryEntry,
   getSyncConfig,
   setSyncConfig,
   applyRemoteChange,
   sqliteExportAll,
+  sqliteSetWriteLock,
   SYNCED_TABLES,
 } from './sqlite-db';
-import { SNAPSHOT_TABLE_KEYS, camelToSnakeTable } from './table-registry';
+import {
+  SNAPSHOT_TABLE_KEYS,
+  SYNC_SCOPES,
+  camelToSnakeTable,
+  tablesForScopes,
+  type SyncScope,
+} from './table-registry';
+import { isNetworkError, isOnline } from './offline';
 
 // Re-exported so existing importers (snapshot-coverage.test) keep their
 // `from './sync-engine'` path. The single source of truth is table-registry.
@@ -63,6 +81,18 @@ interface DeviceMeta {
   updatedAt: string;
 }
 
+/** A device that has written to this account, as listed by {@link listSyncDevices} */
+export interface SyncDevice {
+  deviceId: string;
+  deviceName: string;
+  platform: string;
+  lastSeq: number;
+  /** When the device last flushed (its meta.json `updatedAt`); null if unknown */
+  lastSeen: string | null;
+  /** True for the device running this app */
+  isCurrent: boolean;
+}
+
 /** A full database snapshot for bootstrapping new devices */
 interface SnapshotFile {
   version: 1;
@@ -70,10 +100,12 @@ interface SnapshotFile {
   atSeq: number;
   createdAt: string;
   tables: Record<string, unknown[]>;
+  /** Deletions still inside the GC window (absent in snapshots written before v12) */
+  tombstones?: Tombstone[];
 }
 
 /** Current state of the sync engine */
-export type SyncEngineState = 'idle' | 'syncing' | 'disabled' | 'error' | 'signed-out' | 'auth-expired';
+export type SyncEngineState = 'idle' | 'syncing' | 'retrying' | 'paused' | 'offline' | 'disabled' | 'error' | 'signed-out' | 'auth-expired';
 
 export interface SyncEngineStatus {
   state: SyncEngineState;
@@ -81,6 +113,8 @@ export interface SyncEngineStatus {
   pendingChanges: number;
   connectedDevices: string[];
   error: string | null;
+  /** When the next automatic attempt runs, while state is 'retrying' */
+  nextRetryAt: string | null;
 }
 
 /** Listener for sync engine status changes */
@@ -93,24 +127,54 @@ type StatusListener = (status: SyncEngineStatus) => void;
 /** Flush interval in milliseconds (30 seconds) */
 const FLUSH_INTERVAL_MS = 30_000;
 
-/** Backoff intervals for repeated failures: 30s → 1m → 5m */
-const BACKOFF_INTERVALS_MS = [30_000, 60_000, 300_000];
+/**
+ * Default quiet period after a local write before pushing (5 seconds), so a
+ * burst of markings lands in one journal. Overridable via the
+ * `push_debounce_ms` sync_config key.
+ */
+const DEFAULT_PUSH_DEBOUNCE_MS = 5_000;
+
+/** Don't re-sync on focus/resume if the last successful sync is this recent */
+const RESUME_MIN_INTERVAL_MS = 15_000;
+
+/** Backoff after the first failure; doubles per consecutive failure up to the cap */
+const BACKOFF_BASE_MS = 30_000;
+const BACKOFF_MAX_MS = 300_000;
+
+/** ±20% randomization so devices that failed together don't retry in lockstep */
+const BACKOFF_JITTER = 0.2;
+
+/**
+ * Transient failures retried automatically before surfacing 'error'. After
+ * that the timer keeps trying at the capped interval, but the UI shows the
+ * error so the user knows something is wrong.
+ */
+const MAX_RETRY_ATTEMPTS = 5;
 
 /** Compaction threshold: compact when journal files exceed this count per device */
 const COMPACTION_THRESHOLD = 100;
 
-let backend: StorageBackend | null = null;
+let backend: MeteredStorageBackend | null = null;
 let deviceId: string = '';
 let flushTimer: ReturnType<typeof setTimeout> | null = null;
 let inFlight = false;
 let consecutiveFailures = 0;
-let onlineListener: (() => void) | null = null;
+/** Delay chosen for the pending retry, so the timer and nextRetryAt agree */
+let nextRetryDelayMs: number | null = null;
+let removeWakeListeners: (() => void) | null = null;
+let pushDebounceTimer: ReturnType<typeof setTimeout> | null = null;
+let pushDebounceMs = DEFAULT_PUSH_DEBOUNCE_MS;
+/** User paused automatic sync (persisted as sync_config `sync_paused`). */
+let paused = false;
+/** Tables this device pushes/pulls (from sync_config `sync_scopes`). */
+let scopedTables: ReadonlySet<string> = SYNCED_TABLES;
 let currentStatus: SyncEngineStatus = {
   state: 'disabled',
   lastSyncTime: null,
   pendingChanges: 0,
   connectedDevices: [],
   error: null,
+  nextRetryAt: null,
 };
 const statusListeners = new Set<StatusListener>();
 
@@ -136,6 +200,36 @@ export function getSyncEngineStatus(): SyncEngineStatus {
   return { ...currentStatus };
 }
 
+/**
+ * Re-read the unflushed change_log count into `pendingChanges`.
+ * Called after every local write (database.ts logChange) and after each flush,
+ * so the badge counts writes made between sync cycles.
+ */
+export async function refreshPendingChanges(): Promise<void> {
+  try {
+    const pendingChanges = await countUnflushedChanges();
+    if (pendingChanges !== currentStatus.pendingChanges) {
+      notifyStatusChange({ pendingChanges });
+    }
+  } catch (error) {
+    console.error('[SyncEngine] Failed to count pending changes:', error);
+  }
+}
+
+/**
+ * Hook for database.ts after every local write: refresh the pending badge and
+ * schedule a debounced push. Each write restarts the quiet period.
+ */
+export function notifyLocalWrite(): void {
+  void refreshPendingChanges();
+  if (!backend || paused) return;
+  if (pushDebounceTimer !== null) clearTimeout(pushDebounceTimer);
+  pushDebounceTimer = setTimeout(() => {
+    pushDebounceTimer = null;
+    void scheduledSync();
+  }, pushDebounceMs);
+}
+
 // ============================================================================
 // Initialization
 // ============================================================================
@@ -147,6 +241,7 @@ export function getSyncEngineStatus(): SyncEngineStatus {
  */
 export async function initSyncEngine(): Promise<void> {
   deviceId = await getLocalDeviceId();
+  await refreshPendingChanges();
 
   const accountId = await getSignedInAccount();
   if (accountId) {
@@ -171,13 +266,22 @@ export async function configureHttpBackend(): Promise<void> {
  */
 async function activateHttpBackend({ snapshot }: { snapshot: boolean }): Promise<void> {
   stopFlushTimer();
-  stopOnlineListener();
+  stopWakeListeners();
+  stopPushDebounce();
   inFlight = false;
   consecutiveFailures = 0;
-  backend = new HttpStorageBackend();
+  pushDebounceMs = await loadPushDebounceMs();
+  scopedTables = tablesForScopes(await getSyncScopes());
+  paused = (await getSyncConfig('sync_paused')) === '1';
+  backend = new MeteredStorageBackend(new HttpStorageBackend());
+  if (paused) {
+    // Stay wired up so "Sync Now" works, but make no network calls on our own.
+    notifyStatusChange({ state: 'paused', error: null });
+    return;
+  }
   startFlushTimer();
-  startOnlineListener();
-  notifyStatusChange({ state: 'idle', error: null });
+  startWakeListeners();
+  notifyStatusChange({ state: 'idle', error: null, nextRetryAt: null });
   if (snapshot) {
     try {
       await writeSnapshot();
@@ -196,7 +300,8 @@ async function activateHttpBackend({ snapshot }: { snapshot: boolean }): Promise
  */
 export async function disableSync(): Promise<void> {
   stopFlushTimer();
-  stopOnlineListener();
+  stopWakeListeners();
+  stopPushDebounce();
   inFlight = false;
   consecutiveFailures = 0;
   backend = null;
@@ -204,15 +309,81 @@ export async function disableSync(): Promise<void> {
     state: 'signed-out',
     connectedDevices: [],
     error: null,
+    nextRetryAt: null,
   });
 }
 
+/**
+ * Pause automatic sync (timer, debounced pushes, focus/online wakes) until
+ * {@link resumeSync}. Persists across launches. Local writes keep accumulating
+ * in change_log, and an explicit {@link sync} ("Sync Now") still runs.
+ */
+export async function pauseSync(): Promise<void> {
+  paused = true;
+  await setSyncConfig('sync_paused', '1');
+  stopFlushTimer();
+  stopWakeListeners();
+  stopPushDebounce();
+  if (backend && !inFlight) notifyStatusChange({ state: 'paused', nextRetryAt: null });
+}
+
+/** Resume automatic sync and run a sync immediately to catch up. */
+export async function resumeSync(): Promise<void> {
+  paused = false;
+  await setSyncConfig('sync_paused', '0');
+  if (!backend) return;
+  consecutiveFailures = 0;
+  startFlushTimer();
+  startWakeListeners();
+  notifyStatusChange({ state: 'idle', error: null });
+  await sync();
+}
+
+/** Whether automatic sync is paused. */
+export function isSyncPaused(): boolean {
+  return paused;
+}
+
+/**
+ * The enabled sync scopes (sync_config `sync_scopes`, comma-separated).
+ * Defaults to every scope; unknown names are ignored.
+ */
+export async function getSyncScopes(): Promise<SyncScope[]> {
+  const raw = await getSyncConfig('sync_scopes');
+  if (raw === null) return [...SYNC_SCOPES];
+  const names = new Set(raw.split(',').map(s => s.trim()));
+  return SYNC_SCOPES.filter(s => names.has(s));
+}
+
+/**
+ * Choose which scopes sync. Changes to out-of-scope tables are still recorded
+ * locally but skipped when flushing, and remote changes to them are ignored.
+ * Widening the selection resets watermarks and writes a snapshot so the newly
+ * included tables catch up in both directions.
+ */
+export async function setSyncScopes(scopes: SyncScope[]): Promise<void> {
+  const next = SYNC_SCOPES.filter(s => scopes.includes(s));
+  if (next.length === 0) throw new Error('Select at least one sync scope, or sign out to stop syncing');
+  const previous = new Set(await getSyncScopes());
+  await setSyncConfig('sync_scopes', next.join(','));
+  scopedTables = tablesForScopes(next);
+  if (!backend || !next.some(s => !previous.has(s))) return;
+  await clearSyncWatermarks();
+  try {
+    await writeSnapshot();
+  } catch (err) {
+    console.error('[SyncEngine] Failed to write snapshot after widening scopes (non-fatal):', err);
+  }
+  if (!paused) await sync();
+}
+
 /**
  * Stop the sync engine (app shutdown).
  */
 export async function stopSyncEngine(): Promise<void> {
   stopFlushTimer();
-  stopOnlineListener();
+  stopWakeListeners();
+  stopPushDebounce();
   if (backend) {
     try {
       await flushChanges();
@@ -235,29 +406,45 @@ export async function sync(): Promise<void> {
   // manual "Sync Now", online event) — concurrent runs race journal flushes and
   // watermark writes. The guard lives here so it covers direct callers too.
   if (inFlight) return;
+  // No connectivity: don't spend a failed attempt (or a backoff step). Local
+  // writes stay queued in change_log and the 'online' listener flushes them.
+  if (!isOnline()) {
+    await goOffline();
+    return;
+  }
   inFlight = true;
 
+  const metered = backend;
+  metered.resetCounters();
+  const startedAt = new Date();
+  let pushed = 0;
+  let pulled = 0;
+  let result: 'ok' | 'error' | 'cancelled' = 'ok';
+  let errorMessage: string | null = null;
+
   try {
     notifyStatusChange({ state: 'syncing' });
 
     // 1. Flush local changes to journal files
-    await flushChanges();
+    pushed = await flushChanges();
 
     // 2. Pull and apply changes from other devices
     const { applied, tables } = await pullChanges();
+    pulled = applied;
 
     // 3. Check if compaction is needed
     await maybeCompact();
+    await collectTombstones();
 
     // 4. Update status
     consecutiveFailures = 0;
     const devices = await listConnectedDevices();
     notifyStatusChange({
-      state: 'idle',
+      state: paused ? 'paused' : 'idle',
       lastSyncTime: new Date().toISOString(),
-      pendingChanges: 0,
       connectedDevices: devices,
       error: null,
+      nextRetryAt: null,
     });
 
     if (applied > 0) {
@@ -270,13 +457,16 @@ export async function sync(): Promise<void> {
       }
     }
   } catch (error) {
+    result = isSyncError(error) && error.kind === 'cancelled' ? 'cancelled' : 'error';
+    errorMessage = isSyncError(error) ? error.message : error instanceof Error ? error.message : String(error);
     // Auth error (session expired/revoked): clear token and stop — do not retry.
     // Match any 'auth'-kind error, not just 401, so a revoked-but-403 session
     // can't fall through to the generic branch and retry forever.
     if (isSyncError(error) && error.kind === 'auth') {
       backend = null;
       stopFlushTimer();
-      stopOnlineListener();
+      stopWakeListeners();
+      stopPushDebounce();
       consecutiveFailures = 0;
       // Await so a failed token-clear is logged rather than silently leaving a
       // stale token that would 401 again on next launch.
@@ -289,45 +479,118 @@ export async function sync(): Promise<void> {
         state: 'auth-expired',
         connectedDevices: [],
         error: 'Session expired — sign in again to resume sync',
+        nextRetryAt: null,
       });
       return;
     }
+    // User cancelled a transfer: nothing went wrong, so don't back off.
+    // Unflushed changes stay in change_log and go out on the next sync.
+    if (isSyncError(error) && error.kind === 'cancelled') {
+      notifyStatusChange({ state: paused ? 'paused' : 'idle', error: null });
+      await refreshPendingChanges();
+      return;
+    }
+    // Connectivity dropped mid-cycle: same as starting offline.
+    if (isNetworkError(error) && !isOnline()) {
+      await goOffline();
+      return;
+    }
     consecutiveFailures++;
     console.error('[SyncEngine] Sync failed:', error);
-    notifyStatusChange({
-      state: 'error',
-      error: error instanceof Error ? error.message : String(error),
-    });
+    if (isTransientSyncError(error) && consecutiveFailures <= MAX_RETRY_ATTEMPTS) {
+      nextRetryDelayMs = computeBackoffDelay(consecutiveFailures);
+      notifyStatusChange({
+        state: 'retrying',
+        error: errorMessage,
+        nextRetryAt: new Date(Date.now() + nextRetryDelayMs).toISOString(),
+      });
+      // Re-arm now so the timer fires exactly at nextRetryAt.
+      if (!paused) startFlushTimer();
+    } else {
+      notifyStatusChange({
+        state: 'error',
+        error: error instanceof Error ? error.message : String(error),
+        nextRetryAt: null,
+      });
+    }
   } finally {
+    await logSyncCycle({
+      started_at: startedAt.toISOString(),
+      duration_ms: Date.now() - startedAt.getTime(),
+      direction: pushed > 0 && pulled > 0 ? 'both' : pushed > 0 ? 'push' : pulled > 0 ? 'pull' : 'none',
+      pushed,
+      pulled,
+      bytes_up: metered.bytesWritten,
+      bytes_down: metered.bytesRead,
+      result,
+      error: errorMessage,
+    });
     inFlight = false;
   }
 }
 
+/**
+ * Report 'offline' with the queued change count. Retry timers are dropped;
+ * the next attempt comes from the 'online' event (or the regular interval).
+ */
+async function goOffline(): Promise<void> {
+  nextRetryDelayMs = null;
+  await refreshPendingChanges();
+  notifyStatusChange({ state: paused ? 'paused' : 'offline', error: null, nextRetryAt: null });
+}
+
+/** Best-effort sync_history insert; history must never fail a sync. */
+async function logSyncCycle(entry: Omit<SyncHistoryEntry, 'id'>): Promise<void> {
+  try {
+    await recordSyncHistory(entry);
+  } catch (error) {
+    console.error('[SyncEngine] Failed to record sync history:', error);
+  }
+}
+
+/**
+ * The most recent sync cycles (newest first): when each ran, which way data
+ * moved, how much, and whether it succeeded.
+ */
+export async function getSyncHistory(limit = 50): Promise<SyncHistoryEntry[]> {
+  return sqliteGetSyncHistory(limit);
+}
+
 // ============================================================================
 // Journal Writer
 // ============================================================================
 
 /**
  * Flush pending changes from change_log to a journal file in the sync folder.
+ * Returns the number of entries flushed.
  */
-async function flushChanges(): Promise<void> {
-  if (!backend) return;
+async function flushChanges(): Promise<number> {
+  if (!backend) return 0;
 
   const changes = await getUnflushedChanges();
-  if (changes.length === 0) return;
-
-  const entries: ChangeEntry[] = changes.map(c => ({
-    seq: c.seq,
-    ts: c.updated_at,
-    device: c.device_id,
-    table: c.table_name,
-    op: c.op as 'upsert' | 'delete',
-    id: c.row_id,
-    data: c.data ? JSON.parse(c.data) : undefined,
-  }));
+  if (changes.length === 0) return 0;
+
+  const entries: ChangeEntry[] = changes
+    .filter(c => scopedTables.has(c.table_name))
+    .map(c => ({
+      seq: c.seq,
+      ts: c.updated_at,
+      device: c.device_id,
+      table: c.table_name,
+      op: c.op as 'upsert' | 'delete',
+      id: c.row_id,
+      data: c.data ? JSON.parse(c.data) : undefined,
+    }));
 
   const maxSeq = changes[changes.length - 1].seq;
 
+  // Everything pending is out of scope: consume it without an upload.
+  if (entries.length === 0) {
+    await markChangesFlushed(maxSeq);
+    await refreshPendingChanges();
+    return 0;
+  }
+
   const journal: JournalFile = {
     version: 1,
     device: deviceId,
@@ -345,8 +608,10 @@ async function flushChanges(): Promise<void> {
   // Update device meta
   await writeDeviceMeta(maxSeq);
 
-  notifyStatusChange({ pendingChanges: 0 });
+  // Writes made while the journal was uploading stay pending for the next cycle.
+  await refreshPendingChanges();
   console.log(`[SyncEngine] Flushed ${entries.length} changes to ${filePath}`);
+  return entries.length;
 }
 
 // ============================================================================
@@ -378,7 +643,8 @@ async function pullChanges(): Promise<PullResult> {
       totalApplied += result.applied;
       result.tables.forEach((t) => allTables.add(t));
     } catch (error) {
-      if (isSyncError(error) && error.kind === 'auth') throw error; // 401 → propagate to sync()
+      // 401 and user cancellation → propagate to sync()
+      if (isSyncError(error) && (error.kind === 'auth' || error.kind === 'cancelled')) throw error;
       console.error(`[SyncEngine] Failed to pull from device ${remoteDevice}:`, error);
     }
   }
@@ -431,7 +697,7 @@ async function pullFromDevice(remoteDevice: string): Promise<PullResult> {
 
       for (const entry of journal.entries) {
         if (entry.seq <= watermark) continue;
-        if (!SYNCED_TABLES.has(entry.table)) continue;
+        if (!scopedTables.has(entry.table)) continue;
         if (entry.op !== 'upsert' && entry.op !== 'delete') continue;
 
         const wasApplied = await applyRemoteChange(
@@ -482,11 +748,15 @@ async function writeSnapshot(): Promise<void> {
   // Build the snapshot tables from SNAPSHOT_TABLE_KEYS so coverage of SYNCED_TABLES
   // stays enforceable by a single test (see snapshot-coverage.test.ts). Adding a synced
   // table without listing it here would silently drop it from snapshots/bootstrap.
+  // Out-of-scope tables are left out entirely (not written as empty arrays).
   const tables: Record<string, unknown[]> = {};
   for (const key of SNAPSHOT_TABLE_KEYS) {
+    if (!scopedTables.has(camelToSnakeTable(key))) continue;
     tables[key] = exportData[key as keyof typeof exportData] as unknown[];
   }
-  tables.preferences = exportData.preferences ? [exportData.preferences] : [];
+  if (scopedTables.has('preferences')) {
+    tables.preferences = exportData.preferences ? [exportData.preferences] : [];
+  }
 
   const snapshot: SnapshotFile = {
     version: 1,
@@ -494,6 +764,7 @@ async function writeSnapshot(): Promise<void> {
     atSeq: maxSeq,
     createdAt: new Date().toISOString(),
     tables,
+    tombstones: (await getTombstones()).filter(t => scopedTables.has(t.table_name)),
   };
 
   const snapshotPath = `snapshots/${deviceId}_${maxSeq}.json`;
@@ -522,51 +793,84 @@ async function bootstrapFromSnapshot(remoteDevice: string): Promise<PullResult>
 
   const latestSnapshot = deviceSnapshots[0];
 
-  try {
-    const content = await backend.readText(`snapshots/${latestSnapshot}`);
-    if (content === null) return { applied: 0, tables: new Set() };
-    const snapshot = JSON.parse(content) as SnapshotFile;
-
-    if (snapshot.version !== 1) return { applied: 0, tables: new Set() };
-
-    let applied = 0;
-    const tables = new Set<string>();
-
-    // Apply each table's data
-    for (const [tableName, records] of Object.entries(snapshot.tables)) {
-      // Map camelCase table names to snake_case
-      const dbTableName = camelToSnakeTable(tableName);
-      if (!SYNCED_TABLES.has(dbTableName)) continue;
-
-      for (const record of records as Array<Record<string, unknown>>) {
-        const recordId = (record.id as string) ?? 'main';
-        const updatedAt = (record.updatedAt as string) ?? snapshot.createdAt;
-
-        const wasApplied = await applyRemoteChange(
-          dbTableName,
-          'upsert',
-          recordId,
-          JSON.stringify(record),
-          typeof updatedAt === 'string' ? updatedAt : new Date(updatedAt as number).toISOString(),
-          snapshot.device
-        );
+  const storage = backend;
+  return withWritesLocked(`applying ${remoteDevice} snapshot`, async (): Promise<PullResult> => {
+    try {
+      const content = await storage.readText(`snapshots/${latestSnapshot}`);
+      if (content === null) return { applied: 0, tables: new Set() };
+      const snapshot = JSON.parse(content) as SnapshotFile;
+
+      if (snapshot.version !== 1) return { applied: 0, tables: new Set() };
+
+      let applied = 0;
+      const tables = new Set<string>();
+
+      // Apply each table's data
+      for (const [tableName, records] of Object.entries(snapshot.tables)) {
+        // Map camelCase table names to snake_case
+        const dbTableName = camelToSnakeTable(tableName);
+        if (!scopedTables.has(dbTableName)) continue;
+
+        for (const record of records as Array<Record<string, unknown>>) {
+          const recordId = (record.id as string) ?? 'main';
+          const updatedAt = (record.updatedAt as string) ?? snapshot.createdAt;
+
+          const wasApplied = await applyRemoteChange(
+            dbTableName,
+            'upsert',
+            recordId,
+            JSON.stringify(record),
+            typeof updatedAt === 'string' ? updatedAt : new Date(updatedAt as number).toISOString(),
+            snapshot.device
+          );
+
+          if (wasApplied) {
+            applied++;
+            tables.add(dbTableName);
+          }
+        }
+      }
 
-        if (wasApplied) {
-          applied++;
-          tables.add(dbTableName);
+      // Deletions the snapshot device knows about, so journals from other
+      // devices can't resurrect them here.
+      for (const t of snapshot.tombstones ?? []) {
+        if (!scopedTables.has(t.table_name)) continue;
+        if (await applyRemoteChange(t.table_name, 'delete', t.row_id, null, t.deleted_at, t.device_id)) {
+          tables.add(t.table_name);
         }
       }
-    }
 
-    // Set watermark to the snapshot's seq
-    await setSyncWatermark(remoteDevice, snapshot.atSeq);
+      // Set watermark to the snapshot's seq
+      await setSyncWatermark(remoteDevice, snapshot.atSeq);
 
-    console.log(`[SyncEngine] Bootstrapped ${applied} records from ${remoteDevice} snapshot`);
-    return { applied, tables };
-  } catch (error) {
-    if (isSyncError(error) && error.kind === 'auth') throw error; // 401 → propagate up
-    console.error(`[SyncEngine] Failed to load snapshot ${latestSnapshot}:`, error);
-    return { applied: 0, tables: new Set() };
+      console.log(`[SyncEngine] Bootstrapped ${applied} records from ${remoteDevice} snapshot`);
+      return { applied, tables };
+    } catch (error) {
+      if (isSyncError(error) && error.kind === 'auth') throw error; // 401 → propagate up
+      console.error(`[SyncEngine] Failed to load snapshot ${latestSnapshot}:`, error);
+      return { applied: 0, tables: new Set() };
+    }
+  });
+}
+
+/**
+ * Run `fn` with native writes paused (they fail `database_locked`), so bulk
+ * commands, imports and maintenance can't interleave with data sync is still
+ * bringing in. The engine's own applies are let through: most go through the
+ * SQL plugin, and the native ones (`update_annotation` with `remote`, and
+ * `apply_remote_delete`) take the writer's sync path, which the lock doesn't
+ * stop.
+ */
+async function withWritesLocked<T>(reason: string, fn: () => Promise<T>): Promise<T> {
+  await sqliteSetWriteLock(true, reason).catch(error => {
+    console.warn('[SyncEngine] Could not pause native writes:', error);
+  });
+  try {
+    return await fn();
+  } finally {
+    await sqliteSetWriteLock(false).catch(error => {
+      console.error('[SyncEngine] Could not resume native writes:', error);
+    });
   }
 }
 
@@ -589,6 +893,21 @@ async function maybeCompact(): Promise<void> {
   await compact();
 }
 
+/**
+ * Rewrite this device's remote data under the current encryption key: a fresh
+ * snapshot replaces the journals sealed with the previous key (or none).
+ */
+export async function resealSyncData(): Promise<void> {
+  if (!backend || inFlight) return;
+  inFlight = true;
+  try {
+    await flushChanges();
+    await compact();
+  } finally {
+    inFlight = false;
+  }
+}
+
 /**
  * Compact: write a snapshot, then delete old journal files.
  */
@@ -621,6 +940,20 @@ async function compact(): Promise<void> {
   console.log(`[SyncEngine] Compaction done, pruned up to seq ${lastSnapshotSeq}`);
 }
 
+/**
+ * Drop tombstones past the GC window (`tombstone_gc_days`, default 90).
+ * Non-fatal: a failed prune just leaves the rows for the next cycle.
+ */
+async function collectTombstones(): Promise<void> {
+  try {
+    const raw = await getSyncConfig('tombstone_gc_days');
+    const days = raw === null ? NaN : Number(raw);
+    await pruneTombstones(Number.isFinite(days) && days > 0 ? days : DEFAULT_TOMBSTONE_GC_DAYS);
+  } catch (error) {
+    console.error('[SyncEngine] Failed to prune tombstones:', error);
+  }
+}
+
 /**
  * Remove old snapshot files, keeping only the latest per device.
  */
@@ -736,15 +1069,96 @@ async function listConnectedDevices(): Promise<string[]> {
   return names;
 }
 
+// ============================================================================
+// Device Registry
+// ============================================================================
+
+/**
+ * Every device folder in the account, with its meta.json details. Devices
+ * without readable metadata are still listed (by id) so they can be forgotten.
+ */
+export async function listSyncDevices(): Promise<SyncDevice[]> {
+  if (!backend) return [];
+  const devices: SyncDevice[] = [];
+  for (const folder of await listDeviceFolders()) {
+    let meta: Partial<DeviceMeta> = {};
+    try {
+      const content = await backend.readText(`${folder}/meta.json`);
+      if (content !== null) meta = JSON.parse(content) as DeviceMeta;
+    } catch (error) {
+      if (isSyncError(error) && error.kind === 'auth') throw error;
+    }
+    devices.push({
+      deviceId: folder,
+      deviceName: meta.deviceName || folder,
+      platform: meta.platform ?? 'unknown',
+      lastSeq: meta.lastSeq ?? 0,
+      lastSeen: meta.updatedAt ?? null,
+      isCurrent: folder === deviceId,
+    });
+  }
+  return devices.sort((a, b) => (b.lastSeen ?? '').localeCompare(a.lastSeen ?? ''));
+}
+
+/**
+ * Remove a stale device from the account: delete its journals, meta.json and
+ * snapshots from the sync backend and drop our pull watermark for it.
+ *
+ * Changes already pulled from that device stay in the local database. Anything
+ * it had not yet flushed is gone from the account; if the device is still in
+ * use it re-registers itself on its next sync.
+ */
+export async function forgetDevice(id: string): Promise<void> {
+  if (!backend) throw new Error('Sync is not active');
+  if (id === deviceId) throw new Error('Cannot forget the current device');
+  if (!DEVICE_ID_RE.test(id)) throw new Error(`Invalid device id: ${id}`);
+
+  for (const entry of await backend.list(id)) {
+    if (!entry.isDirectory) await backend.remove(`${id}/${entry.name}`);
+  }
+  for (const entry of await backend.list('snapshots')) {
+    if (entry.name.startsWith(`${id}_`)) await backend.remove(`snapshots/${entry.name}`);
+  }
+  await clearSyncWatermark(id);
+
+  notifyStatusChange({ connectedDevices: await listConnectedDevices() });
+}
+
+/**
+ * Exponential backoff with jitter for the `failures`-th consecutive failure:
+ * 30s, 1m, 2m, 4m, then 5m, each ±20%.
+ */
+export function computeBackoffDelay(failures: number, random: () => number = Math.random): number {
+  const exp = Math.min(BACKOFF_BASE_MS * 2 ** Math.max(failures - 1, 0), BACKOFF_MAX_MS);
+  const jitter = 1 + BACKOFF_JITTER * (2 * random() - 1);
+  return Math.round(exp * jitter);
+}
+
+/**
+ * Failures worth retrying quietly: network/timeouts (offline.ts
+ * isNetworkError), server errors and rate limiting. Client errors and a full
+ * quota won't fix themselves.
+ */
+function isTransientSyncError(error: unknown): boolean {
+  if (isNetworkError(error)) return true;
+  return isSyncError(error) && (error.kind === 'rate_limit' || (error.kind === 'server' && error.statusCode >= 500));
+}
+
 function startFlushTimer(): void {
   stopFlushTimer();
-  const interval = consecutiveFailures > 0
-    ? BACKOFF_INTERVALS_MS[Math.min(consecutiveFailures - 1, BACKOFF_INTERVALS_MS.length - 1)]
-    : FLUSH_INTERVAL_MS;
-  flushTimer = setTimeout(() => void scheduledSync(), interval);
+  let interval = FLUSH_INTERVAL_MS;
+  if (consecutiveFailures > 0) {
+    interval = nextRetryDelayMs ?? computeBackoffDelay(consecutiveFailures);
+  }
+  nextRetryDelayMs = null;
+  flushTimer = setTimeout(() => {
+    flushTimer = null;
+    void scheduledSync();
+  }, interval);
 }
 
 async function scheduledSync(): Promise<void> {
+  if (paused) return;
   // sync() self-guards against overlap (early-returns if already in flight) and
   // resets backoff on success, so the next interval is computed correctly here.
   try {
@@ -752,7 +1166,8 @@ async function scheduledSync(): Promise<void> {
   } catch (error) {
     console.error('[SyncEngine] Periodic sync failed:', error);
   } finally {
-    if (backend) startFlushTimer();
+    // Keep a retry timer armed by a failed sync rather than replacing it.
+    if (backend && !paused && flushTimer === null) startFlushTimer();
   }
 }
 
@@ -763,22 +1178,61 @@ function stopFlushTimer(): void {
   }
 }
 
-function startOnlineListener(): void {
-  stopOnlineListener();
+function stopPushDebounce(): void {
+  if (pushDebounceTimer !== null) {
+    clearTimeout(pushDebounceTimer);
+    pushDebounceTimer = null;
+  }
+}
+
+async function loadPushDebounceMs(): Promise<number> {
+  const raw = await getSyncConfig('push_debounce_ms');
+  const parsed = raw === null ? NaN : Number(raw);
+  return Number.isFinite(parsed) && parsed >= 0 ? parsed : DEFAULT_PUSH_DEBOUNCE_MS;
+}
+
+/**
+ * Sync when connectivity returns and when the app comes back to the
+ * foreground (window focus on desktop, visibilitychange on mobile resume).
+ * Foreground wakes are skipped if a sync completed very recently.
+ */
+function startWakeListeners(): void {
+  stopWakeListeners();
   if (typeof window === 'undefined' || typeof window.addEventListener !== 'function') return;
+  if (typeof document === 'undefined') return;
   // Route through scheduledSync so the inFlight guard is honored — calling sync()
   // raw here could double-fire alongside a timer tick.
-  const handler = (): void => {
+  const online = (): void => {
     if (backend) void scheduledSync();
   };
-  window.addEventListener('online', handler);
-  onlineListener = handler;
+  const offline = (): void => {
+    if (backend && !inFlight) void goOffline();
+  };
+  const resume = (): void => {
+    if (!backend) return;
+    const last = currentStatus.lastSyncTime ? Date.parse(currentStatus.lastSyncTime) : 0;
+    if (Date.now() - last < RESUME_MIN_INTERVAL_MS) return;
+    void scheduledSync();
+  };
+  const onVisibility = (): void => {
+    if (document.visibilityState === 'visible') resume();
+  };
+  window.addEventListener('online', online);
+  window.addEventListener('offline', offline);
+  window.addEventListener('focus', resume);
+  document.addEventListener('visibilitychange', onVisibility);
+  removeWakeListeners = (): void => {
+    window.removeEventListener('online', online);
+    window.removeEventListener('offline', offline);
+    window.removeEventListener('focus', resume);
+    document.removeEventListener('visibilitychange', onVisibility);
+  };
 }
 
-function stopOnlineListener(): void {
-  if (onlineListener !== null && typeof window !== 'undefined') {
-    window.removeEventListener('online', onlineListener);
-    onlineListener = null;
+function stopWakeListeners(): void {
+  if (removeWakeListeners !== null) {
+    removeWakeListeners();
+    removeWakeListeners = null;
   }
 }
 
@@ -51,6 +51,17 @@ describe('getSyncStatusMessage', () => {
     expect(getSyncStatusMessage(status)).toBe('Offline (5 pending)');
   });
 
+  it('returns the retry countdown for retrying state', () => {
+    const next = new Date(Date.now() + 45_000).toISOString();
+    const status = makeSyncStatus({ state: 'retrying', next_retry: next });
+    expect(getSyncStatusMessage(status)).toMatch(/^Connection problem — retrying in 4\ds$/);
+  });
+
+  it('returns "Sync paused (N pending)" for paused state', () => {
+    const status = makeSyncStatus({ state: 'paused', pending_changes: 2 });
+    expect(getSyncStatusMessage(status)).toBe('Sync paused (2 pending)');
+  });
+
   it('returns error message for error state', () => {
     const status = makeSyncStatus({ state: 'error', error: 'Disk full' });
     expect(getSyncStatusMessage(status)).toBe('Sync error: Disk full');
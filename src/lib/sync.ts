@@ -5,6 +5,7 @@
  * HTTP is the only sync transport; sign in with email-OTP to enable sync.
  */
 
+import { invoke } from '@tauri-apps/api/core';
 import { getPreferences } from './database';
 import { isFlagEnabled, FLAG_KEYS } from './feature-flags';
 import {
@@ -13,15 +14,31 @@ import {
   signOut as accountSignOut,
   deleteAccount as accountDeleteAccount,
   clearLocalSession as accountClearLocalSession,
+  cancelSyncTransfers,
+  setupSyncEncryption as accountSetupEncryption,
+  rotateSyncEncryption as accountRotateEncryption,
+  disableSyncEncryption as accountDisableEncryption,
+  type EncryptionStatus,
   isSyncError,
 } from './sync-account';
-import { clearSyncWatermarks } from './sqlite-db';
+import { clearSyncWatermarks, isDemoDatabase, type SyncHistoryEntry } from './sqlite-db';
+import type { SyncScope } from './table-registry';
 import {
   initSyncEngine,
   stopSyncEngine,
   sync as engineSync,
   configureHttpBackend,
   disableSync as engineDisableSync,
+  pauseSync as enginePauseSync,
+  resumeSync as engineResumeSync,
+  listSyncDevices as engineListSyncDevices,
+  forgetDevice as engineForgetDevice,
+  getSyncHistory as engineGetSyncHistory,
+  resealSyncData,
+  refreshPendingChanges,
+  getSyncScopes as engineGetSyncScopes,
+  setSyncScopes as engineSetSyncScopes,
+  type SyncDevice,
   onSyncEngineStatusChange,
   getSyncEngineStatus,
   type SyncEngineStatus,
@@ -33,7 +50,7 @@ import {
 // ============================================================================
 
 /** Sync state for UI display */
-export type SyncState = 'synced' | 'syncing' | 'offline' | 'error' | 'disabled' | 'signed-out' | 'auth-expired';
+export type SyncState = 'synced' | 'syncing' | 'retrying' | 'paused' | 'offline' | 'error' | 'disabled' | 'signed-out' | 'auth-expired';
 
 /** Sync status from the engine, shaped for the UI */
 export interface SyncStatus {
@@ -42,6 +59,8 @@ export interface SyncStatus {
   pending_changes: number;
   error: string | null;
   connected_devices: string[];
+  /** ISO time of the next automatic attempt while state is 'retrying' */
+  next_retry: string | null;
 }
 
 // ============================================================================
@@ -54,6 +73,7 @@ let currentSyncStatus: SyncStatus = {
   pending_changes: 0,
   error: null,
   connected_devices: [],
+  next_retry: null,
 };
 
 type SyncStatusListener = (status: SyncStatus) => void;
@@ -63,6 +83,9 @@ function mapEngineState(engineState: SyncEngineState): SyncState {
   switch (engineState) {
     case 'idle': return 'synced';
     case 'syncing': return 'syncing';
+    case 'retrying': return 'retrying';
+    case 'paused': return 'paused';
+    case 'offline': return 'offline';
     case 'disabled': return 'disabled';
     case 'error': return 'error';
     case 'signed-out': return 'signed-out';
@@ -78,6 +101,7 @@ function engineStatusToSyncStatus(es: SyncEngineStatus): SyncStatus {
     pending_changes: es.pendingChanges,
     error: es.error,
     connected_devices: es.connectedDevices,
+    next_retry: es.nextRetryAt,
   };
 }
 
@@ -106,6 +130,11 @@ export function onSyncStatusChange(listener: SyncStatusListener): () => void {
  * the `forceSyncEnabled` debug flag overrides this. Release builds always sync.
  */
 async function isSyncAllowed(): Promise<{ allowed: boolean; reason?: string }> {
+  // Demo data must never reach a real account, whatever the overrides say.
+  if (await isDemoDatabase()) {
+    return { allowed: false, reason: 'demo database' };
+  }
+
   const isDev = import.meta.env.DEV;
 
   if (isDev) {
@@ -143,6 +172,7 @@ export async function initializeSync(): Promise<void> {
       pending_changes: 0,
       error: null,
       connected_devices: [],
+      next_retry: null,
     });
     return;
   }
@@ -172,7 +202,7 @@ export async function shutdownSync(): Promise<void> {
 // ============================================================================
 
 /**
- * Trigger a manual sync.
+ * Trigger a manual sync ("Sync Now"). Runs even while automatic sync is paused.
  */
 export async function triggerSync(): Promise<boolean> {
   try {
@@ -184,6 +214,147 @@ export async function triggerSync(): Promise<boolean> {
   }
 }
 
+/**
+ * Pause automatic syncing (e.g. on a metered connection). Persists across
+ * launches; local changes queue up until resumed or synced manually.
+ */
+export async function pauseSync(): Promise<void> {
+  await enginePauseSync();
+}
+
+/**
+ * Resume automatic syncing and catch up immediately.
+ */
+export async function resumeSync(): Promise<void> {
+  await engineResumeSync();
+}
+
+/**
+ * Devices that have synced to this account, most recently seen first.
+ */
+export async function listSyncDevices(): Promise<SyncDevice[]> {
+  return engineListSyncDevices();
+}
+
+/**
+ * Remove a stale device's data from the account. Refuses the current device.
+ */
+export async function forgetDevice(id: string): Promise<void> {
+  await engineForgetDevice(id);
+}
+
+export type { SyncDevice };
+
+/**
+ * What this device syncs: `annotations` (study data) and/or `settings`
+ * (preferences, saved views). Both by default.
+ */
+export async function getSyncScopes(): Promise<SyncScope[]> {
+  return engineGetSyncScopes();
+}
+
+/** Choose what this device syncs. Requires at least one scope. */
+export async function setSyncScopes(scopes: SyncScope[]): Promise<void> {
+  await engineSetSyncScopes(scopes);
+}
+
+export { SYNC_SCOPES, type SyncScope } from './table-registry';
+
+/**
+ * Recent sync cycles, newest first — for "my iPad isn't updating" debugging.
+ */
+export async function getSyncHistory(limit = 50): Promise<SyncHistoryEntry[]> {
+  return engineGetSyncHistory(limit);
+}
+
+export type { SyncHistoryEntry } from './sqlite-db';
+
+/**
+ * Cancel the running sync's in-flight uploads/downloads. The engine returns to
+ * idle; anything not yet pushed is retried on the next sync.
+ */
+export async function cancelSync(): Promise<void> {
+  await cancelSyncTransfers();
+}
+
+export { onSyncProgress, type SyncProgress } from './sync-account';
+
+/**
+ * Turn on end-to-end encryption with `passphrase`, then rewrite this device's
+ * remote data so nothing it synced stays readable on the server.
+ */
+export async function enableSyncEncryption(passphrase: string): Promise<EncryptionStatus> {
+  const status = await accountSetupEncryption(passphrase);
+  await resealSyncData();
+  return status;
+}
+
+/**
+ * Change the encryption passphrase. Other devices must enter the new one
+ * before they can read data synced from here afterwards.
+ */
+export async function rotateSyncEncryption(newPassphrase: string): Promise<EncryptionStatus> {
+  const status = await accountRotateEncryption(newPassphrase);
+  await resealSyncData();
+  return status;
+}
+
+/**
+ * Turn off end-to-end encryption. Already-encrypted data stays readable here;
+ * this device's data is rewritten in plaintext.
+ */
+export async function disableSyncEncryption(): Promise<EncryptionStatus> {
+  const status = await accountDisableEncryption();
+  await resealSyncData();
+  return status;
+}
+
+export {
+  getSyncEncryptionStatus,
+  createSyncPairing,
+  acceptSyncPairing,
+  type EncryptionStatus,
+} from './sync-account';
+
+/** Manifest of a `.bmsync` bundle, as returned by {@link exportSyncBundle}. */
+export interface SyncBundleManifest {
+  version: number;
+  deviceId: string;
+  createdAt: string;
+  since: string | null;
+  rows: number;
+  tombstones: number;
+}
+
+/** Per-table outcome of {@link importSyncBundle}. */
+export interface SyncMergeReport {
+  tables: { table: string; inserted: number; updated: number; deleted: number; keptLocal: number }[];
+  applied: number;
+}
+
+/**
+ * Write synced data changed after `since` (everything if omitted) to a
+ * `.bmsync` bundle file, for moving changes between devices by hand.
+ */
+export async function exportSyncBundle(path: string, since?: string): Promise<SyncBundleManifest> {
+  return invoke<SyncBundleManifest>('export_sync_bundle', { path, since: since ?? null });
+}
+
+/**
+ * Merge a `.bmsync` bundle into this device (newest edit wins per row). Merged
+ * rows are logged as local changes, so cloud sync passes them on.
+ */
+export async function importSyncBundle(path: string): Promise<SyncMergeReport> {
+  const report = await invoke<SyncMergeReport>('import_sync_bundle', { path });
+  await refreshPendingChanges();
+  if (report.applied > 0 && typeof window !== 'undefined') {
+    window.dispatchEvent(new CustomEvent('syncDataChanged', {
+      detail: { applied: report.applied, tables: report.tables.map(t => t.table) },
+    }));
+  }
+  return report;
+}
+
 /**
  * Disable sync entirely.
  */
@@ -225,6 +396,17 @@ export function getSyncStatusMessage(status: SyncStatus): string {
       return 'Sync enabled';
     case 'syncing':
       return 'Syncing...';
+    case 'retrying': {
+      if (!status.next_retry) return 'Connection problem — retrying';
+      const secs = Math.max(0, Math.round((Date.parse(status.next_retry) - Date.now()) / 1000));
+      return secs < 60
+        ? `Connection problem — retrying in ${secs}s`
+        : `Connection problem — retrying in ${Math.round(secs / 60)}m`;
+    }
+    case 'paused':
+      return status.pending_changes > 0
+        ? `Sync paused (${status.pending_changes} pending)`
+        : 'Sync paused';
     case 'offline':
       return `Offline (${status.pending_changes} pending)`;
     case 'error':
@@ -247,6 +429,8 @@ export function getSyncStatusIcon(status: SyncStatus): string {
   switch (status.state) {
     case 'synced': return '\u2713';
     case 'syncing': return '\u21BB';
+    case 'retrying': return '\u21BB';
+    case 'paused': return '\u23F8';
     case 'offline': return '\u25CB';
     case 'error': return '\u26A0';
     case 'disabled': return '\u2212';
@@ -7,6 +7,8 @@ import {
   STUDY_SCOPED_COLUMN_TABLES,
   CLEARED_TABLES,
   SNAPSHOT_TABLE_KEYS,
+  SYNC_SCOPES,
+  tablesForScopes,
   camelToSnakeTable,
 } from './table-registry';
 
@@ -25,11 +27,13 @@ describe('table registry', () => {
   it('derives the exact set of generic-CRUD tables', () => {
     expect([...VALID_TABLE_NAMES].sort()).toEqual(
       [
-        'annotations', 'applications', 'chapter_cache', 'chapter_titles',
-        'conclusions', 'entity_notes', 'interpretations', 'keyword_exclusions',
-        'marking_presets', 'multi_translation_views', 'notes', 'observation_lists',
-        'people', 'places', 'preferences', 'section_headings', 'studies',
-        'time_expressions',
+        'annotations', 'applications', 'audio_positions', 'bookmark_folders', 'bookmarks',
+        'chapter_cache', 'chapter_titles', 'conclusions', 'entity_notes', 'highlight_styles',
+        'interpretations', 'keyword_exclusions', 'margin_notes', 'marking_presets',
+        'marking_templates', 'multi_translation_views', 'notes', 'observation_lists', 'people',
+        'places', 'preferences', 'resource_links', 'section_headings', 'studies',
+        'symbol_library', 'tag_links', 'tags', 'time_expressions', 'verse_collections',
+        'workspaces',
       ].sort()
     );
   });
@@ -37,14 +41,30 @@ describe('table registry', () => {
   it('derives the exact set of synced tables', () => {
     expect([...SYNCED_TABLES].sort()).toEqual(
       [
-        'annotations', 'applications', 'chapter_titles', 'conclusions',
-        'entity_notes', 'interpretations', 'keyword_exclusions', 'marking_presets',
-        'multi_translation_views', 'notes', 'observation_lists', 'people',
-        'places', 'preferences', 'section_headings', 'studies', 'time_expressions',
+        'annotations', 'applications', 'audio_positions', 'bookmark_folders', 'bookmarks',
+        'chapter_titles', 'conclusions', 'entity_notes', 'highlight_styles', 'interpretations',
+        'keyword_exclusions', 'margin_notes', 'marking_presets', 'marking_templates',
+        'multi_translation_views', 'notes', 'observation_lists', 'people', 'places',
+        'preferences', 'resource_links', 'section_headings', 'studies', 'symbol_library',
+        'tag_links', 'tags', 'time_expressions', 'verse_collections', 'workspaces',
       ].sort()
     );
   });
 
+  it('assigns every synced table (and only those) to a sync scope', () => {
+    for (const t of TABLE_REGISTRY) {
+      expect(t.syncScope !== undefined).toBe(t.synced === true);
+    }
+    expect([...tablesForScopes(SYNC_SCOPES)].sort()).toEqual([...SYNCED_TABLES].sort());
+  });
+
+  it('puts preferences, saved views and listening positions in the settings scope', () => {
+    expect([...tablesForScopes(['settings'])].sort()).toEqual(['audio_positions', 'multi_translation_views', 'preferences']);
+    const annotations = tablesForScopes(['annotations']);
+    expect(annotations.has('notes')).toBe(true);
+    expect(annotations.has('preferences')).toBe(false);
+  });
+
   it('derives the study cascade column tables', () => {
     expect([...STUDY_SCOPED_COLUMN_TABLES].sort()).toEqual(
       [
@@ -68,6 +88,10 @@ describe('table registry', () => {
     expect(CLEARED_TABLES).toContain('translation_cache');
   });
 
+  it('empties the trash, undo journal and edit history after the tables whose deletes fill them', () => {
+    expect(CLEARED_TABLES.slice(-3)).toEqual(['edit_history', 'trash', 'undo_journal']);
+  });
+
   // --- structural invariants (catch a mis-flagged new table) ---
 
   it('every generic study-data table also has a real study_id column', () => {
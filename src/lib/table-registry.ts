@@ -1,14 +1,25 @@
 /**
  * Single source of truth for database table metadata.
  *
- * Every per-table list in the codebase (valid CRUD names, synced tables, study
- * cascade columns, clear-on-reset, snapshot keys, camelCase↔snake_case mapping)
- * is DERIVED from this registry. Adding a table used to mean updating ~6 hand-
- * maintained lists in lockstep; missing one caused silent data bugs (e.g. the
- * study_id sync loss fixed in the v11 migration). Add a row here instead, and a
- * coverage test (table-registry.test.ts) enforces the invariants.
+ * Every per-table list in the codebase (valid CRUD names, synced tables, sync
+ * scopes, study cascade columns, clear-on-reset, snapshot keys,
+ * camelCase↔snake_case mapping) is DERIVED from this registry. Adding a table
+ * used to mean updating ~6 hand-maintained lists in lockstep; missing one
+ * caused silent data bugs (e.g. the study_id sync loss fixed in the v11
+ * migration). Add a row here instead, and a coverage test
+ * (table-registry.test.ts) enforces the invariants.
  */
 
+/**
+ * User-selectable groups of synced tables. `annotations` is the study data
+ * (markings, notes, observations, studies); `settings` is preferences, saved
+ * layouts and audio Bible listening positions. Reading history and caches are
+ * device-local and never sync.
+ */
+export type SyncScope = 'annotations' | 'settings';
+
+export const SYNC_SCOPES: readonly SyncScope[] = ['annotations', 'settings'];
+
 export interface TableSpec {
   /** snake_case database table name */
   table: string;
@@ -21,6 +32,11 @@ export interface TableSpec {
   genericCrud?: boolean;
   /** Logged to change_log and synced between devices (SYNCED_TABLES). */
   synced?: boolean;
+  /**
+   * Which user-selectable sync scope a synced table belongs to (required when
+   * `synced`). A device only pushes/pulls tables in its enabled scopes.
+   */
+  syncScope?: SyncScope;
   /**
    * Has a real `study_id` column, so study cascade delete / orphan cleanup
    * filter it with `WHERE study_id = ?` (the cascade `directTables`).
@@ -42,29 +58,57 @@ export interface TableSpec {
  * (matters for clearDatabase / export ordering only, not correctness).
  */
 export const TABLE_REGISTRY: readonly TableSpec[] = [
-  { table: 'annotations', camelKey: 'annotations', genericCrud: true, synced: true, clearedOnReset: true },
-  { table: 'section_headings', camelKey: 'sectionHeadings', genericCrud: true, synced: true, studyColumn: true, clearedOnReset: true },
-  { table: 'chapter_titles', camelKey: 'chapterTitles', genericCrud: true, synced: true, studyColumn: true, clearedOnReset: true },
-  { table: 'notes', camelKey: 'notes', genericCrud: true, synced: true, clearedOnReset: true },
-  { table: 'marking_presets', camelKey: 'markingPresets', genericCrud: true, synced: true, studyColumn: true, clearedOnReset: true },
-  { table: 'studies', camelKey: 'studies', genericCrud: true, synced: true, clearedOnReset: true },
-  { table: 'multi_translation_views', camelKey: 'multiTranslationViews', genericCrud: true, synced: true, clearedOnReset: true },
-  { table: 'observation_lists', camelKey: 'observationLists', genericCrud: true, synced: true, studyColumn: true, studyDataTable: true, clearedOnReset: true },
-  { table: 'time_expressions', camelKey: 'timeExpressions', genericCrud: true, synced: true, clearedOnReset: true },
-  { table: 'places', camelKey: 'places', genericCrud: true, synced: true, clearedOnReset: true },
-  { table: 'people', camelKey: 'people', genericCrud: true, synced: true, clearedOnReset: true },
-  { table: 'conclusions', camelKey: 'conclusions', genericCrud: true, synced: true, clearedOnReset: true },
-  { table: 'interpretations', camelKey: 'interpretations', genericCrud: true, synced: true, studyColumn: true, studyDataTable: true, clearedOnReset: true },
-  { table: 'applications', camelKey: 'applications', genericCrud: true, synced: true, studyColumn: true, studyDataTable: true, clearedOnReset: true },
-  { table: 'entity_notes', camelKey: 'entityNotes', genericCrud: true, synced: true, studyColumn: true, studyDataTable: true, clearedOnReset: true },
-  { table: 'keyword_exclusions', camelKey: 'keywordExclusions', genericCrud: true, synced: true, clearedOnReset: true },
+  { table: 'annotations', camelKey: 'annotations', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'section_headings', camelKey: 'sectionHeadings', genericCrud: true, synced: true, syncScope: 'annotations', studyColumn: true, clearedOnReset: true },
+  { table: 'chapter_titles', camelKey: 'chapterTitles', genericCrud: true, synced: true, syncScope: 'annotations', studyColumn: true, clearedOnReset: true },
+  { table: 'notes', camelKey: 'notes', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'marking_presets', camelKey: 'markingPresets', genericCrud: true, synced: true, syncScope: 'annotations', studyColumn: true, clearedOnReset: true },
+  { table: 'studies', camelKey: 'studies', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'multi_translation_views', camelKey: 'multiTranslationViews', genericCrud: true, synced: true, syncScope: 'settings', clearedOnReset: true },
+  { table: 'observation_lists', camelKey: 'observationLists', genericCrud: true, synced: true, syncScope: 'annotations', studyColumn: true, studyDataTable: true, clearedOnReset: true },
+  { table: 'time_expressions', camelKey: 'timeExpressions', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'places', camelKey: 'places', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'people', camelKey: 'people', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'conclusions', camelKey: 'conclusions', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'interpretations', camelKey: 'interpretations', genericCrud: true, synced: true, syncScope: 'annotations', studyColumn: true, studyDataTable: true, clearedOnReset: true },
+  { table: 'applications', camelKey: 'applications', genericCrud: true, synced: true, syncScope: 'annotations', studyColumn: true, studyDataTable: true, clearedOnReset: true },
+  { table: 'entity_notes', camelKey: 'entityNotes', genericCrud: true, synced: true, syncScope: 'annotations', studyColumn: true, studyDataTable: true, clearedOnReset: true },
+  { table: 'keyword_exclusions', camelKey: 'keywordExclusions', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // The user's symbol vocabulary (Rust migration 20), with the study data it marks.
+  { table: 'symbol_library', camelKey: 'symbolLibrary', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // Hierarchical tags and what they're on (Rust migration 21).
+  { table: 'tags', camelKey: 'tags', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'tag_links', camelKey: 'tagLinks', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // Passages kept for coming back to, and their folders (Rust migration 23).
+  { table: 'bookmark_folders', camelKey: 'bookmarkFolders', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  { table: 'bookmarks', camelKey: 'bookmarks', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // Named, ordered verse collections (Rust migration 24).
+  { table: 'verse_collections', camelKey: 'verseCollections', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // Topical study workspaces (Rust migration 25).
+  { table: 'workspaces', camelKey: 'workspaces', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // The palette of styles text marks are drawn with (Rust migration 26).
+  { table: 'highlight_styles', camelKey: 'highlightStyles', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // Key words marked across a book in one go, and the look they get (Rust migration 27).
+  { table: 'marking_templates', camelKey: 'markingTemplates', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // Notes on a chapter or pericope, written in its margin (Rust migration 28).
+  { table: 'margin_notes', camelKey: 'marginNotes', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // Links from notes and marks to articles, Logos resources and files (Rust migration 29).
+  { table: 'resource_links', camelKey: 'resourceLinks', genericCrud: true, synced: true, syncScope: 'annotations', clearedOnReset: true },
+  // Where listening stopped in each audio Bible (Rust migration 18).
+  { table: 'audio_positions', camelKey: 'audioPositions', genericCrud: true, synced: true, syncScope: 'settings', clearedOnReset: true },
   // Synced singleton, but intentionally preserved across a database clear.
-  { table: 'preferences', camelKey: 'preferences', genericCrud: true, synced: true },
+  { table: 'preferences', camelKey: 'preferences', genericCrud: true, synced: true, syncScope: 'settings' },
   // Caches / history: local-only, wiped on reset. chapter_cache is also exposed
   // through generic CRUD; reading_history and translation_cache are not.
   { table: 'chapter_cache', genericCrud: true, clearedOnReset: true },
   { table: 'reading_history', clearedOnReset: true },
   { table: 'translation_cache', clearedOnReset: true },
+  // Deleted notes/annotations kept for restore, the undo journal and each
+  // note's and annotation's edit history (Rust migrations 16-17, 31). Last,
+  // so they are emptied after clearing the tables above fills them.
+  { table: 'edit_history', clearedOnReset: true },
+  { table: 'trash', clearedOnReset: true },
+  { table: 'undo_journal', clearedOnReset: true },
 ];
 
 const tablesWhere = (pred: (t: TableSpec) => boolean | undefined): string[] =>
@@ -80,6 +124,12 @@ export const SYNCED_TABLES: ReadonlySet<string> = new Set(
   tablesWhere((t) => t.synced)
 );
 
+/** Synced tables belonging to any of `scopes`. */
+export function tablesForScopes(scopes: Iterable<SyncScope>): ReadonlySet<string> {
+  const wanted = new Set(scopes);
+  return new Set(tablesWhere((t) => t.synced && t.syncScope !== undefined && wanted.has(t.syncScope)));
+}
+
 /** Generic JSON-data tables that also maintain a study_id column. */
 export const STUDY_COLUMN_DATA_TABLES: ReadonlySet<string> = new Set(
   tablesWhere((t) => t.studyDataTable)
@@ -0,0 +1,113 @@
+/**
+ * Tags
+ *
+ * Labels the user files study data under, nested by path
+ * (`Attributes of God/Faithfulness`), with an optional color. A tag can be
+ * put on annotations (highlights, symbols, underlines), notes and
+ * observation lists. Kept natively in the synced `tags` and `tag_links`
+ * tables (db/tags.rs); wherever a command takes a tag, it accepts the tag's
+ * id or its path, matched without regard to case.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/** What a tag can be put on; `verseList` is an observation list. */
+export type TagItemType = 'annotation' | 'note' | 'verseList';
+
+export const TAG_ITEM_TYPES: readonly TagItemType[] = ['annotation', 'note', 'verseList'];
+
+/** One level of the hierarchy (`Tag` in Rust). */
+export interface Tag {
+  id: string;
+  /** This level's name, without its parents'. */
+  name: string;
+  /** Absent for a top-level tag. */
+  parentId?: string;
+  /** A highlight color or `#rrggbb`. */
+  color?: string;
+  /** ISO timestamps, set by the backend. */
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+/** One tag on one item, as stored in `tag_links`. */
+export interface TagLink {
+  id: string;
+  tagId: string;
+  itemType: TagItemType;
+  itemId: string;
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+export interface TagInfo extends Tag {
+  /** Names from the top down, joined by `/`. */
+  path: string;
+  /** 0 for a top-level tag. */
+  depth: number;
+  /** Items tagged with it directly, not through a subtag. */
+  items: number;
+}
+
+export interface TaggedItem {
+  itemType: TagItemType;
+  itemId: string;
+  /** Paths of the tags it was found by. */
+  tags: string[];
+  /** The annotation, note or observation list as stored. */
+  item: unknown;
+}
+
+/** Every tag in path order, parents before their children. */
+export async function listTags(): Promise<TagInfo[]> {
+  return invoke<TagInfo[]>('list_tags');
+}
+
+/** Make a tag and any parents its path lacks. Rejected if it exists. */
+export async function createTag(path: string, color?: string): Promise<TagInfo> {
+  return invoke<TagInfo>('create_tag', { path, color: color ?? null });
+}
+
+/** Set or clear a tag's color. */
+export async function updateTag(tag: string, color?: string): Promise<TagInfo> {
+  return invoke<TagInfo>('update_tag', { tag, color: color ?? null });
+}
+
+/**
+ * Rename or move a tag, with its subtags and items, to `path`. Rejected if
+ * another tag is already there; merge them instead.
+ */
+export async function renameTag(tag: string, path: string): Promise<TagInfo> {
+  return invoke<TagInfo>('rename_tag', { tag, path });
+}
+
+/** Fold `from` into `into`, which takes over its items and subtags. */
+export async function mergeTags(from: string, into: string): Promise<TagInfo> {
+  return invoke<TagInfo>('merge_tags', { from, into });
+}
+
+/** Delete a tag and its subtags; the items stay. Resolves to how many tags went. */
+export async function deleteTag(tag: string): Promise<number> {
+  return invoke<number>('delete_tag', { tag });
+}
+
+/** Tag an item, making the tag first if a new path is given. */
+export async function tagItem(tag: string, itemType: TagItemType, itemId: string): Promise<TagInfo> {
+  return invoke<TagInfo>('tag_item', { tag, itemType, itemId });
+}
+
+export async function untagItem(tag: string, itemType: TagItemType, itemId: string): Promise<boolean> {
+  return invoke<boolean>('untag_item', { tag, itemType, itemId });
+}
+
+export async function getItemTags(itemType: TagItemType, itemId: string): Promise<TagInfo[]> {
+  return invoke<TagInfo[]>('get_item_tags', { itemType, itemId });
+}
+
+/**
+ * Items carrying a tag, and with `recursive` those carrying one of its
+ * subtags: annotations, then notes, then observation lists, newest first.
+ */
+export async function getItemsByTag(tag: string, recursive = false): Promise<TaggedItem[]> {
+  return invoke<TaggedItem[]>('get_items_by_tag', { tag, recursive });
+}
@@ -0,0 +1,174 @@
+/**
+ * Translations
+ *
+ * Freely licensed translations hosted on biblemarker.app, downloaded as
+ * ready-built content files (see `content.ts`) and mounted once installed,
+ * and the lifecycle of every installed translation however it got here.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { MountedContent } from './content';
+
+/** A translation the catalog offers (`AvailableTranslation` in Rust). */
+export interface AvailableTranslation {
+  id: string;
+  name: string;
+  abbreviation: string | null;
+  /** BCP 47 language tag, e.g. `en`. */
+  language: string;
+  license: string;
+  versification: string;
+  /** Catalog build of the content file. */
+  version: string;
+  url: string;
+  /** Download size in bytes. */
+  size: number;
+  sha256: string;
+  /** A content file for `id` is already installed. */
+  installed: boolean;
+}
+
+export interface DownloadProgress {
+  id: string;
+  received: number;
+  total: number;
+  done: boolean;
+}
+
+/** Fetch the catalog of translations available to download. */
+export async function listAvailableTranslations(): Promise<AvailableTranslation[]> {
+  return invoke<AvailableTranslation[]>('list_available_translations');
+}
+
+/**
+ * Download, verify, install and mount catalog translation `id`. An
+ * interrupted download resumes where it stopped when retried.
+ */
+export async function downloadTranslation(id: string): Promise<MountedContent> {
+  return invoke<MountedContent>('download_translation', { id });
+}
+
+/** Follow translation downloads. Returns an unlisten function. */
+export async function onDownloadProgress(listener: (progress: DownloadProgress) => void): Promise<() => void> {
+  return listen<DownloadProgress>('translations://download-progress', (event) => listener(event.payload));
+}
+
+/** An installed content file (`InstalledTranslation` in Rust). */
+export interface InstalledTranslation {
+  id: string;
+  name: string;
+  path: string;
+  /** Size in bytes. */
+  size: number;
+  mounted: boolean;
+  /** Importer that built it (`usfm`, `sword`, ...). */
+  sourceFormat: string | null;
+  /** Catalog build, for catalog downloads. */
+  catalogVersion: string | null;
+}
+
+/** User data made in a translation. */
+export interface Markings {
+  annotations: number;
+  notes: number;
+}
+
+export interface TranslationInfo extends InstalledTranslation {
+  license: string | null;
+  language: string | null;
+  versification: string | null;
+  version: string | null;
+  verses: number;
+  markings: Markings;
+}
+
+export interface TranslationUpdate {
+  id: string;
+  /** False when the installed copy was already the latest. */
+  updated: boolean;
+  version: string;
+}
+
+export async function listInstalledTranslations(): Promise<InstalledTranslation[]> {
+  return invoke<InstalledTranslation[]>('list_installed_translations');
+}
+
+/** License, language, versification, size and markings of an installed translation. */
+export async function getTranslationInfo(id: string): Promise<TranslationInfo> {
+  return invoke<TranslationInfo>('get_translation_info', { id });
+}
+
+/** Install the catalog's newer build of `id`, if there is one. */
+export async function updateTranslation(id: string): Promise<TranslationUpdate> {
+  return invoke<TranslationUpdate>('update_translation', { id });
+}
+
+/**
+ * Uninstall a translation. Its markings are kept (and show again if it is
+ * reinstalled), but while it has any the removal is refused unless `force`
+ * is set; check `getTranslationInfo(id).markings` and confirm first.
+ * Resolves to the markings left behind.
+ */
+export async function removeTranslation(id: string, force = false): Promise<Markings> {
+  return invoke<Markings>('remove_translation', { id, force });
+}
+
+/** License terms of an installed translation (`TranslationLicense` in Rust). */
+export interface TranslationLicense {
+  id: string;
+  license: string | null;
+  copyright: string | null;
+  /** Text every export must carry. */
+  attribution: string;
+  /** Most verses one export may quote; null for no limit. */
+  quoteLimit: number | null;
+  /** Not freely licensed, or its terms are unknown. */
+  restricted: boolean;
+}
+
+export async function getTranslationLicense(id: string): Promise<TranslationLicense> {
+  return invoke<TranslationLicense>('get_translation_license', { id });
+}
+
+/**
+ * Record the license terms of an installed translation. Empty or omitted
+ * values clear what was recorded.
+ */
+export async function setTranslationLicense(
+  id: string,
+  terms: { license?: string; copyright?: string; attribution?: string; quoteLimit?: number },
+): Promise<TranslationLicense> {
+  return invoke<TranslationLicense>('set_translation_license', { id, ...terms });
+}
+
+/** A book that no longer matches its recorded checksum. */
+export interface DamagedBook {
+  book: string;
+  problem: 'missing' | 'incomplete' | 'corrupted';
+  verses: number;
+  expected: number;
+}
+
+export interface IntegrityReport {
+  id: string;
+  /** SQLite found the file itself sound. */
+  intact: boolean;
+  /** False for files imported before checksums were recorded. */
+  recorded: boolean;
+  books: number;
+  damaged: DamagedBook[];
+  /** Books copied back from the catalog by a repair. */
+  repaired: string[];
+  /** A repair replaced the whole file. */
+  reinstalled: boolean;
+}
+
+/**
+ * Check installed translation `id` against the per-book checksums recorded
+ * when it was imported or downloaded. With `repair`, a catalog
+ * translation's damaged books are downloaded again.
+ */
+export async function verifyTranslation(id: string, repair = false): Promise<IntegrityReport> {
+  return invoke<IntegrityReport>('verify_translation', { id, repair });
+}
@@ -46,6 +46,11 @@ describe('validateAnnotation', () => {
     expect((result as { id: string }).id).toBe('a1')
   })
 
+  it('accepts a boolean translationLocked only', () => {
+    expect(validateAnnotation({ ...validHighlight, translationLocked: true })).toBeDefined()
+    expect(() => validateAnnotation({ ...validHighlight, translationLocked: 'yes' })).toThrow(ValidationError)
+  })
+
   it('throws on non-object', () => {
     expect(() => validateAnnotation(null)).toThrow(ValidationError)
     expect(() => validateAnnotation('x')).toThrow(ValidationError)
@@ -66,6 +71,14 @@ describe('validateAnnotation', () => {
     ).toThrow(ValidationError)
   })
 
+  it('checks word spans within a verse but lets a span across verses end before its start index', () => {
+    const words = { startWordIndex: 6, endWordIndex: 2 }
+    expect(() => validateAnnotation({ ...validHighlight, ...words })).toThrow(ValidationError)
+    expect(() => validateAnnotation({ ...validHighlight, startOffset: 4 })).toThrow(ValidationError)
+    const across = { ...validHighlight, ...words, endRef: { book: 'Gen', chapter: 1, verse: 3 } }
+    expect(validateAnnotation(across)).toMatchObject(words)
+  })
+
   const validSymbol = {
     id: 's1',
     moduleId: 'eng-ESV',
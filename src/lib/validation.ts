@@ -19,6 +19,16 @@ import type { TimeExpression } from '@/types';
 import type { EntityNote } from '@/types';
 import type { KeywordExclusion } from '@/types';
 import type { VerseRef } from '@/types';
+import type { LibrarySymbol } from './symbolLibrary';
+import type { Tag, TagLink } from './tags';
+import { TAG_ITEM_TYPES } from './tags';
+import type { Bookmark, BookmarkFolder } from './bookmarks';
+import type { VerseCollection } from './collections';
+import type { Workspace } from './workspaces';
+import type { HighlightStyle } from './highlightStyles';
+import type { MarkingTemplate } from './markingTemplates';
+import type { MarginNote } from './marginNotes';
+import type { ResourceLink } from './resourceLinks';
 import { HIGHLIGHT_COLORS, isKnownSymbolKey } from '@/types';
 
 /** Validation error with details */
@@ -96,6 +106,9 @@ export function validateAnnotation(annotation: unknown): Annotation {
   // Validate dates
   validateDate(a.createdAt, 'createdAt');
   validateDate(a.updatedAt, 'updatedAt');
+  if (a.translationLocked !== undefined && typeof a.translationLocked !== 'boolean') {
+    throw new ValidationError('Annotation translationLocked must be a boolean if provided', 'translationLocked', a.translationLocked);
+  }
 
   // Type-specific validation
   if (a.type === 'symbol') {
@@ -122,6 +135,13 @@ export function validateAnnotation(annotation: unknown): Annotation {
         throw new ValidationError('Underline annotation must have a valid underline style', 'underlineStyle', textAnn.underlineStyle);
       }
     }
+    if (textAnn.styleId !== undefined && typeof textAnn.styleId !== 'string') {
+      throw new ValidationError('Text annotation styleId must be a string if provided', 'styleId', textAnn.styleId);
+    }
+    if (textAnn.templateId !== undefined && typeof textAnn.templateId !== 'string') {
+      throw new ValidationError('Text annotation templateId must be a string if provided', 'templateId', textAnn.templateId);
+    }
+    validateWordSpan(textAnn);
   }
 
   return annotation as Annotation;
@@ -663,6 +683,319 @@ export function validateKeywordExclusion(exclusion: unknown): KeywordExclusion {
   return k;
 }
 
+/**
+ * Validate a symbol library entry
+ */
+export function validateLibrarySymbol(symbol: unknown): LibrarySymbol {
+  if (!symbol || typeof symbol !== 'object') {
+    throw new ValidationError('Library symbol must be an object', 'symbol', symbol);
+  }
+  const s = symbol as LibrarySymbol;
+  if (typeof s.id !== 'string' || s.id.trim() === '') {
+    throw new ValidationError('Library symbol must have a valid id', 'id', s.id);
+  }
+  if (typeof s.shape !== 'string' || s.shape.trim() === '') {
+    throw new ValidationError('Library symbol must have a valid shape', 'shape', s.shape);
+  }
+  if (typeof s.meaning !== 'string' || s.meaning.trim() === '') {
+    throw new ValidationError('Library symbol must have a meaning', 'meaning', s.meaning);
+  }
+  if (s.color !== undefined && typeof s.color !== 'string') {
+    throw new ValidationError('Library symbol color must be a string if provided', 'color', s.color);
+  }
+  return s;
+}
+
+/**
+ * Validate a tag
+ */
+export function validateTag(tag: unknown): Tag {
+  if (!tag || typeof tag !== 'object') {
+    throw new ValidationError('Tag must be an object', 'tag', tag);
+  }
+  const t = tag as Tag;
+  if (typeof t.id !== 'string' || t.id.trim() === '') {
+    throw new ValidationError('Tag must have a valid id', 'id', t.id);
+  }
+  if (typeof t.name !== 'string' || t.name.trim() === '' || t.name.includes('/')) {
+    throw new ValidationError('Tag must have a name without slashes', 'name', t.name);
+  }
+  if (t.parentId !== undefined && typeof t.parentId !== 'string') {
+    throw new ValidationError('Tag parentId must be a string if provided', 'parentId', t.parentId);
+  }
+  if (t.color !== undefined && typeof t.color !== 'string') {
+    throw new ValidationError('Tag color must be a string if provided', 'color', t.color);
+  }
+  return t;
+}
+
+/**
+ * Validate a link between a tag and an item
+ */
+export function validateTagLink(link: unknown): TagLink {
+  if (!link || typeof link !== 'object') {
+    throw new ValidationError('Tag link must be an object', 'link', link);
+  }
+  const l = link as TagLink;
+  for (const field of ['id', 'tagId', 'itemId'] as const) {
+    if (typeof l[field] !== 'string' || l[field].trim() === '') {
+      throw new ValidationError(`Tag link must have a valid ${field}`, field, l[field]);
+    }
+  }
+  if (!TAG_ITEM_TYPES.includes(l.itemType)) {
+    throw new ValidationError('Tag link must have a known item type', 'itemType', l.itemType);
+  }
+  return l;
+}
+
+/**
+ * Validate a bookmark folder
+ */
+export function validateBookmarkFolder(folder: unknown): BookmarkFolder {
+  if (!folder || typeof folder !== 'object') {
+    throw new ValidationError('Bookmark folder must be an object', 'folder', folder);
+  }
+  const f = folder as BookmarkFolder;
+  if (typeof f.id !== 'string' || f.id.trim() === '') {
+    throw new ValidationError('Bookmark folder must have a valid id', 'id', f.id);
+  }
+  if (typeof f.name !== 'string' || f.name.trim() === '') {
+    throw new ValidationError('Bookmark folder must have a name', 'name', f.name);
+  }
+  return f;
+}
+
+/**
+ * Validate a bookmark
+ */
+export function validateBookmark(bookmark: unknown): Bookmark {
+  if (!bookmark || typeof bookmark !== 'object') {
+    throw new ValidationError('Bookmark must be an object', 'bookmark', bookmark);
+  }
+  const b = bookmark as Bookmark;
+  if (typeof b.id !== 'string' || b.id.trim() === '') {
+    throw new ValidationError('Bookmark must have a valid id', 'id', b.id);
+  }
+  validateVerseRef(b.ref);
+  if (b.endRef !== undefined) {
+    validateVerseRef(b.endRef);
+  }
+  if (b.label !== undefined && typeof b.label !== 'string') {
+    throw new ValidationError('Bookmark label must be a string if provided', 'label', b.label);
+  }
+  if (b.folderId !== undefined && typeof b.folderId !== 'string') {
+    throw new ValidationError('Bookmark folderId must be a string if provided', 'folderId', b.folderId);
+  }
+  return b;
+}
+
+/**
+ * Validate a verse collection and its entries
+ */
+export function validateVerseCollection(collection: unknown): VerseCollection {
+  if (!collection || typeof collection !== 'object') {
+    throw new ValidationError('Verse collection must be an object', 'collection', collection);
+  }
+  const c = collection as VerseCollection;
+  if (typeof c.id !== 'string' || c.id.trim() === '') {
+    throw new ValidationError('Verse collection must have a valid id', 'id', c.id);
+  }
+  if (typeof c.name !== 'string' || c.name.trim() === '') {
+    throw new ValidationError('Verse collection must have a name', 'name', c.name);
+  }
+  if (!Array.isArray(c.entries)) {
+    throw new ValidationError('Verse collection entries must be an array', 'entries', c.entries);
+  }
+  for (const entry of c.entries) {
+    if (typeof entry?.id !== 'string' || entry.id.trim() === '') {
+      throw new ValidationError('Verse collection entry must have a valid id', 'entries.id', entry?.id);
+    }
+    validateVerseRef(entry.ref);
+    if (entry.endRef !== undefined) {
+      validateVerseRef(entry.endRef);
+    }
+  }
+  return c;
+}
+
+/**
+ * Validate a topical study workspace
+ */
+export function validateWorkspace(workspace: unknown): Workspace {
+  if (!workspace || typeof workspace !== 'object') {
+    throw new ValidationError('Workspace must be an object', 'workspace', workspace);
+  }
+  const w = workspace as Workspace;
+  if (typeof w.id !== 'string' || w.id.trim() === '') {
+    throw new ValidationError('Workspace must have a valid id', 'id', w.id);
+  }
+  if (typeof w.name !== 'string' || w.name.trim() === '') {
+    throw new ValidationError('Workspace must have a name', 'name', w.name);
+  }
+  for (const field of ['tagIds', 'collectionIds', 'noteIds', 'keyWordIds'] as const) {
+    const ids = w[field];
+    if (ids !== undefined && (!Array.isArray(ids) || ids.some(id => typeof id !== 'string'))) {
+      throw new ValidationError(`Workspace ${field} must be an array of ids`, field, ids);
+    }
+  }
+  return w;
+}
+
+/**
+ * Validate a text annotation's words: both ends of a word or character range
+ * or neither, never negative, and within one verse an end not before its start
+ * (across verses the end counts in the last verse, as in db/annotations.rs)
+ */
+function validateWordSpan(ann: TextAnnotation): void {
+  const oneVerse = ann.startRef.chapter === ann.endRef.chapter && ann.startRef.verse === ann.endRef.verse;
+  for (const [start, end, field] of [
+    [ann.startWordIndex, ann.endWordIndex, 'WordIndex'],
+    [ann.startOffset, ann.endOffset, 'Offset'],
+  ] as const) {
+    if ((start === undefined) !== (end === undefined)) {
+      throw new ValidationError(`Text annotation start${field} and end${field} go together`, `start${field}`, start);
+    }
+    if (start === undefined || end === undefined) continue;
+    if (!Number.isInteger(start) || !Number.isInteger(end) || start < 0 || end < 0) {
+      throw new ValidationError(`Text annotation start${field} and end${field} must be positions`, `start${field}`, start);
+    }
+    if (oneVerse && end < start) {
+      throw new ValidationError(`Text annotation end${field} is before start${field}`, `end${field}`, end);
+    }
+  }
+}
+
+/**
+ * Validate a highlight style: a name and at least one text part, each with a color
+ */
+export function validateHighlightStyle(style: unknown): HighlightStyle {
+  if (!style || typeof style !== 'object') {
+    throw new ValidationError('Highlight style must be an object', 'style', style);
+  }
+  const s = style as HighlightStyle;
+  if (typeof s.id !== 'string' || s.id.trim() === '') {
+    throw new ValidationError('Highlight style must have a valid id', 'id', s.id);
+  }
+  if (typeof s.name !== 'string' || s.name.trim() === '') {
+    throw new ValidationError('Highlight style must have a name', 'name', s.name);
+  }
+  for (const field of ['fill', 'textColor'] as const) {
+    if (s[field] !== undefined && typeof s[field] !== 'string') {
+      throw new ValidationError(`Highlight style ${field} must be a color`, field, s[field]);
+    }
+  }
+  for (const field of ['underline', 'border'] as const) {
+    const part = s[field];
+    if (part !== undefined && (typeof part !== 'object' || typeof part?.color !== 'string')) {
+      throw new ValidationError(`Highlight style ${field} must have a color`, field, part);
+    }
+  }
+  if (s.symbol !== undefined && (typeof s.symbol?.symbol !== 'string' || !isKnownSymbolKey(s.symbol.symbol))) {
+    throw new ValidationError('Highlight style symbol must be a known symbol', 'symbol', s.symbol);
+  }
+  if (!s.fill && !s.textColor && !s.underline && !s.border) {
+    throw new ValidationError('Highlight style must have a fill, text color, underline or border', 'style', s);
+  }
+  return s;
+}
+
+/**
+ * Validate a marking template: a word to mark and a look to mark it with
+ */
+export function validateMarkingTemplate(template: unknown): MarkingTemplate {
+  if (!template || typeof template !== 'object') {
+    throw new ValidationError('Marking template must be an object', 'template', template);
+  }
+  const t = template as MarkingTemplate;
+  if (typeof t.id !== 'string' || t.id.trim() === '') {
+    throw new ValidationError('Marking template must have a valid id', 'id', t.id);
+  }
+  if (typeof t.word !== 'string' || t.word.trim() === '') {
+    throw new ValidationError('Marking template must have a word', 'word', t.word);
+  }
+  if (t.variants !== undefined && (!Array.isArray(t.variants) || t.variants.some((v) => typeof v !== 'string'))) {
+    throw new ValidationError('Marking template variants must be strings', 'variants', t.variants);
+  }
+  if (!['highlight', 'textColor', 'underline'].includes(t.type)) {
+    throw new ValidationError('Marking template must have a valid type', 'type', t.type);
+  }
+  if (typeof t.color !== 'string' || !(t.color in HIGHLIGHT_COLORS)) {
+    throw new ValidationError('Marking template must have a valid color', 'color', t.color);
+  }
+  if (t.book !== undefined && typeof t.book !== 'string') {
+    throw new ValidationError('Marking template book must be a string if provided', 'book', t.book);
+  }
+  return t;
+}
+
+/**
+ * Validate a margin note: something written beside a chapter or a passage in it
+ */
+export function validateMarginNote(note: unknown): MarginNote {
+  if (!note || typeof note !== 'object') {
+    throw new ValidationError('Margin note must be an object', 'note', note);
+  }
+  const n = note as MarginNote;
+  if (typeof n.id !== 'string' || n.id.trim() === '') {
+    throw new ValidationError('Margin note must have a valid id', 'id', n.id);
+  }
+  if (typeof n.book !== 'string' || n.book.trim() === '') {
+    throw new ValidationError('Margin note must have a book', 'book', n.book);
+  }
+  if (typeof n.chapter !== 'number' || !Number.isInteger(n.chapter) || n.chapter < 1) {
+    throw new ValidationError('Margin note must have a valid chapter', 'chapter', n.chapter);
+  }
+  if (typeof n.content !== 'string' || n.content.trim() === '') {
+    throw new ValidationError('Margin note must have content', 'content', n.content);
+  }
+  if (n.pericope !== undefined) {
+    const p = n.pericope;
+    if (
+      !p || typeof p !== 'object' ||
+      typeof p.verse !== 'number' || typeof p.endChapter !== 'number' || typeof p.endVerse !== 'number' ||
+      p.verse < 1 || p.endVerse < 1 ||
+      p.endChapter < n.chapter || (p.endChapter === n.chapter && p.endVerse < p.verse)
+    ) {
+      throw new ValidationError('Margin note passage must run forwards from its chapter', 'pericope', p);
+    }
+  }
+  if (n.placement !== undefined) {
+    if (!n.placement || typeof n.placement !== 'object' || !['top', 'left', 'right', 'bottom'].includes(n.placement.side)) {
+      throw new ValidationError('Margin note must have a valid placement', 'placement', n.placement);
+    }
+  }
+  return n;
+}
+
+/**
+ * Validate a resource link: a note, mark or list's link to something outside the app
+ */
+export function validateResourceLink(link: unknown): ResourceLink {
+  if (!link || typeof link !== 'object') {
+    throw new ValidationError('Resource link must be an object', 'link', link);
+  }
+  const l = link as ResourceLink;
+  if (typeof l.id !== 'string' || l.id.trim() === '') {
+    throw new ValidationError('Resource link must have a valid id', 'id', l.id);
+  }
+  if (!['annotation', 'note', 'verseList'].includes(l.itemType)) {
+    throw new ValidationError('Resource link must have a valid item type', 'itemType', l.itemType);
+  }
+  if (typeof l.itemId !== 'string' || l.itemId.trim() === '') {
+    throw new ValidationError('Resource link must have an item id', 'itemId', l.itemId);
+  }
+  if (typeof l.url !== 'string' || l.url.trim() === '') {
+    throw new ValidationError('Resource link must have a url', 'url', l.url);
+  }
+  if (l.kind !== undefined && !['web', 'logos', 'file'].includes(l.kind)) {
+    throw new ValidationError('Resource link kind must be web, logos or file', 'kind', l.kind);
+  }
+  if (l.label !== undefined && typeof l.label !== 'string') {
+    throw new ValidationError('Resource link label must be a string if provided', 'label', l.label);
+  }
+  return l;
+}
+
 /**
  * Validate and sanitize data, converting date strings to Date objects
  */
@@ -0,0 +1,106 @@
+/**
+ * Topical Study Workspaces
+ *
+ * One topic ("Grace in Romans") and the tags, verse collections, notes and
+ * key words that belong to it. A workspace only points at its members by id
+ * (db/workspaces.rs, the synced `workspaces` table); they stay where they
+ * are and can be in several workspaces. A workspace can be exported to a
+ * `.bmstudy` file and shared, and importing one merges it like a sync bundle.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { VerseRef } from '@/types';
+import type { SyncMergeReport } from './sync';
+import { refreshPendingChanges } from './sync-engine';
+
+/** A workspace (`Workspace` in Rust). */
+export interface Workspace {
+  id: string;
+  name: string;
+  description?: string;
+  /** Its tags; their subtags come with them. */
+  tagIds: string[];
+  collectionIds: string[];
+  noteIds: string[];
+  /** Its key words, by marking preset id. */
+  keyWordIds: string[];
+  createdAt?: string;
+  updatedAt?: string;
+}
+
+export type WorkspaceMemberKind = 'tag' | 'collection' | 'note' | 'keyWord';
+
+export interface WorkspaceSummary {
+  workspace: Workspace;
+  /** `items` counts what carries the tag or one of its subtags. */
+  tags: { id: string; path: string; items: number }[];
+  collections: { id: string; name: string; entries: number }[];
+  notes: { id: string; title?: string; ref: VerseRef; updatedAt: string }[];
+  /** `marks` counts the annotations made with the key word. */
+  keyWords: { id: string; word: string | null; marks: number }[];
+  /** Ids of members deleted since they were added. */
+  missing: string[];
+}
+
+export interface StudyExport {
+  path: string;
+  workspaceId: string;
+  /** Rows written, by table. */
+  tables: [string, number][];
+}
+
+export interface StudyImport {
+  workspaceId: string;
+  name: string;
+  merge: SyncMergeReport;
+}
+
+export async function getWorkspaces(): Promise<Workspace[]> {
+  return invoke<Workspace[]>('get_workspaces');
+}
+
+export async function createWorkspace(name: string, description?: string): Promise<Workspace> {
+  return invoke<Workspace>('create_workspace', { name, description: description ?? null });
+}
+
+export async function updateWorkspace(id: string, name: string, description?: string): Promise<Workspace> {
+  return invoke<Workspace>('update_workspace', { id, name, description: description ?? null });
+}
+
+/** Delete a workspace; its tags, collections, notes and key words stay. */
+export async function deleteWorkspace(id: string): Promise<boolean> {
+  return invoke<boolean>('delete_workspace', { id });
+}
+
+/** Rejected if there's no such item. A tag may be given by path. */
+export async function addToWorkspace(id: string, kind: WorkspaceMemberKind, itemId: string): Promise<Workspace> {
+  return invoke<Workspace>('add_to_workspace', { id, kind, itemId });
+}
+
+export async function removeFromWorkspace(id: string, kind: WorkspaceMemberKind, itemId: string): Promise<Workspace> {
+  return invoke<Workspace>('remove_from_workspace', { id, kind, itemId });
+}
+
+export async function getWorkspaceSummary(id: string): Promise<WorkspaceSummary> {
+  return invoke<WorkspaceSummary>('get_workspace_summary', { id });
+}
+
+/**
+ * Write a workspace, its members, the items its tags are on and the marks
+ * made with its key words to a `.bmstudy` file at `path`.
+ */
+export async function exportWorkspace(id: string, path: string): Promise<StudyExport> {
+  return invoke<StudyExport>('export_workspace', { id, path });
+}
+
+/** Merge a shared `.bmstudy` file into this device (newest edit wins per row). */
+export async function importWorkspace(path: string): Promise<StudyImport> {
+  const result = await invoke<StudyImport>('import_workspace', { path });
+  await refreshPendingChanges();
+  if (result.merge.applied > 0 && typeof window !== 'undefined') {
+    window.dispatchEvent(new CustomEvent('syncDataChanged', {
+      detail: { applied: result.merge.applied, tables: result.merge.tables.map(t => t.table) },
+    }));
+  }
+  return result;
+}
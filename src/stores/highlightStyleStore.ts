@@ -0,0 +1,50 @@
+/**
+ * Highlight Style Store
+ *
+ * The style palette the reader draws styled annotations with, by id.
+ */
+
+import { create } from 'zustand';
+import {
+  getHighlightStyles,
+  createHighlightStyle,
+  updateHighlightStyle,
+  deleteHighlightStyle,
+  type HighlightStyle,
+} from '@/lib/highlightStyles';
+
+interface HighlightStyleState {
+  styles: Map<string, HighlightStyle>;
+  loadStyles: () => Promise<void>;
+  addStyle: (style: Omit<HighlightStyle, 'id'>) => Promise<HighlightStyle>;
+  saveStyle: (style: HighlightStyle) => Promise<HighlightStyle>;
+  removeStyle: (id: string) => Promise<void>;
+}
+
+export const useHighlightStyleStore = create<HighlightStyleState>()((set, get) => ({
+  styles: new Map(),
+
+  loadStyles: async () => {
+    const all = await getHighlightStyles();
+    set({ styles: new Map(all.map(({ uses: _uses, ...style }) => [style.id, style])) });
+  },
+
+  addStyle: async (style) => {
+    const saved = await createHighlightStyle(style);
+    set({ styles: new Map(get().styles).set(saved.id, saved) });
+    return saved;
+  },
+
+  saveStyle: async (style) => {
+    const saved = await updateHighlightStyle(style);
+    set({ styles: new Map(get().styles).set(saved.id, saved) });
+    return saved;
+  },
+
+  removeStyle: async (id) => {
+    await deleteHighlightStyle(id);
+    const styles = new Map(get().styles);
+    styles.delete(id);
+    set({ styles });
+  },
+}));
@@ -11,6 +11,7 @@ import { getAllObservationLists as dbGetAllLists, saveObservationList as dbSaveL
 import type { VerseRef } from '@/types';
 import { findKeywordMatches } from '@/lib/keywordMatching';
 import { validateObservationList, sanitizeData, ValidationError } from '@/lib/validation';
+import { generateObservationList, refreshObservationList, type ObservationListRequest } from '@/lib/generatedLists';
 
 interface ListState {
   // Lists (cached)
@@ -33,6 +34,10 @@ interface ListState {
   getListsByStudy: (studyId: string) => ObservationList[];
   getOrCreateListForKeyword: (keyWordId: string, studyId?: string, book?: string) => Promise<ObservationList>;
   autoPopulateFromKeyword: (listId: string, keyWordId: string) => Promise<number>;
+  /** Make a list of the marks of a key word, tag or style (natively, linked to its marks). */
+  generateList: (request: ObservationListRequest) => Promise<ObservationList>;
+  /** Bring a generated list up to date with its marks; returns items added and removed. */
+  refreshGeneratedList: (listId: string) => Promise<{ added: number; removed: number }>;
   getMostRecentlyUsedList: () => ObservationList | null;
 }
 
@@ -379,6 +384,19 @@ export const useListStore = create<ListState>()(
         return uniqueItems.length;
       },
 
+      generateList: async (request) => {
+        const list = await generateObservationList(request);
+        await get().loadLists();
+        set({ lastUsedListId: list.id });
+        return get().getList(list.id) ?? list;
+      },
+
+      refreshGeneratedList: async (listId) => {
+        const { added, removed } = await refreshObservationList(listId);
+        if (added + removed > 0) await get().loadLists();
+        return { added, removed };
+      },
+
     }),
     {
       name: 'list-store',
@@ -367,12 +367,41 @@ export function isMarkingStyle(value: unknown): value is MarkingStyle {
   return value === 'none' || value === 'underline' || value === 'highlight';
 }
 
+/**
+ * What a word-level mark covered when it was last known to be right, so it
+ * can find its words again after the translation's text changes (see
+ * src-tauri/src/db/anchors.rs). Set by the backend.
+ */
+export interface TextAnchor {
+  /** The marked words, as the verse wrote them. */
+  exact: string;
+  /** A few words before and after them. */
+  before: string;
+  after: string;
+  /** Fingerprint of the verse text. */
+  verse: string;
+  /** Set when the words were found missing from a changed text. */
+  orphanedAt?: string;
+}
+
 /** Base annotation interface */
 interface BaseAnnotation {
   id: string;
   moduleId: string;          // Which Bible translation
   createdAt: Date;
   updatedAt: Date;
+  /**
+   * Shown in its own translation only, for a mark on wording the others don't
+   * share. Unset, key-word marks are (each translation gets its own copy) and
+   * other marks show in every translation (db/propagation.rs).
+   */
+  translationLocked?: boolean;
+  /**
+   * Set on another translation's mark as the chapter query carries it into the
+   * one being read, renumbered and placed for it. The backend refuses to save
+   * one; edit the stored mark (same id) instead.
+   */
+  carried?: boolean;
 }
 
 /** Text selection annotation (highlight, text color, underline) */
@@ -391,10 +420,19 @@ export interface TextAnnotation extends BaseAnnotation {
   selectedText?: string;      // The exact text that was selected
   startOffset?: number;       // Character offset within start verse
   endOffset?: number;        // Character offset within end verse
+  anchor?: TextAnchor;
+
   
   // Styling
   color: HighlightColor;
   underlineStyle?: UnderlineStyle;
+  /**
+   * The `HighlightStyle` it is drawn with (src/lib/highlightStyles.ts). `type`
+   * and `color` stay as the look for a style since deleted.
+   */
+  styleId?: string;
+  /** The `MarkingTemplate` that made it (src/lib/markingTemplates.ts), which can remove it again. */
+  templateId?: string;
   
   /** Optional link to a MarkingPreset; enables find-by-preset and "marked" in Key Word Finder */
   presetId?: string;
@@ -417,6 +455,7 @@ export interface SymbolAnnotation extends BaseAnnotation {
   startOffset?: number;       // Character offset within verse
   endOffset?: number;         // Character offset within verse
   endRef?: VerseRef;         // For multi-verse selections
+  anchor?: TextAnchor;
   
   // Symbol
   symbol: SymbolKey;
@@ -23,6 +23,12 @@ export interface ObservationScope {
   chapters?: number[];    // Limit to chapters (e.g., [1, 2, 3])
 }
 
+/** The markings a generated list is made from (db/observation_lists.rs) */
+export type ObservationListSource =
+  | { kind: 'keyWord'; presetId: string }
+  | { kind: 'tag'; tag: string }      // Tag id or path; includes its subtags
+  | { kind: 'style'; styleId: string };
+
 /** Observation list - a collection of observations about a specific key word */
 export interface ObservationList {
   id: string;
@@ -31,6 +37,8 @@ export interface ObservationList {
   items: ObservationItem[];
   keyWordId: string;      // Required: link to a key word (MarkingPreset) - list is about this keyword
   studyId?: string;       // Optional: link to a study (for organization)
+  /** Set on a list generated from markings; refreshing it re-reads them. */
+  generatedFrom?: { source: ObservationListSource; moduleId?: string };
   createdAt: Date;
   updatedAt: Date;
 }
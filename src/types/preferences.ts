@@ -32,6 +32,16 @@ export interface AutoBackupConfig {
   maxBackups: number;
 }
 
+/** How copied verses and exported notes cite references */
+export interface CitationPreferences {
+  /** Default 'full'. */
+  style?: 'full' | 'abbreviated' | 'compact';
+  /** Language of book names and default separators, e.g. 'de'. Default 'en'. */
+  locale?: string;
+  /** Overrides the locale's separators. */
+  separators?: { chapterVerse?: string; range?: string };
+}
+
 /** User preferences */
 export interface UserPreferences {
   id: string;                    // 'main' for singleton
@@ -60,6 +70,11 @@ export interface UserPreferences {
   translationLanguageFilter?: string[];
   onboarding?: OnboardingState;
   autoBackup?: AutoBackupConfig;
+  citation?: CitationPreferences;
+  /** Names the user added for books, e.g. "Evangelio de Juan" → "John" */
+  bookAliases?: Record<string, string>;
+  /** System-wide shortcut that opens the reference on the clipboard (desktop; unset = off) */
+  clipboardShortcut?: string;
   /** When false, app will not check GitHub for new releases (default true) */
   checkForUpdates?: boolean;
   /** Last app version the user has seen the What's New popup for */